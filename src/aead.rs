@@ -0,0 +1,153 @@
+//! XChaCha20-Poly1305: the authenticated encryption that keeps the wallet file
+//! secret at rest. ChaCha20 turns a 256-bit key and a nonce into a keystream that
+//! is XORed with the plaintext; Poly1305 computes a one-time MAC over the
+//! ciphertext with a key taken from the first keystream block, so a wrong key or a
+//! flipped bit fails the tag instead of decrypting to garbage. The "X" variant
+//! first hashes the key with the first 16 bytes of a 24-byte nonce (HChaCha20),
+//! which makes random nonces safe to use. Follows RFC 8439 and the XChaCha draft;
+//! written for readability, not speed or constant time.
+
+use num_bigint::BigUint;
+
+use crate::error::CtError;
+
+pub const KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 24;
+pub const TAG_LEN: usize = 16;
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+/// The constants, the key and 16 bytes of counter and nonce, as little-endian words.
+fn initial_state(key: &[u8; KEY_LEN], input: &[u8; 16]) -> [u32; 16] {
+    let mut state = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    for (word, bytes) in state[4..].iter_mut().zip(key.chunks(4).chain(input.chunks(4))) {
+        *word = u32::from_le_bytes(bytes.try_into().expect("4-byte chunk"));
+    }
+    state
+}
+
+/// 20 rounds: 10 column rounds alternating with 10 diagonal rounds.
+fn rounds(state: &mut [u32; 16]) {
+    for _ in 0..10 {
+        quarter_round(state, 0, 4, 8, 12);
+        quarter_round(state, 1, 5, 9, 13);
+        quarter_round(state, 2, 6, 10, 14);
+        quarter_round(state, 3, 7, 11, 15);
+        quarter_round(state, 0, 5, 10, 15);
+        quarter_round(state, 1, 6, 11, 12);
+        quarter_round(state, 2, 7, 8, 13);
+        quarter_round(state, 3, 4, 9, 14);
+    }
+}
+
+/// One 64-byte keystream block for a 12-byte nonce.
+fn chacha20_block(key: &[u8; KEY_LEN], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    let mut input = [0; 16];
+    input[..4].copy_from_slice(&counter.to_le_bytes());
+    input[4..].copy_from_slice(nonce);
+    let initial = initial_state(key, &input);
+    let mut state = initial;
+    rounds(&mut state);
+    let mut block = [0; 64];
+    for (i, (word, start)) in state.iter().zip(initial).enumerate() {
+        block[4 * i..4 * i + 4].copy_from_slice(&word.wrapping_add(start).to_le_bytes());
+    }
+    block
+}
+
+/// XOR `data` with the keystream starting at block `counter`.
+fn chacha20_xor(key: &[u8; KEY_LEN], counter: u32, nonce: &[u8; 12], data: &[u8]) -> Vec<u8> {
+    data.chunks(64)
+        .zip(counter..)
+        .flat_map(|(chunk, counter)| {
+            let block = chacha20_block(key, counter, nonce);
+            chunk.iter().zip(block).map(|(byte, key)| byte ^ key).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// HChaCha20: the rounds without the final addition; words 0-3 and 12-15 are the subkey.
+pub fn hchacha20(key: &[u8; KEY_LEN], nonce: &[u8; 16]) -> [u8; KEY_LEN] {
+    let mut state = initial_state(key, nonce);
+    rounds(&mut state);
+    let mut subkey = [0; KEY_LEN];
+    for (i, word) in state[..4].iter().chain(&state[12..]).enumerate() {
+        subkey[4 * i..4 * i + 4].copy_from_slice(&word.to_le_bytes());
+    }
+    subkey
+}
+
+/// Poly1305: evaluate the message as a polynomial at r, modulo 2^130 - 5, plus s.
+fn poly1305(one_time_key: &[u8], message: &[u8]) -> [u8; TAG_LEN] {
+    let mut r = one_time_key[..16].to_vec();
+    for i in [3, 7, 11, 15] {
+        r[i] &= 0x0f;
+    }
+    for i in [4, 8, 12] {
+        r[i] &= 0xfc;
+    }
+    let r = BigUint::from_bytes_le(&r);
+    let s = BigUint::from_bytes_le(&one_time_key[16..32]);
+    let p = (BigUint::from(1u8) << 130) - 5u8;
+    let mut accumulator = BigUint::from(0u8);
+    for chunk in message.chunks(16) {
+        let block = BigUint::from_bytes_le(&[chunk, &[1]].concat());
+        accumulator = (accumulator + block) * &r % &p;
+    }
+    let tag: BigUint = (accumulator + s) % (BigUint::from(1u8) << 128);
+    let mut bytes = [0; TAG_LEN];
+    let digits = tag.to_bytes_le();
+    bytes[..digits.len()].copy_from_slice(&digits);
+    bytes
+}
+
+/// The RFC 8439 MAC input: aad, ciphertext, each zero-padded to 16 bytes, then both lengths.
+fn mac_data(aad: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+    let pad = |len: usize| vec![0; (16 - len % 16) % 16];
+    [aad, &pad(aad.len()), ciphertext, &pad(ciphertext.len())]
+        .concat()
+        .into_iter()
+        .chain((aad.len() as u64).to_le_bytes())
+        .chain((ciphertext.len() as u64).to_le_bytes())
+        .collect()
+}
+
+/// The ChaCha20 key and 12-byte nonce behind a 24-byte XChaCha nonce.
+fn subkey(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN]) -> ([u8; KEY_LEN], [u8; 12]) {
+    let subkey = hchacha20(key, nonce[..16].try_into().expect("16-byte prefix"));
+    let mut chacha_nonce = [0; 12];
+    chacha_nonce[4..].copy_from_slice(&nonce[16..]);
+    (subkey, chacha_nonce)
+}
+
+/// Encrypt and authenticate `plaintext` (and authenticate `aad`); the tag is appended.
+pub fn seal(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let (key, nonce) = subkey(key, nonce);
+    let mut ciphertext = chacha20_xor(&key, 1, &nonce, plaintext);
+    let tag = poly1305(&chacha20_block(&key, 0, &nonce), &mac_data(aad, &ciphertext));
+    ciphertext.extend_from_slice(&tag);
+    ciphertext
+}
+
+/// Check the tag and decrypt; fails if the key is wrong or anything was changed.
+pub fn open(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, CtError> {
+    if sealed.len() < TAG_LEN {
+        return Err(CtError::MalformedInput(format!("ciphertext is {} bytes, shorter than its tag", sealed.len())));
+    }
+    let (ciphertext, tag) = sealed.split_at(sealed.len() - TAG_LEN);
+    let (key, nonce) = subkey(key, nonce);
+    let expected = poly1305(&chacha20_block(&key, 0, &nonce), &mac_data(aad, ciphertext));
+    if expected.iter().zip(tag).fold(0, |diff, (a, b)| diff | (a ^ b)) != 0 {
+        return Err(CtError::DecryptionFailed);
+    }
+    Ok(chacha20_xor(&key, 1, &nonce, ciphertext))
+}
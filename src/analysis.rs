@@ -0,0 +1,276 @@
+//! What an observer learns from commitments alone: reused blindings, small groups.
+//!
+//! Fresh random blindings make every commitment look unrelated. If two outputs
+//! share r, the H parts cancel: C1 - C2 = (v1 - v2)*G, and a small multiple of G is
+//! easy to recognise, so the difference of the amounts leaks (0 means "equal
+//! amounts"). If they share both v and r, the commitments are identical and anyone
+//! can link them without any arithmetic. `find_links` is the check an analyst runs
+//! over every pair of outputs.
+//!
+//! With a small group, or blindings from a small range, an observer can also just
+//! try every blinding (`brute_force_openings`). When r ranges over the whole group,
+//! every value has exactly one r that opens C, so the search turns up one opening
+//! per value and learns nothing: that is perfect hiding. It is only when r comes from
+//! far fewer than p values that a single opening stands out.
+//!
+//! `simulate_history` plays one payment history between a few customers and a shop.
+//! `observe` shows it the way a plaintext ledger would, or a confidential one. The
+//! analyses (`repeated_amounts`, `price_tags`, `subset_sum`) work in commitment
+//! space: a plaintext amount v is just the commitment v*G with r = 0. So the same
+//! code runs on both ledgers, and the only difference is what the ledger hides.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+use crate::commitment::{pedersen_commit, sum_commitments, Blinding, Commitment, Opening, Scalar};
+use crate::params;
+use crate::rng::ToyRng;
+
+/// A link between two of the commitments handed to `find_links`, by index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Link {
+    /// Same value and same blinding: the commitments are equal.
+    Identical { first: usize, second: usize },
+    /// Same blinding: C_first - C_second = difference*G, so v_first - v_second = difference.
+    SharedBlinding { first: usize, second: usize, difference: i64 },
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Link::Identical { first, second } => write!(f, "#{} and #{} are the same commitment", first, second),
+            Link::SharedBlinding { first, second, difference: 0 } => {
+                write!(f, "#{} and #{} share a blinding and hold equal amounts", first, second)
+            }
+            Link::SharedBlinding { first, second, difference } => {
+                write!(f, "#{} and #{} share a blinding; v{} - v{} = {}", first, second, first, second, difference)
+            }
+        }
+    }
+}
+
+/// d*G -> d for every |d| <= max_difference (d = 0 included).
+fn difference_table(max_difference: i64) -> HashMap<Commitment, i64> {
+    (-max_difference..=max_difference).map(|d| (pedersen_commit(Scalar::new(d), Blinding::new(0)), d)).collect()
+}
+
+/// Every pair of `commitments` that is identical, or whose difference is d*G for
+/// |d| <= `max_difference` (which, with random blindings, practically only happens
+/// when the blinding was reused).
+pub fn find_links(commitments: &[Commitment], max_difference: i64) -> Vec<Link> {
+    let table = difference_table(max_difference);
+    let mut links = Vec::new();
+    for (first, &a) in commitments.iter().enumerate() {
+        for (second, &b) in commitments.iter().enumerate().skip(first + 1) {
+            if a == b {
+                links.push(Link::Identical { first, second });
+            } else if let Some(&difference) = table.get(&(a - b)) {
+                links.push(Link::SharedBlinding { first, second, difference });
+            }
+        }
+    }
+    links
+}
+
+/// Every opening (v, r) of `commitment` with v in the range proof's range and r in
+/// `blindings`, found by trying each r: C - r*H is looked up among the v*G.
+pub fn brute_force_openings(commitment: Commitment, blindings: Range<i64>) -> Vec<Opening> {
+    let values: HashMap<Commitment, i64> = (0..1i64 << params::active().range_bits)
+        .map(|v| (pedersen_commit(Scalar::new(v), Blinding::new(0)), v))
+        .collect();
+    blindings
+        .filter_map(|r| {
+            let rest = commitment - pedersen_commit(Scalar::new(0), Blinding::new(r));
+            values.get(&rest).map(|&v| Opening::new(Scalar::new(v), Blinding::new(r)))
+        })
+        .collect()
+}
+
+/// The first blinding in `blindings` that opens `commitment` to `value`, and how
+/// many were tried. Grinding needs no algebra, only about p/2 tries on average, so
+/// the group order alone decides whether it is feasible.
+pub fn grind_blinding(commitment: Commitment, value: Scalar, blindings: Range<i64>) -> (Option<Blinding>, u64) {
+    let mut tries = 0;
+    let found = blindings.map(Blinding::new).find(|&r| {
+        tries += 1;
+        pedersen_commit(value, r) == commitment
+    });
+    (found, tries)
+}
+
+/// The shop's public price list; the analyst knows it too.
+pub const PRICES: [i64; 4] = [25, 40, 60, 99];
+
+/// One payment of the simulated history, with the openings only its parties know.
+#[derive(Clone, Debug)]
+pub struct Payment {
+    pub inputs: Vec<Opening>,
+    pub outputs: Vec<Opening>,
+    /// Which outputs pay the shop (the rest are change or go to another customer).
+    pub to_shop: Vec<usize>,
+}
+
+/// An amount as an observer of the ledger sees it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Observed {
+    Plain(i64),
+    Hidden(Commitment),
+}
+
+impl Observed {
+    /// The commitment the analyst works with: v*G for a plain v.
+    fn commitment(self) -> Commitment {
+        match self {
+            Observed::Plain(value) => pedersen_commit(Scalar::new(value), Blinding::default()),
+            Observed::Hidden(commitment) => commitment,
+        }
+    }
+
+    /// Can the observer tell that this is `value`? For a hidden amount only if r = 0.
+    pub fn is(self, value: i64) -> bool {
+        self.commitment() == pedersen_commit(Scalar::new(value), Blinding::default())
+    }
+}
+
+impl fmt::Display for Observed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Observed::Plain(value) => write!(f, "{}", value),
+            Observed::Hidden(commitment) => write!(f, "C = {}", commitment),
+        }
+    }
+}
+
+/// `count` payments between three customers and the shop. A customer spends all
+/// their outputs each time: one or two items from the shop (one output per item), or
+/// a transfer to another customer, plus change. Output order is shuffled.
+pub fn simulate_history(count: usize, rng: &mut ToyRng) -> Vec<Payment> {
+    const CUSTOMERS: usize = 3;
+    let max = (1i64 << params::active().range_bits) - 1;
+    let fresh = |value: i64, rng: &mut ToyRng| Opening::new(Scalar::new(value), Blinding::random(rng));
+    let mut wallets: Vec<Vec<Opening>> = (0..CUSTOMERS).map(|_| vec![fresh(rng.between(max / 2, max), rng)]).collect();
+    let mut history = Vec::new();
+    while history.len() < count {
+        let payer = (rng.next_u64() % CUSTOMERS as u64) as usize;
+        let funds: i64 = wallets[payer].iter().map(|o| o.value.as_i64()).sum();
+        let items = 1 + (rng.next_u64() % 2) as usize;
+        let prices: Vec<i64> = (0..items).map(|_| PRICES[(rng.next_u64() % PRICES.len() as u64) as usize]).collect();
+        // Each output with its new owner: None is the shop.
+        let shopping = !rng.next_u64().is_multiple_of(3) && prices.iter().sum::<i64>() <= funds;
+        let mut outputs: Vec<(Opening, Option<usize>)> = if shopping {
+            prices.iter().map(|&price| (fresh(price, rng), None)).collect()
+        } else if funds > 1 {
+            let payee = (payer + 1 + (rng.next_u64() % (CUSTOMERS as u64 - 1)) as usize) % CUSTOMERS;
+            vec![(fresh(rng.between(1, funds / 2 + 1), rng), Some(payee))]
+        } else {
+            wallets[payer].push(fresh(max, rng));
+            continue;
+        };
+        let change = funds - outputs.iter().map(|(o, _)| o.value.as_i64()).sum::<i64>();
+        if change > 0 {
+            outputs.push((fresh(change.min(max), rng), Some(payer)));
+        }
+        for i in (1..outputs.len()).rev() {
+            outputs.swap(i, (rng.next_u64() % (i as u64 + 1)) as usize);
+        }
+        let inputs = std::mem::take(&mut wallets[payer]);
+        for &(output, owner) in &outputs {
+            if let Some(owner) = owner {
+                wallets[owner].push(output);
+            }
+        }
+        let to_shop = outputs.iter().enumerate().filter(|(_, (_, owner))| owner.is_none()).map(|(i, _)| i).collect();
+        history.push(Payment { inputs, outputs: outputs.into_iter().map(|(o, _)| o).collect(), to_shop });
+    }
+    history
+}
+
+/// Each payment's inputs and outputs as the ledger shows them.
+pub fn observe(history: &[Payment], confidential: bool) -> Vec<(Vec<Observed>, Vec<Observed>)> {
+    let show = |openings: &[Opening]| -> Vec<Observed> {
+        openings
+            .iter()
+            .map(|o| if confidential { Observed::Hidden(o.commit()) } else { Observed::Plain(o.value.as_i64()) })
+            .collect()
+    };
+    history.iter().map(|payment| (show(&payment.inputs), show(&payment.outputs))).collect()
+}
+
+/// Output amounts that appear more than once, most frequent first.
+pub fn repeated_amounts(ledger: &[(Vec<Observed>, Vec<Observed>)]) -> Vec<(Observed, usize)> {
+    let mut counts: HashMap<Commitment, (Observed, usize)> = HashMap::new();
+    for output in ledger.iter().flat_map(|(_, outputs)| outputs) {
+        counts.entry(output.commitment()).or_insert((*output, 0)).1 += 1;
+    }
+    let mut repeated: Vec<(Observed, usize)> = counts.into_values().filter(|&(_, n)| n > 1).collect();
+    repeated.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+    repeated
+}
+
+/// (payment, output) pairs whose amount is one of `prices`: the analyst's guess at
+/// which outputs pay the shop.
+pub fn price_tags(ledger: &[(Vec<Observed>, Vec<Observed>)], prices: &[i64]) -> Vec<(usize, usize)> {
+    let mut tags = Vec::new();
+    for (payment, (_, outputs)) in ledger.iter().enumerate() {
+        for (index, output) in outputs.iter().enumerate() {
+            if prices.iter().any(|&price| output.is(price)) {
+                tags.push((payment, index));
+            }
+        }
+    }
+    tags
+}
+
+/// Indices of a subset of `amounts` (at least two) that adds up to `target`, if any.
+/// Hidden amounts only add up to target*G if their blindings happen to cancel. Tries
+/// all 2^n subsets, so it is meant for the outputs of one transaction.
+pub fn subset_sum(amounts: &[Observed], target: i64) -> Option<Vec<usize>> {
+    let goal = pedersen_commit(Scalar::new(target), Blinding::default());
+    (0u32..1 << amounts.len()).filter(|mask| mask.count_ones() >= 2).find_map(|mask| {
+        let chosen: Vec<usize> = (0..amounts.len()).filter(|i| mask >> i & 1 == 1).collect();
+        let commitments: Vec<Commitment> = chosen.iter().map(|&i| amounts[i].commitment()).collect();
+        (sum_commitments(&commitments) == goal).then_some(chosen)
+    })
+}
+
+/// What the analyses turn up on one ledger, scored against the real history.
+#[derive(Clone, Debug)]
+pub struct Findings {
+    /// Outputs whose amount the ledger shows, of `outputs`.
+    pub readable: usize,
+    pub outputs: usize,
+    pub repeated: Vec<(Observed, usize)>,
+    /// Outputs tagged by `price_tags` that really pay the shop, and those that do not.
+    pub tagged_right: usize,
+    pub tagged_wrong: usize,
+    /// How many outputs really pay the shop.
+    pub purchases: usize,
+    /// The first two-item purchase, whose total the analyst looks for as an invoice.
+    pub invoice: Option<usize>,
+    /// Payments with outputs that add up to the invoice's total.
+    pub invoice_matches: Vec<usize>,
+}
+
+/// Run every analysis on `history` as a plaintext or a confidential ledger shows it.
+pub fn analyze(history: &[Payment], confidential: bool) -> Findings {
+    let ledger = observe(history, confidential);
+    let outputs = ledger.iter().flat_map(|(_, outputs)| outputs);
+    let tags = price_tags(&ledger, &PRICES);
+    let tagged_right = tags.iter().filter(|&&(payment, index)| history[payment].to_shop.contains(&index)).count();
+    let invoice = history.iter().position(|payment| payment.to_shop.len() == 2);
+    let invoice_matches = invoice
+        .map(|index| history[index].to_shop.iter().map(|&i| history[index].outputs[i].value.as_i64()).sum())
+        .map(|total| (0..ledger.len()).filter(|&p| subset_sum(&ledger[p].1, total).is_some()).collect())
+        .unwrap_or_default();
+    Findings {
+        readable: outputs.clone().filter(|output| matches!(output, Observed::Plain(_))).count(),
+        outputs: outputs.count(),
+        repeated: repeated_amounts(&ledger),
+        tagged_right,
+        tagged_wrong: tags.len() - tagged_right,
+        purchases: history.iter().map(|payment| payment.to_shop.len()).sum(),
+        invoice,
+        invoice_matches,
+    }
+}
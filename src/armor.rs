@@ -0,0 +1,124 @@
+//! ASCII armor for pasting slates and transactions through chat or email:
+//!
+//! ```text
+//! -----BEGIN CT SLATE-----
+//! Version: 1
+//! <wire bytes in base64, 64 characters per line>
+//! =<first 4 bytes of SHA-256(bytes), hex>
+//! -----END CT SLATE-----
+//! ```
+//!
+//! Whitespace and blank lines around and inside the body are ignored, so text that
+//! a mail client re-wrapped still reads back; the checksum catches anything else.
+//! The version is the format's (`wire::FORMAT_VERSION`); armor written before it had
+//! that line is version 1.
+
+use sha2::{Digest, Sha256};
+
+use crate::error::CtError;
+use crate::wire::{check_version, to_hex, FORMAT_VERSION};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const LINE_WIDTH: usize = 64;
+
+/// Label used for slates.
+pub const SLATE: &str = "SLATE";
+/// Label used for transactions.
+pub const TRANSACTION: &str = "TRANSACTION";
+
+fn checksum(bytes: &[u8]) -> String {
+    to_hex(&Sha256::digest(bytes)[..4])
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>, CtError> {
+    let body = text.trim_end_matches('=');
+    if !text.len().is_multiple_of(4) || text.len() - body.len() > 2 {
+        return Err(CtError::MalformedInput("the body is not complete base64 (was it cut off?)".to_string()));
+    }
+    let mut out = Vec::new();
+    let (mut n, mut bits) = (0u32, 0);
+    for c in body.chars() {
+        let digit = ALPHABET
+            .iter()
+            .position(|&a| a as char == c)
+            .ok_or_else(|| CtError::MalformedInput(format!("'{}' is not a base64 character", c)))?;
+        n = n << 6 | digit as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((n >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+/// Wrap `bytes` in BEGIN/END lines naming `label`.
+pub fn armor(label: &str, bytes: &[u8]) -> String {
+    let body = base64_encode(bytes);
+    let mut out = format!("-----BEGIN CT {}-----\nVersion: {}\n", label, FORMAT_VERSION);
+    for line in body.as_bytes().chunks(LINE_WIDTH) {
+        out.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+        out.push('\n');
+    }
+    out.push_str(&format!("={}\n-----END CT {}-----\n", checksum(bytes), label));
+    out
+}
+
+/// Does `text` look armored (so callers can fall back to other formats)?
+pub fn is_armored(text: &str) -> bool {
+    text.trim_start().starts_with("-----BEGIN CT ")
+}
+
+/// Unwrap armored text, checking that it is labelled `label` and intact.
+pub fn dearmor(label: &str, text: &str) -> Result<Vec<u8>, CtError> {
+    let malformed = |reason: String| CtError::MalformedInput(reason);
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty()).peekable();
+    let begin = lines.next().unwrap_or_default();
+    let found = begin
+        .strip_prefix("-----BEGIN CT ")
+        .and_then(|rest| rest.strip_suffix("-----"))
+        .ok_or_else(|| malformed(format!("expected '-----BEGIN CT {}-----', found '{}'", label, begin)))?;
+    if found != label {
+        return Err(malformed(format!("this is a CT {}, not a CT {}", found, label)));
+    }
+    if let Some(version) = lines.peek().copied().and_then(|line| line.strip_prefix("Version:")) {
+        let version = version.trim();
+        check_version(version.parse().map_err(|_| malformed(format!("'{}' is not a format version", version)))?)?;
+        lines.next();
+    }
+    let end = format!("-----END CT {}-----", label);
+    let (mut body, mut sum) = (String::new(), None);
+    for line in lines {
+        if line == end {
+            let sum: &str = sum.ok_or_else(|| malformed("missing '=checksum' line before the END line".to_string()))?;
+            let bytes = base64_decode(&body)?;
+            if !sum.eq_ignore_ascii_case(&checksum(&bytes)) {
+                return Err(malformed("checksum mismatch: the text was altered or truncated".to_string()));
+            }
+            return Ok(bytes);
+        }
+        if sum.is_some() {
+            return Err(malformed(format!("unexpected '{}' after the checksum line", line)));
+        }
+        match line.strip_prefix('=') {
+            Some(rest) => sum = Some(rest),
+            None => body.push_str(line),
+        }
+    }
+    Err(malformed(format!("missing '{}' (was the text cut off?)", end)))
+}
@@ -0,0 +1,278 @@
+//! Confidential assets (Elements-style): several kinds of coin on one ledger, with
+//! neither the amounts nor the kinds visible.
+//!
+//! Each asset has its own generator H_a, hashed from its name (`asset_tag`), and an
+//! output commits to v units of it over a blinded version of that generator:
+//!     A = H_a + t*H,   C = v*A + r*H = v*H_a + (v*t + r)*H.
+//! A is published with the output, but a fresh t makes two outputs of the same asset
+//! look unrelated. The balance check is unchanged: sum(inputs) = sum(outputs) holds
+//! only if every asset balances separately, since nobody knows how the H_a relate.
+//! (In the mod-p toy group everyone does; see `generators`.)
+//!
+//! Two proofs come with each output:
+//! - a range proof over A instead of G (`AssetRangeProof`), so no asset can go negative;
+//! - a surjection proof that A is one of the inputs' generators: A - A_j = (t - t_j)*H
+//!   for some j, a `sigma::OneOf` that does not say which j.
+//!
+//! Without the second, an output could use any generator, including one nobody has
+//! ever issued. Issuance (`issuance`) is how new generators enter a transaction.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{mod_mul, mod_reduce, verify_balance, Blinding, Commitment, Scalar};
+use crate::error::CtError;
+use crate::generators::hash_to_field;
+use crate::issuance::Issuance;
+use crate::params;
+use crate::rng::ToyRng;
+use crate::sigma::{self, All, Either, OneOf, Or, Representation, SigmaProof};
+use crate::transcript::Transcript;
+
+/// H_a: the unblinded generator of the asset called `name`.
+pub fn asset_tag(name: &str) -> i64 {
+    hash_to_field(&[b"ct/asset/".as_slice(), name.as_bytes()].concat())
+}
+
+/// A = H_a + t*H: the generator of `tag`, blinded by `tag_blinding`.
+pub fn blinded_generator(tag: i64, tag_blinding: Blinding) -> i64 {
+    mod_reduce(tag as i128 + mod_mul(tag_blinding.as_i64(), params::active().h) as i128)
+}
+
+/// v*A + r*H.
+fn commit_over(generator: i64, value: i64, blinding: i64) -> Commitment {
+    Commitment::from_i64(mod_reduce(mod_mul(value, generator) as i128 + mod_mul(blinding, params::active().h) as i128))
+}
+
+/// What the owner of an asset output knows: the asset, the tag blinding t, and (v, r).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetOpening {
+    pub tag: i64,
+    pub tag_blinding: Blinding,
+    pub value: Scalar,
+    pub blinding: Blinding,
+}
+
+impl AssetOpening {
+    /// A fresh opening of `value` units of the asset `tag`.
+    pub fn random(tag: i64, value: Scalar, rng: &mut ToyRng) -> Self {
+        AssetOpening { tag, tag_blinding: Blinding::new(rng.scalar()), value, blinding: Blinding::new(rng.scalar()) }
+    }
+
+    /// A = H_a + t*H.
+    pub fn generator(&self) -> i64 {
+        blinded_generator(self.tag, self.tag_blinding)
+    }
+
+    pub fn commit(&self) -> Commitment {
+        commit_over(self.generator(), self.value.as_i64(), self.blinding.as_i64())
+    }
+
+    /// v*t + r: the commitment's whole H component, which the balance must cancel.
+    fn total_blinding(&self) -> i64 {
+        mod_reduce(mod_mul(self.value.as_i64(), self.tag_blinding.as_i64()) as i128 + self.blinding.as_i64() as i128)
+    }
+}
+
+/// A range proof over the blinded generator A: bit commitments C_i = b_i*A + r_i*H
+/// with sum(2^i * C_i) = C, and one OR-proof per bit that C_i or C_i - A is a
+/// multiple of H.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetRangeProof {
+    pub bits: Vec<Commitment>,
+    pub proof: SigmaProof,
+}
+
+fn range_statement(generator: i64, bits: &[Commitment]) -> All<Or<Representation, Representation>> {
+    let h = params::active().h;
+    let bit = |c: &Commitment| {
+        let one = mod_reduce(c.as_i64() as i128 - generator as i128);
+        Or(Representation { public: c.as_i64(), bases: vec![h] }, Representation { public: one, bases: vec![h] })
+    };
+    All(bits.iter().map(bit).collect())
+}
+
+fn range_transcript(generator: i64, commitment: Commitment, bits: &[Commitment]) -> Transcript {
+    let mut transcript = Transcript::new("ct/asset-range-proof");
+    transcript.append("A", generator);
+    transcript.append("C", commitment.as_i64());
+    transcript.append_all("C_i", &bits.iter().map(|c| c.as_i64()).collect::<Vec<_>>());
+    transcript
+}
+
+/// sum(2^i * C_i).
+fn recompose(bits: &[Commitment]) -> Commitment {
+    let sum: i128 = bits.iter().enumerate().map(|(i, c)| mod_mul(1i64 << i, c.as_i64()) as i128).sum();
+    Commitment::from_i64(mod_reduce(sum))
+}
+
+impl AssetRangeProof {
+    pub fn create(opening: &AssetOpening, rng: &mut ToyRng) -> Result<Self, CtError> {
+        let (value, num_bits) = (opening.value.as_i64(), params::active().range_bits as usize);
+        if value < 0 {
+            return Err(CtError::NegativeValue(value));
+        }
+        if value >= 1i64 << num_bits {
+            return Err(CtError::OverflowedValue(value));
+        }
+        let mut blindings: Vec<i64> = (0..num_bits).map(|_| rng.scalar()).collect();
+        let rest: i128 = (1..num_bits).map(|i| mod_mul(1i64 << i, blindings[i]) as i128).sum();
+        blindings[0] = mod_reduce(opening.blinding.as_i64() as i128 - rest);
+        let generator = opening.generator();
+        let bits: Vec<Commitment> =
+            blindings.iter().enumerate().map(|(i, &r)| commit_over(generator, (value >> i) & 1, r)).collect();
+        let witness = blindings
+            .iter()
+            .enumerate()
+            .map(|(i, &r)| if (value >> i) & 1 == 0 { Either::Left(vec![r]) } else { Either::Right(vec![r]) })
+            .collect();
+        let mut transcript = range_transcript(generator, opening.commit(), &bits);
+        let proof = sigma::prove(&range_statement(generator, &bits), &mut transcript, &witness, rng);
+        Ok(AssetRangeProof { bits, proof })
+    }
+
+    /// 0 <= v < 2^n for C = v*A + r*H.
+    pub fn verify(&self, generator: i64, commitment: Commitment) -> Result<(), CtError> {
+        let num_bits = params::active().range_bits as usize;
+        if self.bits.len() != num_bits {
+            let reason = format!("asset range proof has {} bits, expected {}", self.bits.len(), num_bits);
+            return Err(CtError::MalformedInput(reason));
+        }
+        if recompose(&self.bits) != commitment {
+            return Err(CtError::InvalidProof("bit commitments do not recompose to C".to_string()));
+        }
+        let mut transcript = range_transcript(generator, commitment, &self.bits);
+        if !sigma::verify(&range_statement(generator, &self.bits), &mut transcript, &self.proof) {
+            return Err(CtError::InvalidProof("asset range proof fails".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// A proof that an output's generator is one of the inputs' generators.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SurjectionProof(pub SigmaProof);
+
+/// One of A - A_j is a multiple of H.
+fn surjection_statement(inputs: &[i64], output: i64) -> OneOf<Representation> {
+    let h = params::active().h;
+    let difference = |&input: &i64| mod_reduce(output as i128 - input as i128);
+    OneOf(inputs.iter().map(|input| Representation { public: difference(input), bases: vec![h] }).collect())
+}
+
+fn surjection_transcript(inputs: &[i64], output: i64) -> Transcript {
+    let mut transcript = Transcript::new("ct/surjection");
+    transcript.append_all("input generators", inputs);
+    transcript.append("output generator", output);
+    transcript
+}
+
+impl SurjectionProof {
+    /// Prove `output`'s generator is among `inputs`' generators, knowing both openings.
+    pub fn create(inputs: &[AssetOpening], output: &AssetOpening, rng: &mut ToyRng) -> Result<Self, CtError> {
+        let real = inputs.iter().position(|input| input.tag == output.tag).ok_or_else(|| {
+            CtError::MalformedInput("no input holds the asset this output pays".to_string())
+        })?;
+        let generators: Vec<i64> = inputs.iter().map(AssetOpening::generator).collect();
+        let difference = output.tag_blinding.as_i64() as i128 - inputs[real].tag_blinding.as_i64() as i128;
+        let witness = (real, vec![mod_reduce(difference)]);
+        let statement = surjection_statement(&generators, output.generator());
+        let mut transcript = surjection_transcript(&generators, output.generator());
+        Ok(SurjectionProof(sigma::prove(&statement, &mut transcript, &witness, rng)))
+    }
+
+    pub fn verify(&self, inputs: &[i64], output: i64) -> bool {
+        let mut transcript = surjection_transcript(inputs, output);
+        sigma::verify(&surjection_statement(inputs, output), &mut transcript, &self.0)
+    }
+}
+
+/// A spent output: its blinded generator and commitment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetInput {
+    pub generator: i64,
+    pub commitment: Commitment,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetOutput {
+    pub generator: i64,
+    pub commitment: Commitment,
+    pub range_proof: AssetRangeProof,
+    pub surjection_proof: SurjectionProof,
+}
+
+impl AssetOutput {
+    /// The output as a later transaction spends it.
+    pub fn as_input(&self) -> AssetInput {
+        AssetInput { generator: self.generator, commitment: self.commitment }
+    }
+}
+
+/// A transaction over any mix of assets. Fees are left out.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetTransaction {
+    pub inputs: Vec<AssetInput>,
+    pub issuances: Vec<Issuance>,
+    pub outputs: Vec<AssetOutput>,
+}
+
+impl AssetTransaction {
+    /// Spend `inputs`, and whatever `issuances` create, into `outputs` (asset tag and
+    /// value each). Every output gets fresh blindings except the last output's r,
+    /// which makes the H components cancel. Returns the openings of the outputs too.
+    pub fn create(
+        inputs: &[AssetOpening],
+        issuances: Vec<(Issuance, Vec<AssetOpening>)>,
+        outputs: &[(i64, Scalar)],
+        rng: &mut ToyRng,
+    ) -> Result<(Self, Vec<AssetOpening>), CtError> {
+        let issued = issuances.iter().flat_map(|(_, openings)| openings.iter().copied());
+        let sources: Vec<AssetOpening> = inputs.iter().copied().chain(issued).collect();
+        let mut openings: Vec<AssetOpening> =
+            outputs.iter().map(|&(tag, value)| AssetOpening::random(tag, value, rng)).collect();
+        let total = |openings: &[AssetOpening]| openings.iter().map(|o| o.total_blinding() as i128).sum::<i128>();
+        if let Some((last, others)) = openings.split_last_mut() {
+            let value_times_tag = mod_mul(last.value.as_i64(), last.tag_blinding.as_i64()) as i128;
+            last.blinding = Blinding::new(mod_reduce(total(&sources) - total(others) - value_times_tag));
+        }
+        let outputs = openings
+            .iter()
+            .map(|opening| {
+                Ok(AssetOutput {
+                    generator: opening.generator(),
+                    commitment: opening.commit(),
+                    range_proof: AssetRangeProof::create(opening, rng)?,
+                    surjection_proof: SurjectionProof::create(&sources, opening, rng)?,
+                })
+            })
+            .collect::<Result<Vec<_>, CtError>>()?;
+        let inputs = inputs.iter().map(|i| AssetInput { generator: i.generator(), commitment: i.commit() }).collect();
+        let issuances = issuances.into_iter().map(|(issuance, _)| issuance).collect();
+        Ok((AssetTransaction { inputs, issuances, outputs }, openings))
+    }
+
+    /// The inputs followed by what each issuance adds: what the outputs may draw on.
+    pub fn sources(&self) -> Vec<AssetInput> {
+        let issued = self.issuances.iter().flat_map(Issuance::inputs);
+        self.inputs.iter().copied().chain(issued).collect()
+    }
+
+    /// Every issuance, every output's range and surjection proofs, then
+    /// sum(inputs) + sum(issued) = sum(outputs).
+    pub fn verify(&self) -> Result<(), CtError> {
+        for issuance in &self.issuances {
+            issuance.verify(&self.inputs)?;
+        }
+        let sources = self.sources();
+        let input_generators: Vec<i64> = sources.iter().map(|input| input.generator).collect();
+        for (i, output) in self.outputs.iter().enumerate() {
+            output.range_proof.verify(output.generator, output.commitment)?;
+            if !output.surjection_proof.verify(&input_generators, output.generator) {
+                return Err(CtError::InvalidProof(format!("surjection proof for output {} fails", i)));
+            }
+        }
+        let inputs: Vec<Commitment> = sources.iter().map(|input| input.commitment).collect();
+        let outputs: Vec<Commitment> = self.outputs.iter().map(|output| output.commitment).collect();
+        verify_balance(&inputs, &outputs)
+    }
+}
@@ -0,0 +1,407 @@
+//! `ct attack --scenario <name>`: ways to cheat a confidential transaction and which
+//! check catches each one. Every scenario sets up its own transaction, narrates it,
+//! and reports whether the verifier rejected it.
+
+use pedersen_commitment_tx::analysis::{find_links, grind_blinding};
+use pedersen_commitment_tx::commitment::{
+    pedersen_commit, sum_commitments, verify_balance, Blinding, Commitment, Opening, Scalar,
+};
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::generators::{curve_dlog, dlog_h, forge_blinding, hash_to_curve};
+use pedersen_commitment_tx::range_proof::{
+    range_proof_create, range_proof_create_unchecked, range_proof_recompose, range_proof_verify,
+};
+use pedersen_commitment_tx::params;
+use pedersen_commitment_tx::rng::ToyRng;
+use serde_json::json;
+
+use crate::report::{outcome, result_json, Report};
+
+pub struct AttackScenario {
+    pub name: &'static str,
+    pub summary: &'static str,
+    /// Narrate the attack and return the verifier's verdict on it.
+    run: fn(&mut Report, &mut ToyRng) -> Result<(), CtError>,
+}
+
+/// Every scenario `ct attack` knows, in the order `ct attack` lists them.
+pub const SCENARIOS: &[AttackScenario] = &[
+    AttackScenario {
+        name: "negative-change",
+        summary: "pay 15 out of 10 by adding a -5 change output",
+        run: negative_change,
+    },
+    AttackScenario {
+        name: "value-overflow",
+        summary: "use a change value so large it wraps around p to -5",
+        run: value_overflow,
+    },
+    AttackScenario {
+        name: "wide-range-overflow",
+        summary: "with 62-bit range proofs p - 5 is in range, so the wraparound mints 5",
+        run: wide_range_overflow,
+    },
+    AttackScenario {
+        name: "mismatched-blinding",
+        summary: "amounts balance but the blinding factors do not",
+        run: mismatched_blinding,
+    },
+    AttackScenario {
+        name: "forged-proof",
+        summary: "patch a bit commitment so a range proof for -5 recomposes to C",
+        run: forged_proof,
+    },
+    AttackScenario {
+        name: "toy-proof-forgery",
+        summary: "the original toy proof π = 2C + 1 is forged without knowing v; the bit proof only needs k = H/G",
+        run: toy_proof_forgery,
+    },
+    AttackScenario {
+        name: "blinding-reuse",
+        summary: "a wallet reuses one blinding; an observer links outputs and learns amount differences",
+        run: blinding_reuse,
+    },
+    AttackScenario {
+        name: "grinding",
+        summary: "in a 16-bit group, trying blindings one by one finds a second opening: 10 in, 205 out",
+        run: grinding,
+    },
+    AttackScenario {
+        name: "known-dlog-h",
+        summary: "whoever knows k with H = k*G opens any C to any value: 10 in, 205 out",
+        run: known_dlog_h,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static AttackScenario> {
+    SCENARIOS.iter().find(|s| s.name == name)
+}
+
+/// Run one scenario with a fixed seed, so every run prints the same numbers.
+pub fn run(report: &mut Report, scenario: &AttackScenario) {
+    say!(report, "=== Attack: {} ===", scenario.name);
+    say!(report, "  {}\n", scenario.summary);
+    let verdict = (scenario.run)(report, &mut ToyRng::new(2024));
+    match &verdict {
+        Ok(()) => say!(report, "\nVerdict: ACCEPTED — this attack gets past the checks above."),
+        Err(e) => say!(report, "\nVerdict: REJECTED ({})", e),
+    }
+    report.record("scenario", json!(scenario.name));
+    report.record("verdict", result_json(&verdict));
+}
+
+/// Balance check shared by the scenarios: print both sides and record the result.
+fn check_balance(report: &mut Report, input: Commitment, outputs: &[Commitment]) -> Result<(), CtError> {
+    let result = verify_balance(&[input], outputs);
+    say!(report, "  C_input          = {}", input);
+    say!(report, "  sum(outputs)     = {}", sum_commitments(outputs));
+    match &result {
+        Ok(()) => say!(report, "  verify_balance   => ok"),
+        Err(e) => say!(report, "  verify_balance   => REJECTED ({})", e),
+    }
+    report.record("balance", result_json(&result));
+    result
+}
+
+/// The attack from the demo: 10 = 15 + (-5) balances, but -5 has no range proof.
+fn negative_change(report: &mut Report, rng: &mut ToyRng) -> Result<(), CtError> {
+    let (r_input, r_bob) = (Blinding::new(99999), Blinding::random(rng));
+    let r_change = r_input - r_bob;
+    let c_input = pedersen_commit(Scalar::new(10), r_input);
+    let c_bob = pedersen_commit(Scalar::new(15), r_bob);
+    let value_change = Scalar::new(-5);
+    let c_change = pedersen_commit(value_change, r_change);
+    say!(report, "  Input 10, Bob 15, change -5: 10 = 15 + (-5), so the commitments balance.");
+    check_balance(report, c_input, &[c_bob, c_change])?;
+
+    if let Err(e) = range_proof_create(value_change, r_change, rng) {
+        say!(report, "  An honest wallet refuses to prove the change: {}.", e);
+    }
+    let proof = range_proof_create_unchecked(value_change, r_change, rng);
+    let result = range_proof_verify(c_change, &proof);
+    say!(
+        report,
+        "  The attacker runs the prover anyway; {} bits encode -5 as {}.",
+        params::active().range_bits,
+        (-5i64).rem_euclid(1 << params::active().range_bits)
+    );
+    say!(report, "  verify(C_change, π_change) => {}", outcome(&result));
+    report.record("change_range_proof", result_json(&result));
+    say!(report, "  Caught by: the range proof. Balance alone cannot see negative amounts.");
+    result
+}
+
+/// Commitments live mod p, so v and v + p commit identically: p - 5 is "-5" in disguise.
+fn value_overflow(report: &mut Report, rng: &mut ToyRng) -> Result<(), CtError> {
+    let (r_input, r_bob) = (Blinding::new(99999), Blinding::random(rng));
+    let r_change = r_input - r_bob;
+    let modulus = params::active().modulus;
+    let value_change = Scalar::new(modulus - 5);
+    let c_input = pedersen_commit(Scalar::new(10), r_input);
+    let c_bob = pedersen_commit(Scalar::new(15), r_bob);
+    let c_change = pedersen_commit(value_change, r_change);
+    say!(report, "  Input 10, Bob 15, change p - 5 = {} (a huge positive number).", value_change);
+    say!(report, "  Mod p, 15 + (p - 5) = 10, so the commitments balance with no negative number in sight.");
+    check_balance(report, c_input, &[c_bob, c_change])?;
+    say!(
+        report,
+        "  pedersen_commit(p - 5, r) = {}  ==  pedersen_commit(-5, r) = {}",
+        c_change,
+        pedersen_commit(Scalar::new(-5), r_change)
+    );
+
+    let proof = range_proof_create_unchecked(value_change, r_change, rng);
+    let result = range_proof_verify(c_change, &proof);
+    say!(
+        report,
+        "  A range proof only covers [0, 2^{}); p - 5 needs {} bits.",
+        params::active().range_bits,
+        64 - (modulus - 5).leading_zeros()
+    );
+    say!(report, "  verify(C_change, π_change) => {}", outcome(&result));
+    report.record("change_range_proof", result_json(&result));
+    say!(report, "  Caught by: the range proof, because 2^n is far below p. With 2^n >= p it would pass");
+    say!(report, "  (see wide-range-overflow).");
+    result
+}
+
+/// The same p - 5 change under range proofs wider than p, then under the widest safe bound.
+fn wide_range_overflow(report: &mut Report, rng: &mut ToyRng) -> Result<(), CtError> {
+    let saved = params::active();
+    let result = wraparound_under(report, rng, saved);
+    params::set(saved).expect("the saved parameters were valid");
+    result
+}
+
+fn wraparound_under(report: &mut Report, rng: &mut ToyRng, saved: params::Params) -> Result<(), CtError> {
+    let modulus = saved.modulus;
+    let (r_input, r_bob) = (Blinding::new(99999), Blinding::random(rng));
+    let r_change = r_input - r_bob;
+    let value_change = Scalar::new(modulus - 5);
+    let c_input = pedersen_commit(Scalar::new(10), r_input);
+    let c_bob = pedersen_commit(Scalar::new(15), r_bob);
+    let c_change = pedersen_commit(value_change, r_change);
+    say!(report, "  Input 10, Bob 15, change p - 5 = {}. Mod p, 15 + (p - 5) = 10.", value_change);
+    check_balance(report, c_input, &[c_bob, c_change])?;
+
+    let mut verdicts = Vec::new();
+    for range_bits in [62, saved.max_range_bits(2)] {
+        params::set(params::Params { range_bits, ..saved })?;
+        let proof = range_proof_create_unchecked(value_change, r_change, rng);
+        let result = range_proof_verify(c_change, &proof);
+        let most = 2 * ((1i128 << range_bits) - 1);
+        say!(report, "\n  Range proofs over [0, 2^{}); two outputs add up to at most {}.", range_bits, most);
+        say!(report, "  verify(C_change, π_change) => {}", outcome(&result));
+        report.record(&format!("change_range_proof_{}_bits", range_bits), result_json(&result));
+        verdicts.push(result);
+    }
+    say!(report, "\n  With 62 bits p - 5 is \"in range\": 10 went in, 15 + (p - 5) came out, and the proofs pass,");
+    say!(report, "  so 5 coins were minted from nothing. The bound has to hold for the sum of all outputs:");
+    say!(report, "  k * (2^n - 1) < p, which for 2 outputs and this p means n <= {}.", saved.max_range_bits(2));
+    say!(report, "  Caught by: the range proof, but only with n chosen relative to the modulus.");
+    verdicts.pop().expect("two verdicts")
+}
+
+/// Amounts balance, but the sender picked the change blinding at random.
+fn mismatched_blinding(report: &mut Report, rng: &mut ToyRng) -> Result<(), CtError> {
+    let (r_input, r_bob, r_change) = (Blinding::new(12345), Blinding::new(11111), Blinding::random(rng));
+    say!(
+        report,
+        "  Input 10, Bob 5, change 5, but r_change = {} instead of r_input - r_bob = {}.",
+        r_change,
+        r_input - r_bob
+    );
+    let c_input = pedersen_commit(Scalar::new(10), r_input);
+    let c_bob = pedersen_commit(Scalar::new(5), r_bob);
+    let c_change = pedersen_commit(Scalar::new(5), r_change);
+    let result = check_balance(report, c_input, &[c_bob, c_change]);
+    say!(report, "  Caught by: the balance check. The leftover (r_input - r_bob - r_change)*H does not cancel,");
+    say!(report, "  so C_input - sum(outputs) is a commitment to 0 only if the blindings balance too.");
+    result
+}
+
+/// Fix the recomposition of a -5 proof by hand; the edited bit's OR-proof then breaks.
+fn forged_proof(report: &mut Report, rng: &mut ToyRng) -> Result<(), CtError> {
+    let r_change = Blinding::random(rng);
+    let c_change = pedersen_commit(Scalar::new(-5), r_change);
+    let mut proof = range_proof_create_unchecked(Scalar::new(-5), r_change, rng);
+    let gap = c_change - range_proof_recompose(&proof);
+    say!(report, "  The honest prover algorithm run on -5 gives bits that recompose to the wrong C.");
+    say!(report, "  C_change       = {}", c_change);
+    say!(report, "  sum(2^i * C_i) = {}", range_proof_recompose(&proof));
+    say!(report, "  The attacker adds the difference {} to C_0, so the sum now matches:", gap);
+    proof.bits[0].commitment = proof.bits[0].commitment + gap;
+    say!(report, "  sum(2^i * C_i) = {}", range_proof_recompose(&proof));
+
+    let result = range_proof_verify(c_change, &proof);
+    say!(report, "  verify(C_change, π_forged) => {}", outcome(&result));
+    report.record("forged_range_proof", result_json(&result));
+    say!(report, "  Caught by: the OR-proof of bit 0, which was made for the old C_0: its Fiat–Shamir challenge");
+    say!(report, "  now hashes a different commitment. A new one needs x with C_0 = 0*G + x*H or 1*G + x*H, which");
+    say!(report, "  is out of reach only where the discrete log is hard. Mod p, x = (C_0 - b*G)/H is a division,");
+    say!(report, "  so this attacker could rebuild the OR-proof (see known-dlog-h).");
+    result
+}
+
+/// The first version of this demo "proved" range with π = 2C + valid_bit and
+/// accepted π == 2C + 1. Kept here only to be forged.
+fn toy_proof_verify(commitment: Commitment, proof: i64) -> bool {
+    proof == commitment.as_i64() * 2 + 1
+}
+
+/// π = 2C + 1 is computable from C alone, so it says nothing about v. Bit proofs are bound to
+/// v only while the discrete log of H is unknown, which in the mod-p group it never is.
+fn toy_proof_forgery(report: &mut Report, rng: &mut ToyRng) -> Result<(), CtError> {
+    let r_change = Blinding::random(rng);
+    let c_change = pedersen_commit(Scalar::new(-5), r_change);
+    say!(report, "  The first toy range proof was π = 2C + valid_bit, and the verifier checked π == 2C + 1.");
+    say!(report, "  C_change commits to -5. The attacker never looks at v and simply computes π from C:");
+    let forged = c_change.as_i64() * 2 + 1;
+    say!(report, "  C_change = {}, π = 2C + 1 = {}", c_change, forged);
+    let toy = toy_proof_verify(c_change, forged);
+    say!(report, "  toy_verify(C_change, π) => {}", if toy { "ok" } else { "REJECTED" });
+    report.record("toy_proof", json!(toy));
+    say!(report, "  Anyone can produce π for any C, so the toy proof is not bound to the value at all.");
+
+    say!(report, "\n  The bit-decomposition proof that replaced it needs a proof per bit that C_i opens to");
+    say!(report, "  0 or 1, and the C_i must recompose to C. Run honestly on -5 it fails:");
+    let honest = range_proof_create_unchecked(Scalar::new(-5), r_change, rng);
+    let rejected = range_proof_verify(c_change, &honest);
+    say!(report, "  verify(C_change, π_bits) => {}", outcome(&rejected));
+    report.record("change_range_proof", result_json(&rejected));
+
+    let k = dlog_h();
+    let r_forged = forge_blinding(Opening::new(Scalar::new(-5), r_change), Scalar::new(3), k);
+    say!(report, "\n  But in the mod-p group k = H/G = {} is one division away, and k reopens C_change:", k);
+    say!(report, "  r' = r + (-5 - 3)/k = {}, and pedersen_commit(3, r') = C_change.", r_forged);
+    let forged = range_proof_create(Scalar::new(3), r_forged, rng);
+    let result = forged.and_then(|proof| range_proof_verify(c_change, &proof));
+    say!(report, "  verify(C_change, π_bits for 3 with r') => {}", outcome(&result));
+    report.record("forged_range_proof", result_json(&result));
+    say!(report, "  Caught by: nothing here. The bit proof rejects this only where the discrete log of H is hard");
+    say!(report, "  (a 256-bit curve such as secp256k1); this toy group has no hard discrete log.");
+    result
+}
+
+/// Not a cheat but a leak: every output is valid, and still the observer links them.
+fn blinding_reuse(report: &mut Report, rng: &mut ToyRng) -> Result<(), CtError> {
+    let reused = Blinding::random(rng);
+    let outputs = [
+        (Scalar::new(7), reused),
+        (Scalar::new(7), reused),
+        (Scalar::new(3), reused),
+        (Scalar::new(12), Blinding::random(rng)),
+        (Scalar::new(7), Blinding::random(rng)),
+    ];
+    say!(report, "  A buggy wallet gives outputs #0 to #2 the same blinding r = {}; #3 and #4 get fresh ones.", reused);
+    let mut commitments = Vec::new();
+    for (i, &(value, blinding)) in outputs.iter().enumerate() {
+        let commitment = pedersen_commit(value, blinding);
+        let proof = range_proof_create(value, blinding, rng)?;
+        let result = range_proof_verify(commitment, &proof);
+        say!(report, "  #{}: v = {:<3} C = {:<20} verify(C, π) => {}", i, value, commitment, outcome(&result));
+        result?;
+        commitments.push(commitment);
+    }
+
+    say!(report, "\n  The analyst sees only the commitments and tries every pair: is C_i - C_j some small d*G?");
+    let links = find_links(&commitments, 1000);
+    for link in &links {
+        say!(report, "  found: {}", link);
+    }
+    report.record("links", json!(links.iter().map(|link| link.to_string()).collect::<Vec<_>>()));
+    say!(report, "  #0 - #2 = (7 - 3)*G because the r*H parts cancel. #3 and #4 link to nothing, even though #4");
+    say!(report, "  holds 7 like #0: with fresh blindings the difference is hidden by (r4 - r0)*H.");
+    say!(report, "  Caught by: nothing. Each output is valid; only the wallet can avoid the leak, by drawing a fresh");
+    say!(report, "  blinding for every output (as the keychain's derivation does).");
+    Ok(())
+}
+
+/// Binding fails once k = H/G is known, and in the mod-p group it always is.
+fn known_dlog_h(report: &mut Report, rng: &mut ToyRng) -> Result<(), CtError> {
+    let params = params::active();
+    let k = dlog_h();
+    say!(report, "  In the mod-p group the discrete log is a division: k = H/G = {}/{} mod p.", params.h, params.g);
+    say!(report, "  k = {}", k);
+    say!(report, "  check: k*G = {} = H", pedersen_commit(Scalar::new(k), Blinding::new(0)));
+    say!(report, "  So C = v*G + r*H = (v + r*k)*G, and v can be traded for r at will.");
+
+    let (r_input, r_200) = (Blinding::new(99999), Blinding::random(rng));
+    let c_input = pedersen_commit(Scalar::new(10), r_input);
+    let c_200 = pedersen_commit(Scalar::new(200), r_200);
+    let c_rest = c_input - c_200;
+    let r_rest = forge_blinding(Opening::new(Scalar::new(-190), r_input - r_200), Scalar::new(5), k);
+    say!(report, "\n  Spending an input of 10, the attacker pays 200 to themselves and puts C_input - C_200 in");
+    say!(report, "  the second output. That really commits to -190, but k gives it a second opening:");
+    let reopened = pedersen_commit(Scalar::new(5), r_rest);
+    say!(report, "  r' = r + (-190 - 5)/k = {}, and pedersen_commit(5, r') = {} = C_rest", r_rest, reopened);
+    check_balance(report, c_input, &[c_200, c_rest])?;
+    let paid = range_proof_create(Scalar::new(200), r_200, rng).and_then(|proof| range_proof_verify(c_200, &proof));
+    let rest = range_proof_create(Scalar::new(5), r_rest, rng).and_then(|proof| range_proof_verify(c_rest, &proof));
+    say!(report, "  verify(C_200, π)  => {}", outcome(&paid));
+    say!(report, "  verify(C_rest, π) => {}   (proving 5 with r')", outcome(&rest));
+    report.record("range_proofs", json!({ "paid": result_json(&paid), "rest": result_json(&rest) }));
+    say!(report, "  10 in, 200 + 5 out, and every check passes: a proof only shows that SOME opening is in range.");
+
+    let h = hash_to_curve(b"H");
+    say!(report, "\n  The fix is a group where k is hard to find, plus an H that nobody chose as k*G.");
+    say!(report, "  Hashing the label \"H\" to the toy curve gives H = {}; its designer never knew k,", h);
+    let k_curve = curve_dlog(h).expect("the curve is cyclic");
+    say!(report, "  but with only 199 points a search still finds it: k = {}. On secp256k1 the same search", k_curve);
+    say!(report, "  takes about 2^128 steps, which is why a real H is hashed to a real curve.");
+    say!(report, "  Caught by: nothing here. Binding needs a hard discrete log, and this toy group has none.");
+    paid.and(rest)
+}
+
+/// Tries per second assumed when extrapolating a grind to real group sizes.
+const GRIND_RATE: f64 = 1e9;
+/// A prime just below 2^16, for the grinding scenario.
+const GRIND_MODULUS: i64 = 65521;
+
+/// The known-dlog forgery again, but found by brute force in a small group.
+fn grinding(report: &mut Report, rng: &mut ToyRng) -> Result<(), CtError> {
+    let saved = params::active();
+    params::set(params::Params { modulus: GRIND_MODULUS, g: 2, h: 30011, ..saved })?;
+    let result = grind_in_small_group(report, rng);
+    params::set(saved).expect("the saved parameters were valid");
+    result
+}
+
+fn grind_in_small_group(report: &mut Report, rng: &mut ToyRng) -> Result<(), CtError> {
+    let params = params::active();
+    say!(report, "  Weak parameters: p = {} (16 bits), G = {}, H = {}.", params.modulus, params.g, params.h);
+    let (r_input, r_200) = (Blinding::new(9999), Blinding::random(rng));
+    let c_input = pedersen_commit(Scalar::new(10), r_input);
+    let c_200 = pedersen_commit(Scalar::new(200), r_200);
+    let c_rest = c_input - c_200;
+    say!(report, "  The attacker spends C_input = {} (10 coins) into C_200 = {}", c_input, c_200);
+    say!(report, "  and C_rest = C_input - C_200 = {}, which really commits to -190.", c_rest);
+    say!(report, "  No algebra this time: try r = 0, 1, 2, ... until 5*G + r*H = C_rest.");
+    let (found, tries) = grind_blinding(c_rest, Scalar::new(5), 0..params.modulus);
+    let r_rest = found.expect("some r in [0, p) opens any C to any value");
+    let reopened = pedersen_commit(Scalar::new(5), r_rest);
+    say!(report, "  found r' = {} after {} tries: pedersen_commit(5, r') = {}", r_rest, tries, reopened);
+    report.record("grind", json!({ "modulus": params.modulus, "tries": tries, "blinding": r_rest.as_i64() }));
+
+    check_balance(report, c_input, &[c_200, c_rest])?;
+    let paid = range_proof_create(Scalar::new(200), r_200, rng).and_then(|proof| range_proof_verify(c_200, &proof));
+    let rest = range_proof_create(Scalar::new(5), r_rest, rng).and_then(|proof| range_proof_verify(c_rest, &proof));
+    say!(report, "  verify(C_200, π)  => {}", outcome(&paid));
+    say!(report, "  verify(C_rest, π) => {}   (proving 5 with r')", outcome(&rest));
+    report.record("range_proofs", json!({ "paid": result_json(&paid), "rest": result_json(&rest) }));
+
+    say!(report, "\n  Binding holds only while this search is out of reach. It takes about p/2 tries;");
+    say!(report, "  at {:.0e} tries per second:", GRIND_RATE);
+    for (group, bits) in [("p ~ 2^16 (this group)", 16), ("p ~ 2^61 (the default)", 61), ("a 256-bit group", 256)] {
+        let seconds = 2f64.powi(bits - 1) / GRIND_RATE;
+        let years = seconds / (365.25 * 86400.0);
+        let time = match years {
+            _ if years < 1.0 => format!("{:.1e} seconds", seconds),
+            _ if years < 1e6 => format!("{:.0} years", years),
+            _ => format!("{:.1e} years", years),
+        };
+        say!(report, "    {:<24} {}", group, time);
+    }
+    say!(report, "  Caught by: nothing in a 16-bit group. The group order is what makes grinding hopeless.");
+    paid.and(rest)
+}
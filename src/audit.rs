@@ -0,0 +1,85 @@
+//! Selective disclosure: an owner shows an auditor what some of their outputs hold,
+//! and nothing about the others.
+//!
+//! For each output disclosed, the owner hands over its opening (v, r) and an MMR
+//! inclusion proof against the output root of a block header. The auditor recomputes
+//! C = v*G + r*H and checks that the MMR under that header holds C, so the amount
+//! belongs to an output that really is on the chain. Nobody else learns anything:
+//! the chain still shows only C. The auditor, though, can now open C for anyone, so
+//! a disclosure is only as private as the auditor keeps it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::{BlockHash, BlockHeader};
+use crate::commitment::{Commitment, Opening};
+use crate::error::CtError;
+use crate::ledger::Ledger;
+use crate::mmr::MmrProof;
+
+/// One output opened to the auditor.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisclosedOutput {
+    /// "value" and "blinding" sit next to "inclusion".
+    #[serde(flatten)]
+    pub opening: Opening,
+    /// C = opening.commit() is in the output MMR of the disclosure's block.
+    pub inclusion: MmrProof,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Disclosure {
+    /// The block whose header anchors the inclusion proofs.
+    pub block: BlockHash,
+    pub outputs: Vec<DisclosedOutput>,
+}
+
+impl Disclosure {
+    /// Disclose `openings`, whose outputs must be in the MMR of `ledger` (spent or not),
+    /// as of its tip.
+    pub fn create(ledger: &Ledger, openings: &[Opening]) -> Result<Self, CtError> {
+        let block = ledger
+            .tip()
+            .map(BlockHeader::hash)
+            .ok_or_else(|| CtError::MalformedInput("the ledger has no blocks".to_string()))?;
+        let outputs = openings
+            .iter()
+            .map(|&opening| {
+                let inclusion = ledger.output_mmr().prove(opening.commit()).ok_or_else(|| {
+                    CtError::MalformedInput(format!("{} is not an output on the chain", opening.commit()))
+                })?;
+                Ok(DisclosedOutput { opening, inclusion })
+            })
+            .collect::<Result<_, CtError>>()?;
+        Ok(Disclosure { block, outputs })
+    }
+
+    /// The auditor's check of every output against `header`, which must be the
+    /// disclosure's block: the commitment each opening gives, and whether it is on the chain.
+    pub fn check(&self, header: &BlockHeader) -> Vec<(Commitment, Result<(), CtError>)> {
+        let matches = header.hash() == self.block;
+        self.outputs
+            .iter()
+            .map(|output| {
+                let commitment = output.opening.commit();
+                let result = if !matches {
+                    Err(CtError::MalformedInput(format!("the disclosure is for block {}", self.block)))
+                } else if output.inclusion.verify(&header.output_root, commitment) {
+                    Ok(())
+                } else {
+                    Err(CtError::InvalidProof(format!("{} is not in the block's output MMR", commitment)))
+                };
+                (commitment, result)
+            })
+            .collect()
+    }
+
+    /// All of `check` passes.
+    pub fn verify(&self, header: &BlockHeader) -> Result<(), CtError> {
+        self.check(header).into_iter().try_for_each(|(_, result)| result)
+    }
+
+    /// What the disclosed outputs hold between them.
+    pub fn total(&self) -> i64 {
+        self.outputs.iter().map(|output| output.opening.value.as_i64()).sum()
+    }
+}
@@ -0,0 +1,14 @@
+//! JSON disclosure files for auditors (`ct wallet disclose`, `ct audit`):
+//!
+//! ```json
+//! { "block": "<hash of the header whose output root the proofs are against>",
+//!   "outputs": [
+//!     { "value": 5, "blinding": 1234,
+//!       "inclusion": { "size": 4, "path": [{ "hash": "<hex>", "left": false }],
+//!                      "peaks": ["<hex>", "<hex>"], "peak": 0 } }
+//!   ] }
+//! ```
+//!
+//! `inclusion` is the output's `MmrProof`: the siblings from the leaf up to its
+//! peak, every peak, and which peak the path leads to. The file is what serde writes
+//! for an `audit::Disclosure` (`slate_file::to_json`).
@@ -0,0 +1,92 @@
+//! Batch verification. Every check `Transaction::verify` makes with group elements
+//! is a linear equation sum(s_i * P_i) = 0 (`sigma::Equation`): the balance, each
+//! kernel signature, both branches of every bit's OR-proof, each recomposition.
+//! Instead of checking them one by one, `verify_batch` checks one random linear
+//! combination sum(rho_k * equation_k) = 0. If any equation is false, the sum is
+//! nonzero except with probability about 1/p.
+//!
+//! Terms on the same element merge (G and H are in almost every equation), so the
+//! combination needs fewer scalar multiplications than the equations do separately.
+//! What is left is one multi-scalar multiplication, which `msm` computes with far
+//! fewer group additions than the multiplications one at a time; on a real curve
+//! those additions are most of the verifier's work.
+//! The rho_k are hashed from all the equations, so no prover can pick them.
+
+use std::collections::BTreeMap;
+
+use crate::commitment::{mod_mul, mod_reduce, Commitment};
+use crate::error::CtError;
+use crate::fixed_base::Group;
+use crate::msm::{multi_scalar_mul, separate_additions};
+use crate::sigma::Equation;
+use crate::transaction::Transaction;
+use crate::transcript::Transcript;
+
+/// What one batch check covered, and what it saved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchReport {
+    pub transactions: usize,
+    /// Equations checked by the one combined check.
+    pub equations: usize,
+    /// Scalar multiplications to check the equations one by one: one per term.
+    pub multiplications: usize,
+    /// Scalar multiplications in the combined check: one per distinct element.
+    pub batched_multiplications: usize,
+    /// Group additions for those multiplications one at a time, by double-and-add.
+    pub separate_additions: usize,
+    /// Group additions for them as one multi-scalar multiplication.
+    pub msm_additions: usize,
+}
+
+impl BatchReport {
+    /// How many times fewer scalar multiplications the batch needs.
+    pub fn speedup(&self) -> f64 {
+        self.multiplications as f64 / self.batched_multiplications.max(1) as f64
+    }
+}
+
+/// sum(rho_k * equation_k), with the coefficients of each element added up.
+fn combine(equations: &[Equation]) -> Equation {
+    let mut transcript = Transcript::new("ct/batch");
+    for equation in equations {
+        let terms: Vec<i64> = equation.iter().flat_map(|&(scalar, element)| [scalar, element]).collect();
+        transcript.append_all("equation", &terms);
+    }
+    let mut combined = BTreeMap::new();
+    for equation in equations {
+        let rho = transcript.challenge("rho");
+        for &(scalar, element) in equation {
+            let coefficient: &mut i64 = combined.entry(element).or_default();
+            *coefficient = mod_reduce(*coefficient as i128 + mod_mul(rho, scalar) as i128);
+        }
+    }
+    combined.into_iter().map(|(element, coefficient)| (coefficient, element)).collect()
+}
+
+/// Verify every transaction with one combined check. Checks that need no group
+/// arithmetic still fail individually; if the combined check fails, the
+/// transactions are verified one by one to report the first invalid one.
+pub fn verify_batch(transactions: &[Transaction]) -> Result<BatchReport, CtError> {
+    let mut equations = Vec::new();
+    for tx in transactions {
+        equations.extend(tx.equations()?);
+    }
+    let combined = combine(&equations);
+    let terms: Vec<(Vec<u8>, Commitment)> = combined
+        .iter()
+        .map(|&(coefficient, element)| ((coefficient as u64).to_le_bytes().to_vec(), Commitment::from_i64(element)))
+        .collect();
+    let (sum, msm_additions) = multi_scalar_mul(&terms);
+    if sum != Commitment::identity() {
+        transactions.iter().try_for_each(Transaction::verify)?;
+        return Err(CtError::InvalidProof("the batch check fails".to_string()));
+    }
+    Ok(BatchReport {
+        transactions: transactions.len(),
+        equations: equations.len(),
+        multiplications: equations.iter().map(Vec::len).sum(),
+        batched_multiplications: combined.len(),
+        separate_additions: separate_additions(&terms.into_iter().map(|(k, _)| k).collect::<Vec<_>>()),
+        msm_additions,
+    })
+}
@@ -0,0 +1,74 @@
+//! BigField mode: the same Pedersen arithmetic with arbitrary-precision integers.
+//! The i64 version is capped at 63-bit moduli and generators, whose products still
+//! fit the i128 it reduces from. Here every intermediate is a BigInt, so realistic
+//! 256-bit moduli, generators and blindings work without wraparound bugs.
+//! Still toy math: the group is integers mod p, not curve points.
+
+use num_bigint::{BigInt, BigUint};
+
+use crate::error::CtError;
+
+/// Public parameters with arbitrary-size modulus and generators.
+pub struct BigField {
+    pub modulus: BigUint,
+    pub g: BigUint,
+    pub h: BigUint,
+}
+
+impl BigField {
+    pub fn new(modulus: BigUint, g: BigUint, h: BigUint) -> Self {
+        BigField { modulus, g, h }
+    }
+
+    /// 256-bit parameters: p = 2^255 - 19 (the prime behind Curve25519).
+    /// G and H are the x and y coordinates of secp256k1's base point, used here only
+    /// as two unrelated-looking 256-bit numbers.
+    pub fn realistic_256() -> Self {
+        let modulus = (BigUint::from(1u8) << 255u32) - BigUint::from(19u8);
+        let g = hex("79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798");
+        let h = hex("483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8");
+        BigField::new(modulus, g, h)
+    }
+
+    /// Reject parameters that are not a 256-bit field: p must be odd and in
+    /// (2^254, 2^256), which 2^255 - 19 is too, and G and H non-zero and different mod p.
+    pub fn validate(&self) -> Result<(), CtError> {
+        if self.modulus.bits() < 255 || self.modulus.bits() > 256 || !self.modulus.bit(0) {
+            return Err(CtError::MalformedInput(format!(
+                "bigfield modulus {:#x} is not an odd number in (2^254, 2^256)",
+                self.modulus
+            )));
+        }
+        for (name, generator) in [("G", &self.g), ("H", &self.h)] {
+            if generator % &self.modulus == BigUint::from(0u8) {
+                return Err(CtError::MalformedInput(format!("bigfield generator {} is 0 mod p", name)));
+            }
+        }
+        if &self.g % &self.modulus == &self.h % &self.modulus {
+            return Err(CtError::MalformedInput("bigfield G and H must differ mod p".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Reduce any (possibly negative) integer into [0, p).
+    pub fn reduce(&self, x: &BigInt) -> BigUint {
+        let p = BigInt::from(self.modulus.clone());
+        let r = ((x % &p) + &p) % &p;
+        r.to_biguint().expect("reduced value is non-negative")
+    }
+
+    /// C = v*G + r*H (mod p), exact for any size of v and r.
+    pub fn commit(&self, value: &BigInt, blinding: &BigInt) -> BigUint {
+        let term = value * BigInt::from(self.g.clone()) + blinding * BigInt::from(self.h.clone());
+        self.reduce(&term)
+    }
+
+    /// Homomorphic sum of commitments (mod p).
+    pub fn sum(&self, commitments: &[&BigUint]) -> BigUint {
+        commitments.iter().fold(BigUint::from(0u8), |acc, c| (acc + *c) % &self.modulus)
+    }
+}
+
+fn hex(digits: &str) -> BigUint {
+    BigUint::parse_bytes(digits.as_bytes(), 16).expect("valid hex constant")
+}
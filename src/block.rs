@@ -0,0 +1,142 @@
+//! Toy blocks: transactions grouped under a header that links to the previous block
+//! and carries the block's aggregate kernel data. With those totals alone anyone can
+//! check the block-wide balance
+//!     sum(outputs) - sum(inputs) = (minted - fees - burned)*G + sum(kernel excesses) + offset*H
+//! which is what makes the supply auditable without seeing any amount.
+//! The header also anchors the root of the output MMR (see `mmr`) after the block.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::commitment::{fee_commitment, pedersen_commit, sum_commitments, Blinding, Commitment, Scalar};
+use crate::error::CtError;
+use crate::ledger::Ledger;
+use crate::transaction::Transaction;
+use crate::wire::{from_hex, to_hex, Encode};
+
+/// SHA-256 of a block header's wire encoding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BlockHash(pub [u8; 32]);
+
+impl fmt::Display for BlockHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&to_hex(&self.0))
+    }
+}
+
+impl FromStr for BlockHash {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = from_hex(s)?;
+        let bytes = bytes
+            .try_into()
+            .map_err(|b: Vec<u8>| CtError::MalformedInput(format!("a block hash has 32 bytes, not {}", b.len())))?;
+        Ok(BlockHash(bytes))
+    }
+}
+
+serde_as_text!(BlockHash);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeader {
+    /// Hash of the previous header; all zeros for the genesis block.
+    pub prev: BlockHash,
+    pub height: u64,
+    /// SHA-256 over the txids in block order.
+    #[serde(with = "crate::wire::hex")]
+    pub tx_root: [u8; 32],
+    /// Root of the MMR of every output created up to and including this block.
+    #[serde(with = "crate::wire::hex")]
+    pub output_root: [u8; 32],
+    /// Sum of every kernel's excess.
+    pub kernel_excess: Commitment,
+    /// Sum of every transaction's kernel offset.
+    pub offset: Blinding,
+    pub fees: u64,
+    pub minted: u64,
+    pub burned: u64,
+}
+
+impl BlockHeader {
+    pub fn hash(&self) -> BlockHash {
+        BlockHash(Sha256::digest(self.to_bytes()).into())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Block {
+    pub header: BlockHeader,
+    pub transactions: Vec<Transaction>,
+}
+
+/// The header `transactions` should have on top of the tip of `ledger`.
+fn header_for(ledger: &Ledger, transactions: &[Transaction]) -> BlockHeader {
+    let prev = ledger.tip();
+    let mut tx_root = Sha256::new();
+    let mut outputs = ledger.output_mmr().clone();
+    for tx in transactions {
+        tx_root.update(tx.txid().0);
+        for commitment in tx.output_commitments() {
+            outputs.append(commitment);
+        }
+    }
+    BlockHeader {
+        prev: prev.map(BlockHeader::hash).unwrap_or_default(),
+        height: prev.map_or(0, |p| p.height + 1),
+        tx_root: tx_root.finalize().into(),
+        output_root: outputs.root(),
+        kernel_excess: transactions
+            .iter()
+            .filter_map(|tx| tx.kernel)
+            .fold(Commitment::from_i64(0), |sum, kernel| sum + kernel.excess),
+        offset: transactions.iter().fold(Blinding::default(), |sum, tx| sum + tx.offset),
+        fees: transactions.iter().map(|tx| tx.fee).sum(),
+        minted: transactions.iter().map(|tx| tx.minted).sum(),
+        burned: transactions.iter().map(|tx| tx.burned).sum(),
+    }
+}
+
+impl Block {
+    /// A block on top of the tip of `ledger` (an empty ledger for genesis), with the header filled in.
+    pub fn new(ledger: &Ledger, transactions: Vec<Transaction>) -> Self {
+        Block { header: header_for(ledger, &transactions), transactions }
+    }
+
+    /// Check the block against the chain tip in `ledger`: the header links and its
+    /// totals match the transactions, there is at most one coinbase, the block-wide
+    /// balance holds, and every transaction applies in order (valid, inputs unspent).
+    pub fn validate(&self, ledger: &Ledger) -> Result<(), CtError> {
+        if self.header != header_for(ledger, &self.transactions) {
+            return Err(CtError::MalformedInput(format!(
+                "block {} does not match its transactions or does not extend the tip",
+                self.header.height
+            )));
+        }
+        if self.transactions.iter().filter(|tx| tx.is_coinbase()).count() > 1 {
+            return Err(CtError::InvalidCoinbase("a block may contain only one coinbase".to_string()));
+        }
+
+        let inputs: Vec<Commitment> = self.transactions.iter().flat_map(|tx| tx.input_commitments()).collect();
+        let outputs: Vec<Commitment> = self.transactions.iter().flat_map(|tx| tx.output_commitments()).collect();
+        let header = &self.header;
+        let created = fee_commitment(header.minted) - fee_commitment(header.fees) - fee_commitment(header.burned)
+            + header.kernel_excess
+            + pedersen_commit(Scalar::new(0), header.offset);
+        if sum_commitments(&outputs) - sum_commitments(&inputs) != created {
+            return Err(CtError::BalanceMismatch {
+                inputs: sum_commitments(&inputs) + created,
+                outputs: sum_commitments(&outputs),
+            });
+        }
+
+        let mut scratch = ledger.clone();
+        for tx in &self.transactions {
+            scratch.apply(tx)?;
+        }
+        Ok(())
+    }
+}
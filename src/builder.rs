@@ -0,0 +1,208 @@
+//! `TransactionBuilder`: list the inputs you can open and the output amounts, and
+//! `build` picks the output blindings so they cancel against the inputs' (the last
+//! one is r = sum(input r) - sum(other output r)) and proves every output in range,
+//! one proof per output or, with `aggregate_range_proof`, one for all of them.
+//! An output can also bring its own blinding (`add_output_with_blinding`, e.g. change
+//! derived from a wallet seed); the last output without one takes up the difference.
+//! `add_output_rewindable` also makes its range proof rewindable with a nonce.
+//! An output paid to an address (`add_output_to`) gets its blinding and note from
+//! `ecdh::send_to`, so it counts as bringing its own blinding too.
+//! `memo_to` seals the final opening of the output added last to an address (see
+//! `memo`), whatever its blinding, so the receiver needs no (v, r) from the sender.
+//! With `kernel`, no output takes up the difference: every blinding is independent
+//! and a kernel signs for the excess, as in Mimblewimble.
+//! `burn` destroys part of the inputs' value in the clear, like a fee nobody collects.
+//!
+//! ```text
+//! let (tx, openings) = TransactionBuilder::new()
+//!     .add_input(c_input, Opening::new(Scalar::new(10), r_input))
+//!     .add_output(Scalar::new(5))
+//!     .add_output(Scalar::new(4))
+//!     .fee(1)
+//!     .build(&mut rng)?;
+//! ```
+
+use crate::commitment::{pedersen_commit_checked, Blinding, Commitment, Opening, Scalar};
+use crate::ecdh::{self, Address};
+use crate::error::CtError;
+use crate::kernel::Kernel;
+use crate::memo::Memo;
+use crate::range_proof::{aggregate_range_proof_create, range_proof_create, range_proof_create_rewindable};
+use crate::rng::ToyRng;
+use crate::transaction::{Transaction, TxEntry};
+
+/// Where an output's blinding comes from.
+#[derive(Clone, Copy, Debug)]
+enum OutputBlinding {
+    /// Chosen by `build`.
+    Free,
+    Chosen(Blinding),
+    /// Chosen, with a rewindable range proof for this nonce.
+    Rewindable(Blinding, [u8; 32]),
+    /// Derived from a shared secret with the receiver's address.
+    To(Address),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TransactionBuilder {
+    inputs: Vec<(Commitment, Opening)>,
+    /// Each output's value and where its blinding comes from.
+    outputs: Vec<(Scalar, OutputBlinding)>,
+    /// Outputs, by index, whose opening is sealed to an address.
+    memos: Vec<(usize, Address)>,
+    fee: u64,
+    burned: u64,
+    aggregate: bool,
+    kernel: bool,
+    lock_height: u64,
+}
+
+impl TransactionBuilder {
+    pub fn new() -> Self {
+        TransactionBuilder::default()
+    }
+
+    /// Spend `commitment`, which the sender can open.
+    pub fn add_input(mut self, commitment: Commitment, opening: Opening) -> Self {
+        self.inputs.push((commitment, opening));
+        self
+    }
+
+    /// Pay `value` to a new output; its blinding is chosen by `build`.
+    pub fn add_output(mut self, value: Scalar) -> Self {
+        self.outputs.push((value, OutputBlinding::Free));
+        self
+    }
+
+    /// Pay `value` to a new output with a blinding the caller chose. At least one
+    /// output must be left to `build` so the blindings can cancel.
+    pub fn add_output_with_blinding(mut self, value: Scalar, blinding: Blinding) -> Self {
+        self.outputs.push((value, OutputBlinding::Chosen(blinding)));
+        self
+    }
+
+    /// Like `add_output_with_blinding`, with a range proof the owner can rewind with
+    /// `nonce` (see `range_proof_rewind`). Not with `aggregate_range_proof`.
+    pub fn add_output_rewindable(mut self, value: Scalar, blinding: Blinding, nonce: [u8; 32]) -> Self {
+        self.outputs.push((value, OutputBlinding::Rewindable(blinding, nonce)));
+        self
+    }
+
+    /// Pay `value` to `address`: the output carries a note its receiver finds by scanning.
+    pub fn add_output_to(mut self, value: Scalar, address: Address) -> Self {
+        self.outputs.push((value, OutputBlinding::To(address)));
+        self
+    }
+
+    /// Attach a memo sealing the opening of the output added last to `address`.
+    /// Does nothing before the first output.
+    pub fn memo_to(mut self, address: Address) -> Self {
+        if let Some(last) = self.outputs.len().checked_sub(1) {
+            self.memos.push((last, address));
+        }
+        self
+    }
+
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    /// Destroy `burned` of the inputs' value; see `Transaction::burned`.
+    pub fn burn(mut self, burned: u64) -> Self {
+        self.burned = burned;
+        self
+    }
+
+    /// Prove all outputs in range with one `AggregateRangeProof`.
+    pub fn aggregate_range_proof(mut self) -> Self {
+        self.aggregate = true;
+        self
+    }
+
+    /// Give every output an independent blinding and sign the excess
+    /// x = sum(output r) - sum(input r) with a kernel instead.
+    pub fn kernel(mut self) -> Self {
+        self.kernel = true;
+        self
+    }
+
+    /// Keep the kernel out of any block below `height`. Only with `kernel`.
+    pub fn lock_height(mut self, height: u64) -> Self {
+        self.lock_height = height;
+        self
+    }
+
+    /// The public transaction and the openings of its outputs, in output order.
+    /// Fails if an opening does not match its input, an output cannot be proven in
+    /// range, or the amounts do not balance.
+    pub fn build(self, rng: &mut ToyRng) -> Result<(Transaction, Vec<Opening>), CtError> {
+        for (i, (commitment, opening)) in self.inputs.iter().enumerate() {
+            if opening.commit() != *commitment {
+                return Err(CtError::MalformedInput(format!("input {} does not open to its commitment", i)));
+            }
+        }
+        if self.outputs.is_empty() {
+            return Err(CtError::MalformedInput("a transaction needs at least one output".to_string()));
+        }
+        let last = match self.outputs.iter().rposition(|(_, blinding)| matches!(blinding, OutputBlinding::Free)) {
+            _ if self.kernel => None,
+            Some(last) => Some(last),
+            None => {
+                let reason = "a transaction needs at least one output whose blinding the builder chooses";
+                return Err(CtError::MalformedInput(reason.to_string()));
+            }
+        };
+
+        let mut blindings = Vec::new();
+        let mut notes = Vec::new();
+        for (i, &(value, source)) in self.outputs.iter().enumerate() {
+            let (blinding, note) = match source {
+                OutputBlinding::Chosen(blinding) | OutputBlinding::Rewindable(blinding, _) => (blinding, None),
+                OutputBlinding::To(address) => {
+                    let (blinding, note) = ecdh::send_to(address, value, rng);
+                    (blinding, Some(note))
+                }
+                OutputBlinding::Free if Some(i) == last => (Blinding::default(), None),
+                OutputBlinding::Free => (Blinding::random(rng), None),
+            };
+            blindings.push(blinding);
+            notes.push(note);
+        }
+        let input_total = self.inputs.iter().fold(Blinding::default(), |sum, (_, o)| sum + o.blinding);
+        let excess = blindings.iter().fold(Blinding::default(), |sum, &r| sum + r) - input_total;
+        if let Some(last) = last {
+            blindings[last] = blindings.iter().fold(input_total, |rest, &r| rest - r);
+        }
+
+        let mut outputs = Vec::new();
+        let mut openings = Vec::new();
+        for (i, ((&(value, source), blinding), note)) in self.outputs.iter().zip(blindings).zip(notes).enumerate() {
+            let opening = Opening::new(value, blinding);
+            let commitment = pedersen_commit_checked(value, blinding)?;
+            let proof = match source {
+                _ if self.aggregate => None,
+                OutputBlinding::Rewindable(_, nonce) => Some(range_proof_create_rewindable(value, blinding, &nonce)?),
+                _ => Some(range_proof_create(value, blinding, rng)?),
+            };
+            let memo = self.memos.iter().find(|&&(output, _)| output == i);
+            let memo = memo.map(|&(_, address)| Memo::seal(address, &opening, rng));
+            outputs.push(TxEntry { proof, note, memo, ..TxEntry::input(commitment) });
+            openings.push(opening);
+        }
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|&(commitment, _)| TxEntry::input(commitment))
+            .collect();
+        let mut tx = Transaction::new(inputs, outputs).with_fee(self.fee).with_burn(self.burned);
+        if self.aggregate {
+            tx = tx.with_aggregate_proof(aggregate_range_proof_create(&openings, rng)?);
+        }
+        if self.kernel {
+            tx = tx.with_kernel(Kernel::create_locked(excess, self.fee, self.lock_height, rng));
+        }
+        tx.verify_balance()?;
+        Ok((tx, openings))
+    }
+}
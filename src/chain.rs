@@ -0,0 +1,134 @@
+//! A chain that can hold competing branches. Every block counts as one unit of
+//! work, so the heavier branch is the longer one; ties keep the branch seen first.
+//! The ledger after each main-chain block is kept, so rolling back to a fork
+//! point is dropping the snapshots above it before applying the other branch.
+
+use std::collections::HashMap;
+
+use crate::block::{Block, BlockHash, BlockHeader};
+use crate::error::CtError;
+use crate::ledger::Ledger;
+
+#[derive(Clone, Debug)]
+pub struct Chain {
+    blocks: HashMap<BlockHash, Block>,
+    /// Hashes of the main chain, by height.
+    main: Vec<BlockHash>,
+    /// The ledger after each main-chain block, by height.
+    states: Vec<Ledger>,
+}
+
+impl Chain {
+    /// A chain holding only `genesis`.
+    pub fn new(genesis: Block) -> Result<Self, CtError> {
+        let mut ledger = Ledger::new();
+        let hash = ledger.apply_block(&genesis)?;
+        Ok(Chain { blocks: HashMap::from([(hash, genesis)]), main: vec![hash], states: vec![ledger] })
+    }
+
+    /// Rebuild a chain from `blocks` in the order `blocks()` gives, revalidating each.
+    pub fn replay(blocks: Vec<Block>) -> Result<Self, CtError> {
+        let mut blocks = blocks.into_iter();
+        let genesis = blocks.next().ok_or_else(|| CtError::MalformedInput("no genesis block".to_string()))?;
+        let mut chain = Chain::new(genesis)?;
+        for block in blocks {
+            chain.add_block(block)?;
+        }
+        Ok(chain)
+    }
+
+    /// Every block, side branches included, in an order `replay` accepts: by height,
+    /// main-chain blocks first so that ties resolve the same way again.
+    pub fn blocks(&self) -> Vec<&Block> {
+        let mut blocks: Vec<&Block> = self.blocks.values().collect();
+        blocks.sort_by_key(|block| {
+            let hash = block.header.hash();
+            (block.header.height, !self.is_main(&hash), hash.0)
+        });
+        blocks
+    }
+
+    pub fn block(&self, hash: &BlockHash) -> Option<&Block> {
+        self.blocks.get(hash)
+    }
+
+    /// Headers of the main chain, genesis first.
+    pub fn headers(&self) -> Vec<&BlockHeader> {
+        self.main.iter().map(|hash| &self.blocks[hash].header).collect()
+    }
+
+    /// The ledger at the tip of the main chain.
+    pub fn ledger(&self) -> &Ledger {
+        self.states.last().expect("the chain has a genesis block")
+    }
+
+    pub fn tip(&self) -> &BlockHeader {
+        self.ledger().tip().expect("the chain has a genesis block")
+    }
+
+    /// Is the block with this hash on the main chain?
+    pub fn is_main(&self, hash: &BlockHash) -> bool {
+        self.blocks.get(hash).is_some_and(|block| self.main.get(block.header.height as usize) == Some(hash))
+    }
+
+    /// The height where the branch ending at `hash` leaves the main chain, and the
+    /// branch's blocks above it, oldest first.
+    fn branch(&self, hash: BlockHash) -> Result<(usize, Vec<BlockHash>), CtError> {
+        let mut path = Vec::new();
+        let mut current = hash;
+        loop {
+            let block = self
+                .blocks
+                .get(&current)
+                .ok_or_else(|| CtError::MalformedInput(format!("unknown block {}", current)))?;
+            let height = block.header.height as usize;
+            if self.main.get(height) == Some(&current) {
+                path.reverse();
+                return Ok((height, path));
+            }
+            if height == 0 {
+                return Err(CtError::MalformedInput(format!("block {} has a different genesis", hash)));
+            }
+            path.push(current);
+            current = block.header.prev;
+        }
+    }
+
+    /// The ledger after the block with this hash, on whichever branch it is.
+    pub fn state_after(&self, hash: BlockHash) -> Result<Ledger, CtError> {
+        let (fork, path) = self.branch(hash)?;
+        let mut ledger = self.states[fork].clone();
+        for hash in path {
+            ledger.apply_block(&self.blocks[&hash])?;
+        }
+        Ok(ledger)
+    }
+
+    /// Validate `block` on top of its parent and store it. If that makes its branch
+    /// heavier than the main chain, reorganize: roll the ledger back to the fork
+    /// point and apply the branch. Returns the main-chain blocks that were
+    /// disconnected, newest last (empty without a reorg).
+    pub fn add_block(&mut self, block: Block) -> Result<Vec<Block>, CtError> {
+        let hash = block.header.hash();
+        if self.blocks.contains_key(&hash) {
+            return Err(CtError::MalformedInput(format!("block {} is already known", hash)));
+        }
+        self.state_after(block.header.prev)?.apply_block(&block)?;
+        let height = block.header.height as usize;
+        self.blocks.insert(hash, block);
+        if height < self.main.len() {
+            return Ok(Vec::new());
+        }
+
+        let (fork, path) = self.branch(hash)?;
+        let disconnected = self.main.split_off(fork + 1).iter().map(|hash| self.blocks[hash].clone()).collect();
+        self.states.truncate(fork + 1);
+        for hash in path {
+            let mut ledger = self.ledger().clone();
+            ledger.apply_block(&self.blocks[&hash])?;
+            self.states.push(ledger);
+            self.main.push(hash);
+        }
+        Ok(disconnected)
+    }
+}
@@ -0,0 +1,37 @@
+//! The chain file `ct chain` keeps between runs (`chain.json` by default):
+//!
+//! ```json
+//! { "version": 1, "blocks": ["v1:<wire bytes of a block, hex>", ...] }
+//! ```
+//!
+//! Blocks are stored in replay order (by height, main chain first). Loading
+//! revalidates every block and rebuilds the UTXO set, so a tampered file is
+//! rejected instead of trusted.
+
+use pedersen_commitment_tx::block::Block;
+use pedersen_commitment_tx::chain::Chain;
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::wire::{from_versioned_hex, to_versioned_hex, Encode};
+use serde_json::{json, Value};
+
+use crate::slate_file::parse_json;
+
+pub fn chain_json(chain: &Chain) -> Value {
+    json!({ "blocks": chain.blocks().iter().map(|block| to_versioned_hex(&block.to_bytes())).collect::<Vec<_>>() })
+}
+
+pub fn parse_chain(text: &str) -> Result<Chain, CtError> {
+    let document: Value = parse_json(text)?;
+    let blocks = document
+        .get("blocks")
+        .and_then(Value::as_array)
+        .ok_or_else(|| CtError::MalformedInput("chain file has no \"blocks\" list".to_string()))?;
+    let blocks = blocks
+        .iter()
+        .map(|block| {
+            let hex = block.as_str().ok_or_else(|| CtError::MalformedInput("blocks must be hex strings".to_string()))?;
+            Block::from_bytes(&from_versioned_hex(hex)?)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Chain::replay(blocks)
+}
@@ -0,0 +1,181 @@
+//! `ct challenge`: capture-the-flag puzzles built on the flaws `ct attack` shows.
+//! `new` generates a level from a seed and writes only its public numbers to a file;
+//! `check` regenerates the level from the file's seed, so it knows the secrets again,
+//! and judges the answer. Same level and seed, same puzzle.
+
+use pedersen_commitment_tx::commitment::{lin_comb, pedersen_commit, sum_commitments, Blinding, Commitment, Scalar};
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::params;
+use pedersen_commitment_tx::rng::ToyRng;
+use serde_json::{json, Map, Value};
+
+/// What a level generates from its seed: the numbers the player sees, and the ones
+/// only the checker keeps.
+pub struct Puzzle {
+    pub public: Vec<(&'static str, i64)>,
+    secret: Vec<i64>,
+}
+
+pub struct Level {
+    pub title: &'static str,
+    pub task: &'static str,
+    pub answer_format: &'static str,
+    pub hint: &'static str,
+    generate: fn(&mut ToyRng) -> Puzzle,
+    /// Ok(None) for a correct answer, Ok(Some(why)) for a wrong one.
+    check: fn(&Puzzle, &str) -> Result<Option<String>, CtError>,
+}
+
+/// Every level, from 1.
+pub const LEVELS: &[Level] = &[
+    Level {
+        title: "Shared blinding",
+        task: "One wallet reused a blinding for two of these outputs. Which two?",
+        answer_format: "i,j",
+        hint: "ct attack --scenario blinding-reuse",
+        generate: shared_blinding,
+        check: check_shared_blinding,
+    },
+    Level {
+        title: "Second opening",
+        task: "C commits to some amount with a blinding you do not know. Open it to `target` instead: find r.",
+        answer_format: "r",
+        hint: "ct attack --scenario known-dlog-h",
+        generate: second_opening,
+        check: check_second_opening,
+    },
+    Level {
+        title: "Printing press",
+        task: "Spend C_input into two outputs with in-range amounts that add up to more than it holds.",
+        answer_format: "v1:r1,v2:r2",
+        hint: "a second opening (level 2) for the output that balances",
+        generate: printing_press,
+        check: check_printing_press,
+    },
+];
+
+pub fn find(level: u32) -> Result<&'static Level, CtError> {
+    level
+        .checked_sub(1)
+        .and_then(|i| LEVELS.get(i as usize))
+        .ok_or_else(|| CtError::MalformedInput(format!("there are levels 1 to {}, not {}", LEVELS.len(), level)))
+}
+
+const OUTPUT_NAMES: [&str; 6] = ["C0", "C1", "C2", "C3", "C4", "C5"];
+
+fn in_range(value: i64) -> bool {
+    (0..1i64 << params::active().range_bits).contains(&value)
+}
+
+fn number(text: &str) -> Result<i64, CtError> {
+    text.trim().parse().map_err(|_| CtError::MalformedInput(format!("'{}' is not a number", text.trim())))
+}
+
+/// v*G + r*H for an answer's (v, r). Forged blindings are anywhere in [0, p), so
+/// this goes through `lin_comb`, which cannot overflow.
+fn answer_commit(value: i64, blinding: i64) -> Commitment {
+    Commitment::from_i64(lin_comb(value, blinding))
+}
+
+/// Split "a<sep>b" into two numbers.
+fn pair(text: &str, separator: char) -> Result<(i64, i64), CtError> {
+    let (a, b) = text
+        .split_once(separator)
+        .ok_or_else(|| CtError::MalformedInput(format!("expected two numbers separated by '{}'", separator)))?;
+    Ok((number(a)?, number(b)?))
+}
+
+fn shared_blinding(rng: &mut ToyRng) -> Puzzle {
+    let first = rng.between(0, 6);
+    let second = (first + rng.between(1, 6)) % 6;
+    let reused = Blinding::random(rng);
+    let public = OUTPUT_NAMES
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| {
+            let value = Scalar::new(rng.between(1, 1i64 << params::active().range_bits));
+            let blinding = if i as i64 == first || i as i64 == second { reused } else { Blinding::random(rng) };
+            (name, pedersen_commit(value, blinding).as_i64())
+        })
+        .collect();
+    Puzzle { public, secret: vec![first.min(second), first.max(second)] }
+}
+
+fn check_shared_blinding(puzzle: &Puzzle, answer: &str) -> Result<Option<String>, CtError> {
+    let (i, j) = pair(answer, ',')?;
+    Ok(([i.min(j), i.max(j)] != puzzle.secret[..]).then(|| format!("C{} and C{} do not share a blinding", i, j)))
+}
+
+fn second_opening(rng: &mut ToyRng) -> Puzzle {
+    let max = 1i64 << params::active().range_bits;
+    let commitment = pedersen_commit(Scalar::new(rng.between(0, max)), Blinding::random(rng));
+    Puzzle { public: vec![("C", commitment.as_i64()), ("target", rng.between(0, max))], secret: Vec::new() }
+}
+
+fn check_second_opening(puzzle: &Puzzle, answer: &str) -> Result<Option<String>, CtError> {
+    let (commitment, target) = (Commitment::from_i64(puzzle.public[0].1), puzzle.public[1].1);
+    let opened = answer_commit(target, number(answer)?);
+    Ok((opened != commitment).then(|| format!("{}*G + r*H = {}, not C", target, opened)))
+}
+
+fn printing_press(rng: &mut ToyRng) -> Puzzle {
+    let value = rng.between(1, 1i64 << (params::active().range_bits - 1));
+    let commitment = pedersen_commit(Scalar::new(value), Blinding::random(rng));
+    Puzzle { public: vec![("C_input", commitment.as_i64())], secret: vec![value] }
+}
+
+fn check_printing_press(puzzle: &Puzzle, answer: &str) -> Result<Option<String>, CtError> {
+    let (first, second) = answer
+        .split_once(',')
+        .ok_or_else(|| CtError::MalformedInput("expected two outputs separated by ','".to_string()))?;
+    let outputs = [pair(first, ':')?, pair(second, ':')?];
+    let commitments: Vec<Commitment> = outputs.iter().map(|&(v, r)| answer_commit(v, r)).collect();
+    let sum = sum_commitments(&commitments);
+    let paid: i64 = outputs.iter().map(|&(v, _)| v).sum();
+    let input = puzzle.secret[0];
+    Ok(if let Some(&(v, _)) = outputs.iter().find(|&&(v, _)| !in_range(v)) {
+        Some(format!("{} is out of range; its range proof would fail", v))
+    } else if sum != Commitment::from_i64(puzzle.public[0].1) {
+        Some(format!("the outputs sum to {}, not C_input", sum))
+    } else if paid <= input {
+        Some(format!("that balances, but {} is not more than the {} C_input holds", paid, input))
+    } else {
+        None
+    })
+}
+
+/// The challenge file: the level and seed to regenerate it from, the parameters it
+/// was made with, and the public numbers.
+pub fn challenge_json(level: u32, seed: u64, puzzle: &Puzzle) -> Value {
+    let params = params::active();
+    let shown: Map<String, Value> =
+        puzzle.public.iter().map(|&(name, value)| (name.to_string(), json!(value))).collect();
+    let info = find(level).expect("generated levels exist");
+    json!({
+        "level": level,
+        "seed": seed,
+        "title": info.title,
+        "task": info.task,
+        "answer_format": info.answer_format,
+        "params": { "modulus": params.modulus, "g": params.g, "h": params.h, "range_bits": params.range_bits },
+        "puzzle": shown,
+    })
+}
+
+pub fn generate(level: u32, seed: u64) -> Result<Puzzle, CtError> {
+    Ok((find(level)?.generate)(&mut ToyRng::new(seed)))
+}
+
+/// Regenerate the challenge in `file` and judge `answer`: Ok(None) if it is right.
+pub fn check(file: &Value, answer: &str) -> Result<(u32, Option<String>), CtError> {
+    let malformed = |what: &str| CtError::MalformedInput(format!("the challenge file has no valid '{}'", what));
+    let level = file["level"].as_u64().and_then(|l| u32::try_from(l).ok()).ok_or_else(|| malformed("level"))?;
+    let seed = file["seed"].as_u64().ok_or_else(|| malformed("seed"))?;
+    let puzzle = generate(level, seed)?;
+    if challenge_json(level, seed, &puzzle) != *file {
+        return Err(CtError::MalformedInput(
+            "the challenge file does not match its seed (edited, or made with other --modulus/--g/--h)".to_string(),
+        ));
+    }
+    Ok((level, (find(level)?.check)(&puzzle, answer)?))
+}
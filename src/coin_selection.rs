@@ -0,0 +1,124 @@
+//! Coin selection: which of the wallet's unspent outputs to spend for a payment.
+//! Every strategy returns indices into the candidate values whose sum covers the
+//! target (amount plus fee); whatever is left over becomes a change output.
+//!
+//! - largest-first spends few, large outputs: small transactions, but the wallet
+//!   fills up with small change.
+//! - smallest-first spends the small outputs first and so consolidates dust, at the
+//!   price of bigger transactions (one input per output spent).
+//! - branch-and-bound searches for a subset that hits the target exactly, so the
+//!   transaction needs no change output (and no range proof for one). This toy
+//!   version tries every subset in depth-first order, pruning branches that
+//!   overshoot or can no longer reach the target, and falls back to largest-first
+//!   when no exact match exists.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::CtError;
+
+/// How many branches branch-and-bound explores before giving up on an exact match.
+const BNB_TRIES: usize = 100_000;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Strategy {
+    #[default]
+    LargestFirst,
+    SmallestFirst,
+    BranchAndBound,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 3] = [Strategy::LargestFirst, Strategy::SmallestFirst, Strategy::BranchAndBound];
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Strategy::LargestFirst => "largest-first",
+            Strategy::SmallestFirst => "smallest-first",
+            Strategy::BranchAndBound => "branch-and-bound",
+        })
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Strategy::ALL.into_iter().find(|strategy| strategy.to_string() == s).ok_or_else(|| {
+            let names: Vec<String> = Strategy::ALL.iter().map(Strategy::to_string).collect();
+            CtError::MalformedInput(format!("unknown strategy '{}' (expected one of: {})", s, names.join(", ")))
+        })
+    }
+}
+
+/// Indices of `values` to spend for `target`, chosen by `strategy`.
+pub fn select(values: &[i64], target: i64, strategy: Strategy) -> Result<Vec<usize>, CtError> {
+    let available: i64 = values.iter().sum();
+    if available < target {
+        return Err(CtError::InsufficientFunds { available, needed: target });
+    }
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    match strategy {
+        Strategy::LargestFirst => order.sort_by_key(|&i| std::cmp::Reverse(values[i])),
+        Strategy::SmallestFirst => order.sort_by_key(|&i| values[i]),
+        Strategy::BranchAndBound => {
+            if let Some(exact) = branch_and_bound(values, target) {
+                return Ok(exact);
+            }
+            return select(values, target, Strategy::LargestFirst);
+        }
+    }
+    let mut selected = Vec::new();
+    let mut total = 0;
+    for i in order {
+        if total >= target {
+            break;
+        }
+        selected.push(i);
+        total += values[i];
+    }
+    Ok(selected)
+}
+
+/// Depth-first search over the candidates sorted largest first.
+struct Search<'a> {
+    values: &'a [i64],
+    order: Vec<usize>,
+    /// remaining[k]: what the candidates from position k on add up to.
+    remaining: Vec<i64>,
+    tries: usize,
+}
+
+impl Search<'_> {
+    /// Pick from position `k` on to make up `left`; true once `chosen` hits it exactly.
+    fn run(&mut self, k: usize, left: i64, chosen: &mut Vec<usize>) -> bool {
+        self.tries += 1;
+        if left == 0 {
+            return true;
+        }
+        if k == self.order.len() || left < 0 || self.remaining[k] < left || self.tries > BNB_TRIES {
+            return false;
+        }
+        let candidate = self.order[k];
+        chosen.push(candidate);
+        if self.run(k + 1, left - self.values[candidate], chosen) {
+            return true;
+        }
+        chosen.pop();
+        self.run(k + 1, left, chosen)
+    }
+}
+
+/// A subset of `values` summing to exactly `target`, if the search finds one.
+fn branch_and_bound(values: &[i64], target: i64) -> Option<Vec<usize>> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(values[i]));
+    let mut remaining = vec![0; order.len() + 1];
+    for k in (0..order.len()).rev() {
+        remaining[k] = remaining[k + 1] + values[order[k]];
+    }
+    let mut chosen = Vec::new();
+    Search { values, order, remaining, tries: 0 }.run(0, target, &mut chosen).then_some(chosen)
+}
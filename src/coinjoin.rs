@@ -0,0 +1,153 @@
+//! CoinJoin: a coordinator merges several wallets' payments into one confidential
+//! transaction with a single kernel and a single offset.
+//!
+//! 1. `Registration::new` (each wallet): its inputs, its outputs with range proofs,
+//!    its share of the fee, an offset share and, for the kernel, x_i*H and two MuSig2
+//!    nonces (see `musig`), where x_i = sum(r_out) - sum(r_in) - offset_i.
+//! 2. `Coordinator::register`: checks the inputs are unspent and not registered by
+//!    anyone else, the range proofs, and that the wallet's part balances:
+//!    sum(in) + x_i*H + offset_i*H = sum(out) + fee_i*G.
+//! 3. `Coordinator::session`: once everyone is in, one signing session for the sum
+//!    of the fees; each wallet signs with `RegistrationSecrets::sign`.
+//! 4. `Coordinator::finish`: adds up the partial signatures into one kernel and the
+//!    offset shares into one offset, and sorts the inputs and outputs.
+//!
+//! Cut-through of separate transactions (`cut_through::Aggregate`) keeps one kernel
+//! per transaction, and each kernel balances only with its own inputs and outputs.
+//! Here the one kernel and offset balance only the whole transaction, and with the
+//! amounts hidden any output could belong to any input. The coordinator itself still
+//! sees who registered what; real CoinJoins hide that with blinded registrations.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{fee_commitment, sum_commitments, Blinding, Commitment, Opening};
+use crate::error::CtError;
+use crate::kernel::{excess, public_key, split_excess, Kernel};
+use crate::ledger::Ledger;
+use crate::musig::{self, SecretNonces, Session, Signer};
+use crate::range_proof::{range_proof_create, range_proof_verify};
+use crate::rng::ToyRng;
+use crate::transaction::{Transaction, TxEntry};
+
+/// What a wallet sends the coordinator.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Registration {
+    pub inputs: Vec<TxEntry>,
+    pub outputs: Vec<TxEntry>,
+    pub fee: u64,
+    pub offset: Blinding,
+    /// x_i*H and the round-1 nonces.
+    pub signer: Signer,
+}
+
+/// What a wallet keeps until it signs.
+#[derive(Debug)]
+pub struct RegistrationSecrets {
+    pub excess: Blinding,
+    nonces: SecretNonces,
+}
+
+impl RegistrationSecrets {
+    /// The wallet's partial signature for the coordinator's session.
+    pub fn sign(self, session: &Session) -> i64 {
+        session.sign(self.excess, self.nonces)
+    }
+}
+
+impl Registration {
+    /// Spend `inputs` into `outputs`, paying `fee`. Every value is the wallet's own.
+    pub fn new(
+        inputs: &[Opening],
+        outputs: &[Opening],
+        fee: u64,
+        rng: &mut ToyRng,
+    ) -> Result<(Registration, RegistrationSecrets), CtError> {
+        let available: i64 = inputs.iter().map(|opening| opening.value.as_i64()).sum();
+        let needed = outputs.iter().map(|opening| opening.value.as_i64()).sum::<i64>() + fee as i64;
+        if available != needed {
+            let message = format!("inputs hold {} but outputs and fee need {}", available, needed);
+            return Err(CtError::MalformedInput(message));
+        }
+        let blinding = |openings: &[Opening]| openings.iter().fold(Blinding::default(), |sum, o| sum + o.blinding);
+        let (offset, excess) = split_excess(blinding(outputs) - blinding(inputs), rng);
+        let outputs = outputs
+            .iter()
+            .map(|o| Ok(TxEntry::new(o.commit(), range_proof_create(o.value, o.blinding, rng)?)))
+            .collect::<Result<Vec<_>, CtError>>()?;
+        let inputs = inputs
+            .iter()
+            .map(|opening| TxEntry::input(opening.commit()))
+            .collect();
+        let (nonces, public_nonces) = musig::nonce_round(rng);
+        let signer = Signer { public_excess: public_key(excess), nonces: public_nonces };
+        Ok((Registration { inputs, outputs, fee, offset, signer }, RegistrationSecrets { excess, nonces }))
+    }
+
+    fn commitments(entries: &[TxEntry]) -> Vec<Commitment> {
+        entries.iter().map(|entry| entry.commitment).collect()
+    }
+}
+
+/// Collects registrations and assembles the joint transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coordinator {
+    pub registrations: Vec<Registration>,
+}
+
+impl Coordinator {
+    pub fn new() -> Self {
+        Coordinator::default()
+    }
+
+    /// Check a wallet's registration against `ledger` and the others, and accept it.
+    pub fn register(&mut self, registration: Registration, ledger: &Ledger) -> Result<(), CtError> {
+        ledger.check_inputs(&Transaction::new(registration.inputs.clone(), Vec::new()))?;
+        let mut registered = self.registrations.iter().flat_map(|other| &other.inputs);
+        let claimed_twice = |input: &&TxEntry| registration.inputs.iter().any(|own| own.commitment == input.commitment);
+        if let Some(input) = registered.find(claimed_twice) {
+            return Err(CtError::Conflict(input.commitment));
+        }
+        for output in &registration.outputs {
+            let proof = output
+                .proof
+                .as_ref()
+                .ok_or_else(|| CtError::MalformedInput(format!("output {} has no range proof", output.commitment)))?;
+            range_proof_verify(output.commitment, proof)?;
+        }
+        let inputs = Registration::commitments(&registration.inputs);
+        let outputs = Registration::commitments(&registration.outputs);
+        let claimed = registration.signer.public_excess + public_key(registration.offset);
+        if excess(&inputs, &outputs, registration.fee) != claimed {
+            return Err(CtError::BalanceMismatch {
+                inputs: sum_commitments(&inputs) + claimed,
+                outputs: sum_commitments(&outputs) + fee_commitment(registration.fee),
+            });
+        }
+        self.registrations.push(registration);
+        Ok(())
+    }
+
+    pub fn fee(&self) -> u64 {
+        self.registrations.iter().map(|registration| registration.fee).sum()
+    }
+
+    /// The one signing session for everyone's kernel shares, over the total fee.
+    pub fn session(&self) -> Result<Session, CtError> {
+        let signers = self.registrations.iter().map(|registration| registration.signer).collect();
+        Session::new(signers, &[self.fee() as i64])
+    }
+
+    /// The joint transaction, from every wallet's partial signature (in registration order).
+    pub fn finish(&self, session: &Session, partials: &[i64]) -> Result<Transaction, CtError> {
+        let kernel = Kernel { excess: session.public, signature: session.aggregate(partials)?, lock_height: 0 };
+        let mut inputs: Vec<TxEntry> = self.registrations.iter().flat_map(|r| r.inputs.clone()).collect();
+        let mut outputs: Vec<TxEntry> = self.registrations.iter().flat_map(|r| r.outputs.clone()).collect();
+        // Sorted, so the order says nothing about who registered what.
+        inputs.sort_by_key(|entry| entry.commitment.as_i64());
+        outputs.sort_by_key(|entry| entry.commitment.as_i64());
+        let offset = self.registrations.iter().fold(Blinding::default(), |sum, r| sum + r.offset);
+        let tx = Transaction::new(inputs, outputs).with_fee(self.fee()).with_offset(offset).with_kernel(kernel);
+        tx.verify()?;
+        Ok(tx)
+    }
+}
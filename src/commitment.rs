@@ -0,0 +1,328 @@
+//! Pedersen commitments over integers mod a prime, with newtypes so a value, a
+//! blinding factor and a commitment can never be mixed up by accident.
+
+use std::fmt;
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::CtError;
+use crate::fixed_base::Group;
+use crate::params::{self, Params};
+use crate::rng::ToyRng;
+
+/// Default public parameters (known to everyone, like in a real system); `params` can
+/// swap them at startup. We work modulo a prime so numbers wrap around.
+/// In a real system, G and H would be curve points.
+pub const MODULUS: i64 = 2_i64.pow(61) - 1;
+pub const G: i64 = 3; // "Generator G" — value dimension
+pub const H: i64 = 7; // "Generator H" — blinding/randomness dimension
+
+/// Reduce a (possibly negative, possibly wide) intermediate into [0, p).
+pub fn mod_reduce(x: i128) -> i64 {
+    params::field().reduce(x) as i64
+}
+
+/// a*b mod p. Proof arithmetic multiplies two field elements, so it goes through i128.
+pub fn mod_mul(a: i64, b: i64) -> i64 {
+    params::field().mul(a, b) as i64
+}
+
+/// a^(p-2) = 1/a mod p (Fermat; p is prime).
+pub fn mod_inverse(a: i64) -> i64 {
+    let field = params::field();
+    field.pow(a, field.modulus() - 2) as i64
+}
+
+/// Commitment arithmetic for proof elements: a*G + b*H (mod p) with arbitrary-size a, b.
+pub fn lin_comb(a: i64, b: i64) -> i64 {
+    let params = params::active();
+    mod_reduce(mod_mul(a, params.g) as i128 + mod_mul(b, params.h) as i128)
+}
+
+/// The secret amount v being committed to. May be negative (the attack demo needs that).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Scalar(i64);
+
+impl Scalar {
+    pub const fn new(value: i64) -> Self {
+        Scalar(value)
+    }
+
+    pub fn as_i64(self) -> i64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// The secret blinding factor r that hides v. Only the owner of an output knows it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Blinding(i64);
+
+impl Blinding {
+    pub const fn new(blinding: i64) -> Self {
+        Blinding(blinding)
+    }
+
+    /// A fresh random blinding in [0, 2^32), short enough to read in the demo's output.
+    pub fn random(rng: &mut ToyRng) -> Self {
+        Blinding((rng.next_u64() >> 32) as i64)
+    }
+
+    pub fn as_i64(self) -> i64 {
+        self.0
+    }
+
+    /// The exact sum or difference while it fits in i64, else the same blinding mod p:
+    /// r*H is the same commitment either way.
+    fn from_i128(blinding: i128) -> Self {
+        Blinding(i64::try_from(blinding).unwrap_or_else(|_| mod_reduce(blinding)))
+    }
+}
+
+/// r1 + r2: the blinding of a sum of commitments.
+impl Add for Blinding {
+    type Output = Blinding;
+
+    fn add(self, other: Blinding) -> Blinding {
+        Blinding::from_i128(self.0 as i128 + other.0 as i128)
+    }
+}
+
+/// r1 - r2: the blinding left over after splitting off another output.
+impl Sub for Blinding {
+    type Output = Blinding;
+
+    fn sub(self, other: Blinding) -> Blinding {
+        Blinding::from_i128(self.0 as i128 - other.0 as i128)
+    }
+}
+
+impl fmt::Display for Blinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// What the owner of a commitment knows: the (v, r) it opens to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Opening {
+    pub value: Scalar,
+    pub blinding: Blinding,
+}
+
+impl Opening {
+    pub fn new(value: Scalar, blinding: Blinding) -> Self {
+        Opening { value, blinding }
+    }
+
+    pub fn commit(&self) -> Commitment {
+        pedersen_commit(self.value, self.blinding)
+    }
+}
+
+/// A public commitment C = v*G + r*H (mod p). This is all the ledger ever sees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Commitment(i64);
+
+impl Commitment {
+    /// Wrap a raw commitment value (e.g. one read back from the ledger).
+    pub fn from_i64(raw: i64) -> Self {
+        Commitment(mod_reduce(raw as i128))
+    }
+
+    pub fn as_i64(self) -> i64 {
+        self.0
+    }
+}
+
+/// Homomorphic addition: commit(v1, r1) + commit(v2, r2) = commit(v1 + v2, r1 + r2).
+impl Add for Commitment {
+    type Output = Commitment;
+
+    fn add(self, other: Commitment) -> Commitment {
+        Commitment(mod_reduce(self.0 as i128 + other.0 as i128))
+    }
+}
+
+/// Homomorphic subtraction: commit(v1, r1) - commit(v2, r2) = commit(v1 - v2, r1 - r2).
+impl Sub for Commitment {
+    type Output = Commitment;
+
+    fn sub(self, other: Commitment) -> Commitment {
+        Commitment(mod_reduce(self.0 as i128 - other.0 as i128))
+    }
+}
+
+/// The mod-p group as a `Group`, so batch checks can run through `msm`.
+impl Group for Commitment {
+    fn identity() -> Self {
+        Commitment(0)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        *self + *other
+    }
+}
+
+/// Parse a commitment printed by `Display`: a decimal number in [0, p), or the hex of
+/// `{:#}`, "0x" and exactly 16 lowercase hex digits.
+impl FromStr for Commitment {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let canonical_hex = |hex: &str| hex.len() == 16 && hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+        let raw = match s.trim().strip_prefix("0x") {
+            Some(hex) if canonical_hex(hex) => i64::from_str_radix(hex, 16).ok(),
+            Some(_) => None,
+            None => s.trim().parse().ok(),
+        }
+        .ok_or_else(|| CtError::MalformedInput(format!("'{}' is not a commitment", s)))?;
+        if !(0..params::active().modulus).contains(&raw) {
+            return Err(CtError::MalformedInput(format!("commitment {} is not in [0, p)", raw)));
+        }
+        Ok(Commitment(raw))
+    }
+}
+
+/// A decimal number, or with `{:#}` the canonical hex: "0x" and 16 lowercase digits,
+/// one spelling per commitment whatever the group, like a compressed point's bytes.
+impl fmt::Display for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return write!(f, "0x{:016x}", self.0);
+        }
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// A number, like in the JSON files; checked to be in [0, p) like `FromStr`.
+impl Serialize for Commitment {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Commitment {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        i64::deserialize(deserializer)?.to_string().parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Create a Pedersen commitment: C = v*G + r*H (mod p).
+/// - value: the secret amount (v)
+/// - blinding: random number (r) that hides the value
+///
+/// Anyone can compute C, but without knowing r they cannot find v. The products are
+/// taken in i128, so any v and r commit correctly rather than overflowing i64 first.
+///
+/// Lenient: a negative v or one past p wraps mod p, which is what the attack demos
+/// need. Building transactions goes through `pedersen_commit_checked` instead.
+pub fn pedersen_commit(value: Scalar, blinding: Blinding) -> Commitment {
+    let Params { g, h, .. } = params::active();
+    Commitment(mod_reduce(value.0 as i128 * g as i128 + blinding.0 as i128 * h as i128))
+}
+
+/// `pedersen_commit`, for v in [0, p) only: a value outside that would open to a
+/// different amount than the one asked for (p - 5 is -5 mod p). Any blinding is
+/// fine and is reduced mod p first, since r*H is the same point either way: sums of
+/// blindings, like a change output's input total minus the rest, often pass p.
+pub fn pedersen_commit_checked(value: Scalar, blinding: Blinding) -> Result<Commitment, CtError> {
+    let modulus = params::active().modulus;
+    if value.0 < 0 {
+        return Err(CtError::NegativeValue(value.0));
+    }
+    if value.0 >= modulus {
+        return Err(CtError::OutOfInterval { value: value.0, min: 0, max: modulus - 1 });
+    }
+    Ok(pedersen_commit(value, Blinding(mod_reduce(blinding.0 as i128))))
+}
+
+/// The fee as a commitment: fee*G with zero blinding, so anyone can recompute it
+/// from the fee published in the clear.
+pub fn fee_commitment(fee: u64) -> Commitment {
+    let params = params::active();
+    Commitment(mod_mul((fee % params.modulus as u64) as i64, params.g))
+}
+
+/// Sum of commitments (mod p).
+pub fn sum_commitments(commitments: &[Commitment]) -> Commitment {
+    commitments.iter().fold(Commitment(0), |acc, &c| acc + c)
+}
+
+/// Balance check using only commitments: sum(inputs) = sum(outputs).
+/// Holds exactly when the hidden values AND the blindings balance.
+pub fn verify_balance(inputs: &[Commitment], outputs: &[Commitment]) -> Result<(), CtError> {
+    let sum_inputs = sum_commitments(inputs);
+    let sum_outputs = sum_commitments(outputs);
+    if sum_inputs == sum_outputs {
+        Ok(())
+    } else {
+        Err(CtError::BalanceMismatch { inputs: sum_inputs, outputs: sum_outputs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Values and blindings at the edges of i64 and of the default p.
+    const EXTREMES: [i64; 9] = [i64::MIN, i64::MIN + 1, -MODULUS, -1, 0, 1, MODULUS - 1, MODULUS, i64::MAX];
+
+    /// v*G + r*H mod p the slow way, all in i128.
+    fn reference(value: i64, blinding: i64) -> i64 {
+        (value as i128 * G as i128 + blinding as i128 * H as i128).rem_euclid(MODULUS as i128) as i64
+    }
+
+    fn reduced(blinding: Blinding) -> i128 {
+        (blinding.0 as i128).rem_euclid(MODULUS as i128)
+    }
+
+    #[test]
+    fn commit_at_i64_extremes_matches_i128_reference() {
+        for value in EXTREMES {
+            for blinding in EXTREMES {
+                let commitment = pedersen_commit(Scalar::new(value), Blinding::new(blinding));
+                assert_eq!(commitment.as_i64(), reference(value, blinding), "v = {}, r = {}", value, blinding);
+            }
+        }
+    }
+
+    #[test]
+    fn blinding_sum_and_difference_past_i64_reduce_mod_p() {
+        for a in EXTREMES {
+            for b in EXTREMES {
+                let (sum, difference) = (Blinding::new(a) + Blinding::new(b), Blinding::new(a) - Blinding::new(b));
+                let p = MODULUS as i128;
+                assert_eq!(reduced(sum), (a as i128 + b as i128).rem_euclid(p), "{} + {}", a, b);
+                assert_eq!(reduced(difference), (a as i128 - b as i128).rem_euclid(p), "{} - {}", a, b);
+            }
+        }
+        // Exact while the result still fits.
+        assert_eq!(Blinding::new(i64::MAX) + Blinding::new(-1), Blinding::new(i64::MAX - 1));
+        assert_eq!(Blinding::new(i64::MIN) - Blinding::new(-1), Blinding::new(i64::MIN + 1));
+    }
+
+    #[test]
+    fn homomorphic_at_i64_extremes() {
+        let values = [(i64::MAX, i64::MIN), (i64::MAX, -1), (i64::MIN, 1), (MODULUS - 1, 1), (0, i64::MAX)];
+        for (v1, v2) in values {
+            for r1 in EXTREMES {
+                for r2 in EXTREMES {
+                    let c1 = pedersen_commit(Scalar::new(v1), Blinding::new(r1));
+                    let c2 = pedersen_commit(Scalar::new(v2), Blinding::new(r2));
+                    let sum = pedersen_commit(Scalar::new(v1 + v2), Blinding::new(r1) + Blinding::new(r2));
+                    assert_eq!(c1 + c2, sum, "({}, {}) + ({}, {})", v1, r1, v2, r2);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Spending conditions: a tiny language for what an input must show to spend a
+//! locked output, checked by the ledger when the output is spent.
+//!
+//! - `Signature(K)`: a signature by the key K = k*H.
+//! - `Preimage(h)`: a preimage s with SHA-256(s) = h.
+//! - `After(T)`: the spending block is at height T or above.
+//! - `And(..)`: every condition in the list; `Or(..)`: one of them.
+//!
+//! The spending input carries a `Witness` shaped like the condition, naming for each
+//! `Or` the branch it meets. Every signature covers the locked commitment and the
+//! spending transaction's kernel excess (`message`), so it cannot be moved to another
+//! transaction, and every key in an `And` signs the same message. A hash-time lock
+//! (see `swap::HashLock`) is or(and(hash(h), sig(B)), and(after(T), sig(A))); a
+//! 2-of-3 escrow is or(and(sig(A), sig(B)), and(sig(A), sig(C)), and(sig(B), sig(C))).
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::commitment::{Blinding, Commitment};
+use crate::error::CtError;
+use crate::kernel::{schnorr_sign, schnorr_verify, Kernel, Signature};
+use crate::rng::ToyRng;
+use crate::wire::to_hex;
+
+pub const PREIMAGE_LEN: usize = 32;
+
+/// How deeply `And`s and `Or`s may nest in a decoded condition or witness.
+pub const MAX_DEPTH: usize = 16;
+
+/// SHA-256(s): what a `Preimage` condition commits to.
+pub fn hash_preimage(preimage: &[u8; PREIMAGE_LEN]) -> [u8; 32] {
+    Sha256::digest(preimage).into()
+}
+
+/// The condition on a locked output.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// A signature by this key.
+    Signature(Commitment),
+    /// A preimage of this hash.
+    #[serde(rename = "hash", with = "crate::wire::hex")]
+    Preimage([u8; 32]),
+    /// A spending block at this height or above.
+    After(u64),
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+}
+
+/// What the input spending a locked output shows, one part per part of the condition.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Witness {
+    Signature(Signature),
+    #[serde(with = "crate::wire::hex")]
+    Preimage([u8; PREIMAGE_LEN]),
+    /// Nothing to show: the height is the spending block's.
+    After,
+    /// A witness for every condition, in order.
+    And(Vec<Witness>),
+    /// The index of the condition met, and its witness.
+    Or(u32, Box<Witness>),
+}
+
+/// What a `Signature` witness signs. It starts with a commitment, so it is never a
+/// kernel message (`kernel_message`), which starts with a fee.
+pub fn message(commitment: Commitment, kernel: &Kernel) -> [i64; 2] {
+    [commitment.as_i64(), kernel.excess.as_i64()]
+}
+
+/// The witness for `Signature(k*H)` on `commitment`, spent by a transaction with `kernel`.
+pub fn sign(secret: Blinding, commitment: Commitment, kernel: &Kernel, rng: &mut ToyRng) -> Witness {
+    Witness::Signature(schnorr_sign(secret, &message(commitment, kernel), rng))
+}
+
+impl Condition {
+    /// Can an input with `witness` spend the locked output `commitment`, in a
+    /// transaction with `kernel` that goes into the block at `height`?
+    pub fn check(
+        &self,
+        commitment: Commitment,
+        witness: Option<&Witness>,
+        kernel: Option<&Kernel>,
+        height: u64,
+    ) -> Result<(), CtError> {
+        let locked = |reason: String| CtError::Locked(format!("output {}: {}", commitment, reason));
+        let witness = witness.ok_or_else(|| locked("spending it needs a witness".to_string()))?;
+        let message = kernel.map(|kernel| message(commitment, kernel));
+        self.meets(witness, message.as_ref(), height).map_err(locked)
+    }
+
+    fn meets(&self, witness: &Witness, message: Option<&[i64; 2]>, height: u64) -> Result<(), String> {
+        match (self, witness) {
+            (Condition::Signature(key), Witness::Signature(signature)) => {
+                let message = message.ok_or_else(|| "a signature needs a kernel to sign".to_string())?;
+                if !schnorr_verify(*key, message, signature) {
+                    return Err(format!("the signature by {} fails", key));
+                }
+            }
+            (Condition::Preimage(hash), Witness::Preimage(preimage)) => {
+                if hash_preimage(preimage) != *hash {
+                    return Err(format!("the preimage does not hash to {}", to_hex(hash)));
+                }
+            }
+            (&Condition::After(lock_height), Witness::After) => {
+                if height < lock_height {
+                    return Err(format!("not before height {} (this is {})", lock_height, height));
+                }
+            }
+            (Condition::And(conditions), Witness::And(witnesses)) if conditions.len() == witnesses.len() => {
+                for (condition, witness) in conditions.iter().zip(witnesses) {
+                    condition.meets(witness, message, height)?;
+                }
+            }
+            (Condition::Or(conditions), Witness::Or(branch, witness)) => {
+                let condition = conditions
+                    .get(*branch as usize)
+                    .ok_or_else(|| format!("there is no branch {} of {}", branch, self))?;
+                condition.meets(witness, message, height).map_err(|reason| format!("branch {}: {}", branch, reason))?;
+            }
+            _ => return Err(format!("the witness does not fit {}", self)),
+        }
+        Ok(())
+    }
+}
+
+impl Witness {
+    /// Every preimage the witness shows, e.g. to read a swap's s off the chain.
+    pub fn preimages(&self) -> Vec<[u8; PREIMAGE_LEN]> {
+        match self {
+            Witness::Preimage(preimage) => vec![*preimage],
+            Witness::And(witnesses) => witnesses.iter().flat_map(Witness::preimages).collect(),
+            Witness::Or(_, witness) => witness.preimages(),
+            Witness::Signature(_) | Witness::After => Vec::new(),
+        }
+    }
+}
+
+/// Written as in the module docs: sig(K), hash(<hex>), after(T), and(..), or(..).
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>, name: &str, conditions: &[Condition]| {
+            let parts: Vec<String> = conditions.iter().map(Condition::to_string).collect();
+            write!(f, "{}({})", name, parts.join(", "))
+        };
+        match self {
+            Condition::Signature(key) => write!(f, "sig({})", key),
+            Condition::Preimage(hash) => write!(f, "hash({})", to_hex(hash)),
+            Condition::After(height) => write!(f, "after({})", height),
+            Condition::And(conditions) => list(f, "and", conditions),
+            Condition::Or(conditions) => list(f, "or", conditions),
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! `ct.toml`: parameters and CLI defaults, read from the working directory (or
+//! `--config <path>`). Every key is optional; CLI flags override the file.
+//!
+//! ```toml
+//! [params]
+//! modulus = 2305843009213693951   # p, at most 2^61 - 1
+//! g = 3
+//! h = 7
+//! range_bits = 8                  # range proofs cover [0, 2^range_bits)
+//! subsidy = 50                    # the most a coinbase may mint
+//!
+//! [bigfield]                      # the group of `ct commit --backend bigfield`
+//! modulus = "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed"
+//! g = "0x79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+//! h = "0x483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"
+//!
+//! [cli]
+//! backend = "toy"                 # group used by `ct commit`: toy | bigfield | curve | monero | secp256k1
+//! output = "text"                 # text | json
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use clap::{Args, ValueEnum};
+use num_bigint::BigUint;
+use pedersen_commitment_tx::big_field::BigField;
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::params::Params;
+use toml::{Table, Value};
+
+/// File read when `--config` is not given; it is fine for it not to exist.
+const DEFAULT_PATH: &str = "ct.toml";
+
+/// Which group `ct commit` computes C = v*G + r*H in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// Integers mod p with i64 arithmetic (the one everything else uses)
+    Toy,
+    /// Integers mod a 256-bit p (2^255 - 19 unless set) with arbitrary-precision arithmetic
+    Bigfield,
+    /// Points on the toy curve y^2 = x^3 + 7 over F_211
+    Curve,
+    /// Monero's commitments: points on Ed25519, with the value on H and the blinding on G
+    Monero,
+    /// libsecp256k1-zkp's commitments (Grin, Elements): points on secp256k1, blinding on G
+    Secp256k1,
+}
+
+/// Flags that override `ct.toml`; accepted before or after the subcommand.
+#[derive(Args)]
+pub struct ConfigArgs {
+    /// Read settings from this file instead of ./ct.toml
+    #[arg(long, global = true)]
+    config: Option<String>,
+    /// Modulus p of the toy group
+    #[arg(long, global = true)]
+    modulus: Option<i64>,
+    /// Generator G (value dimension)
+    #[arg(long, global = true)]
+    g: Option<i64>,
+    /// Generator H (blinding dimension)
+    #[arg(long, global = true)]
+    h: Option<i64>,
+    /// Bits covered by range proofs
+    #[arg(long, global = true)]
+    range_bits: Option<u32>,
+    /// Most a coinbase transaction may mint
+    #[arg(long, global = true)]
+    subsidy: Option<u64>,
+    /// Group used by `ct commit`
+    #[arg(long, global = true, value_enum)]
+    backend: Option<Backend>,
+    /// Modulus of the bigfield backend: odd, between 2^254 and 2^256 (decimal or 0x-hex)
+    #[arg(long, global = true)]
+    bigfield_modulus: Option<String>,
+    /// Generator G of the bigfield backend (decimal or 0x-hex)
+    #[arg(long, global = true)]
+    bigfield_g: Option<String>,
+    /// Generator H of the bigfield backend (decimal or 0x-hex)
+    #[arg(long, global = true)]
+    bigfield_h: Option<String>,
+}
+
+pub struct Config {
+    pub params: Params,
+    pub backend: Backend,
+    pub big_field: BigField,
+    pub json: bool,
+}
+
+impl Config {
+    /// Defaults, then `ct.toml`, then the flags in `args` (and `--json`).
+    pub fn load(args: &ConfigArgs, json: bool) -> Result<Config, CtError> {
+        let mut config = Config {
+            params: Params::DEFAULT,
+            backend: Backend::Toy,
+            big_field: BigField::realistic_256(),
+            json: false,
+        };
+        let path = args.config.as_deref().unwrap_or(DEFAULT_PATH);
+        if args.config.is_some() || Path::new(path).exists() {
+            let text = fs::read_to_string(path)
+                .map_err(|e| CtError::MalformedInput(format!("cannot read {}: {}", path, e)))?;
+            config.apply_file(&text).map_err(|e| match e {
+                CtError::MalformedInput(reason) => CtError::MalformedInput(format!("{}: {}", path, reason)),
+                other => other,
+            })?;
+        }
+
+        let params = &mut config.params;
+        params.modulus = args.modulus.unwrap_or(params.modulus);
+        params.g = args.g.unwrap_or(params.g);
+        params.h = args.h.unwrap_or(params.h);
+        params.range_bits = args.range_bits.unwrap_or(params.range_bits);
+        params.subsidy = args.subsidy.unwrap_or(params.subsidy);
+        config.backend = args.backend.unwrap_or(config.backend);
+        let big_field = &mut config.big_field;
+        for (flag, arg, field) in [
+            ("--bigfield-modulus", &args.bigfield_modulus, &mut big_field.modulus),
+            ("--bigfield-g", &args.bigfield_g, &mut big_field.g),
+            ("--bigfield-h", &args.bigfield_h, &mut big_field.h),
+        ] {
+            if let Some(text) = arg {
+                *field = big_integer(flag, text)?;
+            }
+        }
+        config.json |= json;
+        config.params.validate()?;
+        config.big_field.validate()?;
+        Ok(config)
+    }
+
+    fn apply_file(&mut self, text: &str) -> Result<(), CtError> {
+        let table: Table = text.parse().map_err(|e| CtError::MalformedInput(format!("not valid TOML: {}", e)))?;
+        for (section, value) in &table {
+            let Value::Table(entries) = value else {
+                return Err(CtError::MalformedInput(format!("'{}' must be a [section]", section)));
+            };
+            for (key, value) in entries {
+                match (section.as_str(), key.as_str()) {
+                    ("params", "modulus") => self.params.modulus = integer(section, key, value)?,
+                    ("params", "g") => self.params.g = integer(section, key, value)?,
+                    ("params", "h") => self.params.h = integer(section, key, value)?,
+                    ("params", "range_bits") => {
+                        self.params.range_bits = integer(section, key, value)?
+                            .try_into()
+                            .map_err(|_| CtError::MalformedInput("params.range_bits is out of range".to_string()))?
+                    }
+                    ("params", "subsidy") => {
+                        self.params.subsidy = integer(section, key, value)?
+                            .try_into()
+                            .map_err(|_| CtError::MalformedInput("params.subsidy must not be negative".to_string()))?
+                    }
+                    ("bigfield", "modulus") => self.big_field.modulus = big_integer_value(section, key, value)?,
+                    ("bigfield", "g") => self.big_field.g = big_integer_value(section, key, value)?,
+                    ("bigfield", "h") => self.big_field.h = big_integer_value(section, key, value)?,
+                    ("cli", "backend") => {
+                        let message = "cli.backend must be toy, bigfield, curve, monero or secp256k1";
+                        self.backend = Backend::from_str(string(section, key, value)?, true)
+                            .map_err(|_| CtError::MalformedInput(message.to_string()))?
+                    }
+                    ("cli", "output") => {
+                        self.json = match string(section, key, value)? {
+                            "text" => false,
+                            "json" => true,
+                            _ => return Err(CtError::MalformedInput("cli.output must be text or json".to_string())),
+                        }
+                    }
+                    _ => return Err(CtError::MalformedInput(format!("unknown setting {}.{}", section, key))),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn integer(section: &str, key: &str, value: &Value) -> Result<i64, CtError> {
+    value
+        .as_integer()
+        .ok_or_else(|| CtError::MalformedInput(format!("{}.{} must be an integer", section, key)))
+}
+
+/// A 256-bit number in a TOML string (decimal or 0x-hex); small ones may be plain integers.
+fn big_integer_value(section: &str, key: &str, value: &Value) -> Result<BigUint, CtError> {
+    let name = format!("{}.{}", section, key);
+    match value {
+        Value::Integer(n) => {
+            u64::try_from(*n).map(BigUint::from).map_err(|_| CtError::MalformedInput(format!("{} is negative", name)))
+        }
+        _ => big_integer(&name, string(section, key, value)?),
+    }
+}
+
+fn big_integer(name: &str, text: &str) -> Result<BigUint, CtError> {
+    let (digits, radix) = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => (hex, 16),
+        None => (text, 10),
+    };
+    BigUint::parse_bytes(digits.as_bytes(), radix)
+        .ok_or_else(|| CtError::MalformedInput(format!("{} must be a decimal or 0x-hex integer, not {:?}", name, text)))
+}
+
+fn string<'a>(section: &str, key: &str, value: &'a Value) -> Result<&'a str, CtError> {
+    value
+        .as_str()
+        .ok_or_else(|| CtError::MalformedInput(format!("{}.{} must be a string", section, key)))
+}
@@ -0,0 +1,83 @@
+//! Mimblewimble cut-through. Transactions merge into one by concatenating their
+//! inputs, outputs and kernels and adding their fees, mints, burns and offsets. If one
+//! transaction spends an output of another, that commitment appears once as an
+//! output and once as an input; it cancels in the balance, so both can be dropped.
+//! What is left still verifies: the kernels prove no value was created, whoever
+//! signed them, and the removed output's amount and blinding are never needed.
+
+use crate::commitment::{fee_commitment, pedersen_commit, verify_balance, Blinding, Scalar};
+use crate::error::CtError;
+use crate::kernel::Kernel;
+use crate::range_proof::range_proof_verify;
+use crate::transaction::{Transaction, TxEntry};
+
+/// Several transactions merged into one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Aggregate {
+    pub inputs: Vec<TxEntry>,
+    pub outputs: Vec<TxEntry>,
+    /// Every kernel with the fee it signs.
+    pub kernels: Vec<(Kernel, u64)>,
+    pub fee: u64,
+    pub minted: u64,
+    pub burned: u64,
+    pub offset: Blinding,
+}
+
+impl Aggregate {
+    /// Concatenate `transactions` without cutting anything.
+    pub fn merge(transactions: &[Transaction]) -> Self {
+        Aggregate {
+            inputs: transactions.iter().flat_map(|tx| tx.inputs.clone()).collect(),
+            outputs: transactions.iter().flat_map(|tx| tx.outputs.clone()).collect(),
+            kernels: transactions.iter().filter_map(|tx| tx.kernel.map(|kernel| (kernel, tx.fee))).collect(),
+            fee: transactions.iter().map(|tx| tx.fee).sum(),
+            minted: transactions.iter().map(|tx| tx.minted).sum(),
+            burned: transactions.iter().map(|tx| tx.burned).sum(),
+            offset: transactions.iter().fold(Blinding::default(), |sum, tx| sum + tx.offset),
+        }
+    }
+
+    /// Drop every output that an input of the aggregate spends, together with that
+    /// input. Returns how many pairs were removed.
+    pub fn cut_through(&mut self) -> usize {
+        let mut cut = 0;
+        let mut i = 0;
+        while i < self.outputs.len() {
+            match self.inputs.iter().position(|input| input.commitment == self.outputs[i].commitment) {
+                Some(j) => {
+                    self.inputs.remove(j);
+                    self.outputs.remove(i);
+                    cut += 1;
+                }
+                None => i += 1,
+            }
+        }
+        cut
+    }
+
+    /// sum(inputs) + minted*G + sum(E) + offset*H = sum(outputs) + (fee + burned)*G, every kernel
+    /// signs its fee, and every output carries a valid range proof. Outputs covered
+    /// only by an aggregate range proof fail here: cut-through may drop some of the
+    /// outputs that proof is about, so it is not carried over.
+    pub fn verify(&self) -> Result<(), CtError> {
+        let mut inputs: Vec<_> = self.inputs.iter().map(|entry| entry.commitment).collect();
+        inputs.push(fee_commitment(self.minted));
+        inputs.extend(self.kernels.iter().map(|(kernel, _)| kernel.excess));
+        inputs.push(pedersen_commit(Scalar::new(0), self.offset));
+        let mut outputs: Vec<_> = self.outputs.iter().map(|entry| entry.commitment).collect();
+        outputs.push(fee_commitment(self.fee));
+        outputs.push(fee_commitment(self.burned));
+        verify_balance(&inputs, &outputs)?;
+        for (kernel, fee) in &self.kernels {
+            kernel.verify(*fee)?;
+        }
+        for output in &self.outputs {
+            match &output.proof {
+                Some(proof) => range_proof_verify(output.commitment, proof)?,
+                None => return Err(CtError::InvalidProof("missing range proof".to_string())),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1867 @@
+//! The fixed walkthrough (`ct demo`): Alice pays Bob with confidential amounts,
+//! then an attacker tries to inflate the supply with negative change.
+//!
+//! All numbers are computed up front into a `Scenario`; the walkthrough is a list
+//! of discrete steps that only narrate it, so `--interactive` can stop before any
+//! step and ask the user to predict its outcome.
+
+use std::time::{Duration, Instant};
+
+use num_bigint::{BigInt, BigUint};
+use pedersen_commitment_tx::batch::{verify_batch, BatchReport};
+use pedersen_commitment_tx::big_field::BigField;
+use pedersen_commitment_tx::block::{Block, BlockHeader};
+use pedersen_commitment_tx::builder::TransactionBuilder;
+use pedersen_commitment_tx::chain::Chain;
+use pedersen_commitment_tx::commitment::{
+    fee_commitment, pedersen_commit, sum_commitments, Blinding, Commitment, Opening, Scalar,
+};
+use pedersen_commitment_tx::cut_through::Aggregate;
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::horizon::Compacted;
+use pedersen_commitment_tx::kernel::{schnorr_sign, split_excess, Kernel};
+use pedersen_commitment_tx::ledger::Ledger;
+use pedersen_commitment_tx::light_client::LightClient;
+use pedersen_commitment_tx::mempool::Mempool;
+use pedersen_commitment_tx::mmr::MmrProof;
+use pedersen_commitment_tx::opening_proof::{
+    prove_equal, prove_membership, prove_opening, prove_value, verify_equal, verify_membership, verify_opening,
+    verify_value, MembershipProof, OpeningProof,
+};
+use pedersen_commitment_tx::range_proof::{
+    bit_proof_verify, interval_proof_create, interval_proof_verify, prove_bit, range_proof_create,
+    range_proof_create_unchecked, range_proof_recompose, verify_bit, BitProof, IntervalProof, RangeProof,
+};
+use pedersen_commitment_tx::params;
+use pedersen_commitment_tx::rng::ToyRng;
+use pedersen_commitment_tx::toy_curve::{self, Point};
+use pedersen_commitment_tx::transaction::{OutPoint, Transaction, TxEntry};
+use pedersen_commitment_tx::wire::{to_hex, Encode};
+use serde_json::json;
+
+use crate::prompt::{ask_yes_no, pause, yes_no};
+use crate::report::{outcome, proof_json, result_json, Report};
+use crate::slate_file::to_json;
+
+/// Largest payment allowed in step 4s.
+const CAP: i64 = 6;
+/// The only payment amounts allowed in step 4t.
+const DENOMINATIONS: [i64; 4] = [1, 5, 10, 50];
+
+/// Every value the walkthrough shows: the honest transaction, the attack,
+/// and the same transaction in BigField mode and on the toy curve.
+struct Scenario {
+    value_input: Scalar,
+    r_input: Blinding,
+    c_input: Commitment,
+    pi_input: RangeProof,
+    value_to_bob: Scalar,
+    value_change: Scalar,
+    r_bob: Blinding,
+    r_change: Blinding,
+    c_bob: Commitment,
+    c_change: Commitment,
+    pi_bob: RangeProof,
+    pi_change: RangeProof,
+    tx: Transaction,
+    sum_outputs: Commitment,
+    balance: Result<(), CtError>,
+    rp_input: Result<(), CtError>,
+    rp_bob: Result<(), CtError>,
+    rp_change: Result<(), CtError>,
+    verdict: Result<(), CtError>,
+
+    fee_tx: Transaction,
+    value_change_fee: Scalar,
+    unpaid_fee_balance: Result<(), CtError>,
+    fee_balance: Result<(), CtError>,
+    fee_verdict: Result<(), CtError>,
+
+    r_bob_own: Blinding,
+    r_change_own: Blinding,
+    excess_blinding: Blinding,
+    kernel_tx: Transaction,
+    kernel_free_balance: Result<(), CtError>,
+    kernel_verdict: Result<(), CtError>,
+    forged_kernel_balance: Result<(), CtError>,
+
+    offset_tx: Transaction,
+    offset_verdict: Result<(), CtError>,
+
+    coinbase_tx: Transaction,
+    coinbase_verdict: Result<(), CtError>,
+    overmint: u64,
+    overmint_balance: Result<(), CtError>,
+
+    built_tx: Transaction,
+    built_openings: Vec<Opening>,
+    built_verdict: Result<(), CtError>,
+
+    utxos_minted: Vec<(OutPoint, Commitment)>,
+    utxos_paid: Vec<(OutPoint, Commitment)>,
+    apply_coinbase: Result<(), CtError>,
+    apply_tx: Result<(), CtError>,
+    double_spend: Result<(), CtError>,
+
+    genesis: Block,
+    block1: Block,
+    apply_genesis: Result<(), CtError>,
+    apply_block1: Result<(), CtError>,
+    conflicting_block: Result<(), CtError>,
+    bob_inclusion: MmrProof,
+    bob_included: bool,
+    mallory_included: bool,
+
+    pool_added: Vec<Result<(), CtError>>,
+    template: Block,
+    apply_template: Result<(), CtError>,
+    pool_left: usize,
+
+    light_headers: usize,
+    light_bytes: usize,
+    chain_bytes: usize,
+    light_bob: bool,
+    light_mallory: bool,
+    light_tampered: Result<(), CtError>,
+
+    main_height: u64,
+    side_reorg: usize,
+    reorg_height: u64,
+    disconnected: Vec<Block>,
+    returned: usize,
+    bob_unspent_after_reorg: bool,
+
+    compacted: Compacted,
+    compacted_verdict: Result<(), CtError>,
+    tampered_history: Result<(), CtError>,
+
+    opening_proof: OpeningProof,
+    opening_verdict: Result<(), CtError>,
+    opening_replayed: Result<(), CtError>,
+    c_bob_own: Commitment,
+    equality_verdict: Result<(), CtError>,
+    equality_swapped: Result<(), CtError>,
+    equality_refusal: Option<CtError>,
+    value_verdict: Result<(), CtError>,
+    value_wrong: Result<(), CtError>,
+    bit_proofs: Vec<BitProof>,
+    bit_verdicts: Vec<Result<(), CtError>>,
+    bit_swapped: Result<(), CtError>,
+    bit_refusal: Option<CtError>,
+    cap_proof: IntervalProof,
+    cap_verdict: Result<(), CtError>,
+    cap_lowered: Result<(), CtError>,
+    cap_refusal: Option<CtError>,
+    membership_proof: MembershipProof,
+    membership_verdict: Result<(), CtError>,
+    membership_other_set: Result<(), CtError>,
+    membership_refusal: Option<CtError>,
+    aggregated_tx: Transaction,
+    aggregated_verdict: Result<(), CtError>,
+    aggregated_reordered: Result<(), CtError>,
+    batch: Result<BatchReport, CtError>,
+    batch_with_attack: Result<BatchReport, CtError>,
+
+    merged: Aggregate,
+    merged_verdict: Result<(), CtError>,
+    cut: usize,
+    aggregate: Aggregate,
+    aggregate_verdict: Result<(), CtError>,
+
+    value_change_attack: Scalar,
+    c_input_attack: Commitment,
+    c_bob_attack: Commitment,
+    c_change_attack: Commitment,
+    sum_outputs_attack: Commitment,
+    attack_balance: Result<(), CtError>,
+    honest_refusal: Option<CtError>,
+    pi_change_attack: RangeProof,
+    rp_change_attack: Result<(), CtError>,
+    attack_verdict: Result<(), CtError>,
+
+    field: BigField,
+    big_c_input: BigUint,
+    big_sum_outputs: BigUint,
+
+    point_input: Point,
+    point_bob: Point,
+    point_change: Point,
+    point_sum: Point,
+    /// CURVE_COMMITS commitments by double-and-add, then from the fixed-base tables.
+    curve_commit_times: [Duration; 2],
+    tables_agree: bool,
+}
+
+/// How many toy-curve commitments are timed each way.
+const CURVE_COMMITS: i64 = 1000;
+
+impl Scenario {
+    fn build() -> Self {
+        // Fixed seed so every run prints the same proofs.
+        let mut rng = ToyRng::new(2024);
+
+        // Alice has 10 units. She created a commitment earlier using r_input. Only she knows (10, r_input).
+        let value_input = Scalar::new(10);
+        let r_input = Blinding::new(12345); // Alice's secret blinding factor for the input
+        let c_input = pedersen_commit(value_input, r_input);
+        let pi_input = range_proof_create(value_input, r_input, &mut rng).expect("10 is in range");
+
+        // Alice sends 5 to Bob, keeps 5 as change. She picks r_bob and r_change so that r_input = r_bob + r_change.
+        let value_to_bob = Scalar::new(5);
+        let value_change = Scalar::new(5);
+        let r_bob = Blinding::new(11111);
+        let r_change = r_input - r_bob; // so r_bob + r_change = r_input
+        let c_bob = pedersen_commit(value_to_bob, r_bob);
+        let c_change = pedersen_commit(value_change, r_change);
+        let pi_bob = range_proof_create(value_to_bob, r_bob, &mut rng).expect("5 is in range");
+        let pi_change = range_proof_create(value_change, r_change, &mut rng).expect("5 is in range");
+
+        let tx = Transaction::new(
+            vec![TxEntry::new(c_input, pi_input.clone())],
+            vec![TxEntry::new(c_bob, pi_bob.clone()), TxEntry::new(c_change, pi_change.clone())],
+        );
+
+        // Homomorphic: C_input should equal C_bob + C_change (mod p).
+        let sum_outputs = c_bob + c_change;
+        let balance = tx.verify_balance();
+        let [rp_input, rp_bob, rp_change] = range_proof_outcomes(&tx);
+        let verdict = tx.verify();
+
+        // The attacker: input 10, sends 15 to Bob, "change" -5.
+        let value_input_attack = Scalar::new(10);
+        let r_input_attack = Blinding::new(99999);
+        let value_to_bob_attack = Scalar::new(15);  // More than input!
+        let value_change_attack = Scalar::new(-5);   // Negative "change" = creating value
+
+        let r_bob_attack = Blinding::random(&mut rng);
+        let r_change_attack = r_input_attack - r_bob_attack;
+
+        let c_input_attack = pedersen_commit(value_input_attack, r_input_attack);
+        let c_bob_attack = pedersen_commit(value_to_bob_attack, r_bob_attack);
+        let c_change_attack = pedersen_commit(value_change_attack, r_change_attack);
+        let sum_outputs_attack = c_bob_attack + c_change_attack;
+
+        // An honest prover refuses; the attacker runs the proof algorithm anyway.
+        let honest_refusal = range_proof_create(value_change_attack, r_change_attack, &mut rng).err();
+        let pi_change_attack = range_proof_create_unchecked(value_change_attack, r_change_attack, &mut rng);
+        let pi_bob_attack = range_proof_create(value_to_bob_attack, r_bob_attack, &mut rng).expect("15 is in range");
+        let attack_tx = Transaction::new(
+            vec![TxEntry::input(c_input_attack)],
+            vec![TxEntry::new(c_bob_attack, pi_bob_attack), TxEntry::new(c_change_attack, pi_change_attack.clone())],
+        );
+        let attack_balance = attack_tx.verify_balance();
+        let [_, rp_change_attack] = range_proof_outcomes(&attack_tx);
+        let attack_verdict = attack_tx.verify();
+
+        // Same payment with a public fee of 1: the change shrinks to 4, the blindings stay.
+        let value_change_fee = Scalar::new(4);
+        let pi_change_fee = range_proof_create(value_change_fee, r_change, &mut rng).expect("4 is in range");
+        let fee_outputs = vec![
+            TxEntry::new(c_bob, pi_bob.clone()),
+            TxEntry::new(pedersen_commit(value_change_fee, r_change), pi_change_fee),
+        ];
+        let unpaid_fee_balance = Transaction::new(tx.inputs.clone(), fee_outputs.clone()).verify_balance();
+        let fee_tx = Transaction::new(tx.inputs.clone(), fee_outputs).with_fee(1);
+        let fee_balance = fee_tx.verify_balance();
+        let fee_verdict = fee_tx.verify();
+
+        // Mimblewimble style: every owner picks their own blinding, and the sender signs
+        // for the leftover x = r_bob + r_change - r_input with a kernel.
+        let r_bob_own = Blinding::random(&mut rng);
+        let r_change_own = Blinding::random(&mut rng);
+        let own_outputs = vec![
+            TxEntry::new(
+                pedersen_commit(value_to_bob, r_bob_own),
+                range_proof_create(value_to_bob, r_bob_own, &mut rng).expect("5 is in range"),
+            ),
+            TxEntry::new(
+                pedersen_commit(value_change, r_change_own),
+                range_proof_create(value_change, r_change_own, &mut rng).expect("5 is in range"),
+            ),
+        ];
+        let excess_blinding = r_bob_own + r_change_own - r_input;
+        let kernel_free_balance = Transaction::new(tx.inputs.clone(), own_outputs.clone()).verify_balance();
+        let kernel_tx =
+            Transaction::new(tx.inputs.clone(), own_outputs).with_kernel(Kernel::create(excess_blinding, 0, &mut rng));
+        let kernel_verdict = kernel_tx.verify();
+
+        // Mallory moves Alice's input to herself. She knows her own blinding, not r_input.
+        let r_mallory = Blinding::random(&mut rng);
+        let c_mallory = pedersen_commit(value_input, r_mallory);
+        let forged_kernel =
+            Kernel { excess: c_mallory - c_input, signature: schnorr_sign(r_mallory, &[0], &mut rng), lock_height: 0 };
+        let forged_kernel_balance = Transaction::new(
+            tx.inputs.clone(),
+            vec![TxEntry::input(c_mallory)],
+        )
+        .with_kernel(forged_kernel)
+        .verify_balance();
+
+        // Same outputs, but x = offset + x' and only x' goes into the kernel.
+        let (offset, kernel_blinding) = split_excess(excess_blinding, &mut rng);
+        let offset_tx = Transaction::new(tx.inputs.clone(), kernel_tx.outputs.clone())
+            .with_kernel(Kernel::create(kernel_blinding, 0, &mut rng))
+            .with_offset(offset);
+        let offset_verdict = offset_tx.verify();
+
+        // Where Alice's input came from: a coinbase minting 10 straight into C_input.
+        let coinbase_tx = Transaction::coinbase(10, vec![tx.inputs[0].clone()], Kernel::create(r_input, 0, &mut rng));
+        let coinbase_verdict = coinbase_tx.verify();
+        let overmint = params::active().subsidy + 1;
+        let c_overmint = pedersen_commit(Scalar::new(overmint as i64), r_input);
+        let overmint_balance = Transaction::coinbase(
+            overmint,
+            vec![TxEntry::input(c_overmint)],
+            Kernel::create(r_input, 0, &mut rng),
+        )
+        .verify_balance();
+
+        // The builder generalizes r_change = r_input - r_bob to any number of outputs.
+        let (built_tx, built_openings) = TransactionBuilder::new()
+            .add_input(c_input, Opening::new(value_input, r_input))
+            .add_output(Scalar::new(3))
+            .add_output(Scalar::new(2))
+            .add_output(Scalar::new(4))
+            .fee(1)
+            .build(&mut rng)
+            .expect("10 = 3 + 2 + 4 + 1");
+        let built_verdict = built_tx.verify();
+
+        // The ledger: the coinbase creates C_input, Alice's payment spends it.
+        let mut ledger = Ledger::new();
+        let snapshot = |ledger: &Ledger| ledger.utxos().map(|(&o, &c)| (o, c)).collect::<Vec<_>>();
+        let apply_coinbase = ledger.apply(&coinbase_tx).map(|_| ());
+        let utxos_minted = snapshot(&ledger);
+        let apply_tx = ledger.apply(&tx).map(|_| ());
+        let utxos_paid = snapshot(&ledger);
+        // Alice also broadcasts the fee version of her payment: it spends C_input again.
+        let double_spend = ledger.apply(&fee_tx).map(|_| ());
+
+        // The same history as a chain: genesis holds the coinbase, block 1 the payment.
+        let mut chain = Ledger::new();
+        let genesis = Block::new(&chain, vec![coinbase_tx.clone()]);
+        let apply_genesis = chain.apply_block(&genesis).map(|_| ());
+        let conflicting_block = Block::new(&chain, vec![tx.clone(), fee_tx.clone()]).validate(&chain);
+        let block1 = Block::new(&chain, vec![tx.clone()]);
+        let apply_block1 = chain.apply_block(&block1).map(|_| ());
+        let bob_inclusion = chain.output_mmr().prove(c_bob).expect("block 1 created C_bob");
+        let bob_included = bob_inclusion.verify(&block1.header.output_root, c_bob);
+        let mallory_included = bob_inclusion.verify(&block1.header.output_root, c_mallory);
+
+        // The same payment through a mempool on top of genesis: the fee version conflicts.
+        let mut pool_chain = Ledger::new();
+        pool_chain.apply_block(&genesis).expect("genesis applies");
+        let mut pool = Mempool::new();
+        let pool_added = [tx.clone(), fee_tx.clone(), coinbase_tx.clone()]
+            .into_iter()
+            .map(|candidate| pool.add(candidate, &pool_chain).map(|_| ()))
+            .collect();
+        let template = pool.block_template(&pool_chain, None);
+        let apply_template = pool_chain.apply_block(&template).map(|_| ());
+        pool.prune(&pool_chain);
+        let pool_left = pool.len();
+
+        // Reorg: two empty blocks on top of genesis outweigh block 1, and Alice's payment
+        // goes back to the mempool.
+        let mut forked = Chain::new(genesis.clone()).expect("genesis applies");
+        forked.add_block(block1.clone()).expect("block 1 applies");
+        let main_height = forked.tip().height;
+
+        // A light client syncs only the headers, then checks C_bob against the tip's output root.
+        let mut light = LightClient::new();
+        light.sync(forked.headers()).expect("the main chain links");
+        let light_headers = light.headers().len();
+        let light_bytes = light.headers().iter().map(|header| header.to_bytes().len()).sum();
+        let chain_bytes = forked.blocks().iter().map(|block| block.to_bytes().len()).sum();
+        let output_proof = forked.ledger().output_mmr().prove(c_bob).expect("block 1 created C_bob");
+        let light_bob = light.verify_output(c_bob, &output_proof);
+        let light_mallory = light.verify_output(c_mallory, &output_proof);
+        let mut tampered: Vec<BlockHeader> = forked.headers().into_iter().cloned().collect();
+        tampered[0].output_root[0] ^= 1;
+        let light_tampered = LightClient::new().sync(&tampered);
+        let side1 = Block::new(&forked.state_after(genesis.header.hash()).expect("genesis is known"), vec![]);
+        let side_reorg = forked.add_block(side1.clone()).expect("an empty block applies").len();
+        let side2 = Block::new(&forked.state_after(side1.header.hash()).expect("side 1 is known"), vec![]);
+        let disconnected = forked.add_block(side2).expect("an empty block applies");
+        let reorg_height = forked.tip().height;
+        let mut reorg_pool = Mempool::new();
+        let returned = reorg_pool.readd(&disconnected, forked.ledger());
+        let bob_unspent_after_reorg = forked.ledger().find(c_bob).is_some();
+
+        // Horizon: genesis and block 1 are compacted, a newer empty block 2 stays whole.
+        let mut long = Chain::new(genesis.clone()).expect("genesis applies");
+        long.add_block(block1.clone()).expect("block 1 applies");
+        long.add_block(Block::new(long.ledger(), vec![])).expect("an empty block applies");
+        let compacted = Compacted::new(&long, 1).expect("the main chain replays");
+        let compacted_verdict = compacted.validate();
+        let mut tampered = compacted.clone();
+        tampered.history.kernels.pop();
+        let tampered_history = tampered.validate();
+
+        // Cut-through: the coinbase creates C_input and the offset payment spends it.
+        let merged = Aggregate::merge(&[coinbase_tx.clone(), offset_tx.clone()]);
+        let merged_verdict = merged.verify();
+        let mut aggregate = merged.clone();
+        let cut = aggregate.cut_through();
+        let aggregate_verdict = aggregate.verify();
+
+        // Alice proves she can open C_input, without revealing 10 or r_input.
+        let opening_proof = prove_opening(&Opening::new(value_input, r_input), &mut rng);
+        let opening_verdict = verify_opening(c_input, &opening_proof);
+        let opening_replayed = verify_opening(c_bob, &opening_proof);
+
+        // Bob's 5 under two different blindings (steps 2 and 4d): same value, provably.
+        let (bob_opening, bob_own_opening) = (Opening::new(value_to_bob, r_bob), Opening::new(value_to_bob, r_bob_own));
+        let c_bob_own = bob_own_opening.commit();
+        let equality_proof =
+            prove_equal(c_bob, c_bob_own, (&bob_opening, &bob_own_opening), &mut rng).expect("both hide 5");
+        let equality_verdict = verify_equal(c_bob, c_bob_own, &equality_proof);
+        let equality_swapped = verify_equal(c_bob, c_input, &equality_proof);
+        let input_opening = Opening::new(value_input, r_input);
+        let equality_refusal = prove_equal(c_bob, c_input, (&bob_opening, &input_opening), &mut rng).err();
+
+        // Alice shows an auditor that C_input holds exactly 10, keeping r_input secret.
+        let value_proof = prove_value(c_input, &input_opening, &mut rng).expect("the opening matches");
+        let value_verdict = verify_value(c_input, Scalar::new(10), &value_proof);
+        let value_wrong = verify_value(c_input, Scalar::new(11), &value_proof);
+
+        // Standalone OR-proofs for a committed 0 and a committed 1, and a refused 2.
+        let bits = [0, 1, 2].map(|b| Opening::new(Scalar::new(b), Blinding::random(&mut rng)));
+        let bit_proofs: Vec<BitProof> =
+            bits[..2].iter().map(|bit| prove_bit(bit, &mut rng).expect("0 and 1 are bits")).collect();
+        let bit_verdicts =
+            bits[..2].iter().zip(&bit_proofs).map(|(bit, proof)| verify_bit(bit.commit(), proof)).collect();
+        let bit_swapped = verify_bit(bits[0].commit(), &bit_proofs[1]);
+        let bit_refusal = prove_bit(&bits[2], &mut rng).err();
+
+        // Payments are capped at CAP: Bob's 5 proves it is in [0, CAP]; a 7 cannot.
+        let cap_proof = interval_proof_create(value_to_bob, r_bob, 0, CAP, &mut rng).expect("5 is under the cap");
+        let cap_verdict = interval_proof_verify(c_bob, 0, CAP, &cap_proof);
+        let cap_lowered = interval_proof_verify(c_bob, 0, 4, &cap_proof);
+        let cap_refusal = interval_proof_create(Scalar::new(7), r_bob, 0, CAP, &mut rng).err();
+
+        // Only DENOMINATIONS may be paid: Bob's 5 is one of them, a 7 is not.
+        let membership_proof =
+            prove_membership(c_bob, &bob_opening, &DENOMINATIONS, &mut rng).expect("5 is a denomination");
+        let membership_verdict = verify_membership(c_bob, &DENOMINATIONS, &membership_proof);
+        let membership_other_set = verify_membership(c_bob, &[1, 10, 50, 100], &membership_proof);
+        let seven = Opening::new(Scalar::new(7), r_bob);
+        let membership_refusal = prove_membership(seven.commit(), &seven, &DENOMINATIONS, &mut rng).err();
+
+        // Step 4g's transaction again, with one range proof for all three outputs.
+        let (aggregated_tx, _) = TransactionBuilder::new()
+            .add_input(c_input, Opening::new(value_input, r_input))
+            .add_output(Scalar::new(3))
+            .add_output(Scalar::new(2))
+            .add_output(Scalar::new(4))
+            .fee(1)
+            .aggregate_range_proof()
+            .build(&mut rng)
+            .expect("10 = 3 + 2 + 4 + 1");
+        let aggregated_verdict = aggregated_tx.verify();
+        let mut reordered = aggregated_tx.clone();
+        reordered.outputs.reverse();
+        let aggregated_reordered = reordered.verify();
+
+        // Every valid transaction of the walkthrough in one batch, then with the attack added.
+        let valid = [&tx, &fee_tx, &kernel_tx, &offset_tx, &coinbase_tx, &built_tx, &aggregated_tx];
+        let mut batched: Vec<Transaction> = valid.into_iter().cloned().collect();
+        let batch = verify_batch(&batched);
+        batched.push(attack_tx.clone());
+        let batch_with_attack = verify_batch(&batched);
+
+        // BigField mode: input 2^64 + 10 -> Bob 2^64 + change 10, with ~250-bit blindings.
+        let field = BigField::realistic_256();
+        let big_to_bob = BigInt::from(1u8) << 64u32; // more than a u64 can hold
+        let big_change = BigInt::from(10u8);
+        let big_input = &big_to_bob + &big_change;
+        let big_r_input = (BigInt::from(1u8) << 250u32) + 12345;
+        let big_r_bob = (BigInt::from(1u8) << 249u32) + 11111;
+        let big_r_change = &big_r_input - &big_r_bob;
+        let big_c_input = field.commit(&big_input, &big_r_input);
+        let big_c_bob = field.commit(&big_to_bob, &big_r_bob);
+        let big_c_change = field.commit(&big_change, &big_r_change);
+        let big_sum_outputs = field.sum(&[&big_c_bob, &big_c_change]);
+
+        // The honest transaction again, on the toy curve.
+        let point_input = toy_curve::commit(value_input.as_i64(), r_input.as_i64());
+        let point_bob = toy_curve::commit(value_to_bob.as_i64(), r_bob.as_i64());
+        let point_change = toy_curve::commit(value_change.as_i64(), r_change.as_i64());
+        let point_sum = toy_curve::add(point_bob, point_change);
+
+        // The same commitments twice: by double-and-add, then with the tables built.
+        let pairs = || (0..CURVE_COMMITS).map(|k| (k, 7 * k + 3));
+        let double_and_add = |(v, r)| {
+            toy_curve::add(toy_curve::scalar_mul(v, toy_curve::G), toy_curve::scalar_mul(r, toy_curve::H))
+        };
+        let start = Instant::now();
+        let doubled: Vec<Point> = pairs().map(double_and_add).collect();
+        let doubling_time = start.elapsed();
+        toy_curve::precompute();
+        let start = Instant::now();
+        let looked_up: Vec<Point> = pairs().map(|(v, r)| toy_curve::commit(v, r)).collect();
+        let curve_commit_times = [doubling_time, start.elapsed()];
+        let tables_agree = doubled == looked_up;
+
+        Scenario {
+            value_input,
+            r_input,
+            c_input,
+            pi_input,
+            value_to_bob,
+            value_change,
+            r_bob,
+            r_change,
+            c_bob,
+            c_change,
+            pi_bob,
+            pi_change,
+            tx,
+            sum_outputs,
+            balance,
+            rp_input,
+            rp_bob,
+            rp_change,
+            verdict,
+            fee_tx,
+            value_change_fee,
+            unpaid_fee_balance,
+            fee_balance,
+            fee_verdict,
+            r_bob_own,
+            r_change_own,
+            excess_blinding,
+            kernel_tx,
+            kernel_free_balance,
+            kernel_verdict,
+            forged_kernel_balance,
+            offset_tx,
+            offset_verdict,
+            coinbase_tx,
+            coinbase_verdict,
+            overmint,
+            overmint_balance,
+            built_tx,
+            built_openings,
+            built_verdict,
+            utxos_minted,
+            utxos_paid,
+            apply_coinbase,
+            apply_tx,
+            double_spend,
+            genesis,
+            block1,
+            apply_genesis,
+            apply_block1,
+            conflicting_block,
+            bob_inclusion,
+            bob_included,
+            mallory_included,
+            pool_added,
+            template,
+            apply_template,
+            pool_left,
+            light_headers,
+            light_bytes,
+            chain_bytes,
+            light_bob,
+            light_mallory,
+            light_tampered,
+            main_height,
+            side_reorg,
+            reorg_height,
+            disconnected,
+            returned,
+            bob_unspent_after_reorg,
+            compacted,
+            compacted_verdict,
+            tampered_history,
+            opening_proof,
+            opening_verdict,
+            opening_replayed,
+            c_bob_own,
+            equality_verdict,
+            equality_swapped,
+            equality_refusal,
+            value_verdict,
+            value_wrong,
+            bit_proofs,
+            bit_verdicts,
+            bit_swapped,
+            bit_refusal,
+            cap_proof,
+            cap_verdict,
+            cap_lowered,
+            cap_refusal,
+            membership_proof,
+            membership_verdict,
+            membership_other_set,
+            membership_refusal,
+            aggregated_tx,
+            aggregated_verdict,
+            aggregated_reordered,
+            batch,
+            batch_with_attack,
+            merged,
+            merged_verdict,
+            cut,
+            aggregate,
+            aggregate_verdict,
+            value_change_attack,
+            c_input_attack,
+            c_bob_attack,
+            c_change_attack,
+            sum_outputs_attack,
+            attack_balance,
+            honest_refusal,
+            pi_change_attack,
+            rp_change_attack,
+            attack_verdict,
+            field,
+            big_c_input,
+            big_sum_outputs,
+            point_input,
+            point_bob,
+            point_change,
+            point_sum,
+            curve_commit_times,
+            tables_agree,
+        }
+    }
+}
+
+/// The range proof outcomes of a transaction whose N entries all carry a proof.
+fn range_proof_outcomes<const N: usize>(tx: &Transaction) -> [Result<(), CtError>; N] {
+    let outcomes: Vec<_> = tx.range_proof_results().into_iter().map(|(_, result)| result).collect();
+    outcomes.try_into().expect("every entry carries a range proof")
+}
+
+/// One discrete part of the walkthrough.
+struct Step {
+    narrate: fn(&Scenario, &mut Report),
+    prediction: Option<Prediction>,
+}
+
+/// Asked before a step in `--interactive` mode: a yes/no question and its true answer.
+struct Prediction {
+    question: &'static str,
+    answer: fn(&Scenario) -> bool,
+}
+
+const STEPS: &[Step] = &[
+    Step { narrate: parameters, prediction: None },
+    Step { narrate: step1_input, prediction: None },
+    Step { narrate: step2_outputs, prediction: None },
+    Step { narrate: step3_published, prediction: None },
+    Step {
+        narrate: step4_balance,
+        prediction: Some(Prediction {
+            question: "Will C_input = C_bob + C_change hold, checked without any amounts?",
+            answer: |s| s.balance.is_ok(),
+        }),
+    },
+    Step {
+        narrate: step4b_range_proofs,
+        prediction: Some(Prediction {
+            question: "Will all three range proofs verify?",
+            answer: |s| s.rp_input.is_ok() && s.rp_bob.is_ok() && s.rp_change.is_ok(),
+        }),
+    },
+    Step {
+        narrate: step4c_fees,
+        prediction: Some(Prediction {
+            question: "Alice pays Bob 5 and a public fee of 1, keeping 4. Does the check need a fee*G term?",
+            answer: |s| s.unpaid_fee_balance.is_err() && s.fee_balance.is_ok(),
+        }),
+    },
+    Step {
+        narrate: step4d_kernel,
+        prediction: Some(Prediction {
+            question: "Mallory, who does not know r_input, moves Alice's 10 to herself. Can she sign a valid kernel?",
+            answer: |s| s.forged_kernel_balance.is_ok(),
+        }),
+    },
+    Step { narrate: step4e_offset, prediction: None },
+    Step {
+        narrate: step4f_coinbase,
+        prediction: Some(Prediction {
+            question: "A miner's coinbase mints one more than the subsidy, with a valid kernel. Is it accepted?",
+            answer: |s| s.overmint_balance.is_ok(),
+        }),
+    },
+    Step { narrate: step4g_builder, prediction: None },
+    Step {
+        narrate: step4h_ledger,
+        prediction: Some(Prediction {
+            question: "Alice then also broadcasts her fee-paying version (step 4c), which spends C_input too. Accepted?",
+            answer: |s| s.double_spend.is_ok(),
+        }),
+    },
+    Step { narrate: step4i_blocks, prediction: None },
+    Step { narrate: step4j_mempool, prediction: None },
+    Step { narrate: step4k_cut_through, prediction: None },
+    Step { narrate: step4l_reorg, prediction: None },
+    Step { narrate: step4m_light_client, prediction: None },
+    Step { narrate: step4n_horizon, prediction: None },
+    Step { narrate: step4o_opening_proof, prediction: None },
+    Step { narrate: step4p_equality_proof, prediction: None },
+    Step { narrate: step4q_value_proof, prediction: None },
+    Step { narrate: step4r_bit_proof, prediction: None },
+    Step { narrate: step4s_interval_proof, prediction: None },
+    Step { narrate: step4t_membership_proof, prediction: None },
+    Step { narrate: step4u_aggregate_range_proof, prediction: None },
+    Step { narrate: step4v_batch_verification, prediction: None },
+    Step { narrate: step5_secrecy, prediction: None },
+    Step {
+        narrate: attack_balance,
+        prediction: Some(Prediction {
+            question: "Input 10, 15 to Bob, change -5: will the attack pass the balance check?",
+            answer: |s| s.attack_balance.is_ok(),
+        }),
+    },
+    Step {
+        narrate: attack_range_proof,
+        prediction: Some(Prediction {
+            question: "Will the attacker's range proof for the -5 change verify?",
+            answer: |s| s.rp_change_attack.is_ok(),
+        }),
+    },
+    Step { narrate: range_proofs_explained, prediction: None },
+    Step {
+        narrate: big_field_mode,
+        prediction: Some(Prediction {
+            question: "With 256-bit parameters and amounts above 2^64, will the balance still hold?",
+            answer: |s| s.big_c_input == s.big_sum_outputs,
+        }),
+    },
+    Step {
+        narrate: curve_points,
+        prediction: Some(Prediction {
+            question: "On the curve, will C_input - (C_bob + C_change) be the point at infinity?",
+            answer: |s| s.point_input == s.point_sum,
+        }),
+    },
+    Step { narrate: final_message, prediction: None },
+];
+
+/// The full walkthrough: one honest transaction, the negative-value attack, and how
+/// range proofs, BigField mode and the toy curve fit in.
+/// With `json`, the narrative is replaced by one JSON document holding every step's data.
+/// With `interactive`, pause before each step and ask for a prediction where there is one.
+pub fn run(json: bool, interactive: bool) {
+    let scenario = Scenario::build();
+    let mut report = Report::new(json);
+    let (mut correct, mut asked) = (0, 0);
+    for (i, step) in STEPS.iter().enumerate() {
+        if interactive {
+            if i > 0 && !pause(&format!("[{}/{}] Press Enter to continue...", i + 1, STEPS.len())) {
+                return;
+            }
+            if let Some(prediction) = &step.prediction {
+                let Some(guess) = ask_yes_no(&format!("Predict: {} [y/n] ", prediction.question)) else {
+                    return;
+                };
+                let answer = (prediction.answer)(&scenario);
+                asked += 1;
+                if guess == answer {
+                    correct += 1;
+                    println!("  -> You said {}. Correct!\n", yes_no(guess));
+                } else {
+                    println!("  -> You said {}, but the answer is {}. Here is why:\n", yes_no(guess), yes_no(answer));
+                }
+            }
+        }
+        (step.narrate)(&scenario, &mut report);
+    }
+    if interactive && asked > 0 {
+        println!("You predicted {} of {} outcomes correctly.", correct, asked);
+    }
+    report.finish();
+}
+
+fn parameters(_: &Scenario, report: &mut Report) {
+    let params = params::active();
+    say!(report, "{}", "=".repeat(60));
+    say!(report, "CONFIDENTIAL TRANSACTION DEMO (Pedersen Commitments)");
+    say!(report, "{}", "=".repeat(60));
+    say!(report, "\n--- Public parameters (everyone knows these) ---");
+    say!(report, "  Modulus p = {}", params.modulus);
+    say!(report, "  Generator G = {}", params.g);
+    say!(report, "  Generator H = {}", params.h);
+    say!(report, "  Range proof bits n = {}  (proves 0 <= v < {})", params.range_bits, 1i64 << params.range_bits);
+    say!(report, "  Coinbase subsidy = {}  (the most a coinbase may mint)", params.subsidy);
+    let weaknesses = params.weaknesses();
+    say!(report, "  Quick checks on these parameters:");
+    for weakness in &weaknesses {
+        say!(report, "    WEAK: {}", weakness);
+    }
+    if weaknesses.is_empty() {
+        say!(report, "    nothing found (a composite or tiny p, related G and H, or a range near p would show here)");
+    }
+    say!(report, "  (In real crypto, G and H would be curve points.)\n");
+    report.record(
+        "parameters",
+        json!({
+            "modulus": params.modulus,
+            "g": params.g,
+            "h": params.h,
+            "range_bits": params.range_bits,
+            "subsidy": params.subsidy,
+            "weaknesses": weaknesses.iter().map(|w| w.to_string()).collect::<Vec<_>>(),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 1: Alice's initial commitment (input)
+// ---------------------------------------------------------------------------
+fn step1_input(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 1: Alice's input commitment ---");
+    say!(report, "  Alice's input commitment: C_input = {}*G + {}*H", s.value_input, s.r_input);
+    say!(report, "  C_input = {}", s.c_input);
+    say!(report, "  π_input = {}  (toy range proof for this commitment)", s.pi_input);
+    say!(report, "  (The value 10 and blinding 12345 are NEVER sent on the chain.)\n");
+    report.record(
+        "input",
+        json!({
+            "value": s.value_input.as_i64(),
+            "blinding": s.r_input.as_i64(),
+            "commitment": s.c_input.as_i64(),
+            "proof": proof_json(&s.pi_input),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 2: Alice creates commitments for the outputs
+// ---------------------------------------------------------------------------
+fn step2_outputs(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 2: Output commitments (Bob and change) ---");
+    say!(report, "  Bob's output:   value = {}, blinding = {}", s.value_to_bob, s.r_bob);
+    say!(report, "  C_bob    = {}*G + {}*H = {}", s.value_to_bob, s.r_bob, s.c_bob);
+    say!(report, "  π_bob    = {}", s.pi_bob);
+    say!(report, "  Change: value = {}, blinding = {}", s.value_change, s.r_change);
+    say!(report, "  C_change = {}*G + {}*H = {}", s.value_change, s.r_change, s.c_change);
+    say!(report, "  π_change = {}", s.pi_change);
+    say!(report, "  (Again, the actual amounts 5 and 5 are never revealed.)\n");
+    report.record(
+        "outputs",
+        json!({
+            "bob": {
+                "value": s.value_to_bob.as_i64(),
+                "blinding": s.r_bob.as_i64(),
+                "commitment": s.c_bob.as_i64(),
+                "proof": proof_json(&s.pi_bob),
+            },
+            "change": {
+                "value": s.value_change.as_i64(),
+                "blinding": s.r_change.as_i64(),
+                "commitment": s.c_change.as_i64(),
+                "proof": proof_json(&s.pi_change),
+            },
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 3: What gets published (only commitments)
+// ---------------------------------------------------------------------------
+fn step3_published(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 3: What is published on the ledger ---");
+    say!(report, "  The network sees commitments C and range proofs π (no values, no blindings):");
+    say!(report, "    (C_input,  π_input ) = ({}, {})", s.c_input, s.pi_input);
+    say!(report, "    (C_bob,    π_bob   ) = ({}, {})", s.c_bob, s.pi_bob);
+    say!(report, "    (C_change, π_change) = ({}, {})", s.c_change, s.pi_change);
+    say!(report, "  (π contains only commitments to bits and OR-proofs — no bits in the clear.)");
+    say!(report, "  No one can recover 10, 5, or 5 from these alone.");
+    let outpoints = s.tx.outpoints();
+    say!(report, "  txid = SHA-256(wire bytes) = {}", s.tx.txid());
+    say!(report, "  Later transactions refer to outputs as txid:index, e.g. Bob's is {}.\n", outpoints[0]);
+    report.record(
+        "published",
+        json!({
+            "commitments": [s.c_input.as_i64(), s.c_bob.as_i64(), s.c_change.as_i64()],
+            "txid": s.tx.txid().to_string(),
+            "outpoints": outpoints.iter().map(|o| o.to_string()).collect::<Vec<_>>(),
+        }),
+    );
+    // Same data in the file format `ct verify -` reads.
+    report.record("transaction", to_json(&s.tx));
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4: Verification using only commitments
+// ---------------------------------------------------------------------------
+fn step4_balance(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4: Public verification (no values revealed) ---");
+    say!(report, "  Check: C_input ?= C_bob + C_change  (mod p)");
+    say!(report, "  C_input         = {}", s.c_input);
+    say!(report, "  C_bob + C_change = {}", s.sum_outputs);
+    say!(report, "  verify_balance => {}", outcome(&s.balance));
+    if s.balance.is_ok() {
+        say!(report, "  So: sum(input amounts) = sum(output amounts), verified using only commitments.\n");
+    } else {
+        say!(report, "  Verification failed.\n");
+    }
+    report.record(
+        "balance",
+        json!({
+            "sum_inputs": s.c_input.as_i64(),
+            "sum_outputs": s.sum_outputs.as_i64(),
+            "result": result_json(&s.balance),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4b: Range proofs — create and display π, then verify (C, π) without knowing v
+// ---------------------------------------------------------------------------
+// π commits to each bit of v separately, proves each bit commitment hides 0 or 1,
+// and the bits recompose to C. Bulletproofs prove the same statement, just compressed.
+fn step4b_range_proofs(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4b: Range proofs — create π, then verify (C, π) ---");
+    say!(report, "  Prover created π for each commitment (above). Verifier checks using only (C, π):");
+    say!(report, "  verify(C_input,  π_input ) => {}", outcome(&s.rp_input));
+    say!(report, "  verify(C_bob,    π_bob   ) => {}", outcome(&s.rp_bob));
+    say!(report, "  verify(C_change, π_change) => {}", outcome(&s.rp_change));
+    say!(report, "  All range proofs pass. Verifier never saw the values.");
+    say!(report, "  Transaction::verify() (balance + every range proof) => {}\n", outcome(&s.verdict));
+
+    say!(report, "  Inside π_bob (what the verifier actually checks):");
+    for (i, bit) in s.pi_bob.bits.iter().enumerate() {
+        say!(
+            report,
+            "    C_{} = {:>19}   OR-proof \"bit is 0 or 1\" => {}",
+            i,
+            bit.commitment,
+            bit_proof_verify(s.c_bob, i, bit)
+        );
+    }
+    say!(report, "    sum(2^i * C_i) = {}", range_proof_recompose(&s.pi_bob));
+    say!(report, "    C_bob          = {}", s.c_bob);
+    say!(report, "  Each C_i hides one bit; only Bob's wallet knows they are 1,0,1,0,0,0,0,0 (= 5).\n");
+    report.record(
+        "range_proofs",
+        json!({
+            "input": result_json(&s.rp_input),
+            "bob": result_json(&s.rp_bob),
+            "change": result_json(&s.rp_change),
+            "bob_recomposed": range_proof_recompose(&s.pi_bob).as_i64(),
+            "transaction": result_json(&s.verdict),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4c: Fees — a public amount inside the homomorphic check
+// ---------------------------------------------------------------------------
+fn step4c_fees(s: &Scenario, report: &mut Report) {
+    let c_change_fee = s.fee_tx.outputs[1].commitment;
+    let sum_outputs = sum_commitments(&s.fee_tx.output_commitments()) + fee_commitment(s.fee_tx.fee);
+    say!(report, "--- Step 4c: Fees ---");
+    say!(report, "  Real transactions pay a fee, published in the clear so anyone can see it.");
+    say!(
+        report,
+        "  Alice pays Bob {} and a fee of {} out of {}, keeping {} (same blindings as before).",
+        s.value_to_bob,
+        s.fee_tx.fee,
+        s.value_input,
+        s.value_change_fee
+    );
+    say!(report, "  C_change' = {}*G + {}*H = {}", s.value_change_fee, s.r_change, c_change_fee);
+    say!(report, "  Without the fee: C_input ?= C_bob + C_change'         => {}", outcome(&s.unpaid_fee_balance));
+    say!(report, "  With the fee:    C_input ?= C_bob + C_change' + fee*G  (fee*G = {})", fee_commitment(s.fee_tx.fee));
+    say!(report, "  C_input                   = {}", s.c_input);
+    say!(report, "  C_bob + C_change' + fee*G = {}", sum_outputs);
+    say!(report, "  verify_balance            => {}", outcome(&s.fee_balance));
+    say!(report, "  Transaction::verify()     => {}", outcome(&s.fee_verdict));
+    say!(report, "  The fee needs no blinding and no range proof: fee*G is computed from the public number.\n");
+    report.record(
+        "fee",
+        json!({
+            "fee": s.fee_tx.fee,
+            "fee_commitment": fee_commitment(s.fee_tx.fee).as_i64(),
+            "without_fee": result_json(&s.unpaid_fee_balance),
+            "balance": result_json(&s.fee_balance),
+            "transaction": to_json(&s.fee_tx),
+            "result": result_json(&s.fee_verdict),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4d: Kernel — independent blindings, excess and a Schnorr signature
+// ---------------------------------------------------------------------------
+fn step4d_kernel(s: &Scenario, report: &mut Report) {
+    let kernel = s.kernel_tx.kernel.expect("kernel transaction has a kernel");
+    say!(report, "--- Step 4d: Kernel (Mimblewimble) ---");
+    say!(report, "  So far Alice chose r_change = r_input - r_bob, so she knows Bob's blinding and could spend his coin.");
+    say!(
+        report,
+        "  Instead, let every owner pick their own: r_bob = {}, r_change = {}.",
+        s.r_bob_own,
+        s.r_change_own
+    );
+    say!(report, "  Now C_input ?= C_bob + C_change => {}", outcome(&s.kernel_free_balance));
+    say!(report, "  The leftover is the excess E = C_bob + C_change - C_input = x*H,");
+    say!(report, "  with x = r_bob + r_change - r_input = {} (only the sender can compute it).", s.excess_blinding);
+    say!(report, "  E = {}", kernel.excess);
+    say!(
+        report,
+        "  Kernel signature (Schnorr over H with key x): R = {}, s = {}",
+        kernel.signature.nonce,
+        kernel.signature.s
+    );
+    say!(report, "  Check: C_input + E = C_bob + C_change, and s*H = R + e*E");
+    say!(report, "  Transaction::verify() => {}", outcome(&s.kernel_verdict));
+    say!(report, "  Why this stops forgeries: a signature for E exists only if E = x*H for a known x.");
+    say!(report, "  So E hides no value (no G part), and whoever signed knew every input blinding.");
+    say!(report, "  Mallory moves Alice's 10 to her own output. E = (r_mallory - r_input)*H, but she only knows r_mallory:");
+    say!(report, "  verify_balance (with her kernel) => {}", outcome(&s.forged_kernel_balance));
+    say!(report, "  That holds only where the discrete log is hard. Mod p she can compute x = E/H herself and sign,");
+    say!(report, "  as anyone can (see `ct attack --scenario known-dlog-h`); real kernels use a curve.\n");
+    report.record(
+        "kernel",
+        json!({
+            "without_kernel": result_json(&s.kernel_free_balance),
+            "excess": kernel.excess.as_i64(),
+            "nonce": kernel.signature.nonce,
+            "s": kernel.signature.s,
+            "transaction": to_json(&s.kernel_tx),
+            "result": result_json(&s.kernel_verdict),
+            "forged_kernel": result_json(&s.forged_kernel_balance),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4e: Kernel offset — unlinking the kernel from its outputs
+// ---------------------------------------------------------------------------
+fn step4e_offset(s: &Scenario, report: &mut Report) {
+    let kernel = s.kernel_tx.kernel.expect("kernel transaction has a kernel");
+    let offset_kernel = s.offset_tx.kernel.expect("offset transaction has a kernel");
+    say!(report, "--- Step 4e: Kernel offset ---");
+    say!(report, "  In step 4d, E = C_bob + C_change - C_input exactly. Merge many transactions into a block");
+    say!(report, "  and anyone can still try subsets of inputs and outputs until one matches a kernel.");
+    say!(
+        report,
+        "  E == sum(outputs) - sum(inputs)?  {}",
+        yes_no(kernel.excess == s.kernel_tx.excess())
+    );
+    say!(report, "  Grin's fix: split x = offset + x', sign with x' only and publish the offset in the clear.");
+    say!(report, "  offset = {}, E' = x'*H = {}", s.offset_tx.offset, offset_kernel.excess);
+    say!(
+        report,
+        "  E' == sum(outputs) - sum(inputs)?  {}",
+        yes_no(offset_kernel.excess == s.offset_tx.excess())
+    );
+    say!(report, "  Check: C_input + E' + offset*H = C_bob + C_change, and the signature over E'");
+    say!(report, "  Transaction::verify() => {}", outcome(&s.offset_verdict));
+    say!(report, "  A block adds up all its transactions' offsets into one, so no kernel points at its outputs.\n");
+    report.record(
+        "offset",
+        json!({
+            "offset": s.offset_tx.offset.as_i64(),
+            "excess": offset_kernel.excess.as_i64(),
+            "transaction": to_json(&s.offset_tx),
+            "result": result_json(&s.offset_verdict),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4f: Coinbase — where the input came from
+// ---------------------------------------------------------------------------
+fn step4f_coinbase(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4f: Coinbase (minting) ---");
+    say!(report, "  Alice's 10 had to come from somewhere. A coinbase has no inputs and mints a public amount:");
+    say!(report, "  check: minted*G + E = sum(outputs), with minted <= subsidy ({}).", params::active().subsidy);
+    say!(report, "  Coinbase: minted = {}, output C_input = {}", s.coinbase_tx.minted, s.coinbase_tx.outputs[0].commitment);
+    say!(report, "  E = C_input - 10*G = r_input*H, signed with r_input");
+    say!(report, "  Transaction::verify() => {}", outcome(&s.coinbase_verdict));
+    say!(report, "  A miner tries to mint {} (one over the subsidy), with a correct kernel:", s.overmint);
+    say!(report, "  verify_balance => {}", outcome(&s.overmint_balance));
+    say!(report, "  The minted amount is public, so the subsidy rule needs no proof at all.\n");
+    report.record(
+        "coinbase",
+        json!({
+            "transaction": to_json(&s.coinbase_tx),
+            "txid": s.coinbase_tx.txid().to_string(),
+            "result": result_json(&s.coinbase_verdict),
+            "overmint": s.overmint,
+            "overmint_result": result_json(&s.overmint_balance),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4g: TransactionBuilder — blindings chosen for you
+// ---------------------------------------------------------------------------
+fn step4g_builder(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4g: TransactionBuilder ---");
+    say!(report, "  r_change = r_input - r_bob only covers two outputs. The builder takes the inputs' openings");
+    say!(report, "  and the output amounts, picks random blindings, and gives the last output whatever is left:");
+    say!(report, "  TransactionBuilder::new().add_input(C_input, (10, r_input))");
+    say!(report, "      .add_output(3).add_output(2).add_output(4).fee(1).build(rng)");
+    for (i, (opening, entry)) in s.built_openings.iter().zip(&s.built_tx.outputs).enumerate() {
+        say!(report, "  output {}: v = {}, r = {:>11}, C = {}", i, opening.value, opening.blinding, entry.commitment);
+    }
+    let total = s.built_openings.iter().fold(Blinding::default(), |sum, o| sum + o.blinding);
+    say!(report, "  sum(output r) = {} = r_input", total);
+    say!(report, "  Transaction::verify() => {}", outcome(&s.built_verdict));
+    say!(report, "  The openings come back to the sender, who hands each recipient theirs.\n");
+    report.record(
+        "builder",
+        json!({
+            "openings": s.built_openings
+                .iter()
+                .map(|o| json!({ "value": o.value.as_i64(), "blinding": o.blinding.as_i64() }))
+                .collect::<Vec<_>>(),
+            "transaction": to_json(&s.built_tx),
+            "result": result_json(&s.built_verdict),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4h: Ledger — the UTXO set
+// ---------------------------------------------------------------------------
+fn utxos_json(utxos: &[(OutPoint, Commitment)]) -> serde_json::Value {
+    json!(utxos
+        .iter()
+        .map(|(outpoint, c)| json!({ "outpoint": outpoint.to_string(), "commitment": c.as_i64() }))
+        .collect::<Vec<_>>())
+}
+
+fn step4h_ledger(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4h: Ledger (UTXO set) ---");
+    say!(report, "  The ledger keeps only unspent outputs. Applying a transaction verifies it,");
+    say!(report, "  removes its inputs from the set and adds its outputs as txid:index.");
+    say!(report, "  Apply the coinbase from step 4f => {}", outcome(&s.apply_coinbase));
+    for (outpoint, c) in &s.utxos_minted {
+        say!(report, "    {}  C = {}", outpoint, c);
+    }
+    say!(report, "  Apply Alice's payment (spends C_input) => {}", outcome(&s.apply_tx));
+    for (outpoint, c) in &s.utxos_paid {
+        say!(report, "    {}  C = {}", outpoint, c);
+    }
+    say!(report, "  C_input is gone; C_bob and C_change are now spendable. Amounts still never appear.");
+    say!(report, "  Alice also broadcasts the fee version from step 4c, spending C_input a second time.");
+    say!(report, "  It balances and its proofs verify, but: apply => {}", outcome(&s.double_spend));
+    say!(report, "  Only the UTXO set catches this: each commitment can be spent once.\n");
+    report.record(
+        "ledger",
+        json!({
+            "apply_coinbase": result_json(&s.apply_coinbase),
+            "utxos_after_coinbase": utxos_json(&s.utxos_minted),
+            "apply_payment": result_json(&s.apply_tx),
+            "utxos_after_payment": utxos_json(&s.utxos_paid),
+            "double_spend": result_json(&s.double_spend),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4i: Blocks — a minimal chain
+// ---------------------------------------------------------------------------
+fn block_json(block: &Block, result: &Result<(), CtError>) -> serde_json::Value {
+    let header = &block.header;
+    json!({
+        "hash": header.hash().to_string(),
+        "prev": header.prev.to_string(),
+        "height": header.height,
+        "output_root": to_hex(&header.output_root),
+        "transactions": block.transactions.iter().map(|tx| tx.txid().to_string()).collect::<Vec<_>>(),
+        "kernel_excess": header.kernel_excess.as_i64(),
+        "offset": header.offset.as_i64(),
+        "fees": header.fees,
+        "minted": header.minted,
+        "result": result_json(result),
+    })
+}
+
+fn step4i_blocks(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4i: Blocks ---");
+    say!(report, "  Blocks group transactions under a header: previous hash, height, and the block's");
+    say!(report, "  totals (kernel excesses, offsets, fees, minted). From the totals alone anyone can check");
+    say!(report, "  sum(outputs) - sum(inputs) = (minted - fees)*G + sum(E) + offset*H for the whole block.");
+    for (block, result) in [(&s.genesis, &s.apply_genesis), (&s.block1, &s.apply_block1)] {
+        let header = &block.header;
+        say!(report, "  Block {}: {}", header.height, header.hash());
+        say!(report, "    prev {}", header.prev);
+        say!(
+            report,
+            "    {} transaction(s), minted {}, fees {}, sum(E) = {}, offset = {}",
+            block.transactions.len(),
+            header.minted,
+            header.fees,
+            header.kernel_excess,
+            header.offset
+        );
+        say!(report, "    output MMR root {}", to_hex(&header.output_root));
+        say!(report, "    apply_block => {}", outcome(result));
+    }
+    say!(report, "  The output root commits to every output so far. Proof that C_bob is in block 1's root:");
+    say!(
+        report,
+        "    {} sibling(s) up to peak {} of {} => {}",
+        s.bob_inclusion.path.len(),
+        s.bob_inclusion.peak,
+        s.bob_inclusion.peaks.len(),
+        s.bob_included
+    );
+    say!(report, "    the same proof for Mallory's output => {}", s.mallory_included);
+    say!(report, "  A block 1 holding both Alice's payment and its fee version spends C_input twice:");
+    say!(report, "    validate => {}\n", outcome(&s.conflicting_block));
+    report.record(
+        "blocks",
+        json!({
+            "chain": [block_json(&s.genesis, &s.apply_genesis), block_json(&s.block1, &s.apply_block1)],
+            "conflicting_block": result_json(&s.conflicting_block),
+            "inclusion": json!({ "c_bob": s.bob_included, "c_mallory": s.mallory_included }),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4j: Mempool — policing unconfirmed transactions
+// ---------------------------------------------------------------------------
+fn step4j_mempool(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4j: Mempool ---");
+    say!(report, "  Before mining, nodes keep verified transactions in a mempool. The first transaction");
+    say!(report, "  to spend an output wins; a later one spending the same output is a conflict.");
+    say!(report, "  On top of the genesis block:");
+    let labels = ["Alice's payment", "its fee version (same input)", "the coinbase (relayed)"];
+    for (label, result) in labels.iter().zip(&s.pool_added) {
+        say!(report, "    add {:<29} => {}", label, outcome(result));
+    }
+    say!(
+        report,
+        "  Block template at height {}: {} transaction(s), fees {}",
+        s.template.header.height,
+        s.template.transactions.len(),
+        s.template.header.fees
+    );
+    say!(report, "    apply_block => {}", outcome(&s.apply_template));
+    say!(report, "  After mining, the pool is pruned: {} transaction(s) left.\n", s.pool_left);
+    report.record(
+        "mempool",
+        json!({
+            "added": s.pool_added.iter().map(result_json).collect::<Vec<_>>(),
+            "template": block_json(&s.template, &s.apply_template),
+            "left": s.pool_left,
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4k: Cut-through — merging transactions
+// ---------------------------------------------------------------------------
+fn aggregate_json(aggregate: &Aggregate, result: &Result<(), CtError>) -> serde_json::Value {
+    json!({
+        "inputs": aggregate.inputs.iter().map(|entry| entry.commitment.as_i64()).collect::<Vec<_>>(),
+        "outputs": aggregate.outputs.iter().map(|entry| entry.commitment.as_i64()).collect::<Vec<_>>(),
+        "kernels": aggregate.kernels.iter().map(|(kernel, _)| kernel.excess.as_i64()).collect::<Vec<_>>(),
+        "fee": aggregate.fee,
+        "minted": aggregate.minted,
+        "offset": aggregate.offset.as_i64(),
+        "result": result_json(result),
+    })
+}
+
+fn step4k_cut_through(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4k: Cut-through ---");
+    say!(report, "  Transactions can be merged: concatenate inputs, outputs and kernels, add the offsets.");
+    say!(report, "  Merging the coinbase with the kernel/offset payment (which spends C_input):");
+    for (label, aggregate, result) in [
+        ("merged", &s.merged, &s.merged_verdict),
+        ("after cut-through", &s.aggregate, &s.aggregate_verdict),
+    ] {
+        say!(
+            report,
+            "    {:<17}: {} input(s), {} output(s), {} kernel(s) => {}",
+            label,
+            aggregate.inputs.len(),
+            aggregate.outputs.len(),
+            aggregate.kernels.len(),
+            outcome(result)
+        );
+    }
+    say!(report, "  C_input is both created and spent, so it cancels: {} pair(s) removed.", s.cut);
+    say!(report, "  The kernels still prove no value was created, and C_input leaves no trace.\n");
+    report.record(
+        "cut_through",
+        json!({
+            "merged": aggregate_json(&s.merged, &s.merged_verdict),
+            "cut": s.cut,
+            "aggregate": aggregate_json(&s.aggregate, &s.aggregate_verdict),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4l: Reorg — a confidential payment rolled back
+// ---------------------------------------------------------------------------
+fn step4l_reorg(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4l: Chain reorganization ---");
+    say!(report, "  Two miners may extend the same block. The chain keeps both branches and follows the");
+    say!(report, "  heavier one (here: the longer one), rolling the UTXO set back to the fork point.");
+    say!(report, "  Main chain: genesis + block 1 (Alice's payment), height {}.", s.main_height);
+    say!(report, "  A competing empty block 1' arrives: same height, no reorg ({} disconnected).", s.side_reorg);
+    say!(report, "  An empty block 2' on top of it makes that branch heavier:");
+    say!(report, "    new tip height {}, {} block(s) disconnected", s.reorg_height, s.disconnected.len());
+    say!(report, "    C_bob unspent after the reorg? {}", s.bob_unspent_after_reorg);
+    say!(report, "    {} transaction(s) returned to the mempool, waiting to be mined again.\n", s.returned);
+    report.record(
+        "reorg",
+        json!({
+            "main_height": s.main_height,
+            "reorg_height": s.reorg_height,
+            "disconnected": s.disconnected.iter().map(|block| block.header.hash().to_string()).collect::<Vec<_>>(),
+            "returned": s.returned,
+            "c_bob_unspent": s.bob_unspent_after_reorg,
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4m: Light client — headers only
+// ---------------------------------------------------------------------------
+fn step4m_light_client(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4m: Light client ---");
+    say!(report, "  A light client stores only headers; each one anchors the output MMR root.");
+    say!(
+        report,
+        "  Synced {} header(s): {} bytes, against {} bytes of blocks.",
+        s.light_headers,
+        s.light_bytes,
+        s.chain_bytes
+    );
+    say!(report, "  A full node sends an inclusion proof for Bob's output:");
+    say!(report, "    verify_output(C_bob)     => {}", s.light_bob);
+    say!(report, "    verify_output(C_mallory) => {}", s.light_mallory);
+    say!(report, "  The client learns C_bob is on chain, but still nothing about its amount.");
+    say!(report, "  Headers with an altered output root no longer link:");
+    say!(report, "    sync => {}\n", outcome(&s.light_tampered));
+    report.record(
+        "light_client",
+        json!({
+            "headers": s.light_headers,
+            "header_bytes": s.light_bytes,
+            "block_bytes": s.chain_bytes,
+            "c_bob": s.light_bob,
+            "c_mallory": s.light_mallory,
+            "tampered_sync": result_json(&s.light_tampered),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4n: Horizon — pruning spent outputs
+// ---------------------------------------------------------------------------
+fn step4n_horizon(s: &Scenario, report: &mut Report) {
+    let c = &s.compacted;
+    say!(report, "--- Step 4n: Compaction beyond a horizon ---");
+    say!(report, "  Blocks older than the horizon are merged and cut through: a spent output, its range");
+    say!(report, "  proof and the input spending it all go. Headers and kernels stay.");
+    say!(
+        report,
+        "  Chain of {} blocks, horizon 1: {} old block(s) compacted, {} kept whole.",
+        c.headers.len(),
+        c.headers.len() - c.recent.len(),
+        c.recent.len()
+    );
+    say!(
+        report,
+        "    pruned {} spent output(s) with their range proofs: {} bytes -> {} bytes",
+        c.pruned_outputs,
+        c.bytes_before,
+        c.bytes_after
+    );
+    say!(report, "    validate => {}", outcome(&s.compacted_verdict));
+    say!(report, "  Dropping a kernel from the compacted history is caught:");
+    say!(report, "    validate => {}\n", outcome(&s.tampered_history));
+    report.record(
+        "horizon",
+        json!({
+            "blocks": c.headers.len(),
+            "kept": c.recent.len(),
+            "pruned_outputs": c.pruned_outputs,
+            "bytes_before": c.bytes_before,
+            "bytes_after": c.bytes_after,
+            "result": result_json(&s.compacted_verdict),
+            "tampered": result_json(&s.tampered_history),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4o: Proof of knowledge of an opening
+// ---------------------------------------------------------------------------
+fn step4o_opening_proof(s: &Scenario, report: &mut Report) {
+    let proof = &s.opening_proof;
+    say!(report, "--- Step 4o: Proving you can open a commitment ---");
+    say!(report, "  Alice proves she knows (v, r) with C_input = v*G + r*H, revealing neither:");
+    say!(report, "    A = k_v*G + k_r*H = {}", proof.nonce);
+    say!(report, "    e = hash(C, A), z_v = k_v + e*v = {}, z_r = k_r + e*r = {}", proof.z_value, proof.z_blinding);
+    say!(report, "  Verifier checks z_v*G + z_r*H = A + e*C_input => {}", outcome(&s.opening_verdict));
+    say!(report, "  The same proof presented for C_bob (e changes, so it breaks) => {}\n", outcome(&s.opening_replayed));
+    report.record(
+        "opening_proof",
+        json!({
+            "commitment": s.c_input.as_i64(),
+            "nonce": proof.nonce,
+            "z_value": proof.z_value,
+            "z_blinding": proof.z_blinding,
+            "result": result_json(&s.opening_verdict),
+            "replayed_for_c_bob": result_json(&s.opening_replayed),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4p: Equality proof — two commitments, one value
+// ---------------------------------------------------------------------------
+fn step4p_equality_proof(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4p: Proving two commitments hide the same value ---");
+    say!(report, "  If C1 and C2 hide the same v, then C1 - C2 = (r1 - r2)*H: a commitment to zero.");
+    say!(report, "  Proving knowledge of r1 - r2 (Schnorr over H) proves the values are equal.");
+    say!(report, "  Bob's 5 under the blinding of step 2 and of step 4d:");
+    say!(report, "    C_bob = {}, C_bob' = {}", s.c_bob, s.c_bob_own);
+    say!(report, "    verify_equal(C_bob, C_bob')     => {}", outcome(&s.equality_verdict));
+    say!(report, "    same proof for (C_bob, C_input) => {}", outcome(&s.equality_swapped));
+    match &s.equality_refusal {
+        Some(e) => say!(report, "  Proving C_bob (5) equals C_input (10): the honest prover refuses ({}).\n", e),
+        None => say!(report, "  Proving C_bob (5) equals C_input (10): the prover did not refuse!\n"),
+    }
+    report.record(
+        "equality_proof",
+        json!({
+            "c1": s.c_bob.as_i64(),
+            "c2": s.c_bob_own.as_i64(),
+            "result": result_json(&s.equality_verdict),
+            "wrong_pair": result_json(&s.equality_swapped),
+            "refusal": s.equality_refusal.as_ref().map(|e| e.to_string()),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4q: Public-value proof — revealing an amount but not the blinding
+// ---------------------------------------------------------------------------
+fn step4q_value_proof(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4q: Proving a commitment holds a public value ---");
+    say!(report, "  Some amounts are meant to be public (a fee, a burn, an audit). C commits to exactly v");
+    say!(report, "  when C - v*G = r*H, so a Schnorr proof for r shows it without revealing r.");
+    say!(report, "  Alice proves C_input = {} holds 10:", s.c_input);
+    say!(report, "    verify_value(C_input, 10) => {}", outcome(&s.value_verdict));
+    say!(report, "    verify_value(C_input, 11) => {}\n", outcome(&s.value_wrong));
+    report.record(
+        "value_proof",
+        json!({
+            "commitment": s.c_input.as_i64(),
+            "value": 10,
+            "result": result_json(&s.value_verdict),
+            "wrong_value": result_json(&s.value_wrong),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4r: OR-proof — a committed bit
+// ---------------------------------------------------------------------------
+fn step4r_bit_proof(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4r: Proving a commitment hides 0 or 1 ---");
+    say!(report, "  An OR-proof shows \"C = x*H\" (bit 0) OR \"C - G = x*H\" (bit 1). The prover answers the");
+    say!(report, "  true branch and simulates the other; the two sub-challenges must add up to e = hash(...).");
+    for (proof, result) in s.bit_proofs.iter().zip(&s.bit_verdicts) {
+        say!(
+            report,
+            "    C = {:<19} e0 = {:<19} e1 = {:<19} => {}",
+            proof.commitment,
+            proof.e0,
+            proof.e1,
+            outcome(result)
+        );
+    }
+    say!(report, "  Both proofs look alike: nothing shows which commitment holds the 1.");
+    say!(report, "  The proof for the 1 presented for the 0's commitment => {}", outcome(&s.bit_swapped));
+    if let Some(e) = &s.bit_refusal {
+        say!(report, "  Proving a committed 2 is a bit: the honest prover refuses ({}).", e);
+    }
+    say!(report, "  The range proof of step 4b is one such OR-proof per bit.\n");
+    report.record(
+        "bit_proof",
+        json!({
+            "proofs": s
+                .bit_proofs
+                .iter()
+                .zip(&s.bit_verdicts)
+                .map(|(proof, result)| json!({ "commitment": proof.commitment.as_i64(), "result": result_json(result) }))
+                .collect::<Vec<_>>(),
+            "swapped": result_json(&s.bit_swapped),
+            "refusal": s.bit_refusal.as_ref().map(|e| e.to_string()),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4s: Interval proof — a payment under a cap
+// ---------------------------------------------------------------------------
+fn step4s_interval_proof(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4s: Proving a payment is under a cap ---");
+    say!(report, "  Payments here may not exceed {}. min <= v <= max is two range proofs: one that v - min", CAP);
+    say!(report, "  is in range (for C - min*G) and one that max - v is (for max*G - C, blinding -r).");
+    say!(report, "  Bob's payment C_bob = {} in [0, {}]: {}", s.c_bob, CAP, s.cap_proof);
+    say!(report, "    verify for [0, {}] => {}", CAP, outcome(&s.cap_verdict));
+    say!(report, "    verify for [0, 4] => {}", outcome(&s.cap_lowered));
+    if let Some(e) = &s.cap_refusal {
+        say!(report, "  A payment of 7: the honest prover refuses ({}).", e);
+    }
+    say!(report);
+    report.record(
+        "interval_proof",
+        json!({
+            "commitment": s.c_bob.as_i64(),
+            "min": 0,
+            "max": CAP,
+            "result": result_json(&s.cap_verdict),
+            "lower_cap": result_json(&s.cap_lowered),
+            "refusal": s.cap_refusal.as_ref().map(|e| e.to_string()),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4t: Set membership — denomination-restricted payments
+// ---------------------------------------------------------------------------
+fn step4t_membership_proof(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4t: Proving a payment is an allowed denomination ---");
+    say!(report, "  Payments here must be one of {:?}. C holds s_i exactly when C - s_i*G = r*H, so an", DENOMINATIONS);
+    say!(report, "  OR over the value proofs of step 4q (one real, the others simulated) shows C holds one of");
+    say!(report, "  them without saying which.");
+    say!(
+        report,
+        "  Bob's payment C_bob = {}: {} nonces, {} sub-challenges, {} responses",
+        s.c_bob,
+        s.membership_proof.nonces.len(),
+        s.membership_proof.challenges.len(),
+        s.membership_proof.z.len()
+    );
+    say!(report, "    verify for {:?}  => {}", DENOMINATIONS, outcome(&s.membership_verdict));
+    say!(report, "    verify for [1, 10, 50, 100] => {}", outcome(&s.membership_other_set));
+    if let Some(e) = &s.membership_refusal {
+        say!(report, "  A payment of 7: the honest prover refuses ({}).", e);
+    }
+    say!(report);
+    report.record(
+        "membership_proof",
+        json!({
+            "commitment": s.c_bob.as_i64(),
+            "set": DENOMINATIONS,
+            "result": result_json(&s.membership_verdict),
+            "other_set": result_json(&s.membership_other_set),
+            "refusal": s.membership_refusal.as_ref().map(|e| e.to_string()),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4u: Aggregate range proof — one proof for every output
+// ---------------------------------------------------------------------------
+fn step4u_aggregate_range_proof(s: &Scenario, report: &mut Report) {
+    let separate: usize = s.built_tx.outputs.iter().filter_map(|o| o.proof.as_ref()).map(|p| p.to_bytes().len()).sum();
+    let aggregate = s.aggregated_tx.aggregate_proof.as_ref().expect("built with an aggregate proof");
+    let (before, after) = (s.built_tx.to_bytes().len(), s.aggregated_tx.to_bytes().len());
+    say!(report, "--- Step 4u: One range proof for all outputs ---");
+    say!(report, "  Step 4g's transaction proves each of its 3 outputs separately. Proving all their bits");
+    say!(report, "  together under one challenge lets the proof send e instead of every bit's t0, t1:");
+    say!(report, "  the verifier recomputes t_j = s_j*H - e_j*(C_i - j*G) and checks that they hash to e.");
+    say!(report, "    3 range proofs:     {} bytes", separate);
+    say!(report, "    1 aggregate proof:  {} bytes {}", aggregate.to_bytes().len(), aggregate);
+    say!(report, "    whole transaction:  {} bytes -> {} bytes", before, after);
+    say!(report, "  Transaction::verify() => {}", outcome(&s.aggregated_verdict));
+    say!(report, "  The same proof with the outputs in another order => {}", outcome(&s.aggregated_reordered));
+    say!(report, "  Each bit still costs 5 numbers; Bulletproofs make an aggregate logarithmic in size.\n");
+    report.record(
+        "aggregate_range_proof",
+        json!({
+            "transaction": to_json(&s.aggregated_tx),
+            "separate_bytes": separate,
+            "aggregate_bytes": aggregate.to_bytes().len(),
+            "transaction_bytes": { "separate": before, "aggregate": after },
+            "result": result_json(&s.aggregated_verdict),
+            "reordered": result_json(&s.aggregated_reordered),
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 4v: Batch verification — many transactions, one check
+// ---------------------------------------------------------------------------
+fn batch_json(result: &Result<BatchReport, CtError>) -> serde_json::Value {
+    match result {
+        Ok(report) => json!({
+            "ok": true,
+            "transactions": report.transactions,
+            "equations": report.equations,
+            "multiplications": report.multiplications,
+            "batched_multiplications": report.batched_multiplications,
+            "separate_additions": report.separate_additions,
+            "msm_additions": report.msm_additions,
+        }),
+        Err(e) => json!({ "ok": false, "error": e.to_string() }),
+    }
+}
+
+fn step4v_batch_verification(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 4v: Verifying many transactions at once ---");
+    say!(report, "  Every check above is an equation sum(s_i * P_i) = 0: a balance, a kernel signature, each");
+    say!(report, "  branch of each bit's OR-proof. verify_batch adds them up with random weights rho_k (hashed");
+    say!(report, "  from all of them) and checks only the sum; terms on the same element (G, H) merge.");
+    match &s.batch {
+        Ok(batch) => {
+            say!(report, "  The {} valid transactions of this walkthrough:", batch.transactions);
+            say!(report, "    {} equations, {} scalar multiplications one by one", batch.equations, batch.multiplications);
+            say!(
+                report,
+                "    1 combined equation, {} scalar multiplications ({:.1}x fewer) => ok",
+                batch.batched_multiplications,
+                batch.speedup()
+            );
+            say!(
+                report,
+                "    as one multi-scalar multiplication (Pippenger): {} group additions, not {} by double-and-add",
+                batch.msm_additions,
+                batch.separate_additions
+            );
+        }
+        Err(e) => say!(report, "  The valid transactions of this walkthrough => REJECTED ({})", e),
+    }
+    match &s.batch_with_attack {
+        Ok(_) => say!(report, "  With the negative-change attack (below) added => ok"),
+        Err(e) => say!(report, "  With the negative-change attack (below) added => REJECTED ({})", e),
+    }
+    say!(report, "  On a failed batch the transactions are checked one by one to name the culprit. On a");
+    say!(report, "  real curve each of those additions is a point addition, most of the verifier's work.\n");
+    report.record(
+        "batch_verification",
+        json!({ "valid": batch_json(&s.batch), "with_attack": batch_json(&s.batch_with_attack) }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// STEP 5: Why amounts stay secret
+// ---------------------------------------------------------------------------
+fn step5_secrecy(_: &Scenario, report: &mut Report) {
+    say!(report, "--- Step 5: Why amounts stay secret ---");
+    say!(report, "  Given only C = v*G + r*H, there are infinitely many (v, r) that give the same C.");
+    say!(report, "  So from C_input, C_bob, C_change one cannot deduce 10, 5, or 5.");
+    say!(report, "  Verification only needed the equality C_input = C_bob + C_change.\n");
+}
+
+// ---------------------------------------------------------------------------
+// DEMO: Negative value attack — why range proofs are required
+// ---------------------------------------------------------------------------
+fn attack_balance(s: &Scenario, report: &mut Report) {
+    say!(report, "{}", "=".repeat(60));
+    say!(report, "DEMO: How a negative value breaks the system");
+    say!(report, "{}", "=".repeat(60));
+    say!(report, "\n--- Attack: Malicious transaction with negative \"change\" ---");
+    say!(report, "  Attacker has input 10 but wants to send 15 to Bob (creating 5 from nothing).");
+    say!(report, "  They use a NEGATIVE change: value_change = -5.");
+    say!(report, "  Math still balances: 10 = 15 + (-5), so commitment equation holds.\n");
+
+    say!(report, "  C_input (10)  = {}", s.c_input_attack);
+    say!(report, "  C_bob (15)    = {}", s.c_bob_attack);
+    say!(report, "  C_change (-5) = {}", s.c_change_attack);
+    say!(report, "  π_change      = {}", s.pi_change_attack);
+    say!(report, "  C_bob + C_change = {}", s.sum_outputs_attack);
+    say!(report, "  verify_balance   => {}", outcome(&s.attack_balance));
+    say!(report, "\n  Commitment verification PASSES even though 5 units were created from thin air!");
+    if let Some(e) = &s.honest_refusal {
+        say!(report, "  (An honest wallet would refuse to prove this output: {}.)", e);
+    }
+    say!(report);
+}
+
+fn attack_range_proof(s: &Scenario, report: &mut Report) {
+    let num_bits = params::active().range_bits;
+    say!(report, "--- Rejecting the attack with a range proof ---");
+    say!(report, "  Verifier checks (C_change, π_change) without knowing the value:");
+    say!(report, "  verify(C_change, π_change) => {}", outcome(&s.rp_change_attack));
+    say!(report, "  Every bit OR-proof passes (each C_i really hides a 0 or 1), but:");
+    say!(report, "    sum(2^i * C_i) = {}", range_proof_recompose(&s.pi_change_attack));
+    say!(report, "    C_change       = {}", s.c_change_attack);
+    say!(
+        report,
+        "  With {} bits the attacker can only encode -5 as {}, which is a different commitment.",
+        num_bits,
+        s.value_change_attack.as_i64().rem_euclid(1i64 << num_bits)
+    );
+    say!(report, "  Transaction::verify() => {}", outcome(&s.attack_verdict));
+    say!(report, "  The malicious transaction is REJECTED because π fails verification (value was negative).\n");
+    report.record(
+        "negative_value_attack",
+        json!({
+            "commitments": {
+                "input": s.c_input_attack.as_i64(),
+                "bob": s.c_bob_attack.as_i64(),
+                "change": s.c_change_attack.as_i64(),
+            },
+            "change_proof": proof_json(&s.pi_change_attack),
+            "balance": result_json(&s.attack_balance),
+            "change_range_proof": result_json(&s.rp_change_attack),
+            "change_recomposed": range_proof_recompose(&s.pi_change_attack).as_i64(),
+            "transaction": result_json(&s.attack_verdict),
+        }),
+    );
+}
+
+fn range_proofs_explained(_: &Scenario, report: &mut Report) {
+    say!(report, "--- Why a range proof is required ---");
+    say!(report, "  Pedersen commitments only prove sum(inputs) = sum(outputs).");
+    say!(report, "  They do NOT prove that each value is non-negative or bounded.");
+    say!(report, "  Without range proofs, anyone could use negative \"change\" to inflate the supply.");
+    say!(report, "  A range proof proves (without revealing the amount) that a committed value v");
+    say!(report, "  lies in a valid range, e.g. 0 <= v < 2^64. Then negative or huge values are rejected.\n");
+
+    // How a range proof is created and verified (conceptual)
+    say!(report, "--- How a range proof is created (prover side) ---");
+    say!(report, "  Inputs: commitment C = v*G + r*H, and the prover's secret (v, r).");
+    say!(report, "  Goal: prove that 0 <= v < 2^n (e.g. n=64) WITHOUT revealing v or r.");
+    say!(report, "  Bit-decomposition (what this demo does):");
+    say!(report, "    1. Write v in binary: v = b_0 + 2*b_1 + 4*b_2 + ... (each b_i is 0 or 1).");
+    say!(report, "    2. Commit to each bit: C_i = b_i*G + r_i*H, choosing r_i so sum(2^i * r_i) = r.");
+    say!(report, "       Then sum(2^i * C_i) = C exactly.");
+    say!(report, "    3. For each C_i, give an OR-proof: \"C_i = x*H\" (bit 0) OR \"C_i - G = x*H\" (bit 1).");
+    say!(report, "       The true branch is a Schnorr proof; the other is simulated, so no one learns which.");
+    say!(report, "    4. Challenges come from a hash of the proof so far (Fiat–Shamir), so the prover");
+    say!(report, "       cannot choose them.");
+    say!(report, "  Output: proof pi = {{C_i, OR-proof_i}} for i < n. v and r stay secret.");
+    say!(report, "  Bulletproofs prove the same bit statement with an inner-product argument (~700 bytes).\n");
+
+    say!(report, "--- How a range proof is verified (verifier side) ---");
+    say!(report, "  Inputs: commitment C and proof pi (and public parameters G, H, range bound 2^n).");
+    say!(report, "  Verifier does NOT know v or r.");
+    say!(report, "  Steps:");
+    say!(report, "    1. Check that pi is well-formed: exactly n bit commitments and OR-proofs.");
+    say!(report, "    2. Check every OR-proof: e_0 + e_1 = hash(...) and s_j*H = t_j + e_j*(C_i - j*G).");
+    say!(report, "    3. Check sum(2^i * C_i) = C. With all bits 0/1, this means C commits to v in [0, 2^n).");
+    say!(report, "  If all checks pass => \"C commits to a value in range\". If not => reject.");
+    say!(report, "  The verifier never learns v or r, only that the range condition holds.\n");
+
+    // Where Bulletproofs fit in real systems
+    say!(report, "--- Where Bulletproofs fit in real systems ---");
+    say!(report, "  In production (e.g. Monero, Mimblewimble):");
+    say!(report, "  - Each input and output commitment comes with a RANGE PROOF.");
+    say!(report, "  - Bulletproofs are short (~700 bytes) and prove 0 <= v < 2^64 for C = v*G + r*H");
+    say!(report, "    without revealing v or r (zero-knowledge).");
+    say!(report, "  - Verifiers check: (1) sum(input commitments) = sum(output commitments),");
+    say!(report, "    (2) each range proof is valid. Then no negative or overflow amounts are possible.\n");
+}
+
+// ---------------------------------------------------------------------------
+// BigField mode: same math with 256-bit parameters and no overflow
+// ---------------------------------------------------------------------------
+fn big_field_mode(s: &Scenario, report: &mut Report) {
+    say!(report, "--- BigField mode: realistic 256-bit parameters ---");
+    let huge_value = 1i64 << 62;
+    match huge_value.checked_mul(params::active().g) {
+        Some(term) => say!(report, "  i64 math: 2^62 * G = {}", term),
+        None => say!(report, "  i64 math: 2^62 * G does not fit in 64 bits (it would wrap BEFORE the mod p)."),
+    }
+    say!(report, "  BigField uses arbitrary-precision integers instead:");
+    say!(report, "    p = 2^255 - 19 = {}", s.field.modulus);
+    say!(report, "    G = {}", s.field.g);
+    say!(report, "    H = {}", s.field.h);
+    say!(report, "  Input 2^64 + 10 -> Bob 2^64 + change 10, with ~250-bit blindings:");
+    say!(report, "    C_input          = {}", s.big_c_input);
+    say!(report, "    C_bob + C_change = {}", s.big_sum_outputs);
+    say!(report, "    Match? {}\n", s.big_c_input == s.big_sum_outputs);
+    report.record(
+        "big_field",
+        json!({
+            "modulus": s.field.modulus.to_string(),
+            "g": s.field.g.to_string(),
+            "h": s.field.h.to_string(),
+            "commitment_input": s.big_c_input.to_string(),
+            "sum_outputs": s.big_sum_outputs.to_string(),
+            "balanced": s.big_c_input == s.big_sum_outputs,
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Toy elliptic curve: the same transaction with points instead of integers
+// ---------------------------------------------------------------------------
+fn curve_points(s: &Scenario, report: &mut Report) {
+    say!(report, "--- Toy elliptic curve: C = v*G + r*H as actual points ---");
+    say!(
+        report,
+        "  Curve y^2 = x^3 + {}x + {} over F_{}, {} points (prime order).",
+        toy_curve::A,
+        toy_curve::B,
+        toy_curve::P,
+        toy_curve::ORDER
+    );
+    say!(
+        report,
+        "  G = {}, H = {}  (on curve: {})",
+        toy_curve::G,
+        toy_curve::H,
+        toy_curve::is_on_curve(toy_curve::G) && toy_curve::is_on_curve(toy_curve::H)
+    );
+    say!(report, "  C_input  = {}*G + {}*H = {}", s.value_input, s.r_input, s.point_input);
+    say!(report, "  C_bob    = {}*G + {}*H = {}", s.value_to_bob, s.r_bob, s.point_bob);
+    say!(report, "  C_change = {}*G + {}*H = {}", s.value_change, s.r_change, s.point_change);
+    say!(report, "  C_bob + C_change (point addition) = {}", s.point_sum);
+    say!(
+        report,
+        "  C_input - (C_bob + C_change)      = {}",
+        toy_curve::add(s.point_input, toy_curve::neg(s.point_sum))
+    );
+    let [doubling, table] = s.curve_commit_times.map(|time| time.as_secs_f64() * 1000.0);
+    let speedup = doubling / table.max(f64::MIN_POSITIVE);
+    say!(report, "  Fixed-base tables (`toy_curve::precompute`) hold every 4-bit digit's multiple of G and H:");
+    say!(
+        report,
+        "  {} commitments: {:.2} ms by double-and-add, {:.2} ms from the tables ({:.1}x faster, same points: {}).",
+        CURVE_COMMITS,
+        doubling,
+        table,
+        speedup,
+        s.tables_agree
+    );
+    say!(report, "  Same homomorphic check, now with curve points — exactly what real systems do.\n");
+    report.record(
+        "toy_curve",
+        json!({
+            "p": toy_curve::P,
+            "order": toy_curve::ORDER,
+            "g": toy_curve::G.to_string(),
+            "h": toy_curve::H.to_string(),
+            "commitment_input": s.point_input.to_string(),
+            "commitment_bob": s.point_bob.to_string(),
+            "commitment_change": s.point_change.to_string(),
+            "sum_outputs": s.point_sum.to_string(),
+            "balanced": s.point_input == s.point_sum,
+            // Without the timings, which differ from run to run.
+            "fixed_base": { "commitments": CURVE_COMMITS, "same_points": s.tables_agree },
+        }),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Final message
+// ---------------------------------------------------------------------------
+fn final_message(s: &Scenario, report: &mut Report) {
+    say!(report, "{}", "=".repeat(60));
+    if s.balance.is_ok() {
+        say!(report, "Transaction verified without revealing amounts.");
+    } else {
+        say!(report, "Verification failed.");
+    }
+    say!(report, "{}", "=".repeat(60));
+    report.record("verified", json!(s.balance.is_ok()));
+}
@@ -0,0 +1,136 @@
+//! Paying to a published address instead of handing over (v, r). The receiver
+//! publishes a stealth address (A, B) = (a*H, b*H): a scan key and a spend key.
+//! The sender picks a fresh e, attaches R = e*H to the output and derives
+//! everything from the shared secret S = e*A = a*R (Diffie-Hellman):
+//!
+//! - the one-time key P = SHA-256("ct/ecdh/key" ‖ S)*H + B, a fresh key for this
+//!   output alone. Its secret x = SHA-256("ct/ecdh/key" ‖ S) + b needs b, so only
+//!   the receiver could sign with it.
+//! - the blinding r = SHA-256("ct/ecdh/blinding" ‖ S).
+//! - the amount, XORed with the pad SHA-256("ct/ecdh/amount" ‖ S).
+//!
+//! Only the holder of a can compute S from R. So the receiver scans every output's
+//! note and recomputes P (`detect`); on a match it recovers (v, r). Neither R nor P
+//! has anything in common with A or B, and two payments to the same address share
+//! nothing either, so the chain cannot tell who was paid. This is Monero's scheme,
+//! with A used for finding payments and B for owning them.
+//!
+//! The sender knows r too. In Monero that does not matter, because spending is
+//! signed with x; in a Mimblewimble-style chain, where r is the spending key, the
+//! receiver should move the output to a blinding of its own.
+//! Toy group: S = a*e*H mod p, and a = A / H is one division away.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::commitment::{mod_mul, Blinding, Commitment, Opening, Scalar};
+use crate::error::CtError;
+use crate::kernel::public_key;
+use crate::rng::ToyRng;
+
+/// What the sender attaches to an output for the receiver: R = e*H, the one-time
+/// key P and the amount XOR the pad. Public, but readable only with the scan secret.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputNote {
+    pub ephemeral: Commitment,
+    #[serde(rename = "key")]
+    pub one_time_key: Commitment,
+    #[serde(rename = "amount")]
+    pub encrypted_amount: u64,
+}
+
+/// A receiver's published stealth address (A, B) = (a*H, b*H), written addr/A/B (in hex with `{:#}`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Address {
+    pub scan: Commitment,
+    pub spend: Commitment,
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return write!(f, "addr/{:#}/{:#}", self.scan, self.spend);
+        }
+        write!(f, "addr/{}/{}", self.scan, self.spend)
+    }
+}
+
+impl FromStr for Address {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || CtError::MalformedInput(format!("'{}' is not an address addr/scan/spend", s));
+        let (scan, spend) = s.strip_prefix("addr/").and_then(|rest| rest.split_once('/')).ok_or_else(malformed)?;
+        Ok(Address { scan: scan.parse().map_err(|_| malformed())?, spend: spend.parse().map_err(|_| malformed())? })
+    }
+}
+
+serde_as_text!(Address);
+
+/// secret * public: e*A for the sender, a*R for the receiver; both are a*e*H.
+pub fn shared_secret(secret: Blinding, public: Commitment) -> Commitment {
+    Commitment::from_i64(mod_mul(secret.as_i64(), public.as_i64()))
+}
+
+fn hash(label: &str, shared: Commitment) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(label.as_bytes());
+    hasher.update(shared.as_i64().to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// The output blinding both sides derive from S, below 2^32 like `Blinding::random`.
+fn blinding(shared: Commitment) -> Blinding {
+    let digest = hash("ct/ecdh/blinding", shared);
+    Blinding::new(u32::from_le_bytes(digest[..4].try_into().expect("SHA-256 has 32 bytes")) as i64)
+}
+
+/// SHA-256("ct/ecdh/key" ‖ S): what P adds to B, and x adds to b.
+fn key_offset(shared: Commitment) -> Blinding {
+    let digest = hash("ct/ecdh/key", shared);
+    Blinding::new(u32::from_le_bytes(digest[..4].try_into().expect("SHA-256 has 32 bytes")) as i64)
+}
+
+fn amount_pad(shared: Commitment) -> u64 {
+    u64::from_le_bytes(hash("ct/ecdh/amount", shared)[..8].try_into().expect("SHA-256 has 32 bytes"))
+}
+
+/// The sender's side: the blinding for an output of `value` to `address`, and its note.
+pub fn send_to(address: Address, value: Scalar, rng: &mut ToyRng) -> (Blinding, OutputNote) {
+    let ephemeral = Blinding::random(rng);
+    let shared = shared_secret(ephemeral, address.scan);
+    let note = OutputNote {
+        ephemeral: public_key(ephemeral),
+        one_time_key: public_key(key_offset(shared)) + address.spend,
+        encrypted_amount: value.as_i64() as u64 ^ amount_pad(shared),
+    };
+    (blinding(shared), note)
+}
+
+/// Whether `note` pays the address with scan secret a and spend key B: a*R gives S,
+/// and S gives P back only for the address the sender used. Needs no spend secret,
+/// so a scanning service holding a could do it.
+pub fn detect(scan_secret: Blinding, spend: Commitment, note: &OutputNote) -> bool {
+    let shared = shared_secret(scan_secret, note.ephemeral);
+    public_key(key_offset(shared)) + spend == note.one_time_key
+}
+
+/// The one-time secret x with x*H = P, which needs the spend secret b too.
+pub fn one_time_secret(scan_secret: Blinding, spend_secret: Blinding, note: &OutputNote) -> Blinding {
+    key_offset(shared_secret(scan_secret, note.ephemeral)) + spend_secret
+}
+
+/// The receiver's side: the opening of `commitment` if `note` pays the address
+/// (a, B), None if the output belongs to someone else.
+pub fn receive(scan_secret: Blinding, spend: Commitment, commitment: Commitment, note: &OutputNote) -> Option<Opening> {
+    if !detect(scan_secret, spend, note) {
+        return None;
+    }
+    let shared = shared_secret(scan_secret, note.ephemeral);
+    let value = (note.encrypted_amount ^ amount_pad(shared)) as i64;
+    let opening = Opening::new(Scalar::new(value), blinding(shared));
+    (value >= 0 && opening.commit() == commitment).then_some(opening)
+}
@@ -0,0 +1,410 @@
+//! Elements (Liquid) transactions, decoded far enough to line their confidential
+//! fields up with this demo's (`ct inspect elements`):
+//!
+//! - a value commitment (33 bytes: 0x08/0x09, then x) is a `Commitment`, but on
+//!   secp256k1: v*A + r*G for the output's asset generator A, where here it is
+//!   v*G + r*H mod p (or v*A + r*H with `assets`);
+//! - an asset commitment (0x0a/0x0b, then x) is the blinded generator A = H_a + t*H
+//!   of `assets`;
+//! - its range proof is a `RangeProof`, but over base-4 digits with Borromean ring
+//!   signatures, and it can prove v in [min, min + 10^e * (2^m - 1)] instead of [0, 2^n);
+//! - its surjection proof is `assets::SurjectionProof`;
+//! - the fee is an output of its own, explicit and with an empty script, where here
+//!   it is `Transaction::fee`; an input can issue new coins, as with `issuance`.
+//!
+//! The encoding: version (u32), a flag byte (1 = witness follows), a varint count of
+//! inputs and of outputs, the lock time (u32), then the witness. All integers are
+//! little-endian except explicit values (u64, big-endian); a varint is Bitcoin's
+//! (one byte, or 0xfd/0xfe/0xff then 2/4/8 bytes). A field that may be confidential is
+//! 0x00 (null), 0x01 and the explicit value, or a prefix and the 32-byte x.
+//! - input: previous txid (32 bytes), index (u32; bit 31 = issuance, bit 30 = peg-in),
+//!   script (varint length + bytes), sequence (u32), then with bit 31: blinding nonce
+//!   and entropy (32 bytes each), amount and inflation keys (values)
+//! - output: asset, value, nonce (0x02/0x03 for an ECDH key), script
+//! - input witness: amount and inflation keys range proofs, script witness and peg-in
+//!   witness (varint count of byte strings each); output witness: surjection proof,
+//!   range proof
+//!
+//! `checks` does what it can from the transaction alone: every commitment's x must be
+//! on the curve (x^3 + 7 a square mod p, see `secp256k1`), every committed value needs
+//! a range proof and every committed asset a surjection proof, and the proofs' headers
+//! must be well formed. It cannot check the proofs themselves, nor that the
+//! commitments balance: that needs the spent outputs' commitments from their
+//! transactions.
+
+use std::fmt;
+
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+use crate::error::CtError;
+use crate::secp256k1;
+use crate::wire::{to_hex, Reader};
+
+/// A txid or asset id, shown byte-reversed as Elements shows them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ElementsId(pub [u8; 32]);
+
+impl fmt::Display for ElementsId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut reversed = self.0;
+        reversed.reverse();
+        f.write_str(&to_hex(&reversed))
+    }
+}
+
+/// A field that may be hidden behind a commitment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Field<T> {
+    Null,
+    Explicit(T),
+    /// The prefix and x of a secp256k1 point.
+    Committed([u8; 33]),
+}
+
+impl<T> Field<T> {
+    /// Prefix 0 or 1, or one of the two `committed` prefixes.
+    fn read(
+        reader: &mut Reader<'_>,
+        committed: [u8; 2],
+        explicit: impl FnOnce(&mut Reader<'_>) -> Result<T, CtError>,
+    ) -> Result<Self, CtError> {
+        match reader.u8()? {
+            0 => Ok(Field::Null),
+            1 => Ok(Field::Explicit(explicit(reader)?)),
+            prefix if committed.contains(&prefix) => {
+                let mut point = [prefix; 33];
+                point[1..].copy_from_slice(&reader.take::<32>()?);
+                Ok(Field::Committed(point))
+            }
+            prefix => {
+                let at = reader.position() - 1;
+                Err(CtError::MalformedInput(format!("unknown prefix 0x{:02x} at byte {}", prefix, at)))
+            }
+        }
+    }
+
+    pub fn is_committed(&self) -> bool {
+        matches!(self, Field::Committed(_))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Field<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Null => f.write_str("null"),
+            Field::Explicit(value) => write!(f, "explicit {}", value),
+            Field::Committed(point) => write!(f, "committed {}", to_hex(point)),
+        }
+    }
+}
+
+/// New coins issued by an input, or more of an asset it already issued.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputIssuance {
+    pub blinding_nonce: [u8; 32],
+    pub entropy: [u8; 32],
+    pub amount: Field<u64>,
+    pub inflation_keys: Field<u64>,
+    pub amount_range_proof: Vec<u8>,
+    pub inflation_keys_range_proof: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxIn {
+    pub prev_txid: ElementsId,
+    pub prev_index: u32,
+    pub pegin: bool,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+    pub issuance: Option<InputIssuance>,
+    pub script_witness: Vec<Vec<u8>>,
+    pub pegin_witness: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxOut {
+    pub asset: Field<ElementsId>,
+    pub value: Field<u64>,
+    /// The sender's ECDH key, from which the receiver rewinds the range proof.
+    pub nonce: Field<ElementsId>,
+    pub script_pubkey: Vec<u8>,
+    pub surjection_proof: Vec<u8>,
+    pub range_proof: Vec<u8>,
+}
+
+impl TxOut {
+    /// Elements' fee outputs have an empty script.
+    pub fn is_fee(&self) -> bool {
+        self.script_pubkey.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElementsTx {
+    pub version: u32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+    /// Double SHA-256 of the transaction without its witness.
+    pub txid: ElementsId,
+}
+
+/// What a range proof's header says it covers: v in [min_value, max_value], in steps
+/// of 10^exponent, with `mantissa` bits; an exponent of -1 means v = min_value exactly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RangeProofHeader {
+    pub exponent: i32,
+    pub mantissa: u32,
+    pub min_value: u64,
+    pub max_value: u64,
+}
+
+/// What a surjection proof's header says: it picks its asset out of `used` of the
+/// first `inputs` inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SurjectionHeader {
+    pub inputs: usize,
+    pub used: usize,
+}
+
+fn varint(reader: &mut Reader<'_>) -> Result<u64, CtError> {
+    Ok(match reader.u8()? {
+        0xfd => u16::from_le_bytes(reader.take()?) as u64,
+        0xfe => reader.u32()? as u64,
+        0xff => reader.u64()?,
+        byte => byte as u64,
+    })
+}
+
+/// A varint length, checked against what is left so a bad one cannot allocate much.
+fn length(reader: &mut Reader<'_>) -> Result<usize, CtError> {
+    let len = varint(reader)?;
+    if len > reader.remaining() as u64 {
+        return Err(CtError::MalformedInput(format!("length {} at byte {} runs past the end", len, reader.position())));
+    }
+    Ok(len as usize)
+}
+
+fn bytes(reader: &mut Reader<'_>) -> Result<Vec<u8>, CtError> {
+    let len = length(reader)?;
+    Ok(reader.slice(len)?.to_vec())
+}
+
+fn stack(reader: &mut Reader<'_>) -> Result<Vec<Vec<u8>>, CtError> {
+    (0..length(reader)?).map(|_| bytes(reader)).collect()
+}
+
+fn id(reader: &mut Reader<'_>) -> Result<ElementsId, CtError> {
+    Ok(ElementsId(reader.hash()?))
+}
+
+fn value(reader: &mut Reader<'_>) -> Result<Field<u64>, CtError> {
+    Field::read(reader, [0x08, 0x09], |reader| Ok(u64::from_be_bytes(reader.take()?)))
+}
+
+impl ElementsTx {
+    /// Decode a whole transaction; trailing bytes are an error.
+    pub fn from_bytes(raw: &[u8]) -> Result<Self, CtError> {
+        let mut reader = Reader::new(raw);
+        let version = reader.u32()?;
+        let witness = match reader.u8()? {
+            0 => false,
+            1 => true,
+            flags => return Err(CtError::MalformedInput(format!("unknown flags 0x{:02x}", flags))),
+        };
+
+        let mut inputs = Vec::new();
+        for _ in 0..length(&mut reader)? {
+            let prev_txid = id(&mut reader)?;
+            let index = reader.u32()?;
+            // The flags are only in the index of an input that spends something.
+            let flagged = index != u32::MAX;
+            let script_sig = bytes(&mut reader)?;
+            let sequence = reader.u32()?;
+            let issuance = if flagged && index & 1 << 31 != 0 {
+                Some(InputIssuance {
+                    blinding_nonce: reader.hash()?,
+                    entropy: reader.hash()?,
+                    amount: value(&mut reader)?,
+                    inflation_keys: value(&mut reader)?,
+                    amount_range_proof: Vec::new(),
+                    inflation_keys_range_proof: Vec::new(),
+                })
+            } else {
+                None
+            };
+            inputs.push(TxIn {
+                prev_txid,
+                prev_index: if flagged { index & 0x3fff_ffff } else { index },
+                pegin: flagged && index & 1 << 30 != 0,
+                script_sig,
+                sequence,
+                issuance,
+                script_witness: Vec::new(),
+                pegin_witness: Vec::new(),
+            });
+        }
+
+        let mut outputs = Vec::new();
+        for _ in 0..length(&mut reader)? {
+            outputs.push(TxOut {
+                asset: Field::read(&mut reader, [0x0a, 0x0b], id)?,
+                value: value(&mut reader)?,
+                nonce: Field::read(&mut reader, [0x02, 0x03], id)?,
+                script_pubkey: bytes(&mut reader)?,
+                surjection_proof: Vec::new(),
+                range_proof: Vec::new(),
+            });
+        }
+        let lock_time = reader.u32()?;
+        let unwitnessed = [&raw[..4], &[0], &raw[5..reader.position()]].concat();
+
+        if witness {
+            for input in &mut inputs {
+                let (amount_proof, inflation_keys_proof) = (bytes(&mut reader)?, bytes(&mut reader)?);
+                if let Some(issuance) = &mut input.issuance {
+                    issuance.amount_range_proof = amount_proof;
+                    issuance.inflation_keys_range_proof = inflation_keys_proof;
+                }
+                input.script_witness = stack(&mut reader)?;
+                input.pegin_witness = stack(&mut reader)?;
+            }
+            for output in &mut outputs {
+                output.surjection_proof = bytes(&mut reader)?;
+                output.range_proof = bytes(&mut reader)?;
+            }
+        }
+        if reader.remaining() != 0 {
+            return Err(CtError::MalformedInput(format!("{} trailing byte(s)", reader.remaining())));
+        }
+        let txid = ElementsId(Sha256::digest(Sha256::digest(&unwitnessed)).into());
+        Ok(ElementsTx { version, inputs, outputs, lock_time, txid })
+    }
+
+    /// The explicit fee: what the fee outputs pay, per asset.
+    pub fn fees(&self) -> Vec<(ElementsId, u64)> {
+        let mut fees: Vec<(ElementsId, u64)> = Vec::new();
+        for output in self.outputs.iter().filter(|output| output.is_fee()) {
+            if let (Field::Explicit(asset), Field::Explicit(value)) = (&output.asset, &output.value) {
+                match fees.iter_mut().find(|(id, _)| id == asset) {
+                    Some((_, total)) => *total = total.saturating_add(*value),
+                    None => fees.push((*asset, *value)),
+                }
+            }
+        }
+        fees
+    }
+
+    /// Every check that can run here, named by what it is about.
+    pub fn checks(&self) -> Vec<(String, Result<(), CtError>)> {
+        let mut results = Vec::new();
+        for (i, input) in self.inputs.iter().enumerate() {
+            if let Some(issuance) = &input.issuance {
+                let name = format!("input {} issuance", i);
+                results.push((format!("{} amount", name), value_check(&issuance.amount, &issuance.amount_range_proof)));
+                let keys = value_check(&issuance.inflation_keys, &issuance.inflation_keys_range_proof);
+                results.push((format!("{} inflation keys", name), keys));
+            }
+        }
+        for (i, output) in self.outputs.iter().enumerate() {
+            let asset = match &output.asset {
+                Field::Committed(point) => on_curve(point)
+                    .and_then(|()| surjection_proof_header(&output.surjection_proof))
+                    .and_then(|header| match header.inputs == self.inputs.len() {
+                        true => Ok(()),
+                        false => Err(CtError::InvalidProof(format!(
+                            "the surjection proof ranges over {} inputs, not the {} there are",
+                            header.inputs,
+                            self.inputs.len()
+                        ))),
+                    }),
+                _ => no_proof(&output.surjection_proof, "surjection proof"),
+            };
+            results.push((format!("output {} asset", i), asset));
+            results.push((format!("output {} value", i), value_check(&output.value, &output.range_proof)));
+            if let Field::Committed(point) = &output.nonce {
+                results.push((format!("output {} nonce", i), on_curve(point)));
+            }
+        }
+        results
+    }
+}
+
+/// A committed value is on the curve and has a well-formed range proof; any other has none.
+fn value_check(value: &Field<u64>, range_proof: &[u8]) -> Result<(), CtError> {
+    match value {
+        Field::Committed(point) => on_curve(point).and_then(|()| range_proof_header(range_proof).map(|_| ())),
+        _ => no_proof(range_proof, "range proof"),
+    }
+}
+
+fn no_proof(proof: &[u8], what: &str) -> Result<(), CtError> {
+    match proof.is_empty() {
+        true => Ok(()),
+        false => Err(CtError::InvalidProof(format!("an explicit field has a {} of {} bytes", what, proof.len()))),
+    }
+}
+
+/// Is the point's x the x of some secp256k1 point, i.e. is x^3 + 7 a square mod p?
+pub fn on_curve(point: &[u8; 33]) -> Result<(), CtError> {
+    match secp256k1::lift_x(&BigUint::from_bytes_be(&point[1..])) {
+        Some(_) => Ok(()),
+        None => Err(CtError::MalformedInput(format!("{} is not a point on secp256k1", to_hex(point)))),
+    }
+}
+
+/// Read a Borromean range proof's header, as libsecp256k1-zkp does.
+pub fn range_proof_header(proof: &[u8]) -> Result<RangeProofHeader, CtError> {
+    let invalid = |why: &str| CtError::InvalidProof(format!("range proof: {}", why));
+    if proof.len() < 65 {
+        return Err(invalid(&format!("{} bytes is too short", proof.len())));
+    }
+    let flags = proof[0];
+    if flags & 0x80 != 0 {
+        return Err(invalid("reserved flag bit set"));
+    }
+    let (mut exponent, mut mantissa, mut offset) = (-1, 0, 1);
+    if flags & 0x40 != 0 {
+        exponent = (flags & 0x1f) as i32;
+        mantissa = proof[1] as u32 + 1;
+        offset = 2;
+        if exponent > 18 || mantissa > 64 {
+            return Err(invalid(&format!("exponent {} or mantissa {} out of range", exponent, mantissa)));
+        }
+    }
+    let mut min_value = 0;
+    if flags & 0x20 != 0 {
+        min_value = u64::from_be_bytes(proof[offset..offset + 8].try_into().expect("8 bytes"));
+    }
+    let span = match mantissa {
+        0 => 0,
+        bits => u64::MAX >> (64 - bits),
+    };
+    let max_value = 10u64
+        .checked_pow(exponent.max(0) as u32)
+        .and_then(|scale| span.checked_mul(scale))
+        .and_then(|span| span.checked_add(min_value))
+        .ok_or_else(|| invalid("the range overflows 64 bits"))?;
+    Ok(RangeProofHeader { exponent, mantissa, min_value, max_value })
+}
+
+/// Read a surjection proof's header and check its length: input count (u16), a bitmap
+/// of the inputs used, then 32 bytes for the challenge and 32 per input used.
+pub fn surjection_proof_header(proof: &[u8]) -> Result<SurjectionHeader, CtError> {
+    let invalid = |why: String| CtError::InvalidProof(format!("surjection proof: {}", why));
+    if proof.len() < 2 {
+        return Err(invalid("missing".to_string()));
+    }
+    let inputs = u16::from_le_bytes([proof[0], proof[1]]) as usize;
+    let bitmap = proof.get(2..2 + inputs.div_ceil(8)).ok_or_else(|| invalid("truncated bitmap".to_string()))?;
+    let used = bitmap.iter().map(|byte| byte.count_ones() as usize).sum::<usize>();
+    let expected = 2 + bitmap.len() + 32 * (used + 1);
+    if inputs == 0 || inputs > 256 || used == 0 {
+        return Err(invalid(format!("{} of {} inputs used", used, inputs)));
+    }
+    if proof.len() != expected {
+        return Err(invalid(format!("{} bytes, but {} of {} inputs need {}", proof.len(), used, inputs, expected)));
+    }
+    Ok(SurjectionHeader { inputs, used })
+}
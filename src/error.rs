@@ -0,0 +1,70 @@
+//! Errors returned by the library, so callers can tell *which* check failed
+//! (e.g. a range proof vs. the balance equation) instead of getting a bare `false`.
+
+use std::fmt;
+
+use crate::commitment::Commitment;
+use crate::mlsag::KeyImage;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CtError {
+    /// Value does not fit in the range the proof covers (v >= 2^n).
+    OverflowedValue(i64),
+    /// Value is negative; an honest prover refuses to commit to it.
+    NegativeValue(i64),
+    /// Value lies outside the interval an interval proof was asked for.
+    OutOfInterval { value: i64, min: i64, max: i64 },
+    /// sum(inputs) != sum(outputs).
+    BalanceMismatch { inputs: Commitment, outputs: Commitment },
+    /// A proof is well-formed but its verification equations do not hold.
+    InvalidProof(String),
+    /// A transaction spends an output that was already spent.
+    DoubleSpend(Commitment),
+    /// A ring input shows a key image already spent: its output was spent before.
+    KeyImageSpent(KeyImage),
+    /// A transaction spends an output that a pending (mempool) transaction already spends.
+    Conflict(Commitment),
+    /// A transaction spends an output the ledger has never seen.
+    UnknownInput(Commitment),
+    /// A coinbase mints more than the subsidy, or mints while spending inputs.
+    InvalidCoinbase(String),
+    /// An asset issuance breaks a rule: a name issued twice, or a reissuance without a token.
+    InvalidIssuance(String),
+    /// An input spends a locked output without meeting its condition (see `condition`), or
+    /// a height-locked output or kernel comes before its height.
+    Locked(String),
+    /// The wallet's unspent outputs do not cover a payment and its fee.
+    InsufficientFunds { available: i64, needed: i64 },
+    /// Authenticated decryption failed: wrong key or passphrase, or tampered ciphertext.
+    DecryptionFailed,
+    /// Input could not be interpreted (wrong length, unparsable number, ...).
+    MalformedInput(String),
+}
+
+impl fmt::Display for CtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CtError::OverflowedValue(v) => write!(f, "value {} is too large for the range proof", v),
+            CtError::NegativeValue(v) => write!(f, "value {} is negative", v),
+            CtError::OutOfInterval { value, min, max } => write!(f, "value {} is outside [{}, {}]", value, min, max),
+            CtError::BalanceMismatch { inputs, outputs } => {
+                write!(f, "balance mismatch: sum(inputs) = {}, sum(outputs) = {}", inputs, outputs)
+            }
+            CtError::InvalidProof(reason) => write!(f, "invalid proof: {}", reason),
+            CtError::DoubleSpend(c) => write!(f, "double spend: {} was already spent", c),
+            CtError::KeyImageSpent(image) => write!(f, "double spend: key image {} was already spent", image),
+            CtError::Conflict(c) => write!(f, "conflict: {} is already spent by a pending transaction", c),
+            CtError::UnknownInput(c) => write!(f, "unknown input: {} is not in the ledger", c),
+            CtError::InvalidCoinbase(reason) => write!(f, "invalid coinbase: {}", reason),
+            CtError::InvalidIssuance(reason) => write!(f, "invalid issuance: {}", reason),
+            CtError::Locked(reason) => write!(f, "locked: {}", reason),
+            CtError::InsufficientFunds { available, needed } => {
+                write!(f, "insufficient funds: {} needed, {} available", needed, available)
+            }
+            CtError::DecryptionFailed => write!(f, "decryption failed: wrong passphrase or corrupted data"),
+            CtError::MalformedInput(reason) => write!(f, "malformed input: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CtError {}
@@ -0,0 +1,93 @@
+//! `ct exercise`: homework sheets. Each problem is generated from the sheet's seed,
+//! so the instructor runs the same seed with `--key` to get the answer key for exactly
+//! the sheet the students got.
+
+use pedersen_commitment_tx::commitment::{pedersen_commit, Blinding, Scalar};
+use pedersen_commitment_tx::params;
+use pedersen_commitment_tx::range_proof::{range_proof_create_unchecked, range_proof_verify};
+use pedersen_commitment_tx::rng::ToyRng;
+
+/// One problem: what the students are told, and the answer key's line for it.
+pub struct Problem {
+    pub statement: Vec<String>,
+    pub answer: String,
+}
+
+/// The problem kinds, dealt out in this order.
+const KINDS: [fn(&mut ToyRng) -> Problem; 3] = [commitment_problem, balance_problem, invalid_proof_problem];
+
+/// Compute a commitment by hand (small blinding, so a calculator will do).
+fn commitment_problem(rng: &mut ToyRng) -> Problem {
+    let params = params::active();
+    let value = rng.between(0, 1i64 << params.range_bits);
+    let blinding = rng.between(1, 1_000_000);
+    let commitment = pedersen_commit(Scalar::new(value), Blinding::new(blinding));
+    Problem {
+        statement: vec![
+            format!("Compute the commitment C = v*G + r*H mod p for v = {} and r = {},", value, blinding),
+            format!("with p = {}, G = {}, H = {}.", params.modulus, params.g, params.h),
+        ],
+        answer: format!("C = {}*{} + {}*{} = {}", value, params.g, blinding, params.h, commitment),
+    }
+}
+
+/// Find the change blinding that makes a payment balance.
+fn balance_problem(rng: &mut ToyRng) -> Problem {
+    let input = rng.between(20, 1i64 << params::active().range_bits);
+    let fee = rng.between(0, 5);
+    let to_bob = rng.between(1, input - fee);
+    let change = input - fee - to_bob;
+    let (r_input, r_bob) = (rng.between(1, 1_000_000), rng.between(1, 1_000_000));
+    let r_change = r_input - r_bob;
+    let c_change = pedersen_commit(Scalar::new(change), Blinding::new(r_change));
+    Problem {
+        statement: vec![
+            format!("Alice spends an input (v = {}, r = {}) and pays Bob {} (r = {})", input, r_input, to_bob, r_bob),
+            format!("with a fee of {}.", fee),
+            "Which change amount and blinding make sum(in) = sum(out) + fee*G? What is C_change?".to_string(),
+        ],
+        answer: format!(
+            "change v = {} - {} - {} = {}, r = {} - {} = {}, C_change = {}",
+            input, to_bob, fee, change, r_input, r_bob, r_change, c_change
+        ),
+    }
+}
+
+/// Three range proofs, one of which does not verify.
+fn invalid_proof_problem(rng: &mut ToyRng) -> Problem {
+    let bound = 1i64 << params::active().range_bits;
+    let bad = rng.between(0, 3) as usize;
+    let cheat = rng.next_u64() % 2;
+    let mut statement = vec![
+        "Exactly one of these range proofs is invalid. Which one, and why?".to_string(),
+        "(Check each with: ct rangeproof verify --commitment C --proof π)".to_string(),
+    ];
+    let mut answer = String::new();
+    for (i, label) in ["A", "B", "C"].into_iter().enumerate() {
+        let blinding = Blinding::random(rng);
+        let value = if i == bad && cheat == 0 { -rng.between(1, 50) } else { rng.between(0, bound) };
+        let mut commitment = pedersen_commit(Scalar::new(value), blinding);
+        let proof = range_proof_create_unchecked(Scalar::new(value), blinding, rng);
+        if i == bad && cheat == 1 {
+            commitment = pedersen_commit(Scalar::new(value), Blinding::random(rng));
+        }
+        if i == bad {
+            let reason = if cheat == 0 {
+                format!("it was made for v = {}, which is negative", value)
+            } else {
+                "it was made for another commitment (same v, another r)".to_string()
+            };
+            let verdict = range_proof_verify(commitment, &proof).err().map_or_else(String::new, |e| e.to_string());
+            answer = format!("{}: {}; the verifier says: {}", label, reason, verdict);
+        }
+        statement.push(format!("{}: C = {}", label, commitment));
+        statement.push(format!("   π = {}", proof.encode()));
+    }
+    Problem { statement, answer }
+}
+
+/// `count` problems from `seed`, cycling through the kinds.
+pub fn sheet(seed: u64, count: u32) -> Vec<Problem> {
+    let mut rng = ToyRng::new(seed);
+    (0..count as usize).map(|i| KINDS[i % KINDS.len()](&mut rng)).collect()
+}
@@ -0,0 +1,235 @@
+//! Arithmetic mod p without dividing an i128, which is a slow library call: the
+//! hot paths reduce every sum and product of commitments, thousands of times over
+//! in batch verification and the simulations.
+//!
+//! The default p = 2^61 - 1 is a Mersenne prime. Since 2^61 = 1 mod p, a wide
+//! number reduces by adding its 61-bit limbs,
+//!     x = hi*2^61 + lo = hi + lo (mod p),
+//! with one subtraction at the end. Any other odd modulus (from `ct.toml` or
+//! `--modulus`) multiplies in Montgomery form, a*b*2^-64 mod m, where the division
+//! by 2^64 is a shift. An even modulus, which only a weak-parameter demo would
+//! choose, falls back to plain division.
+
+use crate::commitment::MODULUS;
+
+const MERSENNE_61: u64 = MODULUS as u64;
+
+/// x mod 2^61 - 1, for any x.
+pub fn reduce_mersenne(x: u128) -> u64 {
+    let mask = MERSENNE_61 as u128;
+    // Below 2^68, then below 2^61 + 2^7 < 2p.
+    let x = (x & mask) + (x >> 61);
+    let x = ((x & mask) + (x >> 61)) as u64;
+    if x >= MERSENNE_61 { x - MERSENNE_61 } else { x }
+}
+
+/// Multiplication mod an odd m < 2^63, in Montgomery form: a is kept as a*R mod m,
+/// R = 2^64.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Montgomery {
+    modulus: u64,
+    /// -1/m mod 2^64.
+    neg_inverse: u64,
+    /// R^2 mod m, to bring numbers into Montgomery form.
+    r_squared: u64,
+}
+
+impl Montgomery {
+    pub fn new(modulus: u64) -> Self {
+        assert!(modulus % 2 == 1 && modulus < 1 << 63, "Montgomery needs an odd modulus below 2^63");
+        // Newton's iteration doubles the correct low bits each time; m*m = 1 mod 8
+        // gives the first three.
+        let mut inverse = modulus;
+        for _ in 0..5 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(modulus.wrapping_mul(inverse)));
+        }
+        let r = (1u128 << 64) % modulus as u128;
+        Montgomery { modulus, neg_inverse: inverse.wrapping_neg(), r_squared: (r * r % modulus as u128) as u64 }
+    }
+
+    /// t/R mod m, for t < m*R.
+    fn redc(&self, t: u128) -> u64 {
+        let q = (t as u64).wrapping_mul(self.neg_inverse);
+        // t + q*m is a multiple of R, and below 2*m*R < 2^128 as m < 2^63.
+        let reduced = ((t + q as u128 * self.modulus as u128) >> 64) as u64;
+        if reduced >= self.modulus { reduced - self.modulus } else { reduced }
+    }
+
+    pub fn to_montgomery(&self, a: u64) -> u64 {
+        self.redc(a as u128 * self.r_squared as u128)
+    }
+
+    pub fn from_montgomery(&self, a: u64) -> u64 {
+        self.redc(a as u128)
+    }
+
+    /// The product of two numbers in Montgomery form, in Montgomery form.
+    pub fn mul(&self, a: u64, b: u64) -> u64 {
+        self.redc(a as u128 * b as u128)
+    }
+}
+
+/// Arithmetic mod one modulus, by the quickest method that applies to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Mersenne61,
+    Montgomery(Montgomery),
+    Plain(u64),
+}
+
+impl Field {
+    pub fn new(modulus: i64) -> Self {
+        match modulus as u64 {
+            MERSENNE_61 => Field::Mersenne61,
+            m if m % 2 == 1 => Field::Montgomery(Montgomery::new(m)),
+            m => Field::Plain(m),
+        }
+    }
+
+    /// The modulus and, for Montgomery, its two constants: what `params` keeps so that
+    /// reading the active field takes no lock.
+    pub fn to_parts(&self) -> [u64; 3] {
+        match *self {
+            Field::Montgomery(Montgomery { modulus, neg_inverse, r_squared }) => [modulus, neg_inverse, r_squared],
+            _ => [self.modulus(), 0, 0],
+        }
+    }
+
+    /// The inverse of `to_parts`.
+    pub fn from_parts([modulus, neg_inverse, r_squared]: [u64; 3]) -> Self {
+        match modulus {
+            MERSENNE_61 => Field::Mersenne61,
+            m if m % 2 == 1 => Field::Montgomery(Montgomery { modulus, neg_inverse, r_squared }),
+            m => Field::Plain(m),
+        }
+    }
+
+    pub fn modulus(&self) -> u64 {
+        match *self {
+            Field::Mersenne61 => MERSENNE_61,
+            Field::Montgomery(montgomery) => montgomery.modulus,
+            Field::Plain(modulus) => modulus,
+        }
+    }
+
+    /// x mod p in [0, p), for any x.
+    pub fn reduce(&self, x: i128) -> u64 {
+        let reduced = match *self {
+            Field::Mersenne61 => reduce_mersenne(x.unsigned_abs()),
+            // Only an i64 divides natively.
+            _ if i64::try_from(x).is_ok() => (x as i64).unsigned_abs() % self.modulus(),
+            _ => (x.unsigned_abs() % self.modulus() as u128) as u64,
+        };
+        if x < 0 && reduced != 0 { self.modulus() - reduced } else { reduced }
+    }
+
+    /// a*b mod p.
+    pub fn mul(&self, a: i64, b: i64) -> u64 {
+        match *self {
+            Field::Montgomery(montgomery) => {
+                let (a, b) = (self.reduce(a as i128), self.reduce(b as i128));
+                // (a*b/R) * R^2/R = a*b: the second step also leaves Montgomery form.
+                montgomery.mul(montgomery.mul(a, b), montgomery.r_squared)
+            }
+            _ => self.reduce(a as i128 * b as i128),
+        }
+    }
+
+    /// base^exponent mod p, by square-and-multiply.
+    pub fn pow(&self, base: i64, mut exponent: u64) -> u64 {
+        let Field::Montgomery(montgomery) = *self else {
+            let (mut base, mut result) = (self.reduce(base as i128) as i64, self.reduce(1) as i64);
+            while exponent > 0 {
+                if exponent & 1 == 1 {
+                    result = self.mul(result, base) as i64;
+                }
+                base = self.mul(base, base) as i64;
+                exponent >>= 1;
+            }
+            return result as u64;
+        };
+        // Every step stays in Montgomery form, so each costs one reduction.
+        let mut base = montgomery.to_montgomery(self.reduce(base as i128));
+        let mut result = montgomery.to_montgomery(1);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = montgomery.mul(result, base);
+            }
+            base = montgomery.mul(base, base);
+            exponent >>= 1;
+        }
+        montgomery.from_montgomery(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The Mersenne prime, an odd non-Mersenne prime near 2^63 and a small one, and
+    /// two even moduli.
+    const MODULI: [i64; 5] = [MODULUS, i64::MAX - 24, 1_000_003, 1 << 16, 4];
+
+    /// Up to ±2^127, past what an i64 holds.
+    const WIDE: [i128; 8] =
+        [i128::MIN, i128::MIN + 1, -(1 << 100), -(1 << 64), 1 << 64, 1 << 100, i128::MAX - 1, i128::MAX];
+
+    const NARROW: [i64; 8] = [i64::MIN, i64::MIN + 1, -MODULUS, -1, 0, 2, MODULUS, i64::MAX];
+
+    fn reference_pow(base: i64, exponent: u64, modulus: i64) -> u64 {
+        let m = modulus as i128;
+        let (mut base, mut result, mut exponent) = ((base as i128).rem_euclid(m), 1 % m, exponent);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result * base % m;
+            }
+            base = base * base % m;
+            exponent >>= 1;
+        }
+        result as u64
+    }
+
+    #[test]
+    fn reduce_matches_rem_euclid() {
+        for modulus in MODULI {
+            let field = Field::new(modulus);
+            for x in WIDE.into_iter().chain(NARROW.map(i128::from)) {
+                assert_eq!(field.reduce(x), x.rem_euclid(modulus as i128) as u64, "{} mod {}", x, modulus);
+            }
+        }
+    }
+
+    #[test]
+    fn mul_matches_i128_product() {
+        for modulus in MODULI {
+            let field = Field::new(modulus);
+            for a in NARROW {
+                for b in NARROW {
+                    let expected = (a as i128 * b as i128).rem_euclid(modulus as i128) as u64;
+                    assert_eq!(field.mul(a, b), expected, "{} * {} mod {}", a, b, modulus);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pow_matches_square_and_multiply_in_i128() {
+        for modulus in MODULI {
+            let field = Field::new(modulus);
+            for base in NARROW {
+                for exponent in [0, 1, 2, 61, modulus as u64 - 2, u64::MAX] {
+                    let expected = reference_pow(base, exponent, modulus);
+                    assert_eq!(field.pow(base, exponent), expected, "{}^{} mod {}", base, exponent, modulus);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parts_round_trip() {
+        for modulus in MODULI {
+            let field = Field::new(modulus);
+            assert_eq!(Field::from_parts(field.to_parts()), field);
+        }
+    }
+}
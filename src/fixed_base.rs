@@ -0,0 +1,49 @@
+//! Fixed-base tables: multiplying G and H, which never change, without doubling.
+//!
+//! Double-and-add spends one doubling per bit of k and one addition per set bit.
+//! For a base known in advance, the table holds every 4-bit digit's multiple of
+//! every 16^j,
+//!     table[j][d] = d * 16^j * base,
+//! so k*base is one lookup per digit of k and one addition each: 64 additions for
+//! a 256-bit k where double-and-add needs about 384 operations. Building the table
+//! costs about 16 additions per digit, which the first few multiplications repay.
+//! Each curve backend's `precompute` builds its G and H tables; `Params::precompute`
+//! builds them all.
+
+/// What a table needs from a group: a zero and addition.
+pub trait Group: Clone {
+    fn identity() -> Self;
+    fn add(&self, other: &Self) -> Self;
+}
+
+const WINDOW: usize = 16;
+
+#[derive(Clone, Debug)]
+pub struct FixedBase<P> {
+    table: Vec<[P; WINDOW]>,
+}
+
+impl<P: Group> FixedBase<P> {
+    /// The table of `base` for scalars of up to `bits` bits.
+    pub fn new(base: &P, bits: u64) -> Self {
+        let mut table = Vec::new();
+        let mut power = base.clone();
+        for _ in 0..bits.div_ceil(4) {
+            let mut row: [P; WINDOW] = std::array::from_fn(|_| P::identity());
+            for d in 1..WINDOW {
+                row[d] = row[d - 1].add(&power);
+            }
+            // 16^(j+1) * base = 15 * 16^j * base + 16^j * base.
+            power = row[WINDOW - 1].add(&power);
+            table.push(row);
+        }
+        FixedBase { table }
+    }
+
+    /// k*base, for k given as little-endian bytes (high bytes beyond the table must be 0).
+    pub fn mul(&self, k: &[u8]) -> P {
+        let digits = k.iter().flat_map(|byte| [byte & 0xf, byte >> 4]);
+        let terms = self.table.iter().zip(digits).filter(|&(_, d)| d != 0);
+        terms.fold(P::identity(), |sum, (row, d)| sum.add(&row[d as usize]))
+    }
+}
@@ -0,0 +1,66 @@
+//! Where the generators come from, and what goes wrong if anyone knows how they relate.
+//!
+//! Binding rests on nobody knowing k with H = k*G. With it, C = v*G + r*H =
+//! (v + r*k)*G, and the same C opens to any v' with r' = r + (v - v')/k. In this
+//! crate's group, integers mod p under addition, k is just H/G mod p: the discrete log is
+//! one division, so binding is broken for everyone (`dlog_h`, `forge_blinding`).
+//!
+//! Real systems also make sure the designer cannot know k. H is derived by hashing
+//! to the group ("nothing up my sleeve"), so it is a point nobody picked as k*G.
+//! Secp256k1-zkp hashes G's encoding to a curve point, and Bulletproofs hash labels.
+//! `hash_to_curve` does the same on the toy curve. There a brute-force search of its
+//! 199 points still finds k (`curve_dlog`); on a 256-bit curve that search costs
+//! about 2^128 steps. `hash_to_field` shows the derivation for the mod-p group,
+//! where it cannot help, because division still gives k.
+
+use sha2::{Digest, Sha256};
+
+use crate::commitment::{mod_inverse, mod_mul, mod_reduce, Blinding, Opening, Scalar};
+use crate::params;
+use crate::toy_curve::{self, Point};
+
+/// SHA-256("ct/generator" || label || counter) as a number.
+fn hash_label(label: &[u8], counter: u32) -> u64 {
+    let digest = Sha256::new().chain_update(b"ct/generator").chain_update(label).chain_update(counter.to_le_bytes());
+    u64::from_le_bytes(digest.finalize()[..8].try_into().expect("8 bytes"))
+}
+
+/// k with H = k*G in the active mod-p group: H/G mod p.
+pub fn dlog_h() -> i64 {
+    let params = params::active();
+    mod_mul(params.h, mod_inverse(params.g))
+}
+
+/// Knowing k, a blinding that opens `opening`'s commitment to `value` instead.
+pub fn forge_blinding(opening: Opening, value: Scalar, k: i64) -> Blinding {
+    let shift = mod_mul(mod_reduce(opening.value.as_i64() as i128 - value.as_i64() as i128), mod_inverse(k));
+    Blinding::new(mod_reduce(opening.blinding.as_i64() as i128 + shift as i128))
+}
+
+/// An element of the mod-p group hashed from `label`: nonzero and different from G.
+pub fn hash_to_field(label: &[u8]) -> i64 {
+    let params = params::active();
+    (0..)
+        .map(|counter| (hash_label(label, counter) % params.modulus as u64) as i64)
+        .find(|&h| h != 0 && h != params.g)
+        .expect("some counter gives a usable element")
+}
+
+/// A point of the toy curve hashed from `label` by try-and-increment: the hash is
+/// an x coordinate, kept if x^3 + 7 has a square root y (the even one is taken).
+pub fn hash_to_curve(label: &[u8]) -> Point {
+    (0..)
+        .find_map(|counter| {
+            let x = (hash_label(label, counter) % toy_curve::P as u64) as i64;
+            (0..toy_curve::P)
+                .filter(|y| y % 2 == 0)
+                .map(|y| Point::Affine { x, y })
+                .find(|&point| toy_curve::is_on_curve(point))
+        })
+        .expect("about half of all x lie on the curve")
+}
+
+/// k with `point` = k*G on the toy curve, by trying all of them.
+pub fn curve_dlog(point: Point) -> Option<i64> {
+    (0..toy_curve::ORDER).find(|&k| toy_curve::scalar_mul(k, toy_curve::G) == point)
+}
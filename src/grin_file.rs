@@ -0,0 +1,157 @@
+//! Grin's JSON, for `ct inspect grin`. Three shapes are read:
+//!
+//! - a transaction, as `grin-wallet` and the node API show it:
+//!
+//! ```json
+//! { "offset": "<hex>",
+//!   "body": { "inputs":  [{ "features": "Plain", "commit": "<hex>" }],
+//!             "outputs": [{ "features": "Plain", "commit": "<hex>", "proof": "<hex>" }],
+//!             "kernels": [{ "features": { "Plain": { "fee": 7000000 } },
+//!                           "excess": "<hex>", "excess_sig": "<hex>" }] } }
+//! ```
+//!
+//!   Older versions give the kernel `"features": "Plain"` with `"fee"` and
+//!   `"lock_height"` beside it, and newer ones may wrap the inputs in
+//!   `{ "CommitOnly": [..] }` or `{ "FeaturesAndCommit": [..] }`;
+//! - a slate up to version 3, whose `"tx"` is such a transaction;
+//! - a version 4 slate (`"ver": "4:2"`): `"off"`, `"fee"`, `"feat"` (0 plain,
+//!   1 coinbase, 2 height locked, 3 no recent duplicate) with `"feat_args"`, `"coms"`
+//!   (inputs with only `"c"`, outputs with `"c"` and their proof `"p"`) and `"sigs"`,
+//!   each party's public excess `"xs"`; the kernel excess is their sum.
+//!
+//! A fee is the low 40 bits of Grin's fee field, as a number or a string.
+
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::grin::{GrinKernel, GrinOutput, GrinTx};
+use pedersen_commitment_tx::secp256k1::{self, Point};
+use pedersen_commitment_tx::wire::from_hex;
+use serde_json::Value;
+
+use crate::slate_file::not_json;
+
+/// Which of the shapes above a file was.
+pub enum Shape {
+    Transaction,
+    /// A slate of this version, with its transaction.
+    Slate(u64),
+    /// A version 4 slate, in this state (S1..S3 for a payment, I1..I3 for an invoice).
+    SlateV4(String),
+}
+
+const FEATURES: [&str; 4] = ["Plain", "Coinbase", "HeightLocked", "NoRecentDuplicate"];
+
+fn malformed(what: &str) -> CtError {
+    CtError::MalformedInput(format!("{} is missing or not what Grin writes", what))
+}
+
+fn bytes(value: &Value, what: &str) -> Result<Vec<u8>, CtError> {
+    from_hex(value.as_str().ok_or_else(|| malformed(what))?)
+}
+
+fn array<const N: usize>(value: &Value, what: &str) -> Result<[u8; N], CtError> {
+    bytes(value, what)?
+        .try_into()
+        .map_err(|b: Vec<u8>| CtError::MalformedInput(format!("{} has {} bytes, not {}", what, b.len(), N)))
+}
+
+/// A number, or a string holding one; missing is 0.
+fn number(value: &Value, what: &str) -> Result<u64, CtError> {
+    match value {
+        Value::Null => Ok(0),
+        Value::Number(n) => n.as_u64().ok_or_else(|| malformed(what)),
+        Value::String(s) => s.parse().map_err(|_| malformed(what)),
+        _ => Err(malformed(what)),
+    }
+}
+
+fn fee(value: &Value) -> Result<u64, CtError> {
+    Ok(number(value, "the fee")? & ((1 << 40) - 1))
+}
+
+fn list<'a>(value: &'a Value, what: &str) -> Result<&'a Vec<Value>, CtError> {
+    match value {
+        Value::Array(items) => Ok(items),
+        // { "CommitOnly": [..] } and the like.
+        Value::Object(map) if map.len() == 1 => list(map.values().next().expect("one entry"), what),
+        _ => Err(malformed(what)),
+    }
+}
+
+fn kernel(value: &Value) -> Result<GrinKernel, CtError> {
+    let (features, args) = match &value["features"] {
+        Value::String(name) => (name.clone(), value),
+        Value::Object(map) if map.len() == 1 => {
+            let (name, args) = map.iter().next().expect("one entry");
+            (name.clone(), args)
+        }
+        _ => return Err(malformed("a kernel's features")),
+    };
+    Ok(GrinKernel {
+        features,
+        fee: fee(&args["fee"])?,
+        lock_height: number(&args["lock_height"], "a kernel's lock height")?,
+        excess: array(&value["excess"], "a kernel's excess")?,
+        excess_sig: bytes(&value["excess_sig"], "a kernel's signature")?,
+    })
+}
+
+fn transaction(tx: &Value) -> Result<GrinTx, CtError> {
+    let body = &tx["body"];
+    let input = |input: &Value| match input {
+        Value::String(_) => array(input, "an input's commitment"),
+        _ => array(&input["commit"], "an input's commitment"),
+    };
+    let output = |output: &Value| {
+        let commit = array(&output["commit"], "an output's commitment")?;
+        Ok(GrinOutput { commit, proof: bytes(&output["proof"], "an output's proof")? })
+    };
+    Ok(GrinTx {
+        offset: array(&tx["offset"], "the offset")?,
+        inputs: list(&body["inputs"], "the inputs")?.iter().map(input).collect::<Result<_, CtError>>()?,
+        outputs: list(&body["outputs"], "the outputs")?.iter().map(output).collect::<Result<_, CtError>>()?,
+        kernels: list(&body["kernels"], "the kernels")?.iter().map(kernel).collect::<Result<_, CtError>>()?,
+    })
+}
+
+fn slate_v4(slate: &Value) -> Result<GrinTx, CtError> {
+    let mut tx = GrinTx { offset: array(&slate["off"], "the offset")?, ..GrinTx::default() };
+    for com in slate["coms"].as_array().map_or(&[][..], Vec::as_slice) {
+        let commit = array(&com["c"], "a commitment")?;
+        match &com["p"] {
+            Value::Null => tx.inputs.push(commit),
+            proof => tx.outputs.push(GrinOutput { commit, proof: bytes(proof, "an output's proof")? }),
+        }
+    }
+    let excesses = list(&slate["sigs"], "the signatures")?
+        .iter()
+        .map(|sig| Point::from_public_key(&array(&sig["xs"], "a public excess")?))
+        .collect::<Result<Vec<_>, CtError>>()?;
+    let excess = secp256k1::sum(&excesses)
+        .to_commitment()
+        .ok_or_else(|| CtError::MalformedInput("the public excesses add up to nothing".to_string()))?;
+    let features = number(&slate["feat"], "the kernel features")? as usize;
+    tx.kernels.push(GrinKernel {
+        features: FEATURES.get(features).ok_or_else(|| malformed("the kernel features"))?.to_string(),
+        fee: fee(&slate["fee"])?,
+        lock_height: number(&slate["feat_args"]["lock_hgt"], "the lock height")?,
+        excess,
+        excess_sig: Vec::new(),
+    });
+    Ok(tx)
+}
+
+pub fn parse(text: &str) -> Result<(GrinTx, Shape), CtError> {
+    let document: Value = serde_json::from_str(text).map_err(not_json)?;
+    if let Some(version) = document["ver"].as_str() {
+        if !version.starts_with("4:") {
+            return Err(CtError::MalformedInput(format!("slate version {} is not one this reads", version)));
+        }
+        let state = document["sta"].as_str().unwrap_or("S1").to_string();
+        return Ok((slate_v4(&document)?, Shape::SlateV4(state)));
+    }
+    if document.get("tx").is_some() {
+        let version = number(&document["version_info"]["version"], "the slate version")?;
+        return Ok((transaction(&document["tx"])?, Shape::Slate(version)));
+    }
+    Ok((transaction(&document)?, Shape::Transaction))
+}
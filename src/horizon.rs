@@ -0,0 +1,112 @@
+//! Compaction beyond a horizon. Blocks older than the most recent `horizon` are
+//! merged and cut through (see `cut_through`): every output they created that
+//! was spent before the horizon disappears together with its range proof and
+//! the input that spent it. What survives is enough to check the whole history:
+//! the headers, every kernel, and the outputs still unspent at the horizon.
+
+use crate::block::{Block, BlockHeader};
+use crate::chain::Chain;
+use crate::commitment::{Blinding, Commitment};
+use crate::cut_through::Aggregate;
+use crate::error::CtError;
+use crate::ledger::Ledger;
+use crate::light_client::LightClient;
+use crate::wire::Encode;
+
+/// A chain with its old blocks compacted.
+#[derive(Clone, Debug)]
+pub struct Compacted {
+    /// Every main-chain header, genesis first.
+    pub headers: Vec<BlockHeader>,
+    /// The blocks below the horizon, merged and cut through.
+    pub history: Aggregate,
+    /// The last `horizon` blocks, kept whole.
+    pub recent: Vec<Block>,
+    /// Outputs (and range proofs) removed by the cut-through.
+    pub pruned_outputs: usize,
+    /// Wire size of the blocks below the horizon, before and after compaction.
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+    /// The ledger at the horizon (unspent outputs and output MMR), to replay `recent` on.
+    base: Ledger,
+}
+
+/// Wire size of an aggregate: its entries, kernels with their fees, and fee, minted, burned and offset.
+fn aggregate_bytes(aggregate: &Aggregate) -> usize {
+    let entries = aggregate.inputs.iter().chain(&aggregate.outputs).map(|entry| entry.to_bytes().len());
+    let kernels = aggregate.kernels.iter().map(|(kernel, _)| kernel.to_bytes().len() + 8);
+    entries.chain(kernels).sum::<usize>() + 4 * 8
+}
+
+impl Compacted {
+    /// Compact every main-chain block except the most recent `horizon`.
+    pub fn new(chain: &Chain, horizon: u64) -> Result<Self, CtError> {
+        let headers: Vec<BlockHeader> = chain.headers().into_iter().cloned().collect();
+        let cutoff = headers.len().saturating_sub(horizon as usize);
+        let blocks = headers
+            .iter()
+            .map(|header| chain.block(&header.hash()).cloned())
+            .collect::<Option<Vec<Block>>>()
+            .expect("main-chain blocks are stored");
+        let (old, recent) = blocks.split_at(cutoff);
+        let transactions: Vec<_> = old.iter().flat_map(|block| block.transactions.clone()).collect();
+        let mut history = Aggregate::merge(&transactions);
+        let pruned_outputs = history.cut_through();
+        let base = match old.last() {
+            Some(block) => chain.state_after(block.header.hash())?,
+            None => Ledger::new(),
+        };
+        Ok(Compacted {
+            headers,
+            bytes_before: old.iter().map(|block| block.to_bytes().len()).sum(),
+            bytes_after: if old.is_empty() { 0 } else { aggregate_bytes(&history) },
+            history,
+            recent: recent.to_vec(),
+            pruned_outputs,
+            base,
+        })
+    }
+
+    /// Check the compacted chain: the headers link; the history balances, its kernels
+    /// verify and add up to the totals in the old headers; its outputs are exactly the
+    /// unspent outputs at the horizon; and the recent blocks apply on top of them.
+    pub fn validate(&self) -> Result<(), CtError> {
+        LightClient::new().sync(&self.headers)?;
+        self.history.verify()?;
+
+        let mismatch = |what: &str| CtError::MalformedInput(format!("the compacted history does not match {}", what));
+        if !self.history.inputs.is_empty() {
+            return Err(mismatch("its own outputs (an input was never created)"));
+        }
+
+        let old = &self.headers[..self.headers.len() - self.recent.len()];
+        let history = &self.history;
+        let excess = history.kernels.iter().fold(Commitment::from_i64(0), |sum, (kernel, _)| sum + kernel.excess);
+        if old.iter().fold(Commitment::from_i64(0), |sum, header| sum + header.kernel_excess) != excess
+            || old.iter().fold(Blinding::default(), |sum, header| sum + header.offset) != history.offset
+            || old.iter().map(|header| header.fees).sum::<u64>() != history.fee
+            || old.iter().map(|header| header.minted).sum::<u64>() != history.minted
+            || old.iter().map(|header| header.burned).sum::<u64>() != history.burned
+        {
+            return Err(mismatch("the header totals"));
+        }
+
+        let horizon_root = old.last().map_or([0; 32], |header| header.output_root);
+        if self.base.tip() != old.last() || self.base.output_mmr().root() != horizon_root {
+            return Err(mismatch("the horizon header"));
+        }
+        let mut unspent: Vec<i64> = self.base.utxos().map(|(_, c)| c.as_i64()).collect();
+        let mut outputs: Vec<i64> = history.outputs.iter().map(|entry| entry.commitment.as_i64()).collect();
+        unspent.sort_unstable();
+        outputs.sort_unstable();
+        if unspent != outputs {
+            return Err(mismatch("the unspent outputs"));
+        }
+
+        let mut ledger = self.base.clone();
+        for block in &self.recent {
+            ledger.apply_block(block)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+//! Issuance (Elements-style): how confidential assets come to exist.
+//!
+//! A new issuance creates an asset under a name: v units over its unblinded generator
+//! H_a, plus reissuance tokens, units of a second asset whose generator H_t is hashed
+//! from the same name (`token_tag`). Both enter the transaction as extra inputs, so the
+//! outputs' surjection proofs may point at them and the balance counts them. The
+//! issuer either publishes v or hides it as v*H_a + r*H behind a range proof over H_a.
+//!
+//! A reissuance creates more of an existing asset, and only a token holder can make
+//! one: the transaction spends an output holding a token and opens that input's
+//! blinded generator to H_t (it reveals t with A = H_t + t*H). The token goes back
+//! into an output like any other asset, ready for the next reissuance.
+//!
+//! `AssetLedger` adds the rules that need history: a name is issued once, and only an
+//! issued asset can be reissued.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::assets::{asset_tag, blinded_generator, AssetInput, AssetOpening, AssetRangeProof, AssetTransaction};
+use crate::commitment::{Blinding, Commitment, Scalar};
+use crate::error::CtError;
+use crate::generators::hash_to_field;
+use crate::params;
+use crate::rng::ToyRng;
+
+/// H_t: the generator of the reissuance token of the asset called `name`.
+pub fn token_tag(name: &str) -> i64 {
+    hash_to_field(&[b"ct/reissuance-token/".as_slice(), name.as_bytes()].concat())
+}
+
+/// `value` units over the unblinded generator `tag` (t = 0).
+fn unblinded(tag: i64, value: u64, blinding: Blinding) -> AssetOpening {
+    AssetOpening { tag, tag_blinding: Blinding::new(0), value: Scalar::new(value as i64), blinding }
+}
+
+/// A published amount needs no range proof, only to be in range.
+fn check_shown(value: u64) -> Result<Option<AssetRangeProof>, CtError> {
+    if value >= 1u64 << params::active().range_bits {
+        return Err(CtError::OverflowedValue(value as i64));
+    }
+    Ok(None)
+}
+
+/// Units of an asset that enter from nowhere: v*H_a + r*H, with either v published
+/// (and r = 0) or a range proof.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssuedAmount {
+    pub commitment: Commitment,
+    pub explicit: Option<u64>,
+    pub range_proof: Option<AssetRangeProof>,
+}
+
+impl IssuedAmount {
+    /// `value` units of `tag`, and the opening they add to the transaction's inputs.
+    fn create(tag: i64, value: u64, blinded: bool, rng: &mut ToyRng) -> Result<(Self, AssetOpening), CtError> {
+        let blinding = Blinding::new(if blinded { rng.scalar() } else { 0 });
+        let opening = unblinded(tag, value, blinding);
+        let range_proof = if blinded { Some(AssetRangeProof::create(&opening, rng)?) } else { check_shown(value)? };
+        let amount = IssuedAmount { commitment: opening.commit(), explicit: (!blinded).then_some(value), range_proof };
+        Ok((amount, opening))
+    }
+
+    fn verify(&self, tag: i64) -> Result<(), CtError> {
+        match (self.explicit, &self.range_proof) {
+            (Some(value), None) => {
+                check_shown(value)?;
+                if unblinded(tag, value, Blinding::new(0)).commit() != self.commitment {
+                    let reason = format!("the commitment does not hold the {} units shown", value);
+                    return Err(CtError::InvalidIssuance(reason));
+                }
+                Ok(())
+            }
+            (None, Some(proof)) => proof.verify(tag, self.commitment),
+            _ => Err(CtError::MalformedInput("an issued amount is either shown or range-proven".to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssuanceKind {
+    /// A new asset, with this many reissuance tokens.
+    New { tokens: IssuedAmount },
+    /// More of an existing asset: `token_input` holds a token, and `token_blinding`
+    /// is the t that opens its generator to H_t.
+    Reissue { token_input: usize, token_blinding: Blinding },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Issuance {
+    pub asset: String,
+    pub amount: IssuedAmount,
+    pub kind: IssuanceKind,
+}
+
+impl Issuance {
+    /// Create `name` with `amount` units and `tokens` reissuance tokens. Returns the
+    /// openings the issuance adds to the inputs, for `AssetTransaction::create`.
+    pub fn new_asset(
+        name: &str,
+        amount: u64,
+        tokens: u64,
+        blinded: bool,
+        rng: &mut ToyRng,
+    ) -> Result<(Self, Vec<AssetOpening>), CtError> {
+        let (amount, asset_opening) = IssuedAmount::create(asset_tag(name), amount, blinded, rng)?;
+        let (tokens, token_opening) = IssuedAmount::create(token_tag(name), tokens, blinded, rng)?;
+        let issuance = Issuance { asset: name.to_string(), amount, kind: IssuanceKind::New { tokens } };
+        Ok((issuance, vec![asset_opening, token_opening]))
+    }
+
+    /// `amount` more units of `name`, on the authority of `inputs[token_input]`. That
+    /// input should hold a reissuance token; `verify` checks that it does.
+    pub fn reissue(
+        name: &str,
+        amount: u64,
+        blinded: bool,
+        inputs: &[AssetOpening],
+        token_input: usize,
+        rng: &mut ToyRng,
+    ) -> Result<(Self, Vec<AssetOpening>), CtError> {
+        let token = inputs
+            .get(token_input)
+            .ok_or_else(|| CtError::MalformedInput(format!("there is no input {}", token_input)))?;
+        let (amount, opening) = IssuedAmount::create(asset_tag(name), amount, blinded, rng)?;
+        let kind = IssuanceKind::Reissue { token_input, token_blinding: token.tag_blinding };
+        Ok((Issuance { asset: name.to_string(), amount, kind }, vec![opening]))
+    }
+
+    /// The generators and commitments the issuance adds to the inputs, in order.
+    pub fn inputs(&self) -> Vec<AssetInput> {
+        let mut inputs = vec![AssetInput { generator: asset_tag(&self.asset), commitment: self.amount.commitment }];
+        if let IssuanceKind::New { tokens } = &self.kind {
+            inputs.push(AssetInput { generator: token_tag(&self.asset), commitment: tokens.commitment });
+        }
+        inputs
+    }
+
+    /// The amounts are well-formed, and a reissuance spends a token: its input's
+    /// generator is H_t + t*H for the t shown.
+    pub fn verify(&self, inputs: &[AssetInput]) -> Result<(), CtError> {
+        self.amount.verify(asset_tag(&self.asset))?;
+        match &self.kind {
+            IssuanceKind::New { tokens } => tokens.verify(token_tag(&self.asset)),
+            IssuanceKind::Reissue { token_input, token_blinding } => {
+                let holds_token = inputs
+                    .get(*token_input)
+                    .is_some_and(|input| input.generator == blinded_generator(token_tag(&self.asset), *token_blinding));
+                if holds_token {
+                    Ok(())
+                } else {
+                    let reason = format!("input {} does not hold a reissuance token for {}", token_input, self.asset);
+                    Err(CtError::InvalidIssuance(reason))
+                }
+            }
+        }
+    }
+}
+
+/// Unspent asset outputs, and the names issued so far.
+#[derive(Clone, Debug, Default)]
+pub struct AssetLedger {
+    unspent: Vec<AssetInput>,
+    spent: HashSet<Commitment>,
+    issued: HashSet<String>,
+}
+
+impl AssetLedger {
+    pub fn new() -> Self {
+        AssetLedger::default()
+    }
+
+    pub fn unspent(&self) -> &[AssetInput] {
+        &self.unspent
+    }
+
+    pub fn is_issued(&self, name: &str) -> bool {
+        self.issued.contains(name)
+    }
+
+    /// Check the issuance rules and the inputs against the ledger, verify `tx`, then
+    /// spend its inputs and add its outputs. Nothing changes unless all of it passes.
+    pub fn apply(&mut self, tx: &AssetTransaction) -> Result<(), CtError> {
+        let mut naming = HashSet::new();
+        for issuance in &tx.issuances {
+            let name = &issuance.asset;
+            match issuance.kind {
+                IssuanceKind::New { .. } if self.is_issued(name) || !naming.insert(name) => {
+                    return Err(CtError::InvalidIssuance(format!("{} was already issued", name)));
+                }
+                IssuanceKind::Reissue { .. } if !self.is_issued(name) => {
+                    return Err(CtError::InvalidIssuance(format!("{} was never issued", name)));
+                }
+                _ => {}
+            }
+        }
+        let mut spending = Vec::new();
+        for input in &tx.inputs {
+            match self.unspent.iter().position(|unspent| unspent == input) {
+                Some(index) if !spending.contains(&index) => spending.push(index),
+                _ if self.spent.contains(&input.commitment) || spending.iter().any(|&i| self.unspent[i] == *input) => {
+                    return Err(CtError::DoubleSpend(input.commitment));
+                }
+                _ => return Err(CtError::UnknownInput(input.commitment)),
+            }
+        }
+        tx.verify()?;
+        self.spent.extend(tx.inputs.iter().map(|input| input.commitment));
+        self.unspent.retain(|unspent| !tx.inputs.contains(unspent));
+        self.unspent.extend(tx.outputs.iter().map(|output| output.as_input()));
+        self.issued.extend(tx.issuances.iter().map(|issuance| issuance.asset.clone()));
+        Ok(())
+    }
+}
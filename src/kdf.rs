@@ -0,0 +1,35 @@
+//! HMAC-SHA512 and PBKDF2, the key stretching behind BIP39 seeds and wallet
+//! passphrases: thousands of rounds make every guess at a passphrase cost as
+//! much as the wallet spent deriving the real key.
+
+use sha2::{Digest, Sha512};
+
+pub fn hmac_sha512(key: &[u8], message: &[u8]) -> [u8; 64] {
+    const BLOCK: usize = 128;
+    let mut padded = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        padded[..64].copy_from_slice(&Sha512::digest(key));
+    } else {
+        padded[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha512::new();
+    inner.update(padded.map(|b| b ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha512::new();
+    outer.update(padded.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// PBKDF2 with HMAC-SHA512, one 64-byte block.
+pub fn pbkdf2_sha512(password: &[u8], salt: &[u8], rounds: u32) -> [u8; 64] {
+    let mut block = hmac_sha512(password, &[salt, &1u32.to_be_bytes()].concat());
+    let mut output = block;
+    for _ in 1..rounds {
+        block = hmac_sha512(password, &block);
+        for (out, b) in output.iter_mut().zip(block) {
+            *out ^= b;
+        }
+    }
+    output
+}
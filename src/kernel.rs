@@ -0,0 +1,184 @@
+//! Mimblewimble-style transaction kernel. Instead of making the blindings cancel
+//! exactly, the sender publishes the excess
+//!     E = sum(outputs) + fee*G - sum(inputs)
+//! which is x*H for x = sum(output blindings) - sum(input blindings) when the amounts
+//! balance, plus a Schnorr signature with x as the secret key. A valid signature shows
+//! E has no G component (no value was created) and that the signer knew every input's
+//! blinding, so nobody else can assemble a balanced transaction from someone's coins.
+//! Toy group: anyone can compute discrete logs mod p, so the signature is only a model.
+//!
+//! Kernel offset (Grin): the sender splits x = offset + x', signs with x' and publishes
+//! the offset separately. E' = x'*H then matches no set of outputs, and once a block
+//! sums the offsets of all its transactions nobody can tell which kernel went with which outputs.
+//!
+//! Two parties can sign one kernel without sharing secrets (the slate flow): each
+//! publishes x_i*H and k_i*H, both challenges use the sums P and R, and the partial
+//! signatures s_i = k_i + e*x_i add up to a signature for P.
+//!
+//! A kernel can be height-locked (Grin): it signs [fee, lock height] instead of
+//! [fee], and the ledger keeps it out of any block below that height.
+//!
+//! Adaptor signatures: put T = t*H into R without knowing t, and s' = k + e*x is
+//! not a signature but becomes one when t is added. Whoever holds s' and later sees
+//! s = s' + t on a chain learns t, which makes a kernel depend on a secret with no
+//! script (see `swap`).
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{fee_commitment, lin_comb, mod_mul, mod_reduce, sum_commitments, Blinding, Commitment};
+use crate::error::CtError;
+use crate::params;
+use crate::rng::ToyRng;
+use crate::sigma::Equation;
+use crate::transcript::Transcript;
+
+/// Schnorr signature over H: R = k*H, s = k + e*x with e = hash(public key, R, message).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signature {
+    pub nonce: i64,
+    pub s: i64,
+}
+
+fn challenge(public: Commitment, nonce: i64, message: &[i64]) -> i64 {
+    let mut transcript = Transcript::new("ct/signature");
+    transcript.append("public key", public.as_i64());
+    transcript.append("nonce", nonce);
+    transcript.append_all("message", message);
+    transcript.challenge("e")
+}
+
+/// x*H: the public key for secret x (and the public nonce for secret k).
+pub fn public_key(secret: Blinding) -> Commitment {
+    Commitment::from_i64(lin_comb(0, secret.as_i64()))
+}
+
+/// One signer's share s_i = k_i + e*x_i, where the challenge e commits to the
+/// combined key `public` and combined nonce `nonce` of all signers.
+pub fn partial_sign(secret: Blinding, nonce_secret: Blinding, public: Commitment, nonce: i64, message: &[i64]) -> i64 {
+    let e = challenge(public, nonce, message);
+    mod_reduce(nonce_secret.as_i64() as i128 + mod_mul(e, secret.as_i64()) as i128)
+}
+
+/// s_i*H = R_i + e*P_i for one signer's share (`public_i`, `nonce_i`) of (`public`, `nonce`).
+pub fn partial_verify(
+    s: i64,
+    public_i: Commitment,
+    nonce_i: i64,
+    public: Commitment,
+    nonce: i64,
+    message: &[i64],
+) -> bool {
+    let e = challenge(public, nonce, message);
+    lin_comb(0, s) == mod_reduce(nonce_i as i128 + mod_mul(e, public_i.as_i64()) as i128)
+}
+
+/// Sign `message` with secret x, whose public key is x*H.
+pub fn schnorr_sign(secret: Blinding, message: &[i64], rng: &mut ToyRng) -> Signature {
+    let k = Blinding::new(rng.scalar());
+    let (public, nonce) = (public_key(secret), public_key(k).as_i64());
+    Signature { nonce, s: partial_sign(secret, k, public, nonce, message) }
+}
+
+/// s*H = R + e*P.
+pub fn schnorr_verify(public: Commitment, message: &[i64], signature: &Signature) -> bool {
+    partial_verify(signature.s, public, signature.nonce, public, signature.nonce, message)
+}
+
+/// A pre-signature for key P and adaptor point T: R = k*H + T and s' = k + e*x, with e
+/// for that R. Its partial signatures add up like ordinary ones, as long as every
+/// signer uses the R that includes T.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdaptorSignature {
+    pub nonce: i64,
+    pub adaptor: Commitment,
+    pub s: i64,
+}
+
+/// Pre-sign `message` with secret x for the adaptor point T.
+pub fn adaptor_sign(secret: Blinding, adaptor: Commitment, message: &[i64], rng: &mut ToyRng) -> AdaptorSignature {
+    let k = Blinding::new(rng.scalar());
+    let nonce = (public_key(k) + adaptor).as_i64();
+    AdaptorSignature { nonce, adaptor, s: partial_sign(secret, k, public_key(secret), nonce, message) }
+}
+
+impl AdaptorSignature {
+    /// s'*H = R - T + e*P: adding t to s' will give a signature for P.
+    pub fn verify(&self, public: Commitment, message: &[i64]) -> bool {
+        let nonce_without_adaptor = (Commitment::from_i64(self.nonce) - self.adaptor).as_i64();
+        partial_verify(self.s, public, nonce_without_adaptor, public, self.nonce, message)
+    }
+
+    /// (R, s' + t), a Schnorr signature, from the adaptor secret t.
+    pub fn complete(&self, secret: Blinding) -> Signature {
+        Signature { nonce: self.nonce, s: mod_reduce(self.s as i128 + secret.as_i64() as i128) }
+    }
+
+    /// t = s - s', from the completed signature, if that is what `signature` is.
+    pub fn extract(&self, signature: &Signature) -> Option<Blinding> {
+        let secret = Blinding::new(mod_reduce(signature.s as i128 - self.s as i128));
+        (signature.nonce == self.nonce && public_key(secret) == self.adaptor).then_some(secret)
+    }
+}
+
+/// E = sum(outputs) + fee*G - sum(inputs): what the kernel must account for.
+pub fn excess(inputs: &[Commitment], outputs: &[Commitment], fee: u64) -> Commitment {
+    sum_commitments(outputs) + fee_commitment(fee) - sum_commitments(inputs)
+}
+
+/// Split the excess blinding into (offset, kernel blinding) with x = offset + x'.
+pub fn split_excess(excess_blinding: Blinding, rng: &mut ToyRng) -> (Blinding, Blinding) {
+    let offset = Blinding::random(rng);
+    (offset, excess_blinding - offset)
+}
+
+/// What a kernel signs: [fee], or [fee, lock height] for a height-locked one.
+pub fn kernel_message(fee: u64, lock_height: u64) -> Vec<i64> {
+    if lock_height == 0 {
+        vec![fee as i64]
+    } else {
+        vec![fee as i64, lock_height as i64]
+    }
+}
+
+/// The public excess E = x*H and a signature by x over the fee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Kernel {
+    pub excess: Commitment,
+    /// "nonce" and "s" sit next to "excess".
+    #[serde(flatten)]
+    pub signature: Signature,
+    /// The lowest height of a block that may contain the kernel; 0 for any.
+    #[serde(default, skip_serializing_if = "crate::wire::is_default")]
+    pub lock_height: u64,
+}
+
+impl Kernel {
+    /// Sender side: x = sum(output blindings) - sum(input blindings).
+    pub fn create(excess_blinding: Blinding, fee: u64, rng: &mut ToyRng) -> Self {
+        Kernel::create_locked(excess_blinding, fee, 0, rng)
+    }
+
+    /// Like `create`, for a kernel no block below `lock_height` may contain.
+    pub fn create_locked(excess_blinding: Blinding, fee: u64, lock_height: u64, rng: &mut ToyRng) -> Self {
+        Kernel {
+            excess: public_key(excess_blinding),
+            signature: schnorr_sign(excess_blinding, &kernel_message(fee, lock_height), rng),
+            lock_height,
+        }
+    }
+
+    /// s*H - R - e*E = 0: what `verify` checks, for batch verification.
+    pub fn equation(&self, fee: u64) -> Equation {
+        let e = challenge(self.excess, self.signature.nonce, &kernel_message(fee, self.lock_height));
+        vec![(self.signature.s, params::active().h), (-1, self.signature.nonce), (-e, self.excess.as_i64())]
+    }
+
+    /// The signature must verify against E for this fee and lock height.
+    pub fn verify(&self, fee: u64) -> Result<(), CtError> {
+        if schnorr_verify(self.excess, &kernel_message(fee, self.lock_height), &self.signature) {
+            Ok(())
+        } else {
+            Err(CtError::InvalidProof("kernel signature fails".to_string()))
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Deterministic blinding factors. Instead of storing every r, a wallet keeps one
+//! secret seed and derives each blinding from it and a path that names the output
+//! like an HD wallet does: `m/account/change/index`. Anyone with the seed can
+//! recompute every blinding; without it they look random.
+//!
+//! Derivation splits in two, like BIP32's non-hardened keys: each account has a
+//! secret k = SHA-256(seed ‖ account), and the blinding at `m/account/change/index`
+//! is r = k + t with the tweak t = SHA-256(k*H ‖ change ‖ index). The account's
+//! view key k*H is enough to compute every r*H = k*H + t*H, and with it to
+//! recognize the wallet's outputs and read their values (C - r*H = v*G), but not
+//! to spend them, which needs r itself. (In the toy group k*H / H gives k back;
+//! in a real group that is the discrete-log problem.)
+//!
+//! Change outputs get rewindable range proofs whose nonce is hashed from the seed
+//! and the commitment (`rewind_nonce`), so the seed alone finds them on the chain
+//! again (`Wallet::restore`).
+//!
+//! The seed also gives the scan and spend secrets a and b behind the wallet's
+//! stealth address (a*H, b*H), which senders pay to with `ecdh`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::commitment::Blinding;
+use crate::commitment::Commitment;
+use crate::ecdh::Address;
+use crate::error::CtError;
+use crate::kernel::public_key;
+use crate::wire::{from_hex, to_hex};
+
+/// The `change` branch for outputs paid to us.
+pub const RECEIVE: u32 = 0;
+/// The `change` branch for change we pay back to ourselves.
+pub const CHANGE: u32 = 1;
+/// Unused paths in a row after which a search stops looking further along a branch.
+pub const GAP_LIMIT: u32 = 20;
+
+/// The wallet's one secret.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Seed(pub [u8; 32]);
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&to_hex(&self.0))
+    }
+}
+
+impl FromStr for Seed {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = from_hex(s)?
+            .try_into()
+            .map_err(|b: Vec<u8>| CtError::MalformedInput(format!("a seed has 32 bytes, not {}", b.len())))?;
+        Ok(Seed(bytes))
+    }
+}
+
+serde_as_text!(Seed);
+
+/// Where a blinding sits in the tree, written "m/account/change/index".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct KeyPath {
+    pub account: u32,
+    /// `RECEIVE` or `CHANGE`.
+    pub change: u32,
+    pub index: u32,
+}
+
+impl KeyPath {
+    pub fn new(account: u32, change: u32, index: u32) -> Self {
+        KeyPath { account, change, index }
+    }
+}
+
+impl fmt::Display for KeyPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m/{}/{}/{}", self.account, self.change, self.index)
+    }
+}
+
+impl FromStr for KeyPath {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || CtError::MalformedInput(format!("'{}' is not a path m/account/change/index", s));
+        let levels = s
+            .strip_prefix("m/")
+            .ok_or_else(malformed)?
+            .split('/')
+            .map(|level| level.parse::<u32>().map_err(|_| malformed()))
+            .collect::<Result<Vec<_>, _>>()?;
+        match levels[..] {
+            [account, change, index] => Ok(KeyPath { account, change, index }),
+            _ => Err(malformed()),
+        }
+    }
+}
+
+serde_as_text!(KeyPath);
+
+/// The first 4 bytes of a SHA-256 digest as a blinding below 2^32, like `Blinding::random`.
+fn blinding_from(digest: &[u8]) -> Blinding {
+    Blinding::new(u32::from_le_bytes(digest[..4].try_into().expect("SHA-256 has 32 bytes")) as i64)
+}
+
+/// What a watch-only wallet holds for one account: the public k*H. It derives
+/// every r*H of the account, so it finds the outputs, but none of their blindings.
+/// Written view/account/K, K in hex with `{:#}`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ViewKey {
+    pub account: u32,
+    pub public: Commitment,
+}
+
+impl ViewKey {
+    /// t for `m/account/change/index`: SHA-256("ct/blinding" ‖ k*H ‖ change ‖ index).
+    fn tweak(&self, change: u32, index: u32) -> Blinding {
+        let mut hasher = Sha256::new();
+        hasher.update(b"ct/blinding");
+        hasher.update(self.public.as_i64().to_le_bytes());
+        hasher.update(change.to_le_bytes());
+        hasher.update(index.to_le_bytes());
+        blinding_from(&hasher.finalize())
+    }
+
+    /// r*H for the blinding at `m/account/change/index`, without knowing r.
+    pub fn public_blinding(&self, change: u32, index: u32) -> Commitment {
+        self.public + public_key(self.tweak(change, index))
+    }
+}
+
+impl fmt::Display for ViewKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return write!(f, "view/{}/{:#}", self.account, self.public);
+        }
+        write!(f, "view/{}/{}", self.account, self.public)
+    }
+}
+
+impl FromStr for ViewKey {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || CtError::MalformedInput(format!("'{}' is not a view key view/account/point", s));
+        let (account, public) = s.strip_prefix("view/").and_then(|rest| rest.split_once('/')).ok_or_else(malformed)?;
+        Ok(ViewKey {
+            account: account.parse().map_err(|_| malformed())?,
+            public: public.parse().map_err(|_| malformed())?,
+        })
+    }
+}
+
+serde_as_text!(ViewKey);
+
+/// Derives blindings from a seed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keychain {
+    seed: Seed,
+}
+
+impl Keychain {
+    pub fn new(seed: Seed) -> Self {
+        Keychain { seed }
+    }
+
+    pub fn seed(&self) -> &Seed {
+        &self.seed
+    }
+
+    /// The account secret k = SHA-256("ct/account" ‖ seed ‖ account).
+    fn account_secret(&self, account: u32) -> Blinding {
+        let mut hasher = Sha256::new();
+        hasher.update(b"ct/account");
+        hasher.update(self.seed.0);
+        hasher.update(account.to_le_bytes());
+        blinding_from(&hasher.finalize())
+    }
+
+    /// The nonce of a rewindable range proof for `commitment`: SHA-256("ct/rewind" ‖ seed ‖ C).
+    pub fn rewind_nonce(&self, commitment: Commitment) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"ct/rewind");
+        hasher.update(self.seed.0);
+        hasher.update(commitment.as_i64().to_le_bytes());
+        hasher.finalize().into()
+    }
+
+    /// The secret a = SHA-256("ct/scan" ‖ seed) behind the wallet's address.
+    pub fn scan_secret(&self) -> Blinding {
+        let mut hasher = Sha256::new();
+        hasher.update(b"ct/scan");
+        hasher.update(self.seed.0);
+        blinding_from(&hasher.finalize())
+    }
+
+    /// The secret b = SHA-256("ct/spend" ‖ seed), needed for one-time keys.
+    pub fn spend_secret(&self) -> Blinding {
+        let mut hasher = Sha256::new();
+        hasher.update(b"ct/spend");
+        hasher.update(self.seed.0);
+        blinding_from(&hasher.finalize())
+    }
+
+    /// The stealth address (a*H, b*H) to publish; `ecdh::send_to` pays to it.
+    pub fn address(&self) -> Address {
+        Address { scan: public_key(self.scan_secret()), spend: public_key(self.spend_secret()) }
+    }
+
+    /// The view key k*H of `account`, for `ct wallet export-viewkey`.
+    pub fn view_key(&self, account: u32) -> ViewKey {
+        ViewKey { account, public: public_key(self.account_secret(account)) }
+    }
+
+    /// The blinding at `path`: r = k + t, below 2^33.
+    pub fn blinding(&self, path: KeyPath) -> Blinding {
+        let tweak = self.view_key(path.account).tweak(path.change, path.index);
+        Blinding::new(self.account_secret(path.account).as_i64() + tweak.as_i64())
+    }
+}
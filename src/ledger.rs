@@ -0,0 +1,186 @@
+//! In-memory ledger: the set of unspent outputs (UTXOs). Applying a transaction
+//! checks it, spends its inputs and adds its outputs under `txid:index`.
+//! Inputs are matched by commitment, the only thing a confidential input reveals.
+//! Spent commitments are remembered so a second spend is reported as a double spend.
+//! Blocks (`apply_block`) extend the chain whose tip the ledger remembers.
+//! Every output ever created is also appended to an MMR, whose root block headers anchor.
+//! The public amounts (minted, fees, burned) add up to the supply: what the unspent
+//! outputs hold between them, though no single amount is visible.
+//! A locked output keeps its condition while unspent, and an input spending it must
+//! meet the condition (see `condition`) at the height of the next block. A
+//! height-locked output or kernel needs the next block to be at least at its lock
+//! height. A relative lock of N becomes the height N above the block that creates
+//! the output, so a party can be given time to respond before an output is spent
+//! (a penalty).
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::{Block, BlockHash, BlockHeader};
+use crate::commitment::Commitment;
+use crate::condition::Condition;
+use crate::error::CtError;
+use crate::mmr::Mmr;
+use crate::transaction::{OutPoint, Transaction, TxId};
+
+/// Totals of the public amounts of every transaction applied.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Supply {
+    pub minted: u64,
+    pub fees: u64,
+    pub burned: u64,
+}
+
+impl Supply {
+    /// minted - fees - burned: the value held by the unspent outputs.
+    pub fn circulating(&self) -> u64 {
+        self.minted.saturating_sub(self.fees + self.burned)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Ledger {
+    utxos: BTreeMap<OutPoint, Commitment>,
+    locks: BTreeMap<OutPoint, Condition>,
+    lock_heights: BTreeMap<OutPoint, u64>,
+    spent: HashSet<Commitment>,
+    tip: Option<BlockHeader>,
+    outputs: Mmr,
+    supply: Supply,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Ledger::default()
+    }
+
+    /// Header of the last block applied; None before the genesis block.
+    pub fn tip(&self) -> Option<&BlockHeader> {
+        self.tip.as_ref()
+    }
+
+    /// Height of the block the next transactions go into.
+    pub fn next_height(&self) -> u64 {
+        self.tip.as_ref().map_or(0, |tip| tip.height + 1)
+    }
+
+    /// The condition on an unspent output, if it has one.
+    pub fn lock(&self, outpoint: &OutPoint) -> Option<&Condition> {
+        self.locks.get(outpoint)
+    }
+
+    /// Validate `block` on top of the tip, then apply its transactions in order.
+    pub fn apply_block(&mut self, block: &Block) -> Result<BlockHash, CtError> {
+        block.validate(self)?;
+        for tx in &block.transactions {
+            self.apply(tx)?;
+        }
+        self.tip = Some(block.header.clone());
+        Ok(block.header.hash())
+    }
+
+    /// Every output created so far, spent or not.
+    pub fn output_mmr(&self) -> &Mmr {
+        &self.outputs
+    }
+
+    pub fn supply(&self) -> Supply {
+        self.supply
+    }
+
+    /// Unspent outputs, ordered by outpoint.
+    pub fn utxos(&self) -> impl Iterator<Item = (&OutPoint, &Commitment)> {
+        self.utxos.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.utxos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.utxos.is_empty()
+    }
+
+    /// The commitment of the unspent output at `outpoint`, if there is one.
+    pub fn get(&self, outpoint: &OutPoint) -> Option<Commitment> {
+        self.utxos.get(outpoint).copied()
+    }
+
+    /// Where an unspent output with this commitment lives, if there is one.
+    pub fn find(&self, commitment: Commitment) -> Option<OutPoint> {
+        self.utxos.iter().find(|(_, &c)| c == commitment).map(|(&outpoint, _)| outpoint)
+    }
+
+    /// Check that every input of `tx` is unspent and unlocked, and its kernel's lock
+    /// height reached, without changing anything.
+    pub fn check_inputs(&self, tx: &Transaction) -> Result<(), CtError> {
+        self.spendable(tx).map(|_| ())
+    }
+
+    /// The outpoints `tx` would spend: spending an output twice (here or within
+    /// `tx`) is `DoubleSpend`, one never created is `UnknownInput`, and a locked one
+    /// without meeting its lock is `Locked`, as is a kernel below its lock height.
+    fn spendable(&self, tx: &Transaction) -> Result<Vec<OutPoint>, CtError> {
+        let height = self.next_height();
+        if let Some(kernel) = tx.kernel.filter(|kernel| height < kernel.lock_height) {
+            let lock_height = kernel.lock_height;
+            let reason = format!("kernel {}: not before height {} (this is {})", kernel.excess, lock_height, height);
+            return Err(CtError::Locked(reason));
+        }
+        let mut spending = Vec::new();
+        for input in &tx.inputs {
+            let outpoint = self
+                .utxos
+                .iter()
+                .find(|(outpoint, &c)| c == input.commitment && !spending.contains(*outpoint))
+                .map(|(&outpoint, _)| outpoint);
+            match outpoint {
+                Some(outpoint) => {
+                    if let Some(&lock_height) = self.lock_heights.get(&outpoint).filter(|&&h| height < h) {
+                        let reason = format!("not before height {} (this is {})", lock_height, height);
+                        return Err(CtError::Locked(format!("output {}: {}", input.commitment, reason)));
+                    }
+                    if let Some(lock) = self.locks.get(&outpoint) {
+                        lock.check(input.commitment, input.unlock.as_ref(), tx.kernel.as_ref(), height)?;
+                    }
+                    spending.push(outpoint);
+                }
+                None if self.spent.contains(&input.commitment) || self.find(input.commitment).is_some() => {
+                    return Err(CtError::DoubleSpend(input.commitment));
+                }
+                None => return Err(CtError::UnknownInput(input.commitment)),
+            }
+        }
+        Ok(spending)
+    }
+
+    /// Verify `tx`, spend its inputs and add its outputs. Nothing changes unless
+    /// the transaction is valid and every input is unspent (see `check_inputs`).
+    pub fn apply(&mut self, tx: &Transaction) -> Result<TxId, CtError> {
+        tx.verify()?;
+        for outpoint in &self.spendable(tx)? {
+            if let Some(commitment) = self.utxos.remove(outpoint) {
+                self.spent.insert(commitment);
+            }
+            self.locks.remove(outpoint);
+            self.lock_heights.remove(outpoint);
+        }
+        let txid = tx.txid();
+        for (outpoint, output) in tx.outpoints().into_iter().zip(&tx.outputs) {
+            self.utxos.insert(outpoint, output.commitment);
+            if let Some(lock) = &output.lock {
+                self.locks.insert(outpoint, lock.clone());
+            }
+            let lock_height = output.lock_height.max(self.next_height() + output.relative_lock);
+            if lock_height > self.next_height() {
+                self.lock_heights.insert(outpoint, lock_height);
+            }
+            self.outputs.append(output.commitment);
+        }
+        self.supply.minted += tx.minted;
+        self.supply.fees += tx.fee;
+        self.supply.burned += tx.burned;
+        Ok(txid)
+    }
+}
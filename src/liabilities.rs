@@ -0,0 +1,159 @@
+//! Proof of liabilities: a Merkle-sum tree (Maxwell) over Pedersen commitments.
+//!
+//! Each customer's balance is a leaf C_i = b_i*G + r_i*H, hashed with the customer's
+//! id. A parent commits to the sum of its children, C = C_left + C_right, and hashes
+//! both children's hashes and commitments, so the root commits to every balance and to
+//! their total L without showing any of them. Every node carries a range proof: no
+//! balance and no partial sum is negative, so the exchange cannot cancel a debt with
+//! a made-up negative account. The leaves are padded with zero balances up to a power
+//! of two, which also hides how many customers there are.
+//!
+//! The exchange publishes the root and gives each customer the opening of their leaf
+//! and an inclusion proof: the sibling of every node on the way up. The customer checks
+//! that their leaf holds their balance, that every sibling is in range, and that the
+//! path sums and hashes up to the published root. A customer left out, or given less,
+//! finds out. The exchange can then open the root's value (`opening_proof::prove_value`)
+//! and prove reserves of at least L (`reserves`): solvency.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::commitment::{Blinding, Commitment, Opening, Scalar};
+use crate::error::CtError;
+use crate::range_proof::{range_proof_create, range_proof_verify, RangeProof};
+use crate::rng::ToyRng;
+use crate::wire::Encode;
+
+/// The hash of a customer's leaf: their id and their commitment.
+fn leaf_hash(id: &str, commitment: Commitment) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0]);
+    hasher.update((id.len() as u32).to_le_bytes());
+    hasher.update(id.as_bytes());
+    hasher.update(commitment.to_bytes());
+    hasher.finalize().into()
+}
+
+/// A tree node as published: its hash, the commitment to its sum, and a range proof.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SumNode {
+    #[serde(with = "crate::wire::hex")]
+    pub hash: [u8; 32],
+    pub commitment: Commitment,
+    pub proof: RangeProof,
+}
+
+impl SumNode {
+    /// What a parent is computed from: the hash and the commitment.
+    fn summary(&self) -> ([u8; 32], Commitment) {
+        (self.hash, self.commitment)
+    }
+}
+
+/// The parent's hash and commitment; its range proof needs the opening.
+fn parent(left: ([u8; 32], Commitment), right: ([u8; 32], Commitment)) -> ([u8; 32], Commitment) {
+    let mut hasher = Sha256::new();
+    hasher.update([1]);
+    for (hash, commitment) in [left, right] {
+        hasher.update(hash);
+        hasher.update(commitment.to_bytes());
+    }
+    (hasher.finalize().into(), left.1 + right.1)
+}
+
+/// A customer's path to the root: their leaf and the siblings on the way up.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub index: usize,
+    pub leaf: SumNode,
+    pub siblings: Vec<SumNode>,
+}
+
+impl InclusionProof {
+    /// Check, as customer `id` holding the leaf opening `opening`, that the leaf holds
+    /// it, that every node on the path is in range, and that the path leads to `root`.
+    pub fn verify(&self, root: &SumNode, id: &str, opening: &Opening) -> Result<(), CtError> {
+        if self.leaf.commitment != opening.commit() || self.leaf.hash != leaf_hash(id, self.leaf.commitment) {
+            return Err(CtError::InvalidProof(format!("the leaf is not {}'s balance of {}", id, opening.value)));
+        }
+        range_proof_verify(self.leaf.commitment, &self.leaf.proof)?;
+        let mut node = self.leaf.summary();
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            range_proof_verify(sibling.commitment, &sibling.proof)?;
+            node = match (self.index >> level) & 1 {
+                0 => parent(node, sibling.summary()),
+                _ => parent(sibling.summary(), node),
+            };
+        }
+        if node != root.summary() {
+            return Err(CtError::InvalidProof("the path does not lead to the published root".to_string()));
+        }
+        range_proof_verify(root.commitment, &root.proof)
+    }
+}
+
+/// The whole tree, as the exchange keeps it: every node with its opening, by level.
+#[derive(Clone, Debug)]
+pub struct LiabilityTree {
+    ids: Vec<String>,
+    /// levels[0] are the leaves; the last level is the root alone.
+    levels: Vec<Vec<(SumNode, Opening)>>,
+}
+
+impl LiabilityTree {
+    /// Commit to every (id, balance), pad with zero balances and sum up to the root.
+    pub fn new(accounts: &[(String, u64)], rng: &mut ToyRng) -> Result<Self, CtError> {
+        if accounts.is_empty() {
+            return Err(CtError::MalformedInput("a liability tree needs at least one account".to_string()));
+        }
+        let ids: Vec<String> = accounts.iter().map(|(id, _)| id.clone()).collect();
+        let mut leaves = Vec::new();
+        for i in 0..accounts.len().next_power_of_two() {
+            let (id, balance) = accounts.get(i).map_or(("", 0), |(id, balance)| (id.as_str(), *balance));
+            let opening = Opening::new(Scalar::new(balance as i64), Blinding::random(rng));
+            let proof = range_proof_create(opening.value, opening.blinding, rng)?;
+            let commitment = opening.commit();
+            leaves.push((SumNode { hash: leaf_hash(id, commitment), commitment, proof }, opening));
+        }
+        let mut levels = vec![leaves];
+        while let Some(below) = levels.last().filter(|level| level.len() > 1) {
+            let mut level = Vec::new();
+            for pair in below.chunks(2) {
+                let ((left, left_opening), (right, right_opening)) = (&pair[0], &pair[1]);
+                let value = Scalar::new(left_opening.value.as_i64() + right_opening.value.as_i64());
+                let opening = Opening::new(value, left_opening.blinding + right_opening.blinding);
+                let (hash, commitment) = parent(left.summary(), right.summary());
+                let proof = range_proof_create(opening.value, opening.blinding, rng)?;
+                level.push((SumNode { hash, commitment, proof }, opening));
+            }
+            levels.push(level);
+        }
+        Ok(LiabilityTree { ids, levels })
+    }
+
+    /// What the exchange publishes.
+    pub fn root(&self) -> &SumNode {
+        &self.levels.last().expect("the tree has a root")[0].0
+    }
+
+    /// The opening of the root: total liabilities L and the sum of every blinding.
+    pub fn total(&self) -> Opening {
+        self.levels.last().expect("the tree has a root")[0].1
+    }
+
+    /// The customer at `index`, with the opening of their leaf, which the exchange sends them.
+    pub fn account(&self, index: usize) -> Option<(&str, Opening)> {
+        Some((self.ids.get(index)?.as_str(), self.levels[0][index].1))
+    }
+
+    /// The inclusion proof for the customer at `index`.
+    pub fn prove(&self, index: usize) -> Option<InclusionProof> {
+        self.ids.get(index)?;
+        let siblings = self.levels[..self.levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(level, nodes)| nodes[(index >> level) ^ 1].0.clone())
+            .collect();
+        Some(InclusionProof { index, leaf: self.levels[0][index].0.clone(), siblings })
+    }
+}
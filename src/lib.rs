@@ -0,0 +1,119 @@
+//! Educational Demo: Confidential Transactions with Pedersen Commitments
+//! =====================================================================
+//! Library half of the demo: commitments, range proofs and the toy arithmetic
+//! behind them. The binary (`main.rs`) walks through a transaction using these.
+//! TOY code for learning — NOT real cryptography. Do not use in production.
+//!
+//! Every value that goes into a file or over the wire implements serde's
+//! `Serialize`/`Deserialize`, in the shape of the JSON files the binary reads and
+//! writes: commitments and keys as numbers, hashes and byte strings as hex, proofs,
+//! txids, outpoints and key paths as the text the command line shows. Binary formats
+//! such as CBOR (`is_human_readable` false) get proofs in their wire encoding and
+//! hashes and byte strings as raw bytes instead.
+
+/// Serde through a type's `Display` and `FromStr`, for values that already have a
+/// text form (txids, key paths, ...): `serde_as_text!(TxId, OutPoint)`. Proofs, whose
+/// `Display` is only a summary, go through `encode` and `FromStr` in text formats and
+/// through their wire encoding in binary ones: `serde_as_text!(RangeProof => encode)`.
+macro_rules! serde_as_text {
+    ($ty:ty => $encode:ident) => {
+        impl serde::Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if serializer.is_human_readable() {
+                    serializer.serialize_str(&self.$encode())
+                } else {
+                    serializer.serialize_bytes(&crate::wire::Encode::to_bytes(self))
+                }
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                if deserializer.is_human_readable() {
+                    let text = <String as serde::Deserialize>::deserialize(deserializer)?;
+                    text.parse().map_err(serde::de::Error::custom)
+                } else {
+                    let bytes = <Vec<u8> as serde::Deserialize>::deserialize(deserializer)?;
+                    <$ty as crate::wire::Encode>::from_bytes(&bytes).map_err(serde::de::Error::custom)
+                }
+            }
+        }
+    };
+    ($($ty:ty),+) => {
+        $(
+            impl serde::Serialize for $ty {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for $ty {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let text = <String as serde::Deserialize>::deserialize(deserializer)?;
+                    text.parse().map_err(serde::de::Error::custom)
+                }
+            }
+        )+
+    };
+}
+
+pub mod aead;
+pub mod analysis;
+pub mod armor;
+pub mod assets;
+pub mod audit;
+pub mod batch;
+pub mod big_field;
+pub mod block;
+pub mod builder;
+pub mod chain;
+pub mod coin_selection;
+pub mod coinjoin;
+pub mod commitment;
+pub mod condition;
+pub mod cut_through;
+pub mod ecdh;
+pub mod elements;
+pub mod error;
+pub mod field;
+pub mod fixed_base;
+pub mod generators;
+pub mod grin;
+pub mod horizon;
+pub mod issuance;
+pub mod kdf;
+pub mod kernel;
+pub mod keychain;
+pub mod ledger;
+pub mod liabilities;
+pub mod light_client;
+pub mod memo;
+pub mod mempool;
+pub mod mlsag;
+pub mod mmr;
+pub mod mnemonic;
+pub mod monero;
+pub mod mpc;
+pub mod msm;
+pub mod multisig;
+pub mod musig;
+pub mod opening_proof;
+pub mod params;
+pub mod payment_proof;
+pub mod png;
+pub mod qr;
+pub mod range_proof;
+pub mod reserves;
+pub mod ringct;
+pub mod rng;
+pub mod secp256k1;
+pub mod shamir;
+pub mod sigma;
+pub mod slate;
+pub mod swap;
+pub mod toy_curve;
+pub mod transaction;
+pub mod transcript;
+pub mod wallet;
+pub mod watch;
+pub mod wire;
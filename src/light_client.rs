@@ -0,0 +1,51 @@
+//! Light client: keeps only the chain of block headers. Each header anchors the
+//! output MMR root, so a full node can convince the client that an output exists
+//! with an `MmrProof`, without the client holding any transactions or UTXOs.
+//! The client learns that the commitment is on chain, and nothing about its amount.
+
+use crate::block::BlockHeader;
+use crate::commitment::Commitment;
+use crate::error::CtError;
+use crate::mmr::MmrProof;
+
+#[derive(Clone, Debug, Default)]
+pub struct LightClient {
+    headers: Vec<BlockHeader>,
+}
+
+impl LightClient {
+    pub fn new() -> Self {
+        LightClient::default()
+    }
+
+    pub fn headers(&self) -> &[BlockHeader] {
+        &self.headers
+    }
+
+    pub fn tip(&self) -> Option<&BlockHeader> {
+        self.headers.last()
+    }
+
+    /// Append `headers`, each of which must link to the one before it (the first to
+    /// our tip, or be a genesis header). Stops at the first one that does not.
+    pub fn sync<'a>(&mut self, headers: impl IntoIterator<Item = &'a BlockHeader>) -> Result<(), CtError> {
+        for header in headers {
+            let prev = self.tip().map(BlockHeader::hash).unwrap_or_default();
+            let height = self.headers.len() as u64;
+            if header.prev != prev || header.height != height {
+                return Err(CtError::MalformedInput(format!(
+                    "header {} does not extend the tip at height {}",
+                    header.hash(),
+                    height
+                )));
+            }
+            self.headers.push(header.clone());
+        }
+        Ok(())
+    }
+
+    /// Does `proof` show that `commitment` is an output as of the tip?
+    pub fn verify_output(&self, commitment: Commitment, proof: &MmrProof) -> bool {
+        self.tip().is_some_and(|tip| proof.verify(&tip.output_root, commitment))
+    }
+}
@@ -5,230 +5,2911 @@
 //!
 //! Pedersen commitments let us prove "sum(inputs) = sum(outputs)" without
 //! revealing the actual amounts. We only ever show commitments C = v*G + r*H.
+//!
+//! `ct demo` runs the walkthrough; `ct commit` and `ct verify` let you experiment.
+
+#[macro_use]
+mod report;
+mod attack;
+mod audit_file;
+mod challenge;
+mod chain_file;
+mod config;
+mod demo;
+mod exercise;
+mod grin_file;
+mod multisig_file;
+mod payment_proof_file;
+mod por_file;
+mod prompt;
+mod quiz;
+mod repl;
+mod slate_file;
+mod swap_file;
+mod tx_file;
+mod vectors;
+mod walkthrough;
+mod wallet_file;
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
+use pedersen_commitment_tx::analysis::{self, brute_force_openings};
+use pedersen_commitment_tx::armor::{self, armor};
+use pedersen_commitment_tx::audit::Disclosure;
+use pedersen_commitment_tx::block::Block;
+use pedersen_commitment_tx::chain::Chain;
+use pedersen_commitment_tx::coin_selection::Strategy;
+use pedersen_commitment_tx::commitment::{
+    fee_commitment, pedersen_commit, sum_commitments, verify_balance, Blinding, Commitment, Opening, Scalar,
+};
+use pedersen_commitment_tx::condition::{hash_preimage, PREIMAGE_LEN};
+use pedersen_commitment_tx::ecdh::{self, Address};
+use pedersen_commitment_tx::elements::{self, ElementsTx};
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::horizon::Compacted;
+use pedersen_commitment_tx::kernel::public_key;
+use pedersen_commitment_tx::keychain::{Keychain, Seed, ViewKey, CHANGE, RECEIVE};
+use pedersen_commitment_tx::ledger::Ledger;
+use pedersen_commitment_tx::mnemonic::Mnemonic;
+use pedersen_commitment_tx::monero;
+use pedersen_commitment_tx::multisig::{Funding, Offer, OfferSecrets, Share, Spend, SpendSecrets};
+use pedersen_commitment_tx::payment_proof::PaymentProof;
+use pedersen_commitment_tx::png::Image;
+use pedersen_commitment_tx::qr::{self, QrCode};
+use pedersen_commitment_tx::range_proof::{
+    interval_proof_create, interval_proof_verify, range_proof_create, range_proof_verify,
+    IntervalProof, RangeProof,
+};
+use pedersen_commitment_tx::params;
+use pedersen_commitment_tx::ringct;
+use pedersen_commitment_tx::rng::ToyRng;
+use pedersen_commitment_tx::secp256k1;
+use pedersen_commitment_tx::slate::{SenderContext, Slate};
+use pedersen_commitment_tx::swap::{Contract, HashLock};
+use pedersen_commitment_tx::reserves::ReservesProof;
+use pedersen_commitment_tx::transaction::{OutPoint, Transaction};
+use pedersen_commitment_tx::toy_curve;
+use pedersen_commitment_tx::wallet::{Direction, HistoryEntry, OutputStatus, Payment, Wallet};
+use pedersen_commitment_tx::watch::WatchWallet;
+use pedersen_commitment_tx::wire::{from_hex, to_hex, to_versioned_hex, Encode};
+use serde_json::json;
+use slate_file::{parse_json, to_cbor, to_json};
+use swap_file::SwapSecrets;
+use tx_file::Format;
+use wallet_file::Storage;
+
+use config::{Backend, Config, ConfigArgs};
+use report::{interval_proof_json, outcome, proof_json, result_json, Report};
+
+/// Exit codes of `ct verify`, so it can be used in shell pipelines (0 = valid).
+const EXIT_BALANCE_FAILURE: u8 = 1;
+const EXIT_RANGE_PROOF_FAILURE: u8 = 2;
+const EXIT_MALFORMED_INPUT: u8 = 3;
+/// Most blindings `ct crack` tries before it stops and extrapolates instead.
+const CRACK_LIMIT: i64 = 1 << 22;
+/// Where wallet commands read the passphrase of an encrypted wallet file, if set.
+const PASSPHRASE_VAR: &str = "CT_WALLET_PASSPHRASE";
+
+#[derive(Parser)]
+#[command(name = "ct", about = "Confidential transactions with toy Pedersen commitments")]
+struct Cli {
+    /// Emit one machine-readable JSON document instead of text
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(flatten)]
+    config: ConfigArgs,
+    /// Defaults to `demo` when omitted.
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the full walkthrough (honest transaction, negative-value attack, range proofs)
+    Demo {
+        /// Pause before each step and ask you to predict the outcome first
+        #[arg(long, conflicts_with = "json")]
+        interactive: bool,
+    },
+    /// Print the commitment C = v*G + r*H (in the group chosen by --backend)
+    Commit {
+        /// The secret value v
+        #[arg(long, allow_negative_numbers = true)]
+        value: i64,
+        /// The secret blinding factor r
+        #[arg(long, allow_negative_numbers = true)]
+        blinding: i64,
+        /// Print C in hex (toy and bigfield backends; monero and secp256k1 always are), which every command accepts
+        #[arg(long)]
+        hex: bool,
+    },
+    /// Verify a transaction file (`-` reads stdin), or only the balance of --inputs/--outputs.
+    /// Exit code: 0 valid, 1 balance failure, 2 range-proof failure, 3 malformed input.
+    Verify {
+        /// Transaction JSON (or hex) file, or `-` for stdin (see `ct demo --json | jq .transaction`)
+        #[arg(conflicts_with_all = ["inputs", "outputs"], required_unless_present = "inputs")]
+        tx: Option<String>,
+        /// Input commitments, comma-separated
+        #[arg(long, value_delimiter = ',', requires = "outputs")]
+        inputs: Vec<Commitment>,
+        /// Output commitments, comma-separated
+        #[arg(long, value_delimiter = ',', requires = "inputs")]
+        outputs: Vec<Commitment>,
+        /// Public fee: checks sum(inputs) = sum(outputs) + fee*G
+        #[arg(long, default_value_t = 0, requires = "inputs")]
+        fee: u64,
+    },
+    /// Print a transaction file in the canonical binary wire format (or CBOR), as hex
+    Encode {
+        /// Transaction JSON file, or `-` for stdin
+        tx: String,
+        /// Print armored text for pasting into chat or email instead of hex
+        #[arg(long, conflicts_with_all = ["format", "sizes"])]
+        armor: bool,
+        /// wire, cbor or json
+        #[arg(long, value_enum, default_value_t = Format::Wire)]
+        format: Format,
+        /// Compare the transaction's size in every format instead of printing it
+        #[arg(long, conflicts_with = "format")]
+        sizes: bool,
+    },
+    /// Decode a transaction from another system and line its fields up with this demo's
+    Inspect {
+        #[command(subcommand)]
+        action: InspectCommand,
+    },
+    /// Check sum(in) = sum(out) for any number of input and output commitments
+    VerifyBalance {
+        /// Input commitments, comma-separated
+        #[arg(long = "in", value_delimiter = ',', required = true)]
+        inputs: Vec<Commitment>,
+        /// Output commitments, comma-separated
+        #[arg(long = "out", value_delimiter = ',', required = true)]
+        outputs: Vec<Commitment>,
+        /// Public fee: checks sum(inputs) = sum(outputs) + fee*G
+        #[arg(long, default_value_t = 0)]
+        fee: u64,
+    },
+    /// Create a range proof (prover) or check one (verifier) for a single commitment
+    Rangeproof {
+        #[command(subcommand)]
+        action: RangeproofCommand,
+    },
+    /// Slate flow, step 1 (sender): spend an input and write a slate for the receiver
+    Send {
+        /// Value of the input you spend
+        #[arg(long, allow_negative_numbers = true)]
+        input_value: i64,
+        /// Blinding factor of the input you spend
+        #[arg(long, allow_negative_numbers = true)]
+        input_blinding: i64,
+        /// Amount to pay the receiver
+        #[arg(long)]
+        amount: u64,
+        /// Public fee
+        #[arg(long, default_value_t = 0)]
+        fee: u64,
+        /// Where to write the slate for the receiver
+        #[arg(long)]
+        slate_out: String,
+        /// Where to keep your secrets until `ct finalize` (never send this file)
+        #[arg(long, default_value = "sender-context.json")]
+        context: String,
+        /// Write the slate as armored text (and print it) for pasting into chat or email
+        #[arg(long)]
+        armor: bool,
+        /// Ask the receiver at this address (`ct wallet address`) to sign a payment proof
+        #[arg(long)]
+        payment_proof: Option<Address>,
+        /// Seed for blindings and nonces (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Slate flow, step 2 (receiver): add your output and partial signature
+    Receive {
+        /// Slate from the sender
+        #[arg(long)]
+        slate_in: String,
+        /// Where to write the slate for the sender
+        #[arg(long)]
+        slate_out: String,
+        /// Write the slate as armored text (and print it) for pasting into chat or email
+        #[arg(long)]
+        armor: bool,
+        /// Seed for the blinding and nonce (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Add the new output to this wallet file and record the payment in its history
+        /// (needed to sign a payment proof the sender asked for)
+        #[arg(long)]
+        wallet: Option<String>,
+    },
+    /// Slate flow, step 3 (sender): complete the kernel signature
+    Finalize {
+        /// Slate back from the receiver
+        #[arg(long)]
+        slate_in: String,
+        /// The context file `ct send` wrote
+        #[arg(long, default_value = "sender-context.json")]
+        context: String,
+        /// Write the finished transaction here, for `ct verify`
+        #[arg(long)]
+        tx_out: Option<String>,
+        /// Write the transaction as armored text instead of JSON
+        #[arg(long, requires = "tx_out")]
+        armor: bool,
+        /// Add the change output to this wallet file and record the payment in its history
+        #[arg(long)]
+        wallet: Option<String>,
+        /// Where to write the payment proof, if `ct send --payment-proof` asked for one
+        #[arg(long, default_value = "payment-proof.json")]
+        proof_out: String,
+    },
+    /// Show a slate or transaction as a QR code for another device, or read one back
+    Qr {
+        #[command(subcommand)]
+        action: QrCommand,
+    },
+    /// 2-of-2 outputs: fund one jointly with a cosigner, and spend it jointly later
+    Multisig {
+        #[command(subcommand)]
+        action: MultisigCommand,
+    },
+    /// Atomic swaps: lock coins on two chains to one hash, so claiming one side shows how to claim the other
+    Swap {
+        #[command(subcommand)]
+        action: SwapCommand,
+    },
+    /// Keep a toy chain in a file between runs: start it, mine blocks onto it, show it
+    Chain {
+        /// The chain file
+        #[arg(long, default_value = "chain.json")]
+        file: String,
+        #[command(subcommand)]
+        action: ChainCommand,
+    },
+    /// Keep the openings of your outputs in a file: add them, follow the chain, show the balance
+    Wallet {
+        /// The wallet file
+        #[arg(long, default_value = "wallet.json")]
+        file: String,
+        #[command(subcommand)]
+        action: WalletCommand,
+    },
+    /// Follow an account with only its view key: find its outputs and balance, but never spend
+    Watch {
+        /// The watch-only wallet file
+        #[arg(long, default_value = "watch.json")]
+        file: String,
+        #[command(subcommand)]
+        action: WatchCommand,
+    },
+    /// Auditor: check a disclosure file (`ct wallet disclose`) against the chain
+    Audit {
+        /// The disclosure file
+        disclosure: String,
+        /// The chain file
+        #[arg(long, default_value = "chain.json")]
+        chain: String,
+    },
+    /// Check a payment proof from the slate flow (`ct send --payment-proof`)
+    PaymentProof {
+        #[command(subcommand)]
+        action: PaymentProofCommand,
+    },
+    /// Proof of reserves: show your unspent outputs hold at least an amount, without showing any of them
+    Por {
+        #[command(subcommand)]
+        action: PorCommand,
+    },
+    /// Interactive session: commit, add, prove and verify step by step
+    Repl,
+    /// Run one attack on a transaction and show which check catches it
+    Attack {
+        /// Scenario name; leave out to list them all
+        #[arg(long)]
+        scenario: Option<String>,
+    },
+    /// Simulate one payment history on a plaintext and a confidential ledger, and analyse both
+    Analyze {
+        /// Number of payments
+        #[arg(long, default_value_t = 30)]
+        payments: usize,
+        /// Seed for the simulated history
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// RingCT: spend an output hidden among decoys, then try to spend it again
+    Ringct {
+        /// Outputs per ring, the real one included
+        #[arg(long, default_value_t = ringct::RING_SIZE)]
+        ring_size: usize,
+        /// Seed for the toy ledger and the wallet's randomness
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// Confidential assets: one transaction moves two assets, and nobody can tell which output holds which
+    Assets {
+        /// Seed for the blindings
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// Burn coins in the clear and watch the supply shrink, provably, without any amount shown
+    Burn {
+        /// Seed for the blindings
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// Solvency: customer balances in a Merkle-sum tree, checked by each customer, against proven reserves
+    Solvency {
+        /// Seed for the blindings
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// Threshold custody: an output's blinding split 2-of-3, and two custodians spend it
+    Shamir {
+        /// Seed for the blindings and the sharing polynomial
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// MuSig2: several wallets spend into one transaction with a single, jointly signed kernel
+    Musig {
+        /// How many signers
+        #[arg(long, default_value_t = 3)]
+        signers: usize,
+        /// Seed for the blindings and nonces
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// MPC range proof: three parties prove their outputs in range in one aggregate proof, amounts kept apart
+    Mpc {
+        /// Seed for the blindings and proof randomness
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// CoinJoin: a coordinator merges three wallets' payments into one transaction with one kernel
+    Coinjoin {
+        /// Seed for the blindings and nonces
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// Time locks: a payment whose kernel and outputs are locked to heights, rejected early and accepted later
+    Timelock {
+        /// Seed for the blindings and nonces
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// Escrow: a payment locked to any two of buyer, seller and arbiter, or to the buyer alone later
+    Escrow {
+        /// Seed for the blindings and nonces
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// Find (v, r) for a commitment by trying every blinding; use a tiny --modulus
+    Crack {
+        /// The commitment to open
+        #[arg(long)]
+        commitment: Commitment,
+        /// Only try blindings below this (default: all of [0, p))
+        #[arg(long)]
+        max_blinding: Option<i64>,
+    },
+    /// Homework: a sheet of generated problems; --key prints the answers for the same seed
+    Exercise {
+        /// Seed for the sheet (default: from the clock; printed so the key can be made later)
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Number of problems
+        #[arg(long, default_value_t = 3)]
+        count: u32,
+        /// Instructor mode: print the answer key
+        #[arg(long)]
+        key: bool,
+    },
+    /// Capture-the-flag puzzles: generate a level, then check your answer
+    Challenge {
+        #[command(subcommand)]
+        action: ChallengeCommand,
+    },
+    /// Known-answer test vectors for checking another implementation against this one
+    Vectors {
+        #[command(subcommand)]
+        action: VectorsCommand,
+    },
+    /// Self-study: random transactions and proofs; predict whether each check passes
+    Quiz {
+        /// Number of questions
+        #[arg(long, default_value_t = 5)]
+        rounds: u32,
+        /// Seed for the generated scenarios (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum QrCommand {
+    /// Draw a slate or transaction (JSON or armored) as a QR code holding its armored text
+    Encode {
+        /// Slate or transaction file, or `-` for stdin
+        file: String,
+        /// Also write the code to this PNG file
+        #[arg(long)]
+        png: Option<String>,
+    },
+    /// Read a code back from a PNG, or from the text `ct qr encode` printed
+    Decode {
+        /// PNG or text file, or `-` for stdin
+        file: String,
+        /// Write what the code holds to this file (e.g. for `ct receive --slate-in`) as well
+        #[arg(long)]
+        out: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum InspectCommand {
+    /// An Elements (Liquid) transaction: its commitments, range and surjection proofs, and fee
+    Elements {
+        /// The raw transaction as hex, or `-` for stdin
+        hex: String,
+    },
+    /// A Grin transaction or slate (JSON): its commitments and kernels, and whether it balances
+    Grin {
+        /// Transaction or slate JSON file, or `-` for stdin
+        file: String,
+    },
+    /// A Monero output commitment (32-byte hex, e.g. an `outPk`): decode it and try to open it
+    Monero {
+        commitment: String,
+        /// The amount, in piconero; without --mask, checks the coinbase convention G + amount*H
+        #[arg(long)]
+        amount: Option<u64>,
+        /// The output's mask, as 32-byte little-endian hex (what the receiver's wallet derives)
+        #[arg(long, requires = "amount")]
+        mask: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RangeproofCommand {
+    /// Prover: print C and π for a value you know the opening of
+    Create {
+        /// The secret value v
+        #[arg(long, allow_negative_numbers = true)]
+        value: i64,
+        /// The secret blinding factor r
+        #[arg(long, allow_negative_numbers = true)]
+        blinding: i64,
+        #[command(flatten)]
+        interval: IntervalArgs,
+        /// Seed for the proof's random nonces (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Print C and π in hex (π as its wire bytes, shorter than the decimals)
+        #[arg(long)]
+        hex: bool,
+    },
+    /// Verifier: check π against C without knowing v or r
+    Verify {
+        /// The commitment C
+        #[arg(long)]
+        commitment: Commitment,
+        #[command(flatten)]
+        interval: IntervalArgs,
+        /// The proof π, as printed by `rangeproof create` (with the same --min/--max)
+        #[arg(long)]
+        proof: String,
+    },
+}
+
+/// With either bound, `rangeproof` proves min <= v <= max instead of 0 <= v < 2^n.
+#[derive(clap::Args)]
+struct IntervalArgs {
+    /// Smallest allowed value (default 0)
+    #[arg(long, allow_negative_numbers = true)]
+    min: Option<i64>,
+    /// Largest allowed value (default 2^n - 1)
+    #[arg(long, allow_negative_numbers = true)]
+    max: Option<i64>,
+}
+
+impl IntervalArgs {
+    /// The interval asked for, if any.
+    fn bounds(&self) -> Option<(i64, i64)> {
+        if self.min.is_none() && self.max.is_none() {
+            return None;
+        }
+        let max = (1i64 << params::active().range_bits) - 1;
+        Some((self.min.unwrap_or(0), self.max.unwrap_or(max)))
+    }
+}
+
+#[derive(Subcommand)]
+enum ChainCommand {
+    /// Start a new chain whose genesis block holds these transactions (usually a coinbase)
+    Init {
+        /// Transaction files (JSON, hex or armored)
+        tx: Vec<String>,
+    },
+    /// Add a block holding these transactions on top of the tip
+    Mine {
+        /// Transaction files (JSON, hex or armored)
+        tx: Vec<String>,
+    },
+    /// Print the tip and the unspent outputs
+    Show,
+    /// Show how much compacting the blocks below a horizon saves, and check the result
+    Compact {
+        /// Number of recent blocks kept whole
+        #[arg(long, default_value_t = 1)]
+        horizon: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum WalletCommand {
+    /// Start an empty wallet from a new BIP39 mnemonic (printed once; it recreates every derived blinding)
+    Init {
+        /// Number of words in the new mnemonic: 12 or 24
+        #[arg(long, default_value_t = 12, conflicts_with_all = ["mnemonic", "seed"])]
+        words: usize,
+        /// Restore from this mnemonic (the words in quotes) instead of generating one
+        #[arg(long, conflicts_with = "seed")]
+        mnemonic: Option<Mnemonic>,
+        /// Restore from this seed (64 hex digits) instead of generating a mnemonic
+        #[arg(long)]
+        seed: Option<Seed>,
+        /// Encrypt the wallet file with a passphrase (asked twice, or $CT_WALLET_PASSPHRASE)
+        #[arg(long)]
+        encrypt: bool,
+    },
+    /// Rebuild a lost wallet from its mnemonic (or seed) and the chain: rewind range proofs, scan notes and memos
+    Restore {
+        /// The chain file to find the outputs in
+        #[arg(long)]
+        from_chain: String,
+        /// The wallet's mnemonic (the words in quotes)
+        #[arg(long, required_unless_present = "seed", conflicts_with = "seed")]
+        mnemonic: Option<Mnemonic>,
+        /// The wallet's seed (64 hex digits)
+        #[arg(long)]
+        seed: Option<Seed>,
+        /// Encrypt the wallet file with a passphrase (asked twice, or $CT_WALLET_PASSPHRASE)
+        #[arg(long)]
+        encrypt: bool,
+    },
+    /// Derive the blinding for a new output of yours from the seed, and add it
+    Derive {
+        /// The output's value
+        #[arg(long)]
+        value: i64,
+        /// Account number (the first level of the path m/account/change/index)
+        #[arg(long, default_value_t = 0)]
+        account: u32,
+        /// Derive on the change branch (m/account/1/index) instead of the receive branch
+        #[arg(long)]
+        change: bool,
+    },
+    /// Remember an output you can open (e.g. what `ct receive` printed)
+    Add {
+        /// The output's value
+        #[arg(long)]
+        value: i64,
+        /// The output's blinding factor
+        #[arg(long)]
+        blinding: i64,
+    },
+    /// Pay an amount from your unspent outputs: coin selection, change back to the wallet
+    Send {
+        /// Amount to pay
+        #[arg(long)]
+        amount: u64,
+        /// Public fee
+        #[arg(long, default_value_t = 0)]
+        fee: u64,
+        /// Coin selection: largest-first, smallest-first or branch-and-bound
+        #[arg(long, default_value_t = Strategy::LargestFirst)]
+        strategy: Strategy,
+        /// Pay to this address (`ct wallet address`): the receiver finds the output by scanning
+        #[arg(long)]
+        to: Option<Address>,
+        /// With --to: give the payment a random blinding and a memo sealing (v, r) to the address
+        #[arg(long, requires = "to")]
+        memo: bool,
+        /// Write the transaction here, for `ct verify` or `ct chain mine`
+        #[arg(long)]
+        tx_out: String,
+        /// Seed for the output blindings and proof nonces (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Print the address others pay to with `ct wallet send --to`
+    Address {
+        /// Also draw it as a QR code, for a payer's phone to scan
+        #[arg(long)]
+        qr: bool,
+    },
+    /// Find outputs paid to your address on the chain, add them and sync
+    Scan {
+        /// The chain file
+        #[arg(long, default_value = "chain.json")]
+        chain: String,
+    },
+    /// Mark outputs unspent or spent from the chain file's UTXO set
+    Sync {
+        /// The chain file
+        #[arg(long, default_value = "chain.json")]
+        chain: String,
+    },
+    /// Open some of your outputs to an auditor: their (v, r) and proofs that they are on the chain
+    Disclose {
+        /// The chain file
+        #[arg(long, default_value = "chain.json")]
+        chain: String,
+        /// Commitment of an output to disclose; repeat for more
+        #[arg(long = "output", required = true)]
+        outputs: Vec<Commitment>,
+        /// Write the disclosure here, for `ct audit`
+        #[arg(long, default_value = "disclosure.json")]
+        out: String,
+    },
+    /// Encrypt a plain wallet file with a passphrase; every command then asks for it
+    Encrypt,
+    /// Print an account's view key, for a watch-only wallet (`ct watch init`)
+    ExportViewkey {
+        /// The account to watch
+        #[arg(long, default_value_t = 0)]
+        account: u32,
+    },
+    /// Print the confirmed balance and what is still unconfirmed
+    Balance,
+    /// List every payment sent and received, with its time and confirmation status
+    History,
+    /// List every output with its value, blinding, status and outpoint
+    Outputs,
+}
+
+#[derive(Subcommand)]
+enum VectorsCommand {
+    /// Write commitments, sums, range proofs, signatures and transactions with their expected results
+    Generate {
+        /// Seed for the vectors (default: from the clock); the same seed gives the same file
+        #[arg(long)]
+        seed: Option<u64>,
+        #[arg(long, default_value = "vectors.json")]
+        out: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ChallengeCommand {
+    /// Write a puzzle for a level (1: shared blinding, 2: second opening, 3: printing press)
+    New {
+        #[arg(long)]
+        level: u32,
+        /// Seed for the puzzle (default: from the clock); the same seed gives the same puzzle
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Where to write the challenge
+        #[arg(long, default_value = "challenge.json")]
+        out: String,
+    },
+    /// Check an answer to a challenge file
+    Check {
+        #[arg(long, default_value = "challenge.json")]
+        challenge: String,
+        /// Your answer, in the format the challenge asks for
+        #[arg(long)]
+        answer: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum PorCommand {
+    /// Prove that the wallet's unspent outputs on the chain hold at least --amount
+    Create {
+        /// The wallet file (run `ct wallet sync` first)
+        #[arg(long, default_value = "wallet.json")]
+        wallet: String,
+        /// The chain file
+        #[arg(long, default_value = "chain.json")]
+        chain: String,
+        /// The least the reserves hold
+        #[arg(long)]
+        amount: u64,
+        /// Write the proof here
+        #[arg(long, default_value = "reserves.json")]
+        out: String,
+        /// Seed for the proofs' randomness (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Check a proof of reserves against the chain
+    Verify {
+        /// The chain file
+        #[arg(long, default_value = "chain.json")]
+        chain: String,
+        /// The proof file
+        #[arg(long, default_value = "reserves.json")]
+        proof: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum MultisigCommand {
+    /// Funding, step 1 (cosigner): offer your blinding share and nonces to the funder
+    Offer {
+        /// Where to write the offer for the funder
+        #[arg(long, default_value = "offer.json")]
+        out: String,
+        /// Where to keep your secrets until `ct multisig cosign` (never send this file)
+        #[arg(long, default_value = "multisig-offer.json")]
+        secrets: String,
+        /// Seed for the share and nonces (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Funding, step 2 (funder): spend an input into a 2-of-2 output and sign your part
+    Fund {
+        /// The cosigner's offer
+        #[arg(long, default_value = "offer.json")]
+        offer: String,
+        /// Value of the input you spend
+        #[arg(long, allow_negative_numbers = true)]
+        input_value: i64,
+        /// Blinding factor of the input you spend
+        #[arg(long, allow_negative_numbers = true)]
+        input_blinding: i64,
+        /// Amount to lock in the 2-of-2 output
+        #[arg(long)]
+        amount: u64,
+        /// Public fee
+        #[arg(long, default_value_t = 0)]
+        fee: u64,
+        /// Where to keep your share of the output (never send this file)
+        #[arg(long, default_value = "share-a.json")]
+        share: String,
+        /// Where to write the funding for the cosigner
+        #[arg(long, default_value = "funding.json")]
+        out: String,
+        /// Seed for blindings and nonces (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Funding, step 3 (cosigner): finish the range proof and the kernel
+    Cosign {
+        /// The funder's funding
+        #[arg(long, default_value = "funding.json")]
+        funding: String,
+        /// The secrets `ct multisig offer` kept
+        #[arg(long, default_value = "multisig-offer.json")]
+        secrets: String,
+        /// Where to keep your share of the output (never send this file)
+        #[arg(long, default_value = "share-b.json")]
+        share: String,
+        /// Write the finished transaction here, for `ct verify` or `ct chain mine`
+        #[arg(long)]
+        tx_out: String,
+    },
+    /// Spending, step 1 (either party): spend the 2-of-2 output into a new output of yours
+    Spend {
+        /// Your share of the output
+        #[arg(long)]
+        share: String,
+        /// Public fee
+        #[arg(long, default_value_t = 0)]
+        fee: u64,
+        /// Where to keep your secrets until `ct multisig finalize` (never send this file)
+        #[arg(long, default_value = "multisig-spend.json")]
+        secrets: String,
+        /// Where to write the spend for the other party
+        #[arg(long, default_value = "spend.json")]
+        out: String,
+        /// Seed for the blinding and nonce (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Spending, step 2 (the other party): sign for your share
+    Sign {
+        /// The spend from the other party
+        #[arg(long, default_value = "spend.json")]
+        spend: String,
+        /// Your share of the output
+        #[arg(long)]
+        share: String,
+        /// Where to write the signed spend
+        #[arg(long)]
+        out: String,
+        /// Seed for the nonce (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Spending, step 3 (the party who started): add your signature and write the transaction
+    Finalize {
+        /// The signed spend
+        #[arg(long)]
+        spend: String,
+        /// The secrets `ct multisig spend` kept
+        #[arg(long, default_value = "multisig-spend.json")]
+        secrets: String,
+        /// Write the finished transaction here, for `ct verify` or `ct chain mine`
+        #[arg(long)]
+        tx_out: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SwapCommand {
+    /// Step 0 (both parties): make a swap key and, for the party who starts, the secret s
+    Init {
+        /// Also draw the preimage s (the party who starts the swap)
+        #[arg(long)]
+        preimage: bool,
+        /// Where to keep your secrets (never send this file)
+        #[arg(long, default_value = "swap-secrets.json")]
+        out: String,
+        /// Seed for the key and preimage (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Lock an input's coins to a hash, claimable by the other party, refundable to you later
+    Lock {
+        /// Your secrets from `ct swap init`; the refund key is yours
+        #[arg(long, default_value = "swap-secrets.json")]
+        secrets: String,
+        /// Value of the input you spend
+        #[arg(long, allow_negative_numbers = true)]
+        input_value: i64,
+        /// Blinding factor of the input you spend
+        #[arg(long, allow_negative_numbers = true)]
+        input_blinding: i64,
+        /// Amount to lock
+        #[arg(long)]
+        amount: u64,
+        /// Public fee
+        #[arg(long, default_value_t = 0)]
+        fee: u64,
+        /// The other party's swap key
+        #[arg(long)]
+        claim_key: Commitment,
+        /// The hash to lock to, from the other party's contract (default: the hash of your s)
+        #[arg(long)]
+        hash: Option<String>,
+        /// The first block height at which you can take the coins back
+        #[arg(long)]
+        refund_height: u64,
+        /// Where to write the contract for the other party
+        #[arg(long, default_value = "contract.json")]
+        out: String,
+        /// Write the locking transaction here, for `ct chain mine`
+        #[arg(long, default_value = "lock-tx.json")]
+        tx_out: String,
+        /// Seed for blindings and nonces (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Check that a contract's output is on a chain, unspent and locked as the contract says
+    Check {
+        /// The contract file
+        contract: String,
+        /// The chain file
+        #[arg(long, default_value = "chain.json")]
+        chain: String,
+    },
+    /// Claim a contract's coins with s and your key
+    Claim {
+        /// The contract file
+        contract: String,
+        /// Your secrets from `ct swap init`
+        #[arg(long, default_value = "swap-secrets.json")]
+        secrets: String,
+        /// Read s off this chain, where the other party claimed (if your secrets do not hold it)
+        #[arg(long)]
+        preimage_from: Option<String>,
+        /// Public fee
+        #[arg(long, default_value_t = 0)]
+        fee: u64,
+        /// Write the claiming transaction here, for `ct chain mine`
+        #[arg(long, default_value = "claim-tx.json")]
+        tx_out: String,
+        /// Seed for blindings and nonces (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Walkthrough: the same swap with no hash lock, through 2-of-2 outputs and adaptor signatures
+    Scriptless {
+        /// Seed for the blindings, nonces and t
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// Take back the coins you locked, once the refund height is reached
+    Refund {
+        /// The contract file
+        contract: String,
+        /// Your secrets from `ct swap init`
+        #[arg(long, default_value = "swap-secrets.json")]
+        secrets: String,
+        /// Public fee
+        #[arg(long, default_value_t = 0)]
+        fee: u64,
+        /// Write the refunding transaction here, for `ct chain mine`
+        #[arg(long, default_value = "refund-tx.json")]
+        tx_out: String,
+        /// Seed for blindings and nonces (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PaymentProofCommand {
+    /// Check the receiver's signature and, with --chain, that the payment's kernel is on the chain
+    Verify {
+        /// The payment proof file
+        proof: String,
+        /// The chain file
+        #[arg(long)]
+        chain: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum WatchCommand {
+    /// Start a watch-only wallet from a view key (`ct wallet export-viewkey`)
+    Init {
+        /// The view key, view/account/point
+        #[arg(long)]
+        view_key: ViewKey,
+    },
+    /// Find the account's outputs in the chain file's UTXO set
+    Scan {
+        /// The chain file
+        #[arg(long, default_value = "chain.json")]
+        chain: String,
+    },
+    /// Print the balance of the outputs found
+    Balance,
+    /// List the outputs found, with value, path, status and outpoint (no blindings)
+    Outputs,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let config = match Config::load(&cli.config, cli.json).and_then(|c| params::set(c.params).map(|()| c)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("ct: {}", e);
+            return ExitCode::from(EXIT_MALFORMED_INPUT);
+        }
+    };
+    let json = config.json;
+    let mut report = Report::new(json);
+    let code = match cli.command.unwrap_or(Command::Demo { interactive: false }) {
+        Command::Demo { interactive } => {
+            if interactive && json {
+                // `conflicts_with` misses `--json` when it is given before the subcommand.
+                Cli::command()
+                    .error(ErrorKind::ArgumentConflict, "the argument '--interactive' cannot be used with '--json'")
+                    .exit();
+            }
+            demo::run(json, interactive);
+            return ExitCode::SUCCESS;
+        }
+        Command::Commit { value, blinding, hex } => {
+            let commitment = match config.backend {
+                Backend::Toy => {
+                    let commitment = pedersen_commit(Scalar::new(value), Blinding::new(blinding));
+                    if hex { json!(format!("{:#}", commitment)) } else { json!(commitment.as_i64()) }
+                }
+                Backend::Bigfield if hex => {
+                    json!(format!("{:#x}", config.big_field.commit(&value.into(), &blinding.into())))
+                }
+                Backend::Bigfield => json!(config.big_field.commit(&value.into(), &blinding.into()).to_string()),
+                Backend::Curve => json!(toy_curve::commit(value, blinding).to_string()),
+                Backend::Monero => json!(monero::commit(&monero::scalar(value), &monero::scalar(blinding)).to_string()),
+                Backend::Secp256k1 => {
+                    json!(secp256k1::commit(&secp256k1::scalar(value), &secp256k1::scalar(blinding)).to_string())
+                }
+            };
+            let shown = commitment.as_str().map_or_else(|| commitment.to_string(), str::to_string);
+            // Monero and libsecp256k1-zkp put the blinding (Monero's mask) on G and the value on H.
+            let swapped = matches!(config.backend, Backend::Monero | Backend::Secp256k1);
+            let (on_g, on_h) = if swapped { (blinding, value) } else { (value, blinding) };
+            say!(report, "C = {}*G + {}*H = {}", on_g, on_h, shown);
+            report.record("commit", json!({ "value": value, "blinding": blinding, "commitment": commitment }));
+            ExitCode::SUCCESS
+        }
+        Command::Verify { tx: Some(source), .. } => run_verify_tx(&mut report, &source),
+        Command::Verify { tx: None, inputs, outputs, fee } | Command::VerifyBalance { inputs, outputs, fee } => {
+            run_verify_balance(&mut report, &inputs, &outputs, fee)
+        }
+        Command::Encode { tx: source, armor: armored, format, sizes } => match read_tx(&mut report, "encode", &source) {
+            Ok(tx) if sizes => {
+                report_sizes(&mut report, &tx);
+                ExitCode::SUCCESS
+            }
+            Ok(tx) => {
+                run_encode(&mut report, &tx, format, armored);
+                ExitCode::SUCCESS
+            }
+            Err(code) => code,
+        },
+        Command::Inspect { action: InspectCommand::Elements { hex } } => run_inspect_elements(&mut report, &hex),
+        Command::Inspect { action: InspectCommand::Grin { file } } => run_inspect_grin(&mut report, &file),
+        Command::Inspect { action: InspectCommand::Monero { commitment, amount, mask } } => {
+            run_inspect_monero(&mut report, &commitment, amount, mask.as_deref())
+        }
+        Command::Rangeproof { action } => run_rangeproof(&mut report, action),
+        Command::Send { input_value, input_blinding, amount, fee, slate_out, context, armor, payment_proof, seed } => {
+            let input = Opening::new(Scalar::new(input_value), Blinding::new(input_blinding));
+            let mut rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+            let sent = Slate::send(input, amount, fee, &mut rng).map(|(slate, context)| match payment_proof {
+                Some(address) => (slate.request_payment_proof(address.spend), context),
+                None => (slate, context),
+            });
+            run_send(&mut report, input, sent, &slate_out, &context, armor)
+        }
+        Command::Receive { slate_in, slate_out, armor, seed, wallet } => {
+            let rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+            run_receive(&mut report, &slate_in, &slate_out, armor, rng, wallet.as_deref())
+        }
+        Command::Finalize { slate_in, context, tx_out, armor, wallet, proof_out } => {
+            run_finalize(&mut report, &slate_in, &context, tx_out.as_deref(), armor, wallet.as_deref(), &proof_out)
+        }
+        Command::Qr { action: QrCommand::Encode { file, png } } => run_qr_encode(&mut report, &file, png.as_deref()),
+        Command::Qr { action: QrCommand::Decode { file, out } } => run_qr_decode(&mut report, &file, out.as_deref()),
+        Command::Multisig { action } => run_multisig(&mut report, action),
+        Command::Swap { action } => run_swap(&mut report, action),
+        Command::Chain { file, action } => run_chain(&mut report, &file, action),
+        Command::Wallet { file, action } => run_wallet(&mut report, &file, action),
+        Command::Watch { file, action } => run_watch(&mut report, &file, action),
+        Command::PaymentProof { action: PaymentProofCommand::Verify { proof, chain } } => {
+            run_payment_proof_verify(&mut report, &proof, chain.as_deref())
+        }
+        Command::Por { action } => run_por(&mut report, action),
+        Command::Audit { disclosure, chain } => run_audit(&mut report, &disclosure, &chain),
+        Command::Repl => {
+            repl::run();
+            ExitCode::SUCCESS
+        }
+        Command::Attack { scenario: None } => {
+            say!(report, "Attack scenarios (run one with --scenario <name>):");
+            for scenario in attack::SCENARIOS {
+                say!(report, "  {:<20} {}", scenario.name, scenario.summary);
+            }
+            report.record("scenarios", json!(attack::SCENARIOS.iter().map(|s| s.name).collect::<Vec<_>>()));
+            ExitCode::SUCCESS
+        }
+        Command::Attack { scenario: Some(name) } => match attack::find(&name) {
+            Some(scenario) => {
+                attack::run(&mut report, scenario);
+                ExitCode::SUCCESS
+            }
+            None => {
+                let names: Vec<&str> = attack::SCENARIOS.iter().map(|s| s.name).collect();
+                eprintln!("unknown scenario '{}' (known: {})", name, names.join(", "));
+                ExitCode::from(EXIT_MALFORMED_INPUT)
+            }
+        },
+        Command::Exercise { seed, count, key } => {
+            let seed = seed.unwrap_or_else(|| ToyRng::from_entropy().next_u64());
+            let problems = exercise::sheet(seed, count);
+            say!(report, "Exercise sheet {} ({} problems)", seed, problems.len());
+            for (i, problem) in problems.iter().enumerate() {
+                say!(report, "\n{}.", i + 1);
+                for line in &problem.statement {
+                    say!(report, "  {}", line);
+                }
+                if key {
+                    say!(report, "  Answer: {}", problem.answer);
+                }
+            }
+            if !key {
+                say!(report, "\nAnswer key: ct exercise --seed {} --count {} --key", seed, count);
+            }
+            let sheet: Vec<_> = problems
+                .iter()
+                .map(|p| match key {
+                    true => json!({ "statement": p.statement, "answer": p.answer }),
+                    false => json!({ "statement": p.statement }),
+                })
+                .collect();
+            report.record("exercise", json!({ "seed": seed, "problems": sheet }));
+            ExitCode::SUCCESS
+        }
+        Command::Challenge { action } => run_challenge(&mut report, action),
+        Command::Vectors { action: VectorsCommand::Generate { seed, out } } => {
+            let seed = seed.unwrap_or_else(|| ToyRng::from_entropy().next_u64());
+            match vectors::generate(seed).and_then(|document| write_json(&out, &document).map(|()| document)) {
+                Ok(document) => {
+                    say!(report, "Test vectors for seed {} written to {}:", seed, out);
+                    for section in ["commitments", "sums", "range_proofs", "signatures", "transactions"] {
+                        let count = document[section].as_array().map_or(0, Vec::len);
+                        say!(report, "  {:<13} {}", section, count);
+                    }
+                    say!(report, "Regenerate with: ct vectors generate --seed {} --out {}", seed, out);
+                    report.record("vectors", json!({ "seed": seed, "out": out }));
+                    ExitCode::SUCCESS
+                }
+                Err(e) => step_failure(&mut report, "vectors", e),
+            }
+        }
+        Command::Analyze { payments, seed } => run_analyze(&mut report, payments, seed),
+        Command::Ringct { ring_size, seed } => match walkthrough::ringct(&mut report, ring_size, seed) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => step_failure(&mut report, "ringct", error),
+        },
+        Command::Assets { seed } => match walkthrough::assets(&mut report, seed) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => step_failure(&mut report, "assets", error),
+        },
+        Command::Burn { seed } => match walkthrough::burn(&mut report, seed) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => step_failure(&mut report, "burn", error),
+        },
+        Command::Solvency { seed } => match walkthrough::solvency(&mut report, seed) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => step_failure(&mut report, "solvency", error),
+        },
+        Command::Shamir { seed } => match walkthrough::shamir(&mut report, seed) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => step_failure(&mut report, "shamir", error),
+        },
+        Command::Musig { signers, seed } => match walkthrough::musig(&mut report, signers, seed) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => step_failure(&mut report, "musig", error),
+        },
+        Command::Mpc { seed } => match walkthrough::mpc(&mut report, seed) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => step_failure(&mut report, "mpc", error),
+        },
+        Command::Coinjoin { seed } => match walkthrough::coinjoin(&mut report, seed) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => step_failure(&mut report, "coinjoin", error),
+        },
+        Command::Timelock { seed } => match walkthrough::timelock(&mut report, seed) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => step_failure(&mut report, "timelock", error),
+        },
+        Command::Escrow { seed } => match walkthrough::escrow(&mut report, seed) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => step_failure(&mut report, "escrow", error),
+        },
+        Command::Crack { commitment, max_blinding } => run_crack(&mut report, commitment, max_blinding),
+        Command::Quiz { rounds, seed } => {
+            if json {
+                Cli::command()
+                    .error(ErrorKind::ArgumentConflict, "'quiz' is interactive and has no --json output")
+                    .exit();
+            }
+            quiz::run(rounds, seed.map_or_else(ToyRng::from_entropy, ToyRng::new));
+            ExitCode::SUCCESS
+        }
+    };
+    report.finish();
+    code
+}
+
+fn run_challenge(report: &mut Report, action: ChallengeCommand) -> ExitCode {
+    match action {
+        ChallengeCommand::New { level, seed, out } => {
+            let seed = seed.unwrap_or_else(|| ToyRng::from_entropy().next_u64());
+            let written = challenge::generate(level, seed).and_then(|puzzle| {
+                let document = challenge::challenge_json(level, seed, &puzzle);
+                write_json(&out, &document).map(|()| (puzzle, document))
+            });
+            match written {
+                Ok((puzzle, document)) => {
+                    let info = &challenge::LEVELS[level as usize - 1];
+                    say!(report, "Level {}: {} (seed {})", level, info.title, seed);
+                    say!(report, "  {}", info.task);
+                    for (name, value) in &puzzle.public {
+                        say!(report, "  {:<8} = {}", name, value);
+                    }
+                    let format = info.answer_format;
+                    say!(report, "Answer with: ct challenge check --challenge {} --answer \"{}\"", out, format);
+                    say!(report, "Hint, if you need one: {}", info.hint);
+                    report.record("challenge", document);
+                    ExitCode::SUCCESS
+                }
+                Err(e) => step_failure(report, "challenge", e),
+            }
+        }
+        ChallengeCommand::Check { challenge: path, answer } => {
+            let judged = read_file(&path)
+                .and_then(|text| {
+                    serde_json::from_str(&text)
+                        .map_err(|e| CtError::MalformedInput(format!("{} is not valid JSON: {}", path, e)))
+                })
+                .and_then(|file| challenge::check(&file, &answer));
+            match judged {
+                Ok((level, None)) => {
+                    say!(report, "Correct! Level {} solved.", level);
+                    report.record("check", json!({ "level": level, "solved": true }));
+                    ExitCode::SUCCESS
+                }
+                Ok((level, Some(why))) => {
+                    say!(report, "Not yet: {}.", why);
+                    report.record("check", json!({ "level": level, "solved": false, "why": why }));
+                    ExitCode::FAILURE
+                }
+                Err(e) => step_failure(report, "challenge", e),
+            }
+        }
+    }
+}
+
+fn run_analyze(report: &mut Report, payments: usize, seed: u64) -> ExitCode {
+    let history = analysis::simulate_history(payments, &mut ToyRng::new(seed));
+    let prices: Vec<String> = analysis::PRICES.iter().map(i64::to_string).collect();
+    say!(report, "{} payments between 3 customers and a shop (prices {}), seed {}.", payments, prices.join(", "), seed);
+    say!(report, "The analyst runs the same code on both ledgers:\n");
+    let (plain, hidden) = (analysis::analyze(&history, false), analysis::analyze(&history, true));
+    let row = |label: &str, f: &dyn Fn(&analysis::Findings) -> String| {
+        say!(report, "  {:<36} {:<28} {}", label, f(&plain), f(&hidden));
+    };
+    say!(report, "  {:<36} {:<28} {}", "", "plaintext", "confidential");
+    row("amounts readable", &|f| format!("{} of {}", f.readable, f.outputs));
+    row("amounts seen more than once", &|f| match f.repeated.first() {
+        Some((amount, count)) => format!("{} (top: {} x{})", f.repeated.len(), amount, count),
+        None => "none".to_string(),
+    });
+    row("outputs tagged as shop purchases", &|f| {
+        format!("{} right, {} wrong (of {})", f.tagged_right, f.tagged_wrong, f.purchases)
+    });
+    if let Some(invoice) = plain.invoice {
+        row(&format!("payment #{} found by subset sum", invoice), &|f| match f.invoice_matches.contains(&invoice) {
+            true => format!("yes ({} candidate(s))", f.invoice_matches.len()),
+            false => "no".to_string(),
+        });
+    }
+    say!(report, "\nOn the plaintext ledger, prices, change and repeat amounts fall out of simple counting.");
+    say!(report, "On the confidential ledger every output is a fresh commitment, and each analysis comes back empty.");
+    let findings_json = |f: &analysis::Findings| {
+        json!({
+            "readable": f.readable,
+            "outputs": f.outputs,
+            "repeated_amounts": f.repeated.iter().map(|(a, n)| json!([a.to_string(), n])).collect::<Vec<_>>(),
+            "tagged_right": f.tagged_right,
+            "tagged_wrong": f.tagged_wrong,
+            "purchases": f.purchases,
+            "invoice": f.invoice,
+            "invoice_matches": f.invoice_matches,
+        })
+    };
+    let (plaintext, confidential) = (findings_json(&plain), findings_json(&hidden));
+    let ledgers = json!({ "plaintext": plaintext, "confidential": confidential });
+    report.record("analyze", json!({ "payments": payments, "seed": seed, "ledgers": ledgers }));
+    ExitCode::SUCCESS
+}
+
+/// How long `tries` take at `rate` per second, in the largest unit that fits.
+fn duration_text(tries: f64, rate: f64) -> String {
+    let seconds = tries / rate;
+    let units = [("years", 365.25 * 86400.0), ("days", 86400.0), ("hours", 3600.0), ("minutes", 60.0)];
+    match units.iter().find(|(_, size)| seconds >= *size) {
+        Some((unit, size)) if seconds / size >= 1e6 => format!("{:.1e} {}", seconds / size, unit),
+        Some((unit, size)) => format!("{:.1} {}", seconds / size, unit),
+        None => format!("{:.3} seconds", seconds),
+    }
+}
+
+fn run_crack(report: &mut Report, commitment: Commitment, max_blinding: Option<i64>) -> ExitCode {
+    let params = params::active();
+    if commitment.as_i64() >= params.modulus {
+        return step_failure(report, "crack", CtError::MalformedInput(format!("{} is not below p", commitment)));
+    }
+    let space = max_blinding.unwrap_or(params.modulus).clamp(1, params.modulus);
+    let searched = space.min(CRACK_LIMIT);
+    let start = Instant::now();
+    let openings = brute_force_openings(commitment, 0..searched);
+    let seconds = start.elapsed().as_secs_f64().max(1e-6);
+    let rate = searched as f64 / seconds;
+    say!(report, "p = {}, values in [0, 2^{}), blindings in [0, {})", params.modulus, params.range_bits, space);
+    say!(report, "Tried {} blinding(s) in {:.3} s ({:.0} per second).", searched, seconds, rate);
+    if searched < space {
+        let total = duration_text(space as f64, rate);
+        say!(report, "That is only part of the search; all {} would take {}.", space, total);
+    }
+    for opening in openings.iter().take(10) {
+        say!(report, "  C = {}*G + {}*H", opening.value, opening.blinding);
+    }
+    if openings.len() > 10 {
+        say!(report, "  ... and {} more", openings.len() - 10);
+    }
+    match openings.len() {
+        0 => say!(report, "No opening found among these blindings."),
+        1 => say!(report, "Exactly one opening: hiding is broken, the amount is {}.", openings[0].value),
+        n => say!(report, "{} openings: C fits many amounts, and the search cannot tell which one is real.", n),
+    }
+    say!(report, "With r drawn from all of [0, p) every amount has an opening; only a small r range gives one away.");
+    say!(report, "At this speed, trying every blinding takes:");
+    for (group, bits) in [("the default toy group, p ~ 2^61", 61), ("secp256k1, n ~ 2^256", 256)] {
+        say!(report, "  {:<32} {}", group, duration_text(2f64.powi(bits), rate));
+    }
+    let found: Vec<_> =
+        openings.iter().map(|o| json!({ "value": o.value.as_i64(), "blinding": o.blinding.as_i64() })).collect();
+    report.record("crack", json!({ "tried": searched, "space": space, "seconds": seconds, "openings": found }));
+    ExitCode::SUCCESS
+}
+
+fn run_verify_balance(report: &mut Report, inputs: &[Commitment], outputs: &[Commitment], fee: u64) -> ExitCode {
+    let sum_inputs = sum_commitments(inputs);
+    let sum_outputs = sum_commitments(outputs) + fee_commitment(fee);
+    let result = verify_balance(&[sum_inputs], &[sum_outputs]);
+    say!(report, "{} input(s), {} output(s), fee {}", inputs.len(), outputs.len(), fee);
+    say!(report, "sum(inputs)          = {}", sum_inputs);
+    say!(report, "sum(outputs) + fee*G = {}", sum_outputs);
+    match &result {
+        Ok(()) => say!(report, "PASS: sum(inputs) = sum(outputs) + fee*G."),
+        Err(e) => say!(report, "FAIL: {}", e),
+    }
+    report.record(
+        "verify_balance",
+        json!({
+            "inputs": inputs.iter().map(|c| c.as_i64()).collect::<Vec<_>>(),
+            "outputs": outputs.iter().map(|c| c.as_i64()).collect::<Vec<_>>(),
+            "fee": fee,
+            "sum_inputs": sum_inputs.as_i64(),
+            "sum_outputs": sum_outputs.as_i64(),
+            "result": result_json(&result),
+        }),
+    );
+    if result.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// `ct encode`: the hex of the wire bytes or of the CBOR, armored wire bytes, or the JSON.
+fn run_encode(report: &mut Report, tx: &Transaction, format: Format, armored: bool) {
+    let bytes = match format {
+        Format::Wire => tx.to_bytes(),
+        Format::Cbor => to_cbor(tx),
+        Format::Json => {
+            say!(report, "{}", serde_json::to_string_pretty(&to_json(tx)).expect("JSON values always serialize"));
+            report.record("encode", json!({ "transaction": to_json(tx) }));
+            return;
+        }
+    };
+    if armored {
+        say!(report, "{}", armor(armor::TRANSACTION, &bytes).trim_end());
+        report.record("encode", json!({ "bytes": bytes.len(), "armor": armor(armor::TRANSACTION, &bytes) }));
+    } else {
+        say!(report, "{}", to_versioned_hex(&bytes));
+        report.record("encode", json!({ "bytes": bytes.len(), "hex": to_versioned_hex(&bytes) }));
+    }
+}
+
+/// `ct encode --sizes`: the same transaction as JSON, CBOR and wire bytes, and how much
+/// of it is range proofs, which is what a confidential transaction pays for in space.
+fn report_sizes(report: &mut Report, tx: &Transaction) {
+    let pretty = serde_json::to_string_pretty(&to_json(tx)).expect("JSON values always serialize").len();
+    let compact = to_json(tx).to_string().len();
+    let (cbor, wire) = (to_cbor(tx).len(), tx.to_bytes().len());
+    let entries = tx.inputs.iter().chain(&tx.outputs);
+    let proofs = entries.filter_map(|entry| entry.proof.as_ref()).map(|proof| proof.to_bytes().len()).sum::<usize>()
+        + tx.aggregate_proof.as_ref().map_or(0, |proof| proof.to_bytes().len());
+    let percent = |part: usize, whole: usize| part * 100 / whole.max(1);
+    say!(report, "Transaction {}: {} input(s), {} output(s)", tx.txid(), tx.inputs.len(), tx.outputs.len());
+    say!(report, "  JSON, pretty  {:>7} bytes", pretty);
+    say!(report, "  JSON          {:>7} bytes", compact);
+    say!(report, "  CBOR          {:>7} bytes  {:>3}% of the JSON", cbor, percent(cbor, compact));
+    say!(report, "  wire          {:>7} bytes  {:>3}% of the JSON", wire, percent(wire, compact));
+    say!(report, "Range proofs are {} of the {} wire bytes ({}%).", proofs, wire, percent(proofs, wire));
+    report.record(
+        "sizes",
+        json!({ "json_pretty": pretty, "json": compact, "cbor": cbor, "wire": wire, "range_proofs": proofs }),
+    );
+}
+
+/// `ct inspect elements`: decode an Elements transaction, say what each confidential
+/// field is in this demo's terms, and run the checks that need only the transaction.
+fn run_inspect_elements(report: &mut Report, source: &str) -> ExitCode {
+    let hex = if source == "-" { read_stdin() } else { Ok(source.to_string()) };
+    let tx = match hex.and_then(|hex| from_hex(hex.trim())).and_then(|raw| ElementsTx::from_bytes(&raw)) {
+        Ok(tx) => tx,
+        Err(e) => return step_failure(report, "inspect", e),
+    };
+
+    say!(report, "Elements transaction {}", tx.txid);
+    let (version, lock_time) = (tx.version, tx.lock_time);
+    let counts = format!("{} input(s), {} output(s)", tx.inputs.len(), tx.outputs.len());
+    say!(report, "version {}, {}, lock time {}", version, counts, lock_time);
+    let mut inputs = Vec::new();
+    for (i, input) in tx.inputs.iter().enumerate() {
+        let pegin = if input.pegin { " (peg-in from the parent chain)" } else { "" };
+        say!(report, "input {}: spends {}:{}{}", i, input.prev_txid, input.prev_index, pegin);
+        if let Some(issuance) = &input.issuance {
+            let (amount, keys) = (&issuance.amount, &issuance.inflation_keys);
+            say!(report, "  issues {} (inflation keys {}), like an `issuance::Issuance`", amount, keys);
+        }
+        inputs.push(json!({
+            "prev_txid": input.prev_txid.to_string(),
+            "prev_index": input.prev_index,
+            "pegin": input.pegin,
+            "issuance": input.issuance.as_ref().map(|issuance| json!({
+                "amount": issuance.amount.to_string(),
+                "inflation_keys": issuance.inflation_keys.to_string(),
+            })),
+        }));
+    }
+
+    let mut outputs = Vec::new();
+    for (i, output) in tx.outputs.iter().enumerate() {
+        let kind = if output.is_fee() { "fee" } else { "output" };
+        say!(report, "output {} ({}, script of {} bytes):", i, kind, output.script_pubkey.len());
+        say!(report, "  asset {}", output.asset);
+        say!(report, "  value {}", output.value);
+        let range_proof = elements::range_proof_header(&output.range_proof).ok();
+        match range_proof {
+            Some(header) if header.exponent < 0 => {
+                say!(report, "  range proof ({} bytes): v = {} exactly", output.range_proof.len(), header.min_value)
+            }
+            Some(header) => say!(
+                report,
+                "  range proof ({} bytes): v in [{}, {}], a {}-bit mantissa times 10^{}",
+                output.range_proof.len(),
+                header.min_value,
+                header.max_value,
+                header.mantissa,
+                header.exponent
+            ),
+            None => {}
+        }
+        let surjection = elements::surjection_proof_header(&output.surjection_proof).ok();
+        if let Some(header) = surjection {
+            let (used, inputs) = (header.used, header.inputs);
+            say!(report, "  surjection proof over {} of the {} inputs: the asset is one of theirs", used, inputs);
+        }
+        outputs.push(json!({
+            "fee": output.is_fee(),
+            "asset": output.asset.to_string(),
+            "value": output.value.to_string(),
+            "nonce": output.nonce.to_string(),
+            "script_pubkey": to_hex(&output.script_pubkey),
+            "range_proof": range_proof.map(|header| json!({
+                "bytes": output.range_proof.len(),
+                "exponent": header.exponent,
+                "mantissa": header.mantissa,
+                "min_value": header.min_value,
+                "max_value": header.max_value,
+            })),
+            "surjection_proof": surjection.map(|header| json!({ "inputs": header.inputs, "used": header.used })),
+        }));
+    }
+    let mut fees = Vec::new();
+    for (asset, fee) in tx.fees() {
+        say!(report, "fee: {} of asset {}, in the clear like a `Transaction::fee`", fee, asset);
+        fees.push(json!({ "asset": asset.to_string(), "fee": fee }));
+    }
+
+    say!(report, "In this demo's terms: a committed value is a `Commitment` (v*A + r*G on secp256k1), a committed");
+    say!(report, "asset a blinded generator A (`assets`), each range proof a `RangeProof`, each surjection");
+    say!(report, "proof an `assets::SurjectionProof`.");
+    say!(report, "checks:");
+    let mut checks = Vec::new();
+    for (name, result) in tx.checks() {
+        say!(report, "  {}: {}", name, outcome(&result));
+        checks.push(json!({ "check": name, "result": result_json(&result) }));
+    }
+    say!(report, "Not checked: the range and surjection proofs themselves, and that the commitments balance,");
+    say!(report, "which needs the commitments of the outputs spent, from the transactions that made them.");
+    let valid = checks.iter().all(|check| check["result"]["ok"] == json!(true));
+    report.record(
+        "inspect",
+        json!({
+            "txid": tx.txid.to_string(),
+            "version": version,
+            "lock_time": lock_time,
+            "inputs": inputs,
+            "outputs": outputs,
+            "fees": fees,
+            "checks": checks,
+            "valid": valid,
+        }),
+    );
+    if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// `ct inspect grin`: read a Grin transaction or slate, say what its parts are in this
+/// demo's terms, and check that it balances on secp256k1.
+fn run_inspect_grin(report: &mut Report, source: &str) -> ExitCode {
+    let text = if source == "-" { read_stdin() } else { read_file(source) };
+    let (tx, shape) = match text.and_then(|text| grin_file::parse(&text)) {
+        Ok(parsed) => parsed,
+        Err(e) => return step_failure(report, "inspect", e),
+    };
+
+    let (shape_name, partial) = match &shape {
+        grin_file::Shape::Transaction => ("transaction".to_string(), false),
+        grin_file::Shape::Slate(version) => (format!("version {} slate", version), false),
+        grin_file::Shape::SlateV4(state) => (format!("version 4 slate, state {}", state), !state.ends_with('3')),
+    };
+    let (inputs, outputs, kernels) = (tx.inputs.len(), tx.outputs.len(), tx.kernels.len());
+    say!(report, "Grin {}: {} input(s), {} output(s), {} kernel(s)", shape_name, inputs, outputs, kernels);
+    say!(report, "fee {} nanogrin", tx.fee());
+    say!(report, "offset {}", to_hex(&tx.offset));
+    for (i, input) in tx.inputs.iter().enumerate() {
+        say!(report, "input {}: {}", i, to_hex(input));
+    }
+    for (i, output) in tx.outputs.iter().enumerate() {
+        say!(report, "output {}: {}, range proof of {} bytes", i, to_hex(&output.commit), output.proof.len());
+    }
+    for (i, kernel) in tx.kernels.iter().enumerate() {
+        let lock = if kernel.lock_height > 0 { format!(", lock height {}", kernel.lock_height) } else { String::new() };
+        let excess = to_hex(&kernel.excess);
+        say!(report, "kernel {}: {}, fee {}{}, excess {}", i, kernel.features, kernel.fee, lock, excess);
+    }
+    say!(report, "In this demo's terms: each commitment is a `Commitment`, r*G + v*H on secp256k1 where");
+    say!(report, "the demo has v*G + r*H mod p; each kernel a `Kernel` with its excess x*G; each range proof");
+    say!(report, "a 64-bit Bulletproof where the demo has a `RangeProof`.");
+
+    say!(report, "checks:");
+    let mut checks = Vec::new();
+    for (name, result) in tx.checks() {
+        say!(report, "  {}: {}", name, outcome(&result));
+        checks.push(json!({ "check": name, "result": result_json(&result) }));
+    }
+    if tx.is_coinbase() {
+        say!(report, "The balance of a coinbase is not checked: it needs the block reward.");
+    }
+    if partial {
+        say!(report, "A slate before S3 or I3 holds only some parties' commitments: it balances once finalized.");
+    }
+    say!(report, "Not checked: the Bulletproofs and the kernel signatures.");
+    let valid = checks.iter().all(|check| check["result"]["ok"] == json!(true));
+    report.record(
+        "inspect",
+        json!({
+            "shape": shape_name,
+            "fee": tx.fee(),
+            "offset": to_hex(&tx.offset),
+            "inputs": tx.inputs.iter().map(|input| to_hex(input)).collect::<Vec<_>>(),
+            "outputs": tx.outputs.iter().map(|output| to_hex(&output.commit)).collect::<Vec<_>>(),
+            "kernels": tx.kernels.iter().map(|kernel| json!({
+                "features": kernel.features,
+                "fee": kernel.fee,
+                "lock_height": kernel.lock_height,
+                "excess": to_hex(&kernel.excess),
+            })).collect::<Vec<_>>(),
+            "checks": checks,
+            "valid": valid,
+        }),
+    );
+    if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// `ct inspect monero`: decode a Monero commitment and, given an amount (and a mask),
+/// check that it opens to it.
+fn run_inspect_monero(report: &mut Report, hex: &str, amount: Option<u64>, mask: Option<&str>) -> ExitCode {
+    let decoded = monero::Point::from_hex(hex.trim())
+        .and_then(|point| mask.map(monero::scalar_from_hex).transpose().map(|mask| (point, mask)));
+    let (point, mask) = match decoded {
+        Ok(decoded) => decoded,
+        Err(e) => return step_failure(report, "inspect", e),
+    };
+
+    say!(report, "Monero commitment {}", point);
+    say!(report, "On Ed25519: C = mask*G + amount*H, where the demo has C = v*G + r*H mod p.");
+    let mut checks = vec![(
+        "in the order-l subgroup".to_string(),
+        match point.in_subgroup() {
+            true => Ok(()),
+            false => Err(CtError::MalformedInput("the point has a small-order part".to_string())),
+        },
+    )];
+    if let Some(amount) = amount {
+        let xmr = format!("{}.{:012} XMR", amount / monero::PICONERO, amount % monero::PICONERO);
+        let (name, expected) = match &mask {
+            Some(mask) => (format!("opens to {} with the mask", xmr), monero::commit(&amount.into(), mask)),
+            None => (format!("opens to {} with mask 1 (coinbase)", xmr), monero::zero_commit(amount)),
+        };
+        let opened = match expected == point {
+            true => Ok(()),
+            false => Err(CtError::InvalidProof(format!("that opening commits to {}", expected))),
+        };
+        checks.push((name, opened));
+    } else {
+        say!(report, "Its amount stays hidden: --amount (and --mask, from the receiver's wallet) tries an opening.");
+    }
+
+    let mut results = Vec::new();
+    for (name, result) in &checks {
+        say!(report, "  {}: {}", name, outcome(result));
+        results.push(json!({ "check": name, "result": result_json(result) }));
+    }
+    let valid = checks.iter().all(|(_, result)| result.is_ok());
+    report.record("inspect", json!({ "commitment": point.to_string(), "checks": results, "valid": valid }));
+    if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Pixels per module in the PNG `ct qr encode --png` writes.
+const QR_SCALE: usize = 8;
+
+/// `ct qr encode`: the armored text of a slate or transaction, drawn as a QR code.
+fn run_qr_encode(report: &mut Report, source: &str, png: Option<&str>) -> ExitCode {
+    let text = if source == "-" { read_stdin() } else { read_file(source) };
+    let armored = text.and_then(|text| match slate_file::parse_slate(&text) {
+        Ok(slate) => Ok(("slate", armor(armor::SLATE, &slate.to_bytes()))),
+        Err(_) => tx_file::parse(&text).map(|tx| ("transaction", armor(armor::TRANSACTION, &tx.to_bytes()))),
+    });
+    let drawn = armored.and_then(|(kind, text)| {
+        let code = QrCode::encode(text.as_bytes())?;
+        if let Some(path) = png {
+            fs::write(path, code.to_image(QR_SCALE).to_png())
+                .map_err(|e| CtError::MalformedInput(format!("cannot write {}: {}", path, e)))?;
+        }
+        Ok((kind, text, code))
+    });
+    let (kind, text, code) = match drawn {
+        Ok(drawn) => drawn,
+        Err(e) => return step_failure(report, "qr", e),
+    };
+    let (version, size) = (format!("{}-{:?}", code.version(), code.level()), code.size());
+    let summary = format!("QR version {}, {} x {} modules", version, size, size);
+    say!(report, "The {} as armored text, {} bytes: {}", kind, text.len(), summary);
+    say!(report, "{}", code.to_text().trim_end());
+    if let Some(path) = png {
+        say!(report, "Wrote {} ({} pixels a side).", path, code.to_image(QR_SCALE).width);
+    }
+    say!(report, "Scanning it gives the armored text, which every command that reads a {} accepts.", kind);
+    report.record("qr", json!({ "kind": kind, "armor": text, "version": version, "modules": size, "png": png }));
+    ExitCode::SUCCESS
+}
+
+/// `ct qr decode`: what a QR code in a PNG, or drawn as text, holds.
+fn run_qr_decode(report: &mut Report, source: &str, out: Option<&str>) -> ExitCode {
+    let bytes = if source == "-" {
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes).map(|_| bytes)
+    } else {
+        fs::read(source)
+    };
+    let decoded = bytes
+        .map_err(|e| CtError::MalformedInput(format!("cannot read {}: {}", source, e)))
+        .and_then(|bytes| match String::from_utf8(bytes) {
+            Ok(text) => qr::decode_text(&text),
+            Err(e) => qr::decode_image(&Image::from_png(e.as_bytes())?),
+        })
+        .and_then(|data| {
+            let not_text = |_| CtError::MalformedInput("the code holds bytes that are not text".to_string());
+            String::from_utf8(data).map_err(not_text)
+        })
+        .and_then(|text| out.map_or(Ok(()), |path| write_file(path, &text)).map(|()| text));
+    let text = match decoded {
+        Ok(text) => text,
+        Err(e) => return step_failure(report, "qr", e),
+    };
+    say!(report, "{}", text.trim_end());
+    if let Some(path) = out {
+        say!(report, "Wrote it to {}.", path);
+    }
+    report.record("qr", json!({ "text": text, "out": out }));
+    ExitCode::SUCCESS
+}
+
+/// Read and parse a transaction file (`-` is stdin); on failure, record the error
+/// under `step` and give the exit code.
+fn read_tx(report: &mut Report, step: &str, source: &str) -> Result<Transaction, ExitCode> {
+    let text = if source == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text).map(|_| text)
+    } else {
+        fs::read_to_string(source)
+    };
+    text.map_err(|e| e.to_string()).and_then(|t| tx_file::parse(&t).map_err(|e| e.to_string())).map_err(|e| {
+        eprintln!("cannot read transaction from {}: {}", source, e);
+        report.record(step, json!({ "error": e }));
+        ExitCode::from(EXIT_MALFORMED_INPUT)
+    })
+}
+
+fn run_verify_tx(report: &mut Report, source: &str) -> ExitCode {
+    let tx = match read_tx(report, "verify", source) {
+        Ok(tx) => tx,
+        Err(code) => return code,
+    };
+
+    let balance = tx.verify_balance();
+    say!(report, "txid {}", tx.txid());
+    say!(report, "{} input(s), {} output(s), fee {}", tx.inputs.len(), tx.outputs.len(), tx.fee);
+    if tx.is_coinbase() {
+        say!(report, "coinbase: mints {}", tx.minted);
+    }
+    if tx.burned > 0 {
+        say!(report, "burns {}", tx.burned);
+    }
+    match &balance {
+        Ok(()) => say!(report, "balance: ok"),
+        Err(e) => say!(report, "balance: FAIL ({})", e),
+    }
 
-/// Public parameters (known to everyone, like in a real system).
-/// We work modulo a prime so numbers wrap around. In a real system, G and H would be curve points.
-const MODULUS: i64 = 2_i64.pow(61) - 1;
-const G: i64 = 3; // "Generator G" — value dimension
-const H: i64 = 7; // "Generator H" — blinding/randomness dimension
-
-/// Create a Pedersen commitment: C = v*G + r*H (mod p).
-/// - value: the secret amount (v)
-/// - blinding: random number (r) that hides the value
-/// Anyone can compute C, but without knowing r they cannot find v.
-fn pedersen_commit(value: i64, blinding: i64) -> i64 {
-    let term = value * G + blinding * H;
-    ((term % MODULUS) + MODULUS) % MODULUS
-}
-
-/// Toy "range proof" semantic check: value is non-negative (used conceptually; display/verify use toy_range_proof_*).
-/// In a real system you would use a zero-knowledge range proof (e.g. Bulletproofs)
-/// that proves 0 <= v < 2^n for a commitment C = v*G + r*H WITHOUT revealing v or r.
-#[allow(dead_code)]
-fn range_proof(value: i64) -> bool {
-    value >= 0
-}
-
-/// Toy "range proof" as a displayable value π (like we display C).
-/// Prover creates π from (value, C). In reality π would be ~700 bytes and bind to C without revealing v.
-/// Here we encode: π = C*2 + valid_bit (valid_bit = 1 if value >= 0 else 0) so we can show π in the demo.
-fn toy_range_proof_create(value: i64, commitment: i64) -> i64 {
-    let valid_bit = if value >= 0 { 1 } else { 0 };
-    commitment * 2 + valid_bit
-}
-
-/// Toy verification: verifier has only (C, π). Checks that π is valid for C (value was in range).
-/// In reality the verifier runs Bulletproof verification equations; here we check π == C*2+1.
-fn toy_range_proof_verify(commitment: i64, proof: i64) -> bool {
-    proof == commitment * 2 + 1
-}
-
-fn main() {
-    println!("{}", "=".repeat(60));
-    println!("CONFIDENTIAL TRANSACTION DEMO (Pedersen Commitments)");
-    println!("{}", "=".repeat(60));
-    println!("\n--- Public parameters (everyone knows these) ---");
-    println!("  Modulus p = {}", MODULUS);
-    println!("  Generator G = {}", G);
-    println!("  Generator H = {}", H);
-    println!("  (In real crypto, G and H would be curve points.)\n");
-
-    // ---------------------------------------------------------------------------
-    // STEP 1: Alice's initial commitment (input)
-    // ---------------------------------------------------------------------------
-    // Alice has 10 units. She created a commitment earlier using r_input. Only she knows (10, r_input).
-    println!("--- Step 1: Alice's input commitment ---");
-    let value_input = 10i64;
-    let r_input = 12345i64; // Alice's secret blinding factor for the input
-
-    let c_input = pedersen_commit(value_input, r_input);
-    let pi_input = toy_range_proof_create(value_input, c_input);
-    println!("  Alice's input commitment: C_input = {}*G + {}*H", value_input, r_input);
-    println!("  C_input = {}", c_input);
-    println!("  π_input = {}  (toy range proof for this commitment)", pi_input);
-    println!("  (The value 10 and blinding 12345 are NEVER sent on the chain.)\n");
-
-    // ---------------------------------------------------------------------------
-    // STEP 2: Alice creates commitments for the outputs
-    // ---------------------------------------------------------------------------
-    // Alice sends 5 to Bob, keeps 5 as change. She picks r_bob and r_change so that r_input = r_bob + r_change.
-    println!("--- Step 2: Output commitments (Bob and change) ---");
-    let value_to_bob = 5i64;
-    let value_change = 5i64;
-
-    let r_bob = 11111i64;
-    let r_change = r_input - r_bob; // so r_bob + r_change = r_input
-
-    let c_bob = pedersen_commit(value_to_bob, r_bob);
-    let c_change = pedersen_commit(value_change, r_change);
-    let pi_bob = toy_range_proof_create(value_to_bob, c_bob);
-    let pi_change = toy_range_proof_create(value_change, c_change);
-
-    println!("  Bob's output:   value = {}, blinding = {}", value_to_bob, r_bob);
-    println!("  C_bob   = {}*G + {}*H = {}   π_bob   = {}", value_to_bob, r_bob, c_bob, pi_bob);
-    println!("  Change: value = {}, blinding = {}", value_change, r_change);
-    println!("  C_change = {}*G + {}*H = {}   π_change = {}", value_change, r_change, c_change, pi_change);
-    println!("  (Again, the actual amounts 5 and 5 are never revealed.)\n");
-
-    // ---------------------------------------------------------------------------
-    // STEP 3: What gets published (only commitments)
-    // ---------------------------------------------------------------------------
-    println!("--- Step 3: What is published on the ledger ---");
-    println!("  The network sees commitments C and range proofs π (no values, no blindings):");
-    println!("    (C_input,  π_input ) = ({}, {})", c_input, pi_input);
-    println!("    (C_bob,    π_bob   ) = ({}, {})", c_bob, pi_bob);
-    println!("    (C_change, π_change) = ({}, {})", c_change, pi_change);
-    println!("  No one can recover 10, 5, or 5 from these alone.\n");
-
-    // ---------------------------------------------------------------------------
-    // STEP 4: Verification using only commitments
-    // ---------------------------------------------------------------------------
-    // Homomorphic: C_input should equal C_bob + C_change (mod p).
-    let sum_outputs = ((c_bob + c_change) % MODULUS + MODULUS) % MODULUS;
-    let inputs_match_outputs = c_input == sum_outputs;
-
-    println!("--- Step 4: Public verification (no values revealed) ---");
-    println!("  Check: C_input ?= C_bob + C_change  (mod p)");
-    println!("  C_input         = {}", c_input);
-    println!("  C_bob + C_change = {}", sum_outputs);
-    println!("  Match? {}", inputs_match_outputs);
-    if inputs_match_outputs {
-        println!("  So: sum(input amounts) = sum(output amounts), verified using only commitments.\n");
+    // Every output needs a range proof; inputs are checked only if they carry one.
+    let mut range_results = Vec::new();
+    for (entry, result) in tx.range_proof_results() {
+        match &result {
+            Ok(()) => say!(report, "range proof {}: ok", entry),
+            Err(e) => say!(report, "range proof {}: FAIL ({})", entry, e),
+        }
+        range_results.push(json!({ "entry": entry, "result": result_json(&result) }));
+    }
+    let range_ok = range_results.iter().all(|r| r["result"]["ok"] == json!(true));
+    report.record(
+        "verify",
+        json!({
+            "txid": tx.txid().to_string(),
+            "balance": result_json(&balance),
+            "range_proofs": range_results,
+            "valid": balance.is_ok() && range_ok,
+        }),
+    );
+
+    if balance.is_err() {
+        ExitCode::from(EXIT_BALANCE_FAILURE)
+    } else if !range_ok {
+        ExitCode::from(EXIT_RANGE_PROOF_FAILURE)
     } else {
-        println!("  Verification failed.\n");
-    }
-
-    // ---------------------------------------------------------------------------
-    // STEP 4b: Range proofs — create and display π, then verify (C, π) without knowing v
-    // ---------------------------------------------------------------------------
-    // In real systems: Bulletproofs (or similar) prove 0 <= v < 2^n for a commitment
-    // without revealing v. Here we display π like we display C.
-    println!("--- Step 4b: Range proofs — create π, then verify (C, π) ---");
-    println!("  Prover created π for each commitment (above). Verifier checks using only (C, π):");
-    let rp_input = toy_range_proof_verify(c_input, pi_input);
-    let rp_bob = toy_range_proof_verify(c_bob, pi_bob);
-    let rp_change = toy_range_proof_verify(c_change, pi_change);
-    println!("  verify(C_input,  π_input ) => {} (valid)", rp_input);
-    println!("  verify(C_bob,    π_bob   ) => {} (valid)", rp_bob);
-    println!("  verify(C_change, π_change) => {} (valid)", rp_change);
-    println!("  All range proofs pass. Verifier never saw the values.\n");
-
-    // ---------------------------------------------------------------------------
-    // STEP 5: Why amounts stay secret
-    // ---------------------------------------------------------------------------
-    println!("--- Step 5: Why amounts stay secret ---");
-    println!("  Given only C = v*G + r*H, there are infinitely many (v, r) that give the same C.");
-    println!("  So from C_input, C_bob, C_change one cannot deduce 10, 5, or 5.");
-    println!("  Verification only needed the equality C_input = C_bob + C_change.\n");
-
-    // ---------------------------------------------------------------------------
-    // DEMO: Negative value attack — why range proofs are required
-    // ---------------------------------------------------------------------------
-    println!("{}", "=".repeat(60));
-    println!("DEMO: How a negative value breaks the system");
-    println!("{}", "=".repeat(60));
-    println!("\n--- Attack: Malicious transaction with negative \"change\" ---");
-    println!("  Attacker has input 10 but wants to send 15 to Bob (creating 5 from nothing).");
-    println!("  They use a NEGATIVE change: value_change = -5.");
-    println!("  Math still balances: 10 = 15 + (-5), so commitment equation holds.\n");
-
-    let value_input_attack = 10i64;
-    let r_input_attack = 99999i64;
-    let value_to_bob_attack = 15i64;  // More than input!
-    let value_change_attack = -5i64;   // Negative "change" = creating value
-
-    let r_bob_attack = 11111i64;
-    let r_change_attack = r_input_attack - r_bob_attack;
-
-    let c_input_attack = pedersen_commit(value_input_attack, r_input_attack);
-    let c_bob_attack = pedersen_commit(value_to_bob_attack, r_bob_attack);
-    let c_change_attack = pedersen_commit(value_change_attack, r_change_attack);
-
-    let sum_outputs_attack = ((c_bob_attack + c_change_attack) % MODULUS + MODULUS) % MODULUS;
-    let attack_verification_passes = c_input_attack == sum_outputs_attack;
-
-    let pi_change_attack = toy_range_proof_create(value_change_attack, c_change_attack);
-    println!("  C_input (10)  = {}", c_input_attack);
-    println!("  C_bob (15)    = {}", c_bob_attack);
-    println!("  C_change (-5) = {}   π_change = {}", c_change_attack, pi_change_attack);
-    println!("  C_input ?= C_bob + C_change  =>  {}", attack_verification_passes);
-    println!("\n  Commitment verification PASSES even though 5 units were created from thin air!\n");
-
-    println!("--- Rejecting the attack with a range proof ---");
-    println!("  Verifier checks (C_change, π_change) without knowing the value:");
-    let rp_change_attack = toy_range_proof_verify(c_change_attack, pi_change_attack);
-    println!("  verify(C_change, π_change) => {} (INVALID)", rp_change_attack);
-    println!("  The malicious transaction is REJECTED because π fails verification (value was negative).\n");
-
-    println!("--- Why a range proof is required ---");
-    println!("  Pedersen commitments only prove sum(inputs) = sum(outputs).");
-    println!("  They do NOT prove that each value is non-negative or bounded.");
-    println!("  Without range proofs, anyone could use negative \"change\" to inflate the supply.");
-    println!("  A range proof proves (without revealing the amount) that a committed value v");
-    println!("  lies in a valid range, e.g. 0 <= v < 2^64. Then negative or huge values are rejected.\n");
-
-    // ---------------------------------------------------------------------------
-    // How a range proof is created and verified (conceptual)
-    // ---------------------------------------------------------------------------
-    println!("--- How a range proof is created (prover side) ---");
-    println!("  Inputs: commitment C = v*G + r*H, and the prover's secret (v, r).");
-    println!("  Goal: prove that 0 <= v < 2^n (e.g. n=64) WITHOUT revealing v or r.");
-    println!("  Idea (e.g. Bulletproofs-style):");
-    println!("    1. Write v in binary: v = b_0 + 2*b_1 + 4*b_2 + ... (each b_i is 0 or 1).");
-    println!("    2. Encode the bits into Pedersen commitments or vectors in a special way.");
-    println!("    3. Use an inner-product argument to prove that the bits are 0/1 and sum to v.");
-    println!("    4. The proof pi is a short string (~700 bytes) that binds to C.");
-    println!("  Output: proof pi. The prover sends (C, pi) to the verifier; v and r stay secret.\n");
-
-    println!("--- How a range proof is verified (verifier side) ---");
-    println!("  Inputs: commitment C and proof pi (and public parameters G, H, range bound 2^n).");
-    println!("  Verifier does NOT know v or r.");
-    println!("  Steps:");
-    println!("    1. Check that pi is well-formed and has the right size/structure.");
-    println!("    2. Run the verification equation(s): combine C, pi, G, H in a fixed formula.");
-    println!("    3. The math works out only if C actually commits to some v in [0, 2^n).");
-    println!("  If all checks pass => \"C commits to a value in range\". If not => reject.");
-    println!("  The verifier never learns v or r, only that the range condition holds.\n");
-
-    // ---------------------------------------------------------------------------
-    // Where Bulletproofs fit in real systems
-    // ---------------------------------------------------------------------------
-    println!("--- Where Bulletproofs fit in real systems ---");
-    println!("  In production (e.g. Monero, Mimblewimble):");
-    println!("  - Each input and output commitment comes with a RANGE PROOF.");
-    println!("  - Bulletproofs are short (~700 bytes) and prove 0 <= v < 2^64 for C = v*G + r*H");
-    println!("    without revealing v or r (zero-knowledge).");
-    println!("  - Verifiers check: (1) sum(input commitments) = sum(output commitments),");
-    println!("    (2) each range proof is valid. Then no negative or overflow amounts are possible.\n");
-
-    // ---------------------------------------------------------------------------
-    // Final message
-    // ---------------------------------------------------------------------------
-    println!("{}", "=".repeat(60));
-    if inputs_match_outputs {
-        println!("Transaction verified without revealing amounts.");
+        say!(report, "VALID transaction.");
+        ExitCode::SUCCESS
+    }
+}
+
+/// Print a failed step; malformed files exit 3, everything else 1.
+fn step_failure(report: &mut Report, step: &str, error: CtError) -> ExitCode {
+    eprintln!("ct {}: {}", step, error);
+    report.record(step, json!({ "error": error.to_string() }));
+    match error {
+        CtError::MalformedInput(_) => ExitCode::from(EXIT_MALFORMED_INPUT),
+        _ => ExitCode::FAILURE,
+    }
+}
+
+/// Seconds since the Unix epoch, for wallet history entries.
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn read_stdin() -> Result<String, CtError> {
+    let mut text = String::new();
+    std::io::stdin()
+        .read_to_string(&mut text)
+        .map(|_| text)
+        .map_err(|e| CtError::MalformedInput(format!("cannot read stdin: {}", e)))
+}
+
+fn read_file(path: &str) -> Result<String, CtError> {
+    fs::read_to_string(path).map_err(|e| CtError::MalformedInput(format!("cannot read {}: {}", path, e)))
+}
+
+fn write_file(path: &str, text: &str) -> Result<(), CtError> {
+    fs::write(path, text).map_err(|e| CtError::MalformedInput(format!("cannot write {}: {}", path, e)))
+}
+
+fn write_json(path: &str, document: &serde_json::Value) -> Result<(), CtError> {
+    let file = slate_file::versioned(document);
+    write_file(path, &(serde_json::to_string_pretty(&file).expect("JSON values always serialize") + "\n"))
+}
+
+/// Write a slate as JSON, or as armored text that is also printed for copy-pasting.
+fn write_slate(report: &mut Report, path: &str, slate: &Slate, armored: bool) -> Result<(), CtError> {
+    if !armored {
+        return write_json(path, &to_json(slate));
+    }
+    let text = armor(armor::SLATE, &slate.to_bytes());
+    write_file(path, &text)?;
+    say!(report, "{}", text.trim_end());
+    Ok(())
+}
+
+fn run_send(
+    report: &mut Report,
+    input: Opening,
+    sent: Result<(Slate, SenderContext), CtError>,
+    slate_out: &str,
+    context_out: &str,
+    armored: bool,
+) -> ExitCode {
+    let result = sent.and_then(|(slate, context)| {
+        write_json(context_out, &to_json(&context))?;
+        write_slate(report, slate_out, &slate, armored)?;
+        Ok((slate, context))
+    });
+    let (slate, context) = match result {
+        Ok(sent) => sent,
+        Err(e) => return step_failure(report, "send", e),
+    };
+    say!(
+        report,
+        "Spending C = {} ({} = {} + fee {} + change {})",
+        input.commit(),
+        input.value,
+        slate.amount,
+        slate.tx.fee,
+        context.change.value
+    );
+    say!(report, "Your public excess x_s*H = {}, nonce R_s = {}", slate.sender.public_excess, slate.sender.public_nonce);
+    say!(report, "Slate written to {}; send it to the receiver.", slate_out);
+    say!(report, "Secrets kept in {} (do not share it); you need it for `ct finalize`.", context_out);
+    report.record("send", json!({ "slate": to_json(&slate), "context": context_out }));
+    ExitCode::SUCCESS
+}
+
+/// Apply `update` to the wallet file at `path`, if one was given, and save it.
+fn update_wallet(path: Option<&str>, update: impl FnOnce(&mut Wallet) -> Result<(), CtError>) -> Result<(), CtError> {
+    let Some(path) = path else { return Ok(()) };
+    let (mut wallet, storage) = load_wallet(path)?;
+    update(&mut wallet)?;
+    save_wallet(path, &wallet, &storage)
+}
+
+fn run_receive(
+    report: &mut Report,
+    slate_in: &str,
+    slate_out: &str,
+    armored: bool,
+    mut rng: ToyRng,
+    wallet: Option<&str>,
+) -> ExitCode {
+    let result = read_file(slate_in)
+        .and_then(|text| slate_file::parse_slate(&text))
+        .and_then(|slate| slate.receive(&mut rng))
+        .and_then(|(mut slate, output)| {
+            if slate.payment_proof.is_some() && wallet.is_none() {
+                let reason = "the sender asked for a payment proof: receive with --wallet to sign it";
+                return Err(CtError::MalformedInput(reason.to_string()));
+            }
+            update_wallet(wallet, |wallet| {
+                if slate.payment_proof.is_some() {
+                    slate.sign_payment_proof(wallet.keychain().spend_secret(), &mut rng)?;
+                }
+                let commitment = wallet.add(output)?;
+                wallet.record(HistoryEntry::new(now(), Direction::Received, slate.amount as i64, 0, commitment));
+                Ok(())
+            })?;
+            write_slate(report, slate_out, &slate, armored)?;
+            Ok((slate, output))
+        });
+    let (slate, output) = match result {
+        Ok(received) => received,
+        Err(e) => return step_failure(report, "receive", e),
+    };
+    let receiver = slate.receiver.expect("received slates have a receiver");
+    say!(report, "Receiving {}: new output C = {}", slate.amount, output.commit());
+    let (value, blinding) = (output.value, output.blinding);
+    match wallet {
+        Some(path) => say!(report, "Added to {}: value {}, blinding {}", path, value, blinding),
+        None => say!(report, "Keep this opening to spend it later: value {}, blinding {}", value, blinding),
+    }
+    say!(
+        report,
+        "Your public excess x_r*H = {}, nonce R_r = {}, partial signature s_r = {}",
+        receiver.public_excess,
+        receiver.public_nonce,
+        receiver.partial_signature.expect("the receiver signs")
+    );
+    if let Some(request) = slate.payment_proof {
+        say!(report, "Payment proof signed with your address key B = {}", request.receiver);
+    }
+    say!(report, "Slate written to {}; send it back to the sender.", slate_out);
+    report.record(
+        "receive",
+        json!({ "slate": to_json(&slate), "opening": to_json(&output) }),
+    );
+    ExitCode::SUCCESS
+}
+
+fn run_finalize(
+    report: &mut Report,
+    slate_in: &str,
+    context_in: &str,
+    tx_out: Option<&str>,
+    armored: bool,
+    wallet: Option<&str>,
+    proof_out: &str,
+) -> ExitCode {
+    let result = read_file(slate_in)
+        .and_then(|text| slate_file::parse_slate(&text))
+        .and_then(|slate| Ok((slate, parse_json::<SenderContext>(&read_file(context_in)?)?)))
+        .and_then(|(slate, context)| {
+            let amount = slate.amount as i64;
+            let proof = slate.payment_proof()?;
+            let tx = slate.finalize(&context)?;
+            update_wallet(wallet, |wallet| {
+                if context.change.value.as_i64() > 0 {
+                    wallet.add(context.change)?;
+                }
+                let payment = tx.outputs.last().expect("the receiver added an output").commitment;
+                let entry = HistoryEntry::new(now(), Direction::Sent, amount, tx.fee, payment);
+                wallet.record(HistoryEntry { txid: Some(tx.txid()), ..entry });
+                Ok(())
+            })?;
+            Ok((tx, proof))
+        })
+        .and_then(|(tx, proof)| {
+            match tx_out {
+                Some(path) if armored => write_file(path, &armor(armor::TRANSACTION, &tx.to_bytes()))?,
+                Some(path) => write_json(path, &to_json(&tx))?,
+                None => {}
+            }
+            if let Some(proof) = &proof {
+                write_json(proof_out, &to_json(proof))?;
+            }
+            Ok((tx, proof))
+        });
+    let (tx, proof) = match result {
+        Ok(tx) => tx,
+        Err(e) => return step_failure(report, "finalize", e),
+    };
+    let kernel = tx.kernel.expect("finalized transactions have a kernel");
+    say!(report, "Receiver's partial signature: ok");
+    say!(report, "Kernel: E = {}, R = {}, s = s_s + s_r = {}", kernel.excess, kernel.signature.nonce, kernel.signature.s);
+    say!(report, "txid {}", tx.txid());
+    say!(report, "Transaction::verify() => ok");
+    if let Some(path) = tx_out {
+        say!(report, "Transaction written to {}.", path);
+    }
+    if let Some(path) = wallet {
+        say!(report, "Change and payment recorded in {}.", path);
+    }
+    if let Some(proof) = &proof {
+        say!(report, "Receiver's payment proof signature (key B = {}): ok", proof.receiver);
+        say!(report, "Payment proof written to {}; `ct payment-proof verify` checks it.", proof_out);
+    }
+    report.record(
+        "finalize",
+        json!({
+            "txid": tx.txid().to_string(),
+            "transaction": to_json(&tx),
+            "payment_proof": proof.as_ref().map(to_json),
+        }),
+    );
+    ExitCode::SUCCESS
+}
+
+fn run_multisig(report: &mut Report, action: MultisigCommand) -> ExitCode {
+    let result = match action {
+        MultisigCommand::Offer { out, secrets, seed } => {
+            let mut rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+            let (offer, offer_secrets) = Offer::new(&mut rng);
+            write_json(&secrets, &to_json(&offer_secrets))
+                .and_then(|()| write_json(&out, &to_json(&offer)))
+                .map(|()| {
+                    say!(report, "Your share r_b*H = {}, range nonce k_b*H = {}", offer.share, offer.range_nonce);
+                    say!(report, "Offer written to {}; send it to the funder.", out);
+                    say!(report, "Secrets kept in {} (do not share it); `ct multisig cosign` needs them.", secrets);
+                    report.record("offer", to_json(&offer));
+                })
+        }
+        MultisigCommand::Fund { offer, input_value, input_blinding, amount, fee, share, out, seed } => {
+            let input = Opening::new(Scalar::new(input_value), Blinding::new(input_blinding));
+            let mut rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+            read_file(&offer).and_then(|text| parse_json::<Offer>(&text)).and_then(|offer| {
+                let (funding, own_share, change) = Funding::new(input, amount, fee, &offer, &mut rng)?;
+                write_json(&share, &to_json(&own_share))?;
+                write_json(&out, &to_json(&funding))?;
+                say!(report, "2-of-2 output C = {} = {}*G + r_a*H + r_b*H", own_share.commitment, amount);
+                if change.value.as_i64() > 0 {
+                    let (value, blinding) = (change.value, change.blinding);
+                    say!(report, "change C = {}: value {}, blinding {}", change.commit(), value, blinding);
+                }
+                say!(report, "Your partial signature s_a = {}", funding.funder.partial_signature.expect("signed"));
+                say!(report, "Funding written to {}; send it to the cosigner.", out);
+                say!(report, "Your share kept in {} (do not share it); spending needs it.", share);
+                report.record(
+                    "fund",
+                    json!({ "output": own_share.commitment.as_i64(), "change": to_json(&change) }),
+                );
+                Ok(())
+            })
+        }
+        MultisigCommand::Cosign { funding, secrets, share, tx_out } => {
+            read_file(&funding)
+                .and_then(|text| parse_json::<Funding>(&text))
+                .and_then(|funding| Ok((funding, parse_json::<OfferSecrets>(&read_file(&secrets)?)?)))
+                .and_then(|(funding, secrets)| {
+                    let (tx, own_share) = funding.cosign(&secrets)?;
+                    write_json(&share, &to_json(&own_share))?;
+                    write_json(&tx_out, &to_json(&tx))?;
+                    say!(report, "Funder's partial signature: ok");
+                    say!(report, "Range proof for C = {} finished: ok", own_share.commitment);
+                    say!(report, "Transaction::verify() => ok");
+                    say!(report, "Transaction {} written to {}.", tx.txid(), tx_out);
+                    say!(report, "Your share kept in {} (do not share it); spending needs it.", share);
+                    let output = own_share.commitment.as_i64();
+                    report.record("cosign", json!({ "txid": tx.txid().to_string(), "output": output }));
+                    Ok(())
+                })
+        }
+        MultisigCommand::Spend { share, fee, secrets, out, seed } => {
+            let mut rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+            read_file(&share).and_then(|text| parse_json::<Share>(&text)).and_then(|share| {
+                let (spend, spend_secrets) = Spend::new(&share, fee, &mut rng)?;
+                write_json(&secrets, &to_json(&spend_secrets))?;
+                write_json(&out, &to_json(&spend))?;
+                let output = spend_secrets.output;
+                say!(report, "Spending C = {} into C = {} (value {})", share.commitment, output.commit(), output.value);
+                say!(report, "Spend written to {}; send it to the other party.", out);
+                say!(report, "Secrets kept in {} (do not share it); `ct multisig finalize` needs them.", secrets);
+                report.record("spend", to_json(&spend));
+                Ok(())
+            })
+        }
+        MultisigCommand::Sign { spend, share, out, seed } => {
+            let mut rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+            read_file(&spend)
+                .and_then(|text| parse_json::<Spend>(&text))
+                .and_then(|spend| Ok((spend, parse_json::<Share>(&read_file(&share)?)?)))
+                .and_then(|(spend, share)| {
+                    let spend = spend.sign(&share, &mut rng)?;
+                    write_json(&out, &to_json(&spend))?;
+                    let other = spend.other.expect("just signed");
+                    say!(report, "Your partial signature s = {}", other.partial_signature.expect("signed"));
+                    say!(report, "Signed spend written to {}; send it back.", out);
+                    report.record("sign", to_json(&spend));
+                    Ok(())
+                })
+        }
+        MultisigCommand::Finalize { spend, secrets, tx_out } => {
+            read_file(&spend)
+                .and_then(|text| parse_json::<Spend>(&text))
+                .and_then(|spend| Ok((spend, parse_json::<SpendSecrets>(&read_file(&secrets)?)?)))
+                .and_then(|(spend, secrets)| {
+                    let tx = spend.finalize(&secrets)?;
+                    write_json(&tx_out, &to_json(&tx))?;
+                    let output = secrets.output;
+                    say!(report, "Other party's partial signature: ok");
+                    say!(report, "Transaction::verify() => ok");
+                    say!(report, "Transaction {} written to {}.", tx.txid(), tx_out);
+                    say!(report, "Keep the new output's opening: value {}, blinding {}", output.value, output.blinding);
+                    report.record(
+                        "finalize",
+                        json!({ "txid": tx.txid().to_string(), "opening": to_json(&output) }),
+                    );
+                    Ok(())
+                })
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => step_failure(report, "multisig", e),
+    }
+}
+
+fn run_swap(report: &mut Report, action: SwapCommand) -> ExitCode {
+    let read_secrets = |path: &str| read_file(path).and_then(|text| parse_json::<SwapSecrets>(&text));
+    let read_contract = |path: &str| read_file(path).and_then(|text| parse_json::<Contract>(&text));
+    let result = match action {
+        SwapCommand::Init { preimage, out, seed } => {
+            let mut rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+            let key = Blinding::new(rng.scalar());
+            let preimage = preimage.then(|| {
+                let mut bytes = [0u8; PREIMAGE_LEN];
+                bytes.chunks_mut(8).for_each(|chunk| chunk.copy_from_slice(&rng.next_u64().to_le_bytes()));
+                bytes
+            });
+            let secrets = SwapSecrets { key, preimage };
+            write_json(&out, &to_json(&secrets)).map(|()| {
+                say!(report, "Your swap key K = {}; send it to the other party.", public_key(key));
+                if let Some(preimage) = &preimage {
+                    let hash = to_hex(&hash_preimage(preimage));
+                    say!(report, "Hash h = SHA-256(s) = {}; your contract carries it.", hash);
+                }
+                say!(report, "Secrets kept in {} (do not share it).", out);
+                report.record("init", json!({ "key": public_key(key).as_i64() }));
+            })
+        }
+        SwapCommand::Lock {
+            secrets,
+            input_value,
+            input_blinding,
+            amount,
+            fee,
+            claim_key,
+            hash,
+            refund_height,
+            out,
+            tx_out,
+            seed,
+        } => {
+            let input = Opening::new(Scalar::new(input_value), Blinding::new(input_blinding));
+            let mut rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+            read_secrets(&secrets).and_then(|secrets| {
+                let hash = match (hash, secrets.preimage) {
+                    (Some(hex), _) => swap_file::parse_hex32(&hex, "hash")?,
+                    (None, Some(preimage)) => hash_preimage(&preimage),
+                    (None, None) => {
+                        let message = "pass --hash from the other party's contract; your secrets hold no s";
+                        return Err(CtError::MalformedInput(message.to_string()));
+                    }
+                };
+                let lock = HashLock { hash, claim_key, refund_key: public_key(secrets.key), refund_height };
+                let (contract, tx, change) = Contract::lock(input, amount, fee, lock, &mut rng)?;
+                write_json(&out, &to_json(&contract))?;
+                write_json(&tx_out, &to_json(&tx))?;
+                say!(report, "Locked C = {} (value {}) to hash {}", contract.commitment(), amount, to_hex(&hash));
+                say!(report, "Claimable by K = {}; refundable to you from height {}", claim_key, refund_height);
+                if let Some(change) = change {
+                    let (value, blinding) = (change.value, change.blinding);
+                    say!(report, "change C = {}: value {}, blinding {}", change.commit(), value, blinding);
+                }
+                say!(report, "Transaction {} written to {}; mine it with `ct chain mine`.", tx.txid(), tx_out);
+                say!(report, "Contract written to {}; send it to the other party.", out);
+                report.record("lock", to_json(&contract));
+                Ok(())
+            })
+        }
+        SwapCommand::Check { contract, chain } => read_contract(&contract).and_then(|contract| {
+            let chain = load_chain(&chain)?;
+            let ledger = chain.ledger();
+            let outpoint = ledger.find(contract.commitment()).ok_or_else(|| {
+                CtError::MalformedInput(format!("{} is not an unspent output of the chain", contract.commitment()))
+            })?;
+            if ledger.lock(&outpoint) != Some(&contract.lock.condition()) {
+                return Err(CtError::MalformedInput(format!("{} is not locked as the contract says", outpoint)));
+            }
+            if contract.opening.commit() != contract.commitment() || contract.opening.value.as_i64() < 0 {
+                return Err(CtError::MalformedInput("the contract's opening is wrong".to_string()));
+            }
+            let height = ledger.next_height();
+            let (value, refund_height) = (contract.opening.value, contract.lock.refund_height);
+            say!(report, "{} holds C = {} (value {}), unspent.", outpoint, contract.commitment(), value);
+            say!(report, "Its lock matches the contract: ok");
+            say!(report, "Next block: {}; the refund is valid from height {}.", height, refund_height);
+            let record = json!({ "outpoint": outpoint.to_string(), "value": value.as_i64(), "next_height": height });
+            report.record("check", record);
+            Ok(())
+        }),
+        SwapCommand::Claim { contract, secrets, preimage_from, fee, tx_out, seed } => {
+            let mut rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+            read_contract(&contract)
+                .and_then(|contract| Ok((contract, read_secrets(&secrets)?)))
+                .and_then(|(contract, secrets)| {
+                    let preimage = match (secrets.preimage, preimage_from) {
+                        (Some(preimage), _) => preimage,
+                        (None, Some(path)) => contract.find_preimage(&load_chain(&path)?).ok_or_else(|| {
+                            CtError::MalformedInput(format!("no claim on {} shows the preimage yet", path))
+                        })?,
+                        (None, None) => {
+                            let message = "your secrets hold no s; pass --preimage-from with the other chain";
+                            return Err(CtError::MalformedInput(message.to_string()));
+                        }
+                    };
+                    let (tx, output) = contract.claim(preimage, secrets.key, fee, &mut rng)?;
+                    write_json(&tx_out, &to_json(&tx))?;
+                    say!(report, "Preimage s = {}", to_hex(&preimage));
+                    say!(report, "Claiming C = {} into C = {}", contract.commitment(), output.commit());
+                    say!(report, "Transaction {} written to {}; mine it with `ct chain mine`.", tx.txid(), tx_out);
+                    say!(report, "Keep the new output's opening: value {}, blinding {}", output.value, output.blinding);
+                    let record = json!({ "txid": tx.txid().to_string(), "opening": to_json(&output) });
+                    report.record("claim", record);
+                    Ok(())
+                })
+        }
+        SwapCommand::Refund { contract, secrets, fee, tx_out, seed } => {
+            let mut rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+            read_contract(&contract)
+                .and_then(|contract| Ok((contract, read_secrets(&secrets)?)))
+                .and_then(|(contract, secrets)| {
+                    let (tx, output) = contract.refund(secrets.key, fee, &mut rng)?;
+                    write_json(&tx_out, &to_json(&tx))?;
+                    let refund_height = contract.lock.refund_height;
+                    say!(report, "Refunding C = {} into C = {}", contract.commitment(), output.commit());
+                    say!(report, "Transaction {} written to {}.", tx.txid(), tx_out);
+                    say!(report, "Blocks from height {} on accept it.", refund_height);
+                    say!(report, "Keep the new output's opening: value {}, blinding {}", output.value, output.blinding);
+                    let record = json!({ "txid": tx.txid().to_string(), "opening": to_json(&output) });
+                    report.record("refund", record);
+                    Ok(())
+                })
+        }
+        SwapCommand::Scriptless { seed } => walkthrough::scriptless_swap(report, seed),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => step_failure(report, "swap", e),
+    }
+}
+
+fn run_payment_proof_verify(report: &mut Report, source: &str, chain: Option<&str>) -> ExitCode {
+    let loaded = read_file(source)
+        .and_then(|text| parse_json::<PaymentProof>(&text))
+        .and_then(|proof| Ok((proof, chain.map(load_chain).transpose()?)));
+    let (proof, chain) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => return step_failure(report, "payment-proof", e),
+    };
+
+    say!(report, "Payment of {} to key B = {} by kernel E = {}", proof.amount, proof.receiver, proof.kernel_excess);
+    let signature = proof.verify();
+    say!(report, "receiver's signature: {}", outcome(&signature));
+    let height = chain.as_ref().map(|chain| proof.confirmed_in(chain).map(|header| header.height));
+    let confirmed = match height {
+        Some(Some(height)) => {
+            say!(report, "kernel on the chain at height {}: ok", height);
+            Ok(())
+        }
+        Some(None) => {
+            let missing = format!("no kernel with excess {} on the chain", proof.kernel_excess);
+            let confirmed = Err(CtError::MalformedInput(missing));
+            say!(report, "kernel on the chain: {}", outcome(&confirmed));
+            confirmed
+        }
+        None => {
+            say!(report, "Pass --chain to check that the kernel was confirmed.");
+            Ok(())
+        }
+    };
+    let result = signature.and(confirmed);
+    report.record(
+        "payment_proof",
+        json!({
+            "proof": to_json(&proof),
+            "height": height.flatten(),
+            "result": result_json(&result),
+        }),
+    );
+    if result.is_ok() {
+        ExitCode::SUCCESS
     } else {
-        println!("Verification failed.");
+        ExitCode::FAILURE
+    }
+}
+
+fn load_chain(path: &str) -> Result<Chain, CtError> {
+    chain_file::parse_chain(&read_file(path)?)
+}
+
+fn run_chain(report: &mut Report, file: &str, action: ChainCommand) -> ExitCode {
+    let sources = match &action {
+        ChainCommand::Init { tx } | ChainCommand::Mine { tx } => tx.clone(),
+        ChainCommand::Show | ChainCommand::Compact { .. } => Vec::new(),
+    };
+    let writes = matches!(action, ChainCommand::Init { .. } | ChainCommand::Mine { .. });
+    let horizon = match action {
+        ChainCommand::Compact { horizon } => Some(horizon),
+        _ => None,
+    };
+    let mut transactions = Vec::new();
+    for source in &sources {
+        match read_tx(report, "chain", source) {
+            Ok(tx) => transactions.push(tx),
+            Err(code) => return code,
+        }
+    }
+    let result = match action {
+        ChainCommand::Init { .. } if Path::new(file).exists() => {
+            Err(CtError::MalformedInput(format!("{} already exists; remove it to start over", file)))
+        }
+        ChainCommand::Init { .. } => Chain::new(Block::new(&Ledger::new(), transactions)),
+        ChainCommand::Mine { .. } => load_chain(file).and_then(|mut chain| {
+            let block = Block::new(chain.ledger(), transactions);
+            chain.add_block(block)?;
+            Ok(chain)
+        }),
+        ChainCommand::Show | ChainCommand::Compact { .. } => load_chain(file),
+    }
+    .and_then(|chain| {
+        if writes {
+            write_json(file, &chain_file::chain_json(&chain))?;
+        }
+        Ok(chain)
+    });
+    let chain = match result {
+        Ok(chain) => chain,
+        Err(e) => return step_failure(report, "chain", e),
+    };
+
+    let tip = chain.tip();
+    say!(report, "{}: height {}, tip {}", file, tip.height, tip.hash());
+    say!(report, "output MMR root {}", to_hex(&tip.output_root));
+    let supply = chain.ledger().supply();
+    say!(
+        report,
+        "supply {} (minted {}, fees {}, burned {})",
+        supply.circulating(),
+        supply.minted,
+        supply.fees,
+        supply.burned
+    );
+    say!(report, "{} unspent output(s):", chain.ledger().len());
+    for (outpoint, commitment) in chain.ledger().utxos() {
+        say!(report, "  {}  C = {}", outpoint, commitment);
+    }
+    report.record(
+        "chain",
+        json!({
+            "height": tip.height,
+            "tip": tip.hash().to_string(),
+            "output_root": to_hex(&tip.output_root),
+            "supply": {
+                "circulating": supply.circulating(),
+                "minted": supply.minted,
+                "fees": supply.fees,
+                "burned": supply.burned,
+            },
+            "utxos": chain
+                .ledger()
+                .utxos()
+                .map(|(outpoint, commitment)| json!({ "outpoint": outpoint.to_string(), "commitment": commitment.as_i64() }))
+                .collect::<Vec<_>>(),
+        }),
+    );
+    match horizon {
+        Some(horizon) => run_chain_compact(report, &chain, horizon),
+        None => ExitCode::SUCCESS,
+    }
+}
+
+fn run_chain_compact(report: &mut Report, chain: &Chain, horizon: u64) -> ExitCode {
+    let compacted = match Compacted::new(chain, horizon) {
+        Ok(compacted) => compacted,
+        Err(e) => return step_failure(report, "compact", e),
+    };
+    let result = compacted.validate();
+    say!(
+        report,
+        "horizon {}: {} block(s) compacted, {} kept whole",
+        horizon,
+        compacted.headers.len() - compacted.recent.len(),
+        compacted.recent.len()
+    );
+    say!(
+        report,
+        "pruned {} spent output(s) with their range proofs: {} bytes -> {} bytes",
+        compacted.pruned_outputs,
+        compacted.bytes_before,
+        compacted.bytes_after
+    );
+    say!(report, "compacted chain validates: {}", report::outcome(&result));
+    report.record(
+        "compact",
+        json!({
+            "horizon": horizon,
+            "kept": compacted.recent.len(),
+            "pruned_outputs": compacted.pruned_outputs,
+            "bytes_before": compacted.bytes_before,
+            "bytes_after": compacted.bytes_after,
+            "result": result_json(&result),
+        }),
+    );
+    if result.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// The passphrase of an encrypted wallet: `$CT_WALLET_PASSPHRASE`, or asked on the terminal.
+fn passphrase(path: &str) -> Result<String, CtError> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_VAR) {
+        return Ok(passphrase);
+    }
+    prompt::ask_secret(&format!("Passphrase for {}: ", path))
+        .ok_or_else(|| CtError::MalformedInput(format!("no passphrase given for {}", path)))
+}
+
+/// A new passphrase, asked twice unless it comes from `$CT_WALLET_PASSPHRASE`.
+fn new_passphrase(path: &str) -> Result<String, CtError> {
+    let passphrase = passphrase(path)?;
+    if passphrase.is_empty() {
+        return Err(CtError::MalformedInput("the passphrase is empty".to_string()));
+    }
+    if std::env::var(PASSPHRASE_VAR).is_err() && prompt::ask_secret("Repeat it: ").as_ref() != Some(&passphrase) {
+        return Err(CtError::MalformedInput("the passphrases do not match".to_string()));
+    }
+    Ok(passphrase)
+}
+
+fn load_wallet(path: &str) -> Result<(Wallet, Storage), CtError> {
+    wallet_file::load_wallet(&read_file(path)?, || passphrase(path))
+}
+
+fn save_wallet(path: &str, wallet: &Wallet, storage: &Storage) -> Result<(), CtError> {
+    write_json(path, &wallet_file::save_wallet(wallet, storage)?)
+}
+
+fn run_wallet(report: &mut Report, file: &str, action: WalletCommand) -> ExitCode {
+    let result = match &action {
+        WalletCommand::Init { .. } | WalletCommand::Restore { .. } if Path::new(file).exists() => {
+            Err(CtError::MalformedInput(format!("{} already exists; remove it to start over", file)))
+        }
+        WalletCommand::Init { encrypt: true, .. } | WalletCommand::Restore { encrypt: true, .. } => {
+            new_passphrase(file).and_then(|passphrase| Storage::encrypted(&passphrase))
+        }
+        _ => Ok(Storage::Plain),
+    }
+    .and_then(|storage| match &action {
+        WalletCommand::Init { seed: Some(seed), .. } => {
+            say!(report, "restored seed {}", seed);
+            report.record("init", json!({ "seed": seed.to_string() }));
+            Ok((Wallet::new(Keychain::new(*seed)), storage))
+        }
+        WalletCommand::Init { words, mnemonic, seed: None, .. } => {
+            let generated = match mnemonic {
+                Some(mnemonic) => Ok((mnemonic.clone(), false)),
+                None => Mnemonic::generate(*words).map(|mnemonic| (mnemonic, true)),
+            };
+            generated.map(|(mnemonic, new)| {
+                let seed = mnemonic.keychain_seed("");
+                if new {
+                    say!(report, "mnemonic: {}", mnemonic);
+                    let words = mnemonic.words().len();
+                    say!(report, "Write these {} words down: they recreate every derived blinding.", words);
+                } else {
+                    say!(report, "restored from a {}-word mnemonic", mnemonic.words().len());
+                }
+                say!(report, "seed {}", seed);
+                report.record("init", json!({ "mnemonic": mnemonic.to_string(), "seed": seed.to_string() }));
+                (Wallet::new(Keychain::new(seed)), storage)
+            })
+        }
+        WalletCommand::Restore { from_chain, mnemonic, seed, .. } => load_chain(from_chain).map(|chain| {
+            let seed = mnemonic.as_ref().map_or_else(|| seed.expect("clap requires a seed"), |m| m.keychain_seed(""));
+            let wallet = Wallet::restore(Keychain::new(seed), &chain);
+            say!(report, "restored {} output(s) from {}:", wallet.outputs().len(), from_chain);
+            for output in wallet.outputs() {
+                let path = output.path.map_or_else(|| "address".to_string(), |path| path.to_string());
+                let (status, value) = (output.status, output.opening.value);
+                say!(report, "  {:<11} {:<9} v = {:<4} C = {}", status, path, value, output.commitment);
+            }
+            let restored: Vec<_> = wallet.outputs().iter().map(wallet_file::output_json).collect();
+            report.record("restore", json!({ "seed": seed.to_string(), "outputs": restored }));
+            (wallet, storage)
+        }),
+        WalletCommand::Derive { value, account, change } => load_wallet(file).and_then(|(mut wallet, storage)| {
+            let branch = if *change { CHANGE } else { RECEIVE };
+            let (opening, path) = wallet.derive(Scalar::new(*value), *account, branch)?;
+            wallet.record(HistoryEntry::new(now(), Direction::Received, *value, 0, opening.commit()));
+            say!(report, "{}: r = {}, C = {} (value {})", path, opening.blinding, opening.commit(), value);
+            let derived = json!({
+                "path": path.to_string(),
+                "blinding": opening.blinding.as_i64(),
+                "commitment": opening.commit().as_i64(),
+            });
+            report.record("derive", derived);
+            Ok((wallet, storage))
+        }),
+        WalletCommand::Add { value, blinding } => load_wallet(file).and_then(|(mut wallet, storage)| {
+            let commitment = wallet.add(Opening::new(Scalar::new(*value), Blinding::new(*blinding)))?;
+            wallet.record(HistoryEntry::new(now(), Direction::Received, *value, 0, commitment));
+            say!(report, "added C = {} (value {})", commitment, value);
+            Ok((wallet, storage))
+        }),
+        WalletCommand::Send { amount, fee, strategy, to, memo, tx_out, seed } => {
+            load_wallet(file).and_then(|(mut wallet, storage)| {
+                let mut rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+                let payment = wallet.pay(*amount, *fee, *strategy, *to, *memo, &mut rng)?;
+                wallet.record(payment.history_entry(now()));
+                write_json(tx_out, &to_json(&payment.tx))?;
+                report_payment(report, &payment, *strategy, *to, tx_out);
+                Ok((wallet, storage))
+            })
+        }
+        WalletCommand::Address { qr } => load_wallet(file).inspect(|(wallet, _)| {
+            let address = wallet.keychain().address();
+            say!(report, "address {}", address);
+            if *qr {
+                let code = QrCode::encode(address.to_string().as_bytes()).expect("an address fits in a QR code");
+                say!(report, "{}", code.to_text().trim_end());
+            }
+            say!(report, "Payers send to it with `ct wallet send --to {}`;", address);
+            say!(report, "`ct wallet scan` then finds their outputs on the chain.");
+            report.record("address", json!(address.to_string()));
+        }),
+        WalletCommand::Scan { chain } => load_wallet(file).and_then(|(mut wallet, storage)| {
+            let chain = load_chain(chain)?;
+            let found = wallet.scan(&chain);
+            let keychain = *wallet.keychain();
+            let mut found_json = Vec::new();
+            for (opening, note) in &found {
+                let amount = opening.value.as_i64();
+                wallet.record(HistoryEntry::new(now(), Direction::Received, amount, 0, opening.commit()));
+                say!(report, "found C = {}: value {}, blinding {}", opening.commit(), opening.value, opening.blinding);
+                let mut document = to_json(opening);
+                match note {
+                    Some(note) => {
+                        let secret = ecdh::one_time_secret(keychain.scan_secret(), keychain.spend_secret(), note);
+                        let signs = prompt::yes_no(public_key(secret) == note.one_time_key);
+                        say!(
+                            report,
+                            "  one-time key P = {}, secret x = {} (x*H = P: {})",
+                            note.one_time_key,
+                            secret,
+                            signs
+                        );
+                        document["one_time_key"] = json!(note.one_time_key.as_i64());
+                    }
+                    None => say!(report, "  from the output's memo"),
+                }
+                found_json.push(document);
+            }
+            let changed = wallet.sync(chain.ledger());
+            say!(report, "{} output(s) paid to this wallet found, {} output(s) changed status", found.len(), changed);
+            report.record("scan", json!({ "found": found_json, "changed": changed }));
+            Ok((wallet, storage))
+        }),
+        WalletCommand::Sync { chain } => load_wallet(file).and_then(|(mut wallet, storage)| {
+            let changed = wallet.sync(load_chain(chain)?.ledger());
+            say!(report, "{} output(s) changed status", changed);
+            report.record("sync", json!({ "changed": changed }));
+            Ok((wallet, storage))
+        }),
+        WalletCommand::Disclose { chain, outputs, out } => load_wallet(file).and_then(|(wallet, storage)| {
+            let openings = outputs
+                .iter()
+                .map(|&commitment| {
+                    let output = wallet.outputs().iter().find(|output| output.commitment == commitment);
+                    output
+                        .map(|output| output.opening)
+                        .ok_or_else(|| CtError::MalformedInput(format!("the wallet has no output {}", commitment)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let disclosure = Disclosure::create(load_chain(chain)?.ledger(), &openings)?;
+            write_json(out, &to_json(&disclosure))?;
+            say!(
+                report,
+                "{} output(s) disclosed, holding {} in total, written to {}",
+                openings.len(),
+                disclosure.total(),
+                out
+            );
+            report.record("disclose", json!({ "block": disclosure.block.to_string(), "outputs": openings.len() }));
+            Ok((wallet, storage))
+        }),
+        WalletCommand::Encrypt => load_wallet(file).and_then(|(wallet, storage)| {
+            if storage.is_encrypted() {
+                return Err(CtError::MalformedInput(format!("{} is already encrypted", file)));
+            }
+            let storage = Storage::encrypted(&new_passphrase(file)?)?;
+            say!(report, "{} is now encrypted with XChaCha20-Poly1305", file);
+            Ok((wallet, storage))
+        }),
+        WalletCommand::ExportViewkey { account } => load_wallet(file).inspect(|(wallet, _)| {
+            let view_key = wallet.keychain().view_key(*account);
+            say!(report, "view key {}", view_key);
+            say!(report, "It finds account {}'s outputs and their values, but cannot spend them.", account);
+            report.record("view_key", json!(view_key.to_string()));
+        }),
+        WalletCommand::Balance | WalletCommand::Outputs | WalletCommand::History => load_wallet(file),
+    })
+    .and_then(|(wallet, storage)| {
+        let read_only = matches!(
+            action,
+            WalletCommand::Balance
+                | WalletCommand::Outputs
+                | WalletCommand::History
+                | WalletCommand::ExportViewkey { .. }
+                | WalletCommand::Disclose { .. }
+                | WalletCommand::Address { .. }
+        );
+        if !read_only {
+            save_wallet(file, &wallet, &storage)?;
+        }
+        Ok(wallet)
+    });
+    let wallet = match result {
+        Ok(wallet) => wallet,
+        Err(e) => return step_failure(report, "wallet", e),
+    };
+
+    if matches!(action, WalletCommand::History) {
+        say!(report, "{} payment(s):", wallet.history().len());
+        for entry in wallet.history() {
+            let txid = entry.txid.map_or_else(|| "-".to_string(), |txid| txid.to_string());
+            let fee = if entry.fee > 0 { format!(" (fee {})", entry.fee) } else { String::new() };
+            say!(
+                report,
+                "  {}  {:<8} {:>4}{:<10} {:<9}  C = {:<20} {}",
+                wallet_file::format_time(entry.time),
+                entry.direction,
+                entry.amount,
+                fee,
+                wallet_file::status(entry),
+                entry.output,
+                txid
+            );
+        }
+        report.record("history", json!(wallet.history().iter().map(wallet_file::entry_json).collect::<Vec<_>>()));
+    }
+    if matches!(action, WalletCommand::Outputs) {
+        say!(report, "{} output(s):", wallet.outputs().len());
+        for output in wallet.outputs() {
+            let outpoint = output.outpoint.map_or_else(|| "-".to_string(), |outpoint| outpoint.to_string());
+            say!(
+                report,
+                "  {:<11} {:<9} v = {:<4} r = {:<20} C = {:<20} {}",
+                output.status,
+                output.path.map_or_else(|| "-".to_string(), |path| path.to_string()),
+                output.opening.value.as_i64(),
+                output.opening.blinding.as_i64(),
+                output.commitment,
+                outpoint
+            );
+        }
+        report.record("outputs", json!(wallet.outputs().iter().map(wallet_file::output_json).collect::<Vec<_>>()));
+    }
+    let unspent = wallet.outputs().iter().filter(|output| output.status == OutputStatus::Unspent).count();
+    let (balance, pending) = (wallet.balance(), wallet.pending());
+    say!(report, "{}: balance {} in {} unspent output(s), {} unconfirmed", file, balance, unspent, pending);
+    report.record("balance", json!({ "balance": balance, "unspent": unspent, "unconfirmed": pending }));
+    ExitCode::SUCCESS
+}
+
+fn run_watch(report: &mut Report, file: &str, action: WatchCommand) -> ExitCode {
+    let load = || wallet_file::parse_watch(&read_file(file)?);
+    let result = match &action {
+        WatchCommand::Init { .. } if Path::new(file).exists() => {
+            Err(CtError::MalformedInput(format!("{} already exists; remove it to start over", file)))
+        }
+        WatchCommand::Init { view_key } => {
+            say!(report, "watching account {} ({})", view_key.account, view_key);
+            Ok(WatchWallet::new(*view_key))
+        }
+        WatchCommand::Scan { chain } => load().and_then(|mut wallet| {
+            let changed = wallet.scan(load_chain(chain)?.ledger());
+            say!(report, "{} output(s) found or changed status", changed);
+            report.record("scan", json!({ "changed": changed }));
+            Ok(wallet)
+        }),
+        WatchCommand::Balance | WatchCommand::Outputs => load(),
+    }
+    .and_then(|wallet| {
+        if !matches!(action, WatchCommand::Balance | WatchCommand::Outputs) {
+            write_json(file, &wallet_file::watch_json(&wallet))?;
+        }
+        Ok(wallet)
+    });
+    let wallet = match result {
+        Ok(wallet) => wallet,
+        Err(e) => return step_failure(report, "watch", e),
+    };
+
+    if matches!(action, WatchCommand::Outputs) {
+        say!(report, "{} output(s):", wallet.outputs().len());
+        for output in wallet.outputs() {
+            let outpoint = output.outpoint.map_or_else(|| "-".to_string(), |outpoint| outpoint.to_string());
+            say!(
+                report,
+                "  {:<11} {:<9} v = {:<4} C = {:<20} {}",
+                output.status,
+                output.path,
+                output.value.as_i64(),
+                output.commitment,
+                outpoint
+            );
+        }
+        report.record("outputs", json!(wallet.outputs().iter().map(wallet_file::watched_json).collect::<Vec<_>>()));
+    }
+    let unspent = wallet.outputs().iter().filter(|output| output.status == OutputStatus::Unspent).count();
+    say!(report, "{} (watch-only): balance {} in {} unspent output(s)", file, wallet.balance(), unspent);
+    report.record("balance", json!({ "balance": wallet.balance(), "unspent": unspent }));
+    ExitCode::SUCCESS
+}
+
+fn run_audit(report: &mut Report, source: &str, chain: &str) -> ExitCode {
+    let loaded = read_file(source).and_then(|text| parse_json::<Disclosure>(&text)).and_then(|disclosure| {
+        let chain = load_chain(chain)?;
+        match chain.block(&disclosure.block).filter(|block| chain.is_main(&block.header.hash())) {
+            Some(block) => Ok((disclosure, block.header.clone())),
+            None => Err(CtError::MalformedInput(format!("block {} is not on the chain", disclosure.block))),
+        }
+    });
+    let (disclosure, header) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => return step_failure(report, "audit", e),
+    };
+
+    say!(
+        report,
+        "{} output(s) disclosed against block {} (height {}):",
+        disclosure.outputs.len(),
+        header.hash(),
+        header.height
+    );
+    let mut outputs = Vec::new();
+    for (output, (commitment, result)) in disclosure.outputs.iter().zip(disclosure.check(&header)) {
+        let opening = output.opening;
+        say!(
+            report,
+            "  C = {:<20} v = {:<4} r = {:<20} on chain: {}",
+            commitment,
+            opening.value,
+            opening.blinding,
+            outcome(&result)
+        );
+        outputs.push(json!({
+            "commitment": commitment.as_i64(),
+            "value": opening.value.as_i64(),
+            "result": result_json(&result),
+        }));
+    }
+    let result = disclosure.verify(&header);
+    say!(report, "disclosed total {}: {}", disclosure.total(), outcome(&result));
+    report.record(
+        "audit",
+        json!({
+            "block": disclosure.block.to_string(),
+            "height": header.height,
+            "outputs": outputs,
+            "total": disclosure.total(),
+            "result": result_json(&result),
+        }),
+    );
+    if result.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn run_por(report: &mut Report, action: PorCommand) -> ExitCode {
+    let result = match &action {
+        PorCommand::Create { wallet, chain, amount, out, seed } => load_wallet(wallet).and_then(|(wallet, _)| {
+            let chain = load_chain(chain)?;
+            let owned: Vec<(OutPoint, Opening)> = wallet
+                .outputs()
+                .iter()
+                .filter(|output| output.status == OutputStatus::Unspent)
+                .filter_map(|output| output.outpoint.map(|outpoint| (outpoint, output.opening)))
+                .collect();
+            let mut rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+            let proof = ReservesProof::create(chain.ledger(), &owned, *amount, &mut rng)?;
+            write_json(out, &to_json(&proof))?;
+            say!(report, "Proof of reserves written to {}.", out);
+            Ok((proof, chain, Ok(())))
+        }),
+        PorCommand::Verify { chain, proof } => load_chain(chain).and_then(|chain| {
+            let proof = parse_json::<ReservesProof>(&read_file(proof)?)?;
+            if !chain.is_main(&proof.block) {
+                return Err(CtError::MalformedInput(format!("block {} is not on the chain", proof.block)));
+            }
+            let result = proof.verify(&chain.state_after(proof.block)?);
+            Ok((proof, chain, result))
+        }),
+    };
+    let (proof, chain, result) = match result {
+        Ok(checked) => checked,
+        Err(e) => return step_failure(report, "por", e),
+    };
+
+    let ledger = chain.state_after(proof.block).expect("the proof's block is on the chain");
+    let height = ledger.tip().map_or(0, |tip| tip.height);
+    say!(report, "{} output(s) unspent after block {} (height {}):", proof.outputs.len(), proof.block, height);
+    for outpoint in &proof.outputs {
+        say!(report, "  {}  C = {}", outpoint, ledger.get(outpoint).map_or("-".to_string(), |c| c.to_string()));
+    }
+    if let Ok(total) = proof.total(&ledger) {
+        say!(report, "S = sum(C) = {}", total);
+    }
+    say!(report, "reserves >= {}: {}", proof.amount, outcome(&result));
+    report.record(
+        "por",
+        json!({
+            "block": proof.block.to_string(),
+            "height": height,
+            "amount": proof.amount,
+            "outputs": proof.outputs.iter().map(|outpoint| outpoint.to_string()).collect::<Vec<_>>(),
+            "result": result_json(&result),
+        }),
+    );
+    if result.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn report_payment(report: &mut Report, payment: &Payment, strategy: Strategy, to: Option<Address>, tx_out: &str) {
+    say!(report, "{} selected {} input(s):", strategy, payment.spent.len());
+    for commitment in &payment.spent {
+        say!(report, "  C = {}", commitment);
+    }
+    say!(report, "payment output C = {}", payment.payment.commit());
+    let opening = payment.payment;
+    match to {
+        Some(address) => say!(report, "Paid to {}: its owner finds the output with `ct wallet scan`", address),
+        None => say!(report, "Give the receiver its opening: value {}, blinding {}", opening.value, opening.blinding),
+    }
+    match payment.change {
+        Some((opening, path)) => say!(report, "change {} at {}, C = {}", opening.value, path, opening.commit()),
+        None => say!(report, "no change output: the inputs cover the amount and fee exactly"),
+    }
+    say!(report, "Transaction {} written to {}.", payment.tx.txid(), tx_out);
+    report.record(
+        "send",
+        json!({
+            "strategy": strategy.to_string(),
+            "spent": payment.spent.iter().map(|c| c.as_i64()).collect::<Vec<_>>(),
+            "payment": to_json(&payment.payment),
+            "change": payment.change.map(|(opening, path)| {
+                let commitment = opening.commit().as_i64();
+                json!({ "path": path.to_string(), "value": opening.value.as_i64(), "commitment": commitment })
+            }),
+            "txid": payment.tx.txid().to_string(),
+        }),
+    );
+}
+
+fn run_rangeproof(report: &mut Report, action: RangeproofCommand) -> ExitCode {
+    match action {
+        RangeproofCommand::Create { value, blinding, interval, seed, hex } => {
+            let mut rng = seed.map_or_else(ToyRng::from_entropy, ToyRng::new);
+            let (value, blinding) = (Scalar::new(value), Blinding::new(blinding));
+            let created = match interval.bounds() {
+                None => range_proof_create(value, blinding, &mut rng).map(|proof| {
+                    let encoded = if hex { format!("{:#}", proof) } else { proof.encode() };
+                    (encoded, proof_json(&proof))
+                }),
+                Some((min, max)) => interval_proof_create(value, blinding, min, max, &mut rng).map(|proof| {
+                    let encoded = if hex { format!("{:#}", proof) } else { proof.encode() };
+                    (encoded, interval_proof_json(&proof, min, max))
+                }),
+            };
+            match created {
+                Ok((encoded, proof)) => {
+                    let commitment = pedersen_commit(value, blinding);
+                    let shown = if hex { format!("{:#}", commitment) } else { commitment.to_string() };
+                    say!(report, "C = {}", shown);
+                    say!(report, "π = {}", encoded);
+                    report.record("rangeproof_create", json!({ "commitment": commitment.as_i64(), "proof": proof }));
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("cannot create range proof: {}", e);
+                    report.record("rangeproof_create", json!({ "error": e.to_string() }));
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        RangeproofCommand::Verify { commitment, interval, proof } => {
+            let bounds = interval.bounds();
+            let result = match bounds {
+                None => proof.parse::<RangeProof>().map(|proof| range_proof_verify(commitment, &proof)),
+                Some((min, max)) => {
+                    proof.parse::<IntervalProof>().map(|proof| interval_proof_verify(commitment, min, max, &proof))
+                }
+            };
+            let result = match result {
+                Ok(result) => result,
+                Err(e) => return step_failure(report, "rangeproof_verify", e),
+            };
+            match (&result, bounds) {
+                (Ok(()), None) => say!(report, "Valid: C commits to a value in range (value not revealed)."),
+                (Ok(()), Some((min, max))) => {
+                    say!(report, "Valid: C commits to a value in [{}, {}] (value not revealed).", min, max)
+                }
+                (Err(e), _) => say!(report, "INVALID: {}", e),
+            }
+            report.record(
+                "rangeproof_verify",
+                json!({ "commitment": commitment.as_i64(), "result": result_json(&result) }),
+            );
+            if result.is_ok() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
     }
-    println!("{}", "=".repeat(60));
 }
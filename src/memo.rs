@@ -0,0 +1,63 @@
+//! Encrypted memos: an output can carry its own opening (v, r), sealed to the
+//! receiver's address, so the receiver needs nothing delivered out of band.
+//!
+//! A note (see `ecdh`) only works for outputs whose blinding comes from the shared
+//! secret. A memo works for any output: the sender picks a fresh e, attaches
+//! R = e*H and seals v ‖ r with XChaCha20-Poly1305 (see `aead`) under the key
+//! SHA-256("ct/memo" ‖ S), where S = e*A = a*R as in `ecdh`. The commitment is the
+//! associated data, so a memo moved to another output fails to open.
+//!
+//! Every memo has a key of its own, so the nonce can be all zeros. The receiver
+//! tries every memo on the chain with the scan secret a; a memo for someone else
+//! fails its tag. A memo that opens to something other than its commitment's
+//! opening is ignored too: only the commitment counts.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::aead::{self, KEY_LEN, NONCE_LEN};
+use crate::commitment::{Blinding, Commitment, Opening, Scalar};
+use crate::ecdh::{shared_secret, Address};
+use crate::kernel::public_key;
+use crate::rng::ToyRng;
+use crate::wire::Encode;
+
+/// v ‖ r, 8 bytes each, and the 16-byte tag.
+pub const MEMO_LEN: usize = 32;
+
+/// R = e*H and the sealed opening.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Memo {
+    pub ephemeral: Commitment,
+    #[serde(with = "crate::wire::hex")]
+    pub ciphertext: [u8; MEMO_LEN],
+}
+
+fn key(shared: Commitment) -> [u8; KEY_LEN] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"ct/memo");
+    hasher.update(shared.as_i64().to_le_bytes());
+    hasher.finalize().into()
+}
+
+impl Memo {
+    /// Seal `opening` to `address` for the output it opens.
+    pub fn seal(address: Address, opening: &Opening, rng: &mut ToyRng) -> Self {
+        let ephemeral = Blinding::random(rng);
+        let plaintext = [opening.value.as_i64().to_le_bytes(), opening.blinding.as_i64().to_le_bytes()].concat();
+        let key = key(shared_secret(ephemeral, address.scan));
+        let sealed = aead::seal(&key, &[0; NONCE_LEN], &opening.commit().to_bytes(), &plaintext);
+        Memo { ephemeral: public_key(ephemeral), ciphertext: sealed.try_into().expect("16 bytes and a tag") }
+    }
+
+    /// The opening of `commitment` if this memo was sealed to the address with scan
+    /// secret `scan_secret`; None for anyone else's memo, or one that does not open it.
+    pub fn open(&self, scan_secret: Blinding, commitment: Commitment) -> Option<Opening> {
+        let key = key(shared_secret(scan_secret, self.ephemeral));
+        let plaintext = aead::open(&key, &[0; NONCE_LEN], &commitment.to_bytes(), &self.ciphertext).ok()?;
+        let (value, blinding) = plaintext.split_at(8);
+        let value = i64::from_le_bytes(value.try_into().ok()?);
+        let opening = Opening::new(Scalar::new(value), Blinding::new(i64::from_le_bytes(blinding.try_into().ok()?)));
+        (value >= 0 && opening.commit() == commitment).then_some(opening)
+    }
+}
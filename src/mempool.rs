@@ -0,0 +1,75 @@
+//! Mempool: transactions waiting to be mined. A transaction gets in only if it
+//! verifies, spends unspent outputs of the ledger, and spends nothing another
+//! pending transaction already spends; the first one seen wins. Pending
+//! transactions cannot spend each other's outputs.
+
+use crate::block::Block;
+use crate::commitment::Commitment;
+use crate::error::CtError;
+use crate::ledger::Ledger;
+use crate::transaction::{Transaction, TxId};
+
+#[derive(Clone, Debug, Default)]
+pub struct Mempool {
+    transactions: Vec<Transaction>,
+}
+
+impl Mempool {
+    pub fn new() -> Self {
+        Mempool::default()
+    }
+
+    /// Pending transactions in the order they arrived.
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Accept `tx` if it verifies, its inputs are unspent in `ledger`, and no pending
+    /// transaction spends any of them (`Conflict`). Coinbases are for miners only.
+    pub fn add(&mut self, tx: Transaction, ledger: &Ledger) -> Result<TxId, CtError> {
+        if tx.is_coinbase() {
+            return Err(CtError::InvalidCoinbase("a coinbase cannot be relayed".to_string()));
+        }
+        tx.verify()?;
+        ledger.check_inputs(&tx)?;
+        let pending: Vec<Commitment> = self.transactions.iter().flat_map(|t| t.input_commitments()).collect();
+        if let Some(&input) = tx.input_commitments().iter().find(|c| pending.contains(c)) {
+            return Err(CtError::Conflict(input));
+        }
+        let txid = tx.txid();
+        self.transactions.push(tx);
+        Ok(txid)
+    }
+
+    /// A block on top of the ledger's tip: the miner's `coinbase` (if any), then the
+    /// pending transactions, highest fee first.
+    pub fn block_template(&self, ledger: &Ledger, coinbase: Option<Transaction>) -> Block {
+        let mut transactions: Vec<Transaction> = self.transactions.clone();
+        transactions.sort_by_key(|tx| std::cmp::Reverse(tx.fee));
+        Block::new(ledger, coinbase.into_iter().chain(transactions).collect())
+    }
+
+    /// Put back the transactions of blocks a reorg disconnected, where they still
+    /// apply on top of `ledger`. Coinbases and transactions the new branch spent
+    /// against are dropped. Returns how many came back.
+    pub fn readd(&mut self, disconnected: &[Block], ledger: &Ledger) -> usize {
+        let before = self.len();
+        for tx in disconnected.iter().flat_map(|block| &block.transactions) {
+            let _ = self.add(tx.clone(), ledger);
+        }
+        self.len() - before
+    }
+
+    /// Drop transactions that can no longer be mined, e.g. because a block spent their inputs.
+    pub fn prune(&mut self, ledger: &Ledger) {
+        self.transactions.retain(|tx| ledger.check_inputs(tx).is_ok());
+    }
+}
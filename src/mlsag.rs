@@ -0,0 +1,141 @@
+//! MLSAG: a linkable ring signature over several keys per ring member (Monero's
+//! RingCT signature before CLSAG).
+//!
+//! The ring is n members of m keys each, row k over base B_k. The signer knows x_k
+//! with P_{π,k} = x_k*B_k for every row of one member π. The first row is linkable:
+//! it also carries the key image I = x_0*Hp(P_{π,0}).
+//!
+//! Signing closes a loop of challenges around the ring. At π the signer commits to
+//! nonces a_k: L_k = a_k*B_k and R = a_0*Hp(P_{π,0}), and c_{π+1} = hash(L, R).
+//! Every other member i gets random responses s_{i,k}, and
+//!     L_k = s_{i,k}*B_k + c_i*P_{i,k},  R = s_{i,0}*Hp(P_{i,0}) + c_i*I
+//! give c_{i+1}. Back at π the signer answers s_{π,k} = a_k - c_π*x_k, which makes
+//! π's equations give the same L and R as the nonces did. The verifier walks the
+//! loop from c_0 and accepts if it comes back to c_0. Only c_0 and the responses
+//! are sent: n*m + 1 numbers. A `sigma::OneOf` proving the same thing also sends
+//! every member's announcements and sub-challenge.
+//!
+//! Linking: two signatures by the same key have the same I (`link`), whatever their
+//! rings. CLSAG compresses the rows into one by a random linear combination, but it
+//! needs every row over the same base; this crate blinds with H and owns keys with G.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{mod_mul, mod_reduce};
+use crate::generators::hash_to_field;
+use crate::rng::ToyRng;
+use crate::transcript::Transcript;
+
+/// I = x*Hp(P): the same for every signature by x, whatever the ring.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct KeyImage(pub i64);
+
+impl fmt::Display for KeyImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// Hp(P): the base a key image is taken over, hashed from the key so nobody knows
+/// its discrete log relative to G.
+pub fn key_base(key: i64) -> i64 {
+    hash_to_field(&[b"ct/key image".as_slice(), &key.to_le_bytes()].concat())
+}
+
+/// The key image of `secret`, whose public key is `key`.
+pub fn key_image(secret: i64, key: i64) -> KeyImage {
+    KeyImage(mod_mul(secret, key_base(key)))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MlsagSignature {
+    pub key_image: KeyImage,
+    /// c_0, where the verifier starts the loop.
+    pub challenge: i64,
+    /// s_{i,k}, one row per member.
+    pub responses: Vec<Vec<i64>>,
+}
+
+/// s*B + c*P.
+fn combine(s: i64, base: i64, c: i64, public: i64) -> i64 {
+    mod_reduce(mod_mul(s, base) as i128 + mod_mul(c, public) as i128)
+}
+
+/// The transcript every challenge in the loop starts from: the ring, its bases, the
+/// key image and the message.
+fn base_transcript(ring: &[Vec<i64>], bases: &[i64], key_image: KeyImage, message: &[i64]) -> Transcript {
+    let mut transcript = Transcript::new("ct/mlsag");
+    transcript.append_all("bases", bases);
+    transcript.append_all("ring", &ring.concat());
+    transcript.append("key image", key_image.0);
+    transcript.append_all("message", message);
+    transcript
+}
+
+/// c = hash(..., L_0..L_m, R).
+fn next_challenge(transcript: &Transcript, left: &[i64], right: i64) -> i64 {
+    let mut transcript = transcript.clone();
+    transcript.append_all("L", left);
+    transcript.append("R", right);
+    transcript.challenge("c")
+}
+
+/// (L, R) for one member's keys under responses `s` and challenge `c`.
+fn member_commitments(keys: &[i64], bases: &[i64], s: &[i64], c: i64, key_image: KeyImage) -> (Vec<i64>, i64) {
+    let left = bases.iter().zip(keys).zip(s).map(|((&base, &key), &s)| combine(s, base, c, key)).collect();
+    (left, combine(s[0], key_base(keys[0]), c, key_image.0))
+}
+
+/// Sign `message` as member `real` of `ring`, knowing `secrets[k]` with
+/// ring[real][k] = secrets[k]*bases[k].
+pub fn prove(
+    ring: &[Vec<i64>],
+    bases: &[i64],
+    real: usize,
+    secrets: &[i64],
+    message: &[i64],
+    rng: &mut ToyRng,
+) -> MlsagSignature {
+    let n = ring.len();
+    let key_image = key_image(secrets[0], ring[real][0]);
+    let transcript = base_transcript(ring, bases, key_image, message);
+    let nonces: Vec<i64> = bases.iter().map(|_| rng.scalar()).collect();
+    let left: Vec<i64> = nonces.iter().zip(bases).map(|(&a, &base)| mod_mul(a, base)).collect();
+    let mut challenges = vec![0; n];
+    let mut responses = vec![Vec::new(); n];
+    challenges[(real + 1) % n] = next_challenge(&transcript, &left, mod_mul(nonces[0], key_base(ring[real][0])));
+    for i in (1..n).map(|step| (real + step) % n) {
+        responses[i] = bases.iter().map(|_| rng.scalar()).collect();
+        let (left, right) = member_commitments(&ring[i], bases, &responses[i], challenges[i], key_image);
+        challenges[(i + 1) % n] = next_challenge(&transcript, &left, right);
+    }
+    responses[real] = nonces
+        .iter()
+        .zip(secrets)
+        .map(|(&a, &x)| mod_reduce(a as i128 - mod_mul(challenges[real], x) as i128))
+        .collect();
+    MlsagSignature { key_image, challenge: challenges[0], responses }
+}
+
+/// Walk the loop from c_0 and check it closes.
+pub fn verify(ring: &[Vec<i64>], bases: &[i64], message: &[i64], signature: &MlsagSignature) -> bool {
+    let well_formed = !ring.is_empty()
+        && signature.responses.len() == ring.len()
+        && ring.iter().zip(&signature.responses).all(|(keys, s)| keys.len() == bases.len() && s.len() == bases.len());
+    if !well_formed || bases.is_empty() {
+        return false;
+    }
+    let transcript = base_transcript(ring, bases, signature.key_image, message);
+    let end = ring.iter().zip(&signature.responses).fold(signature.challenge, |c, (keys, s)| {
+        let (left, right) = member_commitments(keys, bases, s, c, signature.key_image);
+        next_challenge(&transcript, &left, right)
+    });
+    end == signature.challenge
+}
+
+/// Were both signatures made with the same key? They were if their key images match.
+pub fn link(first: &MlsagSignature, second: &MlsagSignature) -> bool {
+    first.key_image == second.key_image
+}
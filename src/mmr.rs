@@ -0,0 +1,188 @@
+//! Merkle Mountain Range over output commitments, as Mimblewimble nodes keep it.
+//! Leaves are only ever appended, and nodes are stored in post-order: every
+//! parent comes right after its right child. The tree is a row of perfect
+//! binary trees ("peaks") of decreasing height:
+//!
+//! ```text
+//!        6
+//!      /   \
+//!     2     5      9
+//!    / \   / \    / \
+//!   0   1 3   4  7   8  10
+//! ```
+//!
+//! The root bags the peaks together with the node count, so a block header can
+//! commit to every output ever created in 32 bytes. An inclusion proof is the
+//! path from a leaf up to its peak plus the other peaks.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+
+use crate::commitment::Commitment;
+use crate::wire::Encode;
+
+/// Hash of an output commitment as stored in the leaves.
+pub fn leaf_hash(commitment: Commitment) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0]);
+    hasher.update(commitment.to_bytes());
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([1]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Bag the peaks right to left, and mix in the node count.
+fn bag(size: u64, peaks: &[[u8; 32]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([2]);
+    hasher.update(size.to_le_bytes());
+    if let Some((last, rest)) = peaks.split_last() {
+        hasher.update(rest.iter().rev().fold(*last, |bagged, peak| node_hash(peak, &bagged)));
+    }
+    hasher.finalize().into()
+}
+
+/// Number of nodes in a perfect tree of this height.
+fn tree_size(height: u32) -> usize {
+    (2 << height) - 1
+}
+
+/// Proof that a leaf is in the MMR with a given root.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MmrProof {
+    /// Node count of the MMR the proof was made for.
+    pub size: u64,
+    /// Siblings from the leaf up to its peak, each flagged true when it is on the left.
+    #[serde(serialize_with = "path_hex", deserialize_with = "parse_path_hex")]
+    pub path: Vec<([u8; 32], bool)>,
+    /// All peaks, left to right.
+    #[serde(with = "crate::wire::hex_list")]
+    pub peaks: Vec<[u8; 32]>,
+    /// Which peak the path leads to.
+    pub peak: usize,
+}
+
+/// One step of a path as written: `{ "hash": "<hex>", "left": false }`.
+#[derive(Serialize, Deserialize)]
+struct Step {
+    #[serde(with = "crate::wire::hex")]
+    hash: [u8; 32],
+    left: bool,
+}
+
+fn path_hex<S: Serializer>(path: &[([u8; 32], bool)], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(path.iter().map(|&(hash, left)| Step { hash, left }))
+}
+
+fn parse_path_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<([u8; 32], bool)>, D::Error> {
+    Ok(Vec::<Step>::deserialize(deserializer)?.into_iter().map(|step| (step.hash, step.left)).collect())
+}
+
+impl MmrProof {
+    /// Does this proof show that `commitment` is a leaf of the MMR with `root`?
+    pub fn verify(&self, root: &[u8; 32], commitment: Commitment) -> bool {
+        let top = self.path.iter().fold(leaf_hash(commitment), |node, (sibling, left)| {
+            if *left {
+                node_hash(sibling, &node)
+            } else {
+                node_hash(&node, sibling)
+            }
+        });
+        self.peaks.get(self.peak) == Some(&top) && bag(self.size, &self.peaks) == *root
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Mmr {
+    /// (height, hash) for every node, in post-order.
+    nodes: Vec<(u32, [u8; 32])>,
+}
+
+impl Mmr {
+    pub fn new() -> Self {
+        Mmr::default()
+    }
+
+    /// Node count, leaves and parents together.
+    pub fn size(&self) -> u64 {
+        self.nodes.len() as u64
+    }
+
+    pub fn leaves(&self) -> usize {
+        self.nodes.iter().filter(|(height, _)| *height == 0).count()
+    }
+
+    /// Add a leaf, then merge equal-height peaks until none are left.
+    pub fn append(&mut self, commitment: Commitment) {
+        self.nodes.push((0, leaf_hash(commitment)));
+        loop {
+            let right = self.nodes.len() - 1;
+            let (height, right_hash) = self.nodes[right];
+            let Some(left) = right.checked_sub(tree_size(height)) else { break };
+            let (left_height, left_hash) = self.nodes[left];
+            if left_height != height {
+                break;
+            }
+            self.nodes.push((height + 1, node_hash(&left_hash, &right_hash)));
+        }
+    }
+
+    /// Positions of the peaks, left to right.
+    fn peak_positions(&self) -> Vec<usize> {
+        let mut peaks = Vec::new();
+        let mut end = self.nodes.len();
+        while end > 0 {
+            let peak = end - 1;
+            peaks.push(peak);
+            end -= tree_size(self.nodes[peak].0);
+        }
+        peaks.reverse();
+        peaks
+    }
+
+    /// Commits to every leaf appended so far; all zeros when empty.
+    pub fn root(&self) -> [u8; 32] {
+        if self.nodes.is_empty() {
+            return [0; 32];
+        }
+        let peaks: Vec<_> = self.peak_positions().into_iter().map(|p| self.nodes[p].1).collect();
+        bag(self.size(), &peaks)
+    }
+
+    /// An inclusion proof for the first leaf holding `commitment`, if any.
+    pub fn prove(&self, commitment: Commitment) -> Option<MmrProof> {
+        let leaf = leaf_hash(commitment);
+        let mut pos = self.nodes.iter().position(|&node| node == (0, leaf))?;
+        let mut path = Vec::new();
+        loop {
+            let height = self.nodes[pos].0;
+            let parent = if self.nodes.get(pos + 1).is_some_and(|&(h, _)| h == height + 1) {
+                // Right child: the parent follows immediately, the sibling's tree precedes ours.
+                path.push((self.nodes[pos - tree_size(height)].1, true));
+                pos + 1
+            } else {
+                // Left child if a parent exists after the sibling's tree; otherwise a peak.
+                let parent = pos + tree_size(height) + 1;
+                if self.nodes.get(parent).is_none_or(|&(h, _)| h != height + 1) {
+                    break;
+                }
+                path.push((self.nodes[parent - 1].1, false));
+                parent
+            };
+            pos = parent;
+        }
+        let positions = self.peak_positions();
+        Some(MmrProof {
+            size: self.size(),
+            path,
+            peaks: positions.iter().map(|&p| self.nodes[p].1).collect(),
+            peak: positions.iter().position(|&p| p == pos)?,
+        })
+    }
+}
@@ -0,0 +1,114 @@
+//! BIP39 mnemonics: the wallet seed written as 12 or 24 English words.
+//!
+//! The entropy (16 or 32 bytes) is followed by the first bits of its SHA-256 as a
+//! checksum and cut into 11-bit numbers, each an index into the 2048-word list
+//! (`bip39_english.txt`), so a mistyped word is usually caught. The seed is
+//! PBKDF2-HMAC-SHA512 over the words with salt "mnemonic" + passphrase (2048
+//! rounds), exactly as BIP39 wallets compute it; the keychain uses its first 32 bytes.
+
+use std::fmt;
+use std::str::FromStr;
+
+use sha2::{Digest, Sha256};
+
+use crate::error::CtError;
+use crate::kdf::pbkdf2_sha512;
+use crate::keychain::Seed;
+use crate::rng::os_random;
+
+const WORDLIST: &str = include_str!("bip39_english.txt");
+
+fn wordlist() -> Vec<&'static str> {
+    WORDLIST.lines().collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mnemonic {
+    words: Vec<&'static str>,
+}
+
+impl Mnemonic {
+    /// The words for `entropy`: 16, 20, 24, 28 or 32 bytes give 12 to 24 words.
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, CtError> {
+        if !(16..=32).contains(&entropy.len()) || !entropy.len().is_multiple_of(4) {
+            return Err(CtError::MalformedInput(format!("BIP39 entropy is 16 to 32 bytes, not {}", entropy.len())));
+        }
+        let checksum = Sha256::digest(entropy);
+        let bits: Vec<bool> = entropy
+            .iter()
+            .chain(&checksum)
+            .flat_map(|byte| (0..8).rev().map(move |i| byte >> i & 1 == 1))
+            .take(entropy.len() * 8 + entropy.len() / 4)
+            .collect();
+        let list = wordlist();
+        let words = bits
+            .chunks(11)
+            .map(|chunk| list[chunk.iter().fold(0, |index, &bit| index << 1 | usize::from(bit))])
+            .collect();
+        Ok(Mnemonic { words })
+    }
+
+    /// A new mnemonic of 12 or 24 words, with entropy from the operating system. The
+    /// words are a real BIP39 wallet's, so they do not come from `ToyRng`, whose clock
+    /// seed could be guessed.
+    pub fn generate(words: usize) -> Result<Self, CtError> {
+        if words != 12 && words != 24 {
+            return Err(CtError::MalformedInput(format!("a mnemonic has 12 or 24 words, not {}", words)));
+        }
+        let mut entropy = vec![0; words / 6 * 8];
+        os_random(&mut entropy)?;
+        Mnemonic::from_entropy(&entropy)
+    }
+
+    pub fn words(&self) -> &[&'static str] {
+        &self.words
+    }
+
+    /// The 64-byte BIP39 seed.
+    pub fn to_seed(&self, passphrase: &str) -> [u8; 64] {
+        pbkdf2_sha512(self.to_string().as_bytes(), format!("mnemonic{}", passphrase).as_bytes(), 2048)
+    }
+
+    /// The keychain seed: the first 32 bytes of the BIP39 seed.
+    pub fn keychain_seed(&self, passphrase: &str) -> Seed {
+        Seed(self.to_seed(passphrase)[..32].try_into().expect("the BIP39 seed has 64 bytes"))
+    }
+}
+
+impl fmt::Display for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.words.join(" "))
+    }
+}
+
+/// Parse the words back and check the checksum.
+impl FromStr for Mnemonic {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let list = wordlist();
+        let indices = s
+            .split_whitespace()
+            .map(|word| {
+                list.binary_search(&word.to_lowercase().as_str())
+                    .map_err(|_| CtError::MalformedInput(format!("'{}' is not a BIP39 word", word)))
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+        if ![12, 15, 18, 21, 24].contains(&indices.len()) {
+            return Err(CtError::MalformedInput(format!("a mnemonic has 12 to 24 words, not {}", indices.len())));
+        }
+        let bits: Vec<bool> =
+            indices.iter().flat_map(|index| (0..11).rev().map(move |i| index >> i & 1 == 1)).collect();
+        let entropy: Vec<u8> = bits[..bits.len() / 33 * 32]
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0, |acc, &bit| acc << 1 | u8::from(bit)))
+            .collect();
+        let mnemonic = Mnemonic::from_entropy(&entropy)?;
+        if mnemonic.words.len() != indices.len() || mnemonic.words.iter().zip(&indices).any(|(w, &i)| *w != list[i]) {
+            return Err(CtError::MalformedInput("the mnemonic's checksum does not match (a word is wrong)".to_string()));
+        }
+        Ok(mnemonic)
+    }
+}
+
+serde_as_text!(Mnemonic);
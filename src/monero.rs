@@ -0,0 +1,224 @@
+//! Monero's amount commitments, on its curve and with its generators, so a commitment
+//! copied off Monero's chain can be decoded and opened here (`ct commit --backend
+//! monero`, `ct inspect monero`).
+//!
+//! Monero commits to an amount (in piconero, 10^-12 XMR) with a mask as
+//!     C = mask*G + amount*H
+//! on Ed25519, the twisted Edwards curve -x^2 + y^2 = 1 + d*x^2*y^2 over 2^255 - 19:
+//! G is Ed25519's base point and H = 8 * hash_to_point(Keccak-256(G)), so nobody
+//! knows log_G(H). H is written out below rather than derived, as there is no Keccak
+//! here. Note the roles: the demo's C = v*G + r*H puts the value on G.
+//!
+//! A point is 32 bytes: y little-endian, with x's lowest bit in the top bit. Masks are
+//! scalars mod the group order l, also 32 bytes little-endian. Coinbase outputs (and
+//! amounts in the clear) use the mask 1: `zero_commit(amount)` = G + amount*H.
+//!
+//! Arithmetic is affine with num-bigint, like `secp256k1`: slow and not constant-time.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use num_bigint::BigUint;
+
+use crate::error::CtError;
+use crate::fixed_base::{FixedBase, Group};
+use crate::wire::{from_hex, to_hex};
+
+/// Ed25519's base point.
+const G: &str = "5866666666666666666666666666666666666666666666666666666666666666";
+/// Monero's H (`rct::H` in Monero's source).
+const H: &str = "8b655970153799af2aeadc9ff1add0ea6c7251d54154cfa92c173a0dd39c1f94";
+/// The order l of the prime-order subgroup.
+const L: &str = "1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed";
+
+/// 1 XMR in piconero.
+pub const PICONERO: u64 = 1_000_000_000_000;
+
+fn p() -> BigUint {
+    (BigUint::from(1u8) << 255u32) - 19u8
+}
+
+pub fn order() -> BigUint {
+    BigUint::parse_bytes(L.as_bytes(), 16).expect("l is hex")
+}
+
+fn sub(a: &BigUint, b: &BigUint) -> BigUint {
+    let p = p();
+    (a + &p - b % &p) % &p
+}
+
+fn inverse(a: &BigUint) -> BigUint {
+    let p = p();
+    a.modpow(&(&p - 2u8), &p)
+}
+
+/// d = -121665 / 121666.
+fn d() -> BigUint {
+    sub(&BigUint::ZERO, &(BigUint::from(121665u32) * inverse(&BigUint::from(121666u32))))
+}
+
+/// A point in affine coordinates; the identity is (0, 1).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Point {
+    x: BigUint,
+    y: BigUint,
+}
+
+pub fn g() -> Point {
+    Point::from_hex(G).expect("G decodes")
+}
+
+pub fn h() -> Point {
+    Point::from_hex(H).expect("H decodes")
+}
+
+/// G's and H's fixed-base tables, once `precompute` has built them.
+static TABLES: OnceLock<[FixedBase<Point>; 2]> = OnceLock::new();
+
+/// Build G's and H's tables, about a thousand additions each.
+pub fn precompute() {
+    TABLES.get_or_init(|| [FixedBase::new(&g(), 253), FixedBase::new(&h(), 253)]);
+}
+
+/// k*G and k*H, from the tables if they are built. Both lie in the subgroup of
+/// order l, so k may be reduced mod l first.
+fn mul_g(k: &BigUint) -> Point {
+    match TABLES.get() {
+        Some([g, _]) => g.mul(&(k % order()).to_bytes_le()),
+        None => g().mul(k),
+    }
+}
+
+fn mul_h(k: &BigUint) -> Point {
+    match TABLES.get() {
+        Some([_, h]) => h.mul(&(k % order()).to_bytes_le()),
+        None => h().mul(k),
+    }
+}
+
+/// mask*G + amount*H.
+pub fn commit(amount: &BigUint, mask: &BigUint) -> Point {
+    mul_g(mask).add(&mul_h(amount))
+}
+
+/// The commitment with mask 1, for coinbase outputs and amounts in the clear.
+pub fn zero_commit(amount: u64) -> Point {
+    g().add(&mul_h(&BigUint::from(amount)))
+}
+
+/// A small signed number as a scalar mod l, so -1 is l - 1.
+pub fn scalar(value: i64) -> BigUint {
+    let magnitude = BigUint::from(value.unsigned_abs()) % order();
+    if value < 0 { (order() - magnitude) % order() } else { magnitude }
+}
+
+/// A 32-byte little-endian scalar, as Monero writes masks, reduced mod l.
+pub fn scalar_from_hex(hex: &str) -> Result<BigUint, CtError> {
+    let bytes = from_hex(hex)?;
+    if bytes.len() != 32 {
+        return Err(CtError::MalformedInput(format!("a scalar has 32 bytes, not {}", bytes.len())));
+    }
+    Ok(BigUint::from_bytes_le(&bytes) % order())
+}
+
+impl Point {
+    pub fn identity() -> Point {
+        Point { x: BigUint::ZERO, y: BigUint::from(1u8) }
+    }
+
+    /// Decode 32 bytes: y, and the sign of x in the top bit.
+    pub fn decode(bytes: &[u8; 32]) -> Result<Point, CtError> {
+        let invalid = || CtError::MalformedInput(format!("{} is not a point on Ed25519", to_hex(bytes)));
+        let p = p();
+        let odd = bytes[31] & 0x80 != 0;
+        let mut y = *bytes;
+        y[31] &= 0x7f;
+        let y = BigUint::from_bytes_le(&y);
+        if y >= p {
+            return Err(invalid());
+        }
+        // x^2 = (y^2 - 1) / (d*y^2 + 1); p = 5 mod 8, so a root is (x^2)^((p+3)/8),
+        // times sqrt(-1) if that squares to -x^2.
+        let yy = &y * &y % &p;
+        let xx = sub(&yy, &BigUint::from(1u8)) * inverse(&((d() * &yy + 1u8) % &p)) % &p;
+        let mut x = xx.modpow(&((&p + 3u8) >> 3u32), &p);
+        if &x * &x % &p != xx {
+            let sqrt_minus_one = BigUint::from(2u8).modpow(&((&p - 1u8) >> 2u32), &p);
+            x = x * sqrt_minus_one % &p;
+        }
+        if &x * &x % &p != xx || (x == BigUint::ZERO && odd) {
+            return Err(invalid());
+        }
+        if x.bit(0) != odd {
+            x = sub(&BigUint::ZERO, &x);
+        }
+        Ok(Point { x, y })
+    }
+
+    pub fn from_hex(hex: &str) -> Result<Point, CtError> {
+        let bytes: [u8; 32] = from_hex(hex)?
+            .try_into()
+            .map_err(|b: Vec<u8>| CtError::MalformedInput(format!("a point has 32 bytes, not {}", b.len())))?;
+        Point::decode(&bytes)
+    }
+
+    pub fn encode(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        let y = self.y.to_bytes_le();
+        bytes[..y.len()].copy_from_slice(&y);
+        if self.x.bit(0) {
+            bytes[31] |= 0x80;
+        }
+        bytes
+    }
+
+    /// The Edwards addition law, which has no special cases.
+    pub fn add(&self, other: &Point) -> Point {
+        let p = p();
+        let xx = &self.x * &other.x % &p;
+        let yy = &self.y * &other.y % &p;
+        let dxy = d() * &xx % &p * &yy % &p;
+        let x = (&self.x * &other.y + &self.y * &other.x) % &p * inverse(&((BigUint::from(1u8) + &dxy) % &p)) % &p;
+        let y = (yy + xx) % &p * inverse(&sub(&BigUint::from(1u8), &dxy)) % &p;
+        Point { x, y }
+    }
+
+    pub fn neg(&self) -> Point {
+        Point { x: sub(&BigUint::ZERO, &self.x), y: self.y.clone() }
+    }
+
+    /// k * self, by double-and-add (k is not reduced: l*P shows if P has order l).
+    pub fn mul(&self, k: &BigUint) -> Point {
+        let mut result = Point::identity();
+        for i in (0..k.bits()).rev() {
+            result = result.add(&result);
+            if k.bit(i) {
+                result = result.add(self);
+            }
+        }
+        result
+    }
+
+    /// Is the point in the subgroup of order l, where Monero's commitments live? The
+    /// curve has 8*l points, and a point outside the subgroup hides a small-order part.
+    pub fn in_subgroup(&self) -> bool {
+        self.mul(&order()) == Point::identity()
+    }
+}
+
+impl Group for Point {
+    fn identity() -> Self {
+        Point::identity()
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Point::add(self, other)
+    }
+}
+
+/// Written as its 32-byte encoding, in hex.
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&to_hex(&self.encode()))
+    }
+}
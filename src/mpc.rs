@@ -0,0 +1,152 @@
+//! One aggregated range proof from several parties, each holding one output's
+//! opening, that never show each other their amounts. Dealer-based, like the
+//! Bulletproofs MPC: the dealer only relays, draws the challenge and assembles the
+//! proof, and learns nothing a verifier would not.
+//!
+//! The `AggregateRangeProof` statement is an `All` over every bit's OR-proof under
+//! one challenge, and each party's bits only involve its own secrets, so the
+//! protocol splits the prover along party lines:
+//!
+//! 1. `Party::new`: each party splits its value into bit commitments C_i and sends
+//!    them with its C and its part of the announcement (`BitCommitments`).
+//! 2. `Dealer::new`: the dealer checks each party's C_i recompose to its C, absorbs
+//!    every C, C_i and announcement into the aggregate transcript, and draws e.
+//! 3. `Party::respond`: each party answers e for its own bits (`ProofShare`).
+//! 4. `Dealer::finish`: the dealer checks every share against that party's part of
+//!    the announcement, so a bad share is blamed on its sender, and puts together
+//!    an ordinary `AggregateRangeProof`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{lin_comb, Commitment, Opening};
+use crate::error::CtError;
+use crate::params;
+use crate::range_proof::{
+    aggregate_statement, bit_openings, bit_statement_or, check_value, recompose, AggregateRangeProof, CompactBitProof,
+};
+use crate::rng::ToyRng;
+use crate::sigma::{All, Either, Or, Representation, Sigma};
+
+type BitStatements = All<Or<Representation, Representation>>;
+
+/// What a party sends the dealer in round 1.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BitCommitments {
+    pub commitment: Commitment,
+    /// C_0, ..., C_{n-1}.
+    pub bits: Vec<Commitment>,
+    /// The OR-proof announcements for those bits, two per bit.
+    pub announcement: Vec<i64>,
+}
+
+/// A party's answer to the dealer's challenge: (e0, e1, s0, s1) for each of its bits.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofShare {
+    pub response: Vec<i64>,
+}
+
+/// One party's secrets between the rounds.
+pub struct Party {
+    statement: BitStatements,
+    witness: Vec<Either<Vec<i64>, Vec<i64>>>,
+    state: <BitStatements as Sigma>::State,
+}
+
+impl Party {
+    /// Round 1. An honest party refuses a value outside [0, 2^n).
+    pub fn new(opening: Opening, rng: &mut ToyRng) -> Result<(Party, BitCommitments), CtError> {
+        check_value(opening.value)?;
+        let bits = bit_openings(opening.value, opening.blinding, rng);
+        let bit_commitments: Vec<Commitment> =
+            bits.iter().map(|&(bit, blinding)| Commitment::from_i64(lin_comb(bit, blinding))).collect();
+        let witness = bits
+            .iter()
+            .map(|&(bit, blinding)| if bit == 0 { Either::Left(vec![blinding]) } else { Either::Right(vec![blinding]) })
+            .collect();
+        let statement = All(bit_commitments.iter().map(|&c| bit_statement_or(c)).collect());
+        let (state, announcement) = statement.commit(&witness, rng);
+        let message = BitCommitments { commitment: opening.commit(), bits: bit_commitments, announcement };
+        Ok((Party { statement, witness, state }, message))
+    }
+
+    /// Round 2. Takes the party, since answering two challenges for the same
+    /// announcement would give its bits away.
+    pub fn respond(self, challenge: i64) -> ProofShare {
+        ProofShare { response: self.statement.respond(&self.witness, self.state, challenge) }
+    }
+}
+
+/// Collects the parties' messages and assembles the proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dealer {
+    parties: Vec<BitCommitments>,
+    challenge: i64,
+}
+
+impl Dealer {
+    /// Check every party's round-1 message and draw the challenge for all of them.
+    pub fn new(parties: Vec<BitCommitments>) -> Result<Dealer, CtError> {
+        let num_bits = params::active().range_bits as usize;
+        for (i, party) in parties.iter().enumerate() {
+            if party.bits.len() != num_bits || party.announcement.len() != 2 * num_bits {
+                return Err(CtError::MalformedInput(format!(
+                    "party {} sent {} bit commitments and {} announcements, expected {} and {}",
+                    i + 1,
+                    party.bits.len(),
+                    party.announcement.len(),
+                    num_bits,
+                    2 * num_bits
+                )));
+            }
+            if recompose(party.bits.iter().copied()) != party.commitment {
+                return Err(CtError::InvalidProof(format!("party {}'s bit commitments do not recompose to C", i + 1)));
+            }
+        }
+        let commitments: Vec<Commitment> = parties.iter().map(|party| party.commitment).collect();
+        let bits: Vec<Commitment> = parties.iter().flat_map(|party| party.bits.iter().copied()).collect();
+        // The same transcript `aggregate_range_proof_create` runs, with the announcement in pieces.
+        let (_, mut transcript) = aggregate_statement(&commitments, &bits);
+        let announcement: Vec<i64> = parties.iter().flat_map(|party| party.announcement.iter().copied()).collect();
+        transcript.append_all("announcement", &announcement);
+        let challenge = transcript.challenge("e");
+        Ok(Dealer { parties, challenge })
+    }
+
+    /// e, sent to every party.
+    pub fn challenge(&self) -> i64 {
+        self.challenge
+    }
+
+    /// The commitments the proof covers, in party order.
+    pub fn commitments(&self) -> Vec<Commitment> {
+        self.parties.iter().map(|party| party.commitment).collect()
+    }
+
+    /// Check each party's share and assemble the proof. `shares` are in party order.
+    pub fn finish(&self, shares: &[ProofShare]) -> Result<AggregateRangeProof, CtError> {
+        if shares.len() != self.parties.len() {
+            return Err(CtError::MalformedInput(format!(
+                "{} proof shares for {} parties",
+                shares.len(),
+                self.parties.len()
+            )));
+        }
+        let mut bits = Vec::new();
+        for (i, (party, share)) in self.parties.iter().zip(shares).enumerate() {
+            let statement = All(party.bits.iter().map(|&c| bit_statement_or(c)).collect());
+            if share.response.len() != statement.response_len()
+                || !statement.check(&party.announcement, self.challenge, &share.response)
+            {
+                return Err(CtError::InvalidProof(format!("party {}'s proof share fails", i + 1)));
+            }
+            bits.extend(
+                party
+                    .bits
+                    .iter()
+                    .zip(share.response.chunks(4))
+                    .map(|(&commitment, r)| CompactBitProof { commitment, e0: r[0], e1: r[1], s0: r[2], s1: r[3] }),
+            );
+        }
+        Ok(AggregateRangeProof { challenge: self.challenge, bits })
+    }
+}
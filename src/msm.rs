@@ -0,0 +1,89 @@
+//! Multi-scalar multiplication: sum(k_i * P_i) over many points at once, which is
+//! all a batch check (`batch`) has left to compute.
+//!
+//! Pippenger's bucket method cuts every k_i into c-bit digits. For one digit
+//! position each P_i goes into the bucket of its digit, and the weighted sum of the
+//! buckets comes from a running sum taken from the top bucket down,
+//!     sum(d * B_d) = B_top + (B_top + B_top-1) + ... + (B_top + ... + B_1),
+//! two additions per bucket and no multiplication. Positions are joined by doubling
+//! c times between them, as in double-and-add. For n points and b-bit scalars that
+//! is about b/c * (n + 2^(c+1)) additions, where n double-and-adds need about
+//! 1.5*b*n; c grows with log n, so the saving does too.
+
+use crate::fixed_base::Group;
+
+/// Digit width for `terms` points: wider buckets pay off only over more points.
+fn window(terms: usize) -> usize {
+    if terms < 32 { 3 } else { terms.ilog2() as usize * 69 / 100 + 2 }
+}
+
+/// Add `term` to `sum`, counting the addition unless `sum` was still empty.
+fn accumulate<P: Group>(sum: &mut Option<P>, term: &P, additions: &mut usize) {
+    *sum = Some(match sum.take() {
+        Some(sum) => {
+            *additions += 1;
+            sum.add(term)
+        }
+        None => term.clone(),
+    });
+}
+
+/// The `width` bits of little-endian `k` from bit `at` on.
+fn digit(k: &[u8], at: usize, width: usize) -> usize {
+    let bit = |i: usize| k.get(i / 8).map_or(0, |byte| (byte >> (i % 8) & 1) as usize);
+    (0..width).fold(0, |digit, i| digit | bit(at + i) << i)
+}
+
+/// sum(k_i * P_i) by Pippenger's method, with each k_i as little-endian bytes, and
+/// how many group additions (doublings included) it took.
+pub fn multi_scalar_mul<P: Group>(terms: &[(Vec<u8>, P)]) -> (P, usize) {
+    let c = window(terms.len());
+    let bits = terms.iter().map(|(k, _)| 8 * k.len()).max().unwrap_or(0);
+    let mut additions = 0;
+    let mut result: Option<P> = None;
+    for at in (0..bits.div_ceil(c)).rev().map(|position| position * c) {
+        if let Some(sum) = result.as_mut() {
+            for _ in 0..c {
+                *sum = sum.add(sum);
+            }
+            additions += c;
+        }
+        let mut buckets: Vec<Option<P>> = vec![None; (1 << c) - 1];
+        for (k, point) in terms {
+            match digit(k, at, c) {
+                0 => {}
+                d => accumulate(&mut buckets[d - 1], point, &mut additions),
+            }
+        }
+        let (mut running, mut position_sum) = (None, None);
+        for bucket in buckets.iter().rev() {
+            if let Some(bucket) = bucket {
+                accumulate(&mut running, bucket, &mut additions);
+            }
+            if let Some(running) = &running {
+                accumulate(&mut position_sum, running, &mut additions);
+            }
+        }
+        if let Some(position_sum) = &position_sum {
+            accumulate(&mut result, position_sum, &mut additions);
+        }
+    }
+    (result.unwrap_or_else(P::identity), additions)
+}
+
+/// The group additions of the same sum the slow way, each k_i * P_i by
+/// double-and-add and then added up: what `multi_scalar_mul` saves against.
+pub fn separate_additions(scalars: &[Vec<u8>]) -> usize {
+    let nonzero: Vec<(u32, u32)> = scalars
+        .iter()
+        .map(|k| {
+            let ones = k.iter().map(|byte| byte.count_ones()).sum::<u32>();
+            let top = k.iter().rposition(|&byte| byte != 0).map_or(0, |i| 8 * i as u32 + 8 - k[i].leading_zeros());
+            (top, ones)
+        })
+        .filter(|&(_, ones)| ones > 0)
+        .collect();
+    // top - 1 doublings and ones - 1 additions per product, then one addition per product but the first.
+    let products: u32 = nonzero.iter().map(|&(top, ones)| top - 1 + ones - 1).sum();
+    products as usize + nonzero.len().saturating_sub(1)
+}
@@ -0,0 +1,274 @@
+//! 2-of-2 outputs: the blinding of C = v*G + (r_a + r_b)*H is split between two
+//! parties, and neither share alone opens C, so spending it needs both of them to
+//! sign the kernel. Both know v.
+//!
+//! Funding (the funder A pays v into the output, the cosigner B joins it):
+//!
+//! 1. `Offer::new` (B): B's share as r_b*H, a nonce k_b*H for the output's range
+//!    proof and a kernel nonce. The secrets stay in `OfferSecrets`.
+//! 2. `Funding::new` (A): spends A's input, adds change and the 2-of-2 output
+//!    C = v*G + r_a*H + r_b*H with a joint range proof (see `range_proof`), and
+//!    partially signs the kernel. A's excess share is r_a + change - input - offset,
+//!    B's is r_b, so the kernel key is A's public excess + r_b*H.
+//! 3. `Funding::cosign` (B): checks A's partial signature, finishes the range proof,
+//!    adds B's partial signature and returns the finished transaction.
+//!
+//! Spending (either party starts, the other joins), like a slate:
+//!
+//! 1. `Spend::new`: spends C into an output of v - fee whose blinding the starter
+//!    picks; the starter's excess share is r_out - r_own - offset.
+//! 2. `Spend::sign`: the other party's excess share is -r_other; they sign for it.
+//! 3. `Spend::finalize`: the starter checks that signature and adds their own.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{mod_reduce, pedersen_commit_checked, Blinding, Commitment, Opening, Scalar};
+use crate::error::CtError;
+use crate::kernel::{partial_sign, partial_verify, public_key, split_excess, Kernel, Signature};
+use crate::range_proof::{joint_range_proof_finish, joint_range_proof_start, range_proof_create};
+use crate::rng::ToyRng;
+use crate::slate::Participant;
+use crate::transaction::{Transaction, TxEntry};
+
+/// One party's share of a 2-of-2 output: what they keep to spend it later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Share {
+    pub commitment: Commitment,
+    pub value: u64,
+    /// r_a or r_b.
+    pub blinding: Blinding,
+}
+
+/// What the cosigner sends the funder in step 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Offer {
+    /// r_b*H, also the cosigner's public excess.
+    pub share: Commitment,
+    /// k_b*H, for bit 0 of the output's range proof.
+    pub range_nonce: Commitment,
+    /// The cosigner's kernel nonce.
+    pub kernel_nonce: i64,
+}
+
+/// What the cosigner keeps until step 3.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OfferSecrets {
+    pub blinding: Blinding,
+    pub range_nonce: Blinding,
+    pub kernel_nonce: Blinding,
+}
+
+impl Offer {
+    pub fn new(rng: &mut ToyRng) -> (Offer, OfferSecrets) {
+        let secrets = OfferSecrets {
+            blinding: Blinding::random(rng),
+            range_nonce: Blinding::new(rng.scalar()),
+            kernel_nonce: Blinding::new(rng.scalar()),
+        };
+        let offer = Offer {
+            share: public_key(secrets.blinding),
+            range_nonce: public_key(secrets.range_nonce),
+            kernel_nonce: public_key(secrets.kernel_nonce).as_i64(),
+        };
+        (offer, secrets)
+    }
+}
+
+/// The combined kernel key and nonce of two participants.
+fn totals(a: &Participant, b: &Participant) -> (Commitment, i64) {
+    (a.public_excess + b.public_excess, mod_reduce(a.public_nonce as i128 + b.public_nonce as i128))
+}
+
+/// Check `signer`'s partial signature under the combined key and nonce of both.
+fn check_partial(signer: &Participant, other: &Participant, fee: u64) -> Result<i64, CtError> {
+    let (public, nonce) = totals(signer, other);
+    let s = signer
+        .partial_signature
+        .ok_or_else(|| CtError::MalformedInput("the other party has not signed yet".to_string()))?;
+    if !partial_verify(s, signer.public_excess, signer.public_nonce, public, nonce, &[fee as i64]) {
+        return Err(CtError::InvalidProof("the other party's partial signature fails".to_string()));
+    }
+    Ok(s)
+}
+
+/// Both partial signatures, added up into the kernel.
+fn kernel(a: &Participant, b: &Participant, s_a: i64, s_b: i64) -> Kernel {
+    let (excess, nonce) = totals(a, b);
+    Kernel { excess, signature: Signature { nonce, s: mod_reduce(s_a as i128 + s_b as i128) }, lock_height: 0 }
+}
+
+/// The funder's transaction so far, for the cosigner to finish.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Funding {
+    pub amount: u64,
+    /// Input, change and the 2-of-2 output last, whose range proof bit 0 lacks
+    /// the cosigner's response. No kernel yet.
+    #[serde(rename = "transaction")]
+    pub tx: Transaction,
+    pub offer: Offer,
+    /// The funder's public excess, nonce and partial signature.
+    pub funder: Participant,
+}
+
+impl Funding {
+    /// Step 2: spend `input`, pay `amount` into a 2-of-2 output with the cosigner of
+    /// `offer`, and keep the rest (minus `fee`) as change. Returns the funder's share
+    /// and change.
+    pub fn new(
+        input: Opening,
+        amount: u64,
+        fee: u64,
+        offer: &Offer,
+        rng: &mut ToyRng,
+    ) -> Result<(Funding, Share, Opening), CtError> {
+        let change_value = input.value.as_i64() - amount as i64 - fee as i64;
+        if change_value < 0 {
+            let needed = amount as i64 + fee as i64;
+            return Err(CtError::InsufficientFunds { available: input.value.as_i64(), needed });
+        }
+        // No change output without change, so no blinding for it either.
+        let change_blinding = if change_value > 0 { Blinding::random(rng) } else { Blinding::default() };
+        let change = Opening::new(Scalar::new(change_value), change_blinding);
+        let share_blinding = Blinding::random(rng);
+        let value = Scalar::new(amount as i64);
+        let commitment = pedersen_commit_checked(value, share_blinding)? + offer.share;
+        let proof = joint_range_proof_start(value, share_blinding, offer.share, offer.range_nonce, rng)?;
+        let mut outputs = Vec::new();
+        if change_value > 0 {
+            outputs.push(TxEntry::new(change.commit(), range_proof_create(change.value, change.blinding, rng)?));
+        }
+        outputs.push(TxEntry::new(commitment, proof));
+        let (offset, excess) = split_excess(share_blinding + change.blinding - input.blinding, rng);
+        let input_entry = TxEntry::input(input.commit());
+        let tx = Transaction::new(vec![input_entry], outputs)
+            .with_fee(fee)
+            .with_offset(offset);
+
+        let nonce = Blinding::new(rng.scalar());
+        let mut funder = Participant {
+            public_excess: public_key(excess),
+            public_nonce: public_key(nonce).as_i64(),
+            partial_signature: None,
+        };
+        let (public, total_nonce) = totals(&funder, &cosigner(offer));
+        funder.partial_signature = Some(partial_sign(excess, nonce, public, total_nonce, &[fee as i64]));
+        let share = Share { commitment, value: amount, blinding: share_blinding };
+        Ok((Funding { amount, tx, offer: *offer, funder }, share, change))
+    }
+
+    /// The 2-of-2 output: the last one.
+    pub fn output(&self) -> Commitment {
+        self.tx.outputs.last().expect("a funding transaction has the 2-of-2 output").commitment
+    }
+
+    /// Step 3: check the funder's signature, finish the output's range proof and the
+    /// kernel with `secrets`, and return the transaction with the cosigner's share.
+    pub fn cosign(mut self, secrets: &OfferSecrets) -> Result<(Transaction, Share), CtError> {
+        if public_key(secrets.blinding) != self.offer.share {
+            return Err(CtError::MalformedInput("these secrets do not belong to the offer".to_string()));
+        }
+        let cosigner = cosigner(&self.offer);
+        let s_funder = check_partial(&self.funder, &cosigner, self.tx.fee)?;
+        let commitment = self.output();
+        let output = self.tx.outputs.last_mut().expect("a funding transaction has the 2-of-2 output");
+        let proof = output
+            .proof
+            .take()
+            .ok_or_else(|| CtError::InvalidProof("the 2-of-2 output has no range proof".to_string()))?;
+        let value = Scalar::new(self.amount as i64);
+        output.proof = Some(joint_range_proof_finish(commitment, value, proof, secrets.blinding, secrets.range_nonce)?);
+
+        let (public, nonce) = totals(&self.funder, &cosigner);
+        let s = partial_sign(secrets.blinding, secrets.kernel_nonce, public, nonce, &[self.tx.fee as i64]);
+        let tx = self.tx.with_kernel(kernel(&self.funder, &cosigner, s_funder, s));
+        tx.verify()?;
+        Ok((tx, Share { commitment, value: self.amount, blinding: secrets.blinding }))
+    }
+}
+
+/// The cosigner as a kernel participant: excess r_b*H and the offered nonce.
+fn cosigner(offer: &Offer) -> Participant {
+    Participant { public_excess: offer.share, public_nonce: offer.kernel_nonce, partial_signature: None }
+}
+
+/// A spend of a 2-of-2 output, passed between the two parties.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spend {
+    /// Spends the 2-of-2 output into one output; no kernel yet.
+    #[serde(rename = "transaction")]
+    pub tx: Transaction,
+    pub starter: Participant,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub other: Option<Participant>,
+}
+
+/// What the starter keeps until `finalize`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendSecrets {
+    /// The new output's opening.
+    pub output: Opening,
+    pub excess: Blinding,
+    pub nonce: Blinding,
+}
+
+impl Spend {
+    /// Step 1: spend the output of `share` into one output of value - `fee`.
+    pub fn new(share: &Share, fee: u64, rng: &mut ToyRng) -> Result<(Spend, SpendSecrets), CtError> {
+        let value = share.value as i64 - fee as i64;
+        if value < 0 {
+            return Err(CtError::InsufficientFunds { available: share.value as i64, needed: fee as i64 });
+        }
+        let output = Opening::new(Scalar::new(value), Blinding::random(rng));
+        let proof = range_proof_create(output.value, output.blinding, rng)?;
+        let (offset, excess) = split_excess(output.blinding - share.blinding, rng);
+        let input = TxEntry::input(share.commitment);
+        let tx = Transaction::new(vec![input], vec![TxEntry::new(output.commit(), proof)])
+            .with_fee(fee)
+            .with_offset(offset);
+        let nonce = Blinding::new(rng.scalar());
+        let starter = Participant {
+            public_excess: public_key(excess),
+            public_nonce: public_key(nonce).as_i64(),
+            partial_signature: None,
+        };
+        Ok((Spend { tx, starter, other: None }, SpendSecrets { output, excess, nonce }))
+    }
+
+    /// Step 2: the other party, holding `share` of the spent output, signs for -r.
+    pub fn sign(mut self, share: &Share, rng: &mut ToyRng) -> Result<Spend, CtError> {
+        if self.other.is_some() {
+            return Err(CtError::MalformedInput("this spend has already been signed".to_string()));
+        }
+        if self.tx.inputs.first().map(|input| input.commitment) != Some(share.commitment) {
+            return Err(CtError::MalformedInput(format!("this spend does not spend {}", share.commitment)));
+        }
+        let excess = Blinding::default() - share.blinding;
+        let nonce = Blinding::new(rng.scalar());
+        let mut other = Participant {
+            public_excess: public_key(excess),
+            public_nonce: public_key(nonce).as_i64(),
+            partial_signature: None,
+        };
+        let (public, total_nonce) = totals(&self.starter, &other);
+        other.partial_signature = Some(partial_sign(excess, nonce, public, total_nonce, &[self.tx.fee as i64]));
+        self.other = Some(other);
+        Ok(self)
+    }
+
+    /// Step 3: check the other party's signature, add the starter's and attach the kernel.
+    pub fn finalize(self, secrets: &SpendSecrets) -> Result<Transaction, CtError> {
+        let other = self
+            .other
+            .ok_or_else(|| CtError::MalformedInput("the other party has not signed yet".to_string()))?;
+        if public_key(secrets.excess) != self.starter.public_excess {
+            return Err(CtError::MalformedInput("these secrets do not belong to this spend".to_string()));
+        }
+        let s_other = check_partial(&other, &self.starter, self.tx.fee)?;
+        let (public, nonce) = totals(&self.starter, &other);
+        let s = partial_sign(secrets.excess, secrets.nonce, public, nonce, &[self.tx.fee as i64]);
+        let tx = self.tx.with_kernel(kernel(&self.starter, &other, s, s_other));
+        tx.verify()?;
+        Ok(tx)
+    }
+}
+
@@ -0,0 +1,19 @@
+//! JSON files for 2-of-2 outputs (`ct multisig`). What the parties send each other:
+//!
+//! ```json
+//! offer:   { "share": r_b*H, "range_nonce": k_b*H, "kernel_nonce": 0 }
+//! funding: { "amount": 5, "transaction": {...}, "offer": {...},
+//!            "funder": { "public_excess": 0, "public_nonce": 0, "partial_signature": 0 } }
+//! spend:   { "transaction": {...}, "starter": {...}, "other": {...} }
+//! ```
+//!
+//! And what each keeps to themselves:
+//!
+//! ```json
+//! offer secrets: { "blinding": r_b, "range_nonce": k_b, "kernel_nonce": 0 }
+//! share:         { "commitment": C, "value": 5, "blinding": r_a }
+//! spend secrets: { "output": { "value": 4, "blinding": 0 }, "excess": 0, "nonce": 0 }
+//! ```
+//!
+//! `transaction` is in the `tx_file` format, the participants as in `slate_file`. Each
+//! file is the JSON serde writes for its `multisig` type (`slate_file::to_json`).
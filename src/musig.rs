@@ -0,0 +1,135 @@
+//! Toy MuSig2: any number of signers produce one kernel signature in two rounds.
+//!
+//! Each signer i holds a share x_i of the kernel's excess blinding, so E = sum(x_i*H).
+//!
+//! 1. Nonces (`nonce_round`): each signer draws two nonces k_i1, k_i2 and publishes
+//!    R_i1 = k_i1*H and R_i2 = k_i2*H. This round does not depend on the message and
+//!    can run ahead of time.
+//! 2. Signing (`Session::sign`): once every x_i*H and nonce pair is in, everyone
+//!    computes the binding factor b = hash(E, every R_i1, R_i2, message) and the
+//!    combined nonce R = sum(R_i1) + b*sum(R_i2), and signs
+//!    s_i = k_i1 + b*k_i2 + e*x_i with the usual challenge e for (E, R, message).
+//!
+//! `Session::aggregate` checks every s_i and adds them up: (R, sum(s_i)) is a plain
+//! Schnorr signature for E, so a kernel signed by n parties looks like any other.
+//! The second nonce is what makes two rounds enough: since b depends on everyone's
+//! nonces, a signer who waits to see the others' R_i1 cannot steer R.
+//!
+//! Real MuSig2 also weights each key by a hash of all of them, against a signer who
+//! picks x_i*H to cancel the others'. A kernel's key has to be E itself, so the
+//! weights are left out here; the commitments the excess comes from pin it instead.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{mod_mul, mod_reduce, Blinding, Commitment};
+use crate::error::CtError;
+use crate::kernel::{partial_sign, partial_verify, public_key, Signature};
+use crate::rng::ToyRng;
+use crate::transcript::Transcript;
+
+/// A signer's two secret nonces. Not `Clone`: `Session::sign` takes them, so a pair
+/// can sign only once. Signing two messages with one pair gives away x_i.
+#[derive(Debug)]
+pub struct SecretNonces {
+    first: Blinding,
+    second: Blinding,
+}
+
+/// R_i1 = k_i1*H and R_i2 = k_i2*H, what a signer publishes in round 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicNonces {
+    pub first: i64,
+    pub second: i64,
+}
+
+/// Round 1: draw a signer's nonce pair.
+pub fn nonce_round(rng: &mut ToyRng) -> (SecretNonces, PublicNonces) {
+    let secrets = SecretNonces { first: Blinding::new(rng.scalar()), second: Blinding::new(rng.scalar()) };
+    let public = PublicNonces {
+        first: public_key(secrets.first).as_i64(),
+        second: public_key(secrets.second).as_i64(),
+    };
+    (secrets, public)
+}
+
+/// One signer as everyone sees them: x_i*H and the round-1 nonces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signer {
+    pub public_excess: Commitment,
+    pub nonces: PublicNonces,
+}
+
+/// Round 2, the same for every signer once round 1 is done.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    pub signers: Vec<Signer>,
+    pub message: Vec<i64>,
+    /// E = sum(x_i*H), the key the signature is for.
+    pub public: Commitment,
+    /// b.
+    pub binding: i64,
+    /// R = sum(R_i1) + b*sum(R_i2).
+    pub nonce: i64,
+}
+
+impl Session {
+    pub fn new(signers: Vec<Signer>, message: &[i64]) -> Result<Self, CtError> {
+        if signers.is_empty() {
+            return Err(CtError::MalformedInput("a signing session needs at least one signer".to_string()));
+        }
+        let public = signers.iter().fold(Commitment::from_i64(0), |sum, signer| sum + signer.public_excess);
+        let mut transcript = Transcript::new("ct/musig2");
+        transcript.append("public key", public.as_i64());
+        for signer in &signers {
+            transcript.append("nonce 1", signer.nonces.first);
+            transcript.append("nonce 2", signer.nonces.second);
+        }
+        transcript.append_all("message", message);
+        let binding = transcript.challenge("b");
+        let (first, second) = signers.iter().fold((0, 0), |(first, second), signer| {
+            (
+                mod_reduce(first as i128 + signer.nonces.first as i128),
+                mod_reduce(second as i128 + signer.nonces.second as i128),
+            )
+        });
+        let nonce = mod_reduce(first as i128 + mod_mul(binding, second) as i128);
+        Ok(Session { signers, message: message.to_vec(), public, binding, nonce })
+    }
+
+    /// R_i1 + b*R_i2: signer `index`'s part of R.
+    fn signer_nonce(&self, index: usize) -> i64 {
+        let nonces = self.signers[index].nonces;
+        mod_reduce(nonces.first as i128 + mod_mul(self.binding, nonces.second) as i128)
+    }
+
+    /// s_i = k_i1 + b*k_i2 + e*x_i.
+    pub fn sign(&self, secret: Blinding, nonces: SecretNonces) -> i64 {
+        let (first, second) = (nonces.first.as_i64(), nonces.second.as_i64());
+        let nonce_secret = Blinding::new(mod_reduce(first as i128 + mod_mul(self.binding, second) as i128));
+        partial_sign(secret, nonce_secret, self.public, self.nonce, &self.message)
+    }
+
+    /// s_i*H = R_i1 + b*R_i2 + e*x_i*H for signer `index`.
+    pub fn verify_partial(&self, index: usize, s: i64) -> bool {
+        let Some(signer) = self.signers.get(index) else {
+            return false;
+        };
+        partial_verify(s, signer.public_excess, self.signer_nonce(index), self.public, self.nonce, &self.message)
+    }
+
+    /// Check every signer's s_i, in signer order, and add them up into (R, sum(s_i)).
+    pub fn aggregate(&self, partials: &[i64]) -> Result<Signature, CtError> {
+        if partials.len() != self.signers.len() {
+            return Err(CtError::MalformedInput(format!(
+                "{} partial signatures for {} signers",
+                partials.len(),
+                self.signers.len()
+            )));
+        }
+        if let Some(index) = (0..partials.len()).find(|&i| !self.verify_partial(i, partials[i])) {
+            return Err(CtError::InvalidProof(format!("signer {}'s partial signature fails", index + 1)));
+        }
+        let s = partials.iter().fold(0, |sum, &s_i| mod_reduce(sum as i128 + s_i as i128));
+        Ok(Signature { nonce: self.nonce, s })
+    }
+}
@@ -0,0 +1,211 @@
+//! Proof of knowledge of an opening: the prover shows they know (v, r) with
+//! C = v*G + r*H without revealing either (a Schnorr-style sigma protocol with
+//! two secrets, made non-interactive with Fiat–Shamir):
+//!
+//! 1. commit: pick k_v, k_r and send A = k_v*G + k_r*H
+//! 2. challenge: e = hash(C, A)
+//! 3. respond: z_v = k_v + e*v, z_r = k_r + e*r
+//!
+//! The verifier checks z_v*G + z_r*H = A + e*C. The random k_v, k_r hide v and r;
+//! answering for a challenge fixed after A requires knowing both.
+//!
+//! Statements about the value reduce to the same protocol with one secret: if
+//! P = x*H has no G component, proving knowledge of x shows P commits to zero.
+//! Two commitments hide the same value when C1 - C2 = (r1 - r2)*H, and C commits
+//! to a public value v when C - v*G = r*H. C commits to a member of a public set
+//! {s_1, ..., s_n} when one of the C - s_i*G is r*H: an OR over value proofs.
+//!
+//! All of these are `sigma::Representation` proofs: bases [G, H] for an opening,
+//! [H] alone for a commitment to zero.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{pedersen_commit, Blinding, Commitment, Opening, Scalar};
+use crate::error::CtError;
+use crate::params;
+use crate::rng::ToyRng;
+use crate::sigma::{self, OneOf, Representation, SigmaProof};
+use crate::transcript::Transcript;
+
+/// Transcript domains of the proofs about zero commitments.
+const EQUALITY: &str = "ct/equality";
+const VALUE: &str = "ct/public-value";
+const MEMBERSHIP: &str = "ct/membership";
+
+/// Non-interactive proof that the prover can open a commitment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpeningProof {
+    /// A = k_v*G + k_r*H
+    pub nonce: i64,
+    pub z_value: i64,
+    pub z_blinding: i64,
+}
+
+/// C = v*G + r*H, and a transcript that has absorbed C.
+fn opening_statement(commitment: Commitment) -> (Representation, Transcript) {
+    let params = params::active();
+    let mut transcript = Transcript::new("ct/opening");
+    transcript.append("C", commitment.as_i64());
+    (Representation { public: commitment.as_i64(), bases: vec![params.g, params.h] }, transcript)
+}
+
+/// Prover: prove knowledge of `opening` for C = opening.commit().
+pub fn prove_opening(opening: &Opening, rng: &mut ToyRng) -> OpeningProof {
+    let commitment = opening.commit();
+    let witness = vec![opening.value.as_i64(), opening.blinding.as_i64()];
+    let (statement, mut transcript) = opening_statement(commitment);
+    let proof = sigma::prove(&statement, &mut transcript, &witness, rng);
+    OpeningProof { nonce: proof.announcement[0], z_value: proof.response[0], z_blinding: proof.response[1] }
+}
+
+/// Verifier: z_v*G + z_r*H = A + e*C, knowing only C.
+pub fn verify_opening(commitment: Commitment, proof: &OpeningProof) -> Result<(), CtError> {
+    let proof = SigmaProof { announcement: vec![proof.nonce], response: vec![proof.z_value, proof.z_blinding] };
+    let (statement, mut transcript) = opening_statement(commitment);
+    if sigma::verify(&statement, &mut transcript, &proof) {
+        Ok(())
+    } else {
+        Err(CtError::InvalidProof("opening proof fails".to_string()))
+    }
+}
+
+/// Proof of knowledge of x with P = x*H, i.e. that P is a commitment to zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlindingProof {
+    /// A = k*H
+    pub nonce: i64,
+    pub z: i64,
+}
+
+/// P = x*H, where P is `statement[0]`, and a transcript for `domain` that has
+/// absorbed the whole `statement`.
+fn zero_statement(domain: &str, statement: &[i64]) -> (Representation, Transcript) {
+    let mut transcript = Transcript::new(domain);
+    transcript.append_all("statement", statement);
+    (Representation { public: statement[0], bases: vec![params::active().h] }, transcript)
+}
+
+/// Schnorr proof for P = x*H.
+fn prove_blinding(domain: &str, secret: Blinding, statement: &[i64], rng: &mut ToyRng) -> BlindingProof {
+    let (sigma, mut transcript) = zero_statement(domain, statement);
+    let proof = sigma::prove(&sigma, &mut transcript, &vec![secret.as_i64()], rng);
+    BlindingProof { nonce: proof.announcement[0], z: proof.response[0] }
+}
+
+/// z*H = A + e*P.
+fn verify_blinding(domain: &str, statement: &[i64], proof: &BlindingProof) -> bool {
+    let (sigma, mut transcript) = zero_statement(domain, statement);
+    let proof = SigmaProof { announcement: vec![proof.nonce], response: vec![proof.z] };
+    sigma::verify(&sigma, &mut transcript, &proof)
+}
+
+/// Prover: show `c1` and `c2` hide the same value, from their openings.
+/// An honest prover refuses when the openings do not match or the values differ.
+pub fn prove_equal(
+    c1: Commitment,
+    c2: Commitment,
+    openings: (&Opening, &Opening),
+    rng: &mut ToyRng,
+) -> Result<BlindingProof, CtError> {
+    let (o1, o2) = openings;
+    if o1.commit() != c1 || o2.commit() != c2 {
+        return Err(CtError::MalformedInput("the openings do not match the commitments".to_string()));
+    }
+    if o1.value != o2.value {
+        return Err(CtError::InvalidProof("the commitments hide different values".to_string()));
+    }
+    let difference = c1 - c2;
+    Ok(prove_blinding(EQUALITY, o1.blinding - o2.blinding, &[difference.as_i64(), c1.as_i64(), c2.as_i64()], rng))
+}
+
+/// Verifier: C1 - C2 is a commitment to zero, so C1 and C2 hide the same value.
+pub fn verify_equal(c1: Commitment, c2: Commitment, proof: &BlindingProof) -> Result<(), CtError> {
+    if verify_blinding(EQUALITY, &[(c1 - c2).as_i64(), c1.as_i64(), c2.as_i64()], proof) {
+        Ok(())
+    } else {
+        Err(CtError::InvalidProof("equality proof fails".to_string()))
+    }
+}
+
+/// Prover: show `commitment` opens to exactly `opening.value`, keeping the blinding secret.
+pub fn prove_value(commitment: Commitment, opening: &Opening, rng: &mut ToyRng) -> Result<BlindingProof, CtError> {
+    if opening.commit() != commitment {
+        return Err(CtError::MalformedInput("the opening does not match the commitment".to_string()));
+    }
+    let statement = value_statement(commitment, opening.value);
+    Ok(prove_blinding(VALUE, opening.blinding, &statement, rng))
+}
+
+/// Verifier: C - v*G is a commitment to zero, so C commits to exactly the public `value`.
+pub fn verify_value(commitment: Commitment, value: Scalar, proof: &BlindingProof) -> Result<(), CtError> {
+    if verify_blinding(VALUE, &value_statement(commitment, value), proof) {
+        Ok(())
+    } else {
+        Err(CtError::InvalidProof(format!("proof that C commits to {} fails", value)))
+    }
+}
+
+/// (C - v*G, C, v): the zero commitment first, then what the challenge must also bind.
+fn value_statement(commitment: Commitment, value: Scalar) -> [i64; 3] {
+    let zero = commitment - pedersen_commit(value, Blinding::default());
+    [zero.as_i64(), commitment.as_i64(), value.as_i64()]
+}
+
+/// Proof that a commitment hides one of the values of a public set, without saying which.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MembershipProof {
+    /// A_i for every member s_i: the real one a Schnorr nonce, the others simulated.
+    pub nonces: Vec<i64>,
+    /// e_i for every member; they add up to the transcript's challenge.
+    pub challenges: Vec<i64>,
+    pub z: Vec<i64>,
+}
+
+/// "C - s_i*G = x*H" for every member s_i, and a transcript that has absorbed C and the set.
+fn membership_statement(commitment: Commitment, set: &[i64]) -> Result<(OneOf<Representation>, Transcript), CtError> {
+    if set.is_empty() {
+        return Err(CtError::MalformedInput("the set is empty".to_string()));
+    }
+    let h = params::active().h;
+    let members = set.iter().map(|&s| {
+        let zero = commitment - pedersen_commit(Scalar::new(s), Blinding::default());
+        Representation { public: zero.as_i64(), bases: vec![h] }
+    });
+    let mut transcript = Transcript::new(MEMBERSHIP);
+    transcript.append("C", commitment.as_i64());
+    transcript.append_all("set", set);
+    Ok((OneOf(members.collect()), transcript))
+}
+
+/// Prover: show `commitment` opens to one of the values in `set`. An honest prover
+/// refuses when the opening does not match or its value is not in the set.
+pub fn prove_membership(
+    commitment: Commitment,
+    opening: &Opening,
+    set: &[i64],
+    rng: &mut ToyRng,
+) -> Result<MembershipProof, CtError> {
+    if opening.commit() != commitment {
+        return Err(CtError::MalformedInput("the opening does not match the commitment".to_string()));
+    }
+    let (statement, mut transcript) = membership_statement(commitment, set)?;
+    let Some(index) = set.iter().position(|&s| s == opening.value.as_i64()) else {
+        return Err(CtError::InvalidProof(format!("value {} is not in the set", opening.value)));
+    };
+    let witness = (index, vec![opening.blinding.as_i64()]);
+    let proof = sigma::prove(&statement, &mut transcript, &witness, rng);
+    let (challenges, z) = proof.response.split_at(set.len());
+    Ok(MembershipProof { nonces: proof.announcement, challenges: challenges.to_vec(), z: z.to_vec() })
+}
+
+/// Verifier: one of the C - s_i*G is a commitment to zero, so C hides a member of `set`.
+pub fn verify_membership(commitment: Commitment, set: &[i64], proof: &MembershipProof) -> Result<(), CtError> {
+    let (statement, mut transcript) = membership_statement(commitment, set)?;
+    let proof =
+        SigmaProof { announcement: proof.nonces.clone(), response: [&proof.challenges[..], &proof.z[..]].concat() };
+    if sigma::verify(&statement, &mut transcript, &proof) {
+        Ok(())
+    } else {
+        Err(CtError::InvalidProof("set membership proof fails".to_string()))
+    }
+}
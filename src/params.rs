@@ -0,0 +1,211 @@
+//! Public parameters of the i64 group: modulus p, generators G and H, the number
+//! of bits a range proof covers, and the most a coinbase may mint. They start at the
+//! compile-time defaults in `commitment`, `range_proof` and `transaction`; the binary may replace them once at startup
+//! (from `ct.toml` or CLI flags) before any commitment is made.
+//!
+//! `validate` only rejects what the arithmetic cannot handle. Parameters that work
+//! but are weak pass, and `Params::weaknesses` runs quick checks that name the flaws.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+use crate::commitment::{G, H, MODULUS};
+use crate::error::CtError;
+use crate::field::Field;
+use crate::range_proof::RANGE_BITS;
+use crate::transaction::SUBSIDY;
+use crate::{monero, secp256k1, toy_curve};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Params {
+    pub modulus: i64,
+    pub g: i64,
+    pub h: i64,
+    pub range_bits: u32,
+    pub subsidy: u64,
+}
+
+impl Params {
+    pub const DEFAULT: Params = Params { modulus: MODULUS, g: G, h: H, range_bits: RANGE_BITS, subsidy: SUBSIDY };
+
+    /// Reject parameters the arithmetic cannot handle. Products of two field elements
+    /// go through i128, so p may not exceed the default 2^61 - 1.
+    pub fn validate(&self) -> Result<(), CtError> {
+        if !(3..=MODULUS).contains(&self.modulus) {
+            return Err(CtError::MalformedInput(format!("modulus {} is not in [3, 2^61 - 1]", self.modulus)));
+        }
+        for (name, generator) in [("G", self.g), ("H", self.h)] {
+            if !(1..self.modulus).contains(&generator) {
+                return Err(CtError::MalformedInput(format!("generator {} = {} is not in [1, p)", name, generator)));
+            }
+        }
+        if self.g == self.h {
+            return Err(CtError::MalformedInput("G and H must differ".to_string()));
+        }
+        if !(1..=62).contains(&self.range_bits) {
+            return Err(CtError::MalformedInput(format!("range_bits {} is not in [1, 62]", self.range_bits)));
+        }
+        Ok(())
+    }
+
+    /// Build the fixed-base tables of G and H for the curve backends (`fixed_base`), so
+    /// their commitments are lookups and additions from then on. The mod-p group needs
+    /// none, as v*G there is one multiplication. It costs about a thousand additions
+    /// per generator on the 256-bit curves, which a few commitments repay; a program
+    /// that commits once is faster without it, and one that uses a single backend
+    /// calls that backend's `precompute` instead.
+    pub fn precompute() {
+        toy_curve::precompute();
+        secp256k1::precompute();
+        monero::precompute();
+    }
+
+    /// The widest range proof for which `outputs` in-range values cannot add up past p:
+    /// the largest n with outputs * (2^n - 1) < p. Any wider and a huge "in-range" value
+    /// can wrap around p and stand in for a negative one.
+    pub fn max_range_bits(&self, outputs: usize) -> u32 {
+        (1..=62).rev().find(|&n| outputs as i128 * ((1i128 << n) - 1) < i128::from(self.modulus)).unwrap_or(0)
+    }
+
+    /// Quick checks for flaws that `validate` lets through.
+    pub fn weaknesses(&self) -> Vec<Weakness> {
+        let mut found = Vec::new();
+        let prime = is_prime(self.modulus);
+        if !prime {
+            let factor = (2..=self.modulus.isqrt().min(1 << 20)).find(|d| self.modulus % d == 0);
+            found.push(Weakness::CompositeModulus { factor });
+        }
+        let bits = 64 - self.modulus.leading_zeros();
+        if bits < TINY_FIELD_BITS {
+            found.push(Weakness::TinyField { bits });
+        }
+        if prime {
+            // b = a*H/G; a small b (or a small p - b, for negative b) is a relation.
+            let g_inverse = pow_mod(self.g, self.modulus - 2, self.modulus);
+            let relation = (1..=RELATION_LIMIT).find_map(|a| {
+                let b = mul_mod(mul_mod(a, self.h, self.modulus), g_inverse, self.modulus);
+                let b = if b > self.modulus / 2 { b - self.modulus } else { b };
+                (b.abs() <= RELATION_LIMIT).then_some((a, b))
+            });
+            if let Some((a, b)) = relation {
+                found.push(Weakness::RelatedGenerators { a, b });
+            }
+        }
+        if self.range_bits > self.max_range_bits(2) {
+            found.push(Weakness::WideRange { bits: self.range_bits, max: self.max_range_bits(2) });
+        }
+        found
+    }
+}
+
+/// Coefficients up to this are tried when looking for a*H = b*G.
+const RELATION_LIMIT: i64 = 1000;
+/// Below 2^this, `ct crack` searches every blinding in seconds.
+const TINY_FIELD_BITS: u32 = 32;
+
+/// A flaw the quick checks found in a set of parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Weakness {
+    /// p is not prime; `factor` divides it, if trial division found one.
+    CompositeModulus { factor: Option<i64> },
+    /// p has so few bits that trying every blinding is quick.
+    TinyField { bits: u32 },
+    /// a*H = b*G with small a and b, so (v, r) and (v + b*t, r - a*t) open the same C.
+    RelatedGenerators { a: i64, b: i64 },
+    /// Two in-range values can add up past p (see `max_range_bits`).
+    WideRange { bits: u32, max: u32 },
+}
+
+impl fmt::Display for Weakness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Weakness::CompositeModulus { factor: Some(factor) } => {
+                write!(f, "composite modulus: {} divides p, so the group splits into smaller ones", factor)
+            }
+            Weakness::CompositeModulus { factor: None } => {
+                write!(f, "composite modulus: p fails the Miller-Rabin test, so the group splits into smaller ones")
+            }
+            Weakness::TinyField { bits } => {
+                write!(f, "tiny field: p has {} bits, and `ct crack` tries every blinding in seconds", bits)
+            }
+            Weakness::RelatedGenerators { a, b } => write!(
+                f,
+                "related generators: {}*H = {}*G, so (v, r) and (v + {}*t, r - {}*t) open the same C",
+                a, b, b, a
+            ),
+            Weakness::WideRange { bits, max } => {
+                write!(f, "range too wide: two {}-bit values can wrap around p (at most {} bits are safe)", bits, max)
+            }
+        }
+    }
+}
+
+/// a*b mod m without overflow.
+fn mul_mod(a: i64, b: i64, m: i64) -> i64 {
+    (a as i128 * b as i128).rem_euclid(m as i128) as i64
+}
+
+fn pow_mod(mut base: i64, mut exponent: i64, m: i64) -> i64 {
+    let mut result = 1 % m;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Miller-Rabin with the first 12 primes as bases, which is exact far beyond i64.
+fn is_prime(n: i64) -> bool {
+    const BASES: [i64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    if let Some(&base) = BASES.iter().find(|&&base| n % base == 0) {
+        return n == base;
+    }
+    let (mut d, mut s) = (n - 1, 0);
+    while d % 2 == 0 {
+        d /= 2;
+        s += 1;
+    }
+    BASES.iter().all(|&base| {
+        let mut x = pow_mod(base, d, n);
+        x == 1
+            || x == n - 1
+            || (1..s).any(|_| {
+                x = mul_mod(x, x, n);
+                x == n - 1
+            })
+    })
+}
+
+static ACTIVE: RwLock<Params> = RwLock::new(Params::DEFAULT);
+/// The active `Field`'s parts, set up once per `set`. They sit in atomics rather than
+/// behind the lock: every reduction reads them, and a lock costs more than the math.
+/// Relaxed is enough as `set` runs at startup (or in a single-threaded demo).
+static FIELD: [AtomicU64; 3] = [AtomicU64::new(MODULUS as u64), AtomicU64::new(0), AtomicU64::new(0)];
+
+/// The parameters every commitment and proof is currently computed with.
+pub fn active() -> Params {
+    *ACTIVE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Arithmetic mod the active parameters' p.
+pub fn field() -> Field {
+    Field::from_parts(FIELD.each_ref().map(|part| part.load(Ordering::Relaxed)))
+}
+
+/// Replace the active parameters. Commitments made before the switch are not
+/// comparable with ones made after it.
+pub fn set(params: Params) -> Result<(), CtError> {
+    params.validate()?;
+    *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = params;
+    for (part, value) in FIELD.iter().zip(Field::new(params.modulus).to_parts()) {
+        part.store(value, Ordering::Relaxed);
+    }
+    Ok(())
+}
@@ -0,0 +1,81 @@
+//! Payment proofs: the sender can later show that a given receiver was paid.
+//!
+//! When the sender asks for one (`Slate::request_payment_proof`), the slate names
+//! the receiver's key B, the spend key of their address. The receiver signs
+//! (amount, E, x_s*H) with b in step 2, where E = x_s*H + x_r*H is the kernel excess
+//! that will end up on the chain and x_s*H is the sender's share of it. The sender
+//! checks the signature before finalizing and keeps the result.
+//!
+//! Anyone can then check the proof: the signature shows the holder of b agreed to
+//! being paid the amount by the kernel E, and a kernel with excess E on the chain
+//! shows that transaction was confirmed. The sender's share x_s*H ties the proof to
+//! the sender, who alone knows x_s and can sign with it if that is ever disputed.
+//! The proof says nothing about anyone else's outputs, and E was public already.
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::BlockHeader;
+use crate::chain::Chain;
+use crate::commitment::{Blinding, Commitment};
+use crate::error::CtError;
+use crate::kernel::{public_key, schnorr_sign, schnorr_verify, Signature};
+use crate::rng::ToyRng;
+
+/// The sender asked the holder of `receiver` to sign; `signature` is their answer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofRequest {
+    pub receiver: Commitment,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Signature>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentProof {
+    pub amount: u64,
+    /// E, the excess of the payment's kernel.
+    pub kernel_excess: Commitment,
+    /// x_s*H, the sender's share of E.
+    pub sender_excess: Commitment,
+    /// B, the receiver's key.
+    pub receiver: Commitment,
+    pub signature: Signature,
+}
+
+/// What the receiver signs. Three elements, so it is never a kernel message [fee].
+fn message(amount: u64, kernel_excess: Commitment, sender_excess: Commitment) -> [i64; 3] {
+    [amount as i64, kernel_excess.as_i64(), sender_excess.as_i64()]
+}
+
+impl PaymentProof {
+    /// The receiver's side: sign for `amount` paid by the kernel `kernel_excess`
+    /// with the secret b of key B = b*H.
+    pub fn sign(
+        secret: Blinding,
+        amount: u64,
+        kernel_excess: Commitment,
+        sender_excess: Commitment,
+        rng: &mut ToyRng,
+    ) -> Self {
+        let signature = schnorr_sign(secret, &message(amount, kernel_excess, sender_excess), rng);
+        PaymentProof { amount, kernel_excess, sender_excess, receiver: public_key(secret), signature }
+    }
+
+    /// The receiver's signature holds.
+    pub fn verify(&self) -> Result<(), CtError> {
+        let message = message(self.amount, self.kernel_excess, self.sender_excess);
+        if !schnorr_verify(self.receiver, &message, &self.signature) {
+            return Err(CtError::InvalidProof("the receiver's signature on the payment proof fails".to_string()));
+        }
+        Ok(())
+    }
+
+    /// The main-chain block holding the payment's kernel, if it was confirmed.
+    pub fn confirmed_in<'a>(&self, chain: &'a Chain) -> Option<&'a BlockHeader> {
+        chain
+            .blocks()
+            .into_iter()
+            .filter(|block| chain.is_main(&block.header.hash()))
+            .find(|block| block.transactions.iter().any(|tx| tx.kernel.is_some_and(|k| k.excess == self.kernel_excess)))
+            .map(|block| &block.header)
+    }
+}
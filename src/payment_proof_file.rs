@@ -0,0 +1,11 @@
+//! JSON payment proofs (`ct finalize --proof-out`, `ct payment-proof verify`):
+//!
+//! ```json
+//! { "amount": 5,
+//!   "kernel_excess": E,
+//!   "sender_excess": x_s*H,
+//!   "receiver": B,
+//!   "signature": { "nonce": 0, "s": 0 } }
+//! ```
+//!
+//! That is the JSON serde writes for `payment_proof::PaymentProof` (`slate_file::to_json`).
@@ -0,0 +1,477 @@
+//! Just enough PNG to save a QR code as an image and read one back (`ct qr`): 8-bit
+//! grayscale out, any non-interlaced PNG in, as luminance.
+//!
+//! A PNG is an 8-byte signature and then chunks (length, type, data, CRC-32 of type
+//! and data): IHDR gives the size and pixel format, PLTE the palette if there is one,
+//! IDAT the pixels, and IEND closes the file. Every row of pixels starts with a filter
+//! byte saying how it was predicted from its left and upper neighbours, and the rows
+//! are compressed with zlib, a wrapper around DEFLATE (RFC 1951): literals and LZ77
+//! back-references, Huffman-coded. Writing uses DEFLATE's fixed Huffman codes and a
+//! greedy search for matches; reading handles stored, fixed and dynamic blocks.
+
+use crate::error::CtError;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// Widths and heights above this are refused rather than allocated.
+const MAX_SIDE: usize = 16_384;
+/// DEFLATE looks back at most this far.
+const WINDOW: usize = 32_768;
+
+const LENGTH_BASE: [u16; 29] =
+    [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097,
+    6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] =
+    [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+/// The order a dynamic block lists the code lengths of its code-length code in.
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+fn malformed(reason: &str) -> CtError {
+    CtError::MalformedInput(format!("not a PNG this reads: {}", reason))
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { crc >> 1 ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65_521;
+        b = (b + a) % 65_521;
+    }
+    b << 16 | a
+}
+
+/// Bits go out least significant first, as DEFLATE packs them.
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    bits: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn put(&mut self, value: u32, len: u32) {
+        self.bits |= (value as u64) << self.count;
+        self.count += len;
+        while self.count >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// A Huffman code, which goes out most significant bit first.
+    fn put_code(&mut self, code: u32, len: u32) {
+        self.put(code.reverse_bits() >> (32 - len), len);
+    }
+
+    /// A literal/length symbol in the fixed code.
+    fn put_symbol(&mut self, symbol: u32) {
+        match symbol {
+            0..=143 => self.put_code(0x30 + symbol, 8),
+            144..=255 => self.put_code(0x190 + symbol - 144, 9),
+            256..=279 => self.put_code(symbol - 256, 7),
+            _ => self.put_code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.bits as u8);
+        }
+        self.out
+    }
+}
+
+/// The index of the last entry of `bases` at or below `value`.
+fn code_for(bases: &[u16], value: usize) -> usize {
+    bases.iter().rposition(|&base| base as usize <= value).expect("values start at the first base")
+}
+
+/// One fixed-Huffman DEFLATE block. Matches are found through a table of the last
+/// position each 3-byte prefix was seen at, which is plenty for an image of squares.
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let hash = |i: usize| (data[i] as usize) << 7 ^ (data[i + 1] as usize) << 4 ^ data[i + 2] as usize;
+    let mut last = vec![usize::MAX; 1 << 15];
+    let mut out = BitWriter::default();
+    out.put(1, 1); // the final block
+    out.put(1, 2); // fixed codes
+    let mut i = 0;
+    while i < data.len() {
+        let mut length = 0;
+        let mut distance = 0;
+        if i + 3 <= data.len() {
+            let candidate = std::mem::replace(&mut last[hash(i) & 0x7fff], i);
+            if candidate != usize::MAX && i - candidate <= WINDOW {
+                let limit = (data.len() - i).min(258);
+                length = (0..limit).take_while(|&k| data[candidate + k] == data[i + k]).count();
+                distance = i - candidate;
+            }
+        }
+        if length < 3 {
+            out.put_symbol(data[i] as u32);
+            i += 1;
+            continue;
+        }
+        let code = code_for(&LENGTH_BASE, length);
+        out.put_symbol(257 + code as u32);
+        out.put((length - LENGTH_BASE[code] as usize) as u32, LENGTH_EXTRA[code] as u32);
+        let code = code_for(&DISTANCE_BASE, distance);
+        out.put_code(code as u32, 5);
+        out.put((distance - DISTANCE_BASE[code] as usize) as u32, DISTANCE_EXTRA[code] as u32);
+        for j in i + 1..(i + length).min(data.len().saturating_sub(2)) {
+            last[hash(j) & 0x7fff] = j;
+        }
+        i += length;
+    }
+    out.put_symbol(256);
+    out.finish()
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, n: u32) -> Result<u32, CtError> {
+        let mut value = 0;
+        for i in 0..n {
+            let byte = *self.bytes.get(self.pos).ok_or_else(|| malformed("the compressed data stops early"))?;
+            value |= ((byte >> self.bit) & 1) as u32 * (1 << i);
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    /// Skip to the next whole byte, as a stored block does.
+    fn align(&mut self) {
+        if self.bit > 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+/// A canonical Huffman code, from its symbols' code lengths: how many codes each
+/// length has, and the symbols in code order.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16).filter(|&s| lengths[s as usize] > 0).collect();
+        symbols.sort_by_key(|&s| lengths[s as usize]);
+        Huffman { counts, symbols }
+    }
+
+    /// Read one symbol, a bit at a time: codes of each length are consecutive numbers.
+    fn decode(&self, reader: &mut BitReader<'_>) -> Result<u16, CtError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= reader.bits(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(malformed("a Huffman code that is not in the table"))
+    }
+}
+
+fn dynamic_codes(reader: &mut BitReader<'_>) -> Result<(Huffman, Huffman), CtError> {
+    let literals = reader.bits(5)? as usize + 257;
+    let distances = reader.bits(5)? as usize + 1;
+    let mut code_lengths = [0u8; 19];
+    for &i in CODE_LENGTH_ORDER.iter().take(reader.bits(4)? as usize + 4) {
+        code_lengths[i] = reader.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths);
+    let mut lengths = Vec::with_capacity(literals + distances);
+    while lengths.len() < literals + distances {
+        let (len, repeat) = match code_length_code.decode(reader)? {
+            len @ 0..=15 => (len as u8, 1),
+            16 => (*lengths.last().ok_or_else(|| malformed("a repeat with nothing before it"))?, 3 + reader.bits(2)?),
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(len, repeat as usize));
+    }
+    if lengths.len() > literals + distances {
+        return Err(malformed("code lengths run past their count"));
+    }
+    Ok((Huffman::new(&lengths[..literals]), Huffman::new(&lengths[literals..])))
+}
+
+/// Inflate DEFLATE data; also says how many bytes it took up.
+fn inflate(bytes: &[u8]) -> Result<(Vec<u8>, usize), CtError> {
+    let mut reader = BitReader { bytes, pos: 0, bit: 0 };
+    let mut out = Vec::new();
+    loop {
+        let last = reader.bits(1)? == 1;
+        let (literal_code, distance_code) = match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let header = bytes.get(reader.pos..reader.pos + 4).ok_or_else(|| malformed("a cut-off stored block"))?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                if len != !u16::from_le_bytes([header[2], header[3]]) {
+                    return Err(malformed("a stored block's length and its complement disagree"));
+                }
+                let start = reader.pos + 4;
+                let block = bytes.get(start..start + len as usize).ok_or_else(|| malformed("a cut-off stored block"))?;
+                out.extend_from_slice(block);
+                reader.pos = start + len as usize;
+                if last {
+                    break;
+                }
+                continue;
+            }
+            1 => {
+                let mut lengths = [8u8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+            }
+            2 => dynamic_codes(&mut reader)?,
+            _ => return Err(malformed("a DEFLATE block of the reserved type 3")),
+        };
+        loop {
+            let symbol = literal_code.decode(&mut reader)? as usize;
+            if symbol < 256 {
+                out.push(symbol as u8);
+                continue;
+            }
+            if symbol == 256 {
+                break;
+            }
+            let code = symbol - 257;
+            let base = *LENGTH_BASE.get(code).ok_or_else(|| malformed("a length code past 285"))?;
+            let length = base as usize + reader.bits(LENGTH_EXTRA[code] as u32)? as usize;
+            let code = distance_code.decode(&mut reader)? as usize;
+            let base = *DISTANCE_BASE.get(code).ok_or_else(|| malformed("a distance code past 29"))?;
+            let distance = base as usize + reader.bits(DISTANCE_EXTRA[code] as u32)? as usize;
+            if distance > out.len() {
+                return Err(malformed("a back-reference before the start"));
+            }
+            for _ in 0..length {
+                out.push(out[out.len() - distance]);
+            }
+        }
+        if last {
+            break;
+        }
+    }
+    reader.align();
+    Ok((out, reader.pos))
+}
+
+/// zlib: a two-byte header (DEFLATE, 32 KiB window), the data, Adler-32 of the input.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn unzlib(bytes: &[u8]) -> Result<Vec<u8>, CtError> {
+    let [method, flags, ..] = *bytes else { return Err(malformed("no zlib header")) };
+    if method & 0x0f != 8 || !(method as u16 * 256 + flags as u16).is_multiple_of(31) || flags & 0x20 != 0 {
+        return Err(malformed("a zlib header that is not plain DEFLATE"));
+    }
+    let (data, used) = inflate(&bytes[2..])?;
+    let sum = bytes.get(2 + used..6 + used).ok_or_else(|| malformed("no zlib checksum"))?;
+    if sum != adler32(&data).to_be_bytes() {
+        return Err(malformed("the zlib checksum does not match"));
+    }
+    Ok(data)
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+/// Pixels as luminance, row by row: 0 is black, 255 white.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn pixel(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.width + x]
+    }
+
+    /// An 8-bit grayscale PNG. Every row is stored as its difference from the one above
+    /// (filter 2, "up"), which turns a row that repeats into zeros that compress away.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut rows = Vec::with_capacity(self.pixels.len() + self.height);
+        let mut above = vec![0u8; self.width];
+        for row in self.pixels.chunks(self.width) {
+            rows.push(2);
+            rows.extend(row.iter().zip(&above).map(|(&pixel, &up)| pixel.wrapping_sub(up)));
+            above = row.to_vec();
+        }
+        let mut header = Vec::new();
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        header.extend([8, 0, 0, 0, 0]); // 8 bits, grayscale, DEFLATE, standard filters, not interlaced
+        let mut png = SIGNATURE.to_vec();
+        chunk(&mut png, b"IHDR", &header);
+        chunk(&mut png, b"IDAT", &zlib(&rows));
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    /// Read a PNG of any colour type and bit depth, with transparency shown over white.
+    pub fn from_png(bytes: &[u8]) -> Result<Image, CtError> {
+        let mut rest = bytes.strip_prefix(&SIGNATURE).ok_or_else(|| malformed("no PNG signature"))?;
+        let (mut header, mut palette, mut compressed) = (None, Vec::new(), Vec::new());
+        loop {
+            let len = rest.get(..4).ok_or_else(|| malformed("the file stops before IEND"))?;
+            let len = u32::from_be_bytes(len.try_into().expect("4 bytes")) as usize;
+            let body = rest.get(4..8 + len).ok_or_else(|| malformed("a chunk runs past the end"))?;
+            let crc = rest.get(8 + len..12 + len).ok_or_else(|| malformed("a chunk runs past the end"))?;
+            if crc32(body) != u32::from_be_bytes(crc.try_into().expect("4 bytes")) {
+                return Err(malformed("a chunk's CRC does not match"));
+            }
+            let (kind, data) = body.split_at(4);
+            match kind {
+                b"IHDR" => header = Some(data.to_vec()),
+                b"PLTE" => palette = data.to_vec(),
+                b"IDAT" => compressed.extend_from_slice(data),
+                b"IEND" => break,
+                _ => {}
+            }
+            rest = &rest[12 + len..];
+        }
+        let header = header.filter(|h| h.len() == 13).ok_or_else(|| malformed("no IHDR"))?;
+        let dimension = |i: usize| u32::from_be_bytes(header[i..i + 4].try_into().expect("4 bytes")) as usize;
+        let (width, height, depth, color) = (dimension(0), dimension(4), header[8], header[9]);
+        if width == 0 || height == 0 || width > MAX_SIDE || height > MAX_SIDE {
+            return Err(malformed(&format!("{} x {} pixels", width, height)));
+        }
+        if header[12] != 0 {
+            return Err(malformed("interlaced"));
+        }
+        let channels = match (color, depth) {
+            (0, 1 | 2 | 4 | 8 | 16) | (3, 1 | 2 | 4 | 8) => 1,
+            (4, 8 | 16) => 2,
+            (2, 8 | 16) => 3,
+            (6, 8 | 16) => 4,
+            _ => return Err(malformed(&format!("colour type {} at {} bits", color, depth))),
+        };
+        let pixel_bits = channels * depth as usize;
+        let stride = (width * pixel_bits).div_ceil(8);
+        let step = (pixel_bits / 8).max(1);
+        let raw = unzlib(&compressed)?;
+        if raw.len() != height * (stride + 1) {
+            return Err(malformed("the pixel data is not the size IHDR gives"));
+        }
+        let mut previous = vec![0u8; stride];
+        let mut pixels = Vec::with_capacity(width * height);
+        for line in raw.chunks(stride + 1) {
+            let mut row = line[1..].to_vec();
+            for i in 0..stride {
+                let left = if i >= step { row[i - step] } else { 0 };
+                let (up, up_left) = (previous[i], if i >= step { previous[i - step] } else { 0 });
+                let predicted = match line[0] {
+                    0 => 0,
+                    1 => left,
+                    2 => up,
+                    3 => ((left as u16 + up as u16) / 2) as u8,
+                    4 => paeth(left, up, up_left),
+                    filter => return Err(malformed(&format!("row filter {}", filter))),
+                };
+                row[i] = row[i].wrapping_add(predicted);
+            }
+            for x in 0..width {
+                let at = |channel: usize| sample(&row, x * channels + channel, depth);
+                let (gray, alpha) = match color {
+                    0 => (scale(at(0), depth), 255),
+                    3 => {
+                        let index = at(0) as usize;
+                        let rgb =
+                            palette.get(3 * index..3 * index + 3).ok_or_else(|| malformed("a colour not in PLTE"))?;
+                        (luminance(rgb[0], rgb[1], rgb[2]), 255)
+                    }
+                    4 => (scale(at(0), depth), scale(at(1), depth)),
+                    2 => (luminance(at(0) as u8, at(1) as u8, at(2) as u8), 255),
+                    _ => (luminance(at(0) as u8, at(1) as u8, at(2) as u8), at(3) as u8),
+                };
+                pixels.push(((gray as u32 * alpha as u32 + 255 * (255 - alpha as u32)) / 255) as u8);
+            }
+            previous = row;
+        }
+        Ok(Image { width, height, pixels })
+    }
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let (a, b, c) = ((estimate - left as i16).abs(), (estimate - up as i16).abs(), (estimate - up_left as i16).abs());
+    if a <= b && a <= c {
+        left
+    } else if b <= c {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Sample `index` of a row; 16-bit samples keep their high byte.
+fn sample(row: &[u8], index: usize, depth: u8) -> u16 {
+    match depth {
+        16 => row[2 * index] as u16,
+        8 => row[index] as u16,
+        _ => {
+            let bit = index * depth as usize;
+            (row[bit / 8] >> (8 - depth as usize - bit % 8)) as u16 & ((1 << depth) - 1)
+        }
+    }
+}
+
+/// A gray sample of fewer than 8 bits, stretched to 0..=255.
+fn scale(value: u16, depth: u8) -> u8 {
+    match depth {
+        1 | 2 | 4 => (value * 255 / ((1 << depth) - 1)) as u8,
+        _ => value as u8,
+    }
+}
+
+fn luminance(r: u8, g: u8, b: u8) -> u8 {
+    ((299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000) as u8
+}
@@ -0,0 +1,12 @@
+//! JSON files for proofs of reserves (`ct por create`, `ct por verify`):
+//!
+//! ```json
+//! { "block": "<hash of the block the outputs are unspent after>",
+//!   "amount": 40,
+//!   "outputs": ["txid:0", "txid:1"],
+//!   "ownership": { "announcement": [...], "response": [...] },
+//!   "surplus": "<range proof for S - amount*G>" }
+//! ```
+//!
+//! `surplus` is the text encoding printed by `ct rangeproof create`, as serde writes a
+//! `reserves::ReservesProof` (`slate_file::to_json`).
@@ -0,0 +1,56 @@
+//! Reading answers from stdin for the interactive modes (`demo --interactive`, `quiz`)
+//! and the wallet passphrase.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::Command;
+
+/// Wait for Enter. Returns false at end of input.
+pub fn pause(prompt: &str) -> bool {
+    print!("{}", prompt);
+    io::stdout().flush().ok();
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line).unwrap_or(0) > 0
+}
+
+/// Ask until the answer is y/yes/n/no. None at end of input.
+pub fn ask_yes_no(prompt: &str) -> Option<bool> {
+    loop {
+        print!("{}", prompt);
+        io::stdout().flush().ok();
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line).unwrap_or(0) == 0 {
+            return None;
+        }
+        match line.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Some(true),
+            "n" | "no" => return Some(false),
+            _ => println!("  Please answer y or n."),
+        }
+    }
+}
+
+pub fn yes_no(answer: bool) -> &'static str {
+    if answer {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// Read a line without echoing it when stdin is a terminal. The prompt goes to
+/// stderr so it stays out of `--json` output. None at end of input.
+pub fn ask_secret(prompt: &str) -> Option<String> {
+    eprint!("{}", prompt);
+    io::stderr().flush().ok();
+    let terminal = io::stdin().is_terminal();
+    if terminal {
+        Command::new("stty").arg("-echo").status().ok();
+    }
+    let mut line = String::new();
+    let read = io::stdin().lock().read_line(&mut line).unwrap_or(0);
+    if terminal {
+        Command::new("stty").arg("echo").status().ok();
+        eprintln!();
+    }
+    (read > 0).then(|| line.trim_end_matches(['\r', '\n']).to_string())
+}
@@ -0,0 +1,703 @@
+//! QR codes (ISO/IEC 18004), to carry a slate, a transaction or an address from one
+//! device to another the way mobile wallets do: `ct qr encode` draws one in the
+//! terminal or as a PNG (`png`), `ct qr decode` reads it back.
+//!
+//! A symbol of version 1 to 40 is a square of 4*version + 17 modules. Function
+//! patterns have fixed places: three finders in the corners, timing lines between
+//! them, alignment patterns, and two copies of the format information (error
+//! correction level and mask, BCH-protected); from version 7 the version is spelled
+//! out too. The data fills the rest in two-module-wide columns, zigzagging up and down
+//! from the bottom right: a byte-mode segment (mode 0100, the length, the bytes),
+//! padded to capacity, cut into blocks that each get Reed-Solomon error correction
+//! over GF(256), then interleaved. Last, one of eight masks is XORed over the data
+//! modules, whichever scores the lowest penalty (long runs, 2x2 squares, finder
+//! look-alikes, too much dark or light). Level M survives 15% of the codewords being
+//! damaged and L 7%; data is put at M unless it only fits at L.
+//!
+//! Decoding reads a clean grid as drawn here, not a photo: it finds the symbol inside
+//! its quiet zone, reads numeric, alphanumeric and byte segments, and checks the error
+//! correction, but does not repair damage.
+
+use crate::error::CtError;
+use crate::png::Image;
+
+/// Error correction levels, in the order of the tables below.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    L,
+    M,
+    Q,
+    H,
+}
+
+impl Level {
+    /// The two bits the format information gives the level as.
+    fn bits(self) -> u32 {
+        match self {
+            Level::L => 1,
+            Level::M => 0,
+            Level::Q => 3,
+            Level::H => 2,
+        }
+    }
+}
+
+/// Error correction codewords per block, by level and version.
+const ECC_PER_BLOCK: [[u8; 41]; 4] = [
+    [
+        0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28, 30, 30, 26, 28, 30,
+        30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+    [
+        0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28,
+        28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+    ],
+    [
+        0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30, 30, 30, 30, 28, 30,
+        30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+    [
+        0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24, 30, 30, 30, 30, 30,
+        30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+];
+
+/// Error correction blocks, by level and version.
+const BLOCKS: [[u8; 41]; 4] = [
+    [
+        0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13, 14, 15, 16, 17, 18,
+        19, 19, 20, 21, 22, 24, 25,
+    ],
+    [
+        0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29,
+        31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+    ],
+    [
+        0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29, 34, 34, 35, 38, 40,
+        43, 45, 48, 51, 53, 56, 59, 62, 65, 68,
+    ],
+    [
+        0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32, 35, 37, 40, 42, 45, 48,
+        51, 54, 57, 60, 63, 66, 70, 74, 77, 81,
+    ],
+];
+
+const ALPHANUMERIC: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+/// A finder's 1:1:3:1:1 profile with four light modules after it, which the penalty
+/// counts (forwards and backwards) because it makes scanners see a fourth finder.
+const FINDER_LIKE: [bool; 11] = [true, false, true, true, true, false, true, false, false, false, false];
+/// Light modules around the symbol in a PNG, as the standard asks.
+const QUIET_ZONE: usize = 4;
+
+fn bit(value: u32, i: usize) -> bool {
+    value >> i & 1 == 1
+}
+
+fn push_bits(bits: &mut Vec<bool>, value: u32, len: usize) {
+    bits.extend((0..len).rev().map(|i| bit(value, i)));
+}
+
+/// Modules left for data and error correction once the function patterns are placed.
+fn raw_modules(version: usize) -> usize {
+    let mut modules = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        modules -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            modules -= 36;
+        }
+    }
+    modules
+}
+
+/// How the codewords split into blocks: the number of blocks, how many of them are
+/// one data codeword short, the length of those, and the error correction per block.
+fn blocks(version: usize, level: Level) -> (usize, usize, usize, usize) {
+    let count = BLOCKS[level as usize][version] as usize;
+    let raw = raw_modules(version) / 8;
+    (count, count - raw % count, raw / count, ECC_PER_BLOCK[level as usize][version] as usize)
+}
+
+fn data_codewords(version: usize, level: Level) -> usize {
+    let (count, _, _, ecc) = blocks(version, level);
+    raw_modules(version) / 8 - count * ecc
+}
+
+/// Bits in a segment's length field, by mode (numeric, alphanumeric, byte) and version.
+fn count_bits(mode: usize, version: usize) -> usize {
+    let widths = [[10, 12, 14], [9, 11, 13], [8, 16, 16]];
+    widths[mode][match version {
+        1..=9 => 0,
+        10..=26 => 1,
+        _ => 2,
+    }]
+}
+
+/// Multiplication in GF(256), modulo x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_mul(a: u8, b: u8) -> u8 {
+    let mut product = 0u16;
+    for i in (0..8).rev() {
+        product = (product << 1) ^ ((product >> 7) * 0x11d);
+        product ^= (b >> i & 1) as u16 * a as u16;
+    }
+    product as u8
+}
+
+/// (x - 1)(x - 2)(x - 4)...(x - 2^(degree-1)), highest coefficient first and its
+/// leading 1 left out.
+fn rs_divisor(degree: usize) -> Vec<u8> {
+    let mut divisor = vec![0; degree];
+    divisor[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            divisor[j] = gf_mul(divisor[j], root);
+            if j + 1 < degree {
+                divisor[j] ^= divisor[j + 1];
+            }
+        }
+        root = gf_mul(root, 2);
+    }
+    divisor
+}
+
+/// The error correction codewords: the data, as a polynomial, mod the divisor.
+fn rs_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut remainder = vec![0; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ remainder.remove(0);
+        remainder.push(0);
+        for (r, &d) in remainder.iter_mut().zip(divisor) {
+            *r ^= gf_mul(d, factor);
+        }
+    }
+    remainder
+}
+
+/// Give every block its error correction and interleave them: first codeword of
+/// every block, then the second, and so on. Short blocks have a gap at their end of
+/// data, so the error correction of all blocks lines up.
+fn add_error_correction(data: &[u8], version: usize, level: Level) -> Vec<u8> {
+    let (count, short, short_len, ecc) = blocks(version, level);
+    let divisor = rs_divisor(ecc);
+    let mut rest = data;
+    let padded: Vec<Vec<u8>> = (0..count)
+        .map(|j| {
+            let (block, tail) = rest.split_at(short_len - ecc + usize::from(j >= short));
+            rest = tail;
+            let mut padded = block.to_vec();
+            if j < short {
+                padded.push(0);
+            }
+            padded.extend(rs_remainder(block, &divisor));
+            padded
+        })
+        .collect();
+    let mut out = Vec::new();
+    for i in 0..=short_len {
+        for (j, block) in padded.iter().enumerate() {
+            if i != short_len - ecc || j >= short {
+                out.push(block[i]);
+            }
+        }
+    }
+    out
+}
+
+/// Undo `add_error_correction`, checking every block's error correction.
+fn remove_error_correction(codewords: &[u8], version: usize, level: Level) -> Result<Vec<u8>, CtError> {
+    let (count, short, short_len, ecc) = blocks(version, level);
+    let mut next = codewords.iter().copied();
+    let mut padded = vec![Vec::new(); count];
+    for i in 0..=short_len {
+        for (j, block) in padded.iter_mut().enumerate() {
+            let gap = i == short_len - ecc && j < short;
+            block.push(if gap { 0 } else { next.next().expect("the symbol holds every codeword") });
+        }
+    }
+    let divisor = rs_divisor(ecc);
+    let mut data = Vec::new();
+    for (j, block) in padded.iter().enumerate() {
+        let block_data = &block[..short_len - ecc + usize::from(j >= short)];
+        if rs_remainder(block_data, &divisor) != block[short_len + 1 - ecc..] {
+            return Err(CtError::MalformedInput(format!(
+                "QR block {} of {} is damaged (this reader checks the error correction but does not repair)",
+                j + 1,
+                count
+            )));
+        }
+        data.extend_from_slice(block_data);
+    }
+    Ok(data)
+}
+
+/// Where alignment patterns are centred, on both axes.
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let count = version / 7 + 2;
+    let step = (version * 8 + count * 3 + 5) / (count * 4 - 4) * 2;
+    let mut positions = vec![6];
+    positions.extend((0..count - 1).rev().map(|i| 4 * version + 10 - i * step));
+    positions
+}
+
+/// The 15 format bits: level and mask, 10 bits of BCH code, XORed with a fixed pattern.
+fn format_bits(level: Level, mask: usize) -> u32 {
+    let data = level.bits() << 3 | mask as u32;
+    let mut remainder = data;
+    for _ in 0..10 {
+        remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+    }
+    (data << 10 | remainder) ^ 0x5412
+}
+
+/// Does mask `mask` flip the module at (x, y)?
+fn masked(mask: usize, x: usize, y: usize) -> bool {
+    match mask {
+        0 => (x + y).is_multiple_of(2),
+        1 => y.is_multiple_of(2),
+        2 => x.is_multiple_of(3),
+        3 => (x + y).is_multiple_of(3),
+        4 => (x / 3 + y / 2).is_multiple_of(2),
+        5 => x * y % 2 + x * y % 3 == 0,
+        6 => (x * y % 2 + x * y % 3).is_multiple_of(2),
+        _ => ((x + y) % 2 + x * y % 3).is_multiple_of(2),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QrCode {
+    version: usize,
+    level: Level,
+    size: usize,
+    /// Dark modules, row by row.
+    modules: Vec<bool>,
+    /// Modules that belong to a function pattern rather than the data.
+    function: Vec<bool>,
+}
+
+impl QrCode {
+    /// The smallest code that holds `data` in one byte-mode segment.
+    pub fn encode(data: &[u8]) -> Result<QrCode, CtError> {
+        let fits = |version, level| 4 + count_bits(2, version) + 8 * data.len() <= 8 * data_codewords(version, level);
+        let (version, level) = [Level::M, Level::L]
+            .into_iter()
+            .find_map(|level| (1..=40).find(|&version| fits(version, level)).map(|version| (version, level)))
+            .ok_or_else(|| {
+                let most = data_codewords(40, Level::L) - 3;
+                CtError::MalformedInput(format!("{} bytes do not fit in a QR code, which holds {}", data.len(), most))
+            })?;
+        let capacity = 8 * data_codewords(version, level);
+        let mut bits = Vec::new();
+        push_bits(&mut bits, 0b0100, 4);
+        push_bits(&mut bits, data.len() as u32, count_bits(2, version));
+        for &byte in data {
+            push_bits(&mut bits, byte as u32, 8);
+        }
+        // A terminator of up to 4 zero bits, zeros to a whole byte, then alternate pad bytes.
+        let terminated = bits.len() + (capacity - bits.len()).min(4);
+        bits.resize(terminated.next_multiple_of(8), false);
+        let byte = |bits: &[bool]| bits.iter().fold(0, |b, &bit| b << 1 | bit as u8);
+        let mut codewords: Vec<u8> = bits.chunks(8).map(byte).collect();
+        for pad in [0xec, 0x11].into_iter().cycle().take(capacity / 8 - codewords.len()) {
+            codewords.push(pad);
+        }
+        let codewords = add_error_correction(&codewords, version, level);
+
+        let mut code = QrCode::blank(version, level);
+        for (i, (x, y)) in code.data_positions().into_iter().take(8 * codewords.len()).enumerate() {
+            code.modules[y * code.size + x] = bit(codewords[i / 8] as u32, 7 - i % 8);
+        }
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                code.apply_mask(mask);
+                code.draw_format(format_bits(level, mask));
+                let penalty = code.penalty();
+                code.apply_mask(mask);
+                penalty
+            })
+            .expect("there are eight masks");
+        code.apply_mask(mask);
+        code.draw_format(format_bits(level, mask));
+        Ok(code)
+    }
+
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Modules per side.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    /// A symbol with only its function patterns drawn, and the format information
+    /// light, to be filled in.
+    fn blank(version: usize, level: Level) -> QrCode {
+        let size = 4 * version + 17;
+        let mut code =
+            QrCode { version, level, size, modules: vec![false; size * size], function: vec![false; size * size] };
+        for i in 0..size {
+            code.set_function(6, i, i % 2 == 0);
+            code.set_function(i, 6, i % 2 == 0);
+        }
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            code.draw_square(x, y, 4, |ring| ring != 2 && ring != 4);
+        }
+        let positions = alignment_positions(version);
+        for &x in &positions {
+            for &y in &positions {
+                // The three corners with a finder have no alignment pattern.
+                let near = |p: usize| p == 6;
+                let far = |p: usize| p == size - 7;
+                if !(near(x) && near(y) || near(x) && far(y) || far(x) && near(y)) {
+                    code.draw_square(x, y, 2, |ring| ring != 1);
+                }
+            }
+        }
+        code.draw_format(0);
+        if version >= 7 {
+            let mut remainder = version as u32;
+            for _ in 0..12 {
+                remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1f25);
+            }
+            let bits = (version as u32) << 12 | remainder;
+            for i in 0..18 {
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                code.set_function(a, b, bit(bits, i));
+                code.set_function(b, a, bit(bits, i));
+            }
+        }
+        code
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    /// Concentric squares around (x, y) out to `radius`, each dark if `dark(ring)`.
+    fn draw_square(&mut self, x: usize, y: usize, radius: isize, dark: impl Fn(isize) -> bool) {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let (px, py) = (x as isize + dx, y as isize + dy);
+                if (0..self.size as isize).contains(&px) && (0..self.size as isize).contains(&py) {
+                    self.set_function(px as usize, py as usize, dark(dx.abs().max(dy.abs())));
+                }
+            }
+        }
+    }
+
+    /// The format bits, around the top left finder and split between the other two;
+    /// the module above the bottom left copy is always dark.
+    fn draw_format(&mut self, bits: u32) {
+        let size = self.size;
+        for (i, (x, y)) in format_positions(size).into_iter().enumerate() {
+            self.set_function(x, y, bit(bits, i % 15));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    fn read_format(&self) -> Result<(Level, usize), CtError> {
+        let mut copies = [0u32; 2];
+        for (i, (x, y)) in format_positions(self.size).into_iter().enumerate() {
+            copies[i / 15] |= (self.is_dark(x, y) as u32) << (i % 15);
+        }
+        // The valid word nearest to either copy: the BCH code corrects up to 3 bits.
+        let (distance, level, mask) = [Level::L, Level::M, Level::Q, Level::H]
+            .into_iter()
+            .flat_map(|level| (0..8).map(move |mask| (level, mask)))
+            .map(|(level, mask)| {
+                let bits = format_bits(level, mask);
+                let distance = copies.iter().map(|copy| (copy ^ bits).count_ones()).min().expect("two copies");
+                (distance, level, mask)
+            })
+            .min_by_key(|&(distance, ..)| distance)
+            .expect("32 format words");
+        if distance > 3 {
+            return Err(CtError::MalformedInput("the QR format information is unreadable".to_string()));
+        }
+        Ok((level, mask))
+    }
+
+    /// The data modules in the order the codewords' bits fill them, most significant
+    /// bit first: up and down two-module columns from the right, skipping the vertical
+    /// timing line.
+    fn data_positions(&self) -> Vec<(usize, usize)> {
+        let mut positions = Vec::new();
+        let mut right = self.size - 1;
+        loop {
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for row in 0..self.size {
+                let y = if upward { self.size - 1 - row } else { row };
+                for x in [right, right - 1] {
+                    if !self.function[y * self.size + x] {
+                        positions.push((x, y));
+                    }
+                }
+            }
+            if right < 2 {
+                return positions;
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: usize) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if !self.function[y * self.size + x] && masked(mask, x, y) {
+                    self.modules[y * self.size + x] ^= true;
+                }
+            }
+        }
+    }
+
+    /// What the mask choice minimises: 3 + (n - 5) per run of n >= 5 same-coloured
+    /// modules in a row or column, 3 per 2x2 square of one colour, 40 per finder
+    /// look-alike, and 10 per 5% that dark modules are off half.
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+        for line in 0..size {
+            for across in [true, false] {
+                let at = |i: usize| if across { self.is_dark(i, line) } else { self.is_dark(line, i) };
+                let mut run = 1;
+                for i in 1..=size {
+                    if i < size && at(i) == at(i - 1) {
+                        run += 1;
+                        continue;
+                    }
+                    if run >= 5 {
+                        penalty += run - 2;
+                    }
+                    run = 1;
+                }
+                for i in 0..=size - FINDER_LIKE.len() {
+                    let forwards = (0..FINDER_LIKE.len()).all(|k| at(i + k) == FINDER_LIKE[k]);
+                    let backwards = (0..FINDER_LIKE.len()).all(|k| at(i + k) == FINDER_LIKE[FINDER_LIKE.len() - 1 - k]);
+                    penalty += 40 * (forwards as usize + backwards as usize);
+                }
+            }
+        }
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = self.is_dark(x, y);
+                if [(x + 1, y), (x, y + 1), (x + 1, y + 1)].iter().all(|&(x, y)| self.is_dark(x, y) == dark) {
+                    penalty += 3;
+                }
+            }
+        }
+        let (dark, total) = (self.modules.iter().filter(|&&dark| dark).count(), size * size);
+        let off = (dark * 20).abs_diff(total * 10).div_ceil(total);
+        penalty + 10 * off.saturating_sub(1)
+    }
+
+    /// Read the data back out of a symbol's modules (row by row, dark is true).
+    fn from_modules(size: usize, modules: Vec<bool>) -> Result<Vec<u8>, CtError> {
+        if !(21..=177).contains(&size) || size % 4 != 1 {
+            return Err(CtError::MalformedInput(format!(
+                "a QR code is 21 to 177 modules wide in steps of 4, and this one is {}",
+                size
+            )));
+        }
+        let version = (size - 17) / 4;
+        let mut code = QrCode::blank(version, Level::L);
+        code.modules = modules;
+        let (level, mask) = code.read_format()?;
+        code.apply_mask(mask);
+        let codewords: Vec<u8> = code
+            .data_positions()
+            .chunks(8)
+            .take(raw_modules(version) / 8)
+            .map(|byte| byte.iter().fold(0, |b, &(x, y)| b << 1 | code.is_dark(x, y) as u8))
+            .collect();
+        read_segments(&remove_error_correction(&codewords, version, level)?, version)
+    }
+
+    /// Light modules drawn as blocks, two rows to a line, for a terminal with a dark
+    /// background, inside a quiet zone of 2 modules (3 below, to fill the last line).
+    pub fn to_text(&self) -> String {
+        let size = self.size as isize;
+        let inside = |x: isize, y: isize| (0..size).contains(&x) && (0..size).contains(&y);
+        let light = |x: isize, y: isize| !(inside(x, y) && self.is_dark(x as usize, y as usize));
+        let mut text = String::new();
+        for y in (-2..size + 3).step_by(2) {
+            for x in -2..size + 2 {
+                text.push(match (light(x, y), light(x, y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Black modules on white, `scale` pixels each, with the standard's 4-module quiet zone.
+    pub fn to_image(&self, scale: usize) -> Image {
+        let side = (self.size + 2 * QUIET_ZONE) * scale;
+        let dark = |x: usize, y: usize| {
+            let (x, y) = (x.wrapping_sub(QUIET_ZONE), y.wrapping_sub(QUIET_ZONE));
+            x < self.size && y < self.size && self.is_dark(x, y)
+        };
+        let pixels = (0..side * side).map(|i| if dark(i % side / scale, i / side / scale) { 0 } else { 255 }).collect();
+        Image { width: side, height: side, pixels }
+    }
+}
+
+/// Where the 15 format bits go, least significant first: the copy around the top left
+/// finder, then the one split between the top right and bottom left.
+fn format_positions(size: usize) -> Vec<(usize, usize)> {
+    let mut positions: Vec<(usize, usize)> = (0..6).map(|i| (8, i)).collect();
+    positions.extend([(8, 7), (8, 8), (7, 8)]);
+    positions.extend((9..15).map(|i| (14 - i, 8)));
+    positions.extend((0..8).map(|i| (size - 1 - i, 8)));
+    positions.extend((8..15).map(|i| (8, size - 15 + i)));
+    positions
+}
+
+/// Bits of the data codewords, most significant first.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn remaining(&self) -> usize {
+        8 * self.bytes.len() - self.pos
+    }
+
+    fn read(&mut self, len: usize) -> Result<u32, CtError> {
+        if len > self.remaining() {
+            return Err(CtError::MalformedInput("the QR data stops inside a segment".to_string()));
+        }
+        let value = (self.pos..self.pos + len).fold(0, |v, i| v << 1 | (self.bytes[i / 8] >> (7 - i % 8) & 1) as u32);
+        self.pos += len;
+        Ok(value)
+    }
+}
+
+/// The segments' contents, one after the other, up to the terminator.
+fn read_segments(data: &[u8], version: usize) -> Result<Vec<u8>, CtError> {
+    let mut reader = BitReader { bytes: data, pos: 0 };
+    let mut out = Vec::new();
+    while reader.remaining() >= 4 {
+        match reader.read(4)? {
+            0 => break,
+            0b0001 => {
+                let mut count = reader.read(count_bits(0, version))? as usize;
+                while count > 0 {
+                    let digits = count.min(3);
+                    let value = reader.read([4, 7, 10][digits - 1])?;
+                    let text = format!("{:0width$}", value, width = digits);
+                    if text.len() != digits {
+                        return Err(CtError::MalformedInput(format!("{} is not {} digit(s)", value, digits)));
+                    }
+                    out.extend(text.bytes());
+                    count -= digits;
+                }
+            }
+            0b0010 => {
+                let mut count = reader.read(count_bits(1, version))? as usize;
+                while count > 0 {
+                    let pair = count >= 2;
+                    let value = reader.read(if pair { 11 } else { 6 })? as usize;
+                    let chars = if pair { vec![value / 45, value % 45] } else { vec![value] };
+                    for c in chars {
+                        out.push(*ALPHANUMERIC.get(c).ok_or_else(|| {
+                            CtError::MalformedInput(format!("{} is not an alphanumeric QR character", c))
+                        })?);
+                    }
+                    count -= if pair { 2 } else { 1 };
+                }
+            }
+            0b0100 => {
+                let count = reader.read(count_bits(2, version))?;
+                for _ in 0..count {
+                    out.push(reader.read(8)? as u8);
+                }
+            }
+            mode => {
+                return Err(CtError::MalformedInput(format!("QR segment mode {:04b} is not one this reads", mode)))
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// The bounding box of the dark inside the light around it, as (left, top, right,
+/// bottom), inclusive: the symbol inside its quiet zone, whatever lies outside that.
+fn symbol_bounds(width: usize, height: usize, dark: impl Fn(usize, usize) -> bool) -> Option<[usize; 4]> {
+    let bounds = |wanted: bool, [left, top, right, bottom]: [usize; 4]| {
+        (top..=bottom).flat_map(|y| (left..=right).map(move |x| (x, y))).filter(|&(x, y)| dark(x, y) == wanted).fold(
+            None,
+            |found: Option<[usize; 4]>, (x, y)| {
+                let [l, t, r, b] = found.unwrap_or([x, y, x, y]);
+                Some([l.min(x), t.min(y), r.max(x), b.max(y)])
+            },
+        )
+    };
+    bounds(true, bounds(false, [0, 0, width.checked_sub(1)?, height.checked_sub(1)?])?)
+}
+
+/// Read a code back from text `to_text` drew; lines of anything else are skipped.
+pub fn decode_text(text: &str) -> Result<Vec<u8>, CtError> {
+    let drawn = |line: &&str| line.contains(['█', '▀', '▄']) && line.chars().all(|c| "█▀▄ ".contains(c));
+    let lines: Vec<Vec<char>> = text.lines().filter(drawn).map(|line| line.chars().collect()).collect();
+    let width = lines.iter().map(Vec::len).max().unwrap_or(0);
+    // Each character is two modules, one above the other; light is drawn.
+    let dark = |x: usize, y: usize| {
+        !matches!((lines[y / 2].get(x), y % 2), (Some('█'), _) | (Some('▀'), 0) | (Some('▄'), 1))
+    };
+    let not_found = || CtError::MalformedInput("no QR code drawn by `ct qr encode` in the text".to_string());
+    let [left, top, right, bottom] = symbol_bounds(width, 2 * lines.len(), dark).ok_or_else(not_found)?;
+    let size = right - left + 1;
+    if bottom - top + 1 != size {
+        return Err(CtError::MalformedInput(format!("the code is {} x {} modules, not square", size, bottom - top + 1)));
+    }
+    let modules = (0..size * size).map(|i| dark(left + i % size, top + i / size)).collect();
+    QrCode::from_modules(size, modules)
+}
+
+/// Read a code back from an image: the one `to_image` drew, or any upright,
+/// uncropped picture of a code on a light background, at any scale. The size in
+/// modules comes from the timing pattern and the module centres from the bounds.
+pub fn decode_image(image: &Image) -> Result<Vec<u8>, CtError> {
+    let dark = |x: usize, y: usize| image.pixel(x, y) < 128;
+    let not_found = || CtError::MalformedInput("no QR code in the image".to_string());
+    let [left, top, right, bottom] = symbol_bounds(image.width, image.height, dark).ok_or_else(not_found)?;
+    let finder = (left..=right).take_while(|&x| dark(x, top)).count();
+    let (width, height) = ((right - left + 1) as f64, (bottom - top + 1) as f64);
+    // Row 6 and column 6 run finder, light, the timing pattern (dark first and last),
+    // light, finder, so their dark runs count the modules even in a scaled image.
+    let timing = finder * 13 / 14;
+    let runs = |line: &mut dyn Iterator<Item = bool>| {
+        line.fold((0, false), |(runs, before), dark| (runs + (dark && !before) as usize, dark)).0
+    };
+    let across = runs(&mut (left..=right).map(|x| dark(x, top + timing)));
+    let down = runs(&mut (top..=bottom).map(|y| dark(left + timing, y)));
+    if across < 5 || across != down {
+        return Err(not_found());
+    }
+    let size = 2 * (across - 2) + 15;
+    let centre = |i: usize, extent: f64| ((i as f64 + 0.5) * extent / size as f64) as usize;
+    let module = |i: usize| dark(left + centre(i % size, width), top + centre(i / size, height));
+    let modules = (0..size * size).map(module).collect();
+    QrCode::from_modules(size, modules)
+}
@@ -0,0 +1,120 @@
+//! `ct quiz`: random transactions and range proofs, some of them broken. For each one
+//! the user sees what the wallet knows and predicts whether the verifier's check
+//! passes; the answer is then computed with the real checks.
+
+use pedersen_commitment_tx::commitment::{pedersen_commit, verify_balance, Blinding, Scalar};
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::params;
+use pedersen_commitment_tx::range_proof::{range_proof_create_unchecked, range_proof_verify};
+use pedersen_commitment_tx::rng::ToyRng;
+
+use crate::prompt::{ask_yes_no, yes_no};
+
+/// One generated question: what to show, the check's real outcome, and why.
+struct Question {
+    setup: Vec<String>,
+    ask: &'static str,
+    result: Result<(), CtError>,
+    explanation: String,
+}
+
+/// A one-input, two-output payment, possibly with the amounts or blindings off.
+fn balance_question(rng: &mut ToyRng) -> Question {
+    let input = rng.between(10, 200);
+    let to_bob = rng.between(1, input);
+    let r_input = Blinding::random(rng);
+    let r_bob = Blinding::random(rng);
+    let (mut change, mut r_change) = (input - to_bob, r_input - r_bob);
+    let explanation = match rng.next_u64() % 3 {
+        0 => "amounts and blindings both balance, so the commitments do too.".to_string(),
+        1 => {
+            let skew = rng.between(1, 4);
+            change += skew;
+            format!("the outputs add up to {} more than the input, so v*G does not cancel.", skew)
+        }
+        _ => {
+            let skew = rng.between(1, 1000);
+            r_change = Blinding::new(r_change.as_i64() + skew);
+            format!("the amounts balance but the blindings are off by {}, so r*H does not cancel.", skew)
+        }
+    };
+    let c_input = pedersen_commit(Scalar::new(input), r_input);
+    let c_bob = pedersen_commit(Scalar::new(to_bob), r_bob);
+    let c_change = pedersen_commit(Scalar::new(change), r_change);
+    Question {
+        setup: vec![
+            format!("input:  v = {:>3}, r = {}", input, r_input),
+            format!("bob:    v = {:>3}, r = {}", to_bob, r_bob),
+            format!("change: v = {:>3}, r = {}", change, r_change),
+        ],
+        ask: "Will verify_balance(C_input = C_bob + C_change) pass?",
+        result: verify_balance(&[c_input], &[c_bob, c_change]),
+        explanation,
+    }
+}
+
+/// A range proof that may hide an out-of-range value or be checked against the wrong commitment.
+fn range_question(rng: &mut ToyRng) -> Question {
+    let num_bits = params::active().range_bits;
+    let bound = 1i64 << num_bits;
+    let blinding = Blinding::random(rng);
+    let kind = rng.next_u64() % 4;
+    let value = match kind {
+        1 => -rng.between(1, 50),
+        2 => rng.between(bound, bound + 100),
+        _ => rng.between(0, bound),
+    };
+    let commitment = pedersen_commit(Scalar::new(value), blinding);
+    // Proofs are made without the range check, the way an attacker would.
+    let proof = range_proof_create_unchecked(Scalar::new(value), blinding, rng);
+    let mut setup = vec![format!("prover: v = {}, r = {}, C = {}", value, blinding, commitment)];
+    let (checked, explanation) = if kind == 3 {
+        let other = pedersen_commit(Scalar::new(value), Blinding::random(rng));
+        setup.push(format!("verifier checks π against a different C = {} (same v, another r)", other));
+        (other, "π is bound to the commitment it was made for; the bits recompose to the original C.".to_string())
+    } else if (0..bound).contains(&value) {
+        (commitment, format!("{} is in [0, {}), so every bit is 0 or 1 and they recompose to C.", value, bound))
+    } else {
+        let encoded = value.rem_euclid(bound);
+        (
+            commitment,
+            format!("{} bits can only encode {} as {}, so the bits recompose to a different commitment.", num_bits, value, encoded),
+        )
+    };
+    Question {
+        setup,
+        ask: "Will the range proof verify?",
+        result: range_proof_verify(checked, &proof),
+        explanation,
+    }
+}
+
+/// Ask `rounds` random questions and print the score.
+pub fn run(rounds: u32, mut rng: ToyRng) {
+    println!("ct quiz — predict whether each check passes. Range proofs cover 0 <= v < {}.", 1i64 << params::active().range_bits);
+    let (mut correct, mut asked) = (0, 0);
+    for round in 1..=rounds {
+        let question = if rng.next_u64().is_multiple_of(2) { balance_question(&mut rng) } else { range_question(&mut rng) };
+        println!("\nQuestion {}/{}", round, rounds);
+        for line in &question.setup {
+            println!("  {}", line);
+        }
+        let Some(guess) = ask_yes_no(&format!("{} [y/n] ", question.ask)) else {
+            println!();
+            break;
+        };
+        let answer = question.result.is_ok();
+        asked += 1;
+        if guess == answer {
+            correct += 1;
+            println!("  Correct: {}.", yes_no(answer));
+        } else {
+            println!("  Wrong: the answer is {}.", yes_no(answer));
+        }
+        if let Err(e) = &question.result {
+            println!("  The verifier says: {}.", e);
+        }
+        println!("  Why: {}", question.explanation);
+    }
+    println!("\nScore: {} of {}.", correct, asked);
+}
@@ -0,0 +1,701 @@
+//! Bit-decomposition range proof: proves 0 <= v < 2^n for C = v*G + r*H
+//! without revealing v or r (n = RANGE_BITS unless `params` says otherwise).
+//! Two of them prove min <= v <= max (`IntervalProof`), and one `AggregateRangeProof`
+//! covers several commitments at once, even when different parties hold them (`mpc`).
+//!
+//! A rewindable proof (as in Grin) takes all its randomness from a nonce the owner
+//! can recompute from the wallet key. Replaying it gives every bit's OR-proof nonce
+//! k back, and the real branch's response s = k + e*x then yields the bit and its
+//! blinding x. So the owner recovers (v, r) from the proof alone, while to everyone
+//! else the proof looks like any other.
+//!
+//! A joint proof covers an output whose blinding r_a + r_b is split between two
+//! parties (see `multisig`). Party A, who knows v and r_a, makes every bit as usual
+//! and puts r_b*H into C_0, so C_0's blinding is r_0 + r_b. Bit 0's real branch is a
+//! two-party Schnorr proof: its announcement is k_a*H + k_b*H, A answers k_a + e*r_0
+//! and B adds k_b + e*r_b (`joint_range_proof_finish`). Neither learns the other's share.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::commitment::{
+    lin_comb, mod_inverse, mod_mul, mod_reduce, pedersen_commit, Blinding, Commitment, Opening, Scalar,
+};
+use crate::error::CtError;
+use crate::params;
+use crate::rng::ToyRng;
+use crate::sigma::{self, All, CompactProof, Either, Equation, Or, Representation, Sigma, SigmaProof};
+use crate::transcript::Transcript;
+use crate::wire::{from_hex, to_hex, Encode};
+
+/// Default number of bits covered by the range proof: it proves 0 <= v < 2^RANGE_BITS.
+/// Real systems use 64; 8 keeps the per-bit structure small enough to print.
+/// The active value is `params::active().range_bits`.
+pub const RANGE_BITS: u32 = 8;
+
+/// Semantic statement a range proof establishes: 0 <= v < 2^n.
+/// The prover knows v and could just check this; the point of the proof below is
+/// to convince a verifier who only sees C.
+pub fn range_proof(value: Scalar) -> bool {
+    (0..1i64 << params::active().range_bits).contains(&value.as_i64())
+}
+
+/// Proof for one bit: a commitment C_i = b*G + r_i*H and an OR-proof that b is 0 or 1.
+/// The OR-proof shows "C_i = x*H" (b = 0) OR "C_i - G = x*H" (b = 1) for some known x,
+/// without revealing which branch is true: one branch is proven for real, the other is
+/// simulated, and the two sub-challenges must add up to the hash challenge e.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BitProof {
+    pub commitment: Commitment,
+    pub t0: i64,
+    pub t1: i64,
+    pub e0: i64,
+    pub e1: i64,
+    pub s0: i64,
+    pub s1: i64,
+}
+
+/// Bit-decomposition range proof π for C = v*G + r*H.
+/// Contains one BitProof per bit of v. The bit blindings are chosen so that
+/// sum(2^i * C_i) = C exactly, which is what ties the bits to C.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeProof {
+    pub bits: Vec<BitProof>,
+}
+
+impl RangeProof {
+    /// Number of field elements in the proof (7 per bit).
+    pub fn num_elements(&self) -> usize {
+        self.bits.len() * 7
+    }
+
+    /// Text encoding for copy-pasting π between terminals: all field elements as
+    /// comma-separated decimals, 7 per bit (C_i, t0, t1, e0, e1, s0, s1).
+    /// `str::parse::<RangeProof>` reads it back.
+    pub fn encode(&self) -> String {
+        self.bits
+            .iter()
+            .flat_map(|b| [b.commitment.as_i64(), b.t0, b.t1, b.e0, b.e1, b.s0, b.s1])
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Reads `encode`'s decimals, or the hex of the wire bytes that `{:#}` prints.
+impl FromStr for RangeProof {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.contains(',') {
+            return RangeProof::from_bytes(&from_hex(s)?);
+        }
+        let numbers = s
+            .trim()
+            .split(',')
+            .map(|part| {
+                part.trim()
+                    .parse::<i64>()
+                    .map_err(|_| CtError::MalformedInput(format!("'{}' is not a number", part)))
+            })
+            .collect::<Result<Vec<i64>, CtError>>()?;
+        if numbers.len() % 7 != 0 {
+            return Err(CtError::MalformedInput(format!(
+                "range proof has {} numbers, expected a multiple of 7",
+                numbers.len()
+            )));
+        }
+        let bits = numbers
+            .chunks(7)
+            .map(|n| {
+                Ok(BitProof {
+                    commitment: n[0].to_string().parse()?,
+                    t0: n[1],
+                    t1: n[2],
+                    e0: n[3],
+                    e1: n[4],
+                    s0: n[5],
+                    s1: n[6],
+                })
+            })
+            .collect::<Result<Vec<BitProof>, CtError>>()?;
+        Ok(RangeProof { bits })
+    }
+}
+
+serde_as_text!(RangeProof => encode);
+
+/// A summary of the proof's size, or with `{:#}` the lowercase hex of its wire bytes,
+/// which is shorter than `encode` and reads back the same.
+impl fmt::Display for RangeProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return f.write_str(&to_hex(&self.to_bytes()));
+        }
+        write!(
+            f,
+            "[{} bit commitments + {} OR-proofs, {} numbers ≈ {} bytes]",
+            self.bits.len(),
+            self.bits.len(),
+            self.num_elements(),
+            self.num_elements() * 8
+        )
+    }
+}
+
+/// Statement for OR-branch `branch` of a bit commitment: C_i - branch*G (should equal x*H).
+fn bit_statement(bit_commitment: Commitment, branch: i64) -> i64 {
+    mod_reduce(bit_commitment.as_i64() as i128 - mod_mul(branch, params::active().g) as i128)
+}
+
+/// The OR statement for a bit commitment: C_i = x*H (branch 0) or C_i - G = x*H (branch 1).
+pub fn bit_statement_or(bit_commitment: Commitment) -> Or<Representation, Representation> {
+    let h = params::active().h;
+    let branch = |b| Representation { public: bit_statement(bit_commitment, b), bases: vec![h] };
+    Or(branch(0), branch(1))
+}
+
+/// Transcript for one bit: bound to the outer commitment, the bit position and C_i.
+fn bit_transcript(commitment: Commitment, index: usize, bit_commitment: Commitment) -> Transcript {
+    let mut transcript = Transcript::new("ct/range-proof-bit");
+    transcript.append("C", commitment.as_i64());
+    transcript.append("bit index", index as i64);
+    transcript.append("C_i", bit_commitment.as_i64());
+    transcript
+}
+
+/// Prove that `bit_commitment = bit*G + blinding*H` with bit in {0, 1} (OR-proof):
+/// the true branch is an ordinary Schnorr proof, the other one is simulated.
+fn bit_proof_create(
+    commitment: Commitment,
+    index: usize,
+    bit: i64,
+    blinding: i64,
+    rng: &mut ToyRng,
+) -> BitProof {
+    let bit_commitment = Commitment::from_i64(lin_comb(bit, blinding));
+    let witness = if bit == 0 { Either::Left(vec![blinding]) } else { Either::Right(vec![blinding]) };
+    let mut transcript = bit_transcript(commitment, index, bit_commitment);
+    let proof = sigma::prove(&bit_statement_or(bit_commitment), &mut transcript, &witness, rng);
+    let (t, r) = (&proof.announcement, &proof.response);
+    BitProof { commitment: bit_commitment, t0: t[0], t1: t[1], e0: r[0], e1: r[1], s0: r[2], s1: r[3] }
+}
+
+/// Check one bit's OR-proof: e0 + e1 = hash(...) and s_j*H = t_j + e_j*(C_i - j*G) for j = 0, 1.
+pub fn bit_proof_verify(commitment: Commitment, index: usize, proof: &BitProof) -> bool {
+    let mut transcript = bit_transcript(commitment, index, proof.commitment);
+    sigma::verify(&bit_statement_or(proof.commitment), &mut transcript, &bit_sigma_proof(proof))
+}
+
+/// A bit proof's numbers in the sigma layout: announcement [t0, t1], response [e0, e1, s0, s1].
+fn bit_sigma_proof(proof: &BitProof) -> SigmaProof {
+    SigmaProof { announcement: vec![proof.t0, proof.t1], response: vec![proof.e0, proof.e1, proof.s0, proof.s1] }
+}
+
+/// Prover: a standalone OR-proof that C = b*G + r*H with b in {0, 1}, without revealing b.
+/// It is exactly the proof for bit 0 of a one-bit range proof, so its challenge binds C.
+pub fn prove_bit(opening: &Opening, rng: &mut ToyRng) -> Result<BitProof, CtError> {
+    match opening.value.as_i64() {
+        bit @ (0 | 1) => Ok(bit_proof_create(opening.commit(), 0, bit, opening.blinding.as_i64(), rng)),
+        v if v < 0 => Err(CtError::NegativeValue(v)),
+        v => Err(CtError::OverflowedValue(v)),
+    }
+}
+
+/// Verifier: `proof` is an OR-proof for `commitment` itself.
+pub fn verify_bit(commitment: Commitment, proof: &BitProof) -> Result<(), CtError> {
+    if proof.commitment == commitment && bit_proof_verify(commitment, 0, proof) {
+        Ok(())
+    } else {
+        Err(CtError::InvalidProof("OR-proof fails".to_string()))
+    }
+}
+
+/// Prover: create π for C = value*G + blinding*H.
+/// An honest prover refuses values outside [0, 2^n); see
+/// `range_proof_create_unchecked` for what a cheating prover can still produce.
+pub fn range_proof_create(value: Scalar, blinding: Blinding, rng: &mut ToyRng) -> Result<RangeProof, CtError> {
+    check_value(value)?;
+    Ok(range_proof_create_unchecked(value, blinding, rng))
+}
+
+/// What an honest prover checks before proving 0 <= v < 2^n.
+pub fn check_value(value: Scalar) -> Result<(), CtError> {
+    let v = value.as_i64();
+    if v < 0 {
+        return Err(CtError::NegativeValue(v));
+    }
+    if !range_proof(value) {
+        return Err(CtError::OverflowedValue(v));
+    }
+    Ok(())
+}
+
+/// Steps 1 and 2 below: the bits of v and blindings for them that recompose to r.
+pub fn bit_openings(value: Scalar, blinding: Blinding, rng: &mut ToyRng) -> Vec<(i64, i64)> {
+    let num_bits = params::active().range_bits;
+    let encoded = value.as_i64().rem_euclid(1i64 << num_bits);
+
+    let mut bit_blindings: Vec<i64> = (0..num_bits).map(|_| rng.scalar()).collect();
+    let rest: i128 = (1..num_bits as usize)
+        .map(|i| mod_mul(1i64 << i, bit_blindings[i]) as i128)
+        .sum();
+    bit_blindings[0] = mod_reduce(blinding.as_i64() as i128 - rest);
+    bit_blindings.into_iter().enumerate().map(|(i, r)| ((encoded >> i) & 1, r)).collect()
+}
+
+/// Build π without checking the range first (the attack demo uses this).
+/// 1. Split v into n bits b_i (a negative v can only be encoded as v mod 2^n).
+/// 2. Pick random blindings r_1..r_{n-1} and set r_0 = r - sum(2^i * r_i) so the bit
+///    commitments recompose to exactly C.
+/// 3. Attach an OR-proof to every bit commitment.
+pub fn range_proof_create_unchecked(value: Scalar, blinding: Blinding, rng: &mut ToyRng) -> RangeProof {
+    let commitment = pedersen_commit(value, blinding);
+    let bits = bit_openings(value, blinding, rng)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (bit, bit_blinding))| bit_proof_create(commitment, i, bit, bit_blinding, rng))
+        .collect();
+    RangeProof { bits }
+}
+
+/// A seed for the randomness of a rewindable proof, from its nonce and a label.
+fn rewind_rng(nonce: &[u8; 32], label: &str, index: usize) -> ToyRng {
+    let mut hasher = Sha256::new();
+    hasher.update(label.as_bytes());
+    hasher.update(nonce);
+    hasher.update((index as u64).to_le_bytes());
+    ToyRng::new(u64::from_le_bytes(hasher.finalize()[..8].try_into().expect("SHA-256 has 32 bytes")))
+}
+
+/// Prover: π for C = value*G + blinding*H whose randomness all comes from `nonce`,
+/// so `range_proof_rewind` with the same nonce recovers the opening.
+pub fn range_proof_create_rewindable(
+    value: Scalar,
+    blinding: Blinding,
+    nonce: &[u8; 32],
+) -> Result<RangeProof, CtError> {
+    check_value(value)?;
+    let commitment = pedersen_commit(value, blinding);
+    let bits = bit_openings(value, blinding, &mut rewind_rng(nonce, "ct/rewind/bits", 0))
+        .into_iter()
+        .enumerate()
+        .map(|(i, (bit, bit_blinding))| {
+            bit_proof_create(commitment, i, bit, bit_blinding, &mut rewind_rng(nonce, "ct/rewind/bit", i))
+        })
+        .collect();
+    Ok(RangeProof { bits })
+}
+
+/// Owner: the opening of `commitment` if `proof` was made rewindable with `nonce`.
+/// Each bit's OR-proof draws the simulated challenge, the simulated response and
+/// then the real nonce k; the branch whose (e, s) were not drawn is the real one,
+/// and its s = k + e*x gives x = (s - k)/e. None for anyone else's proof.
+pub fn range_proof_rewind(commitment: Commitment, proof: &RangeProof, nonce: &[u8; 32]) -> Option<Opening> {
+    let (mut value, mut blinding) = (0i64, 0i128);
+    for (i, bit) in proof.bits.iter().enumerate() {
+        let mut rng = rewind_rng(nonce, "ct/rewind/bit", i);
+        let (simulated_challenge, simulated_response, k) = (rng.scalar(), rng.scalar(), rng.scalar());
+        let (b, e, s) = if (bit.e1, bit.s1) == (simulated_challenge, simulated_response) {
+            (0, bit.e0, bit.s0)
+        } else if (bit.e0, bit.s0) == (simulated_challenge, simulated_response) {
+            (1, bit.e1, bit.s1)
+        } else {
+            return None;
+        };
+        let x = mod_mul(mod_reduce(s as i128 - k as i128), mod_inverse(e));
+        value += b << i;
+        blinding += mod_mul(1i64 << i, x) as i128;
+    }
+    let opening = Opening::new(Scalar::new(value), Blinding::new(mod_reduce(blinding)));
+    (opening.commit() == commitment).then_some(opening)
+}
+
+/// Party A's part of a joint range proof for C = value*G + blinding*H + `cosigner`,
+/// where `cosigner` = r_b*H is the other party's share and `cosigner_nonce` = k_b*H
+/// their nonce for bit 0. Bit 0's real response still lacks k_b + e*r_b.
+pub fn joint_range_proof_start(
+    value: Scalar,
+    blinding: Blinding,
+    cosigner: Commitment,
+    cosigner_nonce: Commitment,
+    rng: &mut ToyRng,
+) -> Result<RangeProof, CtError> {
+    check_value(value)?;
+    let commitment = pedersen_commit(value, blinding) + cosigner;
+    let mut bits = Vec::new();
+    for (i, (bit, bit_blinding)) in bit_openings(value, blinding, rng).into_iter().enumerate() {
+        if i > 0 {
+            bits.push(bit_proof_create(commitment, i, bit, bit_blinding, rng));
+            continue;
+        }
+        let bit_commitment = Commitment::from_i64(lin_comb(bit, bit_blinding)) + cosigner;
+        let h = params::active().h;
+        let (simulated_challenge, simulated_response, k) = (rng.scalar(), rng.scalar(), rng.scalar());
+        let simulated = Representation { public: bit_statement(bit_commitment, 1 - bit), bases: vec![h] };
+        let simulated_announcement = simulated.recover(simulated_challenge, &[simulated_response])[0];
+        let real_announcement = mod_reduce(lin_comb(0, k) as i128 + cosigner_nonce.as_i64() as i128);
+        let (t0, t1) = if bit == 0 {
+            (real_announcement, simulated_announcement)
+        } else {
+            (simulated_announcement, real_announcement)
+        };
+        let mut transcript = bit_transcript(commitment, 0, bit_commitment);
+        transcript.append_all("announcement", &[t0, t1]);
+        let challenge = mod_reduce(transcript.challenge("e") as i128 - simulated_challenge as i128);
+        let response = mod_reduce(k as i128 + mod_mul(challenge, bit_blinding) as i128);
+        let (e0, e1, s0, s1) = if bit == 0 {
+            (challenge, simulated_challenge, response, simulated_response)
+        } else {
+            (simulated_challenge, challenge, simulated_response, response)
+        };
+        bits.push(BitProof { commitment: bit_commitment, t0, t1, e0, e1, s0, s1 });
+    }
+    Ok(RangeProof { bits })
+}
+
+/// Party B's part: add k_b + e*r_b to bit 0's real response, bit 0 of `value` saying
+/// which branch that is, and check the finished proof against `commitment`.
+pub fn joint_range_proof_finish(
+    commitment: Commitment,
+    value: Scalar,
+    mut proof: RangeProof,
+    blinding: Blinding,
+    nonce: Blinding,
+) -> Result<RangeProof, CtError> {
+    let bit = proof.bits.first_mut().ok_or_else(|| CtError::InvalidProof("the proof has no bits".to_string()))?;
+    let (challenge, response) = match value.as_i64() & 1 {
+        0 => (bit.e0, &mut bit.s0),
+        _ => (bit.e1, &mut bit.s1),
+    };
+    *response = mod_reduce(
+        *response as i128 + nonce.as_i64() as i128 + mod_mul(challenge, blinding.as_i64()) as i128,
+    );
+    range_proof_verify(commitment, &proof)?;
+    Ok(proof)
+}
+
+/// sum(2^i * C_i) over the bit commitments in π (mod p).
+pub fn range_proof_recompose(proof: &RangeProof) -> Commitment {
+    recompose(proof.bits.iter().map(|bit| bit.commitment))
+}
+
+/// sum(2^i * C_i) (mod p).
+pub fn recompose(bit_commitments: impl Iterator<Item = Commitment>) -> Commitment {
+    let sum: i128 = bit_commitments.enumerate().map(|(i, c)| mod_mul(1i64 << i, c.as_i64()) as i128).sum();
+    Commitment::from_i64(mod_reduce(sum))
+}
+
+/// Verifier: has only (C, π). Checks that π has n bits, every bit commitment
+/// hides 0 or 1, and the bits recompose to C. Together: C commits to a v in [0, 2^n).
+pub fn range_proof_verify(commitment: Commitment, proof: &RangeProof) -> Result<(), CtError> {
+    check_bit_count(proof)?;
+    if let Some(i) = (0..proof.bits.len()).find(|&i| !bit_proof_verify(commitment, i, &proof.bits[i])) {
+        return Err(CtError::InvalidProof(format!("OR-proof for bit {} fails", i)));
+    }
+    if range_proof_recompose(proof) != commitment {
+        return Err(CtError::InvalidProof("bit commitments do not recompose to C".to_string()));
+    }
+    Ok(())
+}
+
+fn check_bit_count(proof: &RangeProof) -> Result<(), CtError> {
+    let num_bits = params::active().range_bits;
+    if proof.bits.len() != num_bits as usize {
+        return Err(CtError::MalformedInput(format!(
+            "range proof has {} bits, expected {}",
+            proof.bits.len(),
+            num_bits
+        )));
+    }
+    Ok(())
+}
+
+/// sum(2^i * C_i) - C = 0.
+fn recompose_equation(commitment: Commitment, bit_commitments: &[Commitment]) -> Equation {
+    let bits = bit_commitments.iter().enumerate().map(|(i, c)| (1i64 << i, c.as_i64()));
+    bits.chain([(-1, commitment.as_i64())]).collect()
+}
+
+/// The equations `range_proof_verify` checks, for batch verification: both branches
+/// of every bit's OR-proof and the recomposition. What needs no group arithmetic
+/// (the bit count, each OR's sub-challenges) is checked here.
+pub fn range_proof_equations(commitment: Commitment, proof: &RangeProof) -> Result<Vec<Equation>, CtError> {
+    check_bit_count(proof)?;
+    let mut equations = Vec::new();
+    for (i, bit) in proof.bits.iter().enumerate() {
+        let mut transcript = bit_transcript(commitment, i, bit.commitment);
+        let bit_equations = sigma::equations(&bit_statement_or(bit.commitment), &mut transcript, &bit_sigma_proof(bit))
+            .ok_or_else(|| CtError::InvalidProof(format!("OR-proof for bit {} fails", i)))?;
+        equations.extend(bit_equations);
+    }
+    let bit_commitments: Vec<Commitment> = proof.bits.iter().map(|bit| bit.commitment).collect();
+    equations.push(recompose_equation(commitment, &bit_commitments));
+    Ok(equations)
+}
+
+/// Proof that C commits to a v with min <= v <= max: a range proof for v - min,
+/// whose commitment is C - min*G, and one for max - v, whose commitment is max*G - C.
+/// The verifier derives both commitments from C, so both proofs are about the same v.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntervalProof {
+    pub low: RangeProof,
+    pub high: RangeProof,
+}
+
+impl IntervalProof {
+    /// The two range proofs' encodings, separated by ';'.
+    pub fn encode(&self) -> String {
+        format!("{};{}", self.low.encode(), self.high.encode())
+    }
+}
+
+impl FromStr for IntervalProof {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split(';').collect::<Vec<_>>()[..] {
+            [low, high] => Ok(IntervalProof { low: low.parse()?, high: high.parse()? }),
+            _ => Err(CtError::MalformedInput("an interval proof is two range proofs separated by ';'".to_string())),
+        }
+    }
+}
+
+serde_as_text!(IntervalProof => encode);
+
+impl fmt::Display for IntervalProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return write!(f, "{:#};{:#}", self.low, self.high);
+        }
+        let elements = self.low.num_elements() + self.high.num_elements();
+        write!(f, "[2 range proofs, {} numbers ≈ {} bytes]", elements, elements * 8)
+    }
+}
+
+/// The interval must be non-empty and narrower than 2^n, or v - min and max - v
+/// could not both be in range for every v inside it.
+fn check_interval(min: i64, max: i64) -> Result<(), CtError> {
+    let num_bits = params::active().range_bits;
+    if min > max || max as i128 - min as i128 >= 1i128 << num_bits {
+        return Err(CtError::MalformedInput(format!(
+            "interval [{}, {}] must be non-empty and narrower than 2^{}",
+            min, max, num_bits
+        )));
+    }
+    Ok(())
+}
+
+/// The commitments to v - min and max - v, computed from C alone.
+fn interval_commitments(commitment: Commitment, min: i64, max: i64) -> (Commitment, Commitment) {
+    let (min, max) = (Commitment::from_i64(lin_comb(min, 0)), Commitment::from_i64(lin_comb(max, 0)));
+    (commitment - min, max - commitment)
+}
+
+/// Prover: prove min <= value <= max for C = value*G + blinding*H.
+/// v - min is committed with blinding r, max - v with -r.
+pub fn interval_proof_create(
+    value: Scalar,
+    blinding: Blinding,
+    min: i64,
+    max: i64,
+    rng: &mut ToyRng,
+) -> Result<IntervalProof, CtError> {
+    check_interval(min, max)?;
+    let v = value.as_i64();
+    if !(min..=max).contains(&v) {
+        return Err(CtError::OutOfInterval { value: v, min, max });
+    }
+    Ok(IntervalProof {
+        low: range_proof_create_unchecked(Scalar::new(v - min), blinding, rng),
+        high: range_proof_create_unchecked(Scalar::new(max - v), Blinding::default() - blinding, rng),
+    })
+}
+
+/// Verifier: both range proofs hold for the commitments derived from C.
+pub fn interval_proof_verify(commitment: Commitment, min: i64, max: i64, proof: &IntervalProof) -> Result<(), CtError> {
+    check_interval(min, max)?;
+    let (low, high) = interval_commitments(commitment, min, max);
+    let bound = |what: &str, result: Result<(), CtError>| {
+        result.map_err(|e| match e {
+            CtError::InvalidProof(reason) => CtError::InvalidProof(format!("{}: {}", what, reason)),
+            e => e,
+        })
+    };
+    bound("v - min", range_proof_verify(low, &proof.low))?;
+    bound("max - v", range_proof_verify(high, &proof.high))
+}
+
+/// One bit of an `AggregateRangeProof`: C_i and the OR-proof's response, without
+/// its announcements t0, t1 (the verifier recovers them from the challenge).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactBitProof {
+    pub commitment: Commitment,
+    pub e0: i64,
+    pub e1: i64,
+    pub s0: i64,
+    pub s1: i64,
+}
+
+/// One range proof for several commitments (e.g. every output of a transaction):
+/// n bit commitments per commitment, and a single proof for all of their OR
+/// statements under one challenge, sent as (e, responses). That is 5 numbers per
+/// bit plus e, against 7 per bit for separate proofs. Bulletproofs would shrink
+/// an aggregate to logarithmic size; this only drops the announcements.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AggregateRangeProof {
+    pub challenge: i64,
+    /// n bits for the first commitment, then n for the second, ...
+    pub bits: Vec<CompactBitProof>,
+}
+
+impl AggregateRangeProof {
+    pub fn num_elements(&self) -> usize {
+        1 + self.bits.len() * 5
+    }
+
+    /// Comma-separated decimals: e, then 5 per bit (C_i, e0, e1, s0, s1).
+    pub fn encode(&self) -> String {
+        let bits = self.bits.iter().flat_map(|b| [b.commitment.as_i64(), b.e0, b.e1, b.s0, b.s1]);
+        std::iter::once(self.challenge).chain(bits).map(|n| n.to_string()).collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Like `RangeProof`: decimals, or the hex of the wire bytes.
+impl FromStr for AggregateRangeProof {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.contains(',') {
+            return AggregateRangeProof::from_bytes(&from_hex(s)?);
+        }
+        let numbers = s
+            .trim()
+            .split(',')
+            .map(|part| {
+                part.trim()
+                    .parse::<i64>()
+                    .map_err(|_| CtError::MalformedInput(format!("'{}' is not a number", part)))
+            })
+            .collect::<Result<Vec<i64>, CtError>>()?;
+        let Some((&challenge, rest)) = numbers.split_first().filter(|(_, rest)| rest.len() % 5 == 0) else {
+            return Err(CtError::MalformedInput(format!(
+                "aggregate range proof has {} numbers, expected 1 + a multiple of 5",
+                numbers.len()
+            )));
+        };
+        let bits = rest
+            .chunks(5)
+            .map(|n| Ok(CompactBitProof { commitment: n[0].to_string().parse()?, e0: n[1], e1: n[2], s0: n[3], s1: n[4] }))
+            .collect::<Result<Vec<CompactBitProof>, CtError>>()?;
+        Ok(AggregateRangeProof { challenge, bits })
+    }
+}
+
+serde_as_text!(AggregateRangeProof => encode);
+
+impl fmt::Display for AggregateRangeProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return f.write_str(&to_hex(&self.to_bytes()));
+        }
+        write!(
+            f,
+            "[{} bit commitments + 1 joint proof, {} numbers ≈ {} bytes]",
+            self.bits.len(),
+            self.num_elements(),
+            self.num_elements() * 8
+        )
+    }
+}
+
+/// The OR statement of every bit, and a transcript bound to the commitments and all C_i.
+pub fn aggregate_statement(
+    commitments: &[Commitment],
+    bit_commitments: &[Commitment],
+) -> (All<Or<Representation, Representation>>, Transcript) {
+    let mut transcript = Transcript::new("ct/aggregate-range-proof");
+    transcript.append_all("C", &commitments.iter().map(|c| c.as_i64()).collect::<Vec<_>>());
+    transcript.append_all("C_i", &bit_commitments.iter().map(|c| c.as_i64()).collect::<Vec<_>>());
+    (All(bit_commitments.iter().map(|&c| bit_statement_or(c)).collect()), transcript)
+}
+
+/// Prover: one proof that every opening's value is in [0, 2^n). An honest prover
+/// refuses if any of them is not.
+pub fn aggregate_range_proof_create(openings: &[Opening], rng: &mut ToyRng) -> Result<AggregateRangeProof, CtError> {
+    for opening in openings {
+        check_value(opening.value)?;
+    }
+    let bits: Vec<(i64, i64)> =
+        openings.iter().flat_map(|opening| bit_openings(opening.value, opening.blinding, rng)).collect();
+    let bit_commitments: Vec<Commitment> =
+        bits.iter().map(|&(bit, blinding)| Commitment::from_i64(lin_comb(bit, blinding))).collect();
+    let witness = bits
+        .iter()
+        .map(|&(bit, blinding)| if bit == 0 { Either::Left(vec![blinding]) } else { Either::Right(vec![blinding]) })
+        .collect();
+    let commitments: Vec<Commitment> = openings.iter().map(Opening::commit).collect();
+    let (statement, mut transcript) = aggregate_statement(&commitments, &bit_commitments);
+    let proof = sigma::prove_compact(&statement, &mut transcript, &witness, rng);
+    let bits = bit_commitments
+        .iter()
+        .zip(proof.response.chunks(4))
+        .map(|(&commitment, r)| CompactBitProof { commitment, e0: r[0], e1: r[1], s0: r[2], s1: r[3] })
+        .collect();
+    Ok(AggregateRangeProof { challenge: proof.challenge, bits })
+}
+
+/// Verifier: n bits per commitment, each group recomposing to its commitment, and
+/// the joint proof that every bit commitment hides 0 or 1.
+pub fn aggregate_range_proof_verify(commitments: &[Commitment], proof: &AggregateRangeProof) -> Result<(), CtError> {
+    let num_bits = check_aggregate_bit_count(commitments, proof)?;
+    let bit_commitments: Vec<Commitment> = proof.bits.iter().map(|bit| bit.commitment).collect();
+    for (i, (&commitment, group)) in commitments.iter().zip(bit_commitments.chunks(num_bits)).enumerate() {
+        if recompose(group.iter().copied()) != commitment {
+            return Err(CtError::InvalidProof(format!("bit commitments do not recompose to commitment {}", i)));
+        }
+    }
+    let (statement, mut transcript) = aggregate_statement(commitments, &bit_commitments);
+    if sigma::verify_compact(&statement, &mut transcript, &aggregate_compact_proof(proof)) {
+        Ok(())
+    } else {
+        Err(CtError::InvalidProof("the joint OR-proof fails".to_string()))
+    }
+}
+
+/// n bits per commitment; returns n.
+fn check_aggregate_bit_count(commitments: &[Commitment], proof: &AggregateRangeProof) -> Result<usize, CtError> {
+    let num_bits = params::active().range_bits as usize;
+    if proof.bits.len() != commitments.len() * num_bits {
+        return Err(CtError::MalformedInput(format!(
+            "aggregate range proof has {} bits, expected {} for {} commitment(s)",
+            proof.bits.len(),
+            commitments.len() * num_bits,
+            commitments.len()
+        )));
+    }
+    Ok(num_bits)
+}
+
+fn aggregate_compact_proof(proof: &AggregateRangeProof) -> CompactProof {
+    CompactProof { challenge: proof.challenge, response: proof.bits.iter().flat_map(|b| [b.e0, b.e1, b.s0, b.s1]).collect() }
+}
+
+/// The equations `aggregate_range_proof_verify` checks, for batch verification.
+pub fn aggregate_range_proof_equations(
+    commitments: &[Commitment],
+    proof: &AggregateRangeProof,
+) -> Result<Vec<Equation>, CtError> {
+    let num_bits = check_aggregate_bit_count(commitments, proof)?;
+    let bit_commitments: Vec<Commitment> = proof.bits.iter().map(|bit| bit.commitment).collect();
+    let (statement, mut transcript) = aggregate_statement(commitments, &bit_commitments);
+    let mut equations = sigma::compact_equations(&statement, &mut transcript, &aggregate_compact_proof(proof))
+        .ok_or_else(|| CtError::InvalidProof("the joint OR-proof fails".to_string()))?;
+    for (&commitment, group) in commitments.iter().zip(bit_commitments.chunks(num_bits)) {
+        equations.push(recompose_equation(commitment, group));
+    }
+    Ok(equations)
+}
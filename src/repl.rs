@@ -0,0 +1,149 @@
+//! `ct repl`: explore commitments interactively. Every result gets a name
+//! (C1, C2, ... for commitments, piN for the range proof of CN) that later
+//! commands can refer to.
+
+use std::io::{self, BufRead, Write};
+
+use pedersen_commitment_tx::commitment::{pedersen_commit, verify_balance, Blinding, Commitment, Scalar};
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::range_proof::{range_proof_create, range_proof_verify, RangeProof};
+use pedersen_commitment_tx::rng::ToyRng;
+
+const HELP: &str = "\
+commands:
+  commit <v> <r>          C = v*G + r*H, stored as Cn
+  prove <v> <r>           C plus a range proof, stored as Cn and pin (same n)
+  add <C> <C>             homomorphic sum, stored as Cn
+  sub <C> <C>             homomorphic difference, stored as Cn
+  rangeproof <C> <pi>     verify a range proof against a commitment
+  balance <C,..> <C,..>   check sum(inputs) = sum(outputs)
+  list                    show everything stored so far
+  help                    this text
+  quit                    leave
+<C> is a name like C1 or a literal commitment number.";
+
+struct StoredCommitment {
+    name: String,
+    commitment: Commitment,
+    /// (v, r) when this session created the commitment, so `list` can show it.
+    opening: Option<(Scalar, Blinding)>,
+}
+
+struct Session {
+    commitments: Vec<StoredCommitment>,
+    proofs: Vec<(String, RangeProof)>,
+    rng: ToyRng,
+}
+
+impl Session {
+    fn new() -> Self {
+        Session { commitments: Vec::new(), proofs: Vec::new(), rng: ToyRng::from_entropy() }
+    }
+
+    fn store_commitment(&mut self, commitment: Commitment, opening: Option<(Scalar, Blinding)>) -> String {
+        let name = format!("C{}", self.commitments.len() + 1);
+        self.commitments.push(StoredCommitment { name: name.clone(), commitment, opening });
+        format!("{} = {}", name, commitment)
+    }
+
+    fn commitment(&self, token: &str) -> Result<Commitment, CtError> {
+        match self.commitments.iter().find(|c| c.name == token) {
+            Some(stored) => Ok(stored.commitment),
+            None => token.parse(),
+        }
+    }
+
+    fn proof(&self, token: &str) -> Result<&RangeProof, CtError> {
+        self.proofs
+            .iter()
+            .find(|(name, _)| name == token)
+            .map(|(_, proof)| proof)
+            .ok_or_else(|| CtError::MalformedInput(format!("no range proof named '{}'", token)))
+    }
+
+    fn commitment_list(&self, token: &str) -> Result<Vec<Commitment>, CtError> {
+        token.split(',').map(|t| self.commitment(t)).collect()
+    }
+
+    /// Run one command line and return what to print.
+    fn execute(&mut self, line: &str) -> Result<String, CtError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["help"] => Ok(HELP.to_string()),
+            ["commit", v, r] => {
+                let (value, blinding) = (parse_i64(v)?, parse_i64(r)?);
+                let (value, blinding) = (Scalar::new(value), Blinding::new(blinding));
+                Ok(self.store_commitment(pedersen_commit(value, blinding), Some((value, blinding))))
+            }
+            ["prove", v, r] => {
+                let (value, blinding) = (Scalar::new(parse_i64(v)?), Blinding::new(parse_i64(r)?));
+                let proof = range_proof_create(value, blinding, &mut self.rng)?;
+                let stored = self.store_commitment(pedersen_commit(value, blinding), Some((value, blinding)));
+                let name = format!("pi{}", self.commitments.len());
+                let line = format!("{}\n{} = {}", stored, name, proof);
+                self.proofs.push((name, proof));
+                Ok(line)
+            }
+            ["add", a, b] => {
+                let sum = self.commitment(a)? + self.commitment(b)?;
+                Ok(self.store_commitment(sum, None))
+            }
+            ["sub", a, b] => {
+                let difference = self.commitment(a)? - self.commitment(b)?;
+                Ok(self.store_commitment(difference, None))
+            }
+            ["rangeproof", c, pi] => {
+                let commitment = self.commitment(c)?;
+                range_proof_verify(commitment, self.proof(pi)?)?;
+                Ok(format!("valid: {} commits to a value in range", c))
+            }
+            ["balance", ins, outs] => {
+                verify_balance(&self.commitment_list(ins)?, &self.commitment_list(outs)?)?;
+                Ok("balanced: sum(inputs) = sum(outputs)".to_string())
+            }
+            ["list"] => {
+                let mut lines: Vec<String> = self
+                    .commitments
+                    .iter()
+                    .map(|c| match c.opening {
+                        Some((v, r)) => format!("{} = {}   (v = {}, r = {})", c.name, c.commitment, v, r),
+                        None => format!("{} = {}", c.name, c.commitment),
+                    })
+                    .collect();
+                lines.extend(self.proofs.iter().map(|(name, proof)| format!("{} = {}", name, proof)));
+                Ok(if lines.is_empty() { "(nothing yet)".to_string() } else { lines.join("\n") })
+            }
+            _ => Err(CtError::MalformedInput(format!("unknown command '{}' (try 'help')", line))),
+        }
+    }
+}
+
+fn parse_i64(token: &str) -> Result<i64, CtError> {
+    token
+        .parse()
+        .map_err(|_| CtError::MalformedInput(format!("'{}' is not a number", token)))
+}
+
+/// Read commands from stdin until `quit` or end of input.
+pub fn run() {
+    println!("ct repl — toy Pedersen commitments. Type 'help' for commands.");
+    let mut session = Session::new();
+    let stdin = io::stdin();
+    loop {
+        print!("ct> ");
+        io::stdout().flush().ok();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+        let line = line.trim();
+        match line {
+            "" => continue,
+            "quit" | "exit" => break,
+            _ => match session.execute(line) {
+                Ok(output) => println!("{}", output),
+                Err(e) => println!("error: {}", e),
+            },
+        }
+    }
+}
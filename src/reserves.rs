@@ -0,0 +1,118 @@
+//! Proof of reserves: an exchange shows that outputs it controls on the ledger hold
+//! at least a public amount A, without showing what any one of them holds.
+//!
+//! The proof names the outputs by outpoint, and the verifier looks their commitments
+//! up in the UTXO set at a given block and adds them: S = V*G + R*H. A range proof
+//! for S - A*G = (V - A)*G + R*H shows V - A is in [0, 2^n), so V >= A. (Opening S
+//! outright would prove V = A exactly, and give V away.) An `All` over opening proofs,
+//! one per output, shows the exchange can open every output it lists, not just their
+//! sum, so it cannot borrow someone else's output to make up the amount.
+//!
+//! V - A must fit in the range, like any amount: an exchange holding far more than
+//! it claims proves a larger A instead.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::BlockHash;
+use crate::commitment::{fee_commitment, sum_commitments, Blinding, Commitment, Opening, Scalar};
+use crate::error::CtError;
+use crate::ledger::Ledger;
+use crate::params;
+use crate::range_proof::{range_proof_create, range_proof_verify, RangeProof};
+use crate::rng::ToyRng;
+use crate::sigma::{self, All, Representation, SigmaProof};
+use crate::transaction::OutPoint;
+use crate::transcript::Transcript;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReservesProof {
+    /// The block after which the outputs are unspent.
+    pub block: BlockHash,
+    /// A: the reserves are at least this much.
+    pub amount: u64,
+    pub outputs: Vec<OutPoint>,
+    /// Knowledge of an opening of every output.
+    pub ownership: SigmaProof,
+    /// Range proof for S - A*G.
+    pub surplus: RangeProof,
+}
+
+/// The outputs' commitments in `ledger`, which must hold each of them unspent, once.
+fn commitments(ledger: &Ledger, outputs: &[OutPoint]) -> Result<Vec<Commitment>, CtError> {
+    let mut seen = HashSet::new();
+    outputs
+        .iter()
+        .map(|outpoint| {
+            if !seen.insert(outpoint) {
+                return Err(CtError::MalformedInput(format!("{} is listed twice", outpoint)));
+            }
+            ledger
+                .get(outpoint)
+                .ok_or_else(|| CtError::MalformedInput(format!("{} is not an unspent output", outpoint)))
+        })
+        .collect()
+}
+
+/// C_i = v_i*G + r_i*H for every output, and a transcript that has absorbed A and them.
+fn ownership_statement(amount: u64, commitments: &[Commitment]) -> (All<Representation>, Transcript) {
+    let params = params::active();
+    let mut transcript = Transcript::new("ct/reserves");
+    transcript.append("A", amount as i64);
+    transcript.append_all("C", &commitments.iter().map(|c| c.as_i64()).collect::<Vec<_>>());
+    let statements = commitments
+        .iter()
+        .map(|c| Representation { public: c.as_i64(), bases: vec![params.g, params.h] })
+        .collect();
+    (All(statements), transcript)
+}
+
+impl ReservesProof {
+    /// Prove that the `owned` outputs, unspent at the tip of `ledger`, hold at least `amount`.
+    pub fn create(
+        ledger: &Ledger,
+        owned: &[(OutPoint, Opening)],
+        amount: u64,
+        rng: &mut ToyRng,
+    ) -> Result<Self, CtError> {
+        let block = ledger
+            .tip()
+            .map(|tip| tip.hash())
+            .ok_or_else(|| CtError::MalformedInput("the ledger has no blocks".to_string()))?;
+        let outputs: Vec<OutPoint> = owned.iter().map(|&(outpoint, _)| outpoint).collect();
+        let commitments = commitments(ledger, &outputs)?;
+        if let Some(((outpoint, _), _)) = owned.iter().zip(&commitments).find(|((_, o), &c)| o.commit() != c) {
+            return Err(CtError::MalformedInput(format!("the opening given for {} does not open it", outpoint)));
+        }
+        let total: i64 = owned.iter().map(|(_, opening)| opening.value.as_i64()).sum();
+        if total < amount as i64 {
+            return Err(CtError::InsufficientFunds { available: total, needed: amount as i64 });
+        }
+        let blinding = owned.iter().fold(Blinding::default(), |sum, (_, opening)| sum + opening.blinding);
+        let surplus = range_proof_create(Scalar::new(total - amount as i64), blinding, rng)?;
+        let (statement, mut transcript) = ownership_statement(amount, &commitments);
+        let witness = owned.iter().map(|(_, o)| vec![o.value.as_i64(), o.blinding.as_i64()]).collect();
+        let ownership = sigma::prove(&statement, &mut transcript, &witness, rng);
+        Ok(ReservesProof { block, amount, outputs, ownership, surplus })
+    }
+
+    /// Check the proof against `ledger`, the state after `self.block`: every output is
+    /// unspent there, the exchange can open each, and their sum is at least A.
+    pub fn verify(&self, ledger: &Ledger) -> Result<(), CtError> {
+        if ledger.tip().map(|tip| tip.hash()) != Some(self.block) {
+            return Err(CtError::MalformedInput(format!("the ledger is not the state after block {}", self.block)));
+        }
+        let commitments = commitments(ledger, &self.outputs)?;
+        let (statement, mut transcript) = ownership_statement(self.amount, &commitments);
+        if !sigma::verify(&statement, &mut transcript, &self.ownership) {
+            return Err(CtError::InvalidProof("ownership proof fails".to_string()));
+        }
+        range_proof_verify(sum_commitments(&commitments) - fee_commitment(self.amount), &self.surplus)
+    }
+
+    /// S = V*G + R*H, the sum of the outputs' commitments in `ledger`.
+    pub fn total(&self, ledger: &Ledger) -> Result<Commitment, CtError> {
+        Ok(sum_commitments(&commitments(ledger, &self.outputs)?))
+    }
+}
@@ -0,0 +1,230 @@
+//! RingCT (Monero-style): every input is hidden among decoys.
+//!
+//! A plain confidential transaction names the output it spends, so anyone can follow
+//! the coins even if they cannot read the amounts. Here an output is owned by a
+//! one-time key P = x*G, and an input names a ring of outputs from the ledger: the
+//! real one and decoys picked at random. The spender proves, for one ring member j
+//! without saying which:
+//!
+//! - P_j = x*G: they own it;
+//! - I = x*Hp(P_j), where Hp hashes a key to a group element: I is the key image;
+//! - C_j - C' = z*H: the pseudo-output C' commits to the same amount as C_j.
+//!
+//! That is an MLSAG signature (`mlsag`) over the ring with rows (P_j, C_j - C') and
+//! bases (G, H). The key image depends only on x and P, so spending an output twice,
+//! with any two rings, shows the same I (`mlsag::link`), and the ledger keeps the set
+//! of key images seen instead of a UTXO set.
+//! Outputs are never removed: a spent output keeps serving as a decoy.
+//!
+//! The amounts balance through the pseudo-outputs: sum(C') = sum(outputs) + fee*G.
+//! Toy group: x = P/G is one division, so the ring hides nothing here.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{fee_commitment, mod_mul, mod_reduce, pedersen_commit_checked, sum_commitments, verify_balance};
+use crate::commitment::{Blinding, Commitment, Opening};
+use crate::error::CtError;
+use crate::mlsag::{self, KeyImage, MlsagSignature};
+use crate::params;
+use crate::range_proof::{range_proof_create, range_proof_verify, RangeProof};
+use crate::rng::ToyRng;
+
+/// Ring members an input names, the real one included, unless the ledger has fewer.
+pub const RING_SIZE: usize = 4;
+
+/// The one-time public key x*G.
+pub fn public_key(secret: i64) -> i64 {
+    mod_mul(secret, params::active().g)
+}
+
+/// The key image of the output owned by `secret`.
+pub fn key_image(secret: i64) -> KeyImage {
+    mlsag::key_image(secret, public_key(secret))
+}
+
+/// An output on the ring ledger: who owns it, and what it holds.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RingOutput {
+    pub key: i64,
+    pub commitment: Commitment,
+    pub proof: RangeProof,
+}
+
+impl RingOutput {
+    /// An output owned by `key` (the receiver's P) holding `opening`.
+    pub fn create(key: i64, opening: Opening, rng: &mut ToyRng) -> Result<Self, CtError> {
+        let proof = range_proof_create(opening.value, opening.blinding, rng)?;
+        Ok(RingOutput { key, commitment: opening.commit(), proof })
+    }
+}
+
+/// A spend: the ring (ledger positions, ascending), the pseudo-output and the ring
+/// signature, which carries the key image.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RingInput {
+    pub ring: Vec<usize>,
+    pub pseudo_output: Commitment,
+    pub signature: MlsagSignature,
+}
+
+impl RingInput {
+    pub fn key_image(&self) -> KeyImage {
+        self.signature.key_image
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RingTransaction {
+    pub inputs: Vec<RingInput>,
+    pub outputs: Vec<RingOutput>,
+    pub fee: u64,
+}
+
+/// What the owner of a ledger output knows: where it is, its secret key and its opening.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnedOutput {
+    pub position: usize,
+    pub secret: i64,
+    pub opening: Opening,
+}
+
+/// The MLSAG ring for one input: member j's keys are (P_j, C_j - C'), over (G, H).
+fn signing_ring(members: &[(i64, Commitment)], pseudo_output: Commitment) -> (Vec<Vec<i64>>, [i64; 2]) {
+    let params = params::active();
+    let ring = members.iter().map(|&(key, commitment)| vec![key, (commitment - pseudo_output).as_i64()]).collect();
+    (ring, [params.g, params.h])
+}
+
+/// What every input signs: the transaction's outputs and fee, so neither can be
+/// swapped afterwards. (The ring and the pseudo-output are in the signed ring itself.)
+fn signed_message(outputs: &[RingOutput], fee: u64) -> Vec<i64> {
+    let mut message: Vec<i64> = outputs.iter().map(|output| output.commitment.as_i64()).collect();
+    message.push(fee as i64);
+    message
+}
+
+/// Ledger positions for a ring around `real`: decoys drawn at random from the
+/// other outputs, sorted so the real one's place says nothing.
+pub fn pick_ring(outputs: usize, real: usize, size: usize, rng: &mut ToyRng) -> Vec<usize> {
+    let mut ring = vec![real];
+    while ring.len() < size.min(outputs) {
+        let decoy = (rng.next_u64() % outputs as u64) as usize;
+        if !ring.contains(&decoy) {
+            ring.push(decoy);
+        }
+    }
+    ring.sort_unstable();
+    ring
+}
+
+/// The outputs ever created and the key images ever spent. Nothing is removed.
+#[derive(Clone, Debug, Default)]
+pub struct RingLedger {
+    outputs: Vec<RingOutput>,
+    key_images: HashSet<KeyImage>,
+}
+
+impl RingLedger {
+    pub fn new() -> Self {
+        RingLedger::default()
+    }
+
+    pub fn outputs(&self) -> &[RingOutput] {
+        &self.outputs
+    }
+
+    pub fn is_spent(&self, key_image: KeyImage) -> bool {
+        self.key_images.contains(&key_image)
+    }
+
+    /// Add an output without a transaction (the toy's genesis), returning its position.
+    pub fn mint(&mut self, output: RingOutput) -> Result<usize, CtError> {
+        range_proof_verify(output.commitment, &output.proof)?;
+        self.outputs.push(output);
+        Ok(self.outputs.len() - 1)
+    }
+
+    /// (P_j, C_j) for each position of a ring, which must name distinct outputs in order.
+    fn members(&self, ring: &[usize]) -> Result<Vec<(i64, Commitment)>, CtError> {
+        if ring.is_empty() || ring.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(CtError::MalformedInput("a ring must list distinct outputs in ascending order".to_string()));
+        }
+        ring.iter()
+            .map(|&position| match self.outputs.get(position) {
+                Some(output) => Ok((output.key, output.commitment)),
+                None => Err(CtError::MalformedInput(format!("ring member {} is not on the ledger", position))),
+            })
+            .collect()
+    }
+
+    /// Spend `spends` into `outputs` (receiver key and opening each), every input in a
+    /// ring of `ring_size`. The pseudo-output blindings add up to the output blindings,
+    /// so the pseudo-outputs balance the outputs plus the fee.
+    pub fn spend(
+        &self,
+        spends: &[OwnedOutput],
+        outputs: &[(i64, Opening)],
+        fee: u64,
+        ring_size: usize,
+        rng: &mut ToyRng,
+    ) -> Result<RingTransaction, CtError> {
+        let created = outputs
+            .iter()
+            .map(|&(key, opening)| RingOutput::create(key, opening, rng))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut remaining = outputs.iter().fold(Blinding::new(0), |sum, (_, opening)| sum + opening.blinding);
+        let mut inputs = Vec::new();
+        for (i, spend) in spends.iter().enumerate() {
+            let owned = self.outputs.get(spend.position).filter(|output| {
+                output.key == public_key(spend.secret) && output.commitment == spend.opening.commit()
+            });
+            if owned.is_none() {
+                return Err(CtError::MalformedInput(format!("output {} is not the one described", spend.position)));
+            }
+            let pseudo_blinding = if i + 1 == spends.len() { remaining } else { Blinding::random(rng) };
+            remaining = remaining - pseudo_blinding;
+            let pseudo_output = pedersen_commit_checked(spend.opening.value, pseudo_blinding)?;
+            let ring = pick_ring(self.outputs.len(), spend.position, ring_size, rng);
+            let (keys, bases) = signing_ring(&self.members(&ring)?, pseudo_output);
+            let real = ring.iter().position(|&position| position == spend.position).expect("the ring holds it");
+            let z = mod_reduce(spend.opening.blinding.as_i64() as i128 - pseudo_blinding.as_i64() as i128);
+            let message = signed_message(&created, fee);
+            let signature = mlsag::prove(&keys, &bases, real, &[spend.secret, z], &message, rng);
+            inputs.push(RingInput { ring, pseudo_output, signature });
+        }
+        Ok(RingTransaction { inputs, outputs: created, fee })
+    }
+
+    /// Every key image new (to the ledger and within `tx`), every ring signature
+    /// valid, every output in range, and sum(C') = sum(outputs) + fee*G.
+    pub fn verify(&self, tx: &RingTransaction) -> Result<(), CtError> {
+        let mut seen = HashSet::new();
+        for input in &tx.inputs {
+            if self.is_spent(input.key_image()) || !seen.insert(input.key_image()) {
+                return Err(CtError::KeyImageSpent(input.key_image()));
+            }
+            let (keys, bases) = signing_ring(&self.members(&input.ring)?, input.pseudo_output);
+            if !mlsag::verify(&keys, &bases, &signed_message(&tx.outputs, tx.fee), &input.signature) {
+                let reason = format!("ring signature for key image {} fails", input.key_image());
+                return Err(CtError::InvalidProof(reason));
+            }
+        }
+        for output in &tx.outputs {
+            range_proof_verify(output.commitment, &output.proof)?;
+        }
+        let pseudo_outputs: Vec<Commitment> = tx.inputs.iter().map(|input| input.pseudo_output).collect();
+        let outputs: Vec<Commitment> = tx.outputs.iter().map(|output| output.commitment).collect();
+        verify_balance(&pseudo_outputs, &[sum_commitments(&outputs), fee_commitment(tx.fee)])
+    }
+
+    /// Verify `tx`, remember its key images and add its outputs; returns their positions.
+    pub fn apply(&mut self, tx: &RingTransaction) -> Result<Vec<usize>, CtError> {
+        self.verify(tx)?;
+        self.key_images.extend(tx.inputs.iter().map(RingInput::key_image));
+        let start = self.outputs.len();
+        self.outputs.extend(tx.outputs.iter().cloned());
+        Ok((start..self.outputs.len()).collect())
+    }
+}
@@ -0,0 +1,63 @@
+//! Tiny deterministic random number generator.
+//! It stands in for a cryptographic RNG and is NOT secure; `os_random` reads the
+//! operating system's generator instead, for secrets a real wallet keeps.
+
+use std::fs::File;
+use std::io::Read;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::CtError;
+use crate::params;
+
+/// Where `os_random` gets its bytes.
+const URANDOM: &str = "/dev/urandom";
+
+/// Fill `bytes` from the operating system, for mnemonics, salts and nonces that must
+/// not be guessable the way `ToyRng`'s clock seed is.
+pub fn os_random(bytes: &mut [u8]) -> Result<(), CtError> {
+    File::open(URANDOM)
+        .and_then(|mut file| file.read_exact(bytes))
+        .map_err(|e| CtError::MalformedInput(format!("cannot read {}: {}", URANDOM, e)))
+}
+
+/// Tiny deterministic random number generator (SplitMix64).
+/// Stands in for a cryptographic RNG; seeded so the demo output is reproducible.
+pub struct ToyRng(u64);
+
+impl ToyRng {
+    pub fn new(seed: u64) -> Self {
+        ToyRng(seed)
+    }
+
+    /// Seed from the clock, for CLI commands that should not repeat nonces between runs.
+    pub fn from_entropy() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        ToyRng(nanos ^ u64::from(std::process::id()).rotate_left(32))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix64(self.0)
+    }
+
+    /// A random number in [low, high), which must not be empty.
+    pub fn between(&mut self, low: i64, high: i64) -> i64 {
+        assert!(low < high, "no number lies in [{}, {})", low, high);
+        low.wrapping_add((self.next_u64() % high.abs_diff(low)) as i64)
+    }
+
+    /// A random field element in [0, p).
+    pub fn scalar(&mut self) -> i64 {
+        (self.next_u64() % params::active().modulus as u64) as i64
+    }
+}
+
+/// SplitMix64 finalizer: scrambles the bits of a 64-bit word.
+fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
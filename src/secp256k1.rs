@@ -0,0 +1,240 @@
+//! Just enough secp256k1 to check real confidential transactions (`grin`, `elements`):
+//! points from their 33-byte commitment or public key encoding, addition and
+//! multiplication by a scalar, in affine coordinates with num-bigint. The same group
+//! law as `toy_curve`, over the real 256-bit field. Slow and not constant-time: for
+//! adding up public commitments only, never for anything secret.
+//!
+//! A commitment here is r*G + v*H, with libsecp256k1-zkp's value generator H; the
+//! demo's are v*G + r*H, so G and H swap roles. Its encoding is 0x08 or 0x09, then x:
+//! 0x08 when y is a square mod p, 0x09 when it is not.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use num_bigint::BigUint;
+
+use crate::error::CtError;
+use crate::fixed_base::{FixedBase, Group};
+use crate::wire::to_hex;
+
+const P: &str = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
+/// Order n of the group.
+const N: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+const G_X: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+const G_Y: &str = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+/// H: nobody knows its discrete log to G (it is hashed from G's encoding).
+const H_X: &str = "50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0";
+const H_Y: &str = "31d3c6863973926e049e637cb1b5f40a36dac28af1766968c30c2313f3a38904";
+
+fn big(hex: &str) -> BigUint {
+    BigUint::parse_bytes(hex.as_bytes(), 16).expect("constants are hex")
+}
+
+fn p() -> BigUint {
+    big(P)
+}
+
+/// The group order n; scalars are taken mod n.
+pub fn order() -> BigUint {
+    big(N)
+}
+
+/// A curve point: the point at infinity (the identity) or an affine (x, y).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Point {
+    Infinity,
+    Affine { x: BigUint, y: BigUint },
+}
+
+pub fn g() -> Point {
+    Point::Affine { x: big(G_X), y: big(G_Y) }
+}
+
+pub fn h() -> Point {
+    Point::Affine { x: big(H_X), y: big(H_Y) }
+}
+
+fn sub(a: &BigUint, b: &BigUint) -> BigUint {
+    let p = p();
+    (a + &p - b % &p) % &p
+}
+
+fn inverse(a: &BigUint) -> BigUint {
+    let p = p();
+    a.modpow(&(&p - 2u8), &p)
+}
+
+fn is_square(a: &BigUint) -> bool {
+    let p = p();
+    a.modpow(&((&p - 1u8) >> 1u32), &p) != &p - 1u8
+}
+
+/// The y for `x` that is a square, if x^3 + 7 has a square root at all.
+pub fn lift_x(x: &BigUint) -> Option<BigUint> {
+    let p = p();
+    if *x >= p {
+        return None;
+    }
+    let rhs = (x.modpow(&BigUint::from(3u8), &p) + 7u8) % &p;
+    // p = 3 mod 4, so rhs^((p+1)/4) is a root if there is one, and itself a square.
+    let y = rhs.modpow(&((&p + 1u8) >> 2u32), &p);
+    (y.modpow(&BigUint::from(2u8), &p) == rhs).then_some(y)
+}
+
+impl Point {
+    /// Decode a 33-byte commitment (0x08/0x09, then x).
+    pub fn from_commitment(bytes: &[u8; 33]) -> Result<Point, CtError> {
+        let invalid = || CtError::MalformedInput(format!("{} is not a commitment on secp256k1", to_hex(bytes)));
+        if bytes[0] != 0x08 && bytes[0] != 0x09 {
+            return Err(invalid());
+        }
+        let x = BigUint::from_bytes_be(&bytes[1..]);
+        let y = lift_x(&x).ok_or_else(invalid)?;
+        let point = Point::Affine { x, y };
+        Ok(if bytes[0] == 0x09 { point.neg() } else { point })
+    }
+
+    /// Decode a 33-byte public key (0x02 for an even y, 0x03 for an odd one, then x).
+    pub fn from_public_key(bytes: &[u8; 33]) -> Result<Point, CtError> {
+        let invalid = || CtError::MalformedInput(format!("{} is not a public key on secp256k1", to_hex(bytes)));
+        if bytes[0] != 0x02 && bytes[0] != 0x03 {
+            return Err(invalid());
+        }
+        let x = BigUint::from_bytes_be(&bytes[1..]);
+        let y = lift_x(&x).ok_or_else(invalid)?;
+        let odd = bytes[0] == 0x03;
+        Ok(if y.bit(0) == odd { Point::Affine { x, y } } else { Point::Affine { x, y: sub(&BigUint::ZERO, &y) } })
+    }
+
+    /// The 33-byte commitment encoding; the identity has none.
+    pub fn to_commitment(&self) -> Option<[u8; 33]> {
+        let Point::Affine { x, y } = self else {
+            return None;
+        };
+        let mut bytes = [if is_square(y) { 0x08 } else { 0x09 }; 33];
+        let x = x.to_bytes_be();
+        bytes[33 - x.len()..].copy_from_slice(&x);
+        Some(bytes)
+    }
+
+    pub fn neg(&self) -> Point {
+        match self {
+            Point::Infinity => Point::Infinity,
+            Point::Affine { x, y } => Point::Affine { x: x.clone(), y: sub(&BigUint::ZERO, y) },
+        }
+    }
+
+    pub fn add(&self, other: &Point) -> Point {
+        let p = p();
+        let (x1, y1, x2, y2) = match (self, other) {
+            (Point::Infinity, q) | (q, Point::Infinity) => return q.clone(),
+            (Point::Affine { x: x1, y: y1 }, Point::Affine { x: x2, y: y2 }) => (x1, y1, x2, y2),
+        };
+        let slope = if x1 == x2 {
+            if (y1 + y2) % &p == BigUint::ZERO {
+                return Point::Infinity;
+            }
+            // Tangent: 3x^2 / 2y.
+            (BigUint::from(3u8) * x1 * x1 % &p) * inverse(&(BigUint::from(2u8) * y1 % &p)) % &p
+        } else {
+            sub(y2, y1) * inverse(&sub(x2, x1)) % &p
+        };
+        let x3 = sub(&sub(&(&slope * &slope % &p), x1), x2);
+        let y3 = sub(&(&slope * sub(x1, &x3) % &p), y1);
+        Point::Affine { x: x3, y: y3 }
+    }
+
+    /// k * self, by double-and-add.
+    pub fn mul(&self, k: &BigUint) -> Point {
+        let k = k % order();
+        let mut result = Point::Infinity;
+        for i in (0..k.bits()).rev() {
+            result = result.add(&result);
+            if k.bit(i) {
+                result = result.add(self);
+            }
+        }
+        result
+    }
+}
+
+impl Group for Point {
+    fn identity() -> Self {
+        Point::Infinity
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Point::add(self, other)
+    }
+}
+
+/// G's and H's fixed-base tables, once `precompute` has built them.
+static TABLES: OnceLock<[FixedBase<Point>; 2]> = OnceLock::new();
+
+/// Build G's and H's tables, about a thousand additions each.
+pub fn precompute() {
+    TABLES.get_or_init(|| [FixedBase::new(&g(), 256), FixedBase::new(&h(), 256)]);
+}
+
+/// k*G, from the table if it is built.
+pub fn mul_g(k: &BigUint) -> Point {
+    match TABLES.get() {
+        Some([g, _]) => g.mul(&(k % order()).to_bytes_le()),
+        None => g().mul(k),
+    }
+}
+
+/// k*H, from the table if it is built.
+pub fn mul_h(k: &BigUint) -> Point {
+    match TABLES.get() {
+        Some([_, h]) => h.mul(&(k % order()).to_bytes_le()),
+        None => h().mul(k),
+    }
+}
+
+/// r*G + v*H, as libsecp256k1-zkp's `secp256k1_pedersen_commit` makes it.
+pub fn commit(value: &BigUint, blinding: &BigUint) -> Point {
+    mul_g(blinding).add(&mul_h(value))
+}
+
+/// A small signed number as a scalar mod n, so -1 is n - 1.
+pub fn scalar(value: i64) -> BigUint {
+    let magnitude = BigUint::from(value.unsigned_abs()) % order();
+    if value < 0 { (order() - magnitude) % order() } else { magnitude }
+}
+
+/// Sum of points.
+pub fn sum<'a>(points: impl IntoIterator<Item = &'a Point>) -> Point {
+    points.into_iter().fold(Point::Infinity, |total, point| total.add(point))
+}
+
+/// Written as its commitment encoding, in hex.
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_commitment() {
+            Some(bytes) => f.write_str(&to_hex(&bytes)),
+            None => f.write_str("O (infinity)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wire::from_hex;
+
+    fn decode(hex: &str) -> Point {
+        Point::from_commitment(&from_hex(hex).unwrap().try_into().unwrap()).unwrap()
+    }
+
+    /// Grin's mainnet genesis coinbase, made by libsecp256k1-zkp: its output r*G + v*H
+    /// less its kernel excess r*G is the 60 grin reward times H.
+    #[test]
+    fn commit_matches_grin_genesis() {
+        let output = decode("08c12007af16d1ee55fffe92cef808c77e318dae70c3bc70cb6361f49d517f1b68");
+        let excess = decode("08df2f1d996cee37715d9ac0a0f3b13aae508d1101945acb8044954aee30960be9");
+        let reward = commit(&BigUint::from(60_000_000_000u64), &BigUint::ZERO);
+        assert_eq!(output.add(&excess.neg()), reward);
+        assert_eq!(reward.to_string(), "083ff16bb1a75965d40b0f7e7595d6427d02bff2c60d566ce5c3f5fa9a549b9314");
+    }
+}
@@ -0,0 +1,82 @@
+//! Threshold blindings: Shamir's secret sharing splits an output's blinding r among
+//! n custodians so that any k of them can rebuild it and spend the output.
+//!
+//! r is the constant term of a random polynomial f of degree k - 1 mod p, and
+//! custodian i holds the point (i, f(i)). Any k points fix f, and Lagrange
+//! interpolation at 0 gives back r = f(0). With fewer than k points every r is
+//! equally likely, so k - 1 custodians together learn nothing about it.
+//!
+//! Unlike a 2-of-2 output (`multisig`), the shares have to meet: whoever combines
+//! k of them holds r itself, and could spend alone from then on.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{mod_inverse, mod_mul, mod_reduce, Blinding};
+use crate::error::CtError;
+use crate::params;
+use crate::rng::ToyRng;
+
+/// Custodian `index`'s point (i, f(i)) on the sharing polynomial.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlindingShare {
+    pub index: u64,
+    pub value: i64,
+}
+
+/// Split `secret` into `shares` points, any `threshold` of which rebuild it.
+pub fn split(
+    secret: Blinding,
+    threshold: usize,
+    shares: usize,
+    rng: &mut ToyRng,
+) -> Result<Vec<BlindingShare>, CtError> {
+    if threshold == 0 || threshold > shares {
+        return Err(CtError::MalformedInput(format!("a {}-of-{} sharing needs 1 <= k <= n", threshold, shares)));
+    }
+    if shares as u64 >= params::active().modulus as u64 {
+        return Err(CtError::MalformedInput(format!("{} shares need more points than the group has", shares)));
+    }
+    // f(x) = r + a_1*x + ... + a_{k-1}*x^(k-1), with random a_j.
+    let coefficients: Vec<i64> = std::iter::once(mod_reduce(secret.as_i64() as i128))
+        .chain((1..threshold).map(|_| rng.scalar()))
+        .collect();
+    Ok((1..=shares as u64)
+        .map(|index| {
+            let value = coefficients
+                .iter()
+                .rev()
+                .fold(0, |sum, &a| mod_reduce(mod_mul(sum, index as i64) as i128 + a as i128));
+            BlindingShare { index, value }
+        })
+        .collect())
+}
+
+/// Rebuild f(0) from the shares by Lagrange interpolation. Given fewer shares than
+/// the threshold this still returns a blinding, just not the right one: only the
+/// commitment it should open tells them apart.
+pub fn combine(shares: &[BlindingShare]) -> Result<Blinding, CtError> {
+    if shares.is_empty() {
+        return Err(CtError::MalformedInput("no shares to combine".to_string()));
+    }
+    for (i, share) in shares.iter().enumerate() {
+        if share.index == 0 {
+            return Err(CtError::MalformedInput("share index 0 would be the secret itself".to_string()));
+        }
+        if shares[..i].iter().any(|other| other.index == share.index) {
+            return Err(CtError::MalformedInput(format!("share {} given twice", share.index)));
+        }
+    }
+    // r = sum_i y_i * prod_{j != i} x_j / (x_j - x_i)
+    let secret = shares.iter().fold(0, |sum, share| {
+        let (numerator, denominator) = shares
+            .iter()
+            .filter(|other| other.index != share.index)
+            .fold((1, 1), |(num, den), other| {
+                let (x_i, x_j) = (share.index as i128, other.index as i128);
+                (mod_mul(num, x_j as i64), mod_mul(den, mod_reduce(x_j - x_i)))
+            });
+        let weight = mod_mul(numerator, mod_inverse(denominator));
+        mod_reduce(sum as i128 + mod_mul(share.value, weight) as i128)
+    });
+    Ok(Blinding::new(secret))
+}
@@ -0,0 +1,482 @@
+//! Sigma protocols: three-move proofs of knowledge.
+//!
+//! 1. commit: the prover sends an announcement built from fresh randomness
+//! 2. challenge: the verifier answers with a random e
+//! 3. respond: the prover answers e using the randomness and the witness
+//!
+//! Anyone can fake a transcript if they may pick e first (`simulate`), which is why
+//! the proofs reveal nothing; only a prover who knows the witness can answer an e
+//! fixed after the announcement. `prove` / `verify` draw e from a `Transcript`
+//! (Fiat–Shamir).
+//!
+//! `Representation` is the one base protocol every proof in this crate needs:
+//! knowledge of w_1..w_n with P = w_1*B_1 + ... + w_n*B_n for public bases B_i.
+//! `And` proves two statements at once and `All` any number of them; `Or` proves one
+//! of two without saying which, and `OneOf` one of any number.
+//!
+//! Every announcement can be recomputed from the challenge and the response (`recover`),
+//! so a proof can also be sent as (e, response): `prove_compact` / `verify_compact`.
+//!
+//! A verifier's checks are linear equations between group elements (`equations`),
+//! which `batch` adds up to check many proofs at once.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{mod_mul, mod_reduce};
+use crate::rng::ToyRng;
+use crate::transcript::Transcript;
+
+/// A linear relation sum(scalar_i * element_i) = 0 (mod p) between group elements.
+pub type Equation = Vec<(i64, i64)>;
+
+/// Does sum(scalar_i * element_i) = 0 hold?
+pub fn holds(equation: &Equation) -> bool {
+    mod_reduce(equation.iter().map(|&(scalar, element)| mod_mul(scalar, element) as i128).sum()) == 0
+}
+
+/// One sigma protocol. Announcements and responses are lists of field elements.
+pub trait Sigma {
+    type Witness;
+    /// What the prover keeps between `commit` and `respond`.
+    type State;
+
+    fn announcement_len(&self) -> usize;
+    fn response_len(&self) -> usize;
+    /// Step 1: fresh randomness and the announcement.
+    fn commit(&self, witness: &Self::Witness, rng: &mut ToyRng) -> (Self::State, Vec<i64>);
+    /// Step 3: the response to `challenge`.
+    fn respond(&self, witness: &Self::Witness, state: Self::State, challenge: i64) -> Vec<i64>;
+    /// Verifier: the equations a transcript must satisfy, or None if a condition on the
+    /// challenges alone fails (an OR's sub-challenges must add up to e).
+    fn equations(&self, announcement: &[i64], challenge: i64, response: &[i64]) -> Option<Vec<Equation>>;
+    /// Verifier: does the transcript check out?
+    fn check(&self, announcement: &[i64], challenge: i64, response: &[i64]) -> bool {
+        self.equations(announcement, challenge, response).is_some_and(|equations| equations.iter().all(holds))
+    }
+    /// An (announcement, response) for a challenge picked in advance, without a witness.
+    fn simulate(&self, challenge: i64, rng: &mut ToyRng) -> (Vec<i64>, Vec<i64>);
+    /// The announcement that makes `response` answer `challenge`.
+    fn recover(&self, challenge: i64, response: &[i64]) -> Vec<i64>;
+}
+
+/// Knowledge of w with `public` = sum(w_i * bases_i) (mod p). With bases [G, H] this
+/// is knowing an opening of a commitment; with [H] alone, that `public` commits to zero.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Representation {
+    pub public: i64,
+    pub bases: Vec<i64>,
+}
+
+impl Representation {
+    fn combine(&self, scalars: &[i64]) -> i64 {
+        mod_reduce(scalars.iter().zip(&self.bases).map(|(&s, &b)| mod_mul(s, b) as i128).sum())
+    }
+}
+
+impl Sigma for Representation {
+    type Witness = Vec<i64>;
+    type State = Vec<i64>;
+
+    fn announcement_len(&self) -> usize {
+        1
+    }
+
+    fn response_len(&self) -> usize {
+        self.bases.len()
+    }
+
+    /// t = sum(k_i * B_i) for random k_i.
+    fn commit(&self, _: &Vec<i64>, rng: &mut ToyRng) -> (Vec<i64>, Vec<i64>) {
+        let nonces: Vec<i64> = self.bases.iter().map(|_| rng.scalar()).collect();
+        let announcement = vec![self.combine(&nonces)];
+        (nonces, announcement)
+    }
+
+    /// z_i = k_i + e*w_i.
+    fn respond(&self, witness: &Vec<i64>, nonces: Vec<i64>, challenge: i64) -> Vec<i64> {
+        nonces.iter().zip(witness).map(|(&k, &w)| mod_reduce(k as i128 + mod_mul(challenge, w) as i128)).collect()
+    }
+
+    /// sum(z_i * B_i) - t - e*P = 0.
+    fn equations(&self, announcement: &[i64], challenge: i64, response: &[i64]) -> Option<Vec<Equation>> {
+        let mut equation: Equation = response.iter().copied().zip(self.bases.iter().copied()).collect();
+        equation.extend([(-1, announcement[0]), (-challenge, self.public)]);
+        Some(vec![equation])
+    }
+
+    /// Pick the response, then solve for t = sum(z_i * B_i) - e*P.
+    fn simulate(&self, challenge: i64, rng: &mut ToyRng) -> (Vec<i64>, Vec<i64>) {
+        let response: Vec<i64> = self.bases.iter().map(|_| rng.scalar()).collect();
+        let announcement = self.recover(challenge, &response);
+        (announcement, response)
+    }
+
+    /// t = sum(z_i * B_i) - e*P.
+    fn recover(&self, challenge: i64, response: &[i64]) -> Vec<i64> {
+        vec![mod_reduce(self.combine(response) as i128 - mod_mul(challenge, self.public) as i128)]
+    }
+}
+
+/// Both statements, under the same challenge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct And<A, B>(pub A, pub B);
+
+impl<A: Sigma, B: Sigma> Sigma for And<A, B> {
+    type Witness = (A::Witness, B::Witness);
+    type State = (A::State, B::State);
+
+    fn announcement_len(&self) -> usize {
+        self.0.announcement_len() + self.1.announcement_len()
+    }
+
+    fn response_len(&self) -> usize {
+        self.0.response_len() + self.1.response_len()
+    }
+
+    fn commit(&self, witness: &Self::Witness, rng: &mut ToyRng) -> (Self::State, Vec<i64>) {
+        let (state_a, announcement_a) = self.0.commit(&witness.0, rng);
+        let (state_b, announcement_b) = self.1.commit(&witness.1, rng);
+        ((state_a, state_b), [announcement_a, announcement_b].concat())
+    }
+
+    fn respond(&self, witness: &Self::Witness, state: Self::State, challenge: i64) -> Vec<i64> {
+        [self.0.respond(&witness.0, state.0, challenge), self.1.respond(&witness.1, state.1, challenge)].concat()
+    }
+
+    fn equations(&self, announcement: &[i64], challenge: i64, response: &[i64]) -> Option<Vec<Equation>> {
+        let (announcement_a, announcement_b) = announcement.split_at(self.0.announcement_len());
+        let (response_a, response_b) = response.split_at(self.0.response_len());
+        let equations_a = self.0.equations(announcement_a, challenge, response_a)?;
+        Some([equations_a, self.1.equations(announcement_b, challenge, response_b)?].concat())
+    }
+
+    fn simulate(&self, challenge: i64, rng: &mut ToyRng) -> (Vec<i64>, Vec<i64>) {
+        let (announcement_a, response_a) = self.0.simulate(challenge, rng);
+        let (announcement_b, response_b) = self.1.simulate(challenge, rng);
+        ([announcement_a, announcement_b].concat(), [response_a, response_b].concat())
+    }
+
+    fn recover(&self, challenge: i64, response: &[i64]) -> Vec<i64> {
+        let (response_a, response_b) = response.split_at(self.0.response_len());
+        [self.0.recover(challenge, response_a), self.1.recover(challenge, response_b)].concat()
+    }
+}
+
+/// Any number of statements, under the same challenge: the n-ary `And`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct All<S>(pub Vec<S>);
+
+impl<S> All<S> {
+    /// Split a concatenation of per-statement parts, `len` giving each part's length.
+    fn split<'a>(&self, mut items: &'a [i64], len: impl Fn(&S) -> usize) -> Vec<&'a [i64]> {
+        self.0
+            .iter()
+            .map(|statement| {
+                let (own, rest) = items.split_at(len(statement));
+                items = rest;
+                own
+            })
+            .collect()
+    }
+}
+
+impl<S: Sigma> Sigma for All<S> {
+    type Witness = Vec<S::Witness>;
+    type State = Vec<S::State>;
+
+    fn announcement_len(&self) -> usize {
+        self.0.iter().map(Sigma::announcement_len).sum()
+    }
+
+    fn response_len(&self) -> usize {
+        self.0.iter().map(Sigma::response_len).sum()
+    }
+
+    fn commit(&self, witness: &Self::Witness, rng: &mut ToyRng) -> (Self::State, Vec<i64>) {
+        let (states, announcements): (Vec<_>, Vec<_>) =
+            self.0.iter().zip(witness).map(|(statement, w)| statement.commit(w, rng)).unzip();
+        (states, announcements.concat())
+    }
+
+    fn respond(&self, witness: &Self::Witness, state: Self::State, challenge: i64) -> Vec<i64> {
+        let responses = self.0.iter().zip(witness).zip(state);
+        responses.map(|((statement, w), state)| statement.respond(w, state, challenge)).collect::<Vec<_>>().concat()
+    }
+
+    fn equations(&self, announcement: &[i64], challenge: i64, response: &[i64]) -> Option<Vec<Equation>> {
+        let announcements = self.split(announcement, Sigma::announcement_len);
+        let responses = self.split(response, Sigma::response_len);
+        let parts = self.0.iter().zip(announcements).zip(responses);
+        let equations = parts.map(|((statement, a), z)| statement.equations(a, challenge, z));
+        Some(equations.collect::<Option<Vec<_>>>()?.concat())
+    }
+
+    fn simulate(&self, challenge: i64, rng: &mut ToyRng) -> (Vec<i64>, Vec<i64>) {
+        let (announcements, responses): (Vec<_>, Vec<_>) =
+            self.0.iter().map(|statement| statement.simulate(challenge, rng)).unzip();
+        (announcements.concat(), responses.concat())
+    }
+
+    fn recover(&self, challenge: i64, response: &[i64]) -> Vec<i64> {
+        let responses = self.split(response, Sigma::response_len);
+        self.0.iter().zip(responses).map(|(statement, z)| statement.recover(challenge, z)).collect::<Vec<_>>().concat()
+    }
+}
+
+/// Which side of an `Or` the prover can prove.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// One of two statements. The prover simulates the side they cannot prove under a
+/// challenge e_sim of their choice and proves the other under e - e_sim. The response
+/// is [e_left, e_right, left response..., right response...]; e_left + e_right = e.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Or<A, B>(pub A, pub B);
+
+impl<A: Sigma, B: Sigma> Sigma for Or<A, B> {
+    type Witness = Either<A::Witness, B::Witness>;
+    /// The real side's state, the simulated challenge and the simulated response.
+    type State = (Either<A::State, B::State>, i64, Vec<i64>);
+
+    fn announcement_len(&self) -> usize {
+        self.0.announcement_len() + self.1.announcement_len()
+    }
+
+    fn response_len(&self) -> usize {
+        2 + self.0.response_len() + self.1.response_len()
+    }
+
+    fn commit(&self, witness: &Self::Witness, rng: &mut ToyRng) -> (Self::State, Vec<i64>) {
+        let simulated_challenge = rng.scalar();
+        match witness {
+            Either::Left(w) => {
+                let (announcement_b, response_b) = self.1.simulate(simulated_challenge, rng);
+                let (state, announcement_a) = self.0.commit(w, rng);
+                ((Either::Left(state), simulated_challenge, response_b), [announcement_a, announcement_b].concat())
+            }
+            Either::Right(w) => {
+                let (announcement_a, response_a) = self.0.simulate(simulated_challenge, rng);
+                let (state, announcement_b) = self.1.commit(w, rng);
+                ((Either::Right(state), simulated_challenge, response_a), [announcement_a, announcement_b].concat())
+            }
+        }
+    }
+
+    fn respond(&self, witness: &Self::Witness, state: Self::State, challenge: i64) -> Vec<i64> {
+        let (real, simulated_challenge, simulated) = state;
+        let real_challenge = mod_reduce(challenge as i128 - simulated_challenge as i128);
+        match (witness, real) {
+            (Either::Left(w), Either::Left(state)) => {
+                let response = self.0.respond(w, state, real_challenge);
+                [vec![real_challenge, simulated_challenge], response, simulated].concat()
+            }
+            (Either::Right(w), Either::Right(state)) => {
+                let response = self.1.respond(w, state, real_challenge);
+                [vec![simulated_challenge, real_challenge], simulated, response].concat()
+            }
+            _ => panic!("the state comes from a different witness"),
+        }
+    }
+
+    fn equations(&self, announcement: &[i64], challenge: i64, response: &[i64]) -> Option<Vec<Equation>> {
+        let (challenges, response) = response.split_at(2);
+        let (announcement_a, announcement_b) = announcement.split_at(self.0.announcement_len());
+        let (response_a, response_b) = response.split_at(self.0.response_len());
+        if mod_reduce(challenges[0] as i128 + challenges[1] as i128) != challenge {
+            return None;
+        }
+        let equations_a = self.0.equations(announcement_a, challenges[0], response_a)?;
+        Some([equations_a, self.1.equations(announcement_b, challenges[1], response_b)?].concat())
+    }
+
+    fn simulate(&self, challenge: i64, rng: &mut ToyRng) -> (Vec<i64>, Vec<i64>) {
+        let challenge_a = rng.scalar();
+        let challenge_b = mod_reduce(challenge as i128 - challenge_a as i128);
+        let (announcement_a, response_a) = self.0.simulate(challenge_a, rng);
+        let (announcement_b, response_b) = self.1.simulate(challenge_b, rng);
+        (
+            [announcement_a, announcement_b].concat(),
+            [vec![challenge_a, challenge_b], response_a, response_b].concat(),
+        )
+    }
+
+    fn recover(&self, _: i64, response: &[i64]) -> Vec<i64> {
+        let (challenges, response) = response.split_at(2);
+        let (response_a, response_b) = response.split_at(self.0.response_len());
+        [self.0.recover(challenges[0], response_a), self.1.recover(challenges[1], response_b)].concat()
+    }
+}
+
+/// One of n statements, the n-ary `Or`: every side but the real one is simulated
+/// under a challenge of the prover's choice, and the real one answers the rest. The
+/// response is [e_1, ..., e_n, response_1..., ..., response_n...]; the e_j add up to e.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OneOf<S>(pub Vec<S>);
+
+impl<S: Sigma> Sigma for OneOf<S> {
+    /// Which statement the prover can prove, and its witness.
+    type Witness = (usize, S::Witness);
+    /// The real statement's state, then every challenge and response, the real ones empty.
+    type State = (S::State, Vec<i64>, Vec<Vec<i64>>);
+
+    fn announcement_len(&self) -> usize {
+        self.0.iter().map(Sigma::announcement_len).sum()
+    }
+
+    fn response_len(&self) -> usize {
+        self.0.len() + self.0.iter().map(Sigma::response_len).sum::<usize>()
+    }
+
+    fn commit(&self, witness: &Self::Witness, rng: &mut ToyRng) -> (Self::State, Vec<i64>) {
+        let (real, w) = witness;
+        let (mut state, mut challenges, mut responses, mut announcement) = (None, Vec::new(), Vec::new(), Vec::new());
+        for (j, statement) in self.0.iter().enumerate() {
+            if j == *real {
+                let (real_state, real_announcement) = statement.commit(w, rng);
+                state = Some(real_state);
+                challenges.push(0);
+                responses.push(Vec::new());
+                announcement.extend(real_announcement);
+            } else {
+                let challenge = rng.scalar();
+                let (simulated_announcement, simulated) = statement.simulate(challenge, rng);
+                challenges.push(challenge);
+                responses.push(simulated);
+                announcement.extend(simulated_announcement);
+            }
+        }
+        ((state.expect("the real statement is one of them"), challenges, responses), announcement)
+    }
+
+    fn respond(&self, witness: &Self::Witness, state: Self::State, challenge: i64) -> Vec<i64> {
+        let (real, w) = witness;
+        let (real_state, mut challenges, mut responses) = state;
+        let simulated: i128 = challenges.iter().map(|&e| e as i128).sum();
+        challenges[*real] = mod_reduce(challenge as i128 - simulated);
+        responses[*real] = self.0[*real].respond(w, real_state, challenges[*real]);
+        [challenges, responses.concat()].concat()
+    }
+
+    fn equations(&self, announcement: &[i64], challenge: i64, response: &[i64]) -> Option<Vec<Equation>> {
+        let (challenges, mut response) = response.split_at(self.0.len());
+        let mut announcement = announcement;
+        if mod_reduce(challenges.iter().map(|&e| e as i128).sum()) != challenge {
+            return None;
+        }
+        let equations = self.0.iter().zip(challenges).map(|(statement, &e)| {
+            let (own_announcement, rest_announcement) = announcement.split_at(statement.announcement_len());
+            let (own_response, rest_response) = response.split_at(statement.response_len());
+            (announcement, response) = (rest_announcement, rest_response);
+            statement.equations(own_announcement, e, own_response)
+        });
+        Some(equations.collect::<Option<Vec<_>>>()?.concat())
+    }
+
+    fn simulate(&self, challenge: i64, rng: &mut ToyRng) -> (Vec<i64>, Vec<i64>) {
+        let Some((_, rest)) = self.0.split_last() else { return (Vec::new(), Vec::new()) };
+        let mut challenges: Vec<i64> = rest.iter().map(|_| rng.scalar()).collect();
+        challenges.push(mod_reduce(challenge as i128 - challenges.iter().map(|&e| e as i128).sum::<i128>()));
+        let (announcements, responses): (Vec<_>, Vec<_>) =
+            self.0.iter().zip(&challenges).map(|(statement, &e)| statement.simulate(e, rng)).unzip();
+        (announcements.concat(), [challenges, responses.concat()].concat())
+    }
+
+    fn recover(&self, _: i64, response: &[i64]) -> Vec<i64> {
+        let (challenges, mut response) = response.split_at(self.0.len());
+        let recovered = self.0.iter().zip(challenges).map(|(statement, &e)| {
+            let (own, rest) = response.split_at(statement.response_len());
+            response = rest;
+            statement.recover(e, own)
+        });
+        recovered.collect::<Vec<_>>().concat()
+    }
+}
+
+/// A non-interactive transcript: the announcement and the response.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigmaProof {
+    pub announcement: Vec<i64>,
+    pub response: Vec<i64>,
+}
+
+/// The same proof sent as the challenge instead of the announcement, which the
+/// verifier recovers. Smaller whenever the announcement has more than one element.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactProof {
+    pub challenge: i64,
+    pub response: Vec<i64>,
+}
+
+/// Commit, absorb the announcement, draw e and respond: (announcement, e, response).
+fn run<S: Sigma>(
+    sigma: &S,
+    transcript: &mut Transcript,
+    witness: &S::Witness,
+    rng: &mut ToyRng,
+) -> (Vec<i64>, i64, Vec<i64>) {
+    let (state, announcement) = sigma.commit(witness, rng);
+    transcript.append_all("announcement", &announcement);
+    let challenge = transcript.challenge("e");
+    (announcement, challenge, sigma.respond(witness, state, challenge))
+}
+
+/// Fiat–Shamir: absorb the announcement into `transcript` and draw e from it. The
+/// caller must already have absorbed the statement (the public values), or a proof
+/// could be replayed for another one.
+pub fn prove<S: Sigma>(sigma: &S, transcript: &mut Transcript, witness: &S::Witness, rng: &mut ToyRng) -> SigmaProof {
+    let (announcement, _, response) = run(sigma, transcript, witness, rng);
+    SigmaProof { announcement, response }
+}
+
+/// `prove`, sending e in place of the announcement.
+pub fn prove_compact<S: Sigma>(
+    sigma: &S,
+    transcript: &mut Transcript,
+    witness: &S::Witness,
+    rng: &mut ToyRng,
+) -> CompactProof {
+    let (_, challenge, response) = run(sigma, transcript, witness, rng);
+    CompactProof { challenge, response }
+}
+
+/// Replay the prover's transcript to recompute e and return the proof's equations,
+/// or None if it fails before any group arithmetic.
+pub fn equations<S: Sigma>(sigma: &S, transcript: &mut Transcript, proof: &SigmaProof) -> Option<Vec<Equation>> {
+    if proof.announcement.len() != sigma.announcement_len() || proof.response.len() != sigma.response_len() {
+        return None;
+    }
+    transcript.append_all("announcement", &proof.announcement);
+    let challenge = transcript.challenge("e");
+    sigma.equations(&proof.announcement, challenge, &proof.response)
+}
+
+/// Replay the prover's transcript to recompute e, then check the proof.
+pub fn verify<S: Sigma>(sigma: &S, transcript: &mut Transcript, proof: &SigmaProof) -> bool {
+    equations(sigma, transcript, proof).is_some_and(|equations| equations.iter().all(holds))
+}
+
+/// `equations` for a compact proof: the recovered announcement must yield e.
+pub fn compact_equations<S: Sigma>(
+    sigma: &S,
+    transcript: &mut Transcript,
+    proof: &CompactProof,
+) -> Option<Vec<Equation>> {
+    if proof.response.len() != sigma.response_len() {
+        return None;
+    }
+    let announcement = sigma.recover(proof.challenge, &proof.response);
+    transcript.append_all("announcement", &announcement);
+    if transcript.challenge("e") != proof.challenge {
+        return None;
+    }
+    sigma.equations(&announcement, proof.challenge, &proof.response)
+}
+
+/// Recover the announcement from (e, response), then check that the transcript
+/// really yields e for it and that the proof checks out.
+pub fn verify_compact<S: Sigma>(sigma: &S, transcript: &mut Transcript, proof: &CompactProof) -> bool {
+    compact_equations(sigma, transcript, proof).is_some_and(|equations| equations.iter().all(holds))
+}
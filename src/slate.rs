@@ -0,0 +1,177 @@
+//! Grin-style slate: two parties build one transaction without revealing their
+//! blindings to each other.
+//!
+//! 1. `Slate::send`: the sender spends an input, adds a change output, and publishes
+//!    their share of the kernel (public excess x_s*H and nonce k_s*H). The secrets
+//!    stay in the `SenderContext`.
+//! 2. `Slate::receive`: the receiver adds an output for the amount and their share,
+//!    including a partial signature s_r = k_r + e*x_r.
+//! 3. `Slate::finalize`: the sender checks the receiver's partial signature, adds
+//!    their own, and the two add up to the kernel signature.
+//!
+//! The sender can also ask for a payment proof (see `payment_proof`), which the
+//! receiver signs after step 2 with `sign_payment_proof`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{mod_reduce, Blinding, Commitment, Opening, Scalar};
+use crate::error::CtError;
+use crate::kernel::{partial_sign, partial_verify, public_key, split_excess, Kernel, Signature};
+use crate::payment_proof::{PaymentProof, ProofRequest};
+use crate::range_proof::range_proof_create;
+use crate::rng::ToyRng;
+use crate::transaction::{Transaction, TxEntry};
+
+/// One signer's public contribution to the kernel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Participant {
+    /// x_i*H
+    pub public_excess: Commitment,
+    /// R_i = k_i*H
+    pub public_nonce: i64,
+    /// s_i, once this participant has signed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial_signature: Option<i64>,
+}
+
+/// The partial transaction passed back and forth.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Slate {
+    pub amount: u64,
+    /// The transaction so far: inputs, outputs, fee and offset; no kernel yet.
+    #[serde(rename = "transaction")]
+    pub tx: Transaction,
+    pub sender: Participant,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receiver: Option<Participant>,
+    /// The payment proof the sender asked for, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_proof: Option<ProofRequest>,
+}
+
+/// What the sender must keep (and not send) between `send` and `finalize`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SenderContext {
+    pub change: Opening,
+    /// x_s: the sender's share of the kernel excess, after the offset is split off.
+    pub excess: Blinding,
+    pub nonce: Blinding,
+}
+
+impl Slate {
+    /// Step 1: spend `input` (which the sender can open), pay `amount` plus `fee`,
+    /// and keep the rest as change.
+    pub fn send(input: Opening, amount: u64, fee: u64, rng: &mut ToyRng) -> Result<(Slate, SenderContext), CtError> {
+        let change_value = input.value.as_i64() - amount as i64 - fee as i64;
+        let change = Opening::new(Scalar::new(change_value), Blinding::random(rng));
+        let change_proof = range_proof_create(change.value, change.blinding, rng)?;
+        let (offset, excess) = split_excess(change.blinding - input.blinding, rng);
+        let nonce = Blinding::new(rng.scalar());
+        let tx = Transaction::new(
+            vec![TxEntry::input(input.commit())],
+            vec![TxEntry::new(change.commit(), change_proof)],
+        )
+        .with_fee(fee)
+        .with_offset(offset);
+        let sender = Participant {
+            public_excess: public_key(excess),
+            public_nonce: public_key(nonce).as_i64(),
+            partial_signature: None,
+        };
+        let slate = Slate { amount, tx, sender, receiver: None, payment_proof: None };
+        Ok((slate, SenderContext { change, excess, nonce }))
+    }
+
+    /// The combined public excess and nonce; the receiver must have joined.
+    fn totals(&self) -> Result<(Commitment, i64), CtError> {
+        let receiver = self.receiver.ok_or_else(|| CtError::MalformedInput("the receiver has not signed yet".to_string()))?;
+        let nonce = mod_reduce(self.sender.public_nonce as i128 + receiver.public_nonce as i128);
+        Ok((self.sender.public_excess + receiver.public_excess, nonce))
+    }
+
+    /// Step 2: add an output for `amount` with a fresh blinding, and sign for it.
+    /// Returns the updated slate and the opening of the receiver's new output.
+    pub fn receive(mut self, rng: &mut ToyRng) -> Result<(Slate, Opening), CtError> {
+        if self.receiver.is_some() {
+            return Err(CtError::MalformedInput("this slate has already been received".to_string()));
+        }
+        let output = Opening::new(Scalar::new(self.amount as i64), Blinding::random(rng));
+        let proof = range_proof_create(output.value, output.blinding, rng)?;
+        self.tx.outputs.push(TxEntry::new(output.commit(), proof));
+        let nonce = Blinding::new(rng.scalar());
+        self.receiver = Some(Participant {
+            public_excess: public_key(output.blinding),
+            public_nonce: public_key(nonce).as_i64(),
+            partial_signature: None,
+        });
+        let (public, total_nonce) = self.totals()?;
+        let s = partial_sign(output.blinding, nonce, public, total_nonce, &[self.tx.fee as i64]);
+        self.receiver.as_mut().expect("just set").partial_signature = Some(s);
+        Ok((self, output))
+    }
+
+    /// Ask the holder of `receiver` (an address's spend key B) to sign a payment proof.
+    pub fn request_payment_proof(mut self, receiver: Commitment) -> Self {
+        self.payment_proof = Some(ProofRequest { receiver, signature: None });
+        self
+    }
+
+    /// After step 2, the receiver's signature on the payment proof, with the secret
+    /// b of the key the sender asked for.
+    pub fn sign_payment_proof(&mut self, secret: Blinding, rng: &mut ToyRng) -> Result<(), CtError> {
+        let (kernel_excess, _) = self.totals()?;
+        let request = self
+            .payment_proof
+            .as_mut()
+            .ok_or_else(|| CtError::MalformedInput("the sender did not ask for a payment proof".to_string()))?;
+        if public_key(secret) != request.receiver {
+            return Err(CtError::MalformedInput(format!("the payment proof is for key {}", request.receiver)));
+        }
+        let proof = PaymentProof::sign(secret, self.amount, kernel_excess, self.sender.public_excess, rng);
+        request.signature = Some(proof.signature);
+        Ok(())
+    }
+
+    /// The payment proof, checked: None if the sender did not ask for one, an error
+    /// if the receiver did not sign it or the signature fails.
+    pub fn payment_proof(&self) -> Result<Option<PaymentProof>, CtError> {
+        let Some(request) = self.payment_proof else { return Ok(None) };
+        let (kernel_excess, _) = self.totals()?;
+        let signature = request
+            .signature
+            .ok_or_else(|| CtError::MalformedInput("the receiver has not signed the payment proof".to_string()))?;
+        let proof = PaymentProof {
+            amount: self.amount,
+            kernel_excess,
+            sender_excess: self.sender.public_excess,
+            receiver: request.receiver,
+            signature,
+        };
+        proof.verify()?;
+        Ok(Some(proof))
+    }
+
+    /// Step 3: check the receiver's share, add the sender's, and attach the kernel.
+    /// The result passes `Transaction::verify` or this returns why not. A payment
+    /// proof the sender asked for must be signed and valid too.
+    pub fn finalize(self, context: &SenderContext) -> Result<Transaction, CtError> {
+        let (public, nonce) = self.totals()?;
+        self.payment_proof()?;
+        let receiver = self.receiver.expect("totals checked the receiver");
+        let message = [self.tx.fee as i64];
+        let s_receiver = receiver
+            .partial_signature
+            .ok_or_else(|| CtError::MalformedInput("the receiver has not signed yet".to_string()))?;
+        if !partial_verify(s_receiver, receiver.public_excess, receiver.public_nonce, public, nonce, &message) {
+            return Err(CtError::InvalidProof("receiver's partial signature fails".to_string()));
+        }
+        if public_key(context.excess) != self.sender.public_excess {
+            return Err(CtError::MalformedInput("the sender context does not belong to this slate".to_string()));
+        }
+        let s_sender = partial_sign(context.excess, context.nonce, public, nonce, &message);
+        let s = mod_reduce(s_sender as i128 + s_receiver as i128);
+        let tx = self.tx.with_kernel(Kernel { excess: public, signature: Signature { nonce, s }, lock_height: 0 });
+        tx.verify()?;
+        Ok(tx)
+    }
+}
@@ -0,0 +1,95 @@
+//! JSON files for the slate flow (`ct send`, `ct receive`, `ct finalize`).
+//!
+//! The slate is what the two parties pass back and forth:
+//!
+//! ```json
+//! { "amount": 5,
+//!   "transaction": { "inputs": [...], "outputs": [...], "fee": 1, "offset": 0 },
+//!   "sender":   { "public_excess": 0, "public_nonce": 0 },
+//!   "receiver": { "public_excess": 0, "public_nonce": 0, "partial_signature": 0 },
+//!   "payment_proof": { "receiver": B, "signature": { "nonce": 0, "s": 0 } } }
+//! ```
+//!
+//! `transaction` is in the `tx_file` format; `receiver` appears once the receiver
+//! has signed. `payment_proof` appears if the sender asked for one, and its
+//! `signature` once the receiver has signed it. A slate may also be armored text (`-----BEGIN CT SLATE-----`, see
+//! `armor`), which is easier to paste into chat.
+//!
+//! The sender's context holds secrets and never leaves the sender:
+//!
+//! ```json
+//! { "change": { "value": 4, "blinding": 0 }, "excess": 0, "nonce": 0 }
+//! ```
+//!
+//! Both are the JSON serde writes for `Slate` and `SenderContext`: `to_json` writes
+//! any library value and `parse_json` reads one back. Every file `ct` writes also
+//! starts with the format version, `"version": 1` (`versioned`), which `parse_json`
+//! checks; see `wire` for how versions keep old files loading.
+
+use pedersen_commitment_tx::armor::{self, dearmor, is_armored};
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::slate::Slate;
+use pedersen_commitment_tx::wire::{check_version, Encode, FORMAT_VERSION};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::error::Category;
+use serde_json::Value;
+
+pub fn not_json(e: serde_json::Error) -> CtError {
+    match e.classify() {
+        Category::Data => CtError::MalformedInput(format!("unexpected JSON: {}", e)),
+        _ => CtError::MalformedInput(format!("not valid JSON: {}", e)),
+    }
+}
+
+/// Any value the library can serialize, as a JSON document.
+pub fn to_json<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).expect("library types serialize to JSON")
+}
+
+/// A document as a file: the format version first, then its fields.
+pub fn versioned(document: &Value) -> Value {
+    let Value::Object(fields) = document else { return document.clone() };
+    let mut file = serde_json::Map::new();
+    file.insert("version".to_string(), FORMAT_VERSION.into());
+    for (name, value) in fields {
+        file.entry(name.clone()).or_insert_with(|| value.clone());
+    }
+    Value::Object(file)
+}
+
+/// Read back what `to_json` wrote, refusing a file from a newer format version.
+pub fn parse_json<T: DeserializeOwned>(text: &str) -> Result<T, CtError> {
+    let document: Value = serde_json::from_str(text).map_err(not_json)?;
+    if let Some(version) = document.get("version") {
+        let not_number = || CtError::MalformedInput("\"version\" must be a number".to_string());
+        check_version(version.as_u64().ok_or_else(not_number)?)?;
+    }
+    serde_json::from_str(text).map_err(not_json)
+}
+
+/// CBOR's "self-described CBOR" tag 55799, which starts everything `to_cbor` writes so
+/// a reader can tell it from the wire format.
+pub const CBOR_MAGIC: [u8; 3] = [0xd9, 0xd9, 0xf7];
+
+/// The same data model as `to_json`, as CBOR: proofs and hashes as bytes, not text.
+pub fn to_cbor<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut bytes = CBOR_MAGIC.to_vec();
+    ciborium::into_writer(value, &mut bytes).expect("library types serialize to CBOR");
+    bytes
+}
+
+/// Read back what `to_cbor` wrote.
+pub fn parse_cbor<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CtError> {
+    if !bytes.starts_with(&CBOR_MAGIC) {
+        return Err(CtError::MalformedInput("CBOR must start with the self-described tag d9d9f7".to_string()));
+    }
+    ciborium::from_reader(bytes).map_err(|e| CtError::MalformedInput(format!("not valid CBOR: {}", e)))
+}
+
+pub fn parse_slate(text: &str) -> Result<Slate, CtError> {
+    if is_armored(text) {
+        return Slate::from_bytes(&dearmor(armor::SLATE, text)?);
+    }
+    parse_json(text)
+}
@@ -0,0 +1,290 @@
+//! Atomic swaps between two chains with hash-time locks (HTLCs).
+//!
+//! A locked output carries the condition of a `HashLock` (see `condition`): it can be
+//! spent by the claimant, who shows a preimage s of the lock's hash and signs with
+//! the claim key, or from the refund height on by the locker, who signs with the
+//! refund key. Both know the output's opening (the locker hands it to the claimant
+//! with the `Contract`), so the keys decide who spends it.
+//!
+//! Alice has coins on chain A and Bob on chain B:
+//!
+//! 1. Alice picks s and locks her coins on chain A to h = SHA-256(s), claimable by
+//!    Bob, refundable to her from height T_A.
+//! 2. Bob checks that lock on chain A and locks his coins on chain B to the same h,
+//!    claimable by Alice, refundable to him from an earlier height T_B.
+//! 3. Alice claims on chain B, which shows s to everyone.
+//! 4. Bob reads s off chain B (`Contract::find_preimage`) and claims on chain A.
+//!
+//! If Alice never claims, both refund once their heights pass. Bob's lock expires
+//! first, so Alice cannot wait until he refunds and still claim his coins. Bob must
+//! claim before T_A, while Alice's refund is not yet valid.
+//!
+//! Scriptless swaps (`ScriptlessClaim`) need no lock on the outputs: each side's
+//! coins go into a 2-of-2 output of Alice and Bob (see `multisig`), and each claim
+//! is a joint spend whose kernel nonce includes Alice's T = t*H (see
+//! `kernel::AdaptorSignature`). Bob gets both parties' partial signatures for the
+//! claim on chain A before he signs the one on chain B; neither is a signature
+//! until t is added. Alice adds t to publish her claim on chain B, Bob subtracts the
+//! partials he holds from its kernel's s to get t, and completes his claim on
+//! chain A. On chain, both are ordinary 2-of-2 spends with ordinary kernels. A real
+//! swap also has each side sign a time-locked refund before funding; not here.
+
+use serde::{Deserialize, Serialize};
+
+use crate::builder::TransactionBuilder;
+use crate::chain::Chain;
+use crate::commitment::{mod_reduce, Blinding, Commitment, Opening, Scalar};
+use crate::condition::{self, hash_preimage, Condition, Witness, PREIMAGE_LEN};
+use crate::error::CtError;
+use crate::kernel::{partial_sign, partial_verify, public_key, AdaptorSignature, Kernel};
+use crate::multisig::{Share, Spend};
+use crate::rng::ToyRng;
+use crate::slate::Participant;
+use crate::transaction::Transaction;
+
+/// The terms of a hash-time lock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashLock {
+    #[serde(with = "crate::wire::hex")]
+    pub hash: [u8; 32],
+    /// B: with s, a signature by b spends the output.
+    pub claim_key: Commitment,
+    /// A: from `refund_height` on, a signature by a spends it.
+    pub refund_key: Commitment,
+    pub refund_height: u64,
+}
+
+impl HashLock {
+    /// or(and(hash(h), sig(B)), and(after(T), sig(A))): the claim is branch 0, the refund branch 1.
+    pub fn condition(&self) -> Condition {
+        Condition::Or(vec![
+            Condition::And(vec![Condition::Preimage(self.hash), Condition::Signature(self.claim_key)]),
+            Condition::And(vec![Condition::After(self.refund_height), Condition::Signature(self.refund_key)]),
+        ])
+    }
+}
+
+/// A locked output with its opening: what the locker gives the claimant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contract {
+    pub opening: Opening,
+    pub lock: HashLock,
+}
+
+impl Contract {
+    /// Lock `amount` of `input` under `lock`, with the rest minus `fee` as change.
+    /// Returns the contract, the locking transaction and the change, if any.
+    pub fn lock(
+        input: Opening,
+        amount: u64,
+        fee: u64,
+        lock: HashLock,
+        rng: &mut ToyRng,
+    ) -> Result<(Contract, Transaction, Option<Opening>), CtError> {
+        let change = input.value.as_i64() - amount as i64 - fee as i64;
+        if change < 0 {
+            let needed = amount as i64 + fee as i64;
+            return Err(CtError::InsufficientFunds { available: input.value.as_i64(), needed });
+        }
+        let mut builder =
+            TransactionBuilder::new().add_input(input.commit(), input).add_output(Scalar::new(amount as i64));
+        if change > 0 {
+            builder = builder.add_output(Scalar::new(change));
+        }
+        let (mut tx, openings) = builder.fee(fee).kernel().build(rng)?;
+        tx.outputs[0].lock = Some(lock.condition());
+        Ok((Contract { opening: openings[0], lock }, tx, openings.get(1).copied()))
+    }
+
+    pub fn commitment(&self) -> Commitment {
+        self.opening.commit()
+    }
+
+    /// Spend the locked output to a new output of the signer's, minus `fee`. `unlock`
+    /// puts the signature in the witness for the branch being spent.
+    fn spend(
+        &self,
+        secret: Blinding,
+        fee: u64,
+        rng: &mut ToyRng,
+        unlock: impl FnOnce(Witness) -> Witness,
+    ) -> Result<(Transaction, Opening), CtError> {
+        let value = self.opening.value.as_i64() - fee as i64;
+        if value < 0 {
+            return Err(CtError::InsufficientFunds { available: self.opening.value.as_i64(), needed: fee as i64 });
+        }
+        let (mut tx, openings) = TransactionBuilder::new()
+            .add_input(self.commitment(), self.opening)
+            .add_output(Scalar::new(value))
+            .fee(fee)
+            .kernel()
+            .build(rng)?;
+        let kernel = tx.kernel.expect("the builder signs a kernel");
+        let signature = condition::sign(secret, self.commitment(), &kernel, rng);
+        tx.inputs[0].unlock = Some(unlock(signature));
+        Ok((tx, openings[0]))
+    }
+
+    /// The claimant's spend: needs s and the claim secret b.
+    pub fn claim(
+        &self,
+        preimage: [u8; PREIMAGE_LEN],
+        secret: Blinding,
+        fee: u64,
+        rng: &mut ToyRng,
+    ) -> Result<(Transaction, Opening), CtError> {
+        if hash_preimage(&preimage) != self.lock.hash {
+            return Err(CtError::MalformedInput("the preimage does not hash to the contract's lock".to_string()));
+        }
+        if public_key(secret) != self.lock.claim_key {
+            return Err(CtError::MalformedInput("this is not the contract's claim key".to_string()));
+        }
+        self.spend(secret, fee, rng, |signature| {
+            Witness::Or(0, Box::new(Witness::And(vec![Witness::Preimage(preimage), signature])))
+        })
+    }
+
+    /// The locker's spend, valid from the refund height on: needs the refund secret a.
+    pub fn refund(&self, secret: Blinding, fee: u64, rng: &mut ToyRng) -> Result<(Transaction, Opening), CtError> {
+        if public_key(secret) != self.lock.refund_key {
+            return Err(CtError::MalformedInput("this is not the contract's refund key".to_string()));
+        }
+        self.spend(secret, fee, rng, |signature| {
+            Witness::Or(1, Box::new(Witness::And(vec![Witness::After, signature])))
+        })
+    }
+
+    /// The preimage shown on `chain` by a claim of any output locked to this contract's
+    /// hash, such as the other side of the swap.
+    pub fn find_preimage(&self, chain: &Chain) -> Option<[u8; PREIMAGE_LEN]> {
+        chain
+            .blocks()
+            .into_iter()
+            .filter(|block| chain.is_main(&block.header.hash()))
+            .flat_map(|block| &block.transactions)
+            .flat_map(|tx| &tx.inputs)
+            .flat_map(|input| input.unlock.iter().flat_map(Witness::preimages))
+            .find(|preimage| hash_preimage(preimage) == self.lock.hash)
+    }
+}
+
+/// What a party keeps until it signs a `ScriptlessClaim`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaimSecrets {
+    pub excess: Blinding,
+    pub nonce: Blinding,
+}
+
+/// A claim in a scriptless swap: the spend of a 2-of-2 output to the claimant,
+/// with the adaptor point T in the kernel nonce.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptlessClaim {
+    pub spend: Spend,
+    pub adaptor: Commitment,
+}
+
+impl ScriptlessClaim {
+    /// Step 1 (the claimant): spend the 2-of-2 output of `share` to a new output of
+    /// the claimant's, for T = `adaptor`. Returns the new output's opening.
+    pub fn new(
+        share: &Share,
+        fee: u64,
+        adaptor: Commitment,
+        rng: &mut ToyRng,
+    ) -> Result<(ScriptlessClaim, Opening, ClaimSecrets), CtError> {
+        let (spend, secrets) = Spend::new(share, fee, rng)?;
+        let own = ClaimSecrets { excess: secrets.excess, nonce: secrets.nonce };
+        Ok((ScriptlessClaim { spend, adaptor }, secrets.output, own))
+    }
+
+    /// Step 2 (the other owner): add an excess share of -r and a nonce.
+    pub fn join(&mut self, share: &Share, rng: &mut ToyRng) -> Result<ClaimSecrets, CtError> {
+        if self.spend.other.is_some() {
+            return Err(CtError::MalformedInput("this claim has already been joined".to_string()));
+        }
+        if self.spend.tx.inputs.first().map(|input| input.commitment) != Some(share.commitment) {
+            return Err(CtError::MalformedInput(format!("this claim does not spend {}", share.commitment)));
+        }
+        let secrets = ClaimSecrets { excess: Blinding::default() - share.blinding, nonce: Blinding::new(rng.scalar()) };
+        self.spend.other = Some(Participant {
+            public_excess: public_key(secrets.excess),
+            public_nonce: public_key(secrets.nonce).as_i64(),
+            partial_signature: None,
+        });
+        Ok(secrets)
+    }
+
+    fn participants(&self) -> Result<[&Participant; 2], CtError> {
+        let other = self.spend.other.as_ref();
+        let other = other.ok_or_else(|| CtError::MalformedInput("the other owner has not joined yet".to_string()))?;
+        Ok([&self.spend.starter, other])
+    }
+
+    /// P, the kernel key, and R = R_1 + R_2 + T.
+    fn totals(&self) -> Result<(Commitment, i64), CtError> {
+        let [starter, other] = self.participants()?;
+        let nonces = Commitment::from_i64(starter.public_nonce) + Commitment::from_i64(other.public_nonce);
+        Ok((starter.public_excess + other.public_excess, (nonces + self.adaptor).as_i64()))
+    }
+
+    /// Either owner: s_i = k_i + e*x_i with e for the R that includes T. Not enough,
+    /// with the other's s_j, for a kernel: that needs t as well.
+    pub fn sign(&mut self, secrets: &ClaimSecrets) -> Result<i64, CtError> {
+        let (public, nonce) = self.totals()?;
+        let s = partial_sign(secrets.excess, secrets.nonce, public, nonce, &[self.spend.tx.fee as i64]);
+        let participant = if public_key(secrets.excess) == self.spend.starter.public_excess {
+            &mut self.spend.starter
+        } else {
+            match self.spend.other.as_mut() {
+                Some(other) if other.public_excess == public_key(secrets.excess) => other,
+                _ => return Err(CtError::MalformedInput("these secrets do not belong to this claim".to_string())),
+            }
+        };
+        participant.partial_signature = Some(s);
+        Ok(s)
+    }
+
+    /// Both partial signatures, checked, as one pre-signature for P and T.
+    pub fn presignature(&self) -> Result<AdaptorSignature, CtError> {
+        let (public, nonce) = self.totals()?;
+        let fee = [self.spend.tx.fee as i64];
+        let mut s = 0;
+        for (participant, who) in self.participants()?.into_iter().zip(["claimant", "other owner"]) {
+            let s_i = participant
+                .partial_signature
+                .ok_or_else(|| CtError::MalformedInput(format!("the {} has not signed yet", who)))?;
+            if !partial_verify(s_i, participant.public_excess, participant.public_nonce, public, nonce, &fee) {
+                return Err(CtError::InvalidProof(format!("the {}'s partial signature fails", who)));
+            }
+            s = mod_reduce(s as i128 + s_i as i128);
+        }
+        let presignature = AdaptorSignature { nonce, adaptor: self.adaptor, s };
+        if !presignature.verify(public, &fee) {
+            return Err(CtError::InvalidProof("the pre-signature fails".to_string()));
+        }
+        Ok(presignature)
+    }
+
+    /// The transaction, with the kernel signature completed by t.
+    pub fn complete(&self, secret: Blinding) -> Result<Transaction, CtError> {
+        let (public, _) = self.totals()?;
+        let signature = self.presignature()?.complete(secret);
+        let tx = self.spend.tx.clone().with_kernel(Kernel { excess: public, signature, lock_height: 0 });
+        tx.verify()?;
+        Ok(tx)
+    }
+
+    /// t, from this claim's kernel on `chain`, once the claimant has published it.
+    pub fn extract(&self, chain: &Chain) -> Result<Option<Blinding>, CtError> {
+        let (public, _) = self.totals()?;
+        let presignature = self.presignature()?;
+        Ok(chain
+            .blocks()
+            .into_iter()
+            .filter(|block| chain.is_main(&block.header.hash()))
+            .flat_map(|block| &block.transactions)
+            .filter_map(|tx| tx.kernel)
+            .filter(|kernel| kernel.excess == public)
+            .find_map(|kernel| presignature.extract(&kernel.signature)))
+    }
+}
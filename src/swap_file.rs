@@ -0,0 +1,51 @@
+//! JSON files for atomic swaps (`ct swap`). The contract a locker hands the claimant:
+//!
+//! ```json
+//! { "opening": { "value": 5, "blinding": 0 },
+//!   "lock": { "hash": "<hex>", "claim_key": B, "refund_key": A, "refund_height": 20 } }
+//! ```
+//!
+//! That is the JSON serde writes for `swap::Contract` (`slate_file::to_json`).
+//!
+//! And what each party keeps to themselves: the swap key's secret and, for the party
+//! who starts, the preimage s.
+//!
+//! ```json
+//! { "key": 0, "preimage": "<hex>" }
+//! ```
+
+use pedersen_commitment_tx::commitment::Blinding;
+use pedersen_commitment_tx::condition::PREIMAGE_LEN;
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::wire::from_hex;
+use serde::{Deserialize, Serialize};
+
+/// A party's swap secrets.
+#[derive(Serialize, Deserialize)]
+pub struct SwapSecrets {
+    pub key: Blinding,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "preimage")]
+    pub preimage: Option<[u8; PREIMAGE_LEN]>,
+}
+
+/// An optional preimage as hex.
+mod preimage {
+    use pedersen_commitment_tx::condition::PREIMAGE_LEN;
+    use pedersen_commitment_tx::wire::{hex, to_hex};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(preimage: &Option<[u8; PREIMAGE_LEN]>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_hex(preimage.as_ref().expect("skipped when None")))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<[u8; PREIMAGE_LEN]>, D::Error> {
+        Option::<String>::deserialize(deserializer)?.map(|text| hex::parse(&text)).transpose()
+    }
+}
+
+/// A preimage or a hash, both 32 bytes.
+pub fn parse_hex32(hex: &str, what: &str) -> Result<[u8; 32], CtError> {
+    from_hex(hex)?
+        .try_into()
+        .map_err(|bytes: Vec<u8>| CtError::MalformedInput(format!("a {} has {} bytes, not 32", what, bytes.len())))
+}
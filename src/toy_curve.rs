@@ -0,0 +1,148 @@
+//! Tiny educational elliptic curve: y^2 = x^3 + 7 over F_211.
+//! Same equation as secp256k1 (Bitcoin's curve), but with a 211-element field instead of
+//! a 256-bit one, so every point fits on one line. The curve has 199 points (a prime),
+//! so every point except infinity generates the whole group.
+//! Commitments here are real points: C = v*G + r*H. Brute force breaks everything — toy only.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::fixed_base::{FixedBase, Group};
+
+/// Field prime p.
+pub const P: i64 = 211;
+/// Curve coefficients: y^2 = x^3 + A*x + B.
+pub const A: i64 = 0;
+pub const B: i64 = 7;
+/// Number of points on the curve (group order n). Scalars are taken mod n.
+pub const ORDER: i64 = 199;
+
+/// Generator G (value dimension).
+pub const G: Point = Point::Affine { x: 3, y: 33 };
+/// Generator H (blinding dimension). On a curve this small anyone can find k with
+/// H = k*G by trying all 199 values; real systems make that infeasible.
+pub const H: Point = Point::Affine { x: 4, y: 55 };
+
+/// A curve point: either the point at infinity (the group identity) or an affine (x, y).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Point {
+    Infinity,
+    Affine { x: i64, y: i64 },
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Point::Infinity => write!(f, "O (infinity)"),
+            Point::Affine { x, y } => write!(f, "({}, {})", x, y),
+        }
+    }
+}
+
+fn fp(x: i64) -> i64 {
+    x.rem_euclid(P)
+}
+
+/// Modular inverse in F_p via Fermat: x^(p-2).
+fn fp_inv(x: i64) -> i64 {
+    let mut result = 1;
+    let mut base = fp(x);
+    let mut exp = P - 2;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = fp(result * base);
+        }
+        base = fp(base * base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Does the point satisfy y^2 = x^3 + Ax + B (mod p)?
+pub fn is_on_curve(point: Point) -> bool {
+    match point {
+        Point::Infinity => true,
+        Point::Affine { x, y } => fp(y * y) == fp(x * x * x + A * x + B),
+    }
+}
+
+/// Negation: -(x, y) = (x, -y).
+pub fn neg(point: Point) -> Point {
+    match point {
+        Point::Infinity => Point::Infinity,
+        Point::Affine { x, y } => Point::Affine { x, y: fp(-y) },
+    }
+}
+
+/// Point doubling: tangent line at P, slope = (3x^2 + A) / 2y.
+pub fn double(point: Point) -> Point {
+    match point {
+        Point::Infinity => Point::Infinity,
+        Point::Affine { y: 0, .. } => Point::Infinity,
+        Point::Affine { x, y } => {
+            let slope = fp((3 * x * x + A) * fp_inv(2 * y));
+            let x3 = fp(slope * slope - 2 * x);
+            let y3 = fp(slope * (x - x3) - y);
+            Point::Affine { x: x3, y: y3 }
+        }
+    }
+}
+
+/// Point addition: chord through P and Q, slope = (y2 - y1) / (x2 - x1).
+pub fn add(p: Point, q: Point) -> Point {
+    match (p, q) {
+        (Point::Infinity, _) => q,
+        (_, Point::Infinity) => p,
+        (Point::Affine { x: x1, y: y1 }, Point::Affine { x: x2, y: y2 }) => {
+            if x1 == x2 {
+                return if fp(y1 + y2) == 0 { Point::Infinity } else { double(p) };
+            }
+            let slope = fp((y2 - y1) * fp_inv(x2 - x1));
+            let x3 = fp(slope * slope - x1 - x2);
+            let y3 = fp(slope * (x1 - x3) - y1);
+            Point::Affine { x: x3, y: y3 }
+        }
+    }
+}
+
+/// Scalar multiplication k*P by double-and-add. Negative k is taken mod the group order.
+pub fn scalar_mul(k: i64, point: Point) -> Point {
+    let mut k = k.rem_euclid(ORDER);
+    let mut result = Point::Infinity;
+    let mut addend = point;
+    while k > 0 {
+        if k & 1 == 1 {
+            result = add(result, addend);
+        }
+        addend = double(addend);
+        k >>= 1;
+    }
+    result
+}
+
+impl Group for Point {
+    fn identity() -> Self {
+        Point::Infinity
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        add(*self, *other)
+    }
+}
+
+/// G's and H's fixed-base tables, once `precompute` has built them.
+static TABLES: OnceLock<[FixedBase<Point>; 2]> = OnceLock::new();
+
+/// Build G's and H's tables: 2 rows of 16 points each, as scalars mod 199 have 8 bits.
+pub fn precompute() {
+    TABLES.get_or_init(|| [FixedBase::new(&G, 8), FixedBase::new(&H, 8)]);
+}
+
+/// Pedersen commitment on the curve: C = v*G + r*H, from the tables if they are built.
+pub fn commit(value: i64, blinding: i64) -> Point {
+    let bytes = |k: i64| (k.rem_euclid(ORDER) as u64).to_le_bytes();
+    match TABLES.get() {
+        Some([g, h]) => add(g.mul(&bytes(value)), h.mul(&bytes(blinding))),
+        None => add(scalar_mul(value, G), scalar_mul(blinding, H)),
+    }
+}
@@ -0,0 +1,341 @@
+//! A confidential transaction: N inputs and M outputs, each a commitment with an
+//! optional range proof, plus a fee published in the clear. Valid when
+//! sum(inputs) = sum(outputs) + fee*G and every output (and every input that
+//! carries one) has a range proof that verifies. Instead of one proof per output,
+//! a transaction may carry one aggregate range proof covering all of its outputs. With a kernel, the blindings need
+//! not cancel: sum(inputs) + E = sum(outputs) + fee*G and E carries a signature.
+//! A kernel offset moves part of E into the clear: sum(inputs) + E' + offset*H = sum(outputs) + fee*G.
+//! A coinbase has no inputs and mints up to the subsidy in the clear instead:
+//! minted*G + (kernel excess) = sum(outputs) + fee*G.
+//! A burn destroys value in the clear, like a fee nobody collects: with burned*G on the
+//! outputs' side, the balance itself proves exactly that much left the supply.
+//! A transaction is named by its txid, the SHA-256 of its canonical encoding.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::commitment::{fee_commitment, pedersen_commit, verify_balance, Blinding, Commitment, Scalar};
+use crate::condition::{Condition, Witness};
+use crate::ecdh::OutputNote;
+use crate::error::CtError;
+use crate::kernel::{excess, Kernel};
+use crate::memo::Memo;
+use crate::params;
+use crate::range_proof::{
+    aggregate_range_proof_equations, aggregate_range_proof_verify, range_proof_equations, range_proof_verify,
+    AggregateRangeProof, RangeProof,
+};
+use crate::sigma::Equation;
+use crate::wire::{from_hex, to_hex, Encode};
+
+/// Default for `params::active().subsidy`: the most a coinbase may mint.
+pub const SUBSIDY: u64 = 50;
+
+/// SHA-256 of a transaction's wire encoding, shown as hex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TxId(pub [u8; 32]);
+
+impl fmt::Display for TxId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&to_hex(&self.0))
+    }
+}
+
+impl FromStr for TxId {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = from_hex(s)?;
+        let bytes = bytes
+            .try_into()
+            .map_err(|b: Vec<u8>| CtError::MalformedInput(format!("a txid has 32 bytes, not {}", b.len())))?;
+        Ok(TxId(bytes))
+    }
+}
+
+serde_as_text!(TxId);
+
+/// Output `index` of transaction `txid`, written "txid:index".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OutPoint {
+    pub txid: TxId,
+    pub index: u32,
+}
+
+impl fmt::Display for OutPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.txid, self.index)
+    }
+}
+
+impl FromStr for OutPoint {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (txid, index) = s
+            .split_once(':')
+            .ok_or_else(|| CtError::MalformedInput(format!("'{}' is not txid:index", s)))?;
+        let index = index
+            .parse()
+            .map_err(|_| CtError::MalformedInput(format!("'{}' is not an output index", index)))?;
+        Ok(OutPoint { txid: txid.parse()?, index })
+    }
+}
+
+serde_as_text!(OutPoint);
+
+/// One input or output as published: the commitment and, usually, its range proof.
+/// An output paid to an address also carries the note its receiver scans for, and
+/// an output can carry a memo sealing its opening to the receiver. A locked output
+/// carries its condition, and the input that spends it the witness (see `condition`). A
+/// height-locked output cannot be spent by a block below `lock_height`, and one with
+/// a `relative_lock` of N by a block less than N above the block that created it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxEntry {
+    pub commitment: Commitment,
+    pub proof: Option<RangeProof>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<OutputNote>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<Memo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock: Option<Condition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unlock: Option<Witness>,
+    /// 0 for none.
+    #[serde(default, skip_serializing_if = "crate::wire::is_default")]
+    pub lock_height: u64,
+    /// 0 for none.
+    #[serde(default, skip_serializing_if = "crate::wire::is_default")]
+    pub relative_lock: u64,
+}
+
+impl TxEntry {
+    pub fn new(commitment: Commitment, proof: RangeProof) -> Self {
+        TxEntry { proof: Some(proof), ..TxEntry::input(commitment) }
+    }
+
+    /// An input: the commitment it spends and nothing else.
+    pub fn input(commitment: Commitment) -> Self {
+        TxEntry {
+            commitment,
+            proof: None,
+            note: None,
+            memo: None,
+            lock: None,
+            unlock: None,
+            lock_height: 0,
+            relative_lock: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub inputs: Vec<TxEntry>,
+    pub outputs: Vec<TxEntry>,
+    /// Paid to whoever includes the transaction. Public, so it needs no range proof.
+    #[serde(default)]
+    pub fee: u64,
+    /// Value created from nothing. Only a coinbase (no inputs) may mint, at most the subsidy.
+    #[serde(default, skip_serializing_if = "crate::wire::is_default")]
+    pub minted: u64,
+    /// Value destroyed. Public like the fee, and gone from the supply for good.
+    #[serde(default, skip_serializing_if = "crate::wire::is_default")]
+    pub burned: u64,
+    /// Excess and signature; without one the blindings must cancel exactly.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kernel: Option<Kernel>,
+    /// Public part of the excess blinding, so the kernel's excess matches no set of outputs.
+    #[serde(default, skip_serializing_if = "crate::wire::is_default")]
+    pub offset: Blinding,
+    /// One range proof for all outputs, in output order; they then need none of their own.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aggregate_proof: Option<AggregateRangeProof>,
+}
+
+impl Transaction {
+    /// A transaction without a fee; see `with_fee`.
+    pub fn new(inputs: Vec<TxEntry>, outputs: Vec<TxEntry>) -> Self {
+        Transaction {
+            inputs,
+            outputs,
+            fee: 0,
+            minted: 0,
+            burned: 0,
+            kernel: None,
+            offset: Blinding::default(),
+            aggregate_proof: None,
+        }
+    }
+
+    /// Mint `minted` into `outputs`. The kernel signs for the outputs' blindings.
+    pub fn coinbase(minted: u64, outputs: Vec<TxEntry>, kernel: Kernel) -> Self {
+        Transaction { minted, ..Transaction::new(Vec::new(), outputs) }.with_kernel(kernel)
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        self.minted > 0
+    }
+
+    pub fn with_fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    pub fn with_burn(mut self, burned: u64) -> Self {
+        self.burned = burned;
+        self
+    }
+
+    pub fn with_kernel(mut self, kernel: Kernel) -> Self {
+        self.kernel = Some(kernel);
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Blinding) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_aggregate_proof(mut self, proof: AggregateRangeProof) -> Self {
+        self.aggregate_proof = Some(proof);
+        self
+    }
+
+    /// Hash of the canonical bytes: any change to the transaction changes its txid.
+    pub fn txid(&self) -> TxId {
+        TxId(Sha256::digest(self.to_bytes()).into())
+    }
+
+    /// Where each output can be found once the transaction is published.
+    pub fn outpoints(&self) -> Vec<OutPoint> {
+        let txid = self.txid();
+        (0..self.outputs.len() as u32).map(|index| OutPoint { txid, index }).collect()
+    }
+
+    pub fn input_commitments(&self) -> Vec<Commitment> {
+        self.inputs.iter().map(|e| e.commitment).collect()
+    }
+
+    pub fn output_commitments(&self) -> Vec<Commitment> {
+        self.outputs.iter().map(|e| e.commitment).collect()
+    }
+
+    /// sum(outputs) + (fee + burned)*G - sum(inputs) - minted*G: zero for a transaction without a kernel.
+    pub fn excess(&self) -> Commitment {
+        excess(&self.input_commitments(), &self.output_commitments(), self.fee) + fee_commitment(self.burned)
+            - fee_commitment(self.minted)
+    }
+
+    /// sum(inputs) + minted*G (+ kernel excess + offset*H) = sum(outputs) + (fee + burned)*G, using
+    /// only the commitments and the public fee, subsidy, burn and offset. A kernel's signature is checked too.
+    pub fn verify_balance(&self) -> Result<(), CtError> {
+        self.check_coinbase()?;
+        let mut inputs = self.input_commitments();
+        if self.is_coinbase() {
+            inputs.push(fee_commitment(self.minted));
+        }
+        let mut outputs = self.output_commitments();
+        if self.fee > 0 {
+            outputs.push(fee_commitment(self.fee));
+        }
+        if self.burned > 0 {
+            outputs.push(fee_commitment(self.burned));
+        }
+        if let Some(kernel) = &self.kernel {
+            inputs.push(kernel.excess);
+        }
+        if self.offset != Blinding::default() {
+            inputs.push(pedersen_commit(Scalar::new(0), self.offset));
+        }
+        verify_balance(&inputs, &outputs)?;
+        match &self.kernel {
+            Some(kernel) => kernel.verify(self.fee),
+            None => Ok(()),
+        }
+    }
+
+    /// A coinbase spends nothing and mints at most the subsidy.
+    fn check_coinbase(&self) -> Result<(), CtError> {
+        if self.is_coinbase() {
+            if !self.inputs.is_empty() {
+                return Err(CtError::InvalidCoinbase("a coinbase cannot spend inputs".to_string()));
+            }
+            let subsidy = params::active().subsidy;
+            if self.minted > subsidy {
+                return Err(CtError::InvalidCoinbase(format!(
+                    "mints {} but the subsidy is {}",
+                    self.minted, subsidy
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The outcome of every range proof check, labelled "input i" / "output i", and
+    /// "outputs (aggregate)" for an aggregate proof. Without an aggregate proof every
+    /// output must carry a proof (inputs were checked when they were created), so a
+    /// missing output proof counts as a failure and a missing input proof is skipped.
+    pub fn range_proof_results(&self) -> Vec<(String, Result<(), CtError>)> {
+        let mut results = Vec::new();
+        let aggregated = self.aggregate_proof.is_some();
+        for (label, entries, required) in [("input", &self.inputs, false), ("output", &self.outputs, !aggregated)] {
+            for (i, entry) in entries.iter().enumerate() {
+                let result = match &entry.proof {
+                    Some(proof) => range_proof_verify(entry.commitment, proof),
+                    None if required => Err(CtError::InvalidProof("missing range proof".to_string())),
+                    None => continue,
+                };
+                results.push((format!("{} {}", label, i), result));
+            }
+        }
+        if let Some(proof) = &self.aggregate_proof {
+            let result = aggregate_range_proof_verify(&self.output_commitments(), proof);
+            results.push(("outputs (aggregate)".to_string(), result));
+        }
+        results
+    }
+
+    /// What `verify` checks, as equations for batch verification (see `batch`): the
+    /// balance, the kernel signature and every range proof. Checks that need no group
+    /// arithmetic (coinbase rules, missing proofs, challenges) fail here directly.
+    pub fn equations(&self) -> Result<Vec<Equation>, CtError> {
+        self.check_coinbase()?;
+        let mut balance: Equation = self.input_commitments().iter().map(|c| (1, c.as_i64())).collect();
+        balance.extend(self.output_commitments().iter().map(|c| (-1, c.as_i64())));
+        balance.push((1, fee_commitment(self.minted).as_i64()));
+        balance.push((-1, fee_commitment(self.fee).as_i64()));
+        balance.push((-1, fee_commitment(self.burned).as_i64()));
+        balance.push((1, pedersen_commit(Scalar::new(0), self.offset).as_i64()));
+        let mut equations = vec![balance];
+        if let Some(kernel) = &self.kernel {
+            equations[0].push((1, kernel.excess.as_i64()));
+            equations.push(kernel.equation(self.fee));
+        }
+
+        let aggregated = self.aggregate_proof.is_some();
+        for (entries, required) in [(&self.inputs, false), (&self.outputs, !aggregated)] {
+            for entry in entries {
+                match &entry.proof {
+                    Some(proof) => equations.extend(range_proof_equations(entry.commitment, proof)?),
+                    None if required => return Err(CtError::InvalidProof("missing range proof".to_string())),
+                    None => {}
+                }
+            }
+        }
+        if let Some(proof) = &self.aggregate_proof {
+            equations.extend(aggregate_range_proof_equations(&self.output_commitments(), proof)?);
+        }
+        Ok(equations)
+    }
+
+    /// Full check: the balance first, then every range proof.
+    pub fn verify(&self) -> Result<(), CtError> {
+        self.verify_balance()?;
+        self.range_proof_results().into_iter().try_for_each(|(_, result)| result)
+    }
+}
@@ -0,0 +1,56 @@
+//! Fiat–Shamir transcript. A proof's challenge must be fixed by everything the
+//! verifier will see: which kind of proof it is (the domain), the statement, and
+//! the prover's announcement. Prover and verifier feed the same labelled values
+//! into a `Transcript` and draw the same challenge; change any of them and the
+//! challenge, and with it the proof, no longer matches.
+//!
+//! The domain keeps proofs of different kinds apart: an equality proof and a
+//! public-value proof over the same numbers draw different challenges.
+
+use sha2::{Digest, Sha256};
+
+use crate::params;
+
+#[derive(Clone)]
+pub struct Transcript {
+    hasher: Sha256,
+}
+
+impl Transcript {
+    /// A transcript for proofs of one kind, e.g. "ct/opening".
+    pub fn new(domain: &str) -> Self {
+        let mut transcript = Transcript { hasher: Sha256::new() };
+        transcript.append_label(domain);
+        transcript
+    }
+
+    fn append_label(&mut self, label: &str) {
+        self.hasher.update((label.len() as u64).to_le_bytes());
+        self.hasher.update(label.as_bytes());
+    }
+
+    /// Absorb one labelled field element.
+    pub fn append(&mut self, label: &str, value: i64) {
+        self.append_label(label);
+        self.hasher.update(value.to_le_bytes());
+    }
+
+    /// Absorb a labelled list of field elements (its length too).
+    pub fn append_all(&mut self, label: &str, values: &[i64]) {
+        self.append(label, values.len() as i64);
+        for &value in values {
+            self.hasher.update(value.to_le_bytes());
+        }
+    }
+
+    /// A challenge in [0, p) determined by everything absorbed so far. It is absorbed
+    /// too, so a second challenge differs from the first.
+    pub fn challenge(&mut self, label: &str) -> i64 {
+        self.append_label(label);
+        let digest = self.hasher.clone().finalize();
+        let word = u64::from_le_bytes(digest[..8].try_into().expect("SHA-256 has 32 bytes"));
+        let challenge = (word % params::active().modulus as u64) as i64;
+        self.hasher.update(challenge.to_le_bytes());
+        challenge
+    }
+}
@@ -0,0 +1,72 @@
+//! JSON transaction files for `ct verify <file|->`:
+//!
+//! ```json
+//! { "inputs":  [{ "commitment": 86445, "proof": "..." }],
+//!   "outputs": [{ "commitment": 77792, "proof": "..." }, { "commitment": 8653, "proof": "..." }],
+//!   "fee": 0,
+//!   "minted": 0,
+//!   "burned": 0,
+//!   "kernel": { "excess": 0, "nonce": 0, "s": 0 },
+//!   "offset": 0 }
+//! ```
+//!
+//! An output paid to an address (`ct wallet send --to`) also has
+//! `"note": { "ephemeral": R, "key": P, "amount": <encrypted amount> }` for its
+//! receiver: R = e*H, the one-time key P and the encrypted amount (see `ecdh`).
+//! An output with a memo (`ct wallet send --to --memo`) has
+//! `"memo": { "ephemeral": R, "ciphertext": "<hex of the sealed opening>" }` (see `memo`).
+//! A locked output has a condition in `"lock"` and the input that spends it a witness
+//! in `"unlock"` (see `condition`): `{ "signature": K }`, `{ "hash": "<hex>" }`,
+//! `{ "after": 20 }`, `{ "and": [..] }`, `{ "or": [..] }`, and
+//! `{ "signature": { "nonce": 0, "s": 0 } }`, `{ "preimage": "<hex>" }`, `"after"`,
+//! `{ "and": [..] }`, `{ "or": [0, ..] }` (the branch met and its witness).
+//! A height-locked output or kernel has `"lock_height": 10`, and an output spendable
+//! only some blocks after its own `"relative_lock": 2`; missing means 0, no lock.
+//! `proof` is the text encoding printed by `ct rangeproof create`. It is required
+//! on outputs and optional on inputs (they were checked when they were created),
+//! unless an `"aggregate_proof"` (the text encoding of an `AggregateRangeProof`)
+//! covers all outputs at once.
+//! `fee` is optional and defaults to 0, as are `minted` (only a coinbase, with no
+//! inputs, mints) and `burned`; `kernel` is optional (Mimblewimble-style
+//! transactions carry one instead of exactly cancelling blindings), and so is the
+//! kernel `offset`, which defaults to 0.
+//!
+//! This is the JSON serde writes for `Transaction` (see the library docs).
+//!
+//! Armored text (`-----BEGIN CT TRANSACTION-----`, see `armor`) is read too, and
+//! anything else that does not start with `{` is read as hex, after a `v1:` format
+//! version if it has one (see `wire`): of CBOR if it starts with the self-described tag
+//! d9d9f7 (`ct encode --format cbor`), else of the binary wire format (`ct encode`).
+
+use clap::ValueEnum;
+use pedersen_commitment_tx::armor::{self, dearmor, is_armored};
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::transaction::Transaction;
+use pedersen_commitment_tx::wire::{from_versioned_hex, Encode};
+
+use crate::slate_file::{parse_cbor, parse_json, CBOR_MAGIC};
+
+/// What `ct encode` prints a transaction as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// The canonical binary wire format (`wire`), as hex
+    Wire,
+    /// The JSON's data model in CBOR, as hex: proofs and hashes as bytes, fields by name
+    Cbor,
+    /// The JSON file format above
+    Json,
+}
+
+pub fn parse(text: &str) -> Result<Transaction, CtError> {
+    if is_armored(text) {
+        return Transaction::from_bytes(&dearmor(armor::TRANSACTION, text)?);
+    }
+    if !text.trim_start().starts_with('{') {
+        let bytes = from_versioned_hex(text)?;
+        if bytes.starts_with(&CBOR_MAGIC) {
+            return parse_cbor(&bytes);
+        }
+        return Transaction::from_bytes(&bytes);
+    }
+    parse_json(text)
+}
@@ -0,0 +1,165 @@
+//! `ct vectors generate`: known-answer test vectors. Everything is drawn from the
+//! seed, so the same seed (and parameters) always gives the same file, and another
+//! implementation, or a student's, can recompute every answer and compare:
+//!
+//! - `commitments`: C = v*G + r*H mod p, including v = 0, r = 0 and a negative v;
+//! - `sums`: C1 + C2, which is also the commitment to (v1 + v2, r1 + r2);
+//! - `range_proofs`: proofs in `RangeProof::encode` text and `{:#}` hex, valid ones
+//!   and ones that are tampered with, checked against another commitment, or for a
+//!   value out of range;
+//! - `signatures`: Schnorr signatures over H, valid and checked against another message;
+//! - `transactions`: whole transactions as `ct` writes them, with their txid and wire
+//!   bytes, one that balances and one that does not.
+//!
+//! Every proof, signature and transaction carries the verdict this implementation
+//! reaches: `{"ok": true}` or `{"ok": false, "error": ".."}` (`report::result_json`).
+
+use pedersen_commitment_tx::builder::TransactionBuilder;
+use pedersen_commitment_tx::commitment::{pedersen_commit, Blinding, Commitment, Opening, Scalar};
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::kernel::{public_key, schnorr_sign, schnorr_verify};
+use pedersen_commitment_tx::params;
+use pedersen_commitment_tx::range_proof::{range_proof_create_unchecked, range_proof_verify, RangeProof};
+use pedersen_commitment_tx::rng::ToyRng;
+use pedersen_commitment_tx::transaction::Transaction;
+use pedersen_commitment_tx::wire::{to_hex, Encode};
+use serde_json::{json, Value};
+
+use crate::report::result_json;
+use crate::slate_file::to_json;
+
+/// A value in range for a range proof.
+fn in_range(rng: &mut ToyRng) -> i64 {
+    rng.between(0, 1i64 << params::active().range_bits)
+}
+
+/// Every vector for `seed`, as the file `ct vectors generate` writes.
+pub fn generate(seed: u64) -> Result<Value, CtError> {
+    let mut rng = ToyRng::new(seed);
+    let params = params::active();
+    Ok(json!({
+        "seed": seed,
+        "params": { "modulus": params.modulus, "g": params.g, "h": params.h, "range_bits": params.range_bits },
+        "commitments": commitments(&mut rng),
+        "sums": sums(&mut rng),
+        "range_proofs": range_proofs(&mut rng),
+        "signatures": signatures(&mut rng),
+        "transactions": transactions(&mut rng)?,
+    }))
+}
+
+fn commitment_json(value: i64, blinding: i64) -> Value {
+    let commitment = pedersen_commit(Scalar::new(value), Blinding::new(blinding));
+    json!({ "value": value, "blinding": blinding, "commitment": commitment.as_i64() })
+}
+
+fn commitments(rng: &mut ToyRng) -> Vec<Value> {
+    let mut pairs = vec![(0, 0), (1, 0), (0, 1), (-1, 1)];
+    pairs.extend((0..4).map(|_| (in_range(rng), Blinding::random(rng).as_i64())));
+    pairs.into_iter().map(|(value, blinding)| commitment_json(value, blinding)).collect()
+}
+
+fn sums(rng: &mut ToyRng) -> Vec<Value> {
+    (0..3)
+        .map(|_| {
+            let (v1, v2) = (in_range(rng), in_range(rng));
+            let (r1, r2) = (Blinding::random(rng), Blinding::random(rng));
+            let c1 = pedersen_commit(Scalar::new(v1), r1);
+            let c2 = pedersen_commit(Scalar::new(v2), r2);
+            json!({
+                "left": c1.as_i64(),
+                "right": c2.as_i64(),
+                "sum": (c1 + c2).as_i64(),
+                "opening": { "value": v1 + v2, "blinding": (r1 + r2).as_i64() },
+            })
+        })
+        .collect()
+}
+
+fn proof_vector(case: &str, value: i64, blinding: Blinding, commitment: Commitment, proof: &RangeProof) -> Value {
+    json!({
+        "case": case,
+        "value": value,
+        "blinding": blinding.as_i64(),
+        "commitment": commitment.as_i64(),
+        "proof": proof.encode(),
+        "proof_hex": format!("{:#}", proof),
+        "result": result_json(&range_proof_verify(commitment, proof)),
+    })
+}
+
+fn range_proofs(rng: &mut ToyRng) -> Vec<Value> {
+    let mut vectors = Vec::new();
+    for value in [0, (1i64 << params::active().range_bits) - 1, in_range(rng)] {
+        let blinding = Blinding::random(rng);
+        let proof = range_proof_create_unchecked(Scalar::new(value), blinding, rng);
+        let commitment = pedersen_commit(Scalar::new(value), blinding);
+        vectors.push(proof_vector("valid", value, blinding, commitment, &proof));
+    }
+
+    let (value, blinding) = (in_range(rng), Blinding::random(rng));
+    let commitment = pedersen_commit(Scalar::new(value), blinding);
+    let mut proof = range_proof_create_unchecked(Scalar::new(value), blinding, rng);
+    proof.bits[0].s0 += 1;
+    vectors.push(proof_vector("tampered response", value, blinding, commitment, &proof));
+
+    let proof = range_proof_create_unchecked(Scalar::new(value), blinding, rng);
+    let other = pedersen_commit(Scalar::new(value), Blinding::random(rng));
+    vectors.push(proof_vector("other commitment", value, blinding, other, &proof));
+
+    let value = -rng.between(1, 50);
+    let blinding = Blinding::random(rng);
+    let proof = range_proof_create_unchecked(Scalar::new(value), blinding, rng);
+    let commitment = pedersen_commit(Scalar::new(value), blinding);
+    vectors.push(proof_vector("negative value", value, blinding, commitment, &proof));
+    vectors
+}
+
+fn signatures(rng: &mut ToyRng) -> Vec<Value> {
+    let mut vectors = Vec::new();
+    for case in ["valid", "valid", "other message"] {
+        let secret = Blinding::random(rng);
+        let message = [in_range(rng), in_range(rng)];
+        let signature = schnorr_sign(secret, &message, rng);
+        let checked = if case == "valid" { message } else { [message[0] + 1, message[1]] };
+        let result = match schnorr_verify(public_key(secret), &checked, &signature) {
+            true => Ok(()),
+            false => Err(CtError::InvalidProof("the signature fails".to_string())),
+        };
+        vectors.push(json!({
+            "case": case,
+            "secret": secret.as_i64(),
+            "public_key": public_key(secret).as_i64(),
+            "message": checked,
+            "signature": signature,
+            "result": result_json(&result),
+        }));
+    }
+    vectors
+}
+
+fn transaction_vector(case: &str, tx: &Transaction) -> Value {
+    json!({
+        "case": case,
+        "transaction": to_json(tx),
+        "txid": tx.txid().to_string(),
+        "wire": to_hex(&tx.to_bytes()),
+        "result": result_json(&tx.verify()),
+    })
+}
+
+fn transactions(rng: &mut ToyRng) -> Result<Vec<Value>, CtError> {
+    let input = Opening::new(Scalar::new(rng.between(20, 1i64 << params::active().range_bits)), Blinding::random(rng));
+    let fee = rng.between(0, 5);
+    let to_bob = rng.between(1, input.value.as_i64() - fee);
+    let (paid, _) = TransactionBuilder::new()
+        .add_input(input.commit(), input)
+        .add_output(Scalar::new(to_bob))
+        .add_output(Scalar::new(input.value.as_i64() - fee - to_bob))
+        .fee(fee as u64)
+        .kernel()
+        .build(rng)?;
+    let mut unbalanced = paid.clone();
+    unbalanced.fee += 1;
+    Ok(vec![transaction_vector("valid", &paid), transaction_vector("unbalanced", &unbalanced)])
+}
@@ -0,0 +1,865 @@
+//! The walkthrough commands (`ct ringct`, `ct assets`, `ct burn`, `ct solvency`,
+//! `ct shamir`, `ct musig`, `ct timelock`, `ct mpc`, `ct coinjoin`, `ct escrow` and
+//! `ct swap scriptless`): each builds one seeded scenario from the library, narrates
+//! it step by step and records what it checked.
+
+use pedersen_commitment_tx::assets::{asset_tag, AssetOpening, AssetTransaction, SurjectionProof};
+use pedersen_commitment_tx::block::Block;
+use pedersen_commitment_tx::builder::TransactionBuilder;
+use pedersen_commitment_tx::chain::Chain;
+use pedersen_commitment_tx::coinjoin::{Coordinator, Registration};
+use pedersen_commitment_tx::commitment::{
+    fee_commitment, lin_comb, pedersen_commit, verify_balance, Blinding, Commitment, Opening, Scalar,
+};
+use pedersen_commitment_tx::condition::{self, Condition, Witness};
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::issuance::{token_tag, AssetLedger, Issuance};
+use pedersen_commitment_tx::kernel::{self, public_key, split_excess, Kernel};
+use pedersen_commitment_tx::ledger::Ledger;
+use pedersen_commitment_tx::liabilities::LiabilityTree;
+use pedersen_commitment_tx::mempool::Mempool;
+use pedersen_commitment_tx::mlsag;
+use pedersen_commitment_tx::mpc;
+use pedersen_commitment_tx::multisig::{Funding, Offer, Share};
+use pedersen_commitment_tx::musig;
+use pedersen_commitment_tx::opening_proof::{prove_value, verify_value};
+use pedersen_commitment_tx::params;
+use pedersen_commitment_tx::range_proof::{aggregate_range_proof_verify, range_proof_create};
+use pedersen_commitment_tx::reserves::ReservesProof;
+use pedersen_commitment_tx::ringct::{self, OwnedOutput, RingLedger, RingOutput};
+use pedersen_commitment_tx::rng::ToyRng;
+use pedersen_commitment_tx::shamir;
+use pedersen_commitment_tx::swap::ScriptlessClaim;
+use pedersen_commitment_tx::transaction::{OutPoint, Transaction, TxEntry};
+use pedersen_commitment_tx::wire::to_hex;
+use serde_json::json;
+
+use crate::report::{outcome, result_json, Report};
+
+/// Outputs on the toy RingCT ledger before Alice spends; hers is `RINGCT_ALICE`.
+const RINGCT_OUTPUTS: usize = 8;
+const RINGCT_ALICE: usize = 3;
+
+pub fn ringct(report: &mut Report, ring_size: usize, seed: u64) -> Result<(), CtError> {
+    let mut rng = ToyRng::new(seed);
+    let mut ledger = RingLedger::new();
+    let mut alice = None;
+    for position in 0..RINGCT_OUTPUTS {
+        let secret = rng.scalar();
+        let opening = Opening::new(Scalar::new(64 + (rng.next_u64() % 128) as i64), Blinding::random(&mut rng));
+        ledger.mint(RingOutput::create(ringct::public_key(secret), opening, &mut rng)?)?;
+        if position == RINGCT_ALICE {
+            alice = Some(OwnedOutput { position, secret, opening });
+        }
+    }
+    let alice = alice.expect("Alice's output is minted");
+    say!(report, "The ledger: every output is a one-time key P and a commitment C.");
+    for (position, output) in ledger.outputs().iter().enumerate() {
+        let mark = if position == RINGCT_ALICE { "   <- Alice's" } else { "" };
+        say!(report, "  #{:<3} P = {:<20} C = {}{}", position, output.key, output.commitment, mark);
+    }
+
+    let value = alice.opening.value.as_i64();
+    let (fee, to_bob) = (1, value / 2);
+    let bob = Opening::new(Scalar::new(to_bob), Blinding::random(&mut rng));
+    let change = Opening::new(Scalar::new(value - to_bob - fee as i64), Blinding::random(&mut rng));
+    let outputs = [(ringct::public_key(rng.scalar()), bob), (ringct::public_key(rng.scalar()), change)];
+    let tx = ledger.spend(&[alice], &outputs, fee, ring_size, &mut rng)?;
+    let input = &tx.inputs[0];
+    say!(report, "\nAlice pays Bob {} (change {}, fee {}) from #{}.", to_bob, change.value, fee, RINGCT_ALICE);
+    say!(report, "  ring:          {:?}  (one of these is spent; the signature does not say which)", input.ring);
+    say!(report, "  pseudo-output: {}", input.pseudo_output);
+    say!(report, "  key image:     {}", input.key_image());
+    let first = ledger.apply(&tx).map(|_| ());
+    say!(report, "  ledger: {}", outcome(&first));
+
+    let again = ledger.spend(&[alice], &outputs, fee, ring_size, &mut rng)?;
+    say!(report, "\nAlice spends #{} again, with another ring {:?}.", RINGCT_ALICE, again.inputs[0].ring);
+    say!(report, "  key image:     {} (the same: it depends only on her key)", again.inputs[0].key_image());
+    let linked = mlsag::link(&input.signature, &again.inputs[0].signature);
+    say!(report, "  link(first, second) = {}: both signatures were made with the same key", linked);
+    let second = ledger.apply(&again).map(|_| ());
+    say!(report, "  ledger: {}", outcome(&second));
+
+    report.record(
+        "ringct",
+        json!({
+            "ring_size": ring_size,
+            "seed": seed,
+            "ring": input.ring,
+            "key_image": input.key_image().0,
+            "spend": result_json(&first),
+            "double_spend": { "ring": again.inputs[0].ring, "linked": linked, "result": result_json(&second) },
+        }),
+    );
+    Ok(())
+}
+
+pub fn assets(report: &mut Report, seed: u64) -> Result<(), CtError> {
+    let mut rng = ToyRng::new(seed);
+    let mut ledger = AssetLedger::new();
+    let (gold, silver) = (asset_tag("GOLD"), asset_tag("SILVER"));
+    let (gold_token, silver_token) = (token_tag("GOLD"), token_tag("SILVER"));
+    say!(report, "Asset generators, hashed from the names: GOLD H_a = {}, SILVER H_a = {}", gold, silver);
+    let mut issue = |name: &str, amount: u64, blinded: bool, tag: i64, token: i64| {
+        let issuance = Issuance::new_asset(name, amount, 1, blinded, &mut rng)?;
+        let outputs = [(tag, Scalar::new(amount as i64)), (token, Scalar::new(1))];
+        let (tx, openings) = AssetTransaction::create(&[], vec![issuance], &outputs, &mut rng)?;
+        ledger.apply(&tx).map(|()| openings)
+    };
+    let gold_issued = issue("GOLD", 30, false, gold, gold_token)?;
+    say!(report, "Issued GOLD: 30 units, shown in the clear, and 1 reissuance token.");
+    let silver_issued = issue("SILVER", 50, true, silver, silver_token)?;
+    say!(report, "Issued SILVER: an amount hidden behind a range proof over H_a, and 1 reissuance token.");
+
+    let inputs = [gold_issued[0], silver_issued[0]];
+    let paid = [(silver, 35), (gold, 20), (silver, 15), (gold, 10)];
+    let outputs: Vec<(i64, Scalar)> = paid.iter().map(|&(tag, value)| (tag, Scalar::new(value))).collect();
+    let (mix, openings) = AssetTransaction::create(&inputs, Vec::new(), &outputs, &mut rng)?;
+    say!(report, "\nInputs: 30 GOLD and 50 SILVER. Outputs: 35 SILVER, 20 GOLD, 15 SILVER, 10 GOLD.");
+    say!(report, "What the ledger shows (A = H_a + t*H, C = v*A + r*H):");
+    for (i, input) in mix.inputs.iter().enumerate() {
+        say!(report, "  input  {}  A = {:<20} C = {}", i, input.generator, input.commitment);
+    }
+    for (i, output) in mix.outputs.iter().enumerate() {
+        say!(report, "  output {}  A = {:<20} C = {}", i, output.generator, output.commitment);
+    }
+    say!(report, "No A equals an input's A or another output's, so the assets cannot be matched up.");
+    let verified = ledger.apply(&mix);
+    say!(report, "Range proofs over A, surjection proofs and balance: {}", outcome(&verified));
+    let name = |tag: i64| if tag == gold { "GOLD" } else { "SILVER" };
+    let owners: Vec<String> = openings.iter().map(|o| format!("{} {}", o.value, name(o.tag))).collect();
+    say!(report, "(Only the owners know: {}.)", owners.join(", "));
+
+    // A cheater proves surjection for a GOLD generator with PLATINUM's blinding, and
+    // publishes the PLATINUM generator instead: the proof is for the wrong A.
+    let platinum = AssetOpening::random(asset_tag("PLATINUM"), Scalar::new(5), &mut rng);
+    let pretend = SurjectionProof::create(&inputs, &AssetOpening { tag: gold, ..platinum }, &mut rng)?;
+    let input_generators: Vec<i64> = mix.inputs.iter().map(|input| input.generator).collect();
+    let forgery = pretend.verify(&input_generators, platinum.generator());
+    let verdict = if forgery { "passes" } else { "fails" };
+    say!(report, "\nAn output over PLATINUM, which no input holds: its surjection proof {}", verdict);
+
+    let token = [gold_issued[1]];
+    let reissuance = Issuance::reissue("GOLD", 100, false, &token, 0, &mut rng)?;
+    let outputs_with_token = [(gold, Scalar::new(100)), (gold_token, Scalar::new(1))];
+    let (tx, _) = AssetTransaction::create(&token, vec![reissuance], &outputs_with_token, &mut rng)?;
+    let reissued = ledger.apply(&tx);
+    say!(report, "\nThe GOLD token holder reissues 100 GOLD and keeps the token: {}", outcome(&reissued));
+    let mallory = [openings[0]];
+    let reissuance = Issuance::reissue("GOLD", 100, false, &mallory, 0, &mut rng)?;
+    let outputs = [(silver, Scalar::new(35)), (gold, Scalar::new(100))];
+    let (tx, _) = AssetTransaction::create(&mallory, vec![reissuance], &outputs, &mut rng)?;
+    let stolen = ledger.apply(&tx);
+    say!(report, "Mallory reissues GOLD with her 35 SILVER as the \"token\": {}", outcome(&stolen));
+    let issuance = Issuance::new_asset("GOLD", 100, 1, false, &mut rng)?;
+    let (tx, _) = AssetTransaction::create(&[], vec![issuance], &outputs_with_token, &mut rng)?;
+    let again = ledger.apply(&tx);
+    say!(report, "Mallory issues GOLD as a new asset: {}", outcome(&again));
+
+    let entries = |pairs: Vec<(i64, Commitment)>| {
+        pairs.into_iter().map(|(a, c)| json!({ "generator": a, "commitment": c.as_i64() })).collect::<Vec<_>>()
+    };
+    let inputs_json = entries(mix.inputs.iter().map(|i| (i.generator, i.commitment)).collect());
+    let outputs_json = entries(mix.outputs.iter().map(|o| (o.generator, o.commitment)).collect());
+    report.record(
+        "assets",
+        json!({
+            "inputs": inputs_json,
+            "outputs": outputs_json,
+            "verify": result_json(&verified),
+            "foreign_asset_rejected": !forgery,
+            "reissue": result_json(&reissued),
+            "reissue_without_token": result_json(&stolen),
+            "issue_existing_name": result_json(&again),
+        }),
+    );
+    Ok(())
+}
+
+pub fn burn(report: &mut Report, seed: u64) -> Result<(), CtError> {
+    let mut rng = ToyRng::new(seed);
+    let mut ledger = Ledger::new();
+    let subsidy = params::active().subsidy;
+    let coin = Opening::new(Scalar::new(subsidy as i64), Blinding::random(&mut rng));
+    let entry = TxEntry::new(coin.commit(), range_proof_create(coin.value, coin.blinding, &mut rng)?);
+    let coinbase = Transaction::coinbase(subsidy, vec![entry], Kernel::create(coin.blinding, 0, &mut rng));
+    let genesis = Block::new(&ledger, vec![coinbase]);
+    ledger.apply_block(&genesis)?;
+    say!(report, "Block 0: a coinbase mints {} to Alice, C = {}.", subsidy, coin.commit());
+
+    let burned = subsidy * 2 / 5;
+    let (tx, openings) = TransactionBuilder::new()
+        .add_input(coin.commit(), coin)
+        .add_output(Scalar::new((subsidy - burned) as i64))
+        .burn(burned)
+        .kernel()
+        .build(&mut rng)?;
+    let change = (openings[0].value, tx.outputs[0].commitment);
+    say!(report, "\nAlice burns {} and keeps {} as change, C = {}.", burned, change.0, change.1);
+    say!(report, "  burned = {} is public, like a fee: sum(in) + E = sum(out) + {}*G.", burned, burned);
+    say!(report, "  The change amount stays hidden; the balance alone proves exactly {} left.", burned);
+
+    let mut inflated = tx.clone();
+    inflated.burned += 5;
+    let claimed = ledger.clone().apply(&inflated).map(|_| ());
+    say!(report, "  Claiming a burn of {} with the same outputs: {}", inflated.burned, outcome(&claimed));
+    let block = Block::new(&ledger, vec![tx]);
+    ledger.apply_block(&block)?;
+    say!(report, "Block 1: the burn is mined (header: burned = {}).", block.header.burned);
+
+    let supply = ledger.supply();
+    say!(report, "\nSupply from the public amounts alone:");
+    say!(
+        report,
+        "  minted {} - fees {} - burned {} = {} in circulation",
+        supply.minted,
+        supply.fees,
+        supply.burned,
+        supply.circulating()
+    );
+    // Every block balances, so the unspent outputs hold the circulating supply, blinded by
+    // the sum of the kernel excesses and offsets. Anyone can check it with the headers.
+    let unspent: Vec<Commitment> = ledger.utxos().map(|(_, &c)| c).collect();
+    let headers = [genesis.header, block.header];
+    let excess = headers.iter().fold(Commitment::from_i64(0), |sum, header| sum + header.kernel_excess);
+    let offset = headers.iter().fold(Blinding::default(), |sum, header| sum + header.offset);
+    let expected = fee_commitment(supply.circulating()) + excess + pedersen_commit(Scalar::new(0), offset);
+    let audit = verify_balance(&unspent, &[expected]);
+    say!(report, "  sum(unspent) = {}*G + sum(E) + offset*H: {}", supply.circulating(), outcome(&audit));
+
+    report.record(
+        "burn",
+        json!({
+            "minted": supply.minted,
+            "fees": supply.fees,
+            "burned": supply.burned,
+            "circulating": supply.circulating(),
+            "overclaimed_burn": result_json(&claimed),
+            "audit": result_json(&audit),
+        }),
+    );
+    Ok(())
+}
+
+/// The exchange's customers and their balances in `ct solvency`.
+const CUSTOMERS: [(&str, u64); 5] = [("alice", 12), ("bob", 7), ("carol", 20), ("dave", 3), ("erin", 5)];
+
+pub fn solvency(report: &mut Report, seed: u64) -> Result<(), CtError> {
+    let mut rng = ToyRng::new(seed);
+    let accounts: Vec<(String, u64)> = CUSTOMERS.iter().map(|&(id, balance)| (id.to_string(), balance)).collect();
+    let tree = LiabilityTree::new(&accounts, &mut rng)?;
+    let (root, total) = (tree.root(), tree.total());
+    say!(report, "The exchange commits to {} balances in a Merkle-sum tree and publishes the root:", accounts.len());
+    say!(report, "  hash {}", to_hex(&root.hash));
+    say!(report, "  C = {} (the sum of every balance, in range)", root.commitment);
+    let opened = prove_value(root.commitment, &total, &mut rng)?;
+    let liabilities = total.value.as_i64() as u64;
+    let shown = verify_value(root.commitment, total.value, &opened);
+    say!(report, "  It opens the total: L = {} ({})", liabilities, outcome(&shown));
+
+    say!(report, "\nEach customer checks their leaf and the path to the root:");
+    let mut checks = Vec::new();
+    for index in 0..accounts.len() {
+        let (id, opening) = tree.account(index).expect("every customer has a leaf");
+        let proof = tree.prove(index).expect("every customer has a leaf");
+        let result = proof.verify(root, id, &opening);
+        let siblings = proof.siblings.len();
+        say!(report, "  {:<6} balance {:<3} {} sibling(s): {}", id, opening.value, siblings, outcome(&result));
+        checks.push(json!({ "customer": id, "result": result_json(&result) }));
+    }
+
+    // The reserves: a coinbase pays the subsidy to the exchange in two outputs.
+    let mut ledger = Ledger::new();
+    let subsidy = params::active().subsidy;
+    let coins = [subsidy * 3 / 5, subsidy - subsidy * 3 / 5]
+        .map(|value| Opening::new(Scalar::new(value as i64), Blinding::random(&mut rng)));
+    let mut entries = Vec::new();
+    for coin in &coins {
+        entries.push(TxEntry::new(coin.commit(), range_proof_create(coin.value, coin.blinding, &mut rng)?));
+    }
+    let kernel = Kernel::create(coins[0].blinding + coins[1].blinding, 0, &mut rng);
+    let coinbase = Transaction::coinbase(subsidy, entries, kernel);
+    let owned: Vec<(OutPoint, Opening)> = coinbase.outpoints().into_iter().zip(coins).collect();
+    ledger.apply_block(&Block::new(&ledger, vec![coinbase]))?;
+    let reserves = ReservesProof::create(&ledger, &owned, liabilities, &mut rng)?;
+    let solvent = reserves.verify(&ledger);
+    say!(report, "\nIts {} output(s) on the chain hold at least L: {}", owned.len(), outcome(&solvent));
+
+    // The exchange leaves carol out, to claim smaller liabilities.
+    let (carol, opening) = tree.account(2).expect("carol has a leaf");
+    let without: Vec<(String, u64)> = accounts.iter().filter(|(id, _)| id != carol).cloned().collect();
+    let cheat = LiabilityTree::new(&without, &mut rng)?;
+    let caught = tree.prove(2).expect("carol has a leaf").verify(cheat.root(), carol, &opening);
+    say!(report, "\nA cheating exchange leaves {} out: L = {}.", carol, cheat.total().value);
+    say!(report, "  {} checks her old proof against the new root: {}", carol, outcome(&caught));
+    say!(report, "  (She cannot get a proof for the new root at all: no leaf holds her balance.)");
+
+    report.record(
+        "solvency",
+        json!({
+            "root": { "hash": to_hex(&root.hash), "commitment": root.commitment.as_i64() },
+            "liabilities": liabilities,
+            "total_opened": result_json(&shown),
+            "inclusion": checks,
+            "reserves": result_json(&solvent),
+            "customer_left_out": result_json(&caught),
+        }),
+    );
+    Ok(())
+}
+
+pub fn shamir(report: &mut Report, seed: u64) -> Result<(), CtError> {
+    let mut rng = ToyRng::new(seed);
+    let mut ledger = Ledger::new();
+    let subsidy = params::active().subsidy;
+    let vault = Opening::new(Scalar::new(subsidy as i64), Blinding::random(&mut rng));
+    let entry = TxEntry::new(vault.commit(), range_proof_create(vault.value, vault.blinding, &mut rng)?);
+    let coinbase = Transaction::coinbase(subsidy, vec![entry], Kernel::create(vault.blinding, 0, &mut rng));
+    ledger.apply_block(&Block::new(&ledger, vec![coinbase]))?;
+    say!(report, "Block 0: a coinbase mints {} into the vault, C = {}.", subsidy, vault.commit());
+
+    let shares = shamir::split(vault.blinding, 2, 3, &mut rng)?;
+    say!(report, "\nThe vault's blinding r is split 2-of-3 with f(x) = r + a*x, a random:");
+    for share in &shares {
+        say!(report, "  custodian {} holds f({}) = {}", share.index, share.index, share.value);
+    }
+
+    // One share is a point on a line through (0, r) with unknown slope: any r fits it.
+    // A share is any field element, so commit through lin_comb rather than pedersen_commit.
+    let alone = shamir::combine(&shares[..1])?;
+    let guessed = Commitment::from_i64(lin_comb(vault.value.as_i64(), alone.as_i64()));
+    say!(report, "\nCustodian 1 alone rebuilds r = {}, which commits to {}: not C.", alone.as_i64(), guessed);
+
+    let mut pairs = Vec::new();
+    for (a, b) in [(0, 1), (0, 2), (1, 2)] {
+        let blinding = shamir::combine(&[shares[a], shares[b]])?;
+        let opens = Opening::new(vault.value, blinding).commit() == vault.commit();
+        say!(
+            report,
+            "Custodians {} and {} rebuild r = {}: {}",
+            shares[a].index,
+            shares[b].index,
+            blinding.as_i64(),
+            if opens { "opens C" } else { "does not open C" }
+        );
+        pairs.push(json!({ "custodians": [shares[a].index, shares[b].index], "opens": opens }));
+    }
+
+    let fee = 2;
+    let recovered = Opening::new(vault.value, shamir::combine(&[shares[0], shares[2]])?);
+    let (tx, openings) = TransactionBuilder::new()
+        .add_input(vault.commit(), recovered)
+        .add_output(Scalar::new((subsidy - fee) as i64))
+        .fee(fee)
+        .kernel()
+        .build(&mut rng)?;
+    let verified = tx.verify();
+    say!(report, "\nCustodians 1 and 3 spend the vault into C = {}: {}", tx.outputs[0].commitment, outcome(&verified));
+    let block = Block::new(&ledger, vec![tx]);
+    ledger.apply_block(&block)?;
+    say!(report, "Block 1: the spend is mined, and {} of {} stays in a new output.", openings[0].value, subsidy);
+
+    report.record(
+        "shamir",
+        json!({
+            "threshold": 2,
+            "shares": shares.iter().map(|s| json!({ "index": s.index, "value": s.value })).collect::<Vec<_>>(),
+            "one_share_opens": guessed == vault.commit(),
+            "pairs": pairs,
+            "spend": result_json(&verified),
+        }),
+    );
+    Ok(())
+}
+
+pub fn musig(report: &mut Report, signers: usize, seed: u64) -> Result<(), CtError> {
+    if signers < 2 {
+        return Err(CtError::MalformedInput("--signers must be at least 2".to_string()));
+    }
+    let mut rng = ToyRng::new(seed);
+    let fee = signers as u64;
+    let (mut inputs, mut outputs, mut offset) = (Vec::new(), Vec::new(), Blinding::default());
+    let mut excesses = Vec::new();
+    say!(report, "{} wallets each spend one coin into one transaction and pay 1 of the fee:", signers);
+    for i in 1..=signers {
+        let coin = Opening::new(Scalar::new(10 * i as i64), Blinding::random(&mut rng));
+        let change = Opening::new(Scalar::new(coin.value.as_i64() - 1), Blinding::random(&mut rng));
+        inputs.push(TxEntry::input(coin.commit()));
+        outputs.push(TxEntry::new(change.commit(), range_proof_create(change.value, change.blinding, &mut rng)?));
+        // Each wallet's share of the excess, x_i = r_out - r_in - offset_i, never leaves it.
+        let (offset_i, excess) = split_excess(change.blinding - coin.blinding, &mut rng);
+        offset = offset + offset_i;
+        excesses.push(excess);
+        say!(report, "  signer {}: {} -> {}, x_{}*H = {}", i, coin.commit(), change.commit(), i, public_key(excess));
+    }
+
+    say!(report, "\nRound 1: every signer publishes two nonces R_i1, R_i2.");
+    let (secret_nonces, public_nonces): (Vec<_>, Vec<_>) = (0..signers).map(|_| musig::nonce_round(&mut rng)).unzip();
+    for (i, nonces) in public_nonces.iter().enumerate() {
+        say!(report, "  signer {}: R_1 = {}, R_2 = {}", i + 1, nonces.first, nonces.second);
+    }
+    let session_signers = excesses
+        .iter()
+        .zip(&public_nonces)
+        .map(|(&excess, &nonces)| musig::Signer { public_excess: public_key(excess), nonces })
+        .collect();
+    let session = musig::Session::new(session_signers, &[fee as i64])?;
+    say!(report, "  b = hash(E, all nonces, fee) = {}", session.binding);
+    say!(report, "  R = sum(R_i1) + b*sum(R_i2) = {}", session.nonce);
+
+    say!(report, "\nRound 2: every signer sends s_i = k_i1 + b*k_i2 + e*x_i.");
+    let partials: Vec<i64> = excesses
+        .iter()
+        .zip(secret_nonces)
+        .map(|(&excess, nonces)| session.sign(excess, nonces))
+        .collect();
+    for (i, s) in partials.iter().enumerate() {
+        let check = if session.verify_partial(i, *s) { "ok" } else { "fails" };
+        say!(report, "  signer {}: s_{} = {} ({})", i + 1, i + 1, s, check);
+    }
+    let mut forged = partials.clone();
+    forged[signers - 1] += 1;
+    let caught = session.aggregate(&forged).map(|_| ());
+    say!(report, "  With signer {}'s s off by one: {}", signers, outcome(&caught));
+
+    let signature = session.aggregate(&partials)?;
+    let kernel = Kernel { excess: session.public, signature, lock_height: 0 };
+    let tx = Transaction::new(inputs, outputs).with_fee(fee).with_offset(offset).with_kernel(kernel);
+    let verified = tx.verify();
+    say!(
+        report,
+        "\nOne kernel for all {}: E = {}, (R, s) = ({}, {})",
+        signers,
+        kernel.excess,
+        signature.nonce,
+        signature.s
+    );
+    say!(report, "  The transaction verifies like any other: {}", outcome(&verified));
+
+    report.record(
+        "musig",
+        json!({
+            "signers": signers,
+            "binding": session.binding,
+            "kernel": { "excess": kernel.excess.as_i64(), "nonce": signature.nonce, "s": signature.s },
+            "forged_partial": result_json(&caught),
+            "transaction": result_json(&verified),
+        }),
+    );
+    Ok(())
+}
+
+pub fn timelock(report: &mut Report, seed: u64) -> Result<(), CtError> {
+    let mut rng = ToyRng::new(seed);
+    let mut ledger = Ledger::new();
+    let subsidy = params::active().subsidy;
+    let coin = Opening::new(Scalar::new(subsidy as i64), Blinding::random(&mut rng));
+    let entry = TxEntry::new(coin.commit(), range_proof_create(coin.value, coin.blinding, &mut rng)?);
+    let coinbase = Transaction::coinbase(subsidy, vec![entry], Kernel::create(coin.blinding, 0, &mut rng));
+    ledger.apply_block(&Block::new(&ledger, vec![coinbase]))?;
+    say!(report, "Block 0: a coinbase mints {} to Alice, C = {}.", subsidy, coin.commit());
+
+    let (mut pay, openings) = TransactionBuilder::new()
+        .add_input(coin.commit(), coin)
+        .add_output(Scalar::new(20))
+        .add_output(Scalar::new(subsidy as i64 - 21))
+        .fee(1)
+        .kernel()
+        .lock_height(3)
+        .build(&mut rng)?;
+    pay.outputs[0].lock_height = 5;
+    let kernel = pay.kernel.expect("built with a kernel");
+    say!(report, "\nAlice pays Bob 20. The kernel signs [fee, 3], so no block below 3 may hold it,");
+    say!(report, "and Bob's output C = {} is locked until height 5.", openings[0].commit());
+    let mut lowered = pay.clone();
+    lowered.kernel = Some(Kernel { lock_height: 1, ..kernel });
+    let lowered_verdict = lowered.verify();
+    say!(report, "  The same kernel claiming height 1: {}", outcome(&lowered_verdict));
+
+    let early = ledger.apply_block(&Block::new(&ledger, vec![pay.clone()])).map(|_| ());
+    say!(report, "  Block 1 with the payment: {}", outcome(&early));
+    for _ in 1..3 {
+        ledger.apply_block(&Block::new(&ledger, Vec::new()))?;
+    }
+    say!(report, "  Blocks 1 and 2 are mined without it.");
+    let paid = ledger.apply_block(&Block::new(&ledger, vec![pay])).map(|_| ());
+    say!(report, "  Block 3 with the payment: {}", outcome(&paid));
+    paid.clone()?;
+
+    let bob = openings[0];
+    let (mut spend, kept) = TransactionBuilder::new()
+        .add_input(bob.commit(), bob)
+        .add_output(Scalar::new(bob.value.as_i64() - 1))
+        .fee(1)
+        .kernel()
+        .build(&mut rng)?;
+    spend.outputs[0].relative_lock = 2;
+    say!(report, "\nBob spends his output right away, into C = {} with a relative lock of 2:", kept[0].commit());
+    let too_soon = ledger.apply_block(&Block::new(&ledger, vec![spend.clone()])).map(|_| ());
+    say!(report, "  Block 4 with the spend: {}", outcome(&too_soon));
+    ledger.apply_block(&Block::new(&ledger, Vec::new()))?;
+    say!(report, "  Block 4 is mined without it.");
+    let spent = ledger.apply_block(&Block::new(&ledger, vec![spend])).map(|_| ());
+    say!(report, "  Block 5 with the spend: {}", outcome(&spent));
+    spent.clone()?;
+
+    let kept = kept[0];
+    let (again, _) = TransactionBuilder::new()
+        .add_input(kept.commit(), kept)
+        .add_output(Scalar::new(kept.value.as_i64() - 1))
+        .fee(1)
+        .kernel()
+        .build(&mut rng)?;
+    say!(report, "\nC = {} was created in block 5, so it waits until block 7:", kept.commit());
+    let relative_early = ledger.apply_block(&Block::new(&ledger, vec![again.clone()])).map(|_| ());
+    say!(report, "  Block 6 with a spend of it: {}", outcome(&relative_early));
+    ledger.apply_block(&Block::new(&ledger, Vec::new()))?;
+    let relative_later = ledger.apply_block(&Block::new(&ledger, vec![again])).map(|_| ());
+    say!(report, "  Block 7 with it: {}", outcome(&relative_later));
+    relative_later.clone()?;
+
+    report.record(
+        "timelock",
+        json!({
+            "kernel_lock_height": kernel.lock_height,
+            "lowered_lock_height": result_json(&lowered_verdict),
+            "payment_at_1": result_json(&early),
+            "payment_at_3": result_json(&paid),
+            "spend_at_4": result_json(&too_soon),
+            "spend_at_5": result_json(&spent),
+            "relative_at_6": result_json(&relative_early),
+            "relative_at_7": result_json(&relative_later),
+        }),
+    );
+    Ok(())
+}
+
+/// The parties' amounts in `ct mpc`; none of them sees another's.
+const MPC_VALUES: [(&str, i64); 3] = [("alice", 5), ("bob", 200), ("carol", 37)];
+
+pub fn mpc(report: &mut Report, seed: u64) -> Result<(), CtError> {
+    let mut rng = ToyRng::new(seed);
+    say!(report, "Round 1: each party commits to its amount bit by bit and sends the dealer its announcement.");
+    let (mut parties, mut messages) = (Vec::new(), Vec::new());
+    for &(name, value) in &MPC_VALUES {
+        let opening = Opening::new(Scalar::new(value), Blinding::random(&mut rng));
+        let (party, message) = mpc::Party::new(opening, &mut rng)?;
+        say!(report, "  {}: C = {}, {} bit commitments", name, message.commitment, message.bits.len());
+        parties.push(party);
+        messages.push(message);
+    }
+    let dealer = mpc::Dealer::new(messages)?;
+    say!(report, "The dealer checks each party's bits recompose to its C and draws e = {}.", dealer.challenge());
+
+    say!(report, "\nRound 2: each party answers e for its own bits only.");
+    let shares: Vec<mpc::ProofShare> = parties.into_iter().map(|party| party.respond(dealer.challenge())).collect();
+    let mut tampered = shares.clone();
+    tampered[1].response[2] += 1;
+    let blamed = dealer.finish(&tampered).map(|_| ());
+    say!(report, "  With bob's share altered: {}", outcome(&blamed));
+    let proof = dealer.finish(&shares)?;
+    let commitments = dealer.commitments();
+    let verified = aggregate_range_proof_verify(&commitments, &proof);
+    say!(report, "  The dealer assembles one proof {}", proof);
+    say!(report, "  Anyone checks it against the three commitments: {}", outcome(&verified));
+    // A separate proof sends 7 numbers per bit (`RangeProof::num_elements`).
+    let separate = commitments.len() * 7 * params::active().range_bits as usize;
+    say!(report, "  ({} numbers, against {} for three separate range proofs.)", proof.num_elements(), separate);
+
+    report.record(
+        "mpc",
+        json!({
+            "commitments": commitments.iter().map(|c| c.as_i64()).collect::<Vec<_>>(),
+            "challenge": dealer.challenge(),
+            "numbers": proof.num_elements(),
+            "tampered_share": result_json(&blamed),
+            "aggregate_proof": result_json(&verified),
+        }),
+    );
+    Ok(())
+}
+
+pub fn coinjoin(report: &mut Report, seed: u64) -> Result<(), CtError> {
+    let mut rng = ToyRng::new(seed);
+    let mut ledger = Ledger::new();
+    let subsidy = params::active().subsidy;
+    let faucet = Opening::new(Scalar::new(subsidy as i64), Blinding::random(&mut rng));
+    let entry = TxEntry::new(faucet.commit(), range_proof_create(faucet.value, faucet.blinding, &mut rng)?);
+    let coinbase = Transaction::coinbase(subsidy, vec![entry], Kernel::create(faucet.blinding, 0, &mut rng));
+    ledger.apply_block(&Block::new(&ledger, vec![coinbase]))?;
+    let values = [subsidy * 2 / 5, subsidy * 9 / 25, subsidy / 5];
+    let (funding, coins) = TransactionBuilder::new()
+        .add_input(faucet.commit(), faucet)
+        .add_output(Scalar::new(values[0] as i64))
+        .add_output(Scalar::new(values[1] as i64))
+        .add_output(Scalar::new(values[2] as i64))
+        .fee(subsidy - values.iter().sum::<u64>())
+        .kernel()
+        .build(&mut rng)?;
+    ledger.apply_block(&Block::new(&ledger, vec![funding]))?;
+    say!(report, "Blocks 0-1: alice, bob and carol get coins of {}, {} and {}.", values[0], values[1], values[2]);
+
+    // Each wallet pays 1 of the fee and splits the rest its own way.
+    let mut plans = Vec::new();
+    let wallets = [
+        ("alice", coins[0], vec![values[0] / 3]),
+        ("bob", coins[1], vec![]),
+        ("carol", coins[2], vec![values[2] / 2]),
+    ];
+    for (name, coin, parts) in wallets {
+        let rest = coin.value.as_i64() - 1 - parts.iter().sum::<u64>() as i64;
+        let values = parts.iter().map(|&v| v as i64).chain([rest]);
+        let outputs: Vec<Opening> = values.map(|v| Opening::new(Scalar::new(v), Blinding::random(&mut rng))).collect();
+        plans.push((name, coin, outputs));
+    }
+    let mut coordinator = Coordinator::new();
+    let mut secrets = Vec::new();
+    let mut owners = Vec::new();
+    say!(report, "\nRegistration: each wallet sends its inputs, outputs, fee share, offset share and nonces.");
+    for (name, coin, outputs) in &plans {
+        let (registration, wallet_secrets) = Registration::new(&[*coin], outputs, 1, &mut rng)?;
+        owners.extend(registration.outputs.iter().map(|output| (output.commitment, *name)));
+        owners.push((coin.commit(), *name));
+        coordinator.register(registration, &ledger)?;
+        secrets.push(wallet_secrets);
+        say!(report, "  {}: 1 input, {} output(s), fee 1: accepted", name, outputs.len());
+    }
+    let elsewhere = Opening::new(Scalar::new(coins[0].value.as_i64() - 1), Blinding::random(&mut rng));
+    let (again, _) = Registration::new(&[coins[0]], &[elsewhere], 1, &mut rng)?;
+    let conflict = coordinator.clone().register(again, &ledger);
+    say!(report, "  alice's coin registered a second time: {}", outcome(&conflict));
+
+    let session = coordinator.session()?;
+    let partials: Vec<i64> = secrets.into_iter().map(|wallet| wallet.sign(&session)).collect();
+    let tx = coordinator.finish(&session, &partials)?;
+    say!(report, "\nSigning: one MuSig2 session for the total fee {}; the partials add up to one kernel.", tx.fee);
+    let mut mempool = Mempool::new();
+    mempool.add(tx.clone(), &ledger)?;
+    let block = mempool.block_template(&ledger, None);
+    ledger.apply_block(&block)?;
+    say!(
+        report,
+        "Block 2: the CoinJoin is mined, {} inputs and {} outputs under {} kernel.",
+        tx.inputs.len(),
+        tx.outputs.len(),
+        block.transactions.iter().filter(|tx| tx.kernel.is_some()).count()
+    );
+
+    let owner = |commitment: Commitment| owners.iter().find(|(c, _)| *c == commitment).map_or("?", |(_, name)| *name);
+    say!(report, "\nWhat the chain shows (the owners are only known to the wallets):");
+    for input in &tx.inputs {
+        say!(report, "  in  {:>20}   ({})", input.commitment.to_string(), owner(input.commitment));
+    }
+    for output in &tx.outputs {
+        say!(report, "  out {:>20}   ({})", output.commitment.to_string(), owner(output.commitment));
+    }
+    // An observer trying to split the transaction back up: which groups of inputs and
+    // outputs, with which part of the fee, balance against the kernel and offset?
+    let kernel = tx.kernel.expect("the coordinator signs a kernel");
+    let target = kernel.excess + public_key(tx.offset);
+    let subsets = |entries: &[TxEntry]| -> Vec<Vec<Commitment>> {
+        (1..1u32 << entries.len())
+            .map(|mask| (0..entries.len()).filter(|i| mask >> i & 1 == 1).map(|i| entries[i].commitment).collect())
+            .collect()
+    };
+    let (input_groups, output_groups) = (subsets(&tx.inputs), subsets(&tx.outputs));
+    let mut tried = 0;
+    let mut balanced = 0;
+    for inputs in &input_groups {
+        for outputs in &output_groups {
+            for fee in 0..=tx.fee {
+                tried += 1;
+                if kernel::excess(inputs, outputs, fee) == target {
+                    balanced += 1;
+                }
+            }
+        }
+    }
+    say!(report, "Of {} splits into (inputs, outputs, fee), {} balances against the kernel:", tried, balanced);
+    say!(report, "  the whole transaction. Nothing on the chain says which wallet a group belongs to,");
+    say!(report, "  and with the amounts hidden, nothing else ties an output to an input.");
+
+    report.record(
+        "coinjoin",
+        json!({
+            "inputs": tx.inputs.len(),
+            "outputs": tx.outputs.len(),
+            "kernels": 1,
+            "fee": tx.fee,
+            "second_registration": result_json(&conflict),
+            "groupings_tried": tried,
+            "groupings_balancing": balanced,
+        }),
+    );
+    Ok(())
+}
+
+pub fn escrow(report: &mut Report, seed: u64) -> Result<(), CtError> {
+    let mut rng = ToyRng::new(seed);
+    let mut ledger = Ledger::new();
+    let subsidy = params::active().subsidy;
+    let coin = Opening::new(Scalar::new(subsidy as i64), Blinding::random(&mut rng));
+    let entry = TxEntry::new(coin.commit(), range_proof_create(coin.value, coin.blinding, &mut rng)?);
+    let coinbase = Transaction::coinbase(subsidy, vec![entry], Kernel::create(coin.blinding, 0, &mut rng));
+    ledger.apply_block(&Block::new(&ledger, vec![coinbase]))?;
+    say!(report, "Block 0: a coinbase mints {} to Alice, C = {}.", subsidy, coin.commit());
+
+    let [a, b, c] = [(); 3].map(|()| Blinding::new(rng.scalar()));
+    let [key_a, key_b, key_c] = [a, b, c].map(public_key);
+    let two = |x, y| Condition::And(vec![Condition::Signature(x), Condition::Signature(y)]);
+    let branches = vec![
+        two(key_a, key_b),
+        two(key_b, key_c),
+        two(key_a, key_c),
+        Condition::And(vec![Condition::After(10), Condition::Signature(key_a)]),
+    ];
+    let condition = Condition::Or(branches.clone());
+    let (mut pay, openings) = TransactionBuilder::new()
+        .add_input(coin.commit(), coin)
+        .add_output(Scalar::new(20))
+        .add_output(Scalar::new(subsidy as i64 - 21))
+        .fee(1)
+        .kernel()
+        .build(&mut rng)?;
+    pay.outputs[0].lock = Some(condition.clone());
+    ledger.apply_block(&Block::new(&ledger, vec![pay]))?;
+    let escrow = openings[0];
+    say!(report, "\nAlice buys from Bob, with Carol to settle disputes. Their keys:");
+    say!(report, "  Alice {}, Bob {}, Carol {}", key_a, key_b, key_c);
+    say!(report, "Block 1: Alice pays 20 into C = {}, locked to the or of", escrow.commit());
+    for (i, branch) in branches.iter().enumerate() {
+        say!(report, "  {}: {}", i, branch);
+    }
+    say!(report, "and gives Bob and Carol its opening.");
+
+    // The spend of the escrow to whoever gets the coins, with the witness `unlock` builds
+    // from the kernel.
+    let mut spend = |unlock: &dyn Fn(&Kernel, &mut ToyRng) -> Witness| -> Result<Transaction, CtError> {
+        let (mut tx, _) = TransactionBuilder::new()
+            .add_input(escrow.commit(), escrow)
+            .add_output(Scalar::new(escrow.value.as_i64() - 1))
+            .fee(1)
+            .kernel()
+            .build(&mut rng)?;
+        let kernel = tx.kernel.expect("built with a kernel");
+        tx.inputs[0].unlock = Some(unlock(&kernel, &mut rng));
+        Ok(tx)
+    };
+    let sign = |secret| move |kernel: &Kernel, rng: &mut ToyRng| condition::sign(secret, escrow.commit(), kernel, rng);
+    let both = |x: Blinding, y: Blinding, branch| {
+        move |kernel: &Kernel, rng: &mut ToyRng| {
+            Witness::Or(branch, Box::new(Witness::And(vec![sign(x)(kernel, rng), sign(y)(kernel, rng)])))
+        }
+    };
+
+    say!(report, "\nThe goods never arrive as agreed, and both want the coins.");
+    let alone = spend(&both(b, b, 1))?;
+    let alone_verdict = ledger.apply_block(&Block::new(&ledger, vec![alone])).map(|_| ());
+    say!(report, "  Bob signs both halves of branch 1 himself: {}", outcome(&alone_verdict));
+    let refund = spend(&|kernel: &Kernel, rng: &mut ToyRng| {
+        Witness::Or(3, Box::new(Witness::And(vec![Witness::After, sign(a)(kernel, rng)])))
+    })?;
+    let early_refund = ledger.apply_block(&Block::new(&ledger, vec![refund])).map(|_| ());
+    say!(report, "  Alice takes her refund (branch 3) at block 2: {}", outcome(&early_refund));
+    let settled = spend(&both(b, c, 1))?;
+    let settled_verdict = ledger.apply_block(&Block::new(&ledger, vec![settled])).map(|_| ());
+    say!(report, "  Carol sides with Bob, and both sign branch 1: {}", outcome(&settled_verdict));
+    settled_verdict.clone()?;
+
+    report.record(
+        "escrow",
+        json!({
+            "condition": condition.to_string(),
+            "seller_alone": result_json(&alone_verdict),
+            "early_refund": result_json(&early_refund),
+            "seller_and_arbiter": result_json(&settled_verdict),
+        }),
+    );
+    Ok(())
+}
+
+/// Pay `amount` of `input` into a 2-of-2 output of the funder and a cosigner, and mine
+/// it on `chain`. Returns the funder's share, then the cosigner's.
+fn fund_two_of_two(
+    chain: &mut Chain,
+    input: Opening,
+    amount: u64,
+    rng: &mut ToyRng,
+) -> Result<(Share, Share), CtError> {
+    let (offer, offer_secrets) = Offer::new(rng);
+    let (funding, funder, _) = Funding::new(input, amount, 1, &offer, rng)?;
+    let (tx, cosigner) = funding.cosign(&offer_secrets)?;
+    chain.add_block(Block::new(chain.ledger(), vec![tx]))?;
+    Ok((funder, cosigner))
+}
+
+pub fn scriptless_swap(report: &mut Report, seed: u64) -> Result<(), CtError> {
+    let mut rng = ToyRng::new(seed);
+    let subsidy = params::active().subsidy;
+    let mut chains = Vec::new();
+    for (owner, name) in [("Alice", "A"), ("Bob", "B")] {
+        let coin = Opening::new(Scalar::new(subsidy as i64), Blinding::random(&mut rng));
+        let entry = TxEntry::new(coin.commit(), range_proof_create(coin.value, coin.blinding, &mut rng)?);
+        let coinbase = Transaction::coinbase(subsidy, vec![entry], Kernel::create(coin.blinding, 0, &mut rng));
+        chains.push((Chain::new(Block::new(&Ledger::new(), vec![coinbase]))?, coin));
+        say!(report, "Chain {}, block 0: a coinbase mints {} to {}, C = {}.", name, subsidy, owner, coin.commit());
+    }
+    let [(mut chain_a, alice_coin), (mut chain_b, bob_coin)] = <[_; 2]>::try_from(chains).expect("two chains");
+
+    say!(report, "\nBlock 1 on each chain: Alice pays 30 into a 2-of-2 output with Bob on A, Bob 40 on B.");
+    let (alice_share_a, bob_share_a) = fund_two_of_two(&mut chain_a, alice_coin, 30, &mut rng)?;
+    let (bob_share_b, alice_share_b) = fund_two_of_two(&mut chain_b, bob_coin, 40, &mut rng)?;
+    say!(report, "  chain A: C = {}; chain B: C = {}", alice_share_a.commitment, bob_share_b.commitment);
+
+    let t = Blinding::new(rng.scalar());
+    let adaptor = public_key(t);
+    say!(report, "\nAlice picks t and sends Bob T = t*H = {}.", adaptor);
+
+    say!(report, "\nThe claim on chain A pays Bob. Both sign it with T in the kernel nonce, Alice first:");
+    let (mut claim_a, bob_output, bob_secrets) = ScriptlessClaim::new(&bob_share_a, 1, adaptor, &mut rng)?;
+    let alice_secrets = claim_a.join(&alice_share_a, &mut rng)?;
+    let s_alice = claim_a.sign(&alice_secrets)?;
+    let s_bob = claim_a.sign(&bob_secrets)?;
+    let presignature_a = claim_a.presignature()?;
+    say!(report, "  s_alice = {}, s_bob = {}: pre-signature s' = {} (ok)", s_alice, s_bob, presignature_a.s);
+    let without_secret = claim_a.complete(Blinding::default()).map(|_| ());
+    say!(report, "  Bob mines it with s' alone: {}", outcome(&without_secret));
+
+    say!(report, "\nThe claim on chain B pays Alice. Bob holds his pre-signature, so he signs this one too:");
+    let (mut claim_b, alice_output, alice_secrets) = ScriptlessClaim::new(&alice_share_b, 1, adaptor, &mut rng)?;
+    let bob_secrets = claim_b.join(&bob_share_b, &mut rng)?;
+    let s_alice = claim_b.sign(&alice_secrets)?;
+    let s_bob = claim_b.sign(&bob_secrets)?;
+    let presignature_b = claim_b.presignature()?;
+    say!(report, "  s_alice = {}, s_bob = {}: pre-signature s' = {} (ok)", s_alice, s_bob, presignature_b.s);
+
+    let tx_b = claim_b.complete(t)?;
+    let kernel_b = tx_b.kernel.expect("a completed claim has a kernel");
+    chain_b.add_block(Block::new(chain_b.ledger(), vec![tx_b]))?;
+    say!(report, "\nChain B, block 2: Alice adds t and mines her claim, kernel s = {}.", kernel_b.signature.s);
+    say!(report, "  Alice now owns C = {} (value {}).", alice_output.commit(), alice_output.value);
+
+    let no_claim = || CtError::MalformedInput("Alice's claim is not on chain B".to_string());
+    let learned = claim_b.extract(&chain_b)?.ok_or_else(no_claim)?;
+    let same = if learned == t { "Alice's t" } else { "not Alice's t" };
+    say!(report, "\nBob reads that kernel: s - s' = {}, {}.", learned.as_i64(), same);
+    let tx_a = claim_a.complete(learned)?;
+    let txid_a = tx_a.txid();
+    chain_a.add_block(Block::new(chain_a.ledger(), vec![tx_a]))?;
+    say!(report, "Chain A, block 2: Bob completes his claim with it and mines it, tx {}.", txid_a);
+    say!(report, "  Bob now owns C = {} (value {}).", bob_output.commit(), bob_output.value);
+    say!(report, "\nOn chain both claims are plain 2-of-2 spends: no hash and no lock ties them together.");
+
+    report.record(
+        "scriptless",
+        json!({
+            "adaptor": adaptor.as_i64(),
+            "presignature_only": result_json(&without_secret),
+            "kernel_b": { "nonce": kernel_b.signature.nonce, "s": kernel_b.signature.s },
+            "extracted": learned == t,
+            "heights": [chain_a.tip().height, chain_b.tip().height],
+        }),
+    );
+    Ok(())
+}
@@ -0,0 +1,428 @@
+//! A wallet: the openings (v, r) of the outputs the user controls. The ledger only
+//! ever shows commitments, so without the openings an output can neither be
+//! counted nor spent. `sync` follows the ledger: an output becomes unspent once a
+//! block confirms it and spent once it leaves the UTXO set.
+//!
+//! New outputs get their blindings from the wallet's `Keychain`, so the seed and
+//! each output's path are enough to recompute them.
+//!
+//! `pay` builds a transaction from the wallet: coin selection (see `coin_selection`)
+//! picks the unspent outputs to spend, and the change gets a derived blinding and
+//! a rewindable range proof.
+//!
+//! `restore` rebuilds a lost wallet from the seed and the chain alone: it rewinds
+//! every range proof with the nonce the seed gives for its commitment, and scans
+//! the notes and memos for payments to the wallet's address. Outputs whose opening was only
+//! ever in the wallet file (`add`, or derived but never on chain with a rewindable
+//! proof) cannot be found that way.
+//!
+//! `scan` finds outputs paid to the wallet's address (see `ecdh`): it reads every
+//! note on the chain with the scan secret and adds the outputs whose recomputed
+//! opening matches. It opens every memo (see `memo`) with the same secret too.
+//!
+//! The history lists every payment sent and received. An entry is confirmed once
+//! the output it watches (the payment we sent, or the output we received) is in
+//! the ledger's output MMR; `sync` rechecks it, so a reorg can unconfirm it again.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::builder::TransactionBuilder;
+use crate::chain::Chain;
+use crate::coin_selection::{self, Strategy};
+use crate::commitment::{Commitment, Opening, Scalar};
+use crate::ecdh::{self, Address, OutputNote};
+use crate::error::CtError;
+use crate::keychain::{KeyPath, Keychain, CHANGE, GAP_LIMIT, RECEIVE};
+use crate::ledger::Ledger;
+use crate::range_proof::range_proof_rewind;
+use crate::rng::ToyRng;
+use crate::transaction::{OutPoint, Transaction, TxId};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputStatus {
+    /// Known to the wallet but not (yet) in the ledger.
+    Unconfirmed,
+    /// In the UTXO set.
+    Unspent,
+    /// Was in the UTXO set and has since been spent.
+    Spent,
+}
+
+impl fmt::Display for OutputStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            OutputStatus::Unconfirmed => "unconfirmed",
+            OutputStatus::Unspent => "unspent",
+            OutputStatus::Spent => "spent",
+        })
+    }
+}
+
+impl FromStr for OutputStatus {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unconfirmed" => Ok(OutputStatus::Unconfirmed),
+            "unspent" => Ok(OutputStatus::Unspent),
+            "spent" => Ok(OutputStatus::Spent),
+            _ => Err(CtError::MalformedInput(format!("unknown output status {:?}", s))),
+        }
+    }
+}
+
+serde_as_text!(OutputStatus);
+
+/// One output the wallet can open.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletOutput {
+    pub opening: Opening,
+    pub commitment: Commitment,
+    pub status: OutputStatus,
+    /// Where the ledger holds it; None until it is confirmed.
+    pub outpoint: Option<OutPoint>,
+    /// Where the keychain derives its blinding; None for a blinding added by hand.
+    pub path: Option<KeyPath>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Direction::Sent => "sent",
+            Direction::Received => "received",
+        })
+    }
+}
+
+impl FromStr for Direction {
+    type Err = CtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sent" => Ok(Direction::Sent),
+            "received" => Ok(Direction::Received),
+            _ => Err(CtError::MalformedInput(format!("unknown direction {:?}", s))),
+        }
+    }
+}
+
+serde_as_text!(Direction);
+
+/// One payment in the wallet's history.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// When the wallet recorded it, in seconds since the Unix epoch.
+    pub time: u64,
+    pub direction: Direction,
+    pub amount: i64,
+    /// The public fee; paid by the sender.
+    pub fee: u64,
+    /// The output whose confirmation confirms the payment.
+    pub output: Commitment,
+    /// Known when we built the transaction, or once `sync` finds the output.
+    pub txid: Option<TxId>,
+    pub confirmed: bool,
+}
+
+impl HistoryEntry {
+    /// An unconfirmed entry for a payment that creates `output`.
+    pub fn new(time: u64, direction: Direction, amount: i64, fee: u64, output: Commitment) -> Self {
+        HistoryEntry { time, direction, amount, fee, output, txid: None, confirmed: false }
+    }
+}
+
+/// A transaction `Wallet::pay` built.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Payment {
+    pub tx: Transaction,
+    /// The wallet outputs it spends.
+    pub spent: Vec<Commitment>,
+    /// The opening of the payment output, for the receiver.
+    pub payment: Opening,
+    /// The change output, now in the wallet (unconfirmed), if there is change.
+    pub change: Option<(Opening, KeyPath)>,
+}
+
+impl Payment {
+    /// The history entry for sending this payment at `time`.
+    pub fn history_entry(&self, time: u64) -> HistoryEntry {
+        let amount = self.payment.value.as_i64();
+        HistoryEntry {
+            txid: Some(self.tx.txid()),
+            ..HistoryEntry::new(time, Direction::Sent, amount, self.tx.fee, self.payment.commit())
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Wallet {
+    keychain: Keychain,
+    outputs: Vec<WalletOutput>,
+    history: Vec<HistoryEntry>,
+}
+
+impl Wallet {
+    pub fn new(keychain: Keychain) -> Self {
+        Wallet { keychain, outputs: Vec::new(), history: Vec::new() }
+    }
+
+    /// Rebuild a wallet from stored outputs. Every opening must match its commitment,
+    /// and every derived blinding must be the one the keychain gives for its path.
+    pub fn from_outputs(keychain: Keychain, outputs: Vec<WalletOutput>) -> Result<Self, CtError> {
+        for output in &outputs {
+            if output.opening.commit() != output.commitment {
+                let reason = format!("the opening stored for {} does not match it", output.commitment);
+                return Err(CtError::MalformedInput(reason));
+            }
+            if output.path.is_some_and(|path| keychain.blinding(path) != output.opening.blinding) {
+                let reason = format!("the blinding of {} is not derived from this seed", output.commitment);
+                return Err(CtError::MalformedInput(reason));
+            }
+        }
+        Ok(Wallet { keychain, outputs, history: Vec::new() })
+    }
+
+    /// Restore the history, e.g. from the wallet file.
+    pub fn with_history(mut self, history: Vec<HistoryEntry>) -> Self {
+        self.history = history;
+        self
+    }
+
+    pub fn keychain(&self) -> &Keychain {
+        &self.keychain
+    }
+
+    fn insert(&mut self, opening: Opening, path: Option<KeyPath>) -> Result<Commitment, CtError> {
+        let commitment = opening.commit();
+        if self.outputs.iter().any(|output| output.commitment == commitment) {
+            return Err(CtError::MalformedInput(format!("the wallet already holds {}", commitment)));
+        }
+        let status = OutputStatus::Unconfirmed;
+        self.outputs.push(WalletOutput { opening, commitment, status, outpoint: None, path });
+        Ok(commitment)
+    }
+
+    /// Remember an output we can open, unconfirmed until `sync` finds it.
+    /// Adding the same commitment twice is an error.
+    pub fn add(&mut self, opening: Opening) -> Result<Commitment, CtError> {
+        self.insert(opening, None)
+    }
+
+    /// The first unused path on the `change` branch of `account`.
+    pub fn next_path(&self, account: u32, change: u32) -> KeyPath {
+        let index = self
+            .outputs
+            .iter()
+            .filter_map(|output| output.path)
+            .filter(|path| (path.account, path.change) == (account, change))
+            .map(|path| path.index + 1)
+            .max()
+            .unwrap_or(0);
+        KeyPath::new(account, change, index)
+    }
+
+    /// A new output worth `value` with the blinding at the next unused path, added
+    /// unconfirmed. Returns its opening and path.
+    pub fn derive(&mut self, value: Scalar, account: u32, change: u32) -> Result<(Opening, KeyPath), CtError> {
+        let path = self.next_path(account, change);
+        let opening = Opening::new(value, self.keychain.blinding(path));
+        self.insert(opening, Some(path))?;
+        Ok((opening, path))
+    }
+
+    /// Remember a payment. `Payment::history_entry` makes one for `pay`.
+    pub fn record(&mut self, entry: HistoryEntry) {
+        self.history.push(entry);
+    }
+
+    /// Every payment, in the order they were recorded.
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.history
+    }
+
+    /// Every output, in the order they were added.
+    pub fn outputs(&self) -> &[WalletOutput] {
+        &self.outputs
+    }
+
+    /// Total value of the unspent outputs.
+    pub fn balance(&self) -> i64 {
+        self.outputs
+            .iter()
+            .filter(|output| output.status == OutputStatus::Unspent)
+            .map(|output| output.opening.value.as_i64())
+            .sum()
+    }
+
+    /// Total value of the outputs no block has confirmed yet.
+    pub fn pending(&self) -> i64 {
+        self.outputs
+            .iter()
+            .filter(|output| output.status == OutputStatus::Unconfirmed)
+            .map(|output| output.opening.value.as_i64())
+            .sum()
+    }
+
+    /// The unspent outputs to spend for `target` (amount plus fee), picked by `strategy`.
+    pub fn select(&self, target: i64, strategy: Strategy) -> Result<Vec<&WalletOutput>, CtError> {
+        let unspent: Vec<&WalletOutput> =
+            self.outputs.iter().filter(|output| output.status == OutputStatus::Unspent).collect();
+        let values: Vec<i64> = unspent.iter().map(|output| output.opening.value.as_i64()).collect();
+        Ok(coin_selection::select(&values, target, strategy)?.into_iter().map(|i| unspent[i]).collect())
+    }
+
+    /// Pay `amount` with a public `fee` from account 0: select inputs with `strategy`,
+    /// add the payment output and, if anything is left, a change output whose
+    /// blinding comes from the next change path. The change is added to the wallet.
+    /// A kernel signs for the excess, so the payment's blinding is independent of
+    /// the inputs' even when no change is needed. Paying `to` an address attaches a
+    /// note, so the receiver finds the output by scanning instead of being told (v, r);
+    /// with `memo`, the payment gets a random blinding and a memo sealing it instead.
+    pub fn pay(
+        &mut self,
+        amount: u64,
+        fee: u64,
+        strategy: Strategy,
+        to: Option<Address>,
+        memo: bool,
+        rng: &mut ToyRng,
+    ) -> Result<Payment, CtError> {
+        if amount == 0 {
+            return Err(CtError::MalformedInput("the amount to pay must be positive".to_string()));
+        }
+        let target = amount as i64 + fee as i64;
+        let inputs: Vec<(Commitment, Opening)> =
+            self.select(target, strategy)?.iter().map(|output| (output.commitment, output.opening)).collect();
+        let change_value = inputs.iter().map(|(_, opening)| opening.value.as_i64()).sum::<i64>() - target;
+
+        let mut builder = TransactionBuilder::new().fee(fee).kernel();
+        for &(commitment, opening) in &inputs {
+            builder = builder.add_input(commitment, opening);
+        }
+        builder = match to {
+            Some(address) if memo => builder.add_output(Scalar::new(amount as i64)).memo_to(address),
+            Some(address) => builder.add_output_to(Scalar::new(amount as i64), address),
+            None => builder.add_output(Scalar::new(amount as i64)),
+        };
+        let change_path = self.next_path(0, CHANGE);
+        if change_value > 0 {
+            let opening = Opening::new(Scalar::new(change_value), self.keychain.blinding(change_path));
+            let nonce = self.keychain.rewind_nonce(opening.commit());
+            builder = builder.add_output_rewindable(opening.value, opening.blinding, nonce);
+        }
+        let (tx, openings) = builder.build(rng)?;
+
+        let change = match openings.get(1) {
+            Some(&opening) => {
+                self.insert(opening, Some(change_path))?;
+                Some((opening, change_path))
+            }
+            None => None,
+        };
+        let spent = inputs.iter().map(|&(commitment, _)| commitment).collect();
+        Ok(Payment { tx, spent, payment: openings[0], change })
+    }
+
+    /// Add the outputs on `chain` that were paid to our address and are not in the
+    /// wallet yet, unconfirmed until `sync`. Returns their openings, and their notes
+    /// for outputs found by note rather than by memo.
+    pub fn scan(&mut self, chain: &Chain) -> Vec<(Opening, Option<OutputNote>)> {
+        let (secret, spend) = (self.keychain.scan_secret(), self.keychain.address().spend);
+        let mut found = Vec::new();
+        for block in chain.blocks().into_iter().filter(|block| chain.is_main(&block.header.hash())) {
+            for output in block.transactions.iter().flat_map(|tx| &tx.outputs) {
+                let received = output
+                    .note
+                    .and_then(|note| Some((ecdh::receive(secret, spend, output.commitment, &note)?, Some(note))))
+                    .or_else(|| Some((output.memo?.open(secret, output.commitment)?, None)));
+                if let Some((opening, note)) = received {
+                    if self.insert(opening, None).is_ok() {
+                        found.push((opening, note));
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// A wallet for `keychain` holding every output on `chain` that its seed can
+    /// open: rewindable proofs made with its nonces, and notes paid to its address.
+    /// Derived blindings get their path back from the first `GAP_LIMIT` unused
+    /// paths of account 0 beyond the last one found. Statuses come from the chain's ledger.
+    pub fn restore(keychain: Keychain, chain: &Chain) -> Self {
+        let (secret, spend) = (keychain.scan_secret(), keychain.address().spend);
+        let mut found = Vec::new();
+        for block in chain.blocks().into_iter().filter(|block| chain.is_main(&block.header.hash())) {
+            for tx in &block.transactions {
+                for (output, outpoint) in tx.outputs.iter().zip(tx.outpoints()) {
+                    let rewound = output.proof.as_ref().and_then(|proof| {
+                        range_proof_rewind(output.commitment, proof, &keychain.rewind_nonce(output.commitment))
+                    });
+                    let received = output.note.and_then(|note| ecdh::receive(secret, spend, output.commitment, &note));
+                    let sealed = output.memo.and_then(|memo| memo.open(secret, output.commitment));
+                    if let Some(opening) = rewound.or(received).or(sealed) {
+                        found.push((opening, outpoint));
+                    }
+                }
+            }
+        }
+
+        let mut paths = Vec::new();
+        let (mut index, mut limit) = (0, GAP_LIMIT);
+        while index < limit {
+            for change in [RECEIVE, CHANGE] {
+                let path = KeyPath::new(0, change, index);
+                if found.iter().any(|(opening, _)| opening.blinding == keychain.blinding(path)) {
+                    paths.push(path);
+                    limit = index + 1 + GAP_LIMIT;
+                }
+            }
+            index += 1;
+        }
+
+        let mut wallet = Wallet::new(keychain);
+        for (opening, outpoint) in found {
+            let path = paths.iter().copied().find(|&path| keychain.blinding(path) == opening.blinding);
+            if wallet.insert(opening, path).is_ok() {
+                wallet.outputs.last_mut().expect("just inserted").outpoint = Some(outpoint);
+            }
+        }
+        wallet.sync(chain.ledger());
+        wallet
+    }
+
+    /// Update every output from the ledger: in the UTXO set means unspent; gone
+    /// after having been confirmed means spent. Also confirms (or, after a reorg,
+    /// unconfirms) history entries. Returns how many outputs changed.
+    pub fn sync(&mut self, ledger: &Ledger) -> usize {
+        for entry in &mut self.history {
+            entry.confirmed = ledger.output_mmr().prove(entry.output).is_some();
+            if entry.txid.is_none() {
+                entry.txid = ledger.find(entry.output).map(|outpoint| outpoint.txid);
+            }
+        }
+        let mut changed = 0;
+        for output in &mut self.outputs {
+            let (status, outpoint) = match ledger.find(output.commitment) {
+                Some(outpoint) => (OutputStatus::Unspent, Some(outpoint)),
+                None if output.outpoint.is_some() => (OutputStatus::Spent, output.outpoint),
+                None => (OutputStatus::Unconfirmed, None),
+            };
+            if (status, outpoint) != (output.status, output.outpoint) {
+                output.status = status;
+                output.outpoint = outpoint;
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
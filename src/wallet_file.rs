@@ -0,0 +1,342 @@
+//! The wallet file `ct wallet` keeps between runs (`wallet.json` by default):
+//!
+//! ```json
+//! { "seed": "<32 bytes, hex>",
+//!   "outputs": [{ "value": 5, "path": "m/0/0/0", "commitment": 77792,
+//!                 "status": "unspent", "outpoint": "<txid>:0" },
+//!               { "value": 10, "blinding": 12345, "commitment": 86445, "status": "unconfirmed" }],
+//!   "history": [{ "time": 1760000000, "direction": "received", "amount": 5, "fee": 0,
+//!                 "output": 77792, "txid": "<txid>", "status": "confirmed" }] }
+//! ```
+//!
+//! Outputs derived from the seed store their `path` and their blinding is
+//! recomputed on load; outputs added by hand store the `blinding` itself.
+//! `status` is `unconfirmed`, `unspent` or `spent`; `outpoint` is present once a
+//! block has confirmed the output. A history entry's `time` is in seconds since the
+//! Unix epoch, and its `status` is `confirmed` or `pending`. The seed opens every derived output, so the
+//! file is as secret as the outputs are valuable. Loading checks every opening
+//! against its commitment.
+//!
+//! An encrypted wallet file keeps that whole document sealed under a key stretched
+//! from a passphrase:
+//!
+//! ```json
+//! { "cipher": "xchacha20-poly1305", "kdf": "pbkdf2-hmac-sha512", "rounds": 10000,
+//!   "salt": "<16 bytes, hex>", "nonce": "<24 bytes, hex>", "ciphertext": "<hex, tag last>" }
+//! ```
+//!
+//! Every save draws a fresh nonce; the salt (and so the key) stays until the
+//! passphrase changes.
+//!
+//! A watch-only wallet file (`watch.json` for `ct watch`) holds a view key and the
+//! outputs it found, without blindings:
+//!
+//! ```json
+//! { "view_key": "view/0/<k*H>",
+//!   "outputs": [{ "value": 5, "path": "m/0/0/0", "commitment": 77792,
+//!                 "status": "unspent", "outpoint": "<txid>:0" }] }
+//! ```
+
+use pedersen_commitment_tx::aead::{self, KEY_LEN, NONCE_LEN};
+use pedersen_commitment_tx::commitment::{Blinding, Commitment, Opening, Scalar};
+use pedersen_commitment_tx::error::CtError;
+use pedersen_commitment_tx::keychain::{Keychain, Seed, ViewKey};
+use pedersen_commitment_tx::kdf::pbkdf2_sha512;
+use pedersen_commitment_tx::rng::os_random;
+use pedersen_commitment_tx::wallet::{HistoryEntry, Wallet, WalletOutput};
+use pedersen_commitment_tx::watch::{WatchWallet, WatchedOutput};
+use pedersen_commitment_tx::wire::{from_hex, to_hex};
+use serde_json::{json, Value};
+
+use crate::slate_file::parse_json;
+
+/// An output as listed by `ct wallet outputs`: with its blinding, derived or not.
+pub fn output_json(output: &WalletOutput) -> Value {
+    output_fields(output, true)
+}
+
+/// The stored form leaves a derived blinding out (`with_blinding` false).
+fn output_fields(output: &WalletOutput, with_blinding: bool) -> Value {
+    let mut document = json!({ "value": output.opening.value.as_i64() });
+    if with_blinding || output.path.is_none() {
+        document["blinding"] = json!(output.opening.blinding.as_i64());
+    }
+    if let Some(path) = output.path {
+        document["path"] = json!(path.to_string());
+    }
+    document["commitment"] = json!(output.commitment.as_i64());
+    document["status"] = json!(output.status.to_string());
+    if let Some(outpoint) = output.outpoint {
+        document["outpoint"] = json!(outpoint.to_string());
+    }
+    document
+}
+
+pub fn entry_json(entry: &HistoryEntry) -> Value {
+    let mut document = json!({
+        "time": entry.time,
+        "direction": entry.direction.to_string(),
+        "amount": entry.amount,
+        "fee": entry.fee,
+        "output": entry.output.as_i64(),
+    });
+    if let Some(txid) = entry.txid {
+        document["txid"] = json!(txid.to_string());
+    }
+    document["status"] = json!(status(entry));
+    document
+}
+
+pub fn status(entry: &HistoryEntry) -> &'static str {
+    if entry.confirmed {
+        "confirmed"
+    } else {
+        "pending"
+    }
+}
+
+/// "YYYY-MM-DD HH:MM:SS UTC" for seconds since the Unix epoch.
+pub fn format_time(time: u64) -> String {
+    let (days, seconds) = (time / 86_400, time % 86_400);
+    // Civil date from a day count (Howard Hinnant's algorithm), eras of 400 years.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn wallet_json(wallet: &Wallet) -> Value {
+    json!({
+        "seed": wallet.keychain().seed().to_string(),
+        "outputs": wallet.outputs().iter().map(|output| output_fields(output, false)).collect::<Vec<_>>(),
+        "history": wallet.history().iter().map(entry_json).collect::<Vec<_>>(),
+    })
+}
+
+fn field<'a>(entry: &'a Value, name: &str) -> Result<&'a Value, CtError> {
+    entry.get(name).ok_or_else(|| CtError::MalformedInput(format!("wallet entry has no \"{}\"", name)))
+}
+
+fn number(entry: &Value, name: &str) -> Result<i64, CtError> {
+    field(entry, name)?
+        .as_i64()
+        .ok_or_else(|| CtError::MalformedInput(format!("wallet entry \"{}\" must be an integer", name)))
+}
+
+fn text<'a>(entry: &'a Value, name: &str) -> Result<&'a str, CtError> {
+    field(entry, name)?
+        .as_str()
+        .ok_or_else(|| CtError::MalformedInput(format!("wallet entry \"{}\" must be a string", name)))
+}
+
+fn parse_output(keychain: &Keychain, output: &Value) -> Result<WalletOutput, CtError> {
+    let outpoint = match output.get("outpoint") {
+        None | Some(Value::Null) => None,
+        Some(_) => Some(text(output, "outpoint")?.parse()?),
+    };
+    let (blinding, path) = match output.get("path") {
+        None | Some(Value::Null) => (Blinding::new(number(output, "blinding")?), None),
+        Some(_) => {
+            let path = text(output, "path")?.parse()?;
+            (keychain.blinding(path), Some(path))
+        }
+    };
+    Ok(WalletOutput {
+        opening: Opening::new(Scalar::new(number(output, "value")?), blinding),
+        commitment: Commitment::from_i64(number(output, "commitment")?),
+        status: text(output, "status")?.parse()?,
+        outpoint,
+        path,
+    })
+}
+
+fn parse_entry(entry: &Value) -> Result<HistoryEntry, CtError> {
+    let txid = match entry.get("txid") {
+        None | Some(Value::Null) => None,
+        Some(_) => Some(text(entry, "txid")?.parse()?),
+    };
+    let time = field(entry, "time")?
+        .as_u64()
+        .ok_or_else(|| CtError::MalformedInput("history \"time\" must be a non-negative integer".to_string()))?;
+    let fee = field(entry, "fee")?
+        .as_u64()
+        .ok_or_else(|| CtError::MalformedInput("history \"fee\" must be a non-negative integer".to_string()))?;
+    let confirmed = match text(entry, "status")? {
+        "confirmed" => true,
+        "pending" => false,
+        other => return Err(CtError::MalformedInput(format!("unknown history status {:?}", other))),
+    };
+    Ok(HistoryEntry {
+        time,
+        direction: text(entry, "direction")?.parse()?,
+        amount: number(entry, "amount")?,
+        fee,
+        output: Commitment::from_i64(number(entry, "output")?),
+        txid,
+        confirmed,
+    })
+}
+
+fn parse_wallet(document: &Value) -> Result<Wallet, CtError> {
+    let seed: Seed = document
+        .get("seed")
+        .and_then(Value::as_str)
+        .ok_or_else(|| CtError::MalformedInput("wallet file has no \"seed\"".to_string()))?
+        .parse()?;
+    let keychain = Keychain::new(seed);
+    let outputs = document
+        .get("outputs")
+        .and_then(Value::as_array)
+        .ok_or_else(|| CtError::MalformedInput("wallet file has no \"outputs\" list".to_string()))?;
+    let outputs = outputs.iter().map(|output| parse_output(&keychain, output)).collect::<Result<_, _>>()?;
+    let history = match document.get("history") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(history) => history
+            .as_array()
+            .ok_or_else(|| CtError::MalformedInput("wallet \"history\" must be a list".to_string()))?
+            .iter()
+            .map(parse_entry)
+            .collect::<Result<_, _>>()?,
+    };
+    Ok(Wallet::from_outputs(keychain, outputs)?.with_history(history))
+}
+
+pub const CIPHER: &str = "xchacha20-poly1305";
+pub const KDF: &str = "pbkdf2-hmac-sha512";
+/// PBKDF2 rounds for a new passphrase: each guess at it costs an attacker as much.
+/// Kept low so the unoptimized demo build stays quick; real wallets use far more.
+const ROUNDS: u32 = 10_000;
+/// Authenticated with the ciphertext, so a sealed wallet cannot pass for another kind of file.
+const AAD: &[u8] = b"ct/wallet";
+
+/// How the wallet file is kept on disk.
+#[derive(Clone)]
+pub enum Storage {
+    Plain,
+    /// Sealed with `key`, derived from the passphrase and `salt` in `rounds` rounds.
+    Encrypted { key: [u8; KEY_LEN], salt: [u8; 16], rounds: u32 },
+}
+
+impl Storage {
+    /// Encrypted storage under a new passphrase, with a fresh salt from the operating system.
+    pub fn encrypted(passphrase: &str) -> Result<Self, CtError> {
+        let mut salt = [0; 16];
+        os_random(&mut salt)?;
+        Ok(Storage::Encrypted { key: passphrase_key(passphrase, &salt, ROUNDS), salt, rounds: ROUNDS })
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self, Storage::Encrypted { .. })
+    }
+}
+
+fn passphrase_key(passphrase: &str, salt: &[u8], rounds: u32) -> [u8; KEY_LEN] {
+    pbkdf2_sha512(passphrase.as_bytes(), salt, rounds)[..KEY_LEN].try_into().expect("PBKDF2 gives 64 bytes")
+}
+
+/// The wallet file's contents: the plain document, or that document sealed under a
+/// nonce from the operating system.
+pub fn save_wallet(wallet: &Wallet, storage: &Storage) -> Result<Value, CtError> {
+    let document = wallet_json(wallet);
+    let Storage::Encrypted { key, salt, rounds } = storage else { return Ok(document) };
+    let mut nonce = [0; NONCE_LEN];
+    os_random(&mut nonce)?;
+    let plaintext = serde_json::to_vec(&document).expect("JSON values always serialize");
+    Ok(json!({
+        "cipher": CIPHER,
+        "kdf": KDF,
+        "rounds": rounds,
+        "salt": to_hex(salt),
+        "nonce": to_hex(&nonce),
+        "ciphertext": to_hex(&aead::seal(key, &nonce, AAD, &plaintext)),
+    }))
+}
+
+/// Read a wallet file; `passphrase` is only asked for if the file is encrypted.
+pub fn load_wallet(
+    file: &str,
+    passphrase: impl FnOnce() -> Result<String, CtError>,
+) -> Result<(Wallet, Storage), CtError> {
+    let document: Value = parse_json(file)?;
+    if document.get("ciphertext").is_none() {
+        return Ok((parse_wallet(&document)?, Storage::Plain));
+    }
+    for (name, expected) in [("cipher", CIPHER), ("kdf", KDF)] {
+        let found = text(&document, name)?;
+        if found != expected {
+            return Err(CtError::MalformedInput(format!("unsupported wallet {} {:?}", name, found)));
+        }
+    }
+    let rounds = u32::try_from(number(&document, "rounds")?)
+        .map_err(|_| CtError::MalformedInput("wallet \"rounds\" is out of range".to_string()))?;
+    let salt: [u8; 16] = from_hex(text(&document, "salt")?)?
+        .try_into()
+        .map_err(|_| CtError::MalformedInput("wallet salt must be 16 bytes".to_string()))?;
+    let nonce: [u8; NONCE_LEN] = from_hex(text(&document, "nonce")?)?
+        .try_into()
+        .map_err(|_| CtError::MalformedInput(format!("wallet nonce must be {} bytes", NONCE_LEN)))?;
+    let sealed = from_hex(text(&document, "ciphertext")?)?;
+    let key = passphrase_key(&passphrase()?, &salt, rounds);
+    let plaintext = aead::open(&key, &nonce, AAD, &sealed)?;
+    let plaintext = String::from_utf8(plaintext)
+        .map_err(|_| CtError::MalformedInput("decrypted wallet is not UTF-8".to_string()))?;
+    Ok((parse_wallet(&parse_json(&plaintext)?)?, Storage::Encrypted { key, salt, rounds }))
+}
+
+pub fn watched_json(output: &WatchedOutput) -> Value {
+    let mut document = json!({
+        "value": output.value.as_i64(),
+        "path": output.path.to_string(),
+        "commitment": output.commitment.as_i64(),
+        "status": output.status.to_string(),
+    });
+    if let Some(outpoint) = output.outpoint {
+        document["outpoint"] = json!(outpoint.to_string());
+    }
+    document
+}
+
+pub fn watch_json(wallet: &WatchWallet) -> Value {
+    json!({
+        "view_key": wallet.view_key().to_string(),
+        "outputs": wallet.outputs().iter().map(watched_json).collect::<Vec<_>>(),
+    })
+}
+
+fn parse_watched(output: &Value) -> Result<WatchedOutput, CtError> {
+    let outpoint = match output.get("outpoint") {
+        None | Some(Value::Null) => None,
+        Some(_) => Some(text(output, "outpoint")?.parse()?),
+    };
+    Ok(WatchedOutput {
+        value: Scalar::new(number(output, "value")?),
+        commitment: Commitment::from_i64(number(output, "commitment")?),
+        status: text(output, "status")?.parse()?,
+        outpoint,
+        path: text(output, "path")?.parse()?,
+    })
+}
+
+pub fn parse_watch(file: &str) -> Result<WatchWallet, CtError> {
+    let document: Value = parse_json(file)?;
+    let view_key: ViewKey = text(&document, "view_key")?.parse()?;
+    let outputs = document
+        .get("outputs")
+        .and_then(Value::as_array)
+        .ok_or_else(|| CtError::MalformedInput("watch-only wallet file has no \"outputs\" list".to_string()))?;
+    WatchWallet::from_outputs(view_key, outputs.iter().map(parse_watched).collect::<Result<_, _>>()?)
+}
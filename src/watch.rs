@@ -0,0 +1,118 @@
+//! Watch-only wallets: the view half of a wallet. A `WatchWallet` holds an
+//! account's `ViewKey` instead of the seed, so it can find the account's outputs
+//! in the ledger and read their values, but it cannot open them and so cannot
+//! spend. Useful for a balance display, an accountant, or a hot machine that should
+//! never hold spending keys.
+//!
+//! Scanning: for each path the view key gives r*H, and an unspent C is ours if
+//! C - r*H is v*G for a value v the range proof allows. The values v*G are looked
+//! up in a table, so decoding a value costs one subtraction. Each branch is scanned
+//! until `GAP_LIMIT` paths in a row match nothing, as HD wallets do.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commitment::{pedersen_commit, Blinding, Commitment, Scalar};
+use crate::error::CtError;
+use crate::keychain::{KeyPath, ViewKey, CHANGE, GAP_LIMIT, RECEIVE};
+use crate::ledger::Ledger;
+use crate::params;
+use crate::transaction::OutPoint;
+use crate::wallet::OutputStatus;
+
+/// An output the view key found: its value and path, but not its blinding.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchedOutput {
+    pub value: Scalar,
+    pub commitment: Commitment,
+    pub status: OutputStatus,
+    pub outpoint: Option<OutPoint>,
+    pub path: KeyPath,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WatchWallet {
+    view_key: ViewKey,
+    outputs: Vec<WatchedOutput>,
+}
+
+/// v*G -> v for every value a range proof allows.
+fn value_table() -> HashMap<Commitment, i64> {
+    (0..1i64 << params::active().range_bits).map(|v| (pedersen_commit(Scalar::new(v), Blinding::new(0)), v)).collect()
+}
+
+impl WatchWallet {
+    pub fn new(view_key: ViewKey) -> Self {
+        WatchWallet { view_key, outputs: Vec::new() }
+    }
+
+    /// Rebuild from stored outputs; each must be v*G + r*H for the r*H its path gives.
+    pub fn from_outputs(view_key: ViewKey, outputs: Vec<WatchedOutput>) -> Result<Self, CtError> {
+        for output in &outputs {
+            let public_blinding = view_key.public_blinding(output.path.change, output.path.index);
+            if output.path.account != view_key.account
+                || pedersen_commit(output.value, Blinding::new(0)) + public_blinding != output.commitment
+            {
+                let reason = format!("{} at {} does not belong to this view key", output.commitment, output.path);
+                return Err(CtError::MalformedInput(reason));
+            }
+        }
+        Ok(WatchWallet { view_key, outputs })
+    }
+
+    pub fn view_key(&self) -> &ViewKey {
+        &self.view_key
+    }
+
+    pub fn outputs(&self) -> &[WatchedOutput] {
+        &self.outputs
+    }
+
+    /// Total value of the unspent outputs.
+    pub fn balance(&self) -> i64 {
+        self.outputs.iter().filter(|output| output.status == OutputStatus::Unspent).map(|o| o.value.as_i64()).sum()
+    }
+
+    /// Find the account's outputs among the ledger's unspent outputs, and mark the
+    /// ones found before that are gone as spent. Returns how many outputs are new
+    /// or changed status.
+    pub fn scan(&mut self, ledger: &Ledger) -> usize {
+        let table = value_table();
+        let unspent: Vec<(OutPoint, Commitment)> = ledger.utxos().map(|(&outpoint, &c)| (outpoint, c)).collect();
+        let mut changed = 0;
+        for branch in [RECEIVE, CHANGE] {
+            let mut misses = 0;
+            let mut index = 0;
+            while misses < GAP_LIMIT {
+                let public_blinding = self.view_key.public_blinding(branch, index);
+                let path = KeyPath::new(self.view_key.account, branch, index);
+                let mut found = self.outputs.iter().any(|output| output.path == path);
+                for &(outpoint, commitment) in &unspent {
+                    let Some(&value) = table.get(&(commitment - public_blinding)) else { continue };
+                    found = true;
+                    if self.outputs.iter().all(|output| output.commitment != commitment) {
+                        let status = OutputStatus::Unspent;
+                        let value = Scalar::new(value);
+                        self.outputs.push(WatchedOutput { value, commitment, status, outpoint: Some(outpoint), path });
+                        changed += 1;
+                    }
+                }
+                misses = if found { 0 } else { misses + 1 };
+                index += 1;
+            }
+        }
+        for output in &mut self.outputs {
+            let (status, outpoint) = match ledger.find(output.commitment) {
+                Some(outpoint) => (OutputStatus::Unspent, Some(outpoint)),
+                None => (OutputStatus::Spent, output.outpoint),
+            };
+            if (status, outpoint) != (output.status, output.outpoint) {
+                output.status = status;
+                output.outpoint = outpoint;
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
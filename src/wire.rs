@@ -0,0 +1,712 @@
+//! Canonical binary encoding, so two instances can exchange transactions and hash
+//! or sign exactly the same bytes. Deterministic: every value has one encoding.
+//!
+//! - integers are little-endian: i64/u64 take 8 bytes, u32 4 bytes, u8 1 byte
+//! - commitment, blinding: i64 (a commitment must be in [0, p))
+//! - range proof: u32 bit count, then per bit 7 x i64 (C_i, t0, t1, e0, e1, s0, s1)
+//! - aggregate range proof: e (i64), u32 bit count, then per bit 5 x i64 (C_i, e0, e1, s0, s1)
+//! - entry: commitment, u8 flags (1 = range proof, 2 = note, 4 = memo, 8 = lock,
+//!   16 = unlock, 32 = lock height, 64 = relative lock), then the range proof, the
+//!   note (ephemeral key, one-time key, encrypted amount as u64), the memo (ephemeral
+//!   key, 32 bytes sealed), the condition, the witness, the lock height (u64) and the
+//!   relative lock (u64) that are present
+//! - condition: u8 tag, then 0 = signature: key; 1 = preimage: 32-byte hash; 2 = after:
+//!   height (u64); 3 = and, 4 = or: u32 count + conditions. At most `MAX_DEPTH` deep
+//! - witness: u8 tag, then 0 = signature: nonce, s; 1 = preimage: 32 bytes; 2 = after:
+//!   nothing; 3 = and: u32 count + witnesses; 4 = or: branch (u32) + witness
+//! - kernel: excess, nonce, s
+//! - transaction: u32 count + inputs, u32 count + outputs, fee (u64), minted (u64),
+//!   burned (u64), u8 0 (no kernel), 1 + kernel or 2 + kernel + lock height (u64),
+//!   offset, u8 0/1 + aggregate range proof
+//! - participant: public excess, public nonce (i64), u8 0/1 + partial signature (i64)
+//! - slate: amount (u64), transaction, sender, u8 0/1 + receiver, u8 0/1 + payment
+//!   proof request: receiver key, u8 0/1 + signature (nonce, s)
+//! - block header: prev (32 bytes), height (u64), tx root (32 bytes), kernel excess,
+//!   offset, fees (u64), minted (u64), burned (u64)
+//! - block: header, u32 count + transactions
+//!
+//! None of this carries a version: txids, block hashes and signatures cover these
+//! bytes, and must not change with the files around them. What stores or ships the
+//! bytes says which format wrote it instead, `"version": 1` in a JSON file, a
+//! `Version: 1` line in armor and a `v1:` before hex (`to_versioned_hex`, for the wire
+//! bytes and CBOR `ct encode` prints and the blocks in a chain file), all
+//! `FORMAT_VERSION`, and these rules keep old files loading:
+//!
+//! - a file, armor or hex with no version predates versions and is version 1, whose
+//!   format it has;
+//! - a reader takes its own version and every older one, and refuses a newer one
+//!   rather than misread it;
+//! - readers ignore JSON fields they do not know, so a field that may be missing (and
+//!   then means what its absence meant before) needs no new version. Anything an older
+//!   reader would get wrong does, and so does any change to the bytes above.
+//!
+//! `to_hex`/`from_hex` turn the bytes into text for the command line.
+
+use crate::block::{Block, BlockHash, BlockHeader};
+use crate::commitment::{Blinding, Commitment};
+use crate::condition::{Condition, Witness, MAX_DEPTH, PREIMAGE_LEN};
+use crate::ecdh::OutputNote;
+use crate::error::CtError;
+use crate::kernel::{Kernel, Signature};
+use crate::memo::{Memo, MEMO_LEN};
+use crate::payment_proof::ProofRequest;
+use crate::range_proof::{AggregateRangeProof, BitProof, CompactBitProof, IntervalProof, RangeProof};
+use crate::slate::{Participant, Slate};
+use crate::transaction::{Transaction, TxEntry};
+
+/// The format version this build writes into files and armor.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Check a version read from a file or armor: this build reads 1 up to `FORMAT_VERSION`.
+pub fn check_version(version: u64) -> Result<(), CtError> {
+    match version {
+        0 => Err(CtError::MalformedInput("there is no format version 0".to_string())),
+        v if v > u64::from(FORMAT_VERSION) => Err(CtError::MalformedInput(format!(
+            "format version {} is newer than this build reads (up to {}): upgrade ct to read it",
+            v, FORMAT_VERSION
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// A value with a canonical byte encoding.
+pub trait Encode: Sized {
+    fn write(&self, out: &mut Vec<u8>);
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError>;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write(&mut out);
+        out
+    }
+
+    /// Decode exactly one value; trailing bytes are an error.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, CtError> {
+        let mut reader = Reader { bytes, pos: 0 };
+        let value = Self::read(&mut reader)?;
+        if reader.pos != bytes.len() {
+            return Err(CtError::MalformedInput(format!("{} trailing byte(s)", bytes.len() - reader.pos)));
+        }
+        Ok(value)
+    }
+}
+
+/// Cursor over the bytes being decoded.
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// A reader for formats other than this one (`elements`).
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, pos: 0 }
+    }
+
+    /// How many bytes have been read.
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    pub(crate) fn take<const N: usize>(&mut self) -> Result<[u8; N], CtError> {
+        Ok(self.slice(N)?.try_into().expect("chunk has N bytes"))
+    }
+
+    /// The next `len` bytes.
+    pub(crate) fn slice(&mut self, len: usize) -> Result<&'a [u8], CtError> {
+        let chunk = self
+            .bytes
+            .get(self.pos..self.pos.saturating_add(len))
+            .ok_or_else(|| CtError::MalformedInput(format!("truncated at byte {}", self.pos)))?;
+        self.pos += len;
+        Ok(chunk)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, CtError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32, CtError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, CtError> {
+        Ok(u64::from_le_bytes(self.take()?))
+    }
+
+    pub fn i64(&mut self) -> Result<i64, CtError> {
+        Ok(i64::from_le_bytes(self.take()?))
+    }
+
+    pub fn hash(&mut self) -> Result<[u8; 32], CtError> {
+        self.take()
+    }
+
+    /// A 0/1 presence flag.
+    pub fn flag(&mut self) -> Result<bool, CtError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(CtError::MalformedInput(format!("flag byte {} is not 0 or 1", other))),
+        }
+    }
+
+    /// A u32 count followed by that many values.
+    pub fn list<T: Encode>(&mut self) -> Result<Vec<T>, CtError> {
+        let count = self.u32()?;
+        (0..count).map(|_| T::read(self)).collect()
+    }
+}
+
+fn write_list<T: Encode>(items: &[T], out: &mut Vec<u8>) {
+    out.extend_from_slice(&(items.len() as u32).to_le_bytes());
+    for item in items {
+        item.write(out);
+    }
+}
+
+impl Encode for Commitment {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.as_i64().to_le_bytes());
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        reader.i64()?.to_string().parse()
+    }
+}
+
+impl Encode for Blinding {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.as_i64().to_le_bytes());
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        Ok(Blinding::new(reader.i64()?))
+    }
+}
+
+impl Encode for BitProof {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.commitment.write(out);
+        for n in [self.t0, self.t1, self.e0, self.e1, self.s0, self.s1] {
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        Ok(BitProof {
+            commitment: Commitment::read(reader)?,
+            t0: reader.i64()?,
+            t1: reader.i64()?,
+            e0: reader.i64()?,
+            e1: reader.i64()?,
+            s0: reader.i64()?,
+            s1: reader.i64()?,
+        })
+    }
+}
+
+impl Encode for RangeProof {
+    fn write(&self, out: &mut Vec<u8>) {
+        write_list(&self.bits, out);
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        Ok(RangeProof { bits: reader.list()? })
+    }
+}
+
+impl Encode for CompactBitProof {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.commitment.write(out);
+        for n in [self.e0, self.e1, self.s0, self.s1] {
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        Ok(CompactBitProof {
+            commitment: Commitment::read(reader)?,
+            e0: reader.i64()?,
+            e1: reader.i64()?,
+            s0: reader.i64()?,
+            s1: reader.i64()?,
+        })
+    }
+}
+
+impl Encode for IntervalProof {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.low.write(out);
+        self.high.write(out);
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        Ok(IntervalProof { low: RangeProof::read(reader)?, high: RangeProof::read(reader)? })
+    }
+}
+
+impl Encode for AggregateRangeProof {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.challenge.to_le_bytes());
+        write_list(&self.bits, out);
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        Ok(AggregateRangeProof { challenge: reader.i64()?, bits: reader.list()? })
+    }
+}
+
+impl Encode for OutputNote {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.ephemeral.write(out);
+        self.one_time_key.write(out);
+        out.extend_from_slice(&self.encrypted_amount.to_le_bytes());
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        Ok(OutputNote {
+            ephemeral: Commitment::read(reader)?,
+            one_time_key: Commitment::read(reader)?,
+            encrypted_amount: reader.u64()?,
+        })
+    }
+}
+
+impl Encode for Memo {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.ephemeral.write(out);
+        out.extend_from_slice(&self.ciphertext);
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        Ok(Memo { ephemeral: Commitment::read(reader)?, ciphertext: reader.take::<MEMO_LEN>()? })
+    }
+}
+
+fn too_deep() -> CtError {
+    CtError::MalformedInput(format!("conditions nest more than {} deep", MAX_DEPTH))
+}
+
+/// `Condition::read` with the depth so far, so a decoded condition cannot nest without end.
+fn read_condition(reader: &mut Reader<'_>, depth: usize) -> Result<Condition, CtError> {
+    if depth > MAX_DEPTH {
+        return Err(too_deep());
+    }
+    Ok(match reader.u8()? {
+        0 => Condition::Signature(Commitment::read(reader)?),
+        1 => Condition::Preimage(reader.hash()?),
+        2 => Condition::After(reader.u64()?),
+        tag @ (3 | 4) => {
+            let conditions = (0..reader.u32()?).map(|_| read_condition(reader, depth + 1)).collect::<Result<_, _>>()?;
+            if tag == 3 { Condition::And(conditions) } else { Condition::Or(conditions) }
+        }
+        tag => return Err(CtError::MalformedInput(format!("condition tag {} is not 0 to 4", tag))),
+    })
+}
+
+impl Encode for Condition {
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            Condition::Signature(key) => {
+                out.push(0);
+                key.write(out);
+            }
+            Condition::Preimage(hash) => {
+                out.push(1);
+                out.extend_from_slice(hash);
+            }
+            Condition::After(height) => {
+                out.push(2);
+                out.extend_from_slice(&height.to_le_bytes());
+            }
+            Condition::And(conditions) => {
+                out.push(3);
+                write_list(conditions, out);
+            }
+            Condition::Or(conditions) => {
+                out.push(4);
+                write_list(conditions, out);
+            }
+        }
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        read_condition(reader, 0)
+    }
+}
+
+/// `Witness::read` with the depth so far, as for conditions.
+fn read_witness(reader: &mut Reader<'_>, depth: usize) -> Result<Witness, CtError> {
+    if depth > MAX_DEPTH {
+        return Err(too_deep());
+    }
+    Ok(match reader.u8()? {
+        0 => Witness::Signature(Signature { nonce: reader.i64()?, s: reader.i64()? }),
+        1 => Witness::Preimage(reader.take::<PREIMAGE_LEN>()?),
+        2 => Witness::After,
+        3 => Witness::And((0..reader.u32()?).map(|_| read_witness(reader, depth + 1)).collect::<Result<_, _>>()?),
+        4 => Witness::Or(reader.u32()?, Box::new(read_witness(reader, depth + 1)?)),
+        tag => return Err(CtError::MalformedInput(format!("witness tag {} is not 0 to 4", tag))),
+    })
+}
+
+impl Encode for Witness {
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            Witness::Signature(signature) => {
+                out.push(0);
+                out.extend_from_slice(&signature.nonce.to_le_bytes());
+                out.extend_from_slice(&signature.s.to_le_bytes());
+            }
+            Witness::Preimage(preimage) => {
+                out.push(1);
+                out.extend_from_slice(preimage);
+            }
+            Witness::After => out.push(2),
+            Witness::And(witnesses) => {
+                out.push(3);
+                write_list(witnesses, out);
+            }
+            Witness::Or(branch, witness) => {
+                out.push(4);
+                out.extend_from_slice(&branch.to_le_bytes());
+                witness.write(out);
+            }
+        }
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        read_witness(reader, 0)
+    }
+}
+
+/// Entry flag bits. An entry without a note has the flag byte 0/1 it always had.
+const HAS_PROOF: u8 = 1;
+const HAS_NOTE: u8 = 2;
+const HAS_MEMO: u8 = 4;
+const HAS_LOCK: u8 = 8;
+const HAS_UNLOCK: u8 = 16;
+const HAS_LOCK_HEIGHT: u8 = 32;
+const HAS_RELATIVE_LOCK: u8 = 64;
+
+impl Encode for TxEntry {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.commitment.write(out);
+        let flags = if self.proof.is_some() { HAS_PROOF } else { 0 }
+            | if self.note.is_some() { HAS_NOTE } else { 0 }
+            | if self.memo.is_some() { HAS_MEMO } else { 0 }
+            | if self.lock.is_some() { HAS_LOCK } else { 0 }
+            | if self.unlock.is_some() { HAS_UNLOCK } else { 0 }
+            | if self.lock_height > 0 { HAS_LOCK_HEIGHT } else { 0 }
+            | if self.relative_lock > 0 { HAS_RELATIVE_LOCK } else { 0 };
+        out.push(flags);
+        if let Some(proof) = &self.proof {
+            proof.write(out);
+        }
+        if let Some(note) = &self.note {
+            note.write(out);
+        }
+        if let Some(memo) = &self.memo {
+            memo.write(out);
+        }
+        if let Some(lock) = &self.lock {
+            lock.write(out);
+        }
+        if let Some(unlock) = &self.unlock {
+            unlock.write(out);
+        }
+        if self.lock_height > 0 {
+            out.extend_from_slice(&self.lock_height.to_le_bytes());
+        }
+        if self.relative_lock > 0 {
+            out.extend_from_slice(&self.relative_lock.to_le_bytes());
+        }
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        let commitment = Commitment::read(reader)?;
+        let flags = reader.u8()?;
+        let known = HAS_PROOF | HAS_NOTE | HAS_MEMO | HAS_LOCK | HAS_UNLOCK | HAS_LOCK_HEIGHT | HAS_RELATIVE_LOCK;
+        if flags & !known != 0 {
+            let message = format!("entry flags {} are not a combination of 1, 2, 4, 8, 16, 32 and 64", flags);
+            return Err(CtError::MalformedInput(message));
+        }
+        let proof = if flags & HAS_PROOF != 0 { Some(RangeProof::read(reader)?) } else { None };
+        let note = if flags & HAS_NOTE != 0 { Some(OutputNote::read(reader)?) } else { None };
+        let memo = if flags & HAS_MEMO != 0 { Some(Memo::read(reader)?) } else { None };
+        let lock = if flags & HAS_LOCK != 0 { Some(Condition::read(reader)?) } else { None };
+        let unlock = if flags & HAS_UNLOCK != 0 { Some(Witness::read(reader)?) } else { None };
+        let lock_height = if flags & HAS_LOCK_HEIGHT != 0 { reader.u64()? } else { 0 };
+        let relative_lock = if flags & HAS_RELATIVE_LOCK != 0 { reader.u64()? } else { 0 };
+        Ok(TxEntry { commitment, proof, note, memo, lock, unlock, lock_height, relative_lock })
+    }
+}
+
+/// The lock height goes with the transaction's kernel tag, so `read` leaves it 0.
+impl Encode for Kernel {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.excess.write(out);
+        out.extend_from_slice(&self.signature.nonce.to_le_bytes());
+        out.extend_from_slice(&self.signature.s.to_le_bytes());
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        Ok(Kernel {
+            excess: Commitment::read(reader)?,
+            signature: Signature { nonce: reader.i64()?, s: reader.i64()? },
+            lock_height: 0,
+        })
+    }
+}
+
+impl Encode for Transaction {
+    fn write(&self, out: &mut Vec<u8>) {
+        write_list(&self.inputs, out);
+        write_list(&self.outputs, out);
+        out.extend_from_slice(&self.fee.to_le_bytes());
+        out.extend_from_slice(&self.minted.to_le_bytes());
+        out.extend_from_slice(&self.burned.to_le_bytes());
+        match &self.kernel {
+            None => out.push(0),
+            Some(kernel) if kernel.lock_height == 0 => {
+                out.push(1);
+                kernel.write(out);
+            }
+            Some(kernel) => {
+                out.push(2);
+                kernel.write(out);
+                out.extend_from_slice(&kernel.lock_height.to_le_bytes());
+            }
+        }
+        self.offset.write(out);
+        out.push(self.aggregate_proof.is_some() as u8);
+        if let Some(proof) = &self.aggregate_proof {
+            proof.write(out);
+        }
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        let inputs = reader.list()?;
+        let outputs = reader.list()?;
+        let fee = reader.u64()?;
+        let minted = reader.u64()?;
+        let burned = reader.u64()?;
+        let kernel = match reader.u8()? {
+            0 => None,
+            1 => Some(Kernel::read(reader)?),
+            2 => {
+                let kernel = Kernel::read(reader)?;
+                Some(Kernel { lock_height: reader.u64()?, ..kernel })
+            }
+            tag => return Err(CtError::MalformedInput(format!("kernel tag {} is not 0, 1 or 2", tag))),
+        };
+        let offset = Blinding::read(reader)?;
+        let aggregate_proof = if reader.flag()? { Some(AggregateRangeProof::read(reader)?) } else { None };
+        Ok(Transaction { inputs, outputs, fee, minted, burned, kernel, offset, aggregate_proof })
+    }
+}
+
+impl Encode for Participant {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.public_excess.write(out);
+        out.extend_from_slice(&self.public_nonce.to_le_bytes());
+        out.push(self.partial_signature.is_some() as u8);
+        if let Some(s) = self.partial_signature {
+            out.extend_from_slice(&s.to_le_bytes());
+        }
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        let public_excess = Commitment::read(reader)?;
+        let public_nonce = reader.i64()?;
+        let partial_signature = if reader.flag()? { Some(reader.i64()?) } else { None };
+        Ok(Participant { public_excess, public_nonce, partial_signature })
+    }
+}
+
+impl Encode for Slate {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.amount.to_le_bytes());
+        self.tx.write(out);
+        self.sender.write(out);
+        out.push(self.receiver.is_some() as u8);
+        if let Some(receiver) = &self.receiver {
+            receiver.write(out);
+        }
+        out.push(self.payment_proof.is_some() as u8);
+        if let Some(request) = &self.payment_proof {
+            request.receiver.write(out);
+            out.push(request.signature.is_some() as u8);
+            if let Some(signature) = &request.signature {
+                out.extend_from_slice(&signature.nonce.to_le_bytes());
+                out.extend_from_slice(&signature.s.to_le_bytes());
+            }
+        }
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        let amount = reader.u64()?;
+        let tx = Transaction::read(reader)?;
+        let sender = Participant::read(reader)?;
+        let receiver = if reader.flag()? { Some(Participant::read(reader)?) } else { None };
+        let payment_proof = if reader.flag()? {
+            let receiver = Commitment::read(reader)?;
+            let signature =
+                if reader.flag()? { Some(Signature { nonce: reader.i64()?, s: reader.i64()? }) } else { None };
+            Some(ProofRequest { receiver, signature })
+        } else {
+            None
+        };
+        Ok(Slate { amount, tx, sender, receiver, payment_proof })
+    }
+}
+
+impl Encode for BlockHeader {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.prev.0);
+        out.extend_from_slice(&self.height.to_le_bytes());
+        out.extend_from_slice(&self.tx_root);
+        out.extend_from_slice(&self.output_root);
+        self.kernel_excess.write(out);
+        self.offset.write(out);
+        out.extend_from_slice(&self.fees.to_le_bytes());
+        out.extend_from_slice(&self.minted.to_le_bytes());
+        out.extend_from_slice(&self.burned.to_le_bytes());
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        Ok(BlockHeader {
+            prev: BlockHash(reader.hash()?),
+            height: reader.u64()?,
+            tx_root: reader.hash()?,
+            output_root: reader.hash()?,
+            kernel_excess: Commitment::read(reader)?,
+            offset: Blinding::read(reader)?,
+            fees: reader.u64()?,
+            minted: reader.u64()?,
+            burned: reader.u64()?,
+        })
+    }
+}
+
+impl Encode for Block {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.header.write(out);
+        write_list(&self.transactions, out);
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, CtError> {
+        Ok(Block { header: BlockHeader::read(reader)?, transactions: reader.list()? })
+    }
+}
+
+/// Lowercase hex, two digits per byte.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Inverse of `to_hex`; accepts either case and ignores surrounding whitespace.
+pub fn from_hex(text: &str) -> Result<Vec<u8>, CtError> {
+    let text = text.trim();
+    if !text.len().is_multiple_of(2) || !text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(CtError::MalformedInput("hex must be an even number of hex digits".to_string()));
+    }
+    Ok((0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).expect("checked hex digits"))
+        .collect())
+}
+
+/// `to_hex` behind the format version, `v1:0100...`. Neither `v` nor `:` is a hex
+/// digit, so this cannot be mistaken for the plain hex written before versions.
+pub fn to_versioned_hex(bytes: &[u8]) -> String {
+    format!("v{}:{}", FORMAT_VERSION, to_hex(bytes))
+}
+
+/// Inverse of `to_versioned_hex`, refusing a newer version; plain hex is version 1.
+pub fn from_versioned_hex(text: &str) -> Result<Vec<u8>, CtError> {
+    let text = text.trim();
+    let Some((version, hex)) = text.strip_prefix('v').and_then(|rest| rest.split_once(':')) else {
+        return from_hex(text);
+    };
+    let version = version
+        .parse()
+        .map_err(|_| CtError::MalformedInput(format!("'v{}:' does not give a format version", version)))?;
+    check_version(version)?;
+    from_hex(hex)
+}
+
+/// Serde for fixed-size byte strings (hashes, preimages, sealed memos) as hex, or as
+/// raw bytes in binary formats, with `#[serde(with = "crate::wire::hex")]`.
+pub mod hex {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{from_hex, to_hex};
+
+    pub fn serialize<S: Serializer, const N: usize>(bytes: &[u8; N], serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&to_hex(bytes))
+        } else {
+            serializer.serialize_bytes(bytes)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error> {
+        if deserializer.is_human_readable() {
+            parse(&String::deserialize(deserializer)?)
+        } else {
+            length(Vec::deserialize(deserializer)?)
+        }
+    }
+
+    /// N bytes from hex, with the error of the deserializer at hand.
+    pub fn parse<E: Error, const N: usize>(text: &str) -> Result<[u8; N], E> {
+        length(from_hex(text).map_err(E::custom)?)
+    }
+
+    fn length<E: Error, const N: usize>(bytes: Vec<u8>) -> Result<[u8; N], E> {
+        bytes.try_into().map_err(|bytes: Vec<u8>| E::custom(format!("{} bytes, not {}", bytes.len(), N)))
+    }
+
+    /// One byte string of a list (`hex_list`).
+    pub(super) struct Bytes<const N: usize>(pub [u8; N]);
+
+    impl<const N: usize> Serialize for Bytes<N> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serialize(&self.0, serializer)
+        }
+    }
+
+    impl<'de, const N: usize> Deserialize<'de> for Bytes<N> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserialize(deserializer).map(Bytes)
+        }
+    }
+}
+
+/// `hex` for a list of byte strings, e.g. an MMR proof's peaks.
+pub mod hex_list {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::hex::Bytes;
+
+    pub fn serialize<S: Serializer, const N: usize>(list: &[[u8; N]], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(list.iter().map(|&bytes| Bytes(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<Vec<[u8; N]>, D::Error> {
+        Ok(Vec::<Bytes<N>>::deserialize(deserializer)?.into_iter().map(|bytes| bytes.0).collect())
+    }
+}
+
+/// For `#[serde(skip_serializing_if)]`: a height, amount or offset of 0, which the JSON
+/// leaves out.
+pub fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}